@@ -184,7 +184,7 @@ return function(M)
             end
 
             local bytes_read = va.va_import_region(
-                M.global_state, buffer,
+                M.global_state, buffer, buffer_size,
                 0, 0, 0, M.grid_size, M.grid_size, M.grid_size
             )
 
@@ -16,6 +16,15 @@ ffi.cdef [[
     // Phase 1
     int32_t va_add(int32_t a, int32_t b);
 
+    // Self test: 0 = pass, else bitmask of failed checks
+    enum {
+        VA_SELF_TEST_CONSERVATION = 1,
+        VA_SELF_TEST_DETERMINISM = 2,
+        VA_SELF_TEST_RULE = 4,
+        VA_SELF_TEST_FFI_ROUND_TRIP = 8
+    };
+    uint32_t va_self_test(void);
+
     // Phase 2: Opaque handle lifecycle
     typedef struct State State;
     State* va_create(void);
@@ -23,20 +32,201 @@ ffi.cdef [[
     uint64_t va_get_generation(const State* ptr);
 
     // Phase 3: Small grid + step
+    // 0 ok, 1 null, 2 non-positive dimension, 3 over 2^32 cells, 4 out of
+    // memory; on failure the previous grid is kept
     int32_t va_create_grid(State* ptr, int16_t width, int16_t height, int16_t depth);
     void va_set_cell(State* ptr, int16_t x, int16_t y, int16_t z, uint8_t alive);
     uint8_t va_get_cell(const State* ptr, int16_t x, int16_t y, int16_t z);
+    // Batched edits: count x, y, z triples and one value each (last wins for
+    // repeated cells). Returns the number set; out-of-bounds cells are skipped
+    uint64_t va_set_cells_bulk(State* ptr, const int16_t* coords, const uint8_t* values,
+                               uint64_t count);
+    // Batched reads (rays, particle sets): 0 written outside the grid.
+    // Returns the number of cells inside
+    uint64_t va_get_cells_bulk(const State* ptr, const int16_t* coords, uint64_t count,
+                               uint8_t* out_values);
+    // Raycast (line of sight): origin and direction are double[3] in cell
+    // units (cell centers at +0.5). 1 hit (cell in out_hit, optional distance
+    // to where the ray enters it), 0 miss, -1 error. The field variant stops
+    // at the first cell above threshold
+    int32_t va_raycast(const State* ptr, const double* origin, const double* direction,
+                       double max_dist, int16_t* out_hit, double* out_distance);
+    int32_t va_field_raycast(const Field* field, const double* origin, const double* direction,
+                             double max_dist, uint32_t threshold, int16_t* out_hit,
+                             double* out_distance);
+    // Connected live cells; connectivity 6 (faces), 18 (+edges) or 26
+    // (+corners). Flood fill writes up to max x, y, z triples and returns the
+    // total (null out_coords counts). Labels: one u32 per cell in z,y,x order,
+    // 0 dead, components from 1; returns the component count (null counts)
+    uint64_t va_flood_fill(const State* ptr, int16_t x, int16_t y, int16_t z,
+                           uint8_t connectivity, int16_t* out_coords, uint64_t max);
+    uint64_t va_label_components(const State* ptr, uint8_t connectivity, uint32_t* out_labels,
+                                 uint64_t len);
+    // Pattern search: pw*ph*pd bytes in z,y,x order, 0 dead, 1 alive, other
+    // values match either. rotations != 0 also tries the 24 va_stamp
+    // rotations. Writes up to max (x, y, z, rotation) quads and returns the
+    // total (null out_positions counts)
+    uint64_t va_find_pattern(const State* ptr, const uint8_t* pattern_buf,
+                             int16_t pw, int16_t ph, int16_t pd, uint8_t rotations,
+                             int16_t* out_positions, uint64_t max);
+    // Species 1..16 (0 = dead); births take the majority neighbor species.
+    // relation: whether observer counts other as alive (default 1)
+    int32_t va_set_cell_species(State* ptr, int16_t x, int16_t y, int16_t z, uint8_t species);
+    uint8_t va_get_cell_species(const State* ptr, int16_t x, int16_t y, int16_t z);
+    int32_t va_set_species_relation(State* ptr, uint8_t observer, uint8_t other, uint8_t alive);
+    // Cell age (generations survived; 0 for dead cells). max_age kills cells
+    // that reached it on the next step (0 = no limit). buf_len counts uint16
+    int32_t va_set_age_tracking(State* ptr, uint8_t enabled, uint16_t max_age);
+    uint16_t va_get_cell_age(const State* ptr, int16_t x, int16_t y, int16_t z);
+    uint64_t va_extract_age_region(const State* ptr, uint16_t* out_buf, uint64_t buf_len,
+                                   int16_t min_x, int16_t min_y, int16_t min_z,
+                                   int16_t max_x, int16_t max_y, int16_t max_z);
     void va_step(State* ptr);
+    // Changes the next va_step would make, without stepping: 4 x int16 per
+    // change (x, y, z, alive after the step). Returns the total (may exceed max)
+    uint64_t va_step_preview(const State* ptr, int16_t* out_changes, uint64_t max);
+    // Shards overlapping by two layers: copy each one's layer inside the shared
+    // face into the other's outer (ghost) layer before stepping. b lies beyond
+    // face (0..5 = -x,+x,-y,+y,-z,+z) of a; 1 on mismatched faces, -1 for null
+    int32_t va_exchange_boundaries(State* a, State* b, uint8_t face);
+    // Cells differing between two grids of the same size: 5 x int16 per cell
+    // (x, y, z, value in a, value in b). Returns the total (may exceed max), -1
+    // for null or mismatched dimensions
+    int64_t va_diff(const State* a, const State* b, int16_t* out_buf, uint64_t max);
+    // Deferred world writes: each va_step queues its changes (same records as
+    // va_step_preview); drain up to max per tick. Capacity 0 disables
+    int32_t va_enable_write_queue(State* ptr, uint64_t capacity);
+    uint64_t va_drain_writes(State* ptr, int16_t* out_changes, uint64_t max);
+    uint64_t va_pending_writes(const State* ptr);
+    uint64_t va_get_overflowed_writes(const State* ptr);
+    // Checkpoint ring buffer: every interval generations, max_snapshots kept.
+    // va_rollback lands on the newest checkpoint at or before the target and
+    // returns its generation (-1 if none); changes go to the write queue
+    // Period of the cycle the grid is in (1 = still life), 0 if none seen.
+    // The first call starts tracking; max_period 0 stops it
+    uint32_t va_detect_cycle(State* ptr, uint32_t max_period);
+    // Step until a still life or an oscillator of period <= hash_window shows
+    // up; returns generations run, *out_period 0 if max_generations ran out
+    uint64_t va_run_until_stable(State* ptr, uint64_t max_generations,
+                                 uint32_t hash_window, uint32_t* out_period);
+    // Platform-independent hash of dimensions, generation and cells, for
+    // checking that clients' predicted grids still agree (0 for null)
+    uint64_t va_hash(const State* ptr);
+    int32_t va_enable_history(State* ptr, uint32_t max_snapshots, uint64_t interval);
+    int64_t va_rollback(State* ptr, uint64_t generations_back);
+    uint32_t va_get_history_range(const State* ptr, uint64_t* out_oldest,
+                                  uint64_t* out_newest);
+    // Runs n_generations natively, sampling every stride generations: 8 x int64
+    // per sample (generation, population, inclusive bbox min xyz, max xyz; -1
+    // when empty). Returns the sample count (may exceed max_samples)
+    uint64_t va_fast_forward(State* ptr, uint64_t n_generations, uint64_t stride,
+                             int64_t* out_metrics, uint64_t max_samples);
+    // Zero-copy read access (z,y,x order). Invalidated by va_step,
+    // va_create_grid, va_deserialize*, va_destroy: re-fetch after those.
+    const uint8_t* va_get_cells_ptr(const State* ptr);
+    uint64_t va_get_cells_len(const State* ptr);
 
     // Phase 4: Visualize
-    uint64_t va_extract_region(const State* ptr, uint8_t* out_buf,
+    // out_generation (nullable) receives the generation the data belongs to
+    uint64_t va_extract_region(const State* ptr, uint8_t* out_buf, uint64_t buf_len,
                                 int16_t min_x, int16_t min_y, int16_t min_z,
-                                int16_t max_x, int16_t max_y, int16_t max_z);
+                                int16_t max_x, int16_t max_y, int16_t max_z,
+                                uint64_t* out_generation);
 
     // Phase 5: Bidirectional sync
-    uint64_t va_import_region(State* ptr, const uint8_t* in_buf,
+    uint64_t va_import_region(State* ptr, const uint8_t* in_buf, uint64_t buf_len,
                                int16_t min_x, int16_t min_y, int16_t min_z,
                                int16_t max_x, int16_t max_y, int16_t max_z);
+    // _checked: return the required size; write only if buf_len is large enough
+    // (pass a null buffer to query)
+    uint64_t va_extract_region_checked(const State* ptr, uint8_t* out_buf, uint64_t buf_len,
+                                        int16_t min_x, int16_t min_y, int16_t min_z,
+                                        int16_t max_x, int16_t max_y, int16_t max_z,
+                                        uint64_t* out_generation);
+    uint64_t va_import_region_checked(State* ptr, const uint8_t* in_buf, uint64_t buf_len,
+                                       int16_t min_x, int16_t min_y, int16_t min_z,
+                                       int16_t max_x, int16_t max_y, int16_t max_z);
+    // Pooled extraction buffers (reuse instead of ffi.new per pull)
+    uint8_t* va_acquire_buffer(uint64_t min_size, uint64_t* out_capacity);
+    int32_t va_release_buffer(uint8_t* buf);
+    void va_trim_buffer_pool(void);
+
+    // Reset process-wide state (buffer pool, id registry) left over from a
+    // previous load
+    uint64_t va_reinit(void);
+    // Build description (key = value lines; null out_buf queries the size) and
+    // capability bits: 1 rayon, 2 SIMD, 4 GPU, 8 sparse, 16 compression,
+    // 32 Python, 64 wasm, 128 debug build, 256 shared-memory export,
+    // 512 diffusion oracle
+    uint64_t va_build_info(uint8_t* out_buf, uint64_t capacity);
+    uint32_t va_build_features(void);
+    // Debug report for bug reports (sectioned key = value lines: settings,
+    // counters, field mass ledger, step timing, recent errors). Sizes vary
+    // between calls with the timing: leave some slack over a null-buffer query
+    uint64_t va_dump_debug_report(const State* ptr, uint8_t* out_buf, uint64_t capacity);
+    uint64_t va_field_dump_debug_report(const Field* field, uint8_t* out_buf, uint64_t capacity);
+    // Why the calling thread's last failing call failed (errno-style: not
+    // cleared by successful calls). Codes: 0 none, 1 null handle, 2 invalid
+    // argument, 3 out of bounds, 4 buffer, 5 too large, 6 allocation, 7 busy,
+    // 8 invalid data, 9 invalid state, 10 invalid handle (destroyed, or a
+    // handle of another type). The message is valid until the next failing
+    // call; copy it with ffi.string
+    int32_t va_last_error_code(void);
+    const char* va_last_error_message(void);
+    void va_clear_last_error(void);
+
+    // Mapblocks: 16x16x16 (4096-byte buffers), 64-bit block coordinates
+    uint64_t va_extract_mapblock(const State* ptr, int64_t bx, int64_t by, int64_t bz,
+                                  uint8_t* out_buf, uint64_t* out_generation);
+    uint64_t va_import_mapblock(State* ptr, int64_t bx, int64_t by, int64_t bz,
+                                 const uint8_t* in_buf);
+    uint64_t va_fill_region(State* ptr,
+                             int16_t min_x, int16_t min_y, int16_t min_z,
+                             int16_t max_x, int16_t max_y, int16_t max_z,
+                             uint8_t value);
+    uint64_t va_randomize_region(State* ptr,
+                                  int16_t min_x, int16_t min_y, int16_t min_z,
+                                  int16_t max_x, int16_t max_y, int16_t max_z,
+                                  uint32_t density_ppm, uint64_t seed);
+    void va_clear(State* ptr);
+    // Protection mask (box [min, max), protected 0/1): protected cells are
+    // never born; va_get_suppressed counts the vetoed births
+    uint64_t va_protect_region(State* ptr,
+                                int16_t min_x, int16_t min_y, int16_t min_z,
+                                int16_t max_x, int16_t max_y, int16_t max_z,
+                                uint8_t protected);
+    uint64_t va_get_suppressed(const State* ptr);
+
+    // Snapshots: save/restore for mod storage
+    uint64_t va_serialize(const State* ptr, uint8_t* out_buf, uint64_t capacity);
+    int32_t va_deserialize(State* ptr, const uint8_t* in_buf, uint64_t len);
+    uint64_t va_serialize_compressed(const State* ptr, uint8_t* out_buf, uint64_t capacity);
+    int32_t va_deserialize_compressed(State* ptr, const uint8_t* in_buf, uint64_t len);
+    uint64_t va_serialized_size_hint(const State* ptr, uint8_t compressed);
+    int32_t va_set_rule(State* ptr, uint32_t birth_mask, uint32_t survival_mask);
+    int32_t va_get_rule(const State* ptr, uint32_t* out_birth, uint32_t* out_survival);
+    int32_t va_set_rule_string(State* ptr, const uint8_t* text, uint64_t len);
+    uint64_t va_export_rule_table(const State* ptr, uint8_t* out_buf, uint64_t capacity);
+    // Multi-state rules: 27 entries per state, next = table[state * 27 + k]
+    // with k = neighbors in state 1. Null/0 clears (back to the rule)
+    int32_t va_set_transition_table(State* ptr, const uint8_t* table, uint64_t len);
+    int32_t va_set_cell_state(State* ptr, int16_t x, int16_t y, int16_t z, uint8_t value);
+    // Step mode: 0 = rule/transition table, 1 = WireWorld (cells 0 empty,
+    // 1 wire, 2 electron head, 3 electron tail; set with va_set_cell_state)
+    int32_t va_set_step_mode(State* ptr, uint32_t mode);
+    int32_t va_get_step_mode(const State* ptr);
+
+    // All tunables of a handle as a TOML blob (not NUL-terminated); null
+    // out_buf queries the length. set: 0 ok, -1 bad args, else first bad line
+    uint64_t va_get_config(const State* ptr, uint8_t* out_buf, uint64_t capacity);
+    int32_t va_set_config(State* ptr, const uint8_t* text, uint64_t len);
+
+    // Pattern stamps (rotation 0..23)
+    enum {
+        VA_STAMP_CUBE = 0, VA_STAMP_SPHERE = 1, VA_STAMP_SHELL = 2,
+        VA_STAMP_BLINKER = 3, VA_STAMP_GLIDER = 4, VA_STAMP_RANDOM_BLOB = 5
+    };
+    int32_t va_stamp(State* ptr, uint8_t pattern_id, int16_t x, int16_t y, int16_t z, uint8_t rotation);
 
     // Phase 6: Integer Field + Delta Diffusion
     typedef struct Field Field;
@@ -44,21 +234,429 @@ ffi.cdef [[
     void va_destroy_field(Field* ptr);
     void va_field_set(Field* ptr, int16_t x, int16_t y, int16_t z, uint32_t value);
     uint32_t va_field_get(const Field* ptr, int16_t x, int16_t y, int16_t z);
+    // count points as x, y, z triples; 0 written outside the field.
+    // Returns the number of points inside
+    uint64_t va_field_sample_batch(const Field* ptr, const int16_t* points, uint64_t count,
+                                   uint32_t* out_values);
+    uint64_t va_field_set_cells_bulk(Field* ptr, const int16_t* coords, const uint32_t* values,
+                                     uint64_t count);
     void va_field_step(Field* ptr);
+    // Lenia instead of diffusion: cells in 16.16 fixed point (65536 = alive).
+    // kernel: radius + 1 weights by distance; growth: >= 2 entries in
+    // -65536..65536 over potential 0..65536. 0 ok, 1 bad rule, -1 null field
+    int32_t va_field_step_lenia(Field* ptr, uint8_t radius, const uint16_t* kernel, uint32_t kernel_len,
+                                const int32_t* growth, uint32_t growth_len, uint8_t dt_shift);
     uint64_t va_field_get_generation(const Field* ptr);
+    uint64_t va_field_hash(const Field* field);
+    // va_diff for fields, 5 x int64 per cell
+    int64_t va_field_diff(const Field* a, const Field* b, int64_t* out_buf, uint64_t max);
+    // Summaries computed natively (e.g. to check conservation without reading cells)
+    uint64_t va_field_total(const Field* ptr);
+    uint32_t va_field_min(const Field* ptr);
+    uint32_t va_field_max(const Field* ptr);
+    double va_field_mean(const Field* ptr);
+    // va_fast_forward for a field: 4 x uint64 per sample (generation, total, min, max)
+    uint64_t va_field_fast_forward(Field* ptr, uint64_t n_generations, uint64_t stride,
+                                   uint64_t* out_metrics, uint64_t max_samples);
+    // bucket_count equal buckets over [min, max] (out_min/out_max nullable);
+    // percentile is nearest-rank, 0..100 (0 returned for bad input)
+    int32_t va_field_histogram(const Field* ptr, uint32_t bucket_count, uint64_t* out_buckets,
+                               uint32_t* out_min, uint32_t* out_max);
+    uint32_t va_field_percentile(const Field* ptr, double percent);
+    // One class byte per cell (z,y,x): count of thresholds <= value; thresholds
+    // strictly ascending, at most 255. Null out_buf queries the length
+    uint64_t va_field_extract_thresholded(const Field* ptr, uint8_t* out_buf, uint64_t buf_len,
+                                          const uint32_t* thresholds, uint32_t count);
+    // Zones: the same classification with breakpoints stored on the field
+    // (count 0 removes them). get_zone returns -1 without zones or out of bounds
+    int32_t va_field_set_zones(Field* ptr, const uint32_t* breakpoints, uint32_t count);
+    int32_t va_field_get_zone(const Field* ptr, int16_t x, int16_t y, int16_t z);
+    uint64_t va_field_extract_zones(const Field* ptr, uint8_t* out_buf, uint64_t buf_len,
+                                    int16_t min_x, int16_t min_y, int16_t min_z,
+                                    int16_t max_x, int16_t max_y, int16_t max_z);
+    // buf_len counts uint32 elements; same z,y,x layout as va_extract_region
+    uint64_t va_field_extract_region(const Field* ptr, uint32_t* out_buf, uint64_t buf_len,
+                                      int16_t min_x, int16_t min_y, int16_t min_z,
+                                      int16_t max_x, int16_t max_y, int16_t max_z,
+                                      uint64_t* out_generation);
+    uint64_t va_field_import_region(Field* ptr, const uint32_t* in_buf, uint64_t buf_len,
+                                     int16_t min_x, int16_t min_y, int16_t min_z,
+                                     int16_t max_x, int16_t max_y, int16_t max_z);
+    // Sources/sinks: applied every step before diffusion; rate 0 removes
+    int32_t va_field_add_source(Field* ptr, int16_t x, int16_t y, int16_t z, uint32_t rate_per_step);
+    int32_t va_field_add_sink(Field* ptr, int16_t x, int16_t y, int16_t z, uint32_t rate_per_step);
+    int32_t va_field_remove_source(Field* ptr, int16_t x, int16_t y, int16_t z);
+    void va_field_clear_sources(Field* ptr);
+
+    // Advection: fraction (x 2^16) moved one cell per step along each axis,
+    // sign = direction. (0, -8192, 0) drops 1/8 downward. |x|+|y|+|z| <= 65535.
+    int32_t va_field_set_advection(Field* ptr, int32_t x, int32_t y, int32_t z);
+
+    // Boundary of one outer face (0 -x, 1 +x, 2 -y, 3 +y, 4 -z, 5 +z):
+    // mode 0 reflective (default), 1 fixed at value, 2 open (mass leaves)
+    int32_t va_field_set_boundary(Field* ptr, uint8_t face, uint8_t mode, uint32_t value);
+
+    // Wrap-around axes (nonzero = periodic): flow leaving +x enters at -x.
+    // A periodic axis ignores the boundaries of its two faces
+    int32_t va_field_set_periodic(Field* ptr, uint8_t x, uint8_t y, uint8_t z);
+
+    // Ghost exchange between field shards (see va_exchange_boundaries); each
+    // field's total includes its ghost layers
+    int32_t va_field_exchange_boundaries(Field* a, Field* b, uint8_t face);
+
+    // Protected cells never gain value from a step; the withheld value leaves
+    // the field and is tallied by va_field_get_suppressed
+    uint64_t va_field_protect_region(Field* ptr,
+                                      int16_t min_x, int16_t min_y, int16_t min_z,
+                                      int16_t max_x, int16_t max_y, int16_t max_z,
+                                      uint8_t protected);
+    uint64_t va_field_get_suppressed(const Field* ptr);
+
+    // Configuration blob (as va_get_config / va_set_config): rates, rounding,
+    // advection, boundaries, periodic axes, sources, conductivity curve, phase thresholds
+    uint64_t va_field_get_config(const Field* ptr, uint8_t* out_buf, uint64_t capacity);
+    int32_t va_field_set_config(Field* ptr, const uint8_t* text, uint64_t len);
+    // Scenario bundle ([grid] and [field] sections: size, settings, stamps,
+    // cells, values). Out handles (nullable) are null for absent sections.
+    // 0 ok, -1 bad args, else first bad line
+    int32_t va_load_bundle(const uint8_t* text, uint64_t len, State** out_state,
+                           Field** out_field);
+
+    // Per-axis diffusion shifts replacing diffusion_rate; larger = slower.
+    // (2, 5, 2) makes vertical transport 8x slower. Rates <= 44.
+    int32_t va_field_set_axis_rates(Field* ptr, uint8_t rx, uint8_t ry, uint8_t rz);
+
+    // Conductivity curve: piecewise-linear value -> conductivity (x 2^16),
+    // evaluated at each pair's midpoint; values ascending, <= 16 points, 0 clears
+    int32_t va_field_set_conductivity_curve(Field* ptr, const uint32_t* values,
+                                            const uint16_t* conductivities, uint32_t count);
+
+    // Phase changes: threshold i separates phase i and i+1 (values ascending);
+    // crossing up absorbs latents[i], crossing down releases it. count 0 clears.
+    int32_t va_field_set_phase_thresholds(Field* ptr, const uint32_t* values,
+                                          const uint32_t* latents, uint32_t count);
+    // One uint8 phase per cell, z,y,x order; null out_buf queries the length
+    uint64_t va_field_get_phase(const Field* ptr, uint8_t* out_buf, uint64_t buf_len);
+
+    // Exact-mass resolution changes; fine dims = coarse dims * factor.
+    // 0 ok, 1 bad arguments, 2 coarse cell < factor^3, 3 block sum > u32 max
+    int32_t va_field_refine(const Field* coarse, Field* fine, uint8_t factor);
+    int32_t va_field_aggregate(const Field* fine, Field* coarse, uint8_t factor);
+    // Whole field reduced by factor (block averages, ceil(dim / factor) per
+    // axis). Null out_buf queries the length; out_dims (nullable) gets w, h, d
+    uint64_t va_field_extract_downsampled(const Field* ptr, uint32_t* out_buf, uint64_t buf_len,
+                                          uint8_t factor, int16_t* out_dims);
+
+    // Analytic fills, clipped to the field; return cells written (0 on error).
+    // Boxes are half-open [min, max); gradient axis 0 = x, 1 = y, 2 = z
+    uint64_t va_field_fill_box(Field* ptr, int16_t min_x, int16_t min_y, int16_t min_z,
+                               int16_t max_x, int16_t max_y, int16_t max_z, uint32_t value);
+    uint64_t va_field_fill_sphere(Field* ptr, int16_t cx, int16_t cy, int16_t cz,
+                                  uint16_t radius, uint32_t value);
+    uint64_t va_field_fill_shell(Field* ptr, int16_t cx, int16_t cy, int16_t cz,
+                                 uint16_t radius, uint16_t thickness, uint32_t value);
+    uint64_t va_field_fill_linear_gradient(Field* ptr, int16_t min_x, int16_t min_y,
+                                           int16_t min_z, int16_t max_x, int16_t max_y,
+                                           int16_t max_z, uint8_t axis, uint32_t from,
+                                           uint32_t to);
+    uint64_t va_field_fill_radial_gradient(Field* ptr, int16_t cx, int16_t cy, int16_t cz,
+                                           uint16_t radius, uint32_t inner, uint32_t outer);
+
+    // Rounding of fractional flows
+    enum {
+        VA_ROUNDING_STOCHASTIC = 0,
+        VA_ROUNDING_TRUNCATE = 1,
+        VA_ROUNDING_HASH = 2,
+        VA_ROUNDING_HALF_EVEN = 3
+    };
+    int32_t va_field_set_rounding(Field* ptr, uint8_t mode);
+    int32_t va_field_get_rounding(const Field* ptr);
+
+    // Diffusion algorithm of va_field_step (fused: rotationally symmetric, faster;
+    // blocked: fused in cache-sized blocks, less memory traffic on large fields)
+    enum {
+        VA_ALGORITHM_SEQUENTIAL = 0,
+        VA_ALGORITHM_FUSED = 1,
+        VA_ALGORITHM_BLOCKED = 2
+    };
+    int32_t va_field_set_algorithm(Field* ptr, uint8_t algo_id);
+    int32_t va_field_get_algorithm(const Field* ptr);
+
+    // Flow recording: 3 x int32 per cell (+x, +y, +z face flow of the last step)
+    int32_t va_field_set_flow_recording(Field* ptr, uint8_t enabled);
+    uint64_t va_field_get_flows(const Field* ptr, int32_t* out_buf, uint64_t buf_len);
+
+    // Field snapshots; baseline (nullable) enables delta encoding
+    uint64_t va_field_serialize(const Field* ptr, const Field* baseline,
+                                 uint8_t* out_buf, uint64_t capacity);
+    int32_t va_field_deserialize(Field* ptr, const Field* baseline,
+                                  const uint8_t* in_buf, uint64_t len);
+
+    // Soak test on a copy of the field. Returns 0 or the first violation
+    // (1 conservation, 2 bounds, 3 underflow) with its replayable seed.
+    enum {
+        VA_SOAK_FUSED = 1,
+        VA_SOAK_CONTROLLER = 2,
+        VA_SOAK_RANDOM_PARAMS = 4
+    };
+    int32_t va_soak(const Field* ptr, uint32_t seconds, uint32_t flags,
+                    uint64_t* out_seed, uint64_t* out_rounds);
+    int32_t va_soak_round(const Field* ptr, uint64_t seed, uint32_t flags);
+
+    // Cell tracing: steps n_steps generations, 10 x int64 per generation:
+    // generation, value, next_value, neighbors, flows (-x,+x,-y,+y,-z,+z)
+    uint32_t va_trace_cell(State* ptr, int16_t x, int16_t y, int16_t z,
+                           uint32_t n_steps, int64_t* out);
+    uint32_t va_field_trace_cell(Field* ptr, int16_t x, int16_t y, int16_t z,
+                                 uint32_t n_steps, int64_t* out);
+    // Readable breakdown of one cell (value, last-step face flows, source,
+    // boundaries, phase); null out_text queries the length
+    uint64_t va_field_explain(const Field* ptr, int16_t x, int16_t y, int16_t z,
+                              uint8_t* out_text, uint64_t capacity);
+
+    // Wide fields: u64 cells (i128 flow math) for values beyond u32, e.g. joules.
+    // Diffusion only; same semantics as the va_field_* equivalents.
+    typedef struct Field64 Field64;
+    Field64* va_create_field64(int16_t width, int16_t height, int16_t depth, uint8_t diffusion_rate);
+    void va_destroy_field64(Field64* ptr);
+    void va_field64_set(Field64* ptr, int16_t x, int16_t y, int16_t z, uint64_t value);
+    uint64_t va_field64_get(const Field64* ptr, int16_t x, int16_t y, int16_t z);
+    void va_field64_step(Field64* ptr);
+    uint64_t va_field64_get_generation(const Field64* ptr);
+    uint64_t va_field64_extract_region(const Field64* ptr, uint64_t* out_buf, uint64_t buf_len,
+                                        int16_t min_x, int16_t min_y, int16_t min_z,
+                                        int16_t max_x, int16_t max_y, int16_t max_z,
+                                        uint64_t* out_generation);
+    uint64_t va_field64_import_region(Field64* ptr, const uint64_t* in_buf, uint64_t buf_len,
+                                       int16_t min_x, int16_t min_y, int16_t min_z,
+                                       int16_t max_x, int16_t max_y, int16_t max_z);
+    int32_t va_field64_set_rounding(Field64* ptr, uint8_t mode);
+    int32_t va_field64_set_axis_rates(Field64* ptr, uint8_t rx, uint8_t ry, uint8_t rz);
+
+    // Signed fields: i32 cells (potentials, velocity components), start at 0.
+    // out_ok (nullable) tells a stored 0 from an out-of-bounds read
+    typedef struct IField IField;
+    IField* va_create_ifield(int16_t width, int16_t height, int16_t depth, uint8_t diffusion_rate);
+    void va_destroy_ifield(IField* ptr);
+    void va_ifield_set(IField* ptr, int16_t x, int16_t y, int16_t z, int32_t value);
+    int32_t va_ifield_get(const IField* ptr, int16_t x, int16_t y, int16_t z, uint8_t* out_ok);
+    void va_ifield_step(IField* ptr);
+    uint64_t va_ifield_get_generation(const IField* ptr);
+    uint64_t va_ifield_extract_region(const IField* ptr, int32_t* out_buf, uint64_t buf_len,
+                                      int16_t min_x, int16_t min_y, int16_t min_z,
+                                      int16_t max_x, int16_t max_y, int16_t max_z,
+                                      uint64_t* out_generation);
+    uint64_t va_ifield_import_region(IField* ptr, const int32_t* in_buf, uint64_t buf_len,
+                                     int16_t min_x, int16_t min_y, int16_t min_z,
+                                     int16_t max_x, int16_t max_y, int16_t max_z);
+    int32_t va_ifield_set_rounding(IField* ptr, uint8_t mode);
+    int32_t va_ifield_set_axis_rates(IField* ptr, uint8_t rx, uint8_t ry, uint8_t rz);
+
+    // Coupled fields: lockstep stepping with linear cross-terms (2^16 scale).
+    // A registered field is owned by the coupling: keep using it with
+    // va_field_*, but never va_destroy_field it
+    typedef struct CoupledFields CoupledFields;
+    CoupledFields* va_create_coupled(void);
+    void va_destroy_coupled(CoupledFields* coupled);
+    int32_t va_coupled_register(CoupledFields* coupled, Field* field);
+    int32_t va_coupled_set_coefficient(CoupledFields* coupled, uint8_t target, uint8_t source, int32_t coefficient);
+    int32_t va_coupled_set_matrix(CoupledFields* coupled, const int32_t* coefficients, uint64_t len);
+    void va_coupled_step(CoupledFields* coupled);
+    uint64_t va_coupled_get_generation(const CoupledFields* coupled);
+
+    // Grid-field binding (same dimensions; both stay owned by the caller and
+    // must outlive the binding). Each step: grid steps with births only where
+    // field > birth_threshold (0 = ungated), live cells add emit_rate, field
+    // steps. va_step_bound: 0 ok, 1 null or resized since binding
+    typedef struct Binding Binding;
+    Binding* va_bind_field(State* state, Field* field, uint32_t emit_rate, uint32_t birth_threshold);
+    int32_t va_step_bound(Binding* binding);
+    void va_unbind(Binding* binding);
+
+    // Shared fields: one writer, any number of read-only readers observing the
+    // same cells. va_field_share takes the field (don't use or destroy it
+    // afterwards); unshare returns it once every reader is released (else null)
+    typedef struct FieldWriter FieldWriter;
+    typedef struct FieldReader FieldReader;
+    FieldWriter* va_field_share(Field* field);
+    Field* va_shared_field_unshare(FieldWriter* writer);
+    void va_destroy_shared_field(FieldWriter* writer);
+    FieldReader* va_shared_field_reader(const FieldWriter* writer);
+    uint64_t va_shared_field_readers(const FieldWriter* writer);
+    int32_t va_shared_field_step(FieldWriter* writer);
+    int32_t va_shared_field_set(FieldWriter* writer, int16_t x, int16_t y, int16_t z, uint32_t value);
+    FieldReader* va_reader_clone(const FieldReader* reader);
+    void va_release_reader(FieldReader* reader);
+    uint32_t va_reader_get(const FieldReader* reader, int16_t x, int16_t y, int16_t z);
+    uint64_t va_reader_get_generation(const FieldReader* reader);
+    uint64_t va_reader_total(const FieldReader* reader);
+    uint64_t va_reader_extract_region(const FieldReader* reader, uint32_t* out_buf, uint64_t buf_len,
+                                      int16_t min_x, int16_t min_y, int16_t min_z,
+                                      int16_t max_x, int16_t max_y, int16_t max_z,
+                                      uint64_t* out_generation);
+
+    // Shared-memory export for out-of-process observers (needs build feature
+    // bit 256): a 64-byte layout header, then the cells; call va_shm_publish
+    // after each step they should see
+    typedef struct ShmExport ShmExport;
+    ShmExport* va_field_shm_export(const Field* field, const uint8_t* name, uint64_t name_len);
+    int32_t va_shm_publish(ShmExport* export, const Field* field);
+    uint64_t va_shm_path(const ShmExport* export, uint8_t* out_buf, uint64_t capacity);
+    void va_shm_destroy(ShmExport* export);
+
+    // Field stacks: several layers (e.g. temperature, humidity, pressure)
+    // on one grid, stepped together in one pass
+    typedef struct FieldStack FieldStack;
+    FieldStack* va_create_field_stack(int16_t width, int16_t height, int16_t depth, uint8_t num_layers);
+    void va_destroy_field_stack(FieldStack* ptr);
+    int32_t va_field_stack_set_layer(FieldStack* ptr, uint8_t layer, uint8_t diffusion_rate, uint16_t conductivity);
+    void va_field_stack_set(FieldStack* ptr, uint8_t layer, int16_t x, int16_t y, int16_t z, uint32_t value);
+    uint32_t va_field_stack_get(const FieldStack* ptr, uint8_t layer, int16_t x, int16_t y, int16_t z);
+    void va_field_stack_step(FieldStack* ptr);
+    uint64_t va_field_stack_get_generation(const FieldStack* ptr);
+
+    // Adaptive fields: coarse 4x4x4 blocks where nothing happens, fine cells
+    // where something does (dimensions must be multiples of 4)
+    typedef struct AdaptiveField AdaptiveField;
+    AdaptiveField* va_create_adaptive_field(int16_t width, int16_t height, int16_t depth, uint8_t diffusion_rate);
+    void va_destroy_adaptive_field(AdaptiveField* ptr);
+    int32_t va_adaptive_set(AdaptiveField* ptr, int16_t x, int16_t y, int16_t z, uint32_t value);
+    uint32_t va_adaptive_get(const AdaptiveField* ptr, int16_t x, int16_t y, int16_t z);
+    void va_adaptive_step(AdaptiveField* ptr);
+    int32_t va_adaptive_refine(AdaptiveField* ptr, int16_t x, int16_t y, int16_t z);
+    int32_t va_adaptive_coarsen(AdaptiveField* ptr, int16_t x, int16_t y, int16_t z);
+    // Blocks refine above threshold per cell against a neighbor, coarsen at or below half (0: manual)
+    void va_adaptive_set_threshold(AdaptiveField* ptr, uint32_t threshold);
+    int32_t va_adaptive_set_rounding(AdaptiveField* ptr, uint8_t mode);
+    uint64_t va_adaptive_total(const AdaptiveField* ptr);
+    uint32_t va_adaptive_refined_blocks(const AdaptiveField* ptr);
+    uint64_t va_adaptive_get_generation(const AdaptiveField* ptr);
+
+    // Chunked fields: 16x16x16 chunks stored only where cells differ from the
+    // ambient value, for huge mostly uniform domains (rounding defaults to hash).
+    // va_field_get/set/step/extract_region/total/get_generation/set_rounding and
+    // va_destroy_field take them; other field calls need a dense field
+    Field* va_create_field_chunked(int16_t width, int16_t height, int16_t depth, uint32_t ambient, uint8_t diffusion_rate);
+    uint64_t va_field_chunk_count(const Field* ptr);
+
+    // Unbounded CA grid: 16x16x16 chunks allocated as patterns grow, i32
+    // world coordinates (chunk = coordinate >> 4); B0 is ignored
+    typedef struct InfiniteState InfiniteState;
+    InfiniteState* va_create_infinite(void);
+    void va_destroy_infinite(InfiniteState* ptr);
+    void va_inf_set_cell(InfiniteState* ptr, int32_t x, int32_t y, int32_t z, uint8_t alive);
+    uint8_t va_inf_get_cell(const InfiniteState* ptr, int32_t x, int32_t y, int32_t z);
+    void va_inf_step(InfiniteState* ptr);
+    int32_t va_inf_set_rule(InfiniteState* ptr, uint32_t birth_mask, uint32_t survival_mask);
+    uint64_t va_inf_get_generation(const InfiniteState* ptr);
+    uint64_t va_inf_population(const InfiniteState* ptr);
+    // Writes up to capacity x,y,z chunk triples; returns the chunk count
+    uint64_t va_inf_list_chunks(const InfiniteState* ptr, int32_t* out_keys, uint64_t capacity);
+    uint64_t va_inf_extract_chunk(const InfiniteState* ptr, int32_t chunk_x, int32_t chunk_y, int32_t chunk_z,
+                                  uint8_t* out_buf, uint64_t buf_len);
 
     // Phase 8a: Non-blocking incremental stepping
     typedef struct StepController StepController;
-    StepController* va_create_step_controller(int16_t w, int16_t h, int16_t d, uint8_t diffusion_rate, uint8_t num_threads);
+    // tile_size: 8, 16 or 32 cells (anything else, e.g. 0: 16)
+    StepController* va_create_step_controller(int16_t w, int16_t h, int16_t d, uint8_t diffusion_rate, uint8_t num_threads, uint8_t tile_size);
     StepController* va_create_step_controller_with_initial(int16_t w, int16_t h, int16_t d, uint32_t initial_value, uint8_t diffusion_rate, uint8_t num_threads);
     void va_destroy_step_controller(StepController* ctrl);
+    // Mid-step writes are queued and applied when the step commits
     void va_sc_field_set(StepController* ctrl, int16_t x, int16_t y, int16_t z, uint32_t value);
+    uint64_t va_sc_pending_writes(const StepController* ctrl);
     uint32_t va_sc_field_get(const StepController* ctrl, int16_t x, int16_t y, int16_t z);
     uint64_t va_sc_field_get_generation(const StepController* ctrl);
     int32_t va_sc_begin_step(StepController* ctrl);
-    int32_t va_sc_tick(StepController* ctrl, uint64_t budget_us);
+    // out_elapsed_ns / out_tiles (nullable): time actually spent, tiles finished
+    int32_t va_sc_tick(StepController* ctrl, uint64_t budget_us,
+                       uint64_t* out_elapsed_ns, uint32_t* out_tiles);
+    // At most max_tiles tiles whatever the time (deterministic pacing), same returns
+    int32_t va_sc_tick_tiles(StepController* ctrl, uint32_t max_tiles,
+                             uint64_t* out_elapsed_ns, uint32_t* out_tiles);
+    // out_timing[3] (us): average per tile, last tick, estimated remaining. 0 ok, 1 null
+    int32_t va_sc_get_timing(const StepController* ctrl, double* out_timing);
     int32_t va_sc_is_stepping(const StepController* ctrl);
+    uint8_t va_sc_get_tile_size(const StepController* ctrl);
+    // Share of the active step done (1.0 when idle, -1.0 null); tile counts nullable
+    float va_sc_progress(const StepController* ctrl, uint32_t* out_tiles_done, uint32_t* out_total_tiles);
     void va_sc_step_blocking(StepController* ctrl);
+    int32_t va_sc_set_rounding_seed(StepController* ctrl, uint64_t seed);
+    int32_t va_sc_set_axis_rates(StepController* ctrl, uint8_t rx, uint8_t ry, uint8_t rz);
+    int32_t va_sc_set_periodic(StepController* ctrl, uint8_t x, uint8_t y, uint8_t z);
+    // Tiles nearest (x, y, z) first, e.g. around a player; clear for default order
+    int32_t va_sc_set_focus(StepController* ctrl, int16_t x, int16_t y, int16_t z);
+    int32_t va_sc_clear_focus(StepController* ctrl);
+    // Skip tiles at equilibrium (same results); count skipped by the last step
+    int32_t va_sc_set_skip_quiet(StepController* ctrl, uint8_t enabled);
+    uint64_t va_sc_skipped_tiles(const StepController* ctrl);
+    // Drain after va_sc_tick: 1 = event written, 0 = none. kind 1 = generation
+    // complete; 2-4 = memory shed under the cap (flow record, compaction, threads)
+    enum {
+        VA_EVENT_GENERATION_COMPLETE = 1,
+        VA_EVENT_DEGRADED_FLOW_RECORD = 2,
+        VA_EVENT_DEGRADED_COMPACTED = 3,
+        VA_EVENT_DEGRADED_SINGLE_THREAD = 4
+    };
+    int32_t va_sc_poll_event(StepController* ctrl, uint32_t* out_kind,
+                             uint64_t* out_generation, uint64_t* out_global_tick);
+    // Memory cap in heap bytes (0 = none): begin_step sheds optional memory to
+    // meet it and reports each degradation as an event; the step runs anyway.
+    // Degradations: bit 0 flow record, bit 1 compaction, bit 2 single thread
+    int32_t va_sc_set_memory_cap(StepController* ctrl, uint64_t cap_bytes);
+    uint64_t va_sc_memory_usage(const StepController* ctrl);
+    uint32_t va_sc_get_degradations(const StepController* ctrl);
+    // Post-step pipeline, run in order as each step finalizes (<= 8 ops).
+    // add_* return the op index or -1. The grid is owned by the controller:
+    // read-only, never va_destroy it; invalid after va_sc_post_clear.
+    int32_t va_sc_post_add_threshold(StepController* ctrl, uint32_t threshold);
+    int32_t va_sc_post_add_decay(StepController* ctrl, uint8_t shift);
+    int32_t va_sc_post_add_stats(StepController* ctrl);
+    int32_t va_sc_post_clear(StepController* ctrl);
+    const State* va_sc_post_grid(const StepController* ctrl, uint32_t index);
+    int32_t va_sc_post_stats(const StepController* ctrl, uint32_t index, uint64_t* out_generation,
+                             uint64_t* out_total, uint32_t* out_min, uint32_t* out_max);
+    // Protection mask of the controller's field (as va_field_protect_region)
+    uint64_t va_sc_protect_region(StepController* ctrl,
+                                   int16_t min_x, int16_t min_y, int16_t min_z,
+                                   int16_t max_x, int16_t max_y, int16_t max_z,
+                                   uint8_t protected);
+    uint64_t va_sc_get_suppressed(const StepController* ctrl);
+
+    // Background stepping: the controller moves to a worker thread (0 sps = unlimited)
+    // until va_async_stop returns it. Reads see the latest completed generation.
+    typedef struct AsyncStepper AsyncStepper;
+    AsyncStepper* va_sc_start_async(StepController* ctrl, uint32_t steps_per_second);
+    int32_t va_async_set_rate(const AsyncStepper* stepper, uint32_t steps_per_second);
+    uint64_t va_async_generation(const AsyncStepper* stepper);
+    uint32_t va_async_get(const AsyncStepper* stepper, int16_t x, int16_t y, int16_t z);
+    uint64_t va_async_extract_region(const AsyncStepper* stepper, uint32_t* out_buf, uint64_t buf_len,
+                                     int16_t min_x, int16_t min_y, int16_t min_z,
+                                     int16_t max_x, int16_t max_y, int16_t max_z,
+                                     uint64_t* out_generation);
+    StepController* va_async_stop(AsyncStepper* stepper);
+    void va_async_destroy(AsyncStepper* stepper);
+
+    // Incremental grid stepping: the State moves into the controller until
+    // va_ca_sc_release (null while stepping). Tick returns as va_sc_tick.
+    typedef struct CaStepController CaStepController;
+    CaStepController* va_create_ca_step_controller(State* state);
+    void va_destroy_ca_step_controller(CaStepController* ctrl);
+    State* va_ca_sc_release(CaStepController* ctrl);
+    const State* va_ca_sc_state(const CaStepController* ctrl);
+    int32_t va_ca_sc_set_cell(CaStepController* ctrl, int16_t x, int16_t y, int16_t z, uint8_t alive);
+    int32_t va_ca_sc_begin_step(CaStepController* ctrl);
+    int32_t va_ca_sc_tick(CaStepController* ctrl, uint64_t budget_us,
+                          uint64_t* out_elapsed_ns, uint32_t* out_tiles);
+    int32_t va_ca_sc_is_stepping(const CaStepController* ctrl);
+    void va_ca_sc_step_blocking(CaStepController* ctrl);
+
+    // Overflow audit. Report: x, y, z, axis, value_a, value_b, conductivity, dt.
+    // Returns 0 ok, 1 pair overflow, 2 divisor overflow, -1 null.
+    int32_t va_field_step_checked(Field* ptr, int64_t* out_report);
+    int32_t va_sc_audit_overflow(const StepController* ctrl, int64_t* out_report);
+    // Shadow steps checked against exact arithmetic (build feature bit 512).
+    // Report: generation, x, y, z, expected, actual. Returns 0 ok, 1 mismatch, -1 null.
+    int32_t va_field_shadow_steps(Field* field, uint64_t steps, int64_t* out_report);
 
     // Phase 9c: Cadence FFI
     uint32_t va_sc_cadence_advance(StepController* ctrl, int16_t* out_zone_data, uint32_t max_zones);
@@ -77,10 +675,92 @@ ffi.cdef [[
     // Phase 9c: Infinity Contract FFI
     int32_t va_sc_infinity_create(StepController* ctrl, int16_t x, int16_t y, int16_t z, uint32_t target_value);
     int32_t va_sc_infinity_destroy(StepController* ctrl, int16_t x, int16_t y, int16_t z);
+
+    // Transactions: enlist handles (saved on add), mutate them as usual, then
+    // commit (0 kept, 1 rolled back because a participant stepped) or abort
+    typedef struct Transaction Transaction;
+    Transaction* va_txn_begin(void);
+    int32_t va_txn_add_state(Transaction* txn, State* ptr);
+    int32_t va_txn_add_field(Transaction* txn, Field* ptr);
+    int32_t va_txn_add_controller(Transaction* txn, StepController* ctrl);
+    int32_t va_txn_commit(Transaction* txn);
+    void va_txn_abort(Transaction* txn);
+
+    // Integer handle ids (never 0): a destroyed or mistyped id fails with
+    // error 10 instead of touching freed memory. Kinds: 1 State, 2 Field,
+    // 3 StepController. Id calls are serialized across threads; the *_ptr
+    // escape hatches lend a pointer valid until va_id_destroy
+    uint32_t va_id_create(void);
+    uint32_t va_id_create_field(int16_t w, int16_t h, int16_t d, uint8_t diffusion_rate);
+    uint32_t va_id_create_step_controller(int16_t w, int16_t h, int16_t d, uint8_t diffusion_rate,
+                                          uint8_t num_threads, uint8_t tile_size);
+    int32_t va_id_destroy(uint32_t id);
+    int32_t va_id_kind(uint32_t id);
+    uint64_t va_id_list(uint32_t* out_ids, uint64_t capacity);
+    State* va_id_state_ptr(uint32_t id);
+    Field* va_id_field_ptr(uint32_t id);
+    StepController* va_id_sc_ptr(uint32_t id);
+    int32_t va_id_create_grid(uint32_t id, int16_t w, int16_t h, int16_t d);
+    void va_id_set_cell(uint32_t id, int16_t x, int16_t y, int16_t z, uint8_t alive);
+    uint8_t va_id_get_cell(uint32_t id, int16_t x, int16_t y, int16_t z);
+    void va_id_step(uint32_t id);
+    uint64_t va_id_get_generation(uint32_t id);
+    uint64_t va_id_extract_region(uint32_t id, uint8_t* out_buf, uint64_t buf_len,
+                                  int16_t min_x, int16_t min_y, int16_t min_z,
+                                  int16_t max_x, int16_t max_y, int16_t max_z,
+                                  uint64_t* out_generation);
+    uint64_t va_id_import_region(uint32_t id, const uint8_t* in_buf, uint64_t buf_len,
+                                 int16_t min_x, int16_t min_y, int16_t min_z,
+                                 int16_t max_x, int16_t max_y, int16_t max_z);
+    void va_id_field_set(uint32_t id, int16_t x, int16_t y, int16_t z, uint32_t value);
+    uint32_t va_id_field_get(uint32_t id, int16_t x, int16_t y, int16_t z);
+    void va_id_field_step(uint32_t id);
+    uint64_t va_id_field_total(uint32_t id);
+    int32_t va_id_sc_begin_step(uint32_t id);
+    int32_t va_id_sc_tick(uint32_t id, uint64_t budget_us, uint64_t* out_elapsed_ns,
+                          uint32_t* out_tiles);
+    void va_id_sc_step_blocking(uint32_t id);
+    int32_t va_id_sc_is_stepping(uint32_t id);
+    void va_id_sc_field_set(uint32_t id, int16_t x, int16_t y, int16_t z, uint32_t value);
+    uint32_t va_id_sc_field_get(uint32_t id, int16_t x, int16_t y, int16_t z);
+
+    // Thread-safe State for async environments: the va_shared_* calls lock
+    // internally; va_shared_lock / va_shared_try_lock (null and error 7 if
+    // held) lend the State for ordinary calls until va_shared_unlock, which
+    // any thread may call (it fails, returning 1, unless the lock was taken
+    // that way). Don't call va_shared_* while holding the lock
+    typedef struct SharedState SharedState;
+    SharedState* va_create_shared(void);
+    void va_destroy_shared(SharedState* shared);
+    State* va_shared_lock(const SharedState* shared);
+    State* va_shared_try_lock(const SharedState* shared);
+    int32_t va_shared_unlock(const SharedState* shared);
+    int32_t va_shared_is_locked(const SharedState* shared);
+    int32_t va_shared_create_grid(const SharedState* shared, int16_t w, int16_t h, int16_t d);
+    void va_shared_set_cell(const SharedState* shared, int16_t x, int16_t y, int16_t z, uint8_t alive);
+    uint8_t va_shared_get_cell(const SharedState* shared, int16_t x, int16_t y, int16_t z);
+    void va_shared_step(const SharedState* shared);
+    int32_t va_shared_try_step(const SharedState* shared);
+    uint64_t va_shared_get_generation(const SharedState* shared);
+    uint64_t va_shared_extract_region(const SharedState* shared, uint8_t* out_buf, uint64_t buf_len,
+                                      int16_t min_x, int16_t min_y, int16_t min_z,
+                                      int16_t max_x, int16_t max_y, int16_t max_z,
+                                      uint64_t* out_generation);
+    uint64_t va_shared_import_region(const SharedState* shared, const uint8_t* in_buf, uint64_t buf_len,
+                                     int16_t min_x, int16_t min_y, int16_t min_z,
+                                     int16_t max_x, int16_t max_y, int16_t max_z);
 ]]
 
 local va = ffi.load(modpath .. "/lib/libvoxel_automata.so")
 
+-- A mod reload re-runs this file against the already-loaded library; reclaim
+-- anything the previous run left in its global registries.
+local reclaimed = tonumber(va.va_reinit())
+if reclaimed > 0 then
+    minetest.log("warning", "[voxel_automata] Reclaimed " .. reclaimed ..
+        " leaked buffer(s) from a previous load")
+end
+
 -- Shared state passed to all submodules
 local M = {
     va                     = va,
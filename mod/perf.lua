@@ -58,7 +58,7 @@ return function(M)
                 return false, "Tickrate must be between 10 and 10000 ms"
             end
 
-            local ctrl = va.va_create_step_controller(size_x, size_y, size_z, 3, 1)
+            local ctrl = va.va_create_step_controller(size_x, size_y, size_z, 3, 1, 0)
             if ctrl == nil then
                 return false, "Failed to create step controller"
             end
@@ -122,7 +122,7 @@ return function(M)
             end
         elseif p.phase == "stepping" then
             -- Tick with small budget; stay in STEPPING until step completes
-            local done = va.va_sc_tick(p.controller, 4000) -- 4ms budget
+            local done = va.va_sc_tick(p.controller, 4000, nil, nil) -- 4ms budget
             if done == 1 then
                 p.phase = "rendering"
             end
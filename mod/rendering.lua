@@ -18,8 +18,8 @@ return function(M)
         local buffer = ffi.new("uint8_t[?]", buffer_size)
 
         local bytes_written = va.va_extract_region(
-            M.global_state, buffer,
-            min_x, min_y, min_z, max_x, max_y, max_z
+            M.global_state, buffer, buffer_size,
+            min_x, min_y, min_z, max_x, max_y, max_z, nil
         )
 
         if bytes_written == 0 then
@@ -59,8 +59,8 @@ return function(M)
         local buffer = ffi.new("uint8_t[?]", buffer_size)
 
         local bytes_written = va.va_extract_region(
-            M.global_state, buffer,
-            min_x, min_y, min_z, max_x, max_y, max_z
+            M.global_state, buffer, buffer_size,
+            min_x, min_y, min_z, max_x, max_y, max_z, nil
         )
 
         if bytes_written == 0 then
@@ -99,15 +99,22 @@ return function(M)
         local width = max_x - min_x
         local height = max_y - min_y
         local depth = max_z - min_z
-        local buffer_size = width * height * depth
-        local buffer = ffi.new("uint8_t[?]", buffer_size)
+        -- Pooled buffer: this runs every animation tick, so avoid a fresh
+        -- allocation per call
+        local capacity = ffi.new("uint64_t[1]")
+        local buffer = va.va_acquire_buffer(width * height * depth, capacity)
+        if buffer == nil then
+            minetest.log("warning", "[voxel_automata] Could not acquire extraction buffer")
+            return
+        end
 
         local bytes_written = va.va_extract_region(
-            M.global_state, buffer,
-            min_x, min_y, min_z, max_x, max_y, max_z
+            M.global_state, buffer, capacity[0],
+            min_x, min_y, min_z, max_x, max_y, max_z, nil
         )
 
         if bytes_written == 0 then
+            va.va_release_buffer(buffer)
             minetest.log("warning", "[voxel_automata] Extract region returned 0 bytes")
             return
         end
@@ -141,6 +148,7 @@ return function(M)
                 end
             end
         end
+        va.va_release_buffer(buffer)
 
         vm:set_data(data)
         vm:write_to_map()
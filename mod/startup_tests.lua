@@ -19,6 +19,11 @@ return function(M)
     local result = va.va_add(2, 3)
     test_assert(result == 5, "Phase 1: FFI arithmetic", string.format("expected 5, got %d", result))
 
+    -- Library self test (validates this build on this platform)
+    local self_test = va.va_self_test()
+    test_assert(self_test == 0, "Library self test",
+        string.format("failure bitmask 0x%x (conservation=1, determinism=2, rule=4, ffi=8)", self_test))
+
     -- Phase 2: Handle lifecycle
     local state = va.va_create()
     test_assert(state ~= nil, "Phase 2: Create state", "va_create() returned nil")
@@ -76,7 +81,7 @@ return function(M)
     M.global_field = field
 
     -- Phase 8a: StepController basic
-    local ctrl = va.va_create_step_controller(16, 16, 16, 2, 1)
+    local ctrl = va.va_create_step_controller(16, 16, 16, 2, 1, 0)
     test_assert(ctrl ~= nil, "Phase 8a: Create StepController", "va_create_step_controller() returned nil")
 
     va.va_sc_field_set(ctrl, 0, 0, 0, 3999995905)
@@ -94,7 +99,7 @@ return function(M)
     M.global_step_controller = ctrl
 
     -- NEW: StepController incremental stepping
-    local ctrl2 = va.va_create_step_controller(8, 8, 8, 2, 1)
+    local ctrl2 = va.va_create_step_controller(8, 8, 8, 2, 1, 0)
     test_assert(ctrl2 ~= nil, "Phase 8a: Create test StepController", "returned nil")
 
     va.va_sc_field_set(ctrl2, 4, 4, 4, 1000000)
@@ -104,7 +109,7 @@ return function(M)
     test_assert(begin_result == 0, "Phase 8a: begin_step success",
         string.format("expected 0, got %d", begin_result))
 
-    local done = va.va_sc_tick(ctrl2, 100000000) -- Large budget to complete immediately
+    local done = va.va_sc_tick(ctrl2, 100000000, nil, nil) -- Large budget to complete immediately
     test_assert(done == 1, "Phase 8a: Incremental step completes",
         string.format("expected done=1, got %d", done))
 
@@ -0,0 +1,553 @@
+//! Safe, idiomatic Rust API over the core automaton and field logic.
+//!
+//! The `ffi` module exposes everything through raw pointers for LuaJIT. Rust
+//! callers (game servers, tools, tests) should not have to go through that layer,
+//! so this module adds constructors, methods, and iterators directly on the core
+//! types. Every method here is a thin wrapper over the free functions in
+//! `automaton`, so both surfaces always run the exact same code.
+//!
+//! ```
+//! use voxel_automata::{Field, Rule, State};
+//!
+//! let mut state = State::new(8, 8, 8);
+//! state.set_rule("B4/S4".parse::<Rule>().unwrap());
+//! state.set(4, 4, 4, true);
+//! state.step();
+//! assert_eq!(state.generation(), 1);
+//!
+//! let mut field = Field::new(8, 8, 8, 2);
+//! field.set(4, 4, 4, 1000).unwrap();
+//! let total = field.total();
+//! field.step();
+//! assert_eq!(field.total(), total);
+//! ```
+
+use std::fmt;
+use std::num::NonZeroU32;
+use std::str::FromStr;
+
+use crate::automaton::field::{
+    create_field, create_field_1, field_get, field_in_bounds, field_index_of, field_set,
+    field_step, field_step_fused, Field, FieldError,
+};
+use crate::automaton::grid::{count_neighbors, in_bounds, index_of, try_create_grid, GridError};
+use crate::automaton::incremental::StepController;
+use crate::automaton::region::{
+    clear, extract_mapblock, extract_region, fill_region, import_mapblock, import_region,
+    randomize_region, MAPBLOCK_VOLUME,
+};
+use crate::automaton::rule::{parse_rule, rule_notation, RuleParseError};
+use crate::automaton::snapshot::{
+    compressed_size, deserialize_state, deserialize_state_compressed, serialize_state,
+    serialize_state_compressed, serialized_size, SnapshotError,
+};
+use crate::automaton::stamp::stamp_pattern;
+use crate::automaton::stepping::step_automaton;
+use crate::state::{Rule, State};
+
+/// Iterator over every cell of a 3D buffer in z,y,x order (x changes fastest).
+///
+/// Yields `((x, y, z), value)`. Matches the layout used by region extraction.
+pub struct Cells<'a, T: Copy> {
+    cells: &'a [T],
+    width: i16,
+    height: i16,
+    next: usize,
+}
+
+impl<'a, T: Copy> Cells<'a, T> {
+    fn new(cells: &'a [T], width: i16, height: i16) -> Self {
+        Cells {
+            cells,
+            width,
+            height,
+            next: 0,
+        }
+    }
+}
+
+impl<T: Copy> Iterator for Cells<'_, T> {
+    type Item = ((i16, i16, i16), T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let idx = self.next;
+        let value = *self.cells.get(idx)?;
+        self.next += 1;
+
+        let w = self.width as usize;
+        let h = self.height as usize;
+        let x = (idx % w) as i16;
+        let y = ((idx / w) % h) as i16;
+        let z = (idx / (w * h)) as i16;
+        Some(((x, y, z), value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.cells.len() - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T: Copy> ExactSizeIterator for Cells<'_, T> {}
+
+impl fmt::Display for Rule {
+    /// `B.../S...` notation, e.g. `B4/S4`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&rule_notation(self))
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleParseError;
+
+    /// Parses `B4/S4` or Golly 3D.lua `3D4/4` notation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_rule(s)
+    }
+}
+
+impl State {
+    /// Create a state with an all-dead grid of the given dimensions.
+    ///
+    /// Panics on the dimensions `try_new` rejects.
+    pub fn new(width: i16, height: i16, depth: i16) -> Self {
+        match Self::try_new(width, height, depth) {
+            Ok(state) => state,
+            Err(err) => panic!("cannot create a {width}x{height}x{depth} grid: {err:?}"),
+        }
+    }
+
+    /// Create a state with an all-dead grid, or an error for non-positive
+    /// dimensions, more than `MAX_GRID_CELLS` cells, or a failed allocation.
+    pub fn try_new(width: i16, height: i16, depth: i16) -> Result<Self, GridError> {
+        let mut state = State::default();
+        try_create_grid(&mut state, width, height, depth)?;
+        Ok(state)
+    }
+
+    /// Grid dimensions as `(width, height, depth)`.
+    pub fn dimensions(&self) -> (i16, i16, i16) {
+        (self.width, self.height, self.depth)
+    }
+
+    /// Current generation counter.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Birth/survival rule used by `step`.
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Replace the birth/survival rule (masks are trimmed to neighbor counts 0..=26).
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = Rule {
+            birth: rule.birth & Rule::MASK,
+            survival: rule.survival & Rule::MASK,
+        };
+    }
+
+    /// Whether the coordinates are inside the grid.
+    pub fn contains(&self, x: i16, y: i16, z: i16) -> bool {
+        in_bounds(self, x, y, z)
+    }
+
+    /// Cell value (0 = dead, 1 = alive), or None when out of bounds.
+    pub fn get(&self, x: i16, y: i16, z: i16) -> Option<u8> {
+        if in_bounds(self, x, y, z) {
+            Some(self.cells[index_of(self, x, y, z)])
+        } else {
+            None
+        }
+    }
+
+    /// Whether the cell is alive. Out-of-bounds cells are dead.
+    pub fn is_alive(&self, x: i16, y: i16, z: i16) -> bool {
+        self.get(x, y, z).is_some_and(|v| v != 0)
+    }
+
+    /// Set a cell alive or dead. Returns false (and does nothing) when out of bounds.
+    pub fn set(&mut self, x: i16, y: i16, z: i16, alive: bool) -> bool {
+        if !in_bounds(self, x, y, z) {
+            return false;
+        }
+        let idx = index_of(self, x, y, z);
+        self.cells[idx] = alive as u8;
+        true
+    }
+
+    /// Number of live neighbors in the Moore neighborhood (26 cells).
+    pub fn neighbors(&self, x: i16, y: i16, z: i16) -> u8 {
+        count_neighbors(self, x, y, z)
+    }
+
+    /// Advance one generation.
+    pub fn step(&mut self) {
+        step_automaton(self);
+    }
+
+    /// Advance `n` generations.
+    pub fn step_n(&mut self, n: u64) {
+        for _ in 0..n {
+            step_automaton(self);
+        }
+    }
+
+    /// Stamp a built-in pattern (`automaton::stamp::STAMP_*`) at a position with one
+    /// of 24 rotations. Returns cells set, or None for an unknown pattern/rotation.
+    pub fn stamp(&mut self, pattern_id: u8, at: (i16, i16, i16), rotation: u8) -> Option<u32> {
+        stamp_pattern(self, pattern_id, at.0, at.1, at.2, rotation)
+    }
+
+    /// Number of live cells.
+    pub fn population(&self) -> usize {
+        self.cells.iter().filter(|&&c| c != 0).count()
+    }
+
+    /// Iterate over every cell in z,y,x order.
+    pub fn iter(&self) -> Cells<'_, u8> {
+        Cells::new(&self.cells, self.width, self.height)
+    }
+
+    /// Iterate over the coordinates of live cells.
+    pub fn alive_cells(&self) -> impl Iterator<Item = (i16, i16, i16)> + '_ {
+        self.iter().filter(|&(_, v)| v != 0).map(|(coord, _)| coord)
+    }
+
+    /// Copy the half-open box `[min, max)` into `out` (z,y,x order). Returns bytes written.
+    pub fn extract_region(
+        &self,
+        min: (i16, i16, i16),
+        max: (i16, i16, i16),
+        out: &mut [u8],
+    ) -> u64 {
+        extract_region(self, out, min.0, min.1, min.2, max.0, max.1, max.2)
+    }
+
+    /// Overwrite the half-open box `[min, max)` from `data` (z,y,x order). Returns bytes read.
+    pub fn import_region(
+        &mut self,
+        min: (i16, i16, i16),
+        max: (i16, i16, i16),
+        data: &[u8],
+    ) -> u64 {
+        import_region(self, data, min.0, min.1, min.2, max.0, max.1, max.2)
+    }
+
+    /// Copy out the 16³ mapblock at block coordinates `block` (VoxelManip order).
+    /// Returns how many of its cells lie inside the grid; the rest read as dead.
+    pub fn extract_mapblock(&self, block: (i64, i64, i64), out: &mut [u8; MAPBLOCK_VOLUME]) -> u64 {
+        extract_mapblock(self, [block.0, block.1, block.2], out)
+    }
+
+    /// Overwrite the in-grid part of the 16³ mapblock at `block`. Returns cells written.
+    pub fn import_mapblock(&mut self, block: (i64, i64, i64), data: &[u8; MAPBLOCK_VOLUME]) -> u64 {
+        import_mapblock(self, [block.0, block.1, block.2], data)
+    }
+
+    /// Set every cell of the half-open box `[min, max)` alive or dead. Returns cells written.
+    pub fn fill_region(&mut self, min: (i16, i16, i16), max: (i16, i16, i16), alive: bool) -> u64 {
+        fill_region(
+            self,
+            [min.0, min.1, min.2],
+            [max.0, max.1, max.2],
+            alive as u8,
+        )
+    }
+
+    /// Overwrite `[min, max)` with seeded random soup at `density_ppm` parts per million.
+    /// Returns cells written.
+    pub fn randomize_region(
+        &mut self,
+        min: (i16, i16, i16),
+        max: (i16, i16, i16),
+        density_ppm: u32,
+        seed: u64,
+    ) -> u64 {
+        randomize_region(
+            self,
+            [min.0, min.1, min.2],
+            [max.0, max.1, max.2],
+            density_ppm,
+            seed,
+        )
+    }
+
+    /// Kill every cell (dimensions, rule, and generation are kept).
+    pub fn clear(&mut self) {
+        clear(self);
+    }
+
+    /// Encode as a versioned snapshot (same bytes as `va_serialize`).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![0u8; serialized_size(self)];
+        serialize_state(self, &mut buf).expect("buffer sized by serialized_size");
+        buf
+    }
+
+    /// Decode a snapshot produced by `to_bytes` or `va_serialize`.
+    pub fn from_bytes(data: &[u8]) -> Result<State, SnapshotError> {
+        deserialize_state(data)
+    }
+
+    /// Encode as a run-length encoded snapshot (same bytes as `va_serialize_compressed`).
+    pub fn to_bytes_compressed(&self) -> Vec<u8> {
+        let mut buf = vec![0u8; compressed_size(self)];
+        serialize_state_compressed(self, &mut buf).expect("buffer sized by compressed_size");
+        buf
+    }
+
+    /// Decode a snapshot produced by `to_bytes_compressed` or `va_serialize_compressed`.
+    pub fn from_bytes_compressed(data: &[u8]) -> Result<State, SnapshotError> {
+        deserialize_state_compressed(data)
+    }
+}
+
+impl Field {
+    /// Create a field with every cell at the minimum quantum of 1.
+    pub fn new(width: i16, height: i16, depth: i16, diffusion_rate: u8) -> Self {
+        create_field_1(width, height, depth, diffusion_rate)
+    }
+
+    /// Create a field with every cell at `initial`.
+    pub fn with_initial(
+        width: i16,
+        height: i16,
+        depth: i16,
+        initial: NonZeroU32,
+        diffusion_rate: u8,
+    ) -> Self {
+        create_field(width, height, depth, initial, diffusion_rate)
+    }
+
+    /// Field dimensions as `(width, height, depth)`.
+    pub fn dimensions(&self) -> (i16, i16, i16) {
+        (self.width, self.height, self.depth)
+    }
+
+    /// Whether the coordinates are inside the field.
+    pub fn contains(&self, x: i16, y: i16, z: i16) -> bool {
+        field_in_bounds(self, x, y, z)
+    }
+
+    /// Cell value. Never zero inside bounds (Third Law).
+    pub fn get(&self, x: i16, y: i16, z: i16) -> Result<NonZeroU32, FieldError> {
+        field_get(self, x, y, z)
+    }
+
+    /// Raw cell value, including zero, or None when out of bounds.
+    pub fn get_raw(&self, x: i16, y: i16, z: i16) -> Option<u32> {
+        if field_in_bounds(self, x, y, z) {
+            Some(self.cells[field_index_of(self, x, y, z)])
+        } else {
+            None
+        }
+    }
+
+    /// Set a cell value. Returns `Err(OutOfBounds)` instead of silently ignoring.
+    /// A value of 0 is stored as 1, keeping the Third Law invariant of `get`.
+    pub fn set(&mut self, x: i16, y: i16, z: i16, value: u32) -> Result<(), FieldError> {
+        if !field_in_bounds(self, x, y, z) {
+            return Err(FieldError::OutOfBounds);
+        }
+        field_set(self, x, y, z, value.max(1));
+        Ok(())
+    }
+
+    /// Advance one generation with the sequential algorithm (`va_field_step`'s default).
+    pub fn step(&mut self) {
+        field_step(self);
+    }
+
+    /// Advance one generation with the fused, rotationally symmetric algorithm.
+    pub fn step_fused(&mut self) {
+        field_step_fused(self);
+    }
+
+    /// Record per-axis flows on every subsequent step (see `Field::flow_record`).
+    pub fn set_flow_recording(&mut self, enabled: bool) {
+        self.flow_record = enabled.then(|| self.flow_record.take().unwrap_or_default());
+    }
+
+    /// Flows recorded by the last step: +x, +y, +z face flow per cell.
+    /// None if recording is off or nothing has been recorded yet.
+    pub fn flows(&self) -> Option<&[i32]> {
+        self.flow_record.as_deref().filter(|r| !r.is_empty())
+    }
+
+    /// Total conserved quantity across all cells.
+    pub fn total(&self) -> u64 {
+        self.cells.iter().map(|&v| v as u64).sum()
+    }
+
+    /// Iterate over every cell in z,y,x order.
+    pub fn iter(&self) -> Cells<'_, u32> {
+        Cells::new(&self.cells, self.width, self.height)
+    }
+}
+
+impl StepController {
+    /// Read-only view of the inner field (the committed state between steps).
+    pub fn field(&self) -> &Field {
+        &self.field
+    }
+
+    /// Cell value of the inner field.
+    pub fn get(&self, x: i16, y: i16, z: i16) -> Result<NonZeroU32, FieldError> {
+        field_get(&self.field, x, y, z)
+    }
+
+    /// Set a cell of the inner field, or queue the write until the active step
+    /// commits (see `set_cell`). Returns false if out of bounds.
+    pub fn set(&mut self, x: i16, y: i16, z: i16, value: u32) -> bool {
+        self.set_cell(x, y, z, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_new_and_access() {
+        let mut state = State::new(4, 4, 4);
+        assert_eq!(state.dimensions(), (4, 4, 4));
+        assert_eq!(state.generation(), 0);
+        assert_eq!(state.population(), 0);
+
+        assert!(state.set(1, 2, 3, true));
+        assert!(state.is_alive(1, 2, 3));
+        assert_eq!(state.get(1, 2, 3), Some(1));
+
+        // Out of bounds is reported, not silently dropped
+        assert!(!state.set(4, 0, 0, true));
+        assert_eq!(state.get(-1, 0, 0), None);
+        assert!(!state.is_alive(-1, 0, 0));
+    }
+
+    #[test]
+    fn test_state_try_new_rejects_bad_dimensions() {
+        assert_eq!(
+            State::try_new(0, 4, 4).err(),
+            Some(GridError::NonPositiveDimension)
+        );
+        assert_eq!(
+            State::try_new(4, -1, 4).err(),
+            Some(GridError::NonPositiveDimension)
+        );
+        let huge = State::try_new(i16::MAX, i16::MAX, i16::MAX);
+        assert_eq!(huge.err(), Some(GridError::TooLarge));
+        assert_eq!(State::try_new(3, 4, 5).unwrap().dimensions(), (3, 4, 5));
+    }
+
+    #[test]
+    fn test_state_step_matches_core() {
+        let mut api = State::new(8, 8, 8);
+        for &(x, y, z) in &[(4, 4, 4), (3, 4, 4), (5, 4, 4), (4, 3, 4), (4, 5, 4)] {
+            api.set(x, y, z, true);
+        }
+        let mut core = State::new(8, 8, 8);
+        core.cells = api.cells.clone();
+
+        api.step_n(3);
+        for _ in 0..3 {
+            step_automaton(&mut core);
+        }
+
+        assert_eq!(api.cells, core.cells);
+        assert_eq!(api.generation(), 3);
+    }
+
+    #[test]
+    fn test_state_iterators() {
+        let mut state = State::new(3, 2, 2);
+        state.set(2, 1, 0, true);
+        state.set(0, 0, 1, true);
+
+        assert_eq!(state.iter().len(), 12);
+        let alive: Vec<_> = state.alive_cells().collect();
+        assert_eq!(alive, vec![(2, 1, 0), (0, 0, 1)]);
+
+        // Iterator coordinates round-trip through index_of
+        for ((x, y, z), v) in state.iter() {
+            assert_eq!(state.cells[index_of(&state, x, y, z)], v);
+        }
+    }
+
+    #[test]
+    fn test_state_region_round_trip() {
+        let mut a = State::new(8, 8, 8);
+        a.set(2, 2, 2, true);
+        a.set(3, 3, 3, true);
+
+        let mut buf = vec![0u8; 64];
+        assert_eq!(a.extract_region((0, 0, 0), (4, 4, 4), &mut buf), 64);
+
+        let mut b = State::new(8, 8, 8);
+        assert_eq!(b.import_region((0, 0, 0), (4, 4, 4), &buf), 64);
+        assert!(b.is_alive(2, 2, 2));
+        assert!(b.is_alive(3, 3, 3));
+        assert_eq!(b.population(), 2);
+    }
+
+    #[test]
+    fn test_state_bytes_round_trip() {
+        let mut a = State::new(5, 5, 5);
+        a.set(2, 2, 2, true);
+        a.set_rule(Rule {
+            birth: u32::MAX,
+            survival: 1 << 26,
+        });
+        assert_eq!(a.rule().birth, Rule::MASK);
+
+        let b = State::from_bytes(&a.to_bytes()).unwrap();
+        assert_eq!(b.rule(), a.rule());
+        assert!(b.is_alive(2, 2, 2));
+        assert!(State::from_bytes(&[0u8; 4]).is_err());
+
+        assert_eq!("B4/S4".parse::<Rule>(), Ok(Rule::default()));
+        assert_eq!(Rule::default().to_string(), "B4/S4");
+
+        let c = State::from_bytes_compressed(&a.to_bytes_compressed()).unwrap();
+        assert_eq!(c.cells, a.cells);
+    }
+
+    #[test]
+    fn test_field_api_conserves_mass() {
+        let mut field = Field::new(8, 8, 8, 2);
+        field.set(4, 4, 4, 1_000_000).unwrap();
+        assert_eq!(field.set(8, 0, 0, 5), Err(FieldError::OutOfBounds));
+
+        let before = field.total();
+        field.step();
+        field.step_fused();
+        assert_eq!(field.total(), before);
+        assert_eq!(field.generation, 2);
+
+        let sum: u64 = field.iter().map(|(_, v)| v as u64).sum();
+        assert_eq!(sum, before);
+        assert_eq!(field.get_raw(8, 0, 0), None);
+
+        // Zero would break the never-zero invariant of `get`; it is stored as 1
+        field.set(1, 1, 1, 0).unwrap();
+        assert_eq!(field.get_raw(1, 1, 1), Some(1));
+        assert_eq!(field.get(1, 1, 1).unwrap().get(), 1);
+    }
+
+    #[test]
+    fn test_controller_set_queued_mid_step() {
+        let mut ctrl = StepController::new_1(16, 16, 16, 2, 1);
+        assert!(ctrl.set(8, 8, 8, 5000));
+        assert_eq!(ctrl.get(8, 8, 8).unwrap().get(), 5000);
+
+        ctrl.begin_step().unwrap();
+        assert!(ctrl.set(0, 0, 0, 999));
+        assert!(!ctrl.set(16, 0, 0, 999));
+        assert_eq!(ctrl.get(0, 0, 0).unwrap().get(), 1);
+        while !ctrl.tick(u64::MAX) {}
+
+        assert_eq!(ctrl.get(0, 0, 0).unwrap().get(), 999);
+        assert_eq!(ctrl.field().generation, 1);
+    }
+}
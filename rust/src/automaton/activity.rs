@@ -0,0 +1,172 @@
+//! Quiescent tile skipping for incremental steps.
+//!
+//! A tile whose pairs all have a zero gradient produces no flow at all, in
+//! every rounding mode, so stepping it changes nothing. If that held last
+//! generation for a tile and its 26 neighbors, no cell the tile reads has
+//! changed since (only the flows of those tiles could have changed one), so
+//! the tile is quiet again and the step skips it outright. Tiles next to any
+//! activity are stepped, so a disturbance spreads through an expanding halo
+//! and a field at equilibrium costs next to nothing per generation.
+//!
+//! Whatever else changes cells must say so: `mark_cell` for single cells
+//! (sources, boundary faces, `StepController::set_cell`), `invalidate` for
+//! wholesale changes. Features that change cells in ways the tiles never see
+//! (delta overrides, contracts, phase changes, decay) turn skipping off while
+//! configured (see `StepController::can_skip_quiet`).
+
+use super::kernel::{tile_counts, tile_index, TileCoord};
+
+/// Activity of a tile whose cells may have changed unseen.
+pub const UNKNOWN_ACTIVITY: u32 = u32::MAX;
+
+/// Largest gradient magnitude among the pairs each tile owned when last stepped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileActivity {
+    dims: [i16; 3],
+    /// Tile edge the activity was measured with.
+    tile_size: i16,
+    /// Wrapping axes the activity was measured with.
+    periodic: [bool; 3],
+    /// One entry per tile, in `tile_index` order.
+    max_gradient: Vec<u32>,
+    /// Tiles the last step begun skipped.
+    pub last_skipped: usize,
+}
+
+impl TileActivity {
+    /// Activity of a field of `dims` cells cut into `tile_size`³ tiles,
+    /// nothing known yet.
+    pub fn new(dims: [i16; 3], tile_size: i16, periodic: [bool; 3]) -> Self {
+        let tiles = tile_counts(dims, tile_size);
+        TileActivity {
+            dims,
+            tile_size,
+            periodic,
+            max_gradient: vec![UNKNOWN_ACTIVITY; tiles.iter().product()],
+            last_skipped: 0,
+        }
+    }
+
+    /// Wrapping axes the activity was measured with.
+    pub fn periodic(&self) -> [bool; 3] {
+        self.periodic
+    }
+
+    /// Tile edge the activity was measured with.
+    pub fn tile_size(&self) -> i16 {
+        self.tile_size
+    }
+
+    /// Activity of `tile` (0 = quiet).
+    pub fn max_gradient(&self, tile: TileCoord) -> u32 {
+        self.max_gradient[tile_index(tile, self.tiles())]
+    }
+
+    /// Forget every measurement, so the next step processes every tile.
+    pub fn invalidate(&mut self) {
+        self.max_gradient.fill(UNKNOWN_ACTIVITY);
+    }
+
+    /// Note that the cell at `idx` (z,y,x order) changed outside the tiles'
+    /// flows.
+    pub fn mark_cell(&mut self, idx: usize) {
+        let (w, h) = (self.dims[0] as usize, self.dims[1] as usize);
+        let cell = [idx % w, idx / w % h, idx / (w * h)];
+        let [tx, ty, tz] = cell.map(|c| (c / self.tile_size as usize) as u16);
+        let tile = tile_index(TileCoord { tx, ty, tz }, self.tiles());
+        self.max_gradient[tile] = UNKNOWN_ACTIVITY;
+    }
+
+    /// Note that cells on a face of the field changed: the first (`high` =
+    /// false) or last layer along `axis`.
+    pub fn mark_face(&mut self, axis: usize, high: bool) {
+        let tiles = self.tiles();
+        let layer = if high { tiles[axis] - 1 } else { 0 };
+        for (i, slot) in self.max_gradient.iter_mut().enumerate() {
+            let cell = [
+                i % tiles[0],
+                i / tiles[0] % tiles[1],
+                i / (tiles[0] * tiles[1]),
+            ];
+            if cell[axis] == layer {
+                *slot = UNKNOWN_ACTIVITY;
+            }
+        }
+    }
+
+    /// True if `tile` and every tile around it (wrapping on periodic axes)
+    /// were quiet, so stepping `tile` would change nothing.
+    pub fn is_quiet(&self, tile: TileCoord) -> bool {
+        let tiles = self.tiles();
+        let coords = [tile.tx, tile.ty, tile.tz].map(|c| c as isize);
+        // Neighbor coordinates along an axis (repeats on short periodic axes)
+        let around = |axis: usize| {
+            let count = tiles[axis] as isize;
+            (-1..=1)
+                .map(|d| coords[axis] + d)
+                .filter_map(|c| match c {
+                    c if (0..count).contains(&c) => Some(c as usize),
+                    c if self.periodic[axis] => Some(c.rem_euclid(count) as usize),
+                    _ => None,
+                })
+                .collect::<Vec<usize>>()
+        };
+        let (xs, ys, zs) = (around(0), around(1), around(2));
+        zs.iter().all(|&tz| {
+            ys.iter().all(|&ty| {
+                xs.iter().all(|&tx| {
+                    let near = TileCoord {
+                        tx: tx as u16,
+                        ty: ty as u16,
+                        tz: tz as u16,
+                    };
+                    self.max_gradient(near) == 0
+                })
+            })
+        })
+    }
+
+    /// Replace every tile's activity with a step's measurements, in
+    /// `tile_index` order (0 for tiles it skipped, which stayed quiet).
+    pub fn record(&mut self, gradients: impl IntoIterator<Item = u32>) {
+        for (slot, gradient) in self.max_gradient.iter_mut().zip(gradients) {
+            *slot = gradient;
+        }
+    }
+
+    /// Tiles along x, y, z.
+    fn tiles(&self) -> [usize; 3] {
+        tile_counts(self.dims, self.tile_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quiet_needs_quiet_neighbors() {
+        let tile = |tx, ty, tz| TileCoord { tx, ty, tz };
+        let mut activity = TileActivity::new([64, 16, 16], 16, [false; 3]);
+        assert!(!activity.is_quiet(tile(0, 0, 0)));
+        activity.record(std::iter::repeat(0));
+        assert!(activity.is_quiet(tile(0, 0, 0)));
+
+        activity.mark_cell((3 * 16 + 3) * 64 + 40);
+        assert_eq!(activity.max_gradient(tile(2, 0, 0)), UNKNOWN_ACTIVITY);
+        assert!(activity.is_quiet(tile(0, 0, 0)));
+        assert!(!activity.is_quiet(tile(1, 0, 0)));
+        assert!(!activity.is_quiet(tile(3, 0, 0)));
+        activity.mark_face(0, false);
+        assert!(!activity.is_quiet(tile(0, 0, 0)));
+        assert_eq!(activity.max_gradient(tile(3, 0, 0)), 0);
+
+        // Tile 3 borders tile 0 across a wrapping x axis
+        let mut wrapped = TileActivity::new([64, 16, 16], 16, [true, false, false]);
+        wrapped.record([0, 0, 0, 5]);
+        assert!(!wrapped.is_quiet(tile(0, 0, 0)));
+        assert!(wrapped.is_quiet(tile(1, 0, 0)));
+        wrapped.invalidate();
+        assert!(!wrapped.is_quiet(tile(1, 0, 0)));
+    }
+}
@@ -0,0 +1,424 @@
+//! Two-level adaptive field: coarse blocks where nothing happens, fine cells
+//! where something does.
+//!
+//! The domain is cut into `ADAPTIVE_FACTOR`³ blocks. A coarse block stores only
+//! the total of its cells (16 bytes instead of 256); a refined block stores
+//! every cell. Diffusion runs on the mixed grid with the field formula:
+//!
+//! - fine–fine pairs, inside a refined block or across two of them, exactly as
+//!   in `field_step_fused`;
+//! - coarse–coarse pairs treat each block as one big cell: the face is 16 cells
+//!   wide and the centers 4 apart, so the flow is the fine flow of the totals'
+//!   difference divided by 16;
+//! - where a refined block meets a coarse one, each of the 16 fine cells on the
+//!   face pairs with the coarse block's mean, at half the fine conductance (the
+//!   centers are 2.5 cells apart).
+//!
+//! Every flow is taken from one side and given to the other, so the total is
+//! conserved exactly across level boundaries. Donors are clamped as in the
+//! field (a fine cell to `face_budget` per face, a coarse block to a sixth of
+//! what it holds above 1 per cell, per face), so no cell drops below 1.
+//!
+//! With a threshold set, each step ends by refining coarse blocks whose mean
+//! differs from a neighbor by more than the threshold and coarsening refined
+//! blocks where every difference is at most half of it. Refinement splits the
+//! total evenly, as `field_refine` does, and coarsening sums, so both conserve.
+
+use super::field::{compute_flow, face_budget, pair_key, RoundingMode};
+use super::rng::mix64;
+
+/// Edge of a block in cells.
+pub const ADAPTIVE_FACTOR: i16 = 4;
+
+/// Cells in a block.
+const BLOCK_CELLS: usize = 64;
+
+/// One block of an adaptive field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Block {
+    /// Total of the block's cells, which are not stored.
+    Coarse(u64),
+    /// The block's cells, in z,y,x order.
+    Fine(Box<[u32; BLOCK_CELLS]>),
+}
+
+/// End of a pair: one fine cell (block, offset in block) or a coarse block.
+#[derive(Debug, Clone, Copy)]
+enum Side {
+    Cell(usize, usize),
+    Block(usize),
+}
+
+/// A field of `width` × `height` × `depth` cells stored at two resolutions.
+#[derive(Debug, Clone)]
+pub struct AdaptiveField {
+    pub width: i16,
+    pub height: i16,
+    pub depth: i16,
+    pub diffusion_rate: u8,
+    pub rounding: RoundingMode,
+    pub generation: u64,
+    /// Blocks in z,y,x order.
+    pub blocks: Vec<Block>,
+    /// Refine/coarsen threshold applied after every step, or 0 to leave the
+    /// resolution to `refine`/`coarsen`.
+    pub threshold: u32,
+}
+
+impl AdaptiveField {
+    /// A coarse field of the given size, every cell at 1, or None unless each
+    /// dimension is a positive multiple of `ADAPTIVE_FACTOR`.
+    pub fn new(width: i16, height: i16, depth: i16, diffusion_rate: u8) -> Option<Self> {
+        let fits = |extent: i16| extent > 0 && extent % ADAPTIVE_FACTOR == 0;
+        if !(fits(width) && fits(height) && fits(depth)) {
+            return None;
+        }
+        let [bw, bh, bd] = [width, height, depth].map(|e| (e / ADAPTIVE_FACTOR) as usize);
+        Some(AdaptiveField {
+            width,
+            height,
+            depth,
+            diffusion_rate,
+            rounding: RoundingMode::Stochastic,
+            generation: 0,
+            blocks: vec![Block::Coarse(BLOCK_CELLS as u64); bw * bh * bd],
+            threshold: 0,
+        })
+    }
+
+    /// Blocks along x, y, z.
+    fn block_dims(&self) -> [usize; 3] {
+        [self.width, self.height, self.depth].map(|e| (e / ADAPTIVE_FACTOR) as usize)
+    }
+
+    /// Block index and offset in the block of (x, y, z), or None if out of bounds.
+    fn locate(&self, x: i16, y: i16, z: i16) -> Option<(usize, usize)> {
+        let in_bounds = (0..self.width).contains(&x)
+            && (0..self.height).contains(&y)
+            && (0..self.depth).contains(&z);
+        if !in_bounds {
+            return None;
+        }
+        let [bw, bh, _] = self.block_dims();
+        let f = ADAPTIVE_FACTOR;
+        let block = ((z / f) as usize * bh + (y / f) as usize) * bw + (x / f) as usize;
+        let offset = (((z % f) * f + y % f) * f + x % f) as usize;
+        Some((block, offset))
+    }
+
+    /// Value of (x, y, z), or None if out of bounds. A cell of a coarse block
+    /// reads as the value `refine` would give it.
+    pub fn get(&self, x: i16, y: i16, z: i16) -> Option<u32> {
+        let (block, offset) = self.locate(x, y, z)?;
+        Some(match &self.blocks[block] {
+            Block::Coarse(total) => split_total(*total, block)[offset],
+            Block::Fine(cells) => cells[offset],
+        })
+    }
+
+    /// Set (x, y, z), refining its block first. Returns false (nothing
+    /// changed) if out of bounds or the block cannot be refined.
+    pub fn set(&mut self, x: i16, y: i16, z: i16, value: u32) -> bool {
+        let Some((block, offset)) = self.locate(x, y, z) else {
+            return false;
+        };
+        if !self.refine_block(block) {
+            return false;
+        }
+        match &mut self.blocks[block] {
+            Block::Fine(cells) => cells[offset] = value,
+            Block::Coarse(_) => unreachable!("refined above"),
+        }
+        true
+    }
+
+    /// Sum of every cell (the conserved quantity).
+    pub fn total(&self) -> u64 {
+        self.blocks
+            .iter()
+            .map(|block| match block {
+                Block::Coarse(total) => *total,
+                Block::Fine(cells) => cells.iter().map(|&v| v as u64).sum(),
+            })
+            .sum()
+    }
+
+    /// Number of refined blocks.
+    pub fn refined_blocks(&self) -> usize {
+        self.blocks
+            .iter()
+            .filter(|block| matches!(block, Block::Fine(_)))
+            .count()
+    }
+
+    /// Store the block holding (x, y, z) at full resolution. Returns false if
+    /// out of bounds, or if its total is too large for u32 cells.
+    pub fn refine(&mut self, x: i16, y: i16, z: i16) -> bool {
+        self.locate(x, y, z)
+            .is_some_and(|(block, _)| self.refine_block(block))
+    }
+
+    /// Store the block holding (x, y, z) as its total. Returns false if out of
+    /// bounds.
+    pub fn coarsen(&mut self, x: i16, y: i16, z: i16) -> bool {
+        let Some((block, _)) = self.locate(x, y, z) else {
+            return false;
+        };
+        self.coarsen_block(block);
+        true
+    }
+
+    fn refine_block(&mut self, block: usize) -> bool {
+        if let Block::Coarse(total) = self.blocks[block] {
+            if total / BLOCK_CELLS as u64 >= u32::MAX as u64 {
+                return false;
+            }
+            self.blocks[block] = Block::Fine(Box::new(split_total(total, block)));
+        }
+        true
+    }
+
+    fn coarsen_block(&mut self, block: usize) {
+        if let Block::Fine(cells) = &self.blocks[block] {
+            self.blocks[block] = Block::Coarse(cells.iter().map(|&v| v as u64).sum());
+        }
+    }
+
+    /// Value of `side` in 1/64 units of a cell (a block's total, or a cell
+    /// times 64), so both kinds compare as concentrations.
+    fn concentration(&self, side: Side) -> i64 {
+        match side {
+            Side::Cell(block, offset) => match &self.blocks[block] {
+                Block::Fine(cells) => cells[offset] as i64 * BLOCK_CELLS as i64,
+                Block::Coarse(_) => unreachable!("cell sides are in refined blocks"),
+            },
+            Side::Block(block) => match self.blocks[block] {
+                Block::Coarse(total) => total as i64,
+                Block::Fine(_) => unreachable!("block sides are coarse"),
+            },
+        }
+    }
+
+    /// Visit every pair once as `visit(axis, low, high)`, `high` being the
+    /// +axis side.
+    fn for_each_pair(&self, mut visit: impl FnMut(usize, Side, Side)) {
+        let [bw, bh, bd] = self.block_dims();
+        let f = ADAPTIVE_FACTOR as usize;
+        let local_strides = [1, f, f * f];
+        let block_strides = [1, bw, bw * bh];
+        // Offsets of the face layer of a block normal to `axis` at `layer`
+        let face = |axis: usize, layer: usize| {
+            (0..BLOCK_CELLS).filter(move |&o| o / local_strides[axis] % f == layer)
+        };
+        for bz in 0..bd {
+            for by in 0..bh {
+                for bx in 0..bw {
+                    let block = (bz * bh + by) * bw + bx;
+                    let fine = matches!(self.blocks[block], Block::Fine(_));
+                    for axis in 0..3 {
+                        if fine {
+                            for o in
+                                (0..BLOCK_CELLS).filter(|o| o / local_strides[axis] % f < f - 1)
+                            {
+                                let high = o + local_strides[axis];
+                                visit(axis, Side::Cell(block, o), Side::Cell(block, high));
+                            }
+                        }
+                        if [bx, by, bz][axis] + 1 == [bw, bh, bd][axis] {
+                            continue;
+                        }
+                        let next = block + block_strides[axis];
+                        let next_fine = matches!(self.blocks[next], Block::Fine(_));
+                        match (fine, next_fine) {
+                            (false, false) => visit(axis, Side::Block(block), Side::Block(next)),
+                            (true, true) => {
+                                for (lo, hi) in face(axis, f - 1).zip(face(axis, 0)) {
+                                    visit(axis, Side::Cell(block, lo), Side::Cell(next, hi));
+                                }
+                            }
+                            (true, false) => {
+                                for lo in face(axis, f - 1) {
+                                    visit(axis, Side::Cell(block, lo), Side::Block(next));
+                                }
+                            }
+                            (false, true) => {
+                                for hi in face(axis, 0) {
+                                    visit(axis, Side::Block(block), Side::Cell(next, hi));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Step one generation (see the module docs), then adapt the resolution
+    /// if a threshold is set.
+    pub fn step(&mut self) {
+        // Divisor = 7 * 2^rate * 2^16, as in the field
+        let divisor = (7i64 << self.diffusion_rate as u32) << 16;
+        let conductivity = 65535i64;
+        let id = |side: Side| match side {
+            Side::Cell(block, offset) => block * BLOCK_CELLS + offset,
+            Side::Block(block) => (self.blocks.len() + block) * BLOCK_CELLS,
+        };
+        let mut next = self.blocks.clone();
+        let mut remainder_acc = 0i64;
+        self.for_each_pair(|axis, low, high| {
+            let (a, b) = (self.concentration(low), self.concentration(high));
+            // Gradient in the units the sides exchange, and the extra divisor
+            // for the pair's geometry
+            let (gradient, scale) = match (low, high) {
+                (Side::Cell(..), Side::Cell(..)) => ((a - b) / BLOCK_CELLS as i64, 1),
+                (Side::Block(_), Side::Block(_)) => (a - b, 16),
+                _ => (a - b, 2 * BLOCK_CELLS as i64),
+            };
+            let key = pair_key(self.generation, id(low), axis as u64);
+            let flow = compute_flow(
+                gradient,
+                conductivity,
+                divisor * scale,
+                self.rounding,
+                key,
+                &mut remainder_acc,
+            );
+            let budget = |side: Side, donor: i64| match side {
+                Side::Cell(..) => face_budget((donor / BLOCK_CELLS as i64) as u64) as i64,
+                Side::Block(_) => {
+                    let spare = (donor - BLOCK_CELLS as i64).max(0) / 6;
+                    // A coarse face shared by 16 fine cells splits its budget
+                    match (low, high) {
+                        (Side::Block(_), Side::Block(_)) => spare,
+                        _ => spare / 16,
+                    }
+                }
+            };
+            let flow = flow.clamp(-budget(high, b), budget(low, a));
+            apply(&mut next, low, -flow);
+            apply(&mut next, high, flow);
+        });
+        self.blocks = next;
+        self.generation += 1;
+        if self.threshold > 0 {
+            self.adapt();
+        }
+    }
+
+    /// Refine and coarsen blocks by `threshold` (see the module docs).
+    pub fn adapt(&mut self) {
+        // Largest concentration difference across each block's pairs, in cells
+        let mut activity = vec![0u64; self.blocks.len()];
+        self.for_each_pair(|_, low, high| {
+            let diff = (self.concentration(low) - self.concentration(high)).unsigned_abs()
+                / BLOCK_CELLS as u64;
+            for side in [low, high] {
+                let (Side::Cell(block, _) | Side::Block(block)) = side;
+                activity[block] = activity[block].max(diff);
+            }
+        });
+        let threshold = self.threshold as u64;
+        for (block, &active) in activity.iter().enumerate() {
+            if active > threshold {
+                self.refine_block(block);
+            } else if active <= threshold / 2 {
+                self.coarsen_block(block);
+            }
+        }
+    }
+}
+
+/// Add `amount` to `side` of `blocks`.
+fn apply(blocks: &mut [Block], side: Side, amount: i64) {
+    match side {
+        Side::Cell(block, offset) => {
+            if let Block::Fine(cells) = &mut blocks[block] {
+                cells[offset] = (cells[offset] as i64 + amount) as u32;
+            }
+        }
+        Side::Block(block) => {
+            if let Block::Coarse(total) = &mut blocks[block] {
+                *total = (*total as i64 + amount) as u64;
+            }
+        }
+    }
+}
+
+/// Cells of a block holding `total`: an even split, the leftover units going
+/// one each to cells from a hashed start, as in `field_refine`.
+fn split_total(total: u64, block: usize) -> [u32; BLOCK_CELLS] {
+    let n = BLOCK_CELLS as u64;
+    let (base, extra) = (total / n, total % n);
+    let start = mix64(block as u64) % n;
+    std::array::from_fn(|offset| {
+        let slot = (offset as u64 + n - start) % n;
+        (base + (slot < extra) as u64) as u32
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_set, field_step_fused};
+
+    #[test]
+    fn test_refined_field_steps_like_fused() {
+        let mut adaptive = AdaptiveField::new(8, 8, 12, 2).unwrap();
+        adaptive.rounding = RoundingMode::HalfEven;
+        let mut field = create_field_1(8, 8, 12, 2);
+        field.rounding = RoundingMode::HalfEven;
+        for (i, (x, y, z)) in [(1, 2, 3), (6, 6, 10), (3, 0, 7)].into_iter().enumerate() {
+            let value = 1_000_000 * (i as u32 + 1);
+            assert!(adaptive.set(x, y, z, value));
+            field_set(&mut field, x, y, z, value);
+        }
+        for block in 0..adaptive.blocks.len() {
+            adaptive.refine_block(block);
+        }
+        for _ in 0..5 {
+            adaptive.step();
+            field_step_fused(&mut field);
+        }
+        for z in 0..12 {
+            for y in 0..8 {
+                for x in 0..8 {
+                    let idx = (z as usize * 8 + y as usize) * 8 + x as usize;
+                    assert_eq!(adaptive.get(x, y, z), Some(field.cells[idx]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_adaptive_conserves_across_levels() {
+        assert!(AdaptiveField::new(8, 6, 8, 2).is_none());
+        let mut field = AdaptiveField::new(32, 16, 16, 1).unwrap();
+        field.threshold = 1_000;
+        assert!(field.set(5, 5, 5, 80_000_000));
+        assert!(!field.set(32, 0, 0, 1));
+        let start = field.total();
+        assert_eq!(field.refined_blocks(), 1);
+
+        let mut peak = 0;
+        for _ in 0..60 {
+            field.step();
+            assert_eq!(field.total(), start);
+            peak = peak.max(field.refined_blocks());
+        }
+        // The disturbance spread into neighboring blocks, which refined, but
+        // the far end of the field stayed coarse
+        assert!(peak > 1);
+        assert!(peak < field.blocks.len());
+        assert!(field.get(31, 15, 15).unwrap() >= 1);
+        assert!(field.get(5, 5, 5).unwrap() < 80_000_000);
+
+        // Refine then coarsen is the identity on the total
+        let before = field.blocks[0].clone();
+        assert!(field.refine(0, 0, 0) && field.coarsen(0, 0, 0));
+        let total = |block: &Block| match block {
+            Block::Coarse(total) => *total,
+            Block::Fine(cells) => cells.iter().map(|&v| v as u64).sum(),
+        };
+        assert_eq!(total(&field.blocks[0]), total(&before));
+    }
+}
@@ -0,0 +1,151 @@
+//! Per-cell age: generations a live cell has survived.
+//!
+//! With age tracking enabled, every step sets a newborn cell's age to 0 and
+//! adds 1 (saturating) to every survivor; dead cells read as 0. Renderers use
+//! it to give old structures weathered textures. An optional `max_age` makes
+//! the rule mortal: a cell that has reached it dies on the next step whatever
+//! its neighbors say.
+//!
+//! Cells alive when tracking is enabled start at age 0. Direct writes
+//! (`va_set_cell`, region imports, stamps) do not touch ages, so a cell set
+//! alive between steps is aged from whatever its slot held (0 unless it was
+//! killed the same way).
+
+use super::grid::{in_bounds, index_of};
+use super::region::{clamp_box, for_each_row};
+use crate::state::State;
+
+/// Age per cell and the optional age limit.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CellAge {
+    /// Generations survived per cell, in the cells' z,y,x order.
+    pub ages: Vec<u16>,
+    /// Age at which a live cell dies on the next step (0 = no limit).
+    pub max_age: u16,
+}
+
+impl CellAge {
+    /// Ages for `cells` cells, all 0.
+    pub fn new(cells: usize, max_age: u16) -> Self {
+        CellAge {
+            ages: vec![0; cells],
+            max_age,
+        }
+    }
+
+    /// Kill the cells of `next` (the next generation of `cells`) that reached
+    /// the age limit, then age every cell for the new generation. Ages are
+    /// reset if they no longer match the cell count.
+    pub fn advance(&mut self, cells: &[u8], next: &mut [u8]) {
+        if self.ages.len() != cells.len() {
+            self.ages = vec![0; cells.len()];
+        }
+        for ((age, &alive), next) in self.ages.iter_mut().zip(cells).zip(next) {
+            if alive != 0 && self.max_age != 0 && *age >= self.max_age {
+                *next = 0;
+            }
+            *age = match (alive != 0, *next != 0) {
+                (true, true) => age.saturating_add(1),
+                _ => 0,
+            };
+        }
+    }
+}
+
+/// Age of the cell at (x, y, z): 0 if it is dead, out of bounds, or the grid
+/// does not track ages.
+pub fn cell_age(state: &State, x: i16, y: i16, z: i16) -> u16 {
+    if !in_bounds(state, x, y, z) {
+        return 0;
+    }
+    let idx = index_of(state, x, y, z);
+    match &state.age {
+        Some(age) if state.cells[idx] != 0 => age.ages.get(idx).copied().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Copy the ages of the half-open box `[min, max)`, clamped to the grid, into
+/// `out` in z,y,x order (the layout of `extract_region`). Dead cells read 0.
+/// Returns the number of cells written, or 0 if the box is empty, the grid
+/// does not track ages, or `out` is too small.
+pub fn extract_age_region(state: &State, out: &mut [u16], min: [i16; 3], max: [i16; 3]) -> u64 {
+    let dims = [state.width, state.height, state.depth];
+    let (Some((lo, hi)), Some(age)) = (clamp_box(dims, min, max), &state.age) else {
+        return 0;
+    };
+    let size: usize = (0..3).map(|axis| (hi[axis] - lo[axis]) as usize).product();
+    if out.len() < size || age.ages.len() != state.cells.len() {
+        return 0;
+    }
+    let mut written = 0;
+    for_each_row(dims, lo, hi, |start, len| {
+        let rows = age.ages[start..start + len]
+            .iter()
+            .zip(&state.cells[start..]);
+        for (slot, (&age, &alive)) in out[written..written + len].iter_mut().zip(rows) {
+            *slot = if alive != 0 { age } else { 0 };
+        }
+        written += len;
+    });
+    written as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::grid::create_grid;
+    use crate::automaton::stepping::step_automaton;
+    use crate::state::Rule;
+
+    #[test]
+    fn test_survivors_age_and_die_at_limit() {
+        let mut state = State::default();
+        create_grid(&mut state, 4, 4, 4);
+        // B/S0..26: every live cell survives forever, nothing is born
+        state.rule = Rule {
+            birth: 0,
+            survival: Rule::MASK,
+        };
+        let idx = index_of(&state, 1, 1, 1);
+        state.cells[idx] = 1;
+        state.age = Some(CellAge::new(64, 3));
+
+        step_automaton(&mut state);
+        step_automaton(&mut state);
+        assert_eq!(cell_age(&state, 1, 1, 1), 2);
+        assert_eq!(cell_age(&state, 0, 0, 0), 0);
+        step_automaton(&mut state);
+        assert_eq!(cell_age(&state, 1, 1, 1), 3);
+        step_automaton(&mut state);
+        assert_eq!(state.cells[idx], 0);
+        assert_eq!(cell_age(&state, 1, 1, 1), 0);
+
+        // Without a limit the age saturates instead
+        state.cells[idx] = 1;
+        state.age.as_mut().unwrap().max_age = 0;
+        state.age.as_mut().unwrap().ages[idx] = u16::MAX - 1;
+        step_automaton(&mut state);
+        step_automaton(&mut state);
+        assert_eq!(cell_age(&state, 1, 1, 1), u16::MAX);
+    }
+
+    #[test]
+    fn test_extract_age_region() {
+        let mut state = State::default();
+        create_grid(&mut state, 4, 4, 4);
+        let mut out = [7u16; 8];
+        assert_eq!(extract_age_region(&state, &mut out, [0; 3], [2; 3]), 0);
+
+        state.age = Some(CellAge::new(64, 0));
+        let idx = index_of(&state, 1, 1, 1);
+        state.cells[idx] = 1;
+        state.age.as_mut().unwrap().ages[idx] = 5;
+        // A stale age under a dead cell reads 0
+        state.age.as_mut().unwrap().ages[0] = 9;
+        assert_eq!(extract_age_region(&state, &mut out, [0; 3], [2; 3]), 8);
+        assert_eq!(out, [0, 0, 0, 0, 0, 0, 0, 5]);
+        assert_eq!(extract_age_region(&state, &mut out[..7], [0; 3], [2; 3]), 0);
+        assert_eq!(extract_age_region(&state, &mut out, [3; 3], [9; 3]), 1);
+    }
+}
@@ -0,0 +1,192 @@
+//! Overflow audit for the flow arithmetic.
+//!
+//! The steppers compute `gradient * conductivity * dt / divisor` in plain i64,
+//! which is exact for everyday values but wraps for extreme combinations: a
+//! diffusion_rate above 44 overflows the divisor itself, and in zone-selective
+//! stepping a large cadence (dt) times a full-range gradient exceeds i64. A
+//! wrapped flow silently destroys conservation, so this module redoes the same
+//! arithmetic with checked operations and reports the first offending pair
+//! instead. It is a separate pass so the hot loops stay unchecked.
+
+use super::field::{field_axis_rates, field_index_of, field_step, Field};
+use super::incremental::StepController;
+use crate::automaton::cadence::CadenceNode;
+
+/// Conductivity used by the tiled kernel (`process_tile`), which ignores the
+/// field's own setting.
+pub const KERNEL_CONDUCTIVITY: i64 = 65535;
+
+/// First overflow found by an audit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowOverflow {
+    /// `7 << (diffusion_rate + 16)` does not fit in i64.
+    Divisor { diffusion_rate: u8 },
+    /// `gradient * conductivity * dt` overflows i64 for the pair of cell `a`
+    /// and its +axis neighbor.
+    Pair {
+        a: [i16; 3],
+        axis: u8,
+        value_a: u32,
+        value_b: u32,
+        conductivity: i64,
+        dt: i64,
+    },
+}
+
+/// The flow divisor `7 * 2^shift * 2^16`, or None if it overflows i64.
+pub fn checked_divisor(diffusion_rate: u8) -> Option<i64> {
+    let shift = (diffusion_rate as u32).checked_add(16)?;
+    7i64.checked_mul(1i64.checked_shl(shift)?)
+}
+
+/// Truncated flow `gradient * conductivity * dt / divisor`, or None on overflow.
+pub fn checked_flow(gradient: i64, conductivity: i64, divisor: i64, dt: i64) -> Option<i64> {
+    gradient
+        .checked_mul(conductivity)?
+        .checked_mul(dt)?
+        .checked_div(divisor)
+}
+
+/// Check every adjacent pair of `field` for overflow at the given conductivity
+/// and time step, in the steppers' scan order (x pairs, then y, then z). With
+/// per-axis rates, each axis is checked at its scaled conductivity against the
+/// shared divisor, as the steppers compute it (see `axis_scales`).
+pub fn audit_flows(field: &Field, conductivity: i64, dt: i64) -> Result<(), FlowOverflow> {
+    let rates = field_axis_rates(field);
+    let slowest = rates.into_iter().max().unwrap_or(0);
+    let Some(divisor) = checked_divisor(slowest) else {
+        return Err(FlowOverflow::Divisor {
+            diffusion_rate: slowest,
+        });
+    };
+    // The divisor fits, so every shift is at most 44 and a u16 conductivity
+    // scaled by it stays below 2^60
+    let conductivities = rates.map(|rate| conductivity << (slowest - rate) as u32);
+    // Fast path: the largest possible gradient is safe, so every pair is
+    if conductivities
+        .iter()
+        .all(|&c| checked_flow(u32::MAX as i64, c, divisor, dt).is_some())
+    {
+        return Ok(());
+    }
+
+    for axis in 0..3u8 {
+        let step = match axis {
+            0 => [1, 0, 0],
+            1 => [0, 1, 0],
+            _ => [0, 0, 1],
+        };
+        for z in 0..field.depth - step[2] {
+            for y in 0..field.height - step[1] {
+                for x in 0..field.width - step[0] {
+                    let value_a = field.cells[field_index_of(field, x, y, z)];
+                    let value_b =
+                        field.cells[field_index_of(field, x + step[0], y + step[1], z + step[2])];
+                    let gradient = value_a as i64 - value_b as i64;
+                    let conductivity = conductivities[axis as usize];
+                    if checked_flow(gradient, conductivity, divisor, dt).is_none() {
+                        return Err(FlowOverflow::Pair {
+                            a: [x, y, z],
+                            axis,
+                            value_a,
+                            value_b,
+                            conductivity,
+                            dt,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `field_step` with checked arithmetic: audits first and only steps if no
+/// flow can overflow. On error the field is unchanged. With a conductivity
+/// curve, the audit uses the curve's largest conductivity.
+pub fn field_step_checked(field: &mut Field) -> Result<(), FlowOverflow> {
+    let conductivity = match &field.conductivity_curve {
+        Some(curve) => curve.max(),
+        None => field.conductivity,
+    };
+    audit_flows(field, conductivity as i64, 1)?;
+    field_step(field);
+    Ok(())
+}
+
+/// Audit a StepController's field at the kernel conductivity and the largest
+/// cadence (dt) of any zone in its partition.
+pub fn audit_controller(ctrl: &StepController) -> Result<(), FlowOverflow> {
+    let max_dt = ctrl
+        .cadence_partition
+        .leaves()
+        .into_iter()
+        .filter_map(|leaf| match leaf {
+            CadenceNode::Leaf { cadence, .. } => Some(cadence.get() as i64),
+            CadenceNode::Split { .. } => None,
+        })
+        .max()
+        .unwrap_or(1);
+    audit_flows(&ctrl.field, KERNEL_CONDUCTIVITY, max_dt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::cadence::Cadence;
+    use crate::automaton::field::{create_field_1, field_set};
+
+    #[test]
+    fn test_divisor_limits() {
+        assert_eq!(checked_divisor(0), Some(7 << 16));
+        assert_eq!(checked_divisor(44), Some(7 << 60));
+        assert_eq!(checked_divisor(45), None);
+        assert_eq!(checked_divisor(u8::MAX), None);
+
+        let mut field = create_field_1(2, 2, 2, 50);
+        assert_eq!(
+            field_step_checked(&mut field),
+            Err(FlowOverflow::Divisor { diffusion_rate: 50 })
+        );
+        assert_eq!(field.generation, 0);
+    }
+
+    #[test]
+    fn test_everyday_fields_pass() {
+        let mut field = create_field_1(6, 6, 6, 0);
+        field_set(&mut field, 3, 3, 3, u32::MAX);
+        assert_eq!(field_step_checked(&mut field), Ok(()));
+        assert_eq!(field.generation, 1);
+    }
+
+    #[test]
+    fn test_large_dt_reports_pair() {
+        let mut field = create_field_1(4, 3, 3, 0);
+        field_set(&mut field, 2, 1, 1, u32::MAX);
+        // 2^32 * 2^16 * 2^16 overflows i64
+        let err = audit_flows(&field, KERNEL_CONDUCTIVITY, u16::MAX as i64);
+        assert_eq!(
+            err,
+            Err(FlowOverflow::Pair {
+                a: [1, 1, 1],
+                axis: 0,
+                value_a: 1,
+                value_b: u32::MAX,
+                conductivity: KERNEL_CONDUCTIVITY,
+                dt: u16::MAX as i64,
+            })
+        );
+
+        let mut ctrl = StepController::from_field(field, 1);
+        assert_eq!(audit_controller(&ctrl), Ok(()));
+        ctrl.cadence_partition.root = CadenceNode::Leaf {
+            region: crate::automaton::cadence::Gaaabb::new([0, 0, 0], [4, 3, 3]),
+            cadence: Cadence::new(u16::MAX),
+            accumulator: 0,
+        };
+        assert!(matches!(
+            audit_controller(&ctrl),
+            Err(FlowOverflow::Pair { .. })
+        ));
+    }
+}
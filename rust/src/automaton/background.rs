@@ -0,0 +1,177 @@
+//! Background stepping: a StepController advanced on its own thread.
+//!
+//! The cooperative model (`begin_step` + `tick` from the server loop) keeps
+//! every frame within budget but ties the simulation rate to the caller. An
+//! `AsyncStepper` instead takes the controller over and steps it on a
+//! dedicated worker thread, at most `steps_per_second` generations a second
+//! (0 = as fast as it can). The caller only polls results.
+//!
+//! Results are double-buffered: after each generation the worker copies the
+//! cells into a back buffer and swaps it with the front one under a lock held
+//! only for the swap, so readers always see a complete generation and never
+//! wait for a step. That costs two extra copies of the field. The controller
+//! comes back, with every generation stepped so far, when the stepper stops.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::field::Field;
+use super::incremental::StepController;
+
+/// State shared by the caller and the worker.
+struct Shared {
+    stop: AtomicBool,
+    steps_per_second: AtomicU32,
+    /// Latest completed generation.
+    front: Mutex<Field>,
+}
+
+impl Shared {
+    fn front(&self) -> MutexGuard<'_, Field> {
+        // The lock only guards a swap, which cannot be left half done
+        self.front.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A StepController being stepped on a worker thread.
+pub struct AsyncStepper {
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle<StepController>>,
+}
+
+impl AsyncStepper {
+    /// Start stepping `ctrl` on a new thread. A step already begun on it is
+    /// finished first.
+    pub fn start(ctrl: StepController, steps_per_second: u32) -> Self {
+        let shared = Arc::new(Shared {
+            stop: AtomicBool::new(false),
+            steps_per_second: AtomicU32::new(steps_per_second),
+            front: Mutex::new(ctrl.field.clone()),
+        });
+        let worker_shared = Arc::clone(&shared);
+        let worker = thread::Builder::new()
+            .name("va-async-step".to_string())
+            .spawn(move || run(ctrl, &worker_shared))
+            .ok();
+        AsyncStepper { shared, worker }
+    }
+
+    /// Change the rate limit (0 = unlimited). Takes effect from the next step.
+    pub fn set_rate(&self, steps_per_second: u32) {
+        self.shared
+            .steps_per_second
+            .store(steps_per_second, Ordering::Relaxed);
+        self.wake();
+    }
+
+    /// Run `f` with the latest completed generation.
+    pub fn read<R>(&self, f: impl FnOnce(&Field) -> R) -> R {
+        f(&self.shared.front())
+    }
+
+    /// Generation of the latest completed step.
+    pub fn generation(&self) -> u64 {
+        self.read(|field| field.generation)
+    }
+
+    /// Stop the worker after its current step and take the controller back.
+    /// None if the worker could not be started or panicked.
+    pub fn stop(mut self) -> Option<StepController> {
+        self.join()
+    }
+
+    fn wake(&self) {
+        if let Some(worker) = &self.worker {
+            worker.thread().unpark();
+        }
+    }
+
+    fn join(&mut self) -> Option<StepController> {
+        self.shared.stop.store(true, Ordering::Release);
+        self.wake();
+        self.worker.take()?.join().ok()
+    }
+}
+
+impl Drop for AsyncStepper {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+/// The worker: step, publish, wait for the next slot, until told to stop.
+fn run(mut ctrl: StepController, shared: &Shared) -> StepController {
+    let mut back = ctrl.field.clone();
+    let mut next = Instant::now();
+    while !shared.stop.load(Ordering::Acquire) {
+        let rate = shared.steps_per_second.load(Ordering::Relaxed);
+        if rate != 0 {
+            let now = Instant::now();
+            if now < next {
+                // Woken early by stop or a rate change: check again
+                thread::park_timeout(next - now);
+                continue;
+            }
+            // A slow step does not bank missed slots
+            next = next.max(now - Duration::from_millis(1)) + Duration::from_secs(1) / rate;
+        }
+        ctrl.step_blocking();
+        back.cells.clone_from(&ctrl.field.cells);
+        back.generation = ctrl.field.generation;
+        std::mem::swap(&mut *shared.front(), &mut back);
+    }
+    ctrl
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::field_set;
+
+    fn controller() -> StepController {
+        let mut ctrl = StepController::new_1(20, 20, 20, 2, 1);
+        field_set(&mut ctrl.field, 10, 10, 10, 1_000_000);
+        ctrl
+    }
+
+    fn wait_for(stepper: &AsyncStepper, generation: u64) {
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while stepper.generation() < generation {
+            assert!(Instant::now() < deadline, "worker stalled");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_background_matches_blocking_steps() {
+        let stepper = AsyncStepper::start(controller(), 0);
+        wait_for(&stepper, 3);
+        let snapshot = stepper.read(|field| (field.generation, field.cells.clone()));
+        let mut ctrl = stepper.stop().unwrap();
+        assert!(ctrl.field.generation >= snapshot.0);
+
+        let mut reference = controller();
+        for _ in 0..snapshot.0 {
+            reference.step_blocking();
+        }
+        assert_eq!(snapshot.1, reference.field.cells);
+        // The controller is usable in the cooperative model again
+        let generation = ctrl.field.generation;
+        ctrl.step_blocking();
+        assert_eq!(ctrl.field.generation, generation + 1);
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let stepper = AsyncStepper::start(controller(), 20);
+        wait_for(&stepper, 1);
+        thread::sleep(Duration::from_millis(200));
+        // About 5 steps; generous for a loaded test machine
+        assert!(stepper.generation() <= 8, "{}", stepper.generation());
+        stepper.set_rate(0);
+        wait_for(&stepper, 12);
+        drop(stepper);
+    }
+}
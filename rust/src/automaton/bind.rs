@@ -0,0 +1,129 @@
+//! Two-way coupling between a binary grid and a field of the same size.
+//!
+//! A bound grid and field advance in one combined step: living cells emit
+//! heat (or scent, or pollution) into the field, and the field can in turn
+//! restrict where cells may be born. Each combined step runs, in order:
+//!
+//! 1. The grid steps. If `birth_threshold` is nonzero, a dead cell may only be
+//!    born where the field value is above it (read before this step's
+//!    emission); survival and deaths are unaffected.
+//! 2. Every cell alive in the new generation adds `emit_rate` to its field
+//!    cell (saturating at u32::MAX). Emission is a source: it does not
+//!    conserve the field total.
+//! 3. The field steps, spreading what was emitted.
+
+use super::field::{field_step_selected, Field};
+use super::stepping::step_automaton_gated;
+use crate::state::State;
+
+/// Parameters of a grid-field binding.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GridFieldCoupling {
+    /// Units each live cell adds to the field per step (0 = no emission).
+    pub emit_rate: u32,
+    /// Births need a field value above this (0 = births are not gated).
+    pub birth_threshold: u32,
+}
+
+/// True if `state` and `field` have the same dimensions and can be bound.
+pub fn can_bind(state: &State, field: &Field) -> bool {
+    (state.width, state.height, state.depth) == (field.width, field.height, field.depth)
+}
+
+/// Advance `state` and `field` by one combined step (see the module docs).
+/// Returns false, stepping neither, if their dimensions differ.
+pub fn step_bound(state: &mut State, field: &mut Field, coupling: GridFieldCoupling) -> bool {
+    if !can_bind(state, field) {
+        return false;
+    }
+    let threshold = coupling.birth_threshold;
+    if threshold == 0 {
+        step_automaton_gated(state, |_| true);
+    } else {
+        let cells = &field.cells;
+        step_automaton_gated(state, |idx| cells[idx] > threshold);
+    }
+    if coupling.emit_rate != 0 {
+        for (value, &alive) in field.cells.iter_mut().zip(&state.cells) {
+            if alive != 0 {
+                *value = value.saturating_add(coupling.emit_rate);
+            }
+        }
+    }
+    field_step_selected(field);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_get, field_set};
+    use crate::automaton::grid::{create_grid, index_of};
+    use crate::automaton::stepping::step_automaton;
+
+    fn plus_shape(state: &mut State) {
+        for (x, y) in [(4, 4), (3, 4), (5, 4), (4, 3), (4, 5)] {
+            let idx = index_of(state, x, y, 4);
+            state.cells[idx] = 1;
+        }
+    }
+
+    #[test]
+    fn test_live_cells_emit_into_field() {
+        let mut state = State::default();
+        create_grid(&mut state, 8, 8, 8);
+        plus_shape(&mut state);
+        let mut field = create_field_1(8, 8, 8, 1);
+        let before: u64 = field.cells.iter().map(|&v| v as u64).sum();
+        let coupling = GridFieldCoupling {
+            emit_rate: 1000,
+            birth_threshold: 0,
+        };
+
+        assert!(step_bound(&mut state, &mut field, coupling));
+        let alive = state.cells.iter().filter(|&&c| c != 0).count() as u64;
+        let after: u64 = field.cells.iter().map(|&v| v as u64).sum();
+        assert_eq!(after, before + alive * 1000);
+        assert_eq!((state.generation, field.generation), (1, 1));
+        assert!(field_get(&field, 4, 4, 4).unwrap().get() > 100);
+    }
+
+    #[test]
+    fn test_births_gated_by_field() {
+        let mut start = State::default();
+        create_grid(&mut start, 8, 8, 8);
+        plus_shape(&mut start);
+        let mut field = create_field_1(8, 8, 8, 1);
+        // Only the z = 3 layer is warm enough for births
+        for y in 0..8 {
+            for x in 0..8 {
+                field_set(&mut field, x, y, 3, 500);
+            }
+        }
+        let coupling = GridFieldCoupling {
+            emit_rate: 0,
+            birth_threshold: 100,
+        };
+
+        let mut free = start.clone();
+        step_automaton(&mut free);
+        let mut gated = start.clone();
+        assert!(step_bound(&mut gated, &mut field, coupling));
+        let mut vetoed = 0;
+        for idx in 0..start.cells.len() {
+            let born = start.cells[idx] == 0 && free.cells[idx] != 0;
+            if born && idx / 64 != 3 {
+                assert_eq!(gated.cells[idx], 0);
+                vetoed += 1;
+            } else {
+                assert_eq!(gated.cells[idx], free.cells[idx]);
+            }
+        }
+        assert!(vetoed > 0);
+        assert!(gated.cells[index_of(&gated, 3, 4, 3)] != 0);
+
+        let mut small = create_field_1(4, 4, 4, 1);
+        assert!(!step_bound(&mut gated, &mut small, coupling));
+        assert_eq!(gated.generation, 1);
+    }
+}
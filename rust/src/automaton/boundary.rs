@@ -0,0 +1,205 @@
+//! Boundary conditions on the six outer faces of a field.
+//!
+//! By default every face is a perfect insulator (`Reflective`): no pair
+//! crosses it, and the field conserves its total. The other modes put a ghost
+//! cell beyond each face cell and exchange with it like an ordinary pair,
+//! with the same flow formula, conductivity, axis rate, and rounding:
+//!
+//! - `Fixed(v)`: the ghost always holds `v` ("the sky is always 273K"), so
+//!   mass flows in or out until the face settles at `v`.
+//! - `Open`: the ghost is empty (the quantum of 1) and mass only leaves.
+//!
+//! Fixed and open faces deliberately break conservation: the total changes by
+//! exactly the sum of the boundary flows. They run in phase B, after sources
+//! and before diffusion, reading the values at the start of the step, and
+//! outflows are clamped to the cell's `face_budget` like any other pair, so no
+//! cell drops below 1. Boundary flows are not reported to flow recording.
+//! Faces of a periodic axis (`Field::periodic`) have no boundary: their
+//! conditions are ignored while the axis wraps.
+
+use super::conductivity::pair_conductivity;
+use super::field::{axis_scales, compute_flow, face_budget, field_axis_rates, pair_key, Field};
+use super::kernel::saturate_cell;
+use super::rng::mix64;
+
+/// Faces in the order used by `Field::boundaries` and the FFI.
+pub const FACE_NEG_X: u8 = 0;
+pub const FACE_POS_X: u8 = 1;
+pub const FACE_NEG_Y: u8 = 2;
+pub const FACE_POS_Y: u8 = 3;
+pub const FACE_NEG_Z: u8 = 4;
+pub const FACE_POS_Z: u8 = 5;
+
+/// Condition on one outer face of a field (see the module docs).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Boundary {
+    /// Insulating wall: nothing crosses (original behavior).
+    #[default]
+    Reflective,
+    /// Exchange with a ghost cell held at this value.
+    Fixed(u32),
+    /// Outflow into an empty ghost cell.
+    Open,
+}
+
+impl Boundary {
+    /// Boundary for a C-side mode (0 reflective, 1 fixed at `value`, 2 open),
+    /// or None for an unknown mode. `value` is ignored unless the mode is fixed.
+    pub fn from_mode(mode: u8, value: u32) -> Option<Self> {
+        match mode {
+            0 => Some(Boundary::Reflective),
+            1 => Some(Boundary::Fixed(value)),
+            2 => Some(Boundary::Open),
+            _ => None,
+        }
+    }
+
+    /// Value of the ghost cell, or None for a reflective face.
+    fn ghost(self) -> Option<u32> {
+        match self {
+            Boundary::Reflective => None,
+            Boundary::Fixed(value) => Some(value),
+            Boundary::Open => Some(1),
+        }
+    }
+}
+
+/// Set the condition on `face` (`FACE_NEG_X` ..= `FACE_POS_Z`).
+/// Returns false (field unchanged) for an unknown face.
+pub fn field_set_boundary(field: &mut Field, face: u8, boundary: Boundary) -> bool {
+    match field.boundaries.get_mut(face as usize) {
+        Some(slot) => {
+            *slot = boundary;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Hash key of the boundary pair of `idx` on `face`, distinct from every
+/// interior pair key of the generation.
+#[inline]
+fn boundary_key(generation: u64, idx: usize, face: u8) -> u64 {
+    mix64(pair_key(generation, idx, 3) ^ face as u64)
+}
+
+/// True if `face` exchanges with a ghost cell: not reflective, on an axis
+/// that does not wrap.
+pub fn face_active(field: &Field, face: u8) -> bool {
+    let boundary = field.boundaries[face as usize];
+    boundary != Boundary::Reflective && !field.periodic[face as usize / 2]
+}
+
+/// Phase B: exchange every cell on a fixed or open face with its ghost cell.
+/// Returns the net amount that entered the field (negative if it left).
+pub fn apply_boundaries(field: &mut Field) -> i64 {
+    let active = |face: u8| face_active(field, face);
+    if !(FACE_NEG_X..=FACE_POS_Z).any(active) {
+        return 0;
+    }
+    let dims = [
+        field.width as usize,
+        field.height as usize,
+        field.depth as usize,
+    ];
+    let strides = [1, dims[0], dims[0] * dims[1]];
+    let (scales, divisor) = axis_scales(field_axis_rates(field));
+    let curve = field.conductivity_curve.as_ref();
+    let base_conductivity = field.conductivity as i64;
+
+    let before = &field.cells;
+    let mut after = before.clone();
+    let mut remainder_acc = 0i64;
+    let mut net = 0i64;
+    for face in (FACE_NEG_X..=FACE_POS_Z).filter(|&face| active(face)) {
+        let Some(ghost) = field.boundaries[face as usize].ghost() else {
+            continue;
+        };
+        let axis = face as usize / 2;
+        let layer = if face % 2 == 0 { 0 } else { dims[axis] - 1 };
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        for j in 0..dims[v] {
+            for i in 0..dims[u] {
+                let idx = layer * strides[axis] + i * strides[u] + j * strides[v];
+                let cell = before[idx];
+                let conductivity = pair_conductivity(curve, base_conductivity, cell, ghost);
+                let flow = compute_flow(
+                    cell as i64 - ghost as i64,
+                    conductivity * scales[axis],
+                    divisor,
+                    field.rounding,
+                    boundary_key(field.generation, idx, face),
+                    &mut remainder_acc,
+                )
+                .min(face_budget(cell as u64) as i64);
+                after[idx] = saturate_cell(after[idx] as i64 - flow);
+                net -= flow;
+            }
+        }
+    }
+    field.cells = after;
+    net
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_get, field_set, field_step};
+
+    fn total(field: &Field) -> i64 {
+        field.cells.iter().map(|&v| v as i64).sum()
+    }
+
+    #[test]
+    fn test_fixed_face_pulls_toward_its_value() {
+        let mut field = create_field_1(6, 8, 6, 1);
+        field.cells.fill(1000);
+        assert!(field_set_boundary(
+            &mut field,
+            FACE_POS_Y,
+            Boundary::Fixed(273_000)
+        ));
+        assert!(!field_set_boundary(&mut field, 6, Boundary::Open));
+        let before = total(&field);
+        let entered = apply_boundaries(&mut field);
+        assert_eq!(total(&field), before + entered);
+        assert!(entered > 0);
+        for _ in 0..200 {
+            field_step(&mut field);
+        }
+        // Heated from the top: a monotone profile from the sky downward
+        let column: Vec<u32> = (0..8)
+            .map(|y| field_get(&field, 3, y, 3).unwrap().get())
+            .collect();
+        assert!(column.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(column[7] > 1000 && column[7] < 273_000);
+
+        // Hotter than the sky: the face now cools
+        field.cells.fill(400_000);
+        assert!(apply_boundaries(&mut field) < 0);
+    }
+
+    #[test]
+    fn test_open_faces_drain_without_underflow() {
+        let mut field = create_field_1(5, 5, 5, 0);
+        field_set(&mut field, 2, 2, 2, 10_000_000);
+        field_set(&mut field, 0, 0, 0, 7);
+        for face in FACE_NEG_X..=FACE_POS_Z {
+            field_set_boundary(&mut field, face, Boundary::Open);
+        }
+        let mut previous = total(&field);
+        for _ in 0..300 {
+            field_step(&mut field);
+            assert!(total(&field) <= previous);
+            assert!(field.cells.iter().all(|&v| v >= 1));
+            previous = total(&field);
+        }
+        assert!(previous < 10_000_000 / 100);
+
+        // Reflective everywhere again: the total holds
+        field.boundaries = [Boundary::Reflective; 6];
+        assert_eq!(apply_boundaries(&mut field), 0);
+        field_step(&mut field);
+        assert_eq!(total(&field), previous);
+    }
+}
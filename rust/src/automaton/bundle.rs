@@ -0,0 +1,305 @@
+//! Scenario bundles: a complete simulation setup in one text file.
+//!
+//! A bundle is a configuration blob (see `automaton::config`) split into a
+//! `[grid]` and a `[field]` section, each optional. Besides the settings the
+//! handle's own configuration accepts, a section gives the dimensions and the
+//! initial contents, so a mod can ship a ready-made scenario as a data file:
+//!
+//! ```text
+//! [grid]
+//! size = [32, 16, 32]
+//! rule = "B4/S4"
+//! stamps = [["glider", 4, 4, 20], ["blinker", 16, 8, 16, 3]]
+//! cells = [[1, 1, 1], [2, 1, 1]]
+//!
+//! [field]
+//! # size defaults to the grid's
+//! diffusion_rate = 2
+//! boundaries = ["reflective", "reflective", "open", "open", "reflective", "reflective"]
+//! sources = [[16, 15, 16, 500]]
+//! values = [[0, 0, 0, 300000]]
+//! ```
+//!
+//! `size` is required unless a `[field]` follows a `[grid]`. Stamps are
+//! `[name, x, y, z]` with an optional rotation (see `automaton::stamp`) and
+//! are clipped at the grid's edges; `cells` (live cells) and `values` (field
+//! values) must lie inside. Stamps are applied before cells. Errors name the
+//! line of the bundle text, as configuration errors do.
+
+use super::config::{apply_field_config, apply_state_config, parse_entries, ConfigError, Value};
+use super::field::{create_field_1, field_in_bounds, field_set, Field};
+use super::grid::{create_grid, in_bounds, index_of};
+use super::stamp::{
+    stamp_pattern, STAMP_BLINKER, STAMP_CUBE, STAMP_GLIDER, STAMP_RANDOM_BLOB, STAMP_SHELL,
+    STAMP_SPHERE,
+};
+use crate::state::State;
+
+/// Stamp names usable in `stamps`.
+pub const STAMP_NAMES: [(u8, &str); 6] = [
+    (STAMP_CUBE, "cube"),
+    (STAMP_SPHERE, "sphere"),
+    (STAMP_SHELL, "shell"),
+    (STAMP_BLINKER, "blinker"),
+    (STAMP_GLIDER, "glider"),
+    (STAMP_RANDOM_BLOB, "random_blob"),
+];
+
+/// Diffusion rate of a bundle field that does not set one.
+pub const DEFAULT_DIFFUSION_RATE: u8 = 2;
+
+/// The handles described by a bundle; None for a section it leaves out.
+pub struct Bundle {
+    pub state: Option<State>,
+    pub field: Option<Field>,
+}
+
+/// Keys a section handles itself rather than passing to the handle's
+/// configuration.
+const GRID_KEYS: [&str; 3] = ["size", "stamps", "cells"];
+const FIELD_KEYS: [&str; 2] = ["size", "values"];
+
+/// One section: its header line and the bundle's lines, blank outside it (so
+/// errors keep the bundle's line numbers).
+struct Section<'a> {
+    header: usize,
+    lines: Vec<&'a str>,
+}
+
+/// Build the handles a bundle describes.
+pub fn load_bundle(text: &str) -> Result<Bundle, ConfigError> {
+    let line_count = text.lines().count();
+    let mut grid: Option<Section> = None;
+    let mut field: Option<Section> = None;
+    let mut current: Option<&mut Section> = None;
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        let content = raw.split('#').next().unwrap_or("").trim();
+        let header = content
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+            .filter(|_| !content.contains('='));
+        if let Some(name) = header {
+            let slot = match name.trim() {
+                "grid" => &mut grid,
+                "field" => &mut field,
+                _ => return Err(ConfigError::UnknownKey { line }),
+            };
+            if slot.is_some() {
+                return Err(ConfigError::Invalid { line });
+            }
+            current = Some(slot.insert(Section {
+                header: line,
+                lines: vec![""; line_count],
+            }));
+        } else if let Some(section) = current.as_deref_mut() {
+            section.lines[i] = raw;
+        } else if !content.is_empty() {
+            return Err(ConfigError::UnknownKey { line });
+        }
+    }
+
+    let state = grid.map(load_grid).transpose()?;
+    let dims = state.as_ref().map(|s| [s.width, s.height, s.depth]);
+    let field = field.map(|section| load_field(section, dims)).transpose()?;
+    Ok(Bundle { state, field })
+}
+
+/// Take the entries of `own` keys out of the section, blanking their lines.
+fn take_own(
+    section: &mut Section,
+    own: &[&'static str],
+) -> Result<Vec<(usize, &'static str, Value)>, ConfigError> {
+    let mut taken = Vec::new();
+    for (line, key, value) in parse_entries(&section.lines.join("\n"))? {
+        if let Some(&key) = own.iter().find(|&&k| k == key) {
+            taken.push((line, key, value));
+        }
+    }
+    for &(line, ..) in &taken {
+        section.lines[line - 1] = "";
+    }
+    Ok(taken)
+}
+
+/// The section's `size`, or `default`.
+fn section_size(
+    section: &Section,
+    own: &[(usize, &str, Value)],
+    default: Option<[i16; 3]>,
+) -> Result<[i16; 3], ConfigError> {
+    let Some((line, _, value)) = own.iter().rev().find(|(_, key, _)| *key == "size") else {
+        return default.ok_or(ConfigError::Invalid {
+            line: section.header,
+        });
+    };
+    value
+        .ints()
+        .filter(|size: &[i16; 3]| size.iter().all(|&n| n > 0))
+        .ok_or(ConfigError::Invalid { line: *line })
+}
+
+fn load_grid(mut section: Section) -> Result<State, ConfigError> {
+    let own = take_own(&mut section, &GRID_KEYS)?;
+    let [width, height, depth] = section_size(&section, &own, None)?;
+    let mut state = State::default();
+    create_grid(&mut state, width, height, depth);
+    apply_state_config(&mut state, &section.lines.join("\n"))?;
+
+    let stamps = own.iter().filter(|(_, key, _)| *key == "stamps");
+    let cells = own.iter().filter(|(_, key, _)| *key == "cells");
+    for (line, _, value) in stamps {
+        let invalid = ConfigError::Invalid { line: *line };
+        let Value::List(items) = value else {
+            return Err(invalid);
+        };
+        for item in items {
+            let (id, [x, y, z], rotation) = parse_stamp(item).ok_or(invalid)?;
+            stamp_pattern(&mut state, id, x, y, z, rotation).ok_or(invalid)?;
+        }
+    }
+    for (line, _, value) in cells {
+        let invalid = ConfigError::Invalid { line: *line };
+        for [x, y, z] in value.rows::<i16, 3>().ok_or(invalid)? {
+            if !in_bounds(&state, x, y, z) {
+                return Err(invalid);
+            }
+            let idx = index_of(&state, x, y, z);
+            state.cells[idx] = 1;
+        }
+    }
+    Ok(state)
+}
+
+/// `[name, x, y, z]` or `[name, x, y, z, rotation]`.
+fn parse_stamp(item: &Value) -> Option<(u8, [i16; 3], u8)> {
+    let Value::List(parts) = item else {
+        return None;
+    };
+    let (name, rest) = parts.split_first()?;
+    let name = name.str()?;
+    let &(id, _) = STAMP_NAMES.iter().find(|(_, n)| *n == name)?;
+    let (anchor, rotation) = match rest {
+        [x, y, z] => ([x, y, z], 0),
+        [x, y, z, rotation] => ([x, y, z], rotation.int()?),
+        _ => return None,
+    };
+    let [x, y, z] = anchor.map(Value::int);
+    Some((id, [x?, y?, z?], rotation))
+}
+
+fn load_field(mut section: Section, grid_size: Option<[i16; 3]>) -> Result<Field, ConfigError> {
+    let own = take_own(&mut section, &FIELD_KEYS)?;
+    let [width, height, depth] = section_size(&section, &own, grid_size)?;
+    let mut field = create_field_1(width, height, depth, DEFAULT_DIFFUSION_RATE);
+    apply_field_config(&mut field, &section.lines.join("\n"))?;
+
+    for (line, _, value) in own.iter().filter(|(_, key, _)| *key == "values") {
+        let invalid = ConfigError::Invalid { line: *line };
+        for [x, y, z, v] in value.rows::<i64, 4>().ok_or(invalid)? {
+            let [x, y, z] = [x, y, z].map(|c| i16::try_from(c).unwrap_or(-1));
+            let v = u32::try_from(v).map_err(|_| invalid)?;
+            if !field_in_bounds(&field, x, y, z) {
+                return Err(invalid);
+            }
+            field_set(&mut field, x, y, z, v);
+        }
+    }
+    Ok(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::boundary::Boundary;
+    use crate::automaton::field::field_get;
+    use crate::automaton::rule::parse_rule;
+    use crate::automaton::stamp::pattern_cells;
+
+    const SCENARIO: &str = "\
+# Glider over a warm floor
+[grid]
+size = [16, 12, 16]
+rule = \"B5/S4,5\"
+stamps = [[\"glider\", 4, 4, 10], [\"cube\", 12, 6, 12, 0]]
+cells = [[0, 0, 0]]
+
+[field]
+diffusion_rate = 3
+boundaries = [\"reflective\", \"reflective\", \"fixed:300000\", \"open\", \"reflective\", \"reflective\"]
+sources = [[8, 11, 8, 500]]
+values = [[1, 2, 3, 70000]]
+";
+
+    #[test]
+    fn test_load_complete_scenario() {
+        let Ok(Bundle {
+            state: Some(state),
+            field: Some(field),
+        }) = load_bundle(SCENARIO)
+        else {
+            panic!("scenario rejected");
+        };
+        assert_eq!((state.width, state.height, state.depth), (16, 12, 16));
+        assert_eq!(state.rule, parse_rule("B5/S4,5").unwrap());
+        let glider = pattern_cells(STAMP_GLIDER, 0).unwrap().len();
+        let live = state.cells.iter().filter(|&&c| c != 0).count();
+        assert_eq!(live, glider + 27 + 1);
+
+        assert_eq!((field.width, field.height, field.depth), (16, 12, 16));
+        assert_eq!(field.diffusion_rate, 3);
+        assert_eq!(field.boundaries[2], Boundary::Fixed(300_000));
+        assert_eq!(field.sources.len(), 1);
+        assert_eq!(field_get(&field, 1, 2, 3).unwrap().get(), 70_000);
+    }
+
+    #[test]
+    fn test_errors_name_bundle_lines() {
+        let cases = [
+            ("rule = \"B4/S4\"\n", ConfigError::UnknownKey { line: 1 }),
+            (
+                "[grid]\nrule = \"B4/S4\"\n",
+                ConfigError::Invalid { line: 1 },
+            ),
+            (
+                "[grid]\nsize = [4, 4, 4]\n[grid]\n",
+                ConfigError::Invalid { line: 3 },
+            ),
+            ("\n[world]\n", ConfigError::UnknownKey { line: 2 }),
+            (
+                "[grid]\nsize = [4, 0, 4]\n",
+                ConfigError::Invalid { line: 2 },
+            ),
+            (
+                "[grid]\nsize = [4, 4, 4]\n\nrule = \"B99\"\n",
+                ConfigError::Invalid { line: 4 },
+            ),
+            (
+                "[grid]\nsize = [4, 4, 4]\nstamps = [[\"spaceship\", 1, 1, 1]]\n",
+                ConfigError::Invalid { line: 3 },
+            ),
+            (
+                "[grid]\nsize = [4, 4, 4]\ncells = [[4, 0, 0]]\n",
+                ConfigError::Invalid { line: 3 },
+            ),
+            (
+                "[field]\nsize = [4, 4, 4]\nrule = \"B4/S4\"\n",
+                ConfigError::UnknownKey { line: 3 },
+            ),
+            (
+                "[field]\nsize = [4, 4, 4]\nvalues = [[0, 0, 0, -1]]\n",
+                ConfigError::Invalid { line: 3 },
+            ),
+        ];
+        for (text, error) in cases {
+            assert_eq!(load_bundle(text).err(), Some(error), "{text}");
+        }
+
+        // A lone field needs its own size; either section may be left out
+        let Ok(bundle) = load_bundle("[field]\nsize = [2, 3, 4]\n") else {
+            panic!("field-only bundle rejected");
+        };
+        assert!(bundle.state.is_none());
+        assert_eq!(bundle.field.map(|f| f.cells.len()), Some(24));
+    }
+}
@@ -0,0 +1,268 @@
+//! Non-blocking stepper for the cellular automaton grid.
+//!
+//! The grid counterpart of `incremental`: a generation is computed in 16³
+//! tiles, in Morton order, spread across as many server ticks as the budget
+//! requires. Every cell's next value depends only on the current generation,
+//! which stays untouched in the state until the last tile is done; the new
+//! generation is then committed in one go, through the same birth gate,
+//! protection, ages, write queue, history and cycle tracking as
+//! `step_automaton`, so a sliced step gives exactly its result.
+
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use super::incremental::TickStats;
+use super::kernel::{build_tile_queue, TileCoord, MAPBLOCK_SIZE};
+use super::stepping::{commit_generation, next_cell, tracks_species};
+use crate::state::State;
+
+/// A generation being computed.
+struct CaStep {
+    /// Next generation so far (cells of unfinished tiles are 0).
+    next_cells: Vec<u8>,
+    /// Species ids of the next generation, if the grid tracks species.
+    next_ids: Option<Vec<u8>>,
+    tile_queue: Vec<TileCoord>,
+    /// Index into `tile_queue` of the tile being processed.
+    tile: usize,
+    /// Next row of that tile (see `tile_rows`).
+    row: usize,
+}
+
+/// Steps a grid a budgeted slice at a time.
+pub struct CaStepController {
+    /// The grid, at the last committed generation. Must not change while a
+    /// step is in progress.
+    pub state: State,
+    step: Option<CaStep>,
+}
+
+impl CaStepController {
+    pub fn new(state: State) -> Self {
+        CaStepController { state, step: None }
+    }
+
+    /// Query whether a step is currently in progress.
+    pub fn is_stepping(&self) -> bool {
+        self.step.is_some()
+    }
+
+    /// Begin a new step. Returns false (and does nothing) if one is already in
+    /// progress or the grid is empty.
+    pub fn begin_step(&mut self) -> bool {
+        if self.is_stepping() || self.state.cells.is_empty() {
+            return false;
+        }
+        // In usize: the rounded-up extent can pass i16::MAX
+        let tiles = [self.state.width, self.state.height, self.state.depth]
+            .map(|extent| (extent as usize).div_ceil(MAPBLOCK_SIZE as usize) as u16);
+        let len = self.state.cells.len();
+        self.step = Some(CaStep {
+            next_cells: vec![0; len],
+            next_ids: tracks_species(&self.state).then(|| vec![0; len]),
+            tile_queue: build_tile_queue(tiles[0], tiles[1], tiles[2]),
+            tile: 0,
+            row: 0,
+        });
+        true
+    }
+
+    /// Do bounded work within the given time budget (microseconds), checked
+    /// after every x-row. Returns true if the step completed during this tick.
+    pub fn tick(&mut self, budget_us: u64) -> bool {
+        self.tick_with_stats(budget_us).0
+    }
+
+    /// `tick`, also reporting the time actually consumed and the tiles finished.
+    pub fn tick_with_stats(&mut self, budget_us: u64) -> (bool, TickStats) {
+        let start = Instant::now();
+        let deadline = start + Duration::from_micros(budget_us);
+        let mut tiles = 0u32;
+        let done = self.tick_inner(deadline, &mut tiles);
+        let stats = TickStats {
+            elapsed_ns: start.elapsed().as_nanos().min(u64::MAX as u128) as u64,
+            tiles,
+        };
+        (done, stats)
+    }
+
+    fn tick_inner(&mut self, deadline: Instant, tiles: &mut u32) -> bool {
+        let Some(step) = &mut self.step else {
+            return true;
+        };
+        loop {
+            if step.tile >= step.tile_queue.len() {
+                self.finalize_step();
+                return true;
+            }
+            let tile = step.tile_queue[step.tile];
+            process_rows(&self.state, step, tile, step.row..step.row + 1);
+            step.row += 1;
+            if step.row == tile_rows(&self.state, tile) {
+                step.tile += 1;
+                step.row = 0;
+                *tiles += 1;
+            }
+            if Instant::now() >= deadline {
+                return false; // Budget exhausted, yield to Lua.
+            }
+        }
+    }
+
+    /// Blocking full step (equivalent to begin + tick(MAX) until done).
+    pub fn step_blocking(&mut self) {
+        self.begin_step();
+        while !self.tick(u64::MAX) {}
+    }
+
+    fn finalize_step(&mut self) {
+        if let Some(step) = self.step.take() {
+            commit_generation(&mut self.state, step.next_cells, step.next_ids, |_| true);
+        }
+    }
+}
+
+/// Number of x-rows in `tile` (smaller than 16 × 16 for edge tiles).
+fn tile_rows(state: &State, tile: TileCoord) -> usize {
+    let rows_y = (state.height - tile.ty as i16 * MAPBLOCK_SIZE).min(MAPBLOCK_SIZE);
+    let rows_z = (state.depth - tile.tz as i16 * MAPBLOCK_SIZE).min(MAPBLOCK_SIZE);
+    rows_y as usize * rows_z as usize
+}
+
+/// Compute the x-rows `rows` of `tile`, where row `r` is the row at
+/// y = r % rows_y, z = r / rows_y within the tile.
+fn process_rows(state: &State, step: &mut CaStep, tile: TileCoord, rows: Range<usize>) {
+    let x_start = tile.tx as i16 * MAPBLOCK_SIZE;
+    let y_start = tile.ty as i16 * MAPBLOCK_SIZE;
+    let z_start = tile.tz as i16 * MAPBLOCK_SIZE;
+    let x_end = x_start + MAPBLOCK_SIZE.min(state.width - x_start);
+    let rows_y = (state.height - y_start).min(MAPBLOCK_SIZE) as usize;
+    let (w, h) = (state.width as usize, state.height as usize);
+
+    for row in rows {
+        let y = y_start + (row % rows_y) as i16;
+        let z = z_start + (row / rows_y) as i16;
+        let row_start = (z as usize * h + y as usize) * w;
+        for x in x_start..x_end {
+            let idx = row_start + x as usize;
+            let (cell, id) = next_cell(state, x, y, z);
+            step.next_cells[idx] = cell;
+            if let Some(ids) = &mut step.next_ids {
+                ids[idx] = id;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::grid::create_grid;
+    use crate::automaton::history::History;
+    use crate::automaton::species::{set_cell_species, Species};
+    use crate::automaton::stepping::step_automaton;
+    use crate::automaton::wireworld::{HEAD, TAIL, WIRE};
+    use crate::state::StepMode;
+
+    fn noisy_grid(width: i16, height: i16, depth: i16) -> State {
+        let mut state = State::default();
+        create_grid(&mut state, width, height, depth);
+        let mut seed = 0x9e37_79b9u32;
+        for cell in &mut state.cells {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            *cell = (seed >> 29 == 0) as u8;
+        }
+        state
+    }
+
+    /// Steps `state` both ways, slicing the controller's steps into one row
+    /// per tick, and checks they agree.
+    fn assert_matches_step_automaton(state: State, generations: usize) {
+        let mut reference = state.clone();
+        let mut ctrl = CaStepController::new(state);
+        for _ in 0..generations {
+            step_automaton(&mut reference);
+            assert!(ctrl.begin_step());
+            while !ctrl.tick(0) {}
+            assert_eq!(ctrl.state.cells, reference.cells);
+            assert_eq!(ctrl.state.generation, reference.generation);
+        }
+        assert_eq!(
+            ctrl.state.species.map(|layer| layer.ids),
+            reference.species.map(|layer| layer.ids)
+        );
+    }
+
+    #[test]
+    fn test_sliced_steps_match_step_automaton() {
+        let mut state = noisy_grid(20, 35, 17);
+        state.history = Some(History::new(4, 2));
+        let mut reference = state.clone();
+        assert_matches_step_automaton(state.clone(), 4);
+        // Commits record checkpoints as step_automaton does
+        let mut ctrl = CaStepController::new(state);
+        for _ in 0..4 {
+            ctrl.step_blocking();
+            step_automaton(&mut reference);
+        }
+        let ranges = [&ctrl.state, &reference].map(|s| s.history.as_ref().unwrap().range());
+        assert_eq!(ranges[0], ranges[1]);
+        assert!(ranges[0].is_some());
+    }
+
+    /// Grids needing 256 or more tiles along an axis step every tile, up to
+    /// the largest extent.
+    #[test]
+    fn test_wide_grids() {
+        for width in [4096, 4200, i16::MAX] {
+            let state = noisy_grid(width, 8, 8);
+            let mut reference = state.clone();
+            step_automaton(&mut reference);
+            let mut ctrl = CaStepController::new(state);
+            ctrl.step_blocking();
+            assert!(ctrl.state.cells == reference.cells, "width {width} differs");
+        }
+    }
+
+    #[test]
+    fn test_species_and_wireworld() {
+        let mut state = noisy_grid(18, 18, 18);
+        state.species = Some(Species::new(state.cells.len()));
+        for (i, x) in (0..18).step_by(3).enumerate() {
+            set_cell_species(&mut state, x, 9, 9, 1 + (i % 2) as u8);
+        }
+        assert_matches_step_automaton(state, 3);
+
+        let mut state = State::default();
+        create_grid(&mut state, 20, 4, 4);
+        state.mode = StepMode::WireWorld;
+        for x in 0..20 {
+            state.cells[x as usize] = WIRE;
+        }
+        state.cells[3] = HEAD;
+        state.cells[2] = TAIL;
+        assert_matches_step_automaton(state, 6);
+    }
+
+    #[test]
+    fn test_tick_budget_and_state() {
+        let mut ctrl = CaStepController::new(noisy_grid(40, 20, 20));
+        let before = ctrl.state.cells.clone();
+        assert!(ctrl.begin_step());
+        assert!(!ctrl.begin_step());
+        let (done, stats) = ctrl.tick_with_stats(0);
+        assert!(!done);
+        assert_eq!(stats.tiles, 0); // One row of the first tile
+
+        // The current generation stays readable until the step commits
+        assert_eq!(ctrl.state.cells, before);
+        let (done, stats) = ctrl.tick_with_stats(u64::MAX);
+        assert!(done);
+        assert_eq!(stats.tiles, 12);
+        assert_eq!(ctrl.state.generation, 1);
+        assert!(!ctrl.is_stepping());
+
+        let mut empty = CaStepController::new(State::default());
+        assert!(!empty.begin_step());
+    }
+}
@@ -0,0 +1,385 @@
+//! Chunked sparse storage for huge, mostly uniform `Field`s.
+//!
+//! A field made by `create_field_chunked` keeps no dense `cells`; it cuts its
+//! volume into `CHUNK_EDGE`³ chunks kept in a hash map, and an absent chunk
+//! holds the ambient value in every cell. A dense 1500×1500×500 field needs
+//! 4.5 GB, while the same volume with a few storms in it costs 16 KB per chunk
+//! the storms touch. `field_get`, `field_set`, `field_step_selected` and
+//! `field_extract_region` dispatch here for such a field; the other field
+//! operations (sources, boundaries, phases, advection, the StepController, ...)
+//! need dense cells.
+//!
+//! `chunked_step` runs the fused diffusion pass of `field_step_fused` (same
+//! flow formula, face budgets and pair keys) over the stored chunks and their
+//! -x, -y, -z neighbors only: a pair of two ambient cells has no gradient and so
+//! never flows, whatever the rounding. Chunks that end a step with every cell
+//! back at ambient are dropped. With `RoundingMode::Hash`, the default for
+//! chunked fields, the result is identical to stepping the dense field;
+//! `Stochastic` carries its accumulator in chunk order instead of scan order.
+
+use std::collections::{BTreeSet, HashMap};
+use std::num::NonZeroU32;
+
+use super::field::{
+    compute_flow, create_field_1, face_budget, pair_key, Field, FieldError, RoundingMode,
+};
+use super::region::clamp_box;
+
+/// Edge of a chunk in cells.
+pub const CHUNK_EDGE: usize = 16;
+
+/// Cells in a chunk.
+const CHUNK_CELLS: usize = CHUNK_EDGE * CHUNK_EDGE * CHUNK_EDGE;
+
+/// Chunk coordinates (x, y, z), in chunks.
+pub type ChunkKey = [usize; 3];
+
+/// Sparse cells of a chunked field (see `Field::chunks`).
+#[derive(Debug, Clone)]
+pub struct Chunks {
+    /// Value of every cell outside the stored chunks.
+    pub ambient: u32,
+    /// Stored chunks, cells in z,y,x order. Cells past the far edges of the
+    /// volume stay at ambient.
+    pub map: HashMap<ChunkKey, Box<[u32; CHUNK_CELLS]>>,
+}
+
+/// Create a chunked field with every cell at `ambient` and no chunks stored.
+/// Rounding defaults to `RoundingMode::Hash`, so stepping matches the dense
+/// field cell for cell.
+pub fn create_field_chunked(
+    width: i16,
+    height: i16,
+    depth: i16,
+    ambient: NonZeroU32,
+    diffusion_rate: u8,
+) -> Field {
+    Field {
+        width,
+        height,
+        depth,
+        rounding: RoundingMode::Hash,
+        chunks: Some(Box::new(Chunks {
+            ambient: ambient.get(),
+            map: HashMap::new(),
+        })),
+        ..create_field_1(0, 0, 0, diffusion_rate)
+    }
+}
+
+/// Number of chunks a field stores (0 for a dense field).
+pub fn field_chunk_count(field: &Field) -> usize {
+    field.chunks.as_ref().map_or(0, |chunks| chunks.map.len())
+}
+
+/// Chunk and offset in the chunk of (x, y, z), or None if out of bounds.
+fn locate(dims: [i16; 3], x: i16, y: i16, z: i16) -> Option<(ChunkKey, usize)> {
+    let in_bounds = x >= 0 && x < dims[0] && y >= 0 && y < dims[1] && z >= 0 && z < dims[2];
+    if !in_bounds {
+        return None;
+    }
+    let [x, y, z] = [x, y, z].map(|c| c as usize);
+    let key = [x, y, z].map(|c| c / CHUNK_EDGE);
+    let offset = ((z % CHUNK_EDGE) * CHUNK_EDGE + y % CHUNK_EDGE) * CHUNK_EDGE + x % CHUNK_EDGE;
+    Some((key, offset))
+}
+
+/// Set a cell value, storing its chunk if needed (see `field_set`). A value
+/// of 0 is stored as 1, the minimum quantum; out-of-bounds coordinates are
+/// ignored.
+pub fn chunked_set(chunks: &mut Chunks, dims: [i16; 3], x: i16, y: i16, z: i16, value: u32) {
+    let Some((key, offset)) = locate(dims, x, y, z) else {
+        return;
+    };
+    let value = value.max(1);
+    let ambient = chunks.ambient;
+    if value == ambient && !chunks.map.contains_key(&key) {
+        return;
+    }
+    let chunk = chunks
+        .map
+        .entry(key)
+        .or_insert_with(|| Box::new([ambient; CHUNK_CELLS]));
+    chunk[offset] = value;
+}
+
+/// Get a cell value (see `field_get`).
+pub fn chunked_get(
+    chunks: &Chunks,
+    dims: [i16; 3],
+    x: i16,
+    y: i16,
+    z: i16,
+) -> Result<NonZeroU32, FieldError> {
+    let (key, offset) = locate(dims, x, y, z).ok_or(FieldError::OutOfBounds)?;
+    let value = chunks
+        .map
+        .get(&key)
+        .map_or(chunks.ambient, |chunk| chunk[offset]);
+    NonZeroU32::new(value.max(1)).ok_or(FieldError::OutOfBounds)
+}
+
+/// Sum of every cell (the conserved quantity).
+pub fn chunked_total(chunks: &Chunks, dims: [i16; 3]) -> u64 {
+    let [w, h, d] = dims.map(|e| e.max(0) as u64);
+    let stored_cells: u64 = chunks
+        .map
+        .keys()
+        .map(|&key| chunk_cells(dims, key) as u64)
+        .sum();
+    let stored: u64 = chunks
+        .map
+        .values()
+        .map(|chunk| chunk.iter().map(|&v| v as u64).sum::<u64>())
+        .sum();
+    // Stored chunks hold ambient past the far edges; take those cells back out
+    let padding = chunks.map.len() as u64 * CHUNK_CELLS as u64 - stored_cells;
+    let ambient = chunks.ambient as u64;
+    (w * h * d - stored_cells) * ambient + stored - padding * ambient
+}
+
+/// Copy the half-open box `[min, max)` into `out` (see
+/// `field_extract_region`). Returns the number of cells written, or 0 on
+/// error (empty region after clamping, or `out` too short).
+pub fn chunked_extract_region(
+    chunks: &Chunks,
+    dims: [i16; 3],
+    out: &mut [u32],
+    min: [i16; 3],
+    max: [i16; 3],
+) -> u64 {
+    let Some((lo, hi)) = clamp_box(dims, min, max) else {
+        return 0;
+    };
+    let total: usize = (0..3).map(|axis| (hi[axis] - lo[axis]) as usize).product();
+    if out.len() < total {
+        return 0;
+    }
+    let mut offset = 0;
+    for z in lo[2]..hi[2] {
+        for y in lo[1]..hi[1] {
+            for x in lo[0]..hi[0] {
+                let (key, cell) = locate(dims, x, y, z).expect("clamped to bounds");
+                out[offset] = chunks
+                    .map
+                    .get(&key)
+                    .map_or(chunks.ambient, |chunk| chunk[cell]);
+                offset += 1;
+            }
+        }
+    }
+    offset as u64
+}
+
+/// Cells of chunk `key` inside the volume.
+fn chunk_cells(dims: [i16; 3], key: ChunkKey) -> usize {
+    let extents = dims.map(|e| e as usize);
+    (0..3)
+        .map(|axis| (extents[axis] - key[axis] * CHUNK_EDGE).min(CHUNK_EDGE))
+        .product()
+}
+
+/// Step a chunked field one generation (see the module docs), with its
+/// diffusion rate, conductivity and rounding. No-op on a dense field.
+pub fn chunked_step(field: &mut Field) {
+    let Some(mut chunks) = field.chunks.take() else {
+        return;
+    };
+    step_chunks(field, &mut chunks);
+    field.chunks = Some(chunks);
+    field.generation += 1;
+}
+
+/// One diffusion pass over `chunks`, using the settings of `field`.
+fn step_chunks(field: &Field, chunks: &mut Chunks) {
+    let extents = [field.width, field.height, field.depth].map(|e| e.max(0) as usize);
+    let local_strides = [1, CHUNK_EDGE, CHUNK_EDGE * CHUNK_EDGE];
+    let global_strides = [1, extents[0], extents[0] * extents[1]];
+    // Stored chunks and their -axis neighbors, which own the pairs across
+    // their shared faces
+    let mut active = BTreeSet::new();
+    for &key in chunks.map.keys() {
+        active.insert(key);
+        for axis in 0..3 {
+            if key[axis] > 0 {
+                let mut neighbor = key;
+                neighbor[axis] -= 1;
+                active.insert(neighbor);
+            }
+        }
+    }
+
+    let divisor = (7i64 << field.diffusion_rate as u32) << 16;
+    let conductivity = field.conductivity as i64;
+    let ambient = chunks.ambient;
+    let mut next = chunks.map.clone();
+    let mut remainder_acc = 0i64;
+    for &key in &active {
+        let own = chunks.map.get(&key);
+        let read = |chunk: Option<&Box<[u32; CHUNK_CELLS]>>, offset: usize| {
+            chunk.map_or(ambient, |chunk| chunk[offset])
+        };
+        let mut delta = vec![0i64; CHUNK_CELLS];
+        // Flows into the first layer of the +axis neighbor
+        let mut spill: [Vec<(usize, i64)>; 3] = Default::default();
+        let origin = [0, 1, 2].map(|axis| key[axis] * CHUNK_EDGE);
+        let stop = [0, 1, 2].map(|axis| (extents[axis] - origin[axis]).min(CHUNK_EDGE));
+        for (axis, spill) in spill.iter_mut().enumerate() {
+            let mut neighbor_key = key;
+            neighbor_key[axis] += 1;
+            let neighbor = chunks.map.get(&neighbor_key);
+            for z in 0..stop[2] {
+                for y in 0..stop[1] {
+                    for x in 0..stop[0] {
+                        let local = [x, y, z];
+                        if origin[axis] + local[axis] + 1 >= extents[axis] {
+                            continue;
+                        }
+                        let offset = z * local_strides[2] + y * local_strides[1] + x;
+                        let a = read(own, offset);
+                        let (b, high) = if local[axis] + 1 < CHUNK_EDGE {
+                            (read(own, offset + local_strides[axis]), None)
+                        } else {
+                            let wrapped = offset - (CHUNK_EDGE - 1) * local_strides[axis];
+                            (read(neighbor, wrapped), Some(wrapped))
+                        };
+                        if a == b {
+                            continue;
+                        }
+                        let idx_a: usize = (0..3)
+                            .map(|i| (origin[i] + local[i]) * global_strides[i])
+                            .sum();
+                        let flow = compute_flow(
+                            a as i64 - b as i64,
+                            conductivity,
+                            divisor,
+                            field.rounding,
+                            pair_key(field.generation, idx_a, axis as u64),
+                            &mut remainder_acc,
+                        );
+                        let flow = flow.clamp(
+                            -(face_budget(b as u64) as i64),
+                            face_budget(a as u64) as i64,
+                        );
+                        delta[offset] -= flow;
+                        match high {
+                            None => delta[offset + local_strides[axis]] += flow,
+                            Some(wrapped) => spill.push((wrapped, flow)),
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut apply = |key: ChunkKey, changes: &mut dyn Iterator<Item = (usize, i64)>| {
+            let chunk = next
+                .entry(key)
+                .or_insert_with(|| Box::new([ambient; CHUNK_CELLS]));
+            for (offset, change) in changes {
+                chunk[offset] = (chunk[offset] as i64 + change) as u32;
+            }
+        };
+        if delta.iter().any(|&change| change != 0) {
+            apply(
+                key,
+                &mut delta.iter().copied().enumerate().filter(|&(_, c)| c != 0),
+            );
+        }
+        for (axis, spill) in spill.into_iter().enumerate() {
+            if !spill.is_empty() {
+                let mut neighbor_key = key;
+                neighbor_key[axis] += 1;
+                apply(neighbor_key, &mut spill.into_iter());
+            }
+        }
+    }
+
+    next.retain(|_, chunk| chunk.iter().any(|&v| v != ambient));
+    chunks.map = next;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{field_get, field_set, field_step_fused, field_step_selected};
+    use crate::automaton::region::field_extract_region;
+
+    fn total(field: &Field) -> u64 {
+        let dims = [field.width, field.height, field.depth];
+        chunked_total(field.chunks.as_ref().unwrap(), dims)
+    }
+
+    #[test]
+    fn test_chunked_steps_like_dense() {
+        let mut chunked = create_field_chunked(20, 18, 33, NonZeroU32::MIN, 2);
+        let mut dense = create_field_1(20, 18, 33, 2);
+        dense.rounding = RoundingMode::Hash;
+        // A cell on a chunk corner, one on the far partial edge, one inside
+        for (x, y, z, value) in [
+            (15, 15, 15, 9_000_000),
+            (19, 17, 32, 500_000),
+            (3, 4, 20, 70_000),
+        ] {
+            field_set(&mut chunked, x, y, z, value);
+            field_set(&mut dense, x, y, z, value);
+        }
+        assert!(chunked.cells.is_empty());
+        assert_eq!(
+            total(&chunked),
+            dense.cells.iter().map(|&v| v as u64).sum::<u64>()
+        );
+        for _ in 0..12 {
+            field_step_selected(&mut chunked);
+            field_step_fused(&mut dense);
+        }
+        assert_eq!(chunked.generation, dense.generation);
+        let mut out = vec![0u32; dense.cells.len()];
+        assert_eq!(
+            field_extract_region(&chunked, &mut out, [0, 0, 0], [20, 18, 33]),
+            out.len() as u64
+        );
+        assert_eq!(out, dense.cells);
+        assert_eq!(
+            total(&chunked),
+            dense.cells.iter().map(|&v| v as u64).sum::<u64>()
+        );
+    }
+
+    #[test]
+    fn test_chunked_huge_domain() {
+        let ambient = NonZeroU32::new(300).unwrap();
+        let mut field = create_field_chunked(1500, 1500, 500, ambient, 2);
+        assert_eq!(total(&field), 1500 * 1500 * 500 * 300);
+        assert_eq!(field_get(&field, 1499, 1499, 499).unwrap().get(), 300);
+        assert!(field_get(&field, 1500, 0, 0).is_err());
+
+        // Writing ambient into an absent chunk stores nothing
+        field_set(&mut field, 10, 10, 10, 300);
+        assert_eq!(field_chunk_count(&field), 0);
+        field_set(&mut field, 700, 700, 250, 5_000_000);
+        let before = total(&field);
+        for _ in 0..20 {
+            field_step_selected(&mut field);
+        }
+        assert_eq!(total(&field), before);
+        assert!(field_get(&field, 701, 700, 250).unwrap().get() > 300);
+        assert!(field_chunk_count(&field) <= 27);
+
+        // A chunk back at ambient is dropped by the next step
+        let mut field = create_field_chunked(40, 40, 40, ambient, 2);
+        field_set(&mut field, 5, 5, 5, 999);
+        field_set(&mut field, 5, 5, 5, 300);
+        assert_eq!(field_chunk_count(&field), 1);
+        field_step_selected(&mut field);
+        assert_eq!(field_chunk_count(&field), 0);
+    }
+
+    #[test]
+    fn test_chunked_set_keeps_cells_nonzero() {
+        let mut field = create_field_chunked(32, 32, 32, NonZeroU32::new(50).unwrap(), 2);
+        field_set(&mut field, 3, 3, 3, 0);
+        assert_eq!(field_get(&field, 3, 3, 3).unwrap().get(), 1);
+        // The stored value is the one read back, so the total agrees with it
+        assert_eq!(total(&field), 32 * 32 * 32 * 50 - 49);
+    }
+}
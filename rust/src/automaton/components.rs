@@ -0,0 +1,182 @@
+//! Connected groups of live cells: flood fill from a seed, and labelling of
+//! every component (to notice a structure splitting into pieces).
+//!
+//! Cells are connected when they share a face (6 neighbors), a face or an
+//! edge (18), or any of face, edge and corner (26, the Moore neighborhood the
+//! rules count). Labels are numbered in z,y,x order of each component's first
+//! cell, so the same grid always gets the same labels.
+
+use std::collections::VecDeque;
+
+use super::grid::{in_bounds, index_of};
+use crate::state::State;
+
+/// Which neighbors of a cell count as connected to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Shared faces only (6 neighbors).
+    Face,
+    /// Shared faces or edges (18 neighbors).
+    Edge,
+    /// Shared faces, edges or corners (26 neighbors).
+    Vertex,
+}
+
+impl Connectivity {
+    /// The connectivity with `neighbors` neighbors (6, 18 or 26).
+    pub fn from_neighbors(neighbors: u8) -> Option<Self> {
+        match neighbors {
+            6 => Some(Connectivity::Face),
+            18 => Some(Connectivity::Edge),
+            26 => Some(Connectivity::Vertex),
+            _ => None,
+        }
+    }
+
+    /// Offsets of the connected neighbors.
+    fn offsets(self) -> Vec<[i16; 3]> {
+        let max_axes = match self {
+            Connectivity::Face => 1,
+            Connectivity::Edge => 2,
+            Connectivity::Vertex => 3,
+        };
+        let mut offsets = Vec::new();
+        for dz in -1..=1i16 {
+            for dy in -1..=1i16 {
+                for dx in -1..=1i16 {
+                    let axes = [dx, dy, dz].iter().filter(|&&d| d != 0).count();
+                    if (1..=max_axes).contains(&axes) {
+                        offsets.push([dx, dy, dz]);
+                    }
+                }
+            }
+        }
+        offsets
+    }
+}
+
+/// Coordinates of the cell at `idx`.
+fn position(state: &State, idx: usize) -> [i16; 3] {
+    let (width, height) = (state.width as usize, state.height as usize);
+    [
+        (idx % width) as i16,
+        (idx / width % height) as i16,
+        (idx / (width * height)) as i16,
+    ]
+}
+
+/// Breadth-first walk over the live cells connected to the live cell `start`,
+/// marking each in `seen` and passing its index to `visit`.
+fn walk(
+    state: &State,
+    start: usize,
+    offsets: &[[i16; 3]],
+    seen: &mut [bool],
+    mut visit: impl FnMut(usize),
+) {
+    let mut queue = VecDeque::from([start]);
+    seen[start] = true;
+    while let Some(idx) = queue.pop_front() {
+        visit(idx);
+        let [x, y, z] = position(state, idx);
+        for &[dx, dy, dz] in offsets {
+            let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+            if !in_bounds(state, nx, ny, nz) {
+                continue;
+            }
+            let next = index_of(state, nx, ny, nz);
+            if !seen[next] && state.cells[next] != 0 {
+                seen[next] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+}
+
+/// Coordinates of every live cell connected to `seed`, the seed first and the
+/// rest in breadth-first order. Empty if the seed is dead or outside the grid.
+pub fn flood_fill(state: &State, seed: [i16; 3], connectivity: Connectivity) -> Vec<[i16; 3]> {
+    let [x, y, z] = seed;
+    if !in_bounds(state, x, y, z) || state.cells[index_of(state, x, y, z)] == 0 {
+        return Vec::new();
+    }
+    let mut seen = vec![false; state.cells.len()];
+    let mut cells = Vec::new();
+    walk(
+        state,
+        index_of(state, x, y, z),
+        &connectivity.offsets(),
+        &mut seen,
+        |idx| cells.push(position(state, idx)),
+    );
+    cells
+}
+
+/// Label every cell with its component (0 for dead cells, components from 1),
+/// in the grid's z,y,x order. Returns the labels and the number of
+/// components.
+pub fn label_components(state: &State, connectivity: Connectivity) -> (Vec<u32>, u32) {
+    let offsets = connectivity.offsets();
+    let mut seen = vec![false; state.cells.len()];
+    let mut labels = vec![0; state.cells.len()];
+    let mut components = 0;
+    for start in 0..state.cells.len() {
+        if seen[start] || state.cells[start] == 0 {
+            continue;
+        }
+        components += 1;
+        walk(state, start, &offsets, &mut seen, |idx| {
+            labels[idx] = components
+        });
+    }
+    (labels, components)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::grid::create_grid;
+
+    fn grid_with(cells: &[[i16; 3]]) -> State {
+        let mut state = State::default();
+        create_grid(&mut state, 6, 6, 6);
+        for &[x, y, z] in cells {
+            let idx = index_of(&state, x, y, z);
+            state.cells[idx] = 1;
+        }
+        state
+    }
+
+    #[test]
+    fn test_connectivity_decides_what_touches() {
+        // A face-connected bar, and a cell touching its end only by a corner
+        let state = grid_with(&[[0, 0, 0], [1, 0, 0], [2, 0, 0], [3, 1, 1]]);
+        let face = flood_fill(&state, [0, 0, 0], Connectivity::Face);
+        assert_eq!(face, vec![[0, 0, 0], [1, 0, 0], [2, 0, 0]]);
+        assert_eq!(flood_fill(&state, [0, 0, 0], Connectivity::Edge).len(), 3);
+        assert_eq!(flood_fill(&state, [0, 0, 0], Connectivity::Vertex).len(), 4);
+        assert!(flood_fill(&state, [5, 5, 5], Connectivity::Vertex).is_empty());
+        assert!(flood_fill(&state, [6, 0, 0], Connectivity::Vertex).is_empty());
+        assert_eq!(Connectivity::Face.offsets().len(), 6);
+        assert_eq!(Connectivity::Edge.offsets().len(), 18);
+        assert_eq!(Connectivity::from_neighbors(8), None);
+    }
+
+    #[test]
+    fn test_split_structure_gets_two_labels() {
+        let mut state = grid_with(&[[1, 1, 1], [2, 1, 1], [3, 1, 1], [4, 1, 1]]);
+        let (labels, components) = label_components(&state, Connectivity::Face);
+        assert_eq!(components, 1);
+        assert_eq!(labels.iter().filter(|&&label| label == 1).count(), 4);
+
+        // Break the bar in the middle
+        let idx = index_of(&state, 2, 1, 1);
+        state.cells[idx] = 0;
+        let (labels, components) = label_components(&state, Connectivity::Face);
+        assert_eq!(components, 2);
+        assert_eq!(labels[index_of(&state, 1, 1, 1)], 1);
+        assert_eq!(labels[index_of(&state, 2, 1, 1)], 0);
+        assert_eq!(labels[index_of(&state, 3, 1, 1)], 2);
+        assert_eq!(labels[index_of(&state, 4, 1, 1)], 2);
+    }
+}
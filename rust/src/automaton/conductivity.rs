@@ -0,0 +1,121 @@
+//! Value-dependent conductivity.
+//!
+//! A field's conductivity is normally one constant. A conductivity curve
+//! replaces it with a small piecewise-linear table from cell value to
+//! conductivity, so hot regions can diffuse faster than cold ones (or the
+//! reverse). Each pair is evaluated at the midpoint of its two values, which
+//! keeps the flow antisymmetric (a→b is exactly minus b→a) and therefore
+//! conserved. Everything is integer arithmetic; results are identical on
+//! every platform.
+
+/// Most points a curve may have.
+pub const MAX_CURVE_POINTS: usize = 16;
+
+/// Piecewise-linear map from cell value to conductivity (scaled by 2^16).
+///
+/// Values below the first point use its conductivity; values above the last
+/// point use the last one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConductivityCurve {
+    /// (value, conductivity), strictly ascending by value.
+    points: Vec<(u32, u16)>,
+}
+
+impl ConductivityCurve {
+    /// Build a curve from 1..=16 points strictly ascending by value, or None.
+    pub fn new(points: &[(u32, u16)]) -> Option<Self> {
+        let ascending = points.windows(2).all(|w| w[0].0 < w[1].0);
+        if points.is_empty() || points.len() > MAX_CURVE_POINTS || !ascending {
+            return None;
+        }
+        Some(ConductivityCurve {
+            points: points.to_vec(),
+        })
+    }
+
+    /// The (value, conductivity) points, ascending by value.
+    pub fn points(&self) -> &[(u32, u16)] {
+        &self.points
+    }
+
+    /// Conductivity at `value`, interpolated between the surrounding points.
+    pub fn eval(&self, value: u32) -> u16 {
+        let i = self.points.partition_point(|&(v, _)| v <= value);
+        if i == 0 {
+            return self.points[0].1;
+        }
+        if i == self.points.len() {
+            return self.points[i - 1].1;
+        }
+        let (v0, c0) = self.points[i - 1];
+        let (v1, c1) = self.points[i];
+        let offset = (value - v0) as i64 * (c1 as i64 - c0 as i64) / (v1 - v0) as i64;
+        (c0 as i64 + offset) as u16
+    }
+
+    /// Conductivity for the pair (a, b): the curve at their midpoint.
+    #[inline]
+    pub fn eval_pair(&self, a: u32, b: u32) -> u16 {
+        self.eval(((a as u64 + b as u64) / 2) as u32)
+    }
+
+    /// Largest conductivity anywhere on the curve (for stability audits).
+    pub fn max(&self) -> u16 {
+        self.points.iter().map(|&(_, c)| c).max().unwrap_or(0)
+    }
+}
+
+/// Conductivity for one pair: the curve's if present, else the field constant.
+#[inline]
+pub fn pair_conductivity(curve: Option<&ConductivityCurve>, constant: i64, a: u32, b: u32) -> i64 {
+    match curve {
+        Some(curve) => curve.eval_pair(a, b) as i64,
+        None => constant,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_set, field_step, field_step_fused};
+
+    #[test]
+    fn test_interpolation() {
+        let curve =
+            ConductivityCurve::new(&[(1000, 1000), (2000, 3000), (10_000, 65_535)]).unwrap();
+        assert_eq!(curve.eval(0), 1000);
+        assert_eq!(curve.eval(1000), 1000);
+        assert_eq!(curve.eval(1500), 2000);
+        assert_eq!(curve.eval(2000), 3000);
+        assert_eq!(curve.eval(6000), 34_267);
+        assert_eq!(curve.eval(u32::MAX), 65_535);
+        assert_eq!(curve.eval_pair(1000, 2000), 2000);
+        assert_eq!(curve.eval_pair(u32::MAX, u32::MAX), 65_535);
+        assert_eq!(curve.max(), 65_535);
+
+        assert!(ConductivityCurve::new(&[]).is_none());
+        assert!(ConductivityCurve::new(&[(5, 1), (5, 2)]).is_none());
+        assert!(ConductivityCurve::new(&[(0, 1); MAX_CURVE_POINTS + 1]).is_none());
+    }
+
+    #[test]
+    fn test_hot_region_diffuses_faster() {
+        // Conductive when hot, nearly insulating when cold
+        let curve = ConductivityCurve::new(&[(1000, 500), (100_000, 65_535)]).unwrap();
+        for step in [field_step, field_step_fused] {
+            let mut field = create_field_1(16, 1, 1, 0);
+            field.conductivity_curve = Some(curve.clone());
+            field_set(&mut field, 3, 0, 0, 1_000_000);
+            field_set(&mut field, 12, 0, 0, 2_000);
+            let total = field.total();
+            for _ in 0..10 {
+                step(&mut field);
+            }
+            assert_eq!(field.total(), total);
+            // The hot spot spreads; the cold spot barely moves
+            assert!(field.cells[5] > 1000);
+            assert!(field.cells[12] > 1900);
+            assert!(field.cells[10] < 10);
+        }
+    }
+}
@@ -0,0 +1,530 @@
+//! Text configuration blobs for State and Field handles.
+//!
+//! Mods persist the cells through snapshots, but the tunables (rule, rates,
+//! boundaries, sources, ...) live on the handle and used to be replayed call
+//! by call after a reload. `state_config` / `field_config` write them all as a
+//! small TOML document; `apply_state_config` / `apply_field_config` read one
+//! back:
+//!
+//! ```text
+//! diffusion_rate = 3
+//! axis_rates = [2, 5, 2]
+//! conductivity = 65535
+//! rounding = "hash"
+//! algorithm = "fused"
+//! advection = [0, -8192, 0]
+//! boundaries = ["reflective", "reflective", "reflective", "fixed:273000", "open", "open"]
+//! periodic = [1, 0, 1]
+//! sources = [[1, 2, 3, 500], [4, 0, 0, -20]]
+//! conductivity_curve = [[0, 100], [1000, 65535]]
+//! phase_thresholds = [[273000, 5000]]
+//! zones = [280000, 310000]
+//! ```
+//!
+//! Only this subset of TOML is read: one `key = value` per line, `#` comments,
+//! and integers, strings, and (nested) arrays as values. An empty array turns
+//! an optional setting off (`axis_rates = []` falls back to `diffusion_rate`).
+//! Keys may be left out to keep the handle's current setting, but `sources`
+//! replaces every source when given. The whole blob is checked before anything
+//! is applied: on error the handle is unchanged and the error names the line.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::audit::checked_divisor;
+use super::boundary::Boundary;
+use super::conductivity::ConductivityCurve;
+use super::field::{
+    field_in_bounds, field_index_of, Field, RoundingMode, StepAlgorithm, MAX_ADVECTION,
+};
+use super::phase::{field_set_phase_thresholds, PhaseThreshold};
+use super::rule::{parse_rule, rule_notation};
+use super::stats::field_set_zones;
+use crate::state::State;
+
+/// Why a configuration blob was rejected (the handle is unchanged). Lines
+/// count from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The line is not `key = value` with a readable value.
+    Syntax { line: usize },
+    /// The key is not a setting of this handle type.
+    UnknownKey { line: usize },
+    /// The value has the wrong shape or is out of range.
+    Invalid { line: usize },
+}
+
+impl ConfigError {
+    /// Line of the offending entry.
+    pub fn line(self) -> usize {
+        match self {
+            ConfigError::Syntax { line }
+            | ConfigError::UnknownKey { line }
+            | ConfigError::Invalid { line } => line,
+        }
+    }
+}
+
+/// A parsed value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Value {
+    Int(i64),
+    Str(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    pub(super) fn int<T: TryFrom<i64>>(&self) -> Option<T> {
+        match self {
+            Value::Int(n) => T::try_from(*n).ok(),
+            _ => None,
+        }
+    }
+
+    pub(super) fn str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// A list of exactly `N` integers.
+    pub(super) fn ints<T: TryFrom<i64> + Copy + Default, const N: usize>(&self) -> Option<[T; N]> {
+        let Value::List(items) = self else {
+            return None;
+        };
+        if items.len() != N {
+            return None;
+        }
+        let mut out = [T::default(); N];
+        for (slot, item) in out.iter_mut().zip(items) {
+            *slot = item.int()?;
+        }
+        Some(out)
+    }
+
+    /// A list of rows, each a list of exactly `N` integers.
+    pub(super) fn rows<T: TryFrom<i64> + Copy + Default, const N: usize>(
+        &self,
+    ) -> Option<Vec<[T; N]>> {
+        match self {
+            Value::List(items) => items.iter().map(Value::ints).collect(),
+            _ => None,
+        }
+    }
+}
+
+/// Parse one value from the front of `text`, advancing it.
+fn parse_value(text: &mut &str) -> Option<Value> {
+    *text = text.trim_start();
+    if let Some(rest) = text.strip_prefix('[') {
+        *text = rest;
+        let mut items = Vec::new();
+        loop {
+            *text = text.trim_start();
+            if let Some(rest) = text.strip_prefix(']') {
+                *text = rest;
+                return Some(Value::List(items));
+            }
+            items.push(parse_value(text)?);
+            *text = text.trim_start();
+            // Items are separated by commas; a trailing comma is allowed
+            match text.strip_prefix(',') {
+                Some(rest) => *text = rest,
+                None if text.starts_with(']') => {}
+                None => return None,
+            }
+        }
+    }
+    if let Some(rest) = text.strip_prefix('"') {
+        let end = rest.find('"')?;
+        *text = &rest[end + 1..];
+        return Some(Value::Str(rest[..end].to_string()));
+    }
+    let end = text
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '-' | '+' | '_')))
+        .unwrap_or(text.len());
+    let number = text[..end].replace('_', "").parse().ok()?;
+    *text = &text[end..];
+    Some(Value::Int(number))
+}
+
+/// Parse every `key = value` line of `text` as `(line, key, value)`.
+pub(super) fn parse_entries(text: &str) -> Result<Vec<(usize, &str, Value)>, ConfigError> {
+    let mut entries = Vec::new();
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        // Strings never hold '#', so the first one starts the comment
+        let content = raw.split('#').next().unwrap_or("").trim();
+        if content.is_empty() {
+            continue;
+        }
+        let (key, mut rest) = content
+            .split_once('=')
+            .ok_or(ConfigError::Syntax { line })?;
+        let value = parse_value(&mut rest).ok_or(ConfigError::Syntax { line })?;
+        if !rest.trim().is_empty() {
+            return Err(ConfigError::Syntax { line });
+        }
+        entries.push((line, key.trim(), value));
+    }
+    Ok(entries)
+}
+
+/// Configuration of a State: its rule.
+pub fn state_config(state: &State) -> String {
+    format!("rule = \"{}\"\n", rule_notation(&state.rule))
+}
+
+/// Apply a State configuration (see the module docs).
+pub fn apply_state_config(state: &mut State, text: &str) -> Result<(), ConfigError> {
+    let mut rule = state.rule;
+    for (line, key, value) in parse_entries(text)? {
+        match key {
+            "rule" => {
+                let text = value.str().ok_or(ConfigError::Invalid { line })?;
+                rule = parse_rule(text).map_err(|_| ConfigError::Invalid { line })?;
+            }
+            _ => return Err(ConfigError::UnknownKey { line }),
+        }
+    }
+    state.rule = rule;
+    Ok(())
+}
+
+const ROUNDING_NAMES: [(RoundingMode, &str); 4] = [
+    (RoundingMode::Stochastic, "stochastic"),
+    (RoundingMode::Truncate, "truncate"),
+    (RoundingMode::Hash, "hash"),
+    (RoundingMode::HalfEven, "half_even"),
+];
+
+const ALGORITHM_NAMES: [(StepAlgorithm, &str); 3] = [
+    (StepAlgorithm::Sequential, "sequential"),
+    (StepAlgorithm::Fused, "fused"),
+    (StepAlgorithm::Blocked, "blocked"),
+];
+
+fn boundary_name(boundary: Boundary) -> String {
+    match boundary {
+        Boundary::Reflective => "reflective".to_string(),
+        Boundary::Fixed(value) => format!("fixed:{value}"),
+        Boundary::Open => "open".to_string(),
+    }
+}
+
+fn parse_boundary(name: &str) -> Option<Boundary> {
+    match name {
+        "reflective" => Some(Boundary::Reflective),
+        "open" => Some(Boundary::Open),
+        _ => Some(Boundary::Fixed(name.strip_prefix("fixed:")?.parse().ok()?)),
+    }
+}
+
+/// `[a, b, ...]`
+fn list<T: ToString>(items: impl IntoIterator<Item = T>) -> String {
+    let items: Vec<String> = items.into_iter().map(|item| item.to_string()).collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// Configuration of a Field: every tunable, but not the cells, generation, or
+/// per-cell phases (those belong in a snapshot).
+pub fn field_config(field: &Field) -> String {
+    let mut out = String::new();
+    let rounding = ROUNDING_NAMES
+        .iter()
+        .find(|(mode, _)| *mode == field.rounding)
+        .map_or("stochastic", |(_, name)| name);
+    let algorithm = ALGORITHM_NAMES
+        .iter()
+        .find(|(algo, _)| *algo == field.algorithm)
+        .map_or("sequential", |(_, name)| name);
+    let (w, h) = (field.width as usize, field.height as usize);
+    let sources = field.sources.iter().map(|(&idx, &rate)| {
+        list([
+            (idx % w) as i64,
+            (idx / w % h) as i64,
+            (idx / (w * h)) as i64,
+            rate,
+        ])
+    });
+    let curve = field
+        .conductivity_curve
+        .as_ref()
+        .map_or(&[][..], |c| c.points());
+    let thresholds = field.phases.as_ref().map_or(&[][..], |p| &p.thresholds[..]);
+
+    // Writing to a String cannot fail
+    let _ = writeln!(out, "diffusion_rate = {}", field.diffusion_rate);
+    let _ = writeln!(
+        out,
+        "axis_rates = {}",
+        list(field.axis_rates.iter().flatten())
+    );
+    let _ = writeln!(out, "conductivity = {}", field.conductivity);
+    let _ = writeln!(out, "rounding = \"{rounding}\"");
+    let _ = writeln!(out, "algorithm = \"{algorithm}\"");
+    let _ = writeln!(out, "advection = {}", list(field.advection));
+    let boundaries = field
+        .boundaries
+        .map(|b| format!("\"{}\"", boundary_name(b)));
+    let _ = writeln!(out, "boundaries = {}", list(boundaries));
+    let _ = writeln!(out, "periodic = {}", list(field.periodic.map(u8::from)));
+    let _ = writeln!(out, "sources = {}", list(sources));
+    let curve = curve.iter().map(|&(value, c)| list([value, c as u32]));
+    let _ = writeln!(out, "conductivity_curve = {}", list(curve));
+    let thresholds = thresholds.iter().map(|t| list([t.value, t.latent]));
+    let _ = writeln!(out, "phase_thresholds = {}", list(thresholds));
+    let _ = writeln!(out, "zones = {}", list(field.zones.iter().flatten()));
+    out
+}
+
+/// Apply a Field configuration (see the module docs). Phases are reclassified
+/// only if the thresholds change.
+pub fn apply_field_config(field: &mut Field, text: &str) -> Result<(), ConfigError> {
+    // Settings are staged on a copy without the (large) per-cell state
+    let cells = std::mem::take(&mut field.cells);
+    let flow_record = field.flow_record.take();
+    let phases = field.phases.take();
+    let mut next = field.clone();
+    field.cells = cells;
+    field.flow_record = flow_record;
+    field.phases = phases;
+    let mut thresholds = field.phases.as_ref().map(|p| p.thresholds.clone());
+
+    for (line, key, value) in parse_entries(text)? {
+        let invalid = ConfigError::Invalid { line };
+        match key {
+            "diffusion_rate" => {
+                next.diffusion_rate = value
+                    .int()
+                    .filter(|&rate| checked_divisor(rate).is_some())
+                    .ok_or(invalid)?;
+            }
+            "axis_rates" => {
+                next.axis_rates = match value {
+                    Value::List(ref items) if items.is_empty() => None,
+                    _ => Some(value.ints().ok_or(invalid)?),
+                };
+                let rates = next.axis_rates.unwrap_or_default();
+                if rates.iter().any(|&rate| checked_divisor(rate).is_none()) {
+                    return Err(invalid);
+                }
+            }
+            "conductivity" => next.conductivity = value.int().ok_or(invalid)?,
+            "rounding" => {
+                let name = value.str().ok_or(invalid)?;
+                let (mode, _) = ROUNDING_NAMES
+                    .iter()
+                    .find(|(_, n)| *n == name)
+                    .ok_or(invalid)?;
+                next.rounding = *mode;
+            }
+            "algorithm" => {
+                let name = value.str().ok_or(invalid)?;
+                let (algorithm, _) = ALGORITHM_NAMES
+                    .iter()
+                    .find(|(_, n)| *n == name)
+                    .ok_or(invalid)?;
+                next.algorithm = *algorithm;
+            }
+            "advection" => {
+                next.advection = value.ints().ok_or(invalid)?;
+                let total: u64 = next.advection.iter().map(|b| b.unsigned_abs() as u64).sum();
+                if total > MAX_ADVECTION as u64 {
+                    return Err(invalid);
+                }
+            }
+            "boundaries" => {
+                let Value::List(items) = &value else {
+                    return Err(invalid);
+                };
+                if items.len() != 6 {
+                    return Err(invalid);
+                }
+                for (slot, item) in next.boundaries.iter_mut().zip(items) {
+                    *slot = item.str().and_then(parse_boundary).ok_or(invalid)?;
+                }
+            }
+            "periodic" => {
+                let flags: [u8; 3] = value.ints().ok_or(invalid)?;
+                if flags.iter().any(|&flag| flag > 1) {
+                    return Err(invalid);
+                }
+                next.periodic = flags.map(|flag| flag == 1);
+            }
+            "sources" => {
+                next.sources = BTreeMap::new();
+                for [x, y, z, rate] in value.rows::<i64, 4>().ok_or(invalid)? {
+                    let coords = [x, y, z].map(|c| i16::try_from(c).unwrap_or(-1));
+                    let [x, y, z] = coords;
+                    if !field_in_bounds(field, x, y, z) {
+                        return Err(invalid);
+                    }
+                    if rate != 0 {
+                        next.sources.insert(field_index_of(field, x, y, z), rate);
+                    }
+                }
+            }
+            "conductivity_curve" => {
+                let rows = value.rows::<i64, 2>().ok_or(invalid)?;
+                let points = rows
+                    .iter()
+                    .map(|&[v, c]| Some((u32::try_from(v).ok()?, u16::try_from(c).ok()?)))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(invalid)?;
+                next.conductivity_curve = if points.is_empty() {
+                    None
+                } else {
+                    Some(ConductivityCurve::new(&points).ok_or(invalid)?)
+                };
+            }
+            "phase_thresholds" => {
+                let rows = value.rows::<u32, 2>().ok_or(invalid)?;
+                let list: Vec<PhaseThreshold> = rows
+                    .iter()
+                    .map(|&[value, latent]| PhaseThreshold { value, latent })
+                    .collect();
+                let valid = list.len() <= u8::MAX as usize
+                    && list.windows(2).all(|w| w[0].value < w[1].value);
+                if !valid {
+                    return Err(invalid);
+                }
+                thresholds = (!list.is_empty()).then_some(list);
+            }
+            "zones" => {
+                let Value::List(items) = &value else {
+                    return Err(invalid);
+                };
+                let breakpoints = items
+                    .iter()
+                    .map(Value::int)
+                    .collect::<Option<Vec<u32>>>()
+                    .ok_or(invalid)?;
+                if !field_set_zones(&mut next, &breakpoints) {
+                    return Err(invalid);
+                }
+            }
+            _ => return Err(ConfigError::UnknownKey { line }),
+        }
+    }
+
+    // Everything checked: apply
+    let current = field.phases.as_ref().map(|p| &p.thresholds);
+    let reclassify = current != thresholds.as_ref();
+    next.cells = std::mem::take(&mut field.cells);
+    next.flow_record = field.flow_record.take();
+    next.phases = field.phases.take();
+    *field = next;
+    if reclassify {
+        field_set_phase_thresholds(field, thresholds.as_deref().unwrap_or(&[]));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::boundary::field_set_boundary;
+    use crate::automaton::field::{create_field_1, field_set, field_set_source};
+    use crate::state::Rule;
+
+    #[test]
+    fn test_field_config_round_trip() {
+        let mut field = create_field_1(6, 5, 4, 3);
+        field.axis_rates = Some([2, 5, 2]);
+        field.rounding = RoundingMode::HalfEven;
+        field.algorithm = StepAlgorithm::Fused;
+        field.advection = [0, -8192, 10];
+        field.conductivity = 40_000;
+        field_set_boundary(&mut field, 3, Boundary::Fixed(273_000));
+        field_set_boundary(&mut field, 4, Boundary::Open);
+        field.periodic = [true, false, true];
+        field.zones = Some(vec![280_000, 310_000]);
+        field_set_source(&mut field, 1, 2, 3, 500);
+        field_set_source(&mut field, 5, 0, 0, -20);
+        field.conductivity_curve = ConductivityCurve::new(&[(0, 100), (1000, 65535)]);
+        field_set(&mut field, 0, 0, 0, 300_000);
+        field_set_phase_thresholds(
+            &mut field,
+            &[PhaseThreshold {
+                value: 273_000,
+                latent: 5000,
+            }],
+        );
+        let text = field_config(&field);
+
+        let mut restored = create_field_1(6, 5, 4, 1);
+        field_set(&mut restored, 0, 0, 0, 300_000);
+        assert_eq!(apply_field_config(&mut restored, &text), Ok(()));
+        assert_eq!(field_config(&restored), text);
+        assert_eq!(restored.sources, field.sources);
+        assert_eq!(restored.phases, field.phases);
+        assert_eq!(restored.boundaries, field.boundaries);
+        assert_eq!(restored.periodic, [true, false, true]);
+        assert_eq!(restored.zones, field.zones);
+        assert_eq!(restored.algorithm, StepAlgorithm::Fused);
+
+        // Partial blobs keep the other settings; empty lists switch options off
+        let partial =
+            "# tweak\nrounding = \"hash\"  # order-independent\naxis_rates = []\nzones = []\n";
+        assert_eq!(apply_field_config(&mut restored, partial), Ok(()));
+        assert_eq!(restored.rounding, RoundingMode::Hash);
+        assert_eq!(restored.axis_rates, None);
+        assert_eq!(restored.zones, None);
+        assert_eq!(restored.advection, [0, -8192, 10]);
+        assert_eq!(restored.cells[0], 300_000);
+    }
+
+    #[test]
+    fn test_rejected_config_leaves_handle_unchanged() {
+        let mut field = create_field_1(4, 4, 4, 2);
+        let before = field_config(&field);
+        let cases = [
+            (
+                "diffusion_rate = 1\nrounding = \"nearest\"\n",
+                ConfigError::Invalid { line: 2 },
+            ),
+            ("conductivity = 70000\n", ConfigError::Invalid { line: 1 }),
+            (
+                "advection = [40000, 40000, 0]\n",
+                ConfigError::Invalid { line: 1 },
+            ),
+            (
+                "sources = [[4, 0, 0, 5]]\n",
+                ConfigError::Invalid { line: 1 },
+            ),
+            (
+                "boundaries = [\"open\"]\n",
+                ConfigError::Invalid { line: 1 },
+            ),
+            ("zones = [300, 100]\n", ConfigError::Invalid { line: 1 }),
+            (
+                "\n\nrule = \"B4/S4\"\n",
+                ConfigError::UnknownKey { line: 3 },
+            ),
+            ("diffusion_rate = [1, 2\n", ConfigError::Syntax { line: 1 }),
+            ("diffusion_rate\n", ConfigError::Syntax { line: 1 }),
+        ];
+        for (text, error) in cases {
+            assert_eq!(apply_field_config(&mut field, text), Err(error), "{text}");
+            assert_eq!(field_config(&field), before);
+        }
+
+        let mut state = State::default();
+        let rule = Rule {
+            birth: 1 << 5,
+            survival: 0b1100,
+        };
+        state.rule = rule;
+        let text = state_config(&state);
+        state.rule = Rule::default();
+        assert_eq!(apply_state_config(&mut state, &text), Ok(()));
+        assert_eq!(state.rule, rule);
+        assert_eq!(
+            apply_state_config(&mut state, "rule = \"B99\"\n"),
+            Err(ConfigError::Invalid { line: 1 })
+        );
+        assert_eq!(state.rule, rule);
+    }
+}
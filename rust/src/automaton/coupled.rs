@@ -0,0 +1,208 @@
+//! Several fields stepped in lockstep with linear cross-terms.
+//!
+//! Unlike a FieldStack, whose layers diffuse independently, coupled fields
+//! influence each other: after every field has diffused one generation, each
+//! cell of field `i` gains `sum_j coefficients[i][j] * value_j`, read from the
+//! post-diffusion values of every field (so the result does not depend on
+//! registration order). Coefficients are signed and scaled by 2^16, e.g.
+//! `coefficients[temperature][pollution] = 655` warms a cell by about 1% of its
+//! pollution per generation. Diagonal entries act on the field itself
+//! (growth or decay).
+//!
+//! Cross-terms are sources, not flows: they do not conserve mass. Results are
+//! floored toward negative infinity and clamped to `1..=u32::MAX`.
+
+use super::field::{field_step_selected, Field};
+
+/// Fields a coupling holds at most.
+pub const MAX_COUPLED_FIELDS: usize = 8;
+
+/// Why a field could not be registered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoupleError {
+    /// Already `MAX_COUPLED_FIELDS` fields.
+    Full,
+    /// Dimensions differ from the fields already registered.
+    DimensionMismatch,
+}
+
+/// Fields advanced together, plus their cross-term matrix.
+#[derive(Default)]
+pub struct CoupledFields {
+    /// Boxed so a field keeps its address after registration (FFI handles).
+    pub fields: Vec<Box<Field>>,
+    /// Row-major `n x n` matrix, `coefficients[target * n + source]`, scaled by 2^16.
+    pub coefficients: Vec<i32>,
+    /// Lockstep generations completed.
+    pub generation: u64,
+}
+
+impl CoupledFields {
+    /// Check whether `field` could be registered, without taking it.
+    pub fn check_register(&self, field: &Field) -> Result<(), CoupleError> {
+        if self.fields.len() >= MAX_COUPLED_FIELDS {
+            return Err(CoupleError::Full);
+        }
+        match self.fields.first() {
+            Some(first)
+                if (first.width, first.height, first.depth)
+                    != (field.width, field.height, field.depth) =>
+            {
+                Err(CoupleError::DimensionMismatch)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Register a field, returning its index. The matrix grows with a zero
+    /// row and column, so existing coefficients are kept.
+    pub fn register(&mut self, field: Box<Field>) -> Result<usize, CoupleError> {
+        self.check_register(&field)?;
+        let n = self.fields.len();
+        let mut coefficients = vec![0; (n + 1) * (n + 1)];
+        for target in 0..n {
+            coefficients[target * (n + 1)..target * (n + 1) + n]
+                .copy_from_slice(&self.coefficients[target * n..(target + 1) * n]);
+        }
+        self.coefficients = coefficients;
+        self.fields.push(field);
+        Ok(n)
+    }
+
+    /// Set one coefficient. Returns false if either index is out of range.
+    pub fn set_coefficient(&mut self, target: usize, source: usize, coefficient: i32) -> bool {
+        let n = self.fields.len();
+        if target >= n || source >= n {
+            return false;
+        }
+        self.coefficients[target * n + source] = coefficient;
+        true
+    }
+
+    /// Replace the whole matrix. Returns false unless it has exactly `n * n` entries.
+    pub fn set_matrix(&mut self, coefficients: &[i32]) -> bool {
+        if coefficients.len() != self.coefficients.len() {
+            return false;
+        }
+        self.coefficients.copy_from_slice(coefficients);
+        true
+    }
+
+    /// Step every field one generation (with its selected algorithm, see
+    /// `field_step_selected`), then apply the cross-terms.
+    pub fn step(&mut self) {
+        for field in &mut self.fields {
+            field_step_selected(field);
+        }
+        self.apply_cross_terms();
+        self.generation += 1;
+    }
+
+    fn apply_cross_terms(&mut self) {
+        let n = self.fields.len();
+        let active: Vec<usize> = (0..n)
+            .filter(|&target| {
+                self.coefficients[target * n..(target + 1) * n]
+                    .iter()
+                    .any(|&c| c != 0)
+            })
+            .collect();
+        if active.is_empty() {
+            return;
+        }
+
+        let cells = self.fields[0].cells.len();
+        let mut gains = vec![0i64; active.len() * cells];
+        for (row, &target) in active.iter().enumerate() {
+            let gain = &mut gains[row * cells..(row + 1) * cells];
+            for (source, field) in self.fields.iter().enumerate() {
+                let coefficient = self.coefficients[target * n + source] as i64;
+                if coefficient == 0 {
+                    continue;
+                }
+                for (g, &value) in gain.iter_mut().zip(&field.cells) {
+                    *g += coefficient * value as i64;
+                }
+            }
+        }
+        for (row, &target) in active.iter().enumerate() {
+            let gain = &gains[row * cells..(row + 1) * cells];
+            for (value, &g) in self.fields[target].cells.iter_mut().zip(gain) {
+                *value = (*value as i64 + (g >> 16)).clamp(1, u32::MAX as i64) as u32;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_get, field_set, field_step};
+
+    #[test]
+    fn test_pollution_warms_temperature() {
+        let mut coupled = CoupledFields::default();
+        let mut temperature = Box::new(create_field_1(6, 6, 6, 2));
+        let mut pollution = Box::new(create_field_1(6, 6, 6, 2));
+        temperature.cells.fill(1000);
+        pollution.cells.fill(200);
+        field_set(&mut pollution, 3, 3, 3, 200_000);
+        assert_eq!(coupled.register(temperature), Ok(0));
+        assert_eq!(coupled.register(pollution), Ok(1));
+        // Temperature gains half the pollution; pollution decays by 1/4
+        assert!(coupled.set_coefficient(0, 1, 1 << 15));
+        assert!(coupled.set_coefficient(1, 1, -(1 << 14)));
+        assert!(!coupled.set_coefficient(2, 0, 1));
+
+        coupled.step();
+        assert_eq!(coupled.generation, 1);
+        let (t, p) = (&coupled.fields[0], &coupled.fields[1]);
+        assert_eq!((t.generation, p.generation), (1, 1));
+        assert_eq!(field_get(t, 0, 0, 0).unwrap().get(), 1100);
+        assert_eq!(field_get(p, 0, 0, 0).unwrap().get(), 150);
+        assert!(field_get(t, 3, 3, 3).unwrap().get() > 10_000);
+
+        // Uncoupled fields step exactly as on their own
+        let mut alone = create_field_1(6, 6, 6, 2);
+        field_set(&mut alone, 1, 2, 3, 50_000);
+        *coupled.fields[0] = alone.clone();
+        assert!(coupled.set_matrix(&[0; 4]));
+        coupled.step();
+        field_step(&mut alone);
+        assert_eq!(coupled.fields[0].cells, alone.cells);
+        assert!(!coupled.set_matrix(&[0; 3]));
+    }
+
+    #[test]
+    fn test_register_limits_and_clamping() {
+        let mut coupled = CoupledFields::default();
+        coupled
+            .register(Box::new(create_field_1(4, 4, 4, 2)))
+            .unwrap();
+        assert!(coupled.set_coefficient(0, 0, -(1 << 20)));
+        assert_eq!(
+            coupled.register(Box::new(create_field_1(4, 5, 4, 2))),
+            Err(CoupleError::DimensionMismatch)
+        );
+        coupled
+            .register(Box::new(create_field_1(4, 4, 4, 2)))
+            .unwrap();
+        // Growing the matrix keeps the existing entry
+        assert_eq!(coupled.coefficients, [-(1 << 20), 0, 0, 0]);
+        for _ in 2..MAX_COUPLED_FIELDS {
+            coupled
+                .register(Box::new(create_field_1(4, 4, 4, 2)))
+                .unwrap();
+        }
+        assert_eq!(
+            coupled.register(Box::new(create_field_1(4, 4, 4, 2))),
+            Err(CoupleError::Full)
+        );
+
+        coupled.fields[1].cells.fill(u32::MAX);
+        assert!(coupled.set_coefficient(1, 1, 1 << 16));
+        coupled.step();
+        assert!(coupled.fields[0].cells.iter().all(|&v| v == 1));
+        assert!(coupled.fields[1].cells.iter().all(|&v| v == u32::MAX));
+    }
+}
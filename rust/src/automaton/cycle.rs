@@ -0,0 +1,262 @@
+//! Cycle detection: noticing that a grid has settled into a still life or an
+//! oscillator.
+//!
+//! Once tracking is on, every step records a 64-bit hash of the generation it
+//! committed, keeping the last `window` of them. The grid has entered a cycle
+//! of period `p` when the current generation hashes the same as the one `p`
+//! steps back; a still life (including an empty grid) has period 1. Stepping
+//! is deterministic, so from then on the grid repeats forever, and a mod can
+//! stop stepping it until a player changes something.
+//!
+//! The hash covers everything that decides the next generation: the cells,
+//! species ids, and ages when an age limit makes them matter. The current
+//! generation is hashed afresh when asked, so a cell edited since the last
+//! step is not mistaken for a cycle. Equal hashes of different grids are
+//! possible but vanishingly unlikely (about 2^-64 per comparison).
+
+use std::collections::VecDeque;
+
+use super::hash::{absorb, words};
+use super::rng::mix64;
+use super::stepping::step_automaton;
+use crate::state::State;
+
+/// Longest period `detect_cycle` looks for.
+pub const MAX_CYCLE_PERIOD: u32 = 4096;
+
+/// Hashes of the most recent generations, oldest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CycleTracker {
+    hashes: VecDeque<(u64, u64)>,
+    window: usize,
+}
+
+impl CycleTracker {
+    /// An empty tracker remembering `window` generations.
+    pub fn new(window: usize) -> Self {
+        CycleTracker {
+            hashes: VecDeque::new(),
+            window,
+        }
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Remember the hash of `generation`. Hashes of later generations (left
+    /// over from before a rollback) are forgotten.
+    pub fn record(&mut self, generation: u64, hash: u64) {
+        while self
+            .hashes
+            .back()
+            .is_some_and(|&(recorded, _)| recorded >= generation)
+        {
+            self.hashes.pop_back();
+        }
+        if self.hashes.len() >= self.window {
+            self.hashes.pop_front();
+        }
+        self.hashes.push_back((generation, hash));
+    }
+
+    /// Smallest period up to `max_period` such that the generation that many
+    /// steps before `generation` had `hash`.
+    pub fn period(&self, generation: u64, hash: u64, max_period: u32) -> Option<u32> {
+        self.hashes
+            .iter()
+            .rev()
+            .filter_map(|&(recorded, h)| Some((generation.checked_sub(recorded)?, h)))
+            .filter(|&(back, _)| (1..=max_period as u64).contains(&back))
+            .find(|&(_, h)| h == hash)
+            .map(|(back, _)| back as u32)
+    }
+
+    /// Forget every hash, keeping the window.
+    pub fn clear(&mut self) {
+        self.hashes.clear();
+    }
+}
+
+/// Hash of everything that decides the state's next generation.
+pub fn generation_hash(state: &State) -> u64 {
+    let mut hash = absorb(mix64(state.cells.len() as u64), words(&state.cells));
+    if let Some(layer) = &state.species {
+        hash = absorb(hash, words(&layer.ids));
+    }
+    if let Some(age) = state.age.as_ref().filter(|age| age.max_age != 0) {
+        let packed = age.ages.chunks(4).map(|ages| {
+            ages.iter()
+                .rev()
+                .fold(0, |word, &age| word << 16 | age as u64)
+        });
+        hash = absorb(hash, packed);
+    }
+    hash
+}
+
+/// Record the state's current generation if it tracks cycles. Called after
+/// each committed step.
+pub fn record_generation(state: &mut State) {
+    if state.cycles.is_some() {
+        let hash = generation_hash(state);
+        if let Some(tracker) = &mut state.cycles {
+            tracker.record(state.generation, hash);
+        }
+    }
+}
+
+/// Period of the cycle the state is in, looking up to `max_period`
+/// (at most `MAX_CYCLE_PERIOD`) generations back, or 0 if none is seen.
+///
+/// Tracking starts with the first call, which records the current generation
+/// and widens the window to `max_period` if needed, so a cycle of period p is
+/// reported from the p-th step after that. A `max_period` of 0 turns tracking
+/// off.
+pub fn detect_cycle(state: &mut State, max_period: u32) -> u32 {
+    if max_period == 0 {
+        state.cycles = None;
+        return 0;
+    }
+    let max_period = max_period.min(MAX_CYCLE_PERIOD);
+    let hash = generation_hash(state);
+    let generation = state.generation;
+    let tracker = state.cycles.get_or_insert_with(CycleTracker::default);
+    // The current generation is kept too, for the steps that follow
+    tracker.window = tracker.window.max(max_period as usize + 1);
+    let period = tracker.period(generation, hash, max_period);
+    tracker.record(generation, hash);
+    period.unwrap_or(0)
+}
+
+/// Outcome of `run_until_stable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StableRun {
+    /// Steps taken.
+    pub generations: u64,
+    /// Period of the cycle the grid ended in (1 for a still life), or 0 if
+    /// `max_generations` ran out first.
+    pub period: u32,
+}
+
+/// Step the state until it settles into a still life or an oscillator of
+/// period up to `window` (1..=`MAX_CYCLE_PERIOD`), or `max_generations` steps
+/// have been taken (pre-baking decorative structures at map generation).
+///
+/// A still life is the period-1 case, so a grid whose cells stop changing is
+/// caught after one step; a population that merely stays constant is not
+/// enough, since a glider keeps its population while it travels. The
+/// look-back uses its own hashes, leaving `detect_cycle` tracking as it was.
+pub fn run_until_stable(state: &mut State, max_generations: u64, window: u32) -> StableRun {
+    let window = window.clamp(1, MAX_CYCLE_PERIOD);
+    let mut tracker = CycleTracker::new(window as usize);
+    tracker.record(state.generation, generation_hash(state));
+    for generations in 1..=max_generations {
+        step_automaton(state);
+        let hash = generation_hash(state);
+        if let Some(period) = tracker.period(state.generation, hash, window) {
+            return StableRun {
+                generations,
+                period,
+            };
+        }
+        tracker.record(state.generation, hash);
+    }
+    StableRun {
+        generations: max_generations,
+        period: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::age::CellAge;
+    use crate::automaton::grid::{create_grid, index_of};
+    use crate::automaton::stamp::{stamp_pattern, STAMP_BLINKER};
+    use crate::automaton::stepping::step_automaton;
+    use crate::state::Rule;
+
+    fn grid() -> State {
+        let mut state = State::default();
+        create_grid(&mut state, 10, 10, 10);
+        state
+    }
+
+    #[test]
+    fn test_oscillator_and_still_life() {
+        let mut state = grid();
+        stamp_pattern(&mut state, STAMP_BLINKER, 4, 4, 4, 0).unwrap();
+        assert_eq!(detect_cycle(&mut state, 8), 0);
+        step_automaton(&mut state);
+        assert_eq!(detect_cycle(&mut state, 8), 0);
+        step_automaton(&mut state);
+        assert_eq!(detect_cycle(&mut state, 8), 2);
+        // Too short a look-back misses it
+        assert_eq!(detect_cycle(&mut state, 1), 0);
+
+        // An edit breaks the cycle until the grid settles again
+        state.cells.fill(0);
+        assert_eq!(detect_cycle(&mut state, 8), 0);
+        step_automaton(&mut state);
+        assert_eq!(detect_cycle(&mut state, 8), 1);
+        assert_eq!(detect_cycle(&mut state, 0), 0);
+        assert!(state.cycles.is_none());
+    }
+
+    #[test]
+    fn test_age_limit_is_part_of_the_hash() {
+        let mut state = grid();
+        // With no births and survival on 7, a 2x2x2 cube is a still life...
+        // until its cells age out
+        state.rule = Rule {
+            birth: 0,
+            survival: 1 << 7,
+        };
+        for (x, y, z) in [(4, 4, 4), (5, 4, 4), (4, 5, 4), (5, 5, 4)] {
+            for dz in 0..2 {
+                let idx = index_of(&state, x, y, z + dz);
+                state.cells[idx] = 1;
+            }
+        }
+        let cells = state.cells.clone();
+        step_automaton(&mut state);
+        assert_eq!(state.cells, cells, "not a still life");
+        state.age = Some(CellAge::new(state.cells.len(), 5));
+        detect_cycle(&mut state, 4);
+        step_automaton(&mut state);
+        assert_eq!(detect_cycle(&mut state, 4), 0);
+
+        // Without a limit, ages do not change what happens next
+        state.age.as_mut().unwrap().max_age = 0;
+        step_automaton(&mut state);
+        step_automaton(&mut state);
+        assert_eq!(detect_cycle(&mut state, 4), 1);
+    }
+
+    #[test]
+    fn test_run_until_stable() {
+        let mut state = grid();
+        stamp_pattern(&mut state, STAMP_BLINKER, 4, 4, 4, 0).unwrap();
+        let run = run_until_stable(&mut state, 100, 8);
+        assert_eq!(
+            run,
+            StableRun {
+                generations: 2,
+                period: 2
+            }
+        );
+        assert_eq!(state.generation, 2);
+        // Already cycling: one more period confirms it
+        assert_eq!(run_until_stable(&mut state, 100, 8).generations, 2);
+        // A window shorter than the period never sees it
+        let run = run_until_stable(&mut state, 10, 1);
+        assert_eq!(run.period, 0);
+        assert_eq!(run.generations, 10);
+        assert!(state.cycles.is_none());
+
+        let mut empty = grid();
+        assert_eq!(run_until_stable(&mut empty, 100, 8).generations, 1);
+        assert_eq!(run_until_stable(&mut empty, 0, 8).period, 0);
+    }
+}
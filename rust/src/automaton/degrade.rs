@@ -0,0 +1,208 @@
+//! Graceful degradation of a StepController under memory pressure.
+//!
+//! With a memory cap set, `begin_step` estimates the controller's heap usage
+//! plus the step buffers it is about to allocate. While that exceeds the cap,
+//! the controller sheds optional memory one `Degradation` at a time, in the
+//! order of `Degradation::ALL`, and queues a `StepEvent::Degraded` for each.
+//! If allocating the step buffers fails outright, every remaining degradation
+//! is applied (cap or not) before one more attempt; only then does
+//! `begin_step` give up.
+//!
+//! Degradations never touch the cells or anything else that changes results:
+//! a degraded controller steps exactly like before, it just records less and
+//! runs on fewer threads. The cap is a target rather than a hard limit; a step
+//! whose buffers cannot fit under it even after every degradation still runs.
+//! Each degradation is applied at most once per controller.
+
+use std::mem::size_of;
+
+use super::delta::{Contract, EntityHandle, NeighborKind, RemoteEndpoint};
+use super::events::StepEvent;
+use super::incremental::StepController;
+use super::poststep::PostStepOp;
+
+/// Memory a controller can give up without changing its results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Degradation {
+    /// Flow recording is turned off and its buffer (3 x i32 per cell) freed.
+    FlowRecordDropped,
+    /// Spare capacity of the controller's buffers (overrides, contracts,
+    /// post-step grids) is released.
+    BuffersCompacted,
+    /// The thread pool is rebuilt with a single worker, freeing the stacks of
+    /// the others.
+    SingleThreaded,
+}
+
+impl Degradation {
+    /// Every degradation, in the order they are applied.
+    pub const ALL: [Degradation; 3] = [
+        Degradation::FlowRecordDropped,
+        Degradation::BuffersCompacted,
+        Degradation::SingleThreaded,
+    ];
+
+    /// Bit of this degradation in `MemoryPolicy::applied` (stable across
+    /// versions).
+    pub fn bit(self) -> u32 {
+        match self {
+            Degradation::FlowRecordDropped => 1,
+            Degradation::BuffersCompacted => 2,
+            Degradation::SingleThreaded => 4,
+        }
+    }
+}
+
+/// A controller's memory cap and the degradations applied so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryPolicy {
+    /// Heap bytes (as estimated by `heap_bytes`) to stay under; None = no cap.
+    pub cap: Option<u64>,
+    /// `Degradation::bit`s of the degradations applied.
+    pub applied: u32,
+}
+
+/// Estimated heap bytes held by the controller: its field and the field's
+/// side buffers, an active step, overrides, contracts and post-step grids.
+/// Thread stacks and allocator overhead are not counted.
+pub fn heap_bytes(ctrl: &StepController) -> u64 {
+    let field = &ctrl.field;
+    let mut bytes = field.cells.capacity() * size_of::<u32>();
+    bytes += field
+        .flow_record
+        .as_ref()
+        .map_or(0, |flows| flows.capacity() * size_of::<i32>());
+    bytes += field.phases.as_ref().map_or(0, |p| p.phase.capacity());
+    bytes += field.protection.as_ref().map_or(0, |p| p.mask.capacity());
+    if let Some(step) = &ctrl.active_step {
+        bytes += (step.source.capacity() + step.target.capacity()) * size_of::<u32>();
+        bytes += step.cell_has_override.capacity();
+    }
+    bytes += ctrl.delta_overrides.capacity() * size_of::<((usize, usize), NeighborKind)>();
+    let contracts = &ctrl.contract_list;
+    bytes += contracts.contracts.capacity() * size_of::<Contract>();
+    bytes += contracts.remote_endpoints.capacity() * size_of::<RemoteEndpoint>();
+    bytes += contracts.entity_handles.capacity() * size_of::<EntityHandle>();
+    for op in &ctrl.post_step.ops {
+        if let PostStepOp::ThresholdCouple { grid, .. } = op {
+            bytes += grid.cells.capacity();
+        }
+    }
+    bytes as u64
+}
+
+/// Heap bytes of the buffers a step over `cells` cells allocates (source and
+/// target copies plus one override flag per cell).
+pub fn step_bytes(cells: usize) -> u64 {
+    (cells * (2 * size_of::<u32>() + size_of::<bool>())) as u64
+}
+
+/// Apply degradations in order while the controller's usage plus `reserve`
+/// bytes exceeds its cap (all remaining ones if `force`). Returns the bits of
+/// the degradations applied by this call.
+pub fn relieve(ctrl: &mut StepController, reserve: u64, force: bool) -> u32 {
+    let mut applied = 0;
+    for action in Degradation::ALL {
+        let over_cap = match ctrl.memory.cap {
+            Some(cap) => heap_bytes(ctrl).saturating_add(reserve) > cap,
+            None => false,
+        };
+        if !(force || over_cap) {
+            break;
+        }
+        if ctrl.memory.applied & action.bit() != 0 {
+            continue;
+        }
+        apply(ctrl, action);
+        ctrl.memory.applied |= action.bit();
+        applied |= action.bit();
+        ctrl.events.push(StepEvent::Degraded {
+            action,
+            generation: ctrl.field.generation,
+            global_tick: ctrl.global_tick,
+        });
+    }
+    applied
+}
+
+fn apply(ctrl: &mut StepController, action: Degradation) {
+    match action {
+        Degradation::FlowRecordDropped => ctrl.field.flow_record = None,
+        Degradation::BuffersCompacted => {
+            ctrl.delta_overrides.shrink_to_fit();
+            ctrl.contract_list.contracts.shrink_to_fit();
+            ctrl.contract_list.remote_endpoints.shrink_to_fit();
+            ctrl.contract_list.entity_handles.shrink_to_fit();
+            ctrl.field.cells.shrink_to_fit();
+            for op in &mut ctrl.post_step.ops {
+                if let PostStepOp::ThresholdCouple { grid, .. } = op {
+                    grid.cells.shrink_to_fit();
+                }
+            }
+        }
+        Degradation::SingleThreaded => {
+            if ctrl.thread_pool.current_num_threads() > 1 {
+                if let Ok(pool) = rayon::ThreadPoolBuilder::new().num_threads(1).build() {
+                    ctrl.thread_pool = pool;
+                }
+            }
+        }
+    }
+}
+
+/// An empty buffer with room for `cells` values, or None if the allocation
+/// fails.
+pub fn try_buffer(cells: usize) -> Option<Vec<u32>> {
+    let mut buffer = Vec::new();
+    buffer.try_reserve_exact(cells).ok()?;
+    Some(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cap_sheds_in_order() {
+        let mut ctrl = StepController::new_1(16, 16, 16, 2, 2);
+        ctrl.field.flow_record = Some(vec![0; 3 * ctrl.field.cells.len()]);
+        let usage = heap_bytes(&ctrl);
+        assert!(usage >= 16 * 16 * 16 * 16);
+
+        // No cap: nothing happens
+        assert_eq!(relieve(&mut ctrl, 0, false), 0);
+        // A cap the flow record alone is enough to meet
+        ctrl.memory.cap = Some(usage + step_bytes(4096) - 4096 * 12);
+        ctrl.begin_step().unwrap();
+        assert_eq!(ctrl.memory.applied, Degradation::FlowRecordDropped.bit());
+        assert!(ctrl.field.flow_record.is_none());
+        assert_eq!(ctrl.thread_pool.current_num_threads(), 2);
+        let Some(StepEvent::Degraded { action, .. }) = ctrl.events.pop() else {
+            panic!("degradation not reported");
+        };
+        assert_eq!(action, Degradation::FlowRecordDropped);
+        ctrl.step_blocking();
+
+        // An unreachable cap sheds everything once, and the step still runs
+        ctrl.memory.cap = Some(1);
+        ctrl.step_blocking();
+        assert_eq!(ctrl.memory.applied, 7);
+        assert_eq!(ctrl.thread_pool.current_num_threads(), 1);
+        assert_eq!(ctrl.field.generation, 2);
+        assert_eq!(relieve(&mut ctrl, 0, true), 0);
+    }
+
+    #[test]
+    fn test_degraded_controller_steps_identically() {
+        let mut plain = StepController::new_1(20, 20, 20, 2, 2);
+        plain.field.cells[4200] = 1_000_000;
+        let mut degraded = StepController::from_field(plain.field.clone(), 2);
+        degraded.memory.cap = Some(0);
+        for _ in 0..3 {
+            plain.step_blocking();
+            degraded.step_blocking();
+        }
+        assert_eq!(degraded.memory.applied, 7);
+        assert_eq!(plain.field.cells, degraded.field.cells);
+    }
+}
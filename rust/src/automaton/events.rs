@@ -0,0 +1,105 @@
+//! Queued notifications from a StepController.
+//!
+//! Lua cannot safely be called back from inside a step (LuaJIT callbacks from
+//! C are slow and must not re-enter the JIT), so the controller queues events
+//! instead and the caller drains them right after `tick` returns. A step that
+//! finishes inside a tick is then handled in that same server tick, without
+//! polling `is_stepping`.
+
+use std::collections::VecDeque;
+
+use super::degrade::Degradation;
+
+/// Events retained before the oldest are dropped (a caller that never drains
+/// the queue must not grow it without bound).
+pub const EVENT_QUEUE_CAPACITY: usize = 256;
+
+/// Something a caller may want to react to immediately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepEvent {
+    /// An incremental step finalized; the field is now at `generation`.
+    GenerationComplete { generation: u64, global_tick: u64 },
+    /// Memory was shed under pressure (see `degrade`), before the step from
+    /// `generation` began.
+    Degraded {
+        action: Degradation,
+        generation: u64,
+        global_tick: u64,
+    },
+}
+
+impl StepEvent {
+    /// Numeric kind for the FFI (stable across versions).
+    pub fn kind(&self) -> u32 {
+        match self {
+            StepEvent::GenerationComplete { .. } => 1,
+            StepEvent::Degraded { action, .. } => match action {
+                Degradation::FlowRecordDropped => 2,
+                Degradation::BuffersCompacted => 3,
+                Degradation::SingleThreaded => 4,
+            },
+        }
+    }
+}
+
+/// Bounded FIFO of events.
+#[derive(Debug, Default)]
+pub struct EventQueue {
+    events: VecDeque<StepEvent>,
+    /// Events discarded because the queue was full.
+    pub dropped: u64,
+}
+
+impl EventQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue an event, dropping the oldest one if the queue is full.
+    pub fn push(&mut self, event: StepEvent) {
+        if self.events.len() >= EVENT_QUEUE_CAPACITY {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+    }
+
+    /// Take the oldest event.
+    pub fn pop(&mut self) -> Option<StepEvent> {
+        self.events.pop_front()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fifo_and_capacity() {
+        let mut queue = EventQueue::new();
+        assert_eq!(queue.pop(), None);
+        for generation in 0..EVENT_QUEUE_CAPACITY as u64 + 2 {
+            queue.push(StepEvent::GenerationComplete {
+                generation,
+                global_tick: generation,
+            });
+        }
+        assert_eq!(queue.len(), EVENT_QUEUE_CAPACITY);
+        assert_eq!(queue.dropped, 2);
+        assert_eq!(
+            queue.pop(),
+            Some(StepEvent::GenerationComplete {
+                generation: 2,
+                global_tick: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! Running many generations natively with periodic summary samples.
+//!
+//! Balancing a rule offline (does this colony die out, explode, or settle?)
+//! needs thousands of generations but only a few numbers from each. Fast
+//! forward steps the handle as usual and hands a summary to the caller after
+//! every `stride` generations, so nothing but the samples crosses the FFI.
+//!
+//! Samples are taken after generations `stride`, `2 * stride`, ... counted
+//! from the start of the run, up to `generations`; a run whose length is not a
+//! multiple of `stride` ends with a partial stride that is not sampled.
+
+use super::field::{field_step_selected, Field};
+use super::poststep::FieldStats;
+use super::stepping::step_automaton;
+use crate::state::State;
+
+/// Summary of a grid after a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridSample {
+    pub generation: u64,
+    /// Live cells.
+    pub population: u64,
+    /// Inclusive bounding box `(min, max)` of the live cells, None if there
+    /// are none.
+    pub bbox: Option<([i16; 3], [i16; 3])>,
+}
+
+impl GridSample {
+    /// Summary of the grid as it is now.
+    pub fn of(state: &State) -> Self {
+        let (w, h) = (state.width as usize, state.height as usize);
+        let mut population = 0;
+        let mut bbox: Option<([i16; 3], [i16; 3])> = None;
+        for (idx, _) in state.cells.iter().enumerate().filter(|(_, &c)| c != 0) {
+            population += 1;
+            let p = [idx % w, idx / w % h, idx / (w * h)].map(|c| c as i16);
+            let (lo, hi) = bbox.get_or_insert((p, p));
+            for axis in 0..3 {
+                lo[axis] = lo[axis].min(p[axis]);
+                hi[axis] = hi[axis].max(p[axis]);
+            }
+        }
+        GridSample {
+            generation: state.generation,
+            population,
+            bbox,
+        }
+    }
+}
+
+/// Step the grid `generations` times, calling `sample` after every `stride`
+/// generations. Returns the number of samples taken (0 without a grid or for
+/// a stride of 0, in which case nothing is stepped).
+pub fn fast_forward(
+    state: &mut State,
+    generations: u64,
+    stride: u64,
+    mut sample: impl FnMut(GridSample),
+) -> u64 {
+    if state.cells.is_empty() || stride == 0 {
+        return 0;
+    }
+    for done in 1..=generations {
+        step_automaton(state);
+        if done % stride == 0 {
+            sample(GridSample::of(state));
+        }
+    }
+    generations / stride
+}
+
+/// `fast_forward` for a field: steps with `field_step_selected` and samples its
+/// `FieldStats` (total mass, min, max).
+pub fn field_fast_forward(
+    field: &mut Field,
+    generations: u64,
+    stride: u64,
+    mut sample: impl FnMut(FieldStats),
+) -> u64 {
+    if stride == 0 {
+        return 0;
+    }
+    for done in 1..=generations {
+        field_step_selected(field);
+        if done % stride == 0 {
+            sample(FieldStats::of(field));
+        }
+    }
+    generations / stride
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_set};
+    use crate::automaton::grid::{create_grid, index_of};
+
+    #[test]
+    fn test_grid_samples_every_stride() {
+        let mut state = State::default();
+        create_grid(&mut state, 12, 12, 12);
+        for (x, y) in [(6, 6), (5, 6), (7, 6), (6, 5), (6, 7)] {
+            let idx = index_of(&state, x, y, 6);
+            state.cells[idx] = 1;
+        }
+        let first = GridSample::of(&state);
+        assert_eq!(first.population, 5);
+        assert_eq!(first.bbox, Some(([5, 5, 6], [7, 7, 6])));
+
+        let mut reference = state.clone();
+        let mut samples = Vec::new();
+        assert_eq!(fast_forward(&mut state, 7, 3, |s| samples.push(s)), 2);
+        assert_eq!(samples.len(), 2);
+        assert_eq!(state.generation, 7);
+        for _ in 0..3 {
+            step_automaton(&mut reference);
+        }
+        assert_eq!(samples[0], GridSample::of(&reference));
+        assert_eq!(samples[1].generation, 6);
+
+        assert_eq!(fast_forward(&mut state, 5, 0, |_| unreachable!()), 0);
+        assert_eq!(state.generation, 7);
+        let empty = GridSample::of(&State::default());
+        assert_eq!((empty.population, empty.bbox), (0, None));
+    }
+
+    #[test]
+    fn test_field_samples_conserve_mass() {
+        let mut field = create_field_1(8, 8, 8, 1);
+        field_set(&mut field, 4, 4, 4, 100_000);
+        let total = FieldStats::of(&field).total;
+        let mut samples = Vec::new();
+        assert_eq!(
+            field_fast_forward(&mut field, 20, 5, |s| samples.push(s)),
+            4
+        );
+        let generations: Vec<u64> = samples.iter().map(|s| s.generation).collect();
+        assert_eq!(generations, [5, 10, 15, 20]);
+        assert!(samples.iter().all(|s| s.total == total));
+        assert!(samples[3].max < samples[0].max);
+    }
+}
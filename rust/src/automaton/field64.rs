@@ -0,0 +1,178 @@
+//! Wide-value field: u64 cells with i128 flow arithmetic.
+//!
+//! A u32 cell tops out near 4.3e9, which is too little for energy in joules at
+//! realistic scales. `Field64` stores u64 cells and runs the same diffusion
+//! pass as `Field` (`field::diffuse`), monomorphized for u64 cells so that
+//! `gradient * conductivity` is computed in i128 and cannot overflow. Rounding
+//! modes, per-axis rates, and the stability bound behave exactly as for `Field`.
+//!
+//! Only diffusion is supported; conductivity curves, sources, advection,
+//! phases, and flow recording remain `Field`-only.
+
+use super::field::{diffuse, DiffusionPass, FieldError, RoundingMode};
+use std::num::NonZeroU64;
+
+/// A 3D field of u64 values (see the module docs).
+#[derive(Clone)]
+pub struct Field64 {
+    pub width: i16,
+    pub height: i16,
+    pub depth: i16,
+    pub cells: Vec<u64>,
+    pub generation: u64,
+    pub diffusion_rate: u8, // power-of-2 shift, as for `Field`
+    /// Per-axis shifts (x, y, z) replacing `diffusion_rate` when set.
+    pub axis_rates: Option<[u8; 3]>,
+    pub conductivity: u16, // Scaled by 2^16, as for `Field`
+    /// Rounding of fractional flows.
+    pub rounding: RoundingMode,
+}
+
+/// Initialize a field with every cell at 1 (the minimum quantum, as for `Field`).
+pub fn create_field64(width: i16, height: i16, depth: i16, diffusion_rate: u8) -> Field64 {
+    let size = (width as usize) * (height as usize) * (depth as usize);
+    Field64 {
+        width,
+        height,
+        depth,
+        cells: vec![1; size],
+        generation: 0,
+        diffusion_rate,
+        axis_rates: None,
+        conductivity: 65535, // Fully conductive by default (C_mat ~ 1.0)
+        rounding: RoundingMode::Stochastic,
+    }
+}
+
+/// Linear index of a 3D coordinate (z,y,x order).
+#[inline]
+pub fn field64_index_of(field: &Field64, x: i16, y: i16, z: i16) -> usize {
+    z as usize * field.height as usize * field.width as usize
+        + y as usize * field.width as usize
+        + x as usize
+}
+
+/// Check if coordinates are within field bounds.
+#[inline]
+pub fn field64_in_bounds(field: &Field64, x: i16, y: i16, z: i16) -> bool {
+    x >= 0 && x < field.width && y >= 0 && y < field.height && z >= 0 && z < field.depth
+}
+
+/// Set a cell value (out-of-bounds writes are ignored).
+pub fn field64_set(field: &mut Field64, x: i16, y: i16, z: i16, value: u64) {
+    if field64_in_bounds(field, x, y, z) {
+        let idx = field64_index_of(field, x, y, z);
+        field.cells[idx] = value;
+    }
+}
+
+/// Get a cell value, never zero inside the field (see `field_get`).
+pub fn field64_get(field: &Field64, x: i16, y: i16, z: i16) -> Result<NonZeroU64, FieldError> {
+    if field64_in_bounds(field, x, y, z) {
+        let idx = field64_index_of(field, x, y, z);
+        NonZeroU64::new(field.cells[idx].max(1)).ok_or(FieldError::OutOfBounds)
+    } else {
+        Err(FieldError::OutOfBounds)
+    }
+}
+
+/// Diffusion shift of each axis (x, y, z), as `field_axis_rates`.
+#[inline]
+pub fn field64_axis_rates(field: &Field64) -> [u8; 3] {
+    field.axis_rates.unwrap_or([field.diffusion_rate; 3])
+}
+
+/// Step the field with sequential axis-wise diffusion, as `field_step`.
+pub fn field64_step(field: &mut Field64) {
+    step(field, true);
+}
+
+/// Step the field with fused simultaneous diffusion, as `field_step_fused`.
+pub fn field64_step_fused(field: &mut Field64) {
+    step(field, false);
+}
+
+fn step(field: &mut Field64, sequential: bool) {
+    let pass = DiffusionPass {
+        dims: [
+            field.width as usize,
+            field.height as usize,
+            field.depth as usize,
+        ],
+        generation: field.generation,
+        rounding: field.rounding,
+        rates: field64_axis_rates(field),
+        sequential,
+        block: None,
+        periodic: [false; 3],
+    };
+    let conductivity = field.conductivity as i64;
+    let mut new_cells = field.cells.clone();
+    diffuse(
+        &pass,
+        &mut field.cells,
+        &mut new_cells,
+        |_, _| conductivity,
+        |_, _, _, _| {},
+    );
+    field.cells = new_cells;
+    field.generation += 1;
+}
+
+/// Total of all cells, or None if it exceeds u64 (possible near u64::MAX).
+pub fn field64_total(field: &Field64) -> Option<u64> {
+    field
+        .cells
+        .iter()
+        .try_fold(0u64, |sum, &v| sum.checked_add(v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_set, field_step_fused};
+
+    #[test]
+    fn test_matches_u32_field_in_range() {
+        let mut narrow = create_field_1(9, 7, 5, 2);
+        let mut wide = create_field64(9, 7, 5, 2);
+        field_set(&mut narrow, 4, 3, 2, 3_000_000_000);
+        field64_set(&mut wide, 4, 3, 2, 3_000_000_000);
+        for mode in [RoundingMode::Stochastic, RoundingMode::HalfEven] {
+            narrow.rounding = mode;
+            wide.rounding = mode;
+            for _ in 0..10 {
+                field_step_fused(&mut narrow);
+                field64_step_fused(&mut wide);
+            }
+        }
+        let widened: Vec<u64> = narrow.cells.iter().map(|&v| v as u64).collect();
+        assert_eq!(wide.cells, widened);
+        assert_eq!(wide.generation, 20);
+    }
+
+    #[test]
+    fn test_joule_scale_values_conserved() {
+        // Far beyond u32: 2^62 in one cell would overflow i64 gradient * conductivity
+        let mut field = create_field64(6, 6, 6, 1);
+        field.axis_rates = Some([1, 3, 1]);
+        field64_set(&mut field, 3, 3, 3, 1 << 62);
+        let total = field64_total(&field).unwrap();
+        for step in 0..30 {
+            if step % 2 == 0 {
+                field64_step(&mut field);
+            } else {
+                field64_step_fused(&mut field);
+            }
+        }
+        assert_eq!(field64_total(&field), Some(total));
+        assert!(field.cells.iter().all(|&v| v >= 1));
+        let center = field64_get(&field, 3, 3, 3).unwrap().get();
+        assert!(center < 1 << 62);
+        assert!(
+            field64_get(&field, 4, 3, 3).unwrap().get()
+                > field64_get(&field, 3, 4, 3).unwrap().get()
+        );
+        assert_eq!(field64_get(&field, 6, 0, 0), Err(FieldError::OutOfBounds));
+    }
+}
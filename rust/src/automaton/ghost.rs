@@ -0,0 +1,210 @@
+//! Ghost-cell exchange between adjacent handles, for worlds sharded across
+//! several grids or fields (one per mapchunk).
+//!
+//! Two neighbors overlap by two layers. The outermost layer of each on the
+//! shared face is a ghost owned by the other: before a step, each ghost is
+//! overwritten with the neighbor's layer just inside the face. A pattern or
+//! a heat front then crosses the seam as if the handles were one grid, as
+//! long as the exchange runs before every step.
+//!
+//! For fields the ghosts are copies, so each handle's total includes its
+//! ghosts and is not conserved on its own; sum the interiors to get the
+//! world's total.
+
+use super::boundary::{FACE_NEG_X, FACE_POS_Z};
+use super::field::Field;
+use crate::state::State;
+
+/// Why an exchange was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExchangeError {
+    /// Face id above 5.
+    InvalidFace,
+    /// The faces differ in size, or a handle is under two cells thick along the
+    /// exchange axis.
+    ShapeMismatch,
+}
+
+/// Exchange ghost layers of grids `a` and `b`, `b` lying beyond `face` of `a`
+/// (0 = -x, 1 = +x, 2 = -y, 3 = +y, 4 = -z, 5 = +z). Only live/dead cells are
+/// copied, not species, ages or protection.
+pub fn exchange_boundaries(a: &mut State, b: &mut State, face: u8) -> Result<(), ExchangeError> {
+    let (a_dims, b_dims) = ([a.width, a.height, a.depth], [b.width, b.height, b.depth]);
+    exchange_layers(a_dims, &mut a.cells, b_dims, &mut b.cells, face)
+}
+
+/// `exchange_boundaries` for fields.
+pub fn field_exchange_boundaries(
+    a: &mut Field,
+    b: &mut Field,
+    face: u8,
+) -> Result<(), ExchangeError> {
+    let (a_dims, b_dims) = ([a.width, a.height, a.depth], [b.width, b.height, b.depth]);
+    exchange_layers(a_dims, &mut a.cells, b_dims, &mut b.cells, face)
+}
+
+/// Exchange the ghost layers of two z,y,x-ordered grids (see the module docs).
+fn exchange_layers<T: Copy>(
+    a_dims: [i16; 3],
+    a_cells: &mut [T],
+    b_dims: [i16; 3],
+    b_cells: &mut [T],
+    face: u8,
+) -> Result<(), ExchangeError> {
+    if !(FACE_NEG_X..=FACE_POS_Z).contains(&face) {
+        return Err(ExchangeError::InvalidFace);
+    }
+    // Order the pair so `low` lies on the -axis side
+    let axis = (face / 2) as usize;
+    let ((low_dims, low), (high_dims, high)) = if face % 2 == 1 {
+        ((a_dims, a_cells), (b_dims, b_cells))
+    } else {
+        ((b_dims, b_cells), (a_dims, a_cells))
+    };
+    let [low_dims, high_dims] = [low_dims, high_dims].map(|dims| dims.map(|e| e.max(0) as usize));
+    let across = (0..3).filter(|&other| other != axis);
+    if across
+        .clone()
+        .any(|other| low_dims[other] != high_dims[other])
+        || low_dims[axis] < 2
+        || high_dims[axis] < 2
+        || low.len() != low_dims.iter().product::<usize>()
+        || high.len() != high_dims.iter().product::<usize>()
+    {
+        return Err(ExchangeError::ShapeMismatch);
+    }
+
+    let index = |dims: [usize; 3], pos: [usize; 3]| (pos[2] * dims[1] + pos[1]) * dims[0] + pos[0];
+    let [u, v]: [usize; 2] = {
+        let mut others = across;
+        [others.next().unwrap(), others.next().unwrap()]
+    };
+    let last = low_dims[axis] - 1;
+    for j in 0..low_dims[v] {
+        for i in 0..low_dims[u] {
+            let at = |layer: usize| {
+                let mut pos = [0; 3];
+                pos[axis] = layer;
+                pos[u] = i;
+                pos[v] = j;
+                pos
+            };
+            low[index(low_dims, at(last))] = high[index(high_dims, at(1))];
+            high[index(high_dims, at(0))] = low[index(low_dims, at(last - 1))];
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::boundary::FACE_POS_X;
+    use crate::automaton::field::{
+        create_field_1, field_get, field_set, field_step_fused, RoundingMode,
+    };
+    use crate::automaton::grid::{create_grid, index_of};
+    use crate::automaton::stepping::step_automaton;
+
+    #[test]
+    fn test_sharded_field_steps_like_one_field() {
+        // One 14-wide field, and two 8-wide shards overlapping by two columns:
+        // the left shard covers x 0..8 (ghost at 7), the right one x 6..14
+        // (ghost at 6)
+        let mut whole = create_field_1(14, 4, 4, 2);
+        let mut left = create_field_1(8, 4, 4, 2);
+        let mut right = create_field_1(8, 4, 4, 2);
+        // Hash rounding keys pairs by index, which differs between shards
+        for field in [&mut whole, &mut left, &mut right] {
+            field.rounding = RoundingMode::HalfEven;
+        }
+        field_set(&mut whole, 5, 1, 2, 3_000_000);
+        field_set(&mut left, 5, 1, 2, 3_000_000);
+        for _ in 0..6 {
+            field_exchange_boundaries(&mut left, &mut right, FACE_POS_X).unwrap();
+            field_step_fused(&mut whole);
+            field_step_fused(&mut left);
+            field_step_fused(&mut right);
+        }
+        for z in 0..4 {
+            for y in 0..4 {
+                for x in 0..14 {
+                    let expected = field_get(&whole, x, y, z);
+                    if x < 7 {
+                        assert_eq!(field_get(&left, x, y, z), expected);
+                    } else {
+                        assert_eq!(field_get(&right, x - 6, y, z), expected);
+                    }
+                }
+            }
+        }
+        // Heat crossed the seam into the right shard's interior
+        assert!(field_get(&right, 2, 1, 2).unwrap().get() > 1);
+    }
+
+    #[test]
+    fn test_sharded_grid_steps_like_one_grid() {
+        // Two 10-deep grids stacked along z overlapping by two layers
+        let mut whole = State::default();
+        create_grid(&mut whole, 6, 6, 18);
+        let mut bottom = State::default();
+        create_grid(&mut bottom, 6, 6, 10);
+        let mut top = State::default();
+        create_grid(&mut top, 6, 6, 10);
+        // A blob straddling the seam (whole z 7..11 = bottom 7..9 + top 0..3)
+        let blob = [
+            (2, 2, 7),
+            (3, 2, 8),
+            (2, 3, 8),
+            (3, 3, 9),
+            (2, 2, 10),
+            (3, 3, 10),
+        ];
+        for &(x, y, z) in &blob {
+            let idx = index_of(&whole, x, y, z);
+            whole.cells[idx] = 1;
+            if z < 9 {
+                let idx = index_of(&bottom, x, y, z);
+                bottom.cells[idx] = 1;
+            } else {
+                let idx = index_of(&top, x, y, z - 8);
+                top.cells[idx] = 1;
+            }
+        }
+        whole.rule.birth = 1 << 2 | 1 << 3;
+        bottom.rule = whole.rule;
+        top.rule = whole.rule;
+        for _ in 0..4 {
+            // Exchanged from the top's side: the bottom lies beyond its -z face
+            exchange_boundaries(&mut top, &mut bottom, 4).unwrap();
+            step_automaton(&mut whole);
+            step_automaton(&mut bottom);
+            step_automaton(&mut top);
+            for z in 0..18 {
+                for y in 0..6 {
+                    for x in 0..6 {
+                        let expected = whole.cells[index_of(&whole, x, y, z)];
+                        // Interiors only: bottom 0..9, top 1..10 (whole 9..18)
+                        if z < 9 {
+                            assert_eq!(bottom.cells[index_of(&bottom, x, y, z)], expected);
+                        } else {
+                            assert_eq!(top.cells[index_of(&top, x, y, z - 8)], expected);
+                        }
+                    }
+                }
+            }
+        }
+        assert!(top.cells.iter().any(|&cell| cell != 0));
+
+        let mut small = State::default();
+        create_grid(&mut small, 5, 6, 10);
+        assert_eq!(
+            exchange_boundaries(&mut top, &mut small, 4),
+            Err(ExchangeError::ShapeMismatch)
+        );
+        assert_eq!(
+            exchange_boundaries(&mut top, &mut bottom, 6),
+            Err(ExchangeError::InvalidFace)
+        );
+    }
+}
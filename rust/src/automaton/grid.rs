@@ -2,14 +2,91 @@
 
 use crate::state::State;
 
+/// Most cells a grid may hold (4 GiB of cells, before species and age layers).
+pub const MAX_GRID_CELLS: u64 = 1 << 32;
+
+/// Why `try_create_grid` refused to create a grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridError {
+    /// A dimension is zero or negative.
+    NonPositiveDimension,
+    /// The grid would hold more than `MAX_GRID_CELLS` cells.
+    TooLarge,
+    /// Memory for the cells (or the species/age layers) could not be allocated.
+    AllocationFailed,
+}
+
 /// Initialize a grid with the given dimensions.
 pub fn create_grid(state: &mut State, width: i16, height: i16, depth: i16) {
     let size = (width as usize) * (height as usize) * (depth as usize);
+    let ids = state.species.as_ref().map(|_| vec![0; size]);
+    let ages = state.age.as_ref().map(|_| vec![0; size]);
+    install_grid(state, [width, height, depth], vec![0; size], ids, ages);
+}
+
+/// `create_grid`, checking the dimensions and allocating fallibly instead of
+/// aborting the process. On error the state is unchanged.
+pub fn try_create_grid(
+    state: &mut State,
+    width: i16,
+    height: i16,
+    depth: i16,
+) -> Result<(), GridError> {
+    if width <= 0 || height <= 0 || depth <= 0 {
+        return Err(GridError::NonPositiveDimension);
+    }
+    let size = (width as u64)
+        .checked_mul(height as u64)
+        .and_then(|area| area.checked_mul(depth as u64))
+        .filter(|&size| size <= MAX_GRID_CELLS)
+        .and_then(|size| usize::try_from(size).ok())
+        .ok_or(GridError::TooLarge)?;
+    let cells = try_zeroed(size)?;
+    let ids = state
+        .species
+        .as_ref()
+        .map(|_| try_zeroed(size))
+        .transpose()?;
+    let ages = state.age.as_ref().map(|_| try_zeroed(size)).transpose()?;
+    install_grid(state, [width, height, depth], cells, ids, ages);
+    Ok(())
+}
+
+/// `len` zeros, or `AllocationFailed`.
+fn try_zeroed<T: Clone + Default>(len: usize) -> Result<Vec<T>, GridError> {
+    let mut buffer = Vec::new();
+    buffer
+        .try_reserve_exact(len)
+        .map_err(|_| GridError::AllocationFailed)?;
+    buffer.resize(len, T::default());
+    Ok(buffer)
+}
+
+/// Make `cells` the state's grid, resetting the generation and per-grid layers.
+fn install_grid(
+    state: &mut State,
+    [width, height, depth]: [i16; 3],
+    cells: Vec<u8>,
+    ids: Option<Vec<u8>>,
+    ages: Option<Vec<u16>>,
+) {
     state.width = width;
     state.height = height;
     state.depth = depth;
-    state.cells = vec![0; size];
+    state.cells = cells;
     state.generation = 0;
+    if let (Some(layer), Some(ids)) = (&mut state.species, ids) {
+        layer.ids = ids;
+    }
+    if let (Some(age), Some(ages)) = (&mut state.age, ages) {
+        age.ages = ages;
+    }
+    if let Some(history) = &mut state.history {
+        history.clear();
+    }
+    if let Some(tracker) = &mut state.cycles {
+        tracker.clear();
+    }
 }
 
 /// Calculate the linear index for a 3D coordinate.
@@ -26,6 +103,41 @@ pub fn in_bounds(state: &State, x: i16, y: i16, z: i16) -> bool {
     x >= 0 && x < state.width && y >= 0 && y < state.height && z >= 0 && z < state.depth
 }
 
+/// Set many cells in one pass (e.g. a batch of player edits). `coords` holds
+/// x, y, z triples; cell `i` becomes alive if `values[i]` is non-zero, dead
+/// otherwise. Stops at the shorter of the two; a cell listed twice takes its
+/// last value. Returns the number of cells inside the grid, which were set.
+pub fn set_cells_bulk(state: &mut State, coords: &[i16], values: &[u8]) -> u64 {
+    let mut inside = 0;
+    for (coord, &value) in coords.chunks_exact(3).zip(values) {
+        let (x, y, z) = (coord[0], coord[1], coord[2]);
+        if in_bounds(state, x, y, z) {
+            let idx = index_of(state, x, y, z);
+            state.cells[idx] = (value != 0) as u8;
+            inside += 1;
+        }
+    }
+    inside
+}
+
+/// Read many cells in one pass, the inverse of `set_cells_bulk`: `out[i]`
+/// receives the cell at triple `i` of `coords` (1 alive, 0 dead), or 0 outside
+/// the grid. Stops at the shorter of the two. Returns the number of cells
+/// inside the grid.
+pub fn get_cells_bulk(state: &State, coords: &[i16], out: &mut [u8]) -> u64 {
+    let mut inside = 0;
+    for (coord, value) in coords.chunks_exact(3).zip(out) {
+        let (x, y, z) = (coord[0], coord[1], coord[2]);
+        *value = if in_bounds(state, x, y, z) {
+            inside += 1;
+            state.cells[index_of(state, x, y, z)]
+        } else {
+            0
+        };
+    }
+    inside
+}
+
 /// Count alive neighbors using Moore neighborhood (26 neighbors).
 pub fn count_neighbors(state: &State, x: i16, y: i16, z: i16) -> u8 {
     let mut count = 0;
@@ -59,13 +171,7 @@ mod tests {
 
     #[test]
     fn test_create_grid() {
-        let mut state = State {
-            width: 0,
-            height: 0,
-            depth: 0,
-            cells: Vec::new(),
-            generation: 0,
-        };
+        let mut state = State::default();
 
         create_grid(&mut state, 8, 8, 8);
         assert_eq!(state.width, 8);
@@ -76,6 +182,30 @@ mod tests {
         assert!(state.cells.iter().all(|&c| c == 0));
     }
 
+    #[test]
+    fn test_try_create_grid() {
+        let mut state = State::default();
+        create_grid(&mut state, 4, 4, 4);
+        state.cells[5] = 1;
+
+        let refused = [
+            ((0, 8, 8), GridError::NonPositiveDimension),
+            ((8, -3, 8), GridError::NonPositiveDimension),
+            ((i16::MAX, i16::MAX, i16::MAX), GridError::TooLarge),
+            ((2048, 2048, 1025), GridError::TooLarge),
+        ];
+        for ((w, h, d), error) in refused {
+            assert_eq!(try_create_grid(&mut state, w, h, d), Err(error));
+            // The old grid survives a refused resize
+            assert_eq!(state.cells.len(), 64);
+            assert_eq!(state.cells[5], 1);
+        }
+
+        assert_eq!(try_create_grid(&mut state, 6, 5, 4), Ok(()));
+        assert_eq!(state.cells.len(), 120);
+        assert!(state.cells.iter().all(|&c| c == 0));
+    }
+
     #[test]
     fn test_index_of() {
         let state = State {
@@ -84,6 +214,7 @@ mod tests {
             depth: 4,
             cells: vec![0; 64],
             generation: 0,
+            ..State::default()
         };
 
         // First cell
@@ -104,6 +235,7 @@ mod tests {
             depth: 4,
             cells: vec![0; 64],
             generation: 0,
+            ..State::default()
         };
 
         // Valid bounds
@@ -128,6 +260,7 @@ mod tests {
             depth: 8,
             cells: vec![0; 512],
             generation: 0,
+            ..State::default()
         };
 
         // Set up a cross pattern: center + 4 neighbors
@@ -0,0 +1,185 @@
+//! Signed field: i32 cells for quantities that go negative.
+//!
+//! Electric potential or a velocity component has no natural zero floor, and
+//! storing it in a u32 `Field` needs an offset that every reader has to undo.
+//! `IField` stores i32 cells and runs the same diffusion pass as `Field`
+//! (`field::diffuse`): gradients may be negative on either side of zero, and
+//! each pair flow is subtracted from one cell and added to its neighbor, so
+//! the signed sum is conserved exactly. Rounding modes, per-axis rates, and
+//! the stability bound behave exactly as for `Field`.
+//!
+//! There is no minimum quantum: 0 and negative values are ordinary cells.
+//! Only diffusion is supported; conductivity curves, sources, advection,
+//! phases, and flow recording remain `Field`-only.
+
+use super::field::{diffuse, DiffusionPass, FieldError, RoundingMode};
+
+/// A 3D field of i32 values (see the module docs).
+#[derive(Clone)]
+pub struct IField {
+    pub width: i16,
+    pub height: i16,
+    pub depth: i16,
+    pub cells: Vec<i32>,
+    pub generation: u64,
+    pub diffusion_rate: u8, // power-of-2 shift, as for `Field`
+    /// Per-axis shifts (x, y, z) replacing `diffusion_rate` when set.
+    pub axis_rates: Option<[u8; 3]>,
+    pub conductivity: u16, // Scaled by 2^16, as for `Field`
+    /// Rounding of fractional flows.
+    pub rounding: RoundingMode,
+}
+
+/// Initialize a field with every cell at 0.
+pub fn create_ifield(width: i16, height: i16, depth: i16, diffusion_rate: u8) -> IField {
+    let size = (width as usize) * (height as usize) * (depth as usize);
+    IField {
+        width,
+        height,
+        depth,
+        cells: vec![0; size],
+        generation: 0,
+        diffusion_rate,
+        axis_rates: None,
+        conductivity: 65535, // Fully conductive by default (C_mat ~ 1.0)
+        rounding: RoundingMode::Stochastic,
+    }
+}
+
+/// Linear index of a 3D coordinate (z,y,x order).
+#[inline]
+pub fn ifield_index_of(field: &IField, x: i16, y: i16, z: i16) -> usize {
+    z as usize * field.height as usize * field.width as usize
+        + y as usize * field.width as usize
+        + x as usize
+}
+
+/// Check if coordinates are within field bounds.
+#[inline]
+pub fn ifield_in_bounds(field: &IField, x: i16, y: i16, z: i16) -> bool {
+    x >= 0 && x < field.width && y >= 0 && y < field.height && z >= 0 && z < field.depth
+}
+
+/// Set a cell value (out-of-bounds writes are ignored).
+pub fn ifield_set(field: &mut IField, x: i16, y: i16, z: i16, value: i32) {
+    if ifield_in_bounds(field, x, y, z) {
+        let idx = ifield_index_of(field, x, y, z);
+        field.cells[idx] = value;
+    }
+}
+
+/// Get a cell value.
+pub fn ifield_get(field: &IField, x: i16, y: i16, z: i16) -> Result<i32, FieldError> {
+    if ifield_in_bounds(field, x, y, z) {
+        Ok(field.cells[ifield_index_of(field, x, y, z)])
+    } else {
+        Err(FieldError::OutOfBounds)
+    }
+}
+
+/// Diffusion shift of each axis (x, y, z), as `field_axis_rates`.
+#[inline]
+pub fn ifield_axis_rates(field: &IField) -> [u8; 3] {
+    field.axis_rates.unwrap_or([field.diffusion_rate; 3])
+}
+
+/// Step the field with sequential axis-wise diffusion, as `field_step`.
+pub fn ifield_step(field: &mut IField) {
+    step(field, true);
+}
+
+/// Step the field with fused simultaneous diffusion, as `field_step_fused`.
+pub fn ifield_step_fused(field: &mut IField) {
+    step(field, false);
+}
+
+fn step(field: &mut IField, sequential: bool) {
+    let pass = DiffusionPass {
+        dims: [
+            field.width as usize,
+            field.height as usize,
+            field.depth as usize,
+        ],
+        generation: field.generation,
+        rounding: field.rounding,
+        rates: ifield_axis_rates(field),
+        sequential,
+        block: None,
+        periodic: [false; 3],
+    };
+    let conductivity = field.conductivity as i64;
+    let mut new_cells = field.cells.clone();
+    diffuse(
+        &pass,
+        &mut field.cells,
+        &mut new_cells,
+        |_, _| conductivity,
+        |_, _, _, _| {},
+    );
+    field.cells = new_cells;
+    field.generation += 1;
+}
+
+/// Signed sum of all cells (conserved by stepping).
+pub fn ifield_sum(field: &IField) -> i64 {
+    field.cells.iter().map(|&v| v as i64).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_set, field_step};
+
+    #[test]
+    fn test_signed_sum_conserved() {
+        // A dipole: positive and negative charge diffusing toward each other
+        let mut field = create_ifield(8, 6, 5, 2);
+        ifield_set(&mut field, 1, 3, 2, 1_000_000);
+        ifield_set(&mut field, 6, 3, 2, -1_500_000);
+        ifield_set(&mut field, 4, 0, 0, -7);
+        let sum = ifield_sum(&field);
+        for mode in [RoundingMode::Stochastic, RoundingMode::Hash] {
+            field.rounding = mode;
+            for step in 0..20 {
+                if step % 2 == 0 {
+                    ifield_step(&mut field);
+                } else {
+                    ifield_step_fused(&mut field);
+                }
+                assert_eq!(ifield_sum(&field), sum);
+            }
+        }
+        assert!(ifield_get(&field, 1, 3, 2).unwrap() > 0);
+        assert!(ifield_get(&field, 6, 3, 2).unwrap() < 0);
+        assert!(field
+            .cells
+            .iter()
+            .all(|&v| (-1_500_000..=1_000_000).contains(&v)));
+        assert_eq!(ifield_get(&field, 8, 0, 0), Err(FieldError::OutOfBounds));
+    }
+
+    #[test]
+    fn test_offset_encoding_equivalent() {
+        // The signed field computes what the Lua-side offset encoding did
+        const OFFSET: i64 = 1 << 30;
+        let mut signed = create_ifield(7, 5, 3, 1);
+        let mut offset = create_field_1(7, 5, 3, 1);
+        offset.cells.fill(OFFSET as u32);
+        for (x, y, z, value) in [(3, 2, 1, -400_000), (0, 0, 0, 250_000)] {
+            ifield_set(&mut signed, x, y, z, value);
+            field_set(&mut offset, x, y, z, (OFFSET + value as i64) as u32);
+        }
+        signed.rounding = RoundingMode::HalfEven;
+        offset.rounding = RoundingMode::HalfEven;
+        for _ in 0..15 {
+            ifield_step(&mut signed);
+            field_step(&mut offset);
+        }
+        let decoded: Vec<i32> = offset
+            .cells
+            .iter()
+            .map(|&v| (v as i64 - OFFSET) as i32)
+            .collect();
+        assert_eq!(signed.cells, decoded);
+    }
+}
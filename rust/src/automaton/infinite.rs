@@ -0,0 +1,280 @@
+//! Unbounded cellular automaton grid in sparse 16³ chunks.
+//!
+//! `InfiniteState` keys chunks by chunk coordinates (world coordinate >> 4,
+//! i32 world coordinates) and allocates them as patterns grow into them, so a
+//! pattern is never walled in by a grid size. Chunks with no live cell are
+//! dropped after every step.
+//!
+//! Stepping applies the birth/survival rule of `State` over the 26-cell Moore
+//! neighborhood. Only chunks holding live cells and their 26 neighbors can
+//! change, which needs births to require at least one live neighbor: bit 0 of
+//! the birth mask (B0, which would fill infinite space in one step) is ignored.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::state::Rule;
+
+/// Edge of a chunk in cells.
+pub const INF_CHUNK_EDGE: i32 = 16;
+
+/// Cells in a chunk.
+pub const INF_CHUNK_CELLS: usize = 16 * 16 * 16;
+
+/// Chunk coordinates (x, y, z), in chunks.
+pub type InfChunkKey = [i32; 3];
+
+type Chunk = Box<[u8; INF_CHUNK_CELLS]>;
+
+/// An unbounded grid of live (1) and dead (0) cells.
+#[derive(Debug, Clone, Default)]
+pub struct InfiniteState {
+    /// Allocated chunks, cells in z,y,x order. Every chunk holds a live cell.
+    pub chunks: HashMap<InfChunkKey, Chunk>,
+    pub generation: u64,
+    pub rule: Rule,
+}
+
+/// Chunk and offset in the chunk of world cell (x, y, z).
+fn locate(x: i32, y: i32, z: i32) -> (InfChunkKey, usize) {
+    let key = [x, y, z].map(|c| c.div_euclid(INF_CHUNK_EDGE));
+    let [lx, ly, lz] = [x, y, z].map(|c| c.rem_euclid(INF_CHUNK_EDGE) as usize);
+    (key, (lz * 16 + ly) * 16 + lx)
+}
+
+/// Set a cell alive or dead, allocating its chunk if needed and freeing it once
+/// its last live cell dies.
+pub fn inf_set_cell(state: &mut InfiniteState, x: i32, y: i32, z: i32, alive: bool) {
+    let (key, offset) = locate(x, y, z);
+    if alive {
+        state
+            .chunks
+            .entry(key)
+            .or_insert_with(|| Box::new([0; INF_CHUNK_CELLS]))[offset] = 1;
+    } else if let Some(chunk) = state.chunks.get_mut(&key) {
+        chunk[offset] = 0;
+        if chunk.iter().all(|&cell| cell == 0) {
+            state.chunks.remove(&key);
+        }
+    }
+}
+
+/// Whether a cell is alive.
+pub fn inf_get_cell(state: &InfiniteState, x: i32, y: i32, z: i32) -> bool {
+    let (key, offset) = locate(x, y, z);
+    state
+        .chunks
+        .get(&key)
+        .is_some_and(|chunk| chunk[offset] != 0)
+}
+
+/// Number of live cells.
+pub fn inf_population(state: &InfiniteState) -> u64 {
+    state
+        .chunks
+        .values()
+        .map(|chunk| chunk.iter().filter(|&&cell| cell != 0).count() as u64)
+        .sum()
+}
+
+/// Keys of the allocated chunks, sorted (z, then y, then x).
+pub fn inf_chunk_keys(state: &InfiniteState) -> Vec<InfChunkKey> {
+    let mut keys: Vec<_> = state.chunks.keys().copied().collect();
+    keys.sort_by_key(|&[x, y, z]| (z, y, x));
+    keys
+}
+
+/// Copy chunk `key` into `out` (z,y,x order, 1 = alive). An unallocated chunk
+/// reads as all dead. Returns false (`out` untouched) if `out` is shorter than
+/// a chunk.
+pub fn inf_extract_chunk(state: &InfiniteState, key: InfChunkKey, out: &mut [u8]) -> bool {
+    let Some(out) = out.get_mut(..INF_CHUNK_CELLS) else {
+        return false;
+    };
+    match state.chunks.get(&key) {
+        Some(chunk) => out.copy_from_slice(&chunk[..]),
+        None => out.fill(0),
+    }
+    true
+}
+
+/// Step the grid forward one generation (see the module docs).
+pub fn inf_step(state: &mut InfiniteState) {
+    let mut candidates = BTreeSet::new();
+    for &[cx, cy, cz] in state.chunks.keys() {
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    candidates.insert([cx + dx, cy + dy, cz + dz]);
+                }
+            }
+        }
+    }
+
+    let birth = state.rule.birth & !1;
+    let survival = state.rule.survival;
+    let mut next = HashMap::new();
+    // The chunk with a one-cell margin from its neighbors, 18³ cells
+    const PAD: usize = 18;
+    for key in candidates {
+        let neighbors: [Option<&Chunk>; 27] = std::array::from_fn(|i| {
+            let offset = [i % 3, i / 3 % 3, i / 9].map(|c| c as i32 - 1);
+            state
+                .chunks
+                .get(&[0, 1, 2].map(|axis| key[axis] + offset[axis]))
+        });
+        if neighbors.iter().all(Option::is_none) {
+            continue;
+        }
+        let mut padded = [0u8; PAD * PAD * PAD];
+        for (pz, z) in (-1i32..=16).enumerate() {
+            for (py, y) in (-1i32..=16).enumerate() {
+                for (px, x) in (-1i32..=16).enumerate() {
+                    // Which of the 27 chunks, and where in it
+                    let [nx, ny, nz] =
+                        [x, y, z].map(|c| (c.div_euclid(INF_CHUNK_EDGE) + 1) as usize);
+                    let Some(chunk) = neighbors[(nz * 3 + ny) * 3 + nx] else {
+                        continue;
+                    };
+                    let [lx, ly, lz] = [x, y, z].map(|c| c.rem_euclid(INF_CHUNK_EDGE) as usize);
+                    padded[(pz * PAD + py) * PAD + px] = chunk[(lz * 16 + ly) * 16 + lx];
+                }
+            }
+        }
+
+        let mut cells = Box::new([0u8; INF_CHUNK_CELLS]);
+        let mut alive_after = false;
+        for z in 0..16 {
+            for y in 0..16 {
+                for x in 0..16 {
+                    let mut count = 0;
+                    for dz in 0..3 {
+                        for dy in 0..3 {
+                            let row = ((z + dz) * PAD + y + dy) * PAD + x;
+                            count += padded[row] + padded[row + 1] + padded[row + 2];
+                        }
+                    }
+                    let center = padded[((z + 1) * PAD + y + 1) * PAD + x + 1];
+                    count -= center;
+                    let mask = if center != 0 { survival } else { birth };
+                    if mask & (1 << count) != 0 {
+                        cells[(z * 16 + y) * 16 + x] = 1;
+                        alive_after = true;
+                    }
+                }
+            }
+        }
+        if alive_after {
+            next.insert(key, cells);
+        }
+    }
+    state.chunks = next;
+    state.generation += 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::grid::{create_grid, index_of};
+    use crate::automaton::rng::SplitMix64;
+    use crate::automaton::stepping::step_automaton;
+    use crate::state::State;
+
+    #[test]
+    fn test_infinite_matches_bounded_grid_away_from_walls() {
+        let rule = Rule {
+            birth: 1 << 5 | 1 << 6,
+            survival: 1 << 4 | 1 << 5 | 1 << 6,
+        };
+        let mut bounded = State {
+            rule,
+            ..Default::default()
+        };
+        create_grid(&mut bounded, 40, 40, 40);
+        let mut infinite = InfiniteState {
+            rule,
+            ..Default::default()
+        };
+        // A random blob straddling the chunk corner at the world origin
+        let mut rng = SplitMix64::new(7);
+        for z in 16..24 {
+            for y in 16..24 {
+                for x in 16..24 {
+                    if rng.chance_256(90) {
+                        let idx = index_of(&bounded, x, y, z);
+                        bounded.cells[idx] = 1;
+                        inf_set_cell(
+                            &mut infinite,
+                            x as i32 - 20,
+                            y as i32 - 20,
+                            z as i32 - 20,
+                            true,
+                        );
+                    }
+                }
+            }
+        }
+        assert_eq!(infinite.chunks.len(), 8);
+        for _ in 0..6 {
+            step_automaton(&mut bounded);
+            inf_step(&mut infinite);
+        }
+        for z in 0..40 {
+            for y in 0..40 {
+                for x in 0..40 {
+                    let alive = bounded.cells[index_of(&bounded, x, y, z)] != 0;
+                    assert_eq!(
+                        inf_get_cell(&infinite, x as i32 - 20, y as i32 - 20, z as i32 - 20),
+                        alive
+                    );
+                }
+            }
+        }
+        let population = bounded.cells.iter().filter(|&&c| c != 0).count() as u64;
+        assert_eq!(inf_population(&infinite), population);
+    }
+
+    #[test]
+    fn test_infinite_grows_and_frees_chunks() {
+        // B1/S: every live cell seeds its whole neighborhood and dies
+        let mut state = InfiniteState {
+            rule: Rule {
+                birth: 1 << 1,
+                survival: 0,
+            },
+            ..Default::default()
+        };
+        inf_set_cell(&mut state, 1_000_000, -5, 15, true);
+        assert!(inf_get_cell(&state, 1_000_000, -5, 15));
+        for _ in 0..3 {
+            inf_step(&mut state);
+        }
+        // The pattern crossed into new chunks on every side it touches
+        assert!(state.chunks.len() > 1);
+        assert!(inf_chunk_keys(&state).contains(&[62_500, -1, 1]));
+
+        let mut out = vec![9u8; INF_CHUNK_CELLS];
+        assert!(inf_extract_chunk(&state, [0, 0, 0], &mut out));
+        assert!(out.iter().all(|&c| c == 0));
+        assert!(!inf_extract_chunk(&state, [0, 0, 0], &mut out[..10]));
+
+        // Clearing every live cell frees every chunk
+        for key in inf_chunk_keys(&state) {
+            assert!(inf_extract_chunk(&state, key, &mut out));
+            for (offset, &cell) in out.iter().enumerate() {
+                if cell != 0 {
+                    let [x, y, z] = [offset % 16, offset / 16 % 16, offset / 256].map(|c| c as i32);
+                    inf_set_cell(
+                        &mut state,
+                        key[0] * 16 + x,
+                        key[1] * 16 + y,
+                        key[2] * 16 + z,
+                        false,
+                    );
+                }
+            }
+        }
+        assert!(state.chunks.is_empty());
+        inf_step(&mut state);
+        assert_eq!(state.generation, 4);
+    }
+}
@@ -3,10 +3,22 @@
 //! Core invariant: Snapshot double-buffer preserves field_step_fused correctness.
 //! All reads from immutable generation-N snapshot, all writes to generation-N+1 buffer.
 //! Tile processing order doesn't affect result (commutative accumulation across tiles).
+//! Each tile also owns its rounding stream: its remainder accumulator starts from an
+//! offset derived from (tile coord, generation, rounding seed), never from another
+//! tile's leftover remainder, so any order or thread count gives identical output.
+//!
+//! Tiles run in parallel in batches of one color (see `tile_color`): a tile writes
+//! only its own cells and the first layer past its positive faces, so tiles of a
+//! color never write the same cell and the target needs no locking.
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 
-use std::sync::atomic::AtomicUsize;
+use rayon::prelude::*;
 
 use crate::automaton::delta::{ContractKind, ContractList, NeighborOverrides};
+use crate::automaton::field::{self, axis_scales, face_budget, pair_key, RoundingMode};
+use crate::automaton::rng::{hash_coord, mix64};
 
 /// Apply flow between one real cell and one virtual neighbor held at `virtual_value`.
 /// The real cell loses flow (or gains if gradient is negative). Mass is not conserved:
@@ -29,18 +41,22 @@ fn apply_one_sided(
     let gradient = source[src_a as usize] as i64 - virtual_value;
     let flow = compute_flow(gradient, conductivity, divisor, dt, remainder_acc);
     *consumed += flow;
-    target[dst_a as usize] = ((target[dst_a as usize] as i64) - flow) as u32;
+    target[dst_a as usize] = saturate_cell(target[dst_a as usize] as i64 - flow);
     *remainder_acc = 0;
 }
 
 pub const MAPBLOCK_SIZE: i16 = 16;
 
+/// Tile edges a StepController accepts (see `tile_size_for`). All of them
+/// align with mapblock boundaries.
+pub const TILE_SIZES: [i16; 3] = [8, 16, 32];
+
 /// 3D tile coordinate.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct TileCoord {
-    pub tx: u8,
-    pub ty: u8,
-    pub tz: u8,
+    pub tx: u16,
+    pub ty: u16,
+    pub tz: u16,
 }
 
 /// Snapshot of field state being stepped. Owned by the scheduler during a step.
@@ -51,10 +67,10 @@ pub struct IncrementalStep {
     /// Accumulating output for generation N+1 (written by tile processors).
     pub target: Vec<u32>,
 
-    /// Ordered list of tile coordinates to process, in Morton order.
+    /// Ordered list of tile coordinates to process (see `order_tiles`).
     pub tile_queue: Vec<TileCoord>,
 
-    /// Index into tile_queue: next tile to process. Atomic for future Rayon use.
+    /// Index into tile_queue: next tile to process.
     pub next_tile: AtomicUsize,
 
     /// Total number of tiles.
@@ -68,9 +84,18 @@ pub struct IncrementalStep {
     pub height: i16,
     pub depth: i16,
 
+    /// Tile edge in cells (one of `TILE_SIZES`).
+    pub tile_size: i16,
+
     /// Diffusion rate (cached).
     pub diffusion_rate: u8,
 
+    /// Per-axis diffusion shifts (x, y, z), cached from `field_axis_rates`.
+    pub axis_rates: [u8; 3],
+
+    /// Wrapping axes (x, y, z), cached from `Field::periodic`.
+    pub periodic: [bool; 3],
+
     /// Sparse per-pair contract overrides. Key: (owner_idx, neighbor_idx).
     /// Empty for fully-modal fields.
     pub delta_overrides: NeighborOverrides,
@@ -83,26 +108,54 @@ pub struct IncrementalStep {
     /// zone's cadence for zone-selective steps. Scales flow proportionally so the
     /// physical time constant is preserved across different cadences.
     pub dt: i64,
+
+    /// Seed for the per-tile rounding streams (see `tile_rounding_offset`).
+    pub rounding_seed: u64,
+
+    /// Rounding of fractional flows, cached from the field. Stochastic uses the
+    /// per-tile accumulator; the per-pair modes (Truncate, Hash, HalfEven) depend
+    /// only on the pair and generation, so with them a full step is bit-identical
+    /// to `field_step_fused`.
+    pub rounding: RoundingMode,
+
+    /// Tile left half-done when a tick's budget ran out mid-tile, or None.
+    pub partial_tile: Option<TileCursor>,
+
+    /// Largest gradient magnitude among each tile's pairs, in `tile_index`
+    /// order, when tracking activity (see `activity`); otherwise empty.
+    pub tile_gradients: Vec<AtomicU32>,
+}
+
+/// Resume point inside a partially processed tile.
+#[derive(Clone, Copy, Debug)]
+pub struct TileCursor {
+    /// Index into `tile_queue` of the tile being processed.
+    pub tile_idx: usize,
+    /// Next row to process (see `process_tile_rows`).
+    pub row: usize,
+    /// The tile's remainder accumulator after the rows already processed.
+    pub remainder_acc: i64,
 }
 
 /// Interleave bits of x, y, z to produce a Morton code.
-/// For tile indices up to 255 (supports fields up to 4096 per axis), u32 output suffices.
-fn morton_encode(x: u8, y: u8, z: u8) -> u32 {
-    fn spread_bits(v: u8) -> u32 {
-        let mut x = v as u32;
-        x = (x | (x << 16)) & 0x030000FF;
-        x = (x | (x << 8)) & 0x0300F00F;
-        x = (x | (x << 4)) & 0x030C30C3;
-        x = (x | (x << 2)) & 0x09249249;
+/// Tile indices are at most 16 bits (i16 extents in tiles of 8 or more cells
+/// need 12), so the 48-bit code fits a u64.
+fn morton_encode(x: u16, y: u16, z: u16) -> u64 {
+    fn spread_bits(v: u16) -> u64 {
+        let mut x = v as u64;
+        x = (x | (x << 32)) & 0x0000_FFFF_0000_FFFF;
+        x = (x | (x << 16)) & 0x00FF_0000_FF00_00FF;
+        x = (x | (x << 8)) & 0xF00F_00F0_0F00_F00F;
+        x = (x | (x << 4)) & 0x30C3_0C30_C30C_30C3;
+        x = (x | (x << 2)) & 0x9249_2492_4924_9249;
         x
     }
     spread_bits(x) | (spread_bits(y) << 1) | (spread_bits(z) << 2)
 }
-// TODO: verify that a 1500x1500x500 field is valid
 
 /// Build a list of all tile coordinates, sorted by Morton code.
-pub fn build_tile_queue(tiles_x: u8, tiles_y: u8, tiles_z: u8) -> Vec<TileCoord> {
-    let mut tiles: Vec<(u32, TileCoord)> = Vec::new();
+pub fn build_tile_queue(tiles_x: u16, tiles_y: u16, tiles_z: u16) -> Vec<TileCoord> {
+    let mut tiles: Vec<(u64, TileCoord)> = Vec::new();
 
     for tz in 0..tiles_z {
         for ty in 0..tiles_y {
@@ -118,6 +171,128 @@ pub fn build_tile_queue(tiles_x: u8, tiles_y: u8, tiles_z: u8) -> Vec<TileCoord>
     tiles.into_iter().map(|(_, coord)| coord).collect()
 }
 
+/// Tiles along x, y, z for a field of `dims` cells cut into `tile_size`³ tiles.
+pub fn tile_counts(dims: [i16; 3], tile_size: i16) -> [usize; 3] {
+    dims.map(|extent| (extent as usize).div_ceil(tile_size as usize))
+}
+
+/// The tile edge to use for a field of `dims` cells when `requested` is asked
+/// for: `requested` if it is one of `TILE_SIZES`, else `MAPBLOCK_SIZE`, grown
+/// to the next size while an axis would need more than 256 tiles, which keeps
+/// the tile queue and per-tile bookkeeping of very long fields small. Longer
+/// fields still step, in more tiles of the largest size.
+pub fn tile_size_for(requested: i16, dims: [i16; 3]) -> i16 {
+    let size = if TILE_SIZES.contains(&requested) {
+        requested
+    } else {
+        MAPBLOCK_SIZE
+    };
+    let fits = |size: &&i16| tile_counts(dims, **size).iter().all(|&n| n <= 256);
+    TILE_SIZES
+        .iter()
+        .filter(|&&candidate| candidate >= size)
+        .find(fits)
+        .copied()
+        .unwrap_or(TILE_SIZES[TILE_SIZES.len() - 1])
+}
+
+/// Tiles along x, y, z of the field being stepped.
+pub fn step_tile_counts(step: &IncrementalStep) -> [usize; 3] {
+    tile_counts([step.width, step.height, step.depth], step.tile_size)
+}
+
+/// Position of `tile` among `tiles` (the counts along x, y, z), x fastest.
+pub fn tile_index(tile: TileCoord, tiles: [usize; 3]) -> usize {
+    (tile.tz as usize * tiles[1] + tile.ty as usize) * tiles[0] + tile.tx as usize
+}
+
+/// Parallel color of a tile among `tiles` (the counts along x, y, z), in
+/// `0..27`: tiles of one color write disjoint cells. Along each axis tiles
+/// alternate between two colors, since a tile writes one layer into its +axis
+/// neighbor only; on a periodic axis with an odd number of tiles the last
+/// tile, which writes into tile 0, gets a third.
+pub fn tile_color(tile: TileCoord, tiles: [usize; 3], periodic: [bool; 3]) -> u8 {
+    let coords = [tile.tx, tile.ty, tile.tz];
+    (0..3).rev().fold(0, |color, axis| {
+        let count = tiles[axis];
+        let coord = coords[axis] as usize;
+        let wraps = periodic[axis] && count > 1 && count % 2 == 1 && coord == count - 1;
+        color * 3 + if wraps { 2 } else { (coord % 2) as u8 }
+    })
+}
+
+/// Distance in tiles from `tile` to the tile `focus` (tile coordinates, among
+/// `tiles`), along the axis where it is largest; the short way round on a
+/// periodic axis.
+pub fn tile_focus_ring(
+    tile: TileCoord,
+    focus: [usize; 3],
+    tiles: [usize; 3],
+    periodic: [bool; 3],
+) -> u16 {
+    let coords = [tile.tx, tile.ty, tile.tz];
+    (0..3)
+        .map(|axis| {
+            let dist = (coords[axis] as usize).abs_diff(focus[axis]);
+            if periodic[axis] {
+                dist.min(tiles[axis] - dist) as u16
+            } else {
+                dist as u16
+            }
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Sort tiles into processing order: by `tile_color`, in Morton order within a
+/// color. With a focus cell, tiles are first grouped into rings of increasing
+/// `tile_focus_ring` from the tile holding it, each sorted that way, so the
+/// tiles around the focus are done first and parallel batches still find runs
+/// of one color.
+pub fn order_tiles(
+    tiles: &mut [TileCoord],
+    dims: [i16; 3],
+    tile_size: i16,
+    periodic: [bool; 3],
+    focus: Option<[i16; 3]>,
+) {
+    let counts = tile_counts(dims, tile_size);
+    let focus = focus.map(|cell| {
+        [0, 1, 2].map(|axis| (cell[axis].clamp(0, dims[axis] - 1) / tile_size) as usize)
+    });
+    tiles.sort_unstable_by_key(|&tile| {
+        let ring = focus.map_or(0, |focus| tile_focus_ring(tile, focus, counts, periodic));
+        let color = tile_color(tile, counts, periodic);
+        (ring, color, morton_encode(tile.tx, tile.ty, tile.tz))
+    });
+}
+
+/// End of the batch of at most `max_len` queue tiles starting at `start` that
+/// share its color (see `process_tile_batch`).
+pub fn tile_batch_end(step: &IncrementalStep, start: usize, max_len: usize) -> usize {
+    let tiles = step_tile_counts(step);
+    let color = |tile| tile_color(tile, tiles, step.periodic);
+    let first = color(step.tile_queue[start]);
+    let limit = (start + max_len).min(step.total_tiles);
+    start
+        + step.tile_queue[start..limit]
+            .iter()
+            .take_while(|&&tile| color(tile) == first)
+            .count()
+}
+
+/// Starting value of a tile's remainder accumulator, in `0..divisor`.
+///
+/// Depends only on the tile coordinate, the generation being produced, and the seed,
+/// so a tile rounds the same way whichever thread runs it and whenever it is run.
+/// A random start (rather than 0) also spreads the first rounding-up of each tile
+/// instead of always biasing the tile's first pairs towards truncation.
+pub fn tile_rounding_offset(seed: u64, tile: TileCoord, generation: u64, divisor: i64) -> i64 {
+    let (tx, ty, tz) = (tile.tx as i16, tile.ty as i16, tile.tz as i16);
+    let hash = hash_coord(seed ^ mix64(generation), tx, ty, tz);
+    (hash % divisor as u64) as i64
+}
+
 /// Compute linear index in field cells using row-major z/y/x layout.
 #[inline]
 fn field_index(field: &IncrementalStep, x: i16, y: i16, z: i16) -> usize {
@@ -129,13 +304,12 @@ fn field_index(field: &IncrementalStep, x: i16, y: i16, z: i16) -> usize {
 /// Compute diffusion flow: ΔΦ = (ΔV * C_mat) / (N_base * S_face * 2^shift * 2^16)
 /// Uses stochastic rounding via remainder accumulator for realistic small-scale diffusion.
 ///
-/// Known issue: vacuum decay. The remainder accumulator is shared across all
-/// cells in a tile. When it builds up from non-zero gradients and then encounters a
-/// zero-gradient pair (two adjacent cells both at zero), stochastic rounding can produce a
-/// flow of ±1 between them. The unsigned wrapping cast in process_tile then turns a -1 into
-/// u32::MAX (2^32 - 1), creating massive spontaneous mass. This mirrors quantum vacuum
-/// fluctuations: a true zero-energy state is physically impossible, and achieving one in-game
-/// triggers an energy release. To be addressed in a future physics engine revision.
+/// The accumulator is shared by every pair of a tile, so a rounded-up unit can land
+/// on a pair whose donor has almost nothing left: a cell at 1 next to six empty
+/// neighbors could be asked for six units. Callers limit each spatial flow with
+/// `clamp_to_donor`, which keeps every cell at or above the minimum quantum, and
+/// write cells back through `saturate_cell` instead of a wrapping cast.
+/// A zero gradient never rounds: the accumulator stays below `divisor` between calls.
 #[inline]
 pub fn compute_flow(
     gradient: i64,
@@ -173,6 +347,36 @@ pub fn compute_flow(
     }
 }
 
+/// `compute_flow` for the stochastic mode; the per-pair modes round exactly as
+/// the field steppers do (`field::compute_flow`, keyed by `key`), with `dt`
+/// folded into the conductivity. Stochastic rounding depends on the order
+/// pairs are visited in, so only the per-pair modes match the fused stepper
+/// exactly.
+#[inline(always)]
+fn pair_flow(
+    rounding: RoundingMode,
+    key: u64,
+    gradient: i64,
+    conductivity: i64,
+    divisor: i64,
+    dt: i64,
+    remainder_acc: &mut i64,
+) -> i64 {
+    match rounding {
+        RoundingMode::Stochastic => {
+            compute_flow(gradient, conductivity, divisor, dt, remainder_acc)
+        }
+        mode => field::compute_flow(
+            gradient,
+            conductivity * dt,
+            divisor,
+            mode,
+            key,
+            remainder_acc,
+        ),
+    }
+}
+
 /// Resolve the flow for a spatial pair, checking the override map when `check` is true.
 #[inline(always)]
 fn resolve_pair(
@@ -184,42 +388,190 @@ fn resolve_pair(
     conductivity: i64,
     divisor: i64,
     dt: i64,
+    rounding: (RoundingMode, u64),
     remainder_acc: &mut i64,
 ) -> i64 {
+    let (mode, key) = rounding;
     if check {
         if let Some(kind) = overrides.get_mut(&(idx_a, idx_b)) {
             return kind.apply(gradient, conductivity, divisor, remainder_acc,
-                |g, c, d, acc| compute_flow(g, c, d, dt, acc));
+                |g, c, d, acc| pair_flow(mode, key, g, c, d, dt, acc));
         }
     }
-    compute_flow(gradient, conductivity, divisor, dt, remainder_acc)
+    pair_flow(
+        mode,
+        key,
+        gradient,
+        conductivity,
+        divisor,
+        dt,
+        remainder_acc,
+    )
+}
+
+/// Limit a spatial pair flow to what its donor can spare (positive = a gives to b).
+///
+/// A cell takes part in at most six spatial pairs per step, so granting each pair
+/// at most its `face_budget` (a sixth of the generation-N value above the quantum
+/// of 1) means no combination of outflows can take the cell below 1. Budgets are
+/// read from the frozen `source`, so the result does not depend on tile order or
+/// thread count, and the field steppers apply the same clamp. Within the
+/// stability bound this only ever trims a rounded-up unit from a nearly empty donor.
+#[inline(always)]
+pub fn clamp_to_donor(flow: i64, source_a: u32, source_b: u32) -> i64 {
+    let spare = |value: u32| face_budget(value as u64) as i64;
+    flow.clamp(-spare(source_b), spare(source_a))
+}
+
+/// Store a cell value, saturating instead of wrapping if it leaves the u32 range.
+///
+/// With `clamp_to_donor` spatial pairs stay in range; this guards the one-sided
+/// contract post-pass and unstable `dt` (see `compute_flow`'s debug_assert) from
+/// turning -1 into u32::MAX.
+#[inline(always)]
+pub fn saturate_cell(value: i64) -> u32 {
+    value.clamp(0, u32::MAX as i64) as u32
+}
+
+/// Coordinate of the +axis neighbor of `coord`: the first layer after the last
+/// one on a periodic axis, None at a closed boundary.
+#[inline(always)]
+fn next_coord(coord: i16, extent: i16, periodic: bool) -> Option<i16> {
+    if coord + 1 < extent {
+        Some(coord + 1)
+    } else if periodic {
+        Some(0)
+    } else {
+        None
+    }
 }
 
 /// Apply a resolved flow symmetrically to both sides of a spatial pair.
 #[inline(always)]
 fn apply_pair(target: &mut [u32], idx_a: usize, idx_b: usize, flow: i64) {
-    target[idx_a] = ((target[idx_a] as i64) - flow) as u32;
-    target[idx_b] = ((target[idx_b] as i64) + flow) as u32;
+    target[idx_a] = saturate_cell(target[idx_a] as i64 - flow);
+    target[idx_b] = saturate_cell(target[idx_b] as i64 + flow);
+}
+
+/// View a cell buffer as atomics, so the tiles of a batch can write it from
+/// several threads through a shared reference.
+fn atomic_cells(cells: &mut [u32]) -> &[AtomicU32] {
+    // SAFETY: AtomicU32 has the size and alignment of u32, and the exclusive
+    // borrow rules out non-atomic access while the view lives
+    unsafe { &*(cells as *mut [u32] as *const [AtomicU32]) }
+}
+
+/// Add `delta` to a target cell, saturating. No two threads write a cell in
+/// the same batch, so a relaxed load and store suffice.
+#[inline(always)]
+fn add_to_cell(target: &[AtomicU32], idx: usize, delta: i64) {
+    let value = target[idx].load(Ordering::Relaxed) as i64 + delta;
+    target[idx].store(saturate_cell(value), Ordering::Relaxed);
 }
 
 /// Process a single 16³ tile. Computes phase C (diffusion flows).
 /// Formula: ΔΦ = (ΔV * C_mat) / (N_base * S_face * 2^shift * 2^16)
 /// Stability: divisor >= 7 ensures no cell loses more than 1/7 of its value per step.
 pub fn process_tile(step: &mut IncrementalStep, tile: TileCoord) {
-    let x_start = tile.tx as i16 * MAPBLOCK_SIZE;
-    let y_start = tile.ty as i16 * MAPBLOCK_SIZE;
-    let z_start = tile.tz as i16 * MAPBLOCK_SIZE;
+    let mut remainder_acc = tile_start_remainder(step, tile);
+    let rows = tile_row_count(step, tile);
+    process_tile_rows(step, tile, 0..rows, &mut remainder_acc);
+}
 
-    let x_end = (x_start + MAPBLOCK_SIZE).min(step.width);
-    let y_end = (y_start + MAPBLOCK_SIZE).min(step.height);
-    let z_end = (z_start + MAPBLOCK_SIZE).min(step.depth);
+/// Flow divisor for the step's axis rates (shared by all axes, see `axis_scales`).
+#[inline]
+fn step_divisor(step: &IncrementalStep) -> i64 {
+    axis_scales(step.axis_rates).1
+}
 
-    let shift = step.diffusion_rate as u32;
-    // Conductivity is fixed at ~1.0 (fully conductive, scaled by 2^16)
-    let conductivity = 65535i64;
-    let divisor = (7i64 << shift) << 16;
+/// Initial remainder accumulator for `tile` (its rounding stream offset).
+pub fn tile_start_remainder(step: &IncrementalStep, tile: TileCoord) -> i64 {
+    tile_rounding_offset(step.rounding_seed, tile, step.target_generation, step_divisor(step))
+}
+
+/// Number of x-rows in `tile` (fewer than the tile size squared for edge tiles).
+pub fn tile_row_count(step: &IncrementalStep, tile: TileCoord) -> usize {
+    let size = step.tile_size;
+    let rows_y = (step.height - tile.ty as i16 * size).min(size);
+    let rows_z = (step.depth - tile.tz as i16 * size).min(size);
+    rows_y as usize * rows_z as usize
+}
+
+/// Process the x-rows `rows` of `tile`, where row `r` is the row at
+/// y = r % rows_y, z = r / rows_y within the tile (the order `process_tile` uses).
+///
+/// Processing a tile's rows in consecutive ranges, threading the same
+/// `remainder_acc` through, gives exactly the result of one `process_tile` call,
+/// so a tile can be split across ticks at any row boundary.
+pub fn process_tile_rows(
+    step: &mut IncrementalStep,
+    tile: TileCoord,
+    rows: Range<usize>,
+    remainder_acc: &mut i64,
+) {
+    let mut target = std::mem::take(&mut step.target);
+    let mut overrides = std::mem::take(&mut step.delta_overrides);
+    tile_rows(
+        step,
+        atomic_cells(&mut target),
+        &mut overrides,
+        tile,
+        rows,
+        remainder_acc,
+    );
+    step.target = target;
+    step.delta_overrides = overrides;
+}
+
+/// Process the queue tiles `tiles` whole, in parallel on the current Rayon
+/// pool. They must share a color (see `tile_batch_end`), and the step must
+/// have no overrides: the tiles cannot share the map mutably.
+pub fn process_tile_batch(step: &mut IncrementalStep, tiles: Range<usize>) {
+    debug_assert!(step.delta_overrides.is_empty());
+    let mut target = std::mem::take(&mut step.target);
+    let cells = atomic_cells(&mut target);
+    let shared = &*step;
+    shared.tile_queue[tiles].par_iter().for_each(|&tile| {
+        let mut remainder_acc = tile_start_remainder(shared, tile);
+        let rows = tile_row_count(shared, tile);
+        let mut no_overrides = NeighborOverrides::new();
+        tile_rows(
+            shared,
+            cells,
+            &mut no_overrides,
+            tile,
+            0..rows,
+            &mut remainder_acc,
+        );
+    });
+    step.target = target;
+}
+
+/// `process_tile_rows` on a shared target, with the overrides held apart.
+fn tile_rows(
+    step: &IncrementalStep,
+    target: &[AtomicU32],
+    overrides: &mut NeighborOverrides,
+    tile: TileCoord,
+    rows: Range<usize>,
+    remainder_acc: &mut i64,
+) {
+    let size = step.tile_size;
+    let x_start = tile.tx as i16 * size;
+    let y_start = tile.ty as i16 * size;
+    let z_start = tile.tz as i16 * size;
+
+    let x_end = x_start + size.min(step.width - x_start);
+    let rows_y = (step.height - y_start).min(size) as usize;
+
+    // Conductivity is fixed at ~1.0 (fully conductive, scaled by 2^16), then
+    // scaled per axis so all three share the divisor
+    let (scales, divisor) = axis_scales(step.axis_rates);
+    let conductivities = scales.map(|scale| 65535i64 * scale);
     let dt = step.dt;
-    let mut remainder_acc = 0i64;
+    let rounding = step.rounding;
+    // Pair keys use the source generation, as in the field steppers
+    let generation = step.target_generation - 1;
 
     // Phase A: Consume deltas (no-op for current diffusion)
     // Future hook: consume persistent cross-generation deltas
@@ -229,79 +581,99 @@ pub fn process_tile(step: &mut IncrementalStep, tile: TileCoord) {
 
     // Phase C: Compute and apply diffusion flows
     // Owner-writes-positive: cell (x, y, z) owns the pair with (x+1, y, z), (x, y+1, z), (x, y, z+1)
-    // This prevents double-counting at tile boundaries.
-
-    for z in z_start..z_end {
-        for y in y_start..y_end {
-            for x in x_start..x_end {
-                let idx_a = field_index(step, x, y, z);
-                let check_override = step.cell_has_override[idx_a];
-
-                // X-axis pair: (x, y, z) with (x+1, y, z) or mirror at boundary
-                if x + 1 < step.width {
-                    let idx_b = field_index(step, x + 1, y, z);
-                    let gradient = step.source[idx_a] as i64 - step.source[idx_b] as i64;
-                    let flow = resolve_pair(
-                        &mut step.delta_overrides,
-                        check_override,
-                        idx_a,
-                        idx_b,
-                        gradient,
-                        conductivity,
-                        divisor,
-                        dt,
-                        &mut remainder_acc,
-                    );
-                    apply_pair(&mut step.target, idx_a, idx_b, flow);
-                } else {
-                    let flow = compute_flow(0, conductivity, divisor, dt, &mut remainder_acc);
-                    step.target[idx_a] = ((step.target[idx_a] as i64) - flow) as u32;
-                }
-
-                // Y-axis pair: (x, y, z) with (x, y+1, z) or mirror at boundary
-                if y + 1 < step.height {
-                    let idx_b = field_index(step, x, y + 1, z);
-                    let gradient = step.source[idx_a] as i64 - step.source[idx_b] as i64;
-                    let flow = resolve_pair(
-                        &mut step.delta_overrides,
-                        check_override,
-                        idx_a,
-                        idx_b,
-                        gradient,
-                        conductivity,
-                        divisor,
-                        dt,
-                        &mut remainder_acc,
-                    );
-                    apply_pair(&mut step.target, idx_a, idx_b, flow);
-                } else {
-                    let flow = compute_flow(0, conductivity, divisor, dt, &mut remainder_acc);
-                    step.target[idx_a] = ((step.target[idx_a] as i64) - flow) as u32;
-                }
-
-                // Z-axis pair: (x, y, z) with (x, y, z+1) or mirror at boundary
-                if z + 1 < step.depth {
-                    let idx_b = field_index(step, x, y, z + 1);
-                    let gradient = step.source[idx_a] as i64 - step.source[idx_b] as i64;
-                    let flow = resolve_pair(
-                        &mut step.delta_overrides,
-                        check_override,
-                        idx_a,
-                        idx_b,
-                        gradient,
-                        conductivity,
-                        divisor,
-                        dt,
-                        &mut remainder_acc,
-                    );
-                    apply_pair(&mut step.target, idx_a, idx_b, flow);
-                } else {
-                    let flow = compute_flow(0, conductivity, divisor, dt, &mut remainder_acc);
-                    step.target[idx_a] = ((step.target[idx_a] as i64) - flow) as u32;
-                }
+    // This prevents double-counting at tile boundaries. On a periodic axis the last
+    // layer owns the pair that wraps to the first.
+
+    let mut max_gradient = 0u64;
+    for row in rows {
+        let y = y_start + (row % rows_y) as i16;
+        let z = z_start + (row / rows_y) as i16;
+        for x in x_start..x_end {
+            let idx_a = field_index(step, x, y, z);
+            let check_override = step.cell_has_override[idx_a];
+
+            // X-axis pair with (x+1, y, z), wrapping if periodic; mirror at a closed boundary
+            if let Some(nx) = next_coord(x, step.width, step.periodic[0]) {
+                let idx_b = field_index(step, nx, y, z);
+                let gradient = step.source[idx_a] as i64 - step.source[idx_b] as i64;
+                max_gradient = max_gradient.max(gradient.unsigned_abs());
+                let flow = resolve_pair(
+                    overrides,
+                    check_override,
+                    idx_a,
+                    idx_b,
+                    gradient,
+                    conductivities[0],
+                    divisor,
+                    dt,
+                    (rounding, pair_key(generation, idx_a, 0)),
+                    remainder_acc,
+                );
+                let flow = clamp_to_donor(flow, step.source[idx_a], step.source[idx_b]);
+                add_to_cell(target, idx_a, -flow);
+                add_to_cell(target, idx_b, flow);
+            } else {
+                let flow = compute_flow(0, conductivities[0], divisor, dt, remainder_acc);
+                add_to_cell(target, idx_a, -flow);
+            }
+
+            // Y-axis pair with (x, y+1, z), wrapping if periodic; mirror at a closed boundary
+            if let Some(ny) = next_coord(y, step.height, step.periodic[1]) {
+                let idx_b = field_index(step, x, ny, z);
+                let gradient = step.source[idx_a] as i64 - step.source[idx_b] as i64;
+                max_gradient = max_gradient.max(gradient.unsigned_abs());
+                let flow = resolve_pair(
+                    overrides,
+                    check_override,
+                    idx_a,
+                    idx_b,
+                    gradient,
+                    conductivities[1],
+                    divisor,
+                    dt,
+                    (rounding, pair_key(generation, idx_a, 1)),
+                    remainder_acc,
+                );
+                let flow = clamp_to_donor(flow, step.source[idx_a], step.source[idx_b]);
+                add_to_cell(target, idx_a, -flow);
+                add_to_cell(target, idx_b, flow);
+            } else {
+                let flow = compute_flow(0, conductivities[1], divisor, dt, remainder_acc);
+                add_to_cell(target, idx_a, -flow);
+            }
+
+            // Z-axis pair with (x, y, z+1), wrapping if periodic; mirror at a closed boundary
+            if let Some(nz) = next_coord(z, step.depth, step.periodic[2]) {
+                let idx_b = field_index(step, x, y, nz);
+                let gradient = step.source[idx_a] as i64 - step.source[idx_b] as i64;
+                max_gradient = max_gradient.max(gradient.unsigned_abs());
+                let flow = resolve_pair(
+                    overrides,
+                    check_override,
+                    idx_a,
+                    idx_b,
+                    gradient,
+                    conductivities[2],
+                    divisor,
+                    dt,
+                    (rounding, pair_key(generation, idx_a, 2)),
+                    remainder_acc,
+                );
+                let flow = clamp_to_donor(flow, step.source[idx_a], step.source[idx_b]);
+                add_to_cell(target, idx_a, -flow);
+                add_to_cell(target, idx_b, flow);
+            } else {
+                let flow = compute_flow(0, conductivities[2], divisor, dt, remainder_acc);
+                add_to_cell(target, idx_a, -flow);
             }
         }
     }
+    if let Some(slot) = step
+        .tile_gradients
+        .get(tile_index(tile, step_tile_counts(step)))
+    {
+        slot.fetch_max(max_gradient.min(u32::MAX as u64) as u32, Ordering::Relaxed);
+    }
 }
 
 /// Process all ContractList entries after the tile pass, using the frozen source snapshot.
@@ -0,0 +1,201 @@
+//! Lenia-style continuous automaton on the integer field.
+//!
+//! Lenia replaces Life's neighbor count with a smooth convolution and its
+//! birth/survival sets with a growth curve. This is a fixed-point
+//! approximation over the field's u32 cells, where `LENIA_ONE` stands for a
+//! fully alive cell:
+//!
+//! 1. The potential of a cell is the kernel-weighted mean of its neighborhood,
+//!    every value capped at `LENIA_ONE`, so it lies in `0..=LENIA_ONE`. The
+//!    kernel is radial: `kernel[d]` weighs the cells at (rounded) Euclidean
+//!    distance `d`, up to the radius.
+//! 2. The growth curve maps the potential to a change in
+//!    `-LENIA_ONE..=LENIA_ONE`, interpolating linearly between table entries
+//!    spread evenly over `0..=LENIA_ONE`.
+//! 3. Each cell adds its growth shifted right by `dt_shift` (time step
+//!    `1 / 2^dt_shift`) and is clamped to `1..=LENIA_ONE`.
+//!
+//! Periodic axes wrap; elsewhere cells beyond the field count as 0. Lenia does
+//! not conserve mass: diffusion, sources, advection, boundaries and phases are
+//! not applied, but the protection mask still keeps protected cells from
+//! growing.
+
+use super::field::Field;
+use super::protect::apply_protection;
+
+/// Field value of a fully alive cell (1.0 in 16.16 fixed point).
+pub const LENIA_ONE: u32 = 1 << 16;
+
+/// Largest kernel radius (a radius r reads (2r+1)^3 cells per cell).
+pub const MAX_LENIA_RADIUS: u8 = 12;
+
+/// A validated Lenia kernel and growth curve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeniaRule {
+    /// Neighborhood offsets (dx, dy, dz) with a nonzero weight.
+    taps: Vec<([i16; 3], u64)>,
+    total_weight: u64,
+    growth: Vec<i32>,
+    dt_shift: u8,
+}
+
+impl LeniaRule {
+    /// A rule from a radial kernel profile (`radius + 1` weights, by distance)
+    /// and a growth table (at least 2 entries, each within
+    /// `-LENIA_ONE..=LENIA_ONE`). None if the radius is 0 or above
+    /// `MAX_LENIA_RADIUS`, the profile has the wrong length or no weight,
+    /// `dt_shift` is above 16, or the growth table is too short or out of range.
+    pub fn new(radius: u8, kernel: &[u16], growth: &[i32], dt_shift: u8) -> Option<Self> {
+        if radius == 0 || radius > MAX_LENIA_RADIUS || kernel.len() != radius as usize + 1 {
+            return None;
+        }
+        let one = LENIA_ONE as i32;
+        if growth.len() < 2 || growth.iter().any(|g| !(-one..=one).contains(g)) || dt_shift > 16 {
+            return None;
+        }
+        let r = radius as i16;
+        let mut taps = Vec::new();
+        for dz in -r..=r {
+            for dy in -r..=r {
+                for dx in -r..=r {
+                    let d2 = (dx * dx + dy * dy + dz * dz) as u32;
+                    let weight = kernel.get(round_sqrt(d2) as usize).copied().unwrap_or(0);
+                    if weight != 0 {
+                        taps.push(([dx, dy, dz], weight as u64));
+                    }
+                }
+            }
+        }
+        let total_weight = taps.iter().map(|&(_, w)| w).sum();
+        if total_weight == 0 {
+            return None;
+        }
+        Some(LeniaRule {
+            taps,
+            total_weight,
+            growth: growth.to_vec(),
+            dt_shift,
+        })
+    }
+
+    /// Growth for a potential in `0..=LENIA_ONE`.
+    pub fn growth_at(&self, potential: u32) -> i32 {
+        let segments = (self.growth.len() - 1) as u64;
+        let pos = potential.min(LENIA_ONE) as u64 * segments;
+        let (idx, frac) = ((pos >> 16) as usize, (pos & 0xFFFF) as i64);
+        let lo = self.growth[idx] as i64;
+        let hi = self.growth.get(idx + 1).map_or(lo, |&g| g as i64);
+        (lo + (((hi - lo) * frac) >> 16)) as i32
+    }
+}
+
+/// Nearest integer to the square root of `n`.
+fn round_sqrt(n: u32) -> u32 {
+    let mut root = (n as f64).sqrt() as u32;
+    while root * root > n {
+        root -= 1;
+    }
+    // (root + 0.5)^2 = root^2 + root + 0.25
+    if n - root * root > root {
+        root + 1
+    } else {
+        root
+    }
+}
+
+/// Step the field one generation under `rule`.
+pub fn field_step_lenia(field: &mut Field, rule: &LeniaRule) {
+    let dims = [field.width, field.height, field.depth];
+    let (w, h) = (dims[0] as usize, dims[1] as usize);
+    let source: Vec<u32> = field.cells.iter().map(|&v| v.min(LENIA_ONE)).collect();
+    let mut next = field.cells.clone();
+
+    for (idx, value) in next.iter_mut().enumerate() {
+        let here = [
+            (idx % w) as i16,
+            (idx / w % h) as i16,
+            (idx / (w * h)) as i16,
+        ];
+        let mut weighted = 0u64;
+        'taps: for &(offset, weight) in &rule.taps {
+            let mut at = [0usize; 3];
+            for axis in 0..3 {
+                let mut c = here[axis] as i32 + offset[axis] as i32;
+                let len = dims[axis] as i32;
+                if field.periodic[axis] {
+                    c = c.rem_euclid(len);
+                } else if !(0..len).contains(&c) {
+                    continue 'taps;
+                }
+                at[axis] = c as usize;
+            }
+            weighted += weight * source[at[0] + at[1] * w + at[2] * w * h] as u64;
+        }
+        let potential = (weighted / rule.total_weight) as u32;
+        let grown = source[idx] as i64 + (rule.growth_at(potential) >> rule.dt_shift) as i64;
+        *value = grown.clamp(1, LENIA_ONE as i64) as u32;
+    }
+
+    apply_protection(&mut field.protection, &field.cells, &mut next);
+    field.cells = next;
+    field.generation += 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_get, field_set};
+
+    const ONE: i32 = LENIA_ONE as i32;
+
+    #[test]
+    fn test_rule_validation_and_growth_curve() {
+        assert!(LeniaRule::new(0, &[1], &[0, 0], 0).is_none());
+        assert!(LeniaRule::new(2, &[0, 1], &[0, 0], 0).is_none());
+        assert!(LeniaRule::new(1, &[0, 0], &[0, 0], 0).is_none());
+        assert!(LeniaRule::new(1, &[0, 1], &[0], 0).is_none());
+        assert!(LeniaRule::new(1, &[0, 1], &[0, ONE + 1], 0).is_none());
+        assert!(LeniaRule::new(MAX_LENIA_RADIUS + 1, &[1; 14], &[0, 0], 0).is_none());
+
+        let rule = LeniaRule::new(1, &[0, 1], &[-ONE, ONE], 0).unwrap();
+        // Face neighbors are at distance 1; edges (1.41) round to 1, corners
+        // (1.73) to 2
+        assert_eq!(rule.taps.len(), 18);
+        assert_eq!(rule.growth_at(0), -ONE);
+        assert_eq!(rule.growth_at(LENIA_ONE / 2), 0);
+        assert_eq!(rule.growth_at(LENIA_ONE), ONE);
+        assert_eq!(round_sqrt(2), 1);
+        assert_eq!(round_sqrt(3), 2);
+        assert_eq!(round_sqrt(12), 3);
+    }
+
+    #[test]
+    fn test_uniform_periodic_field_at_fixed_point() {
+        let mut field = create_field_1(6, 6, 6, 1);
+        field.periodic = [true; 3];
+        field.cells.fill(LENIA_ONE / 2);
+        // G(U) = 2U - 1 vanishes at U = 1/2
+        let rule = LeniaRule::new(2, &[0, 2, 1], &[-ONE, ONE], 2).unwrap();
+        field_step_lenia(&mut field, &rule);
+        assert!(field.cells.iter().all(|&v| v == LENIA_ONE / 2));
+        assert_eq!(field.generation, 1);
+    }
+
+    #[test]
+    fn test_growth_and_decay() {
+        let mut field = create_field_1(5, 5, 5, 1);
+        field_set(&mut field, 2, 2, 2, LENIA_ONE);
+        // Grow where the neighborhood is dense, decay where it is empty
+        let rule = LeniaRule::new(1, &[1, 1], &[-ONE, -ONE, ONE], 1).unwrap();
+        field_step_lenia(&mut field, &rule);
+        // The lone cell's neighborhood is nearly empty: it decays by half
+        let center = field_get(&field, 2, 2, 2).unwrap().get();
+        assert!(center < LENIA_ONE && center > LENIA_ONE / 4);
+        assert_eq!(field_get(&field, 0, 0, 0).unwrap().get(), 1);
+
+        field.cells.fill(LENIA_ONE - 1000);
+        field.periodic = [true; 3];
+        field_step_lenia(&mut field, &rule);
+        assert!(field.cells.iter().all(|&v| v == LENIA_ONE));
+    }
+}
@@ -0,0 +1,248 @@
+//! Mutex-guarded State for handles used from several threads.
+//!
+//! Handles are single-threaded by contract, but Luanti async environments run
+//! their own Lua states on worker threads and may reach a handle the main
+//! thread also uses. A `SharedState` owns its State behind a `Locked` lock
+//! that every access takes, so the calls of different threads serialize
+//! instead of racing.
+//!
+//! Unlike a `std::sync::Mutex`, a `Locked` can also be held across FFI calls
+//! (`acquire` / `release`): a Lua caller locks once, makes any number of
+//! ordinary State calls, and unlocks, possibly from another thread. The lock
+//! remembers how it is held, so an explicit unlock can never release the lock
+//! of a guard that another thread is still working under.
+
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+
+use crate::state::{HandleKind, HandleTag, State, Tagged};
+
+/// Who holds a `Locked`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Hold {
+    Free,
+    /// A `LockedGuard`, released when it drops.
+    Guard,
+    /// `acquire` / `try_acquire`, released by `release`.
+    Explicit,
+}
+
+/// A value behind a lock that can be held by a guard or explicitly.
+pub struct Locked<T> {
+    held: Mutex<Hold>,
+    released: Condvar,
+    value: UnsafeCell<T>,
+}
+
+// The lock hands out access to one thread at a time
+unsafe impl<T: Send> Send for Locked<T> {}
+unsafe impl<T: Send> Sync for Locked<T> {}
+
+/// Access to a `Locked` value, released on drop.
+pub struct LockedGuard<'a, T> {
+    lock: &'a Locked<T>,
+}
+
+impl<T> Locked<T> {
+    pub fn new(value: T) -> Self {
+        Locked {
+            held: Mutex::new(Hold::Free),
+            released: Condvar::new(),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    fn held(&self) -> MutexGuard<'_, Hold> {
+        self.held.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Wait for the lock to be free and take it as `hold`.
+    fn take(&self, hold: Hold) {
+        let mut held = self.held();
+        while *held != Hold::Free {
+            held = self
+                .released
+                .wait(held)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        *held = hold;
+    }
+
+    /// Take the lock as `hold` if it is free.
+    fn try_take(&self, hold: Hold) -> bool {
+        let mut held = self.held();
+        if *held != Hold::Free {
+            return false;
+        }
+        *held = hold;
+        true
+    }
+
+    /// Free the lock if it is held as `hold`.
+    fn give_back(&self, hold: Hold) -> bool {
+        let mut held = self.held();
+        if *held != hold {
+            return false;
+        }
+        *held = Hold::Free;
+        self.released.notify_one();
+        true
+    }
+
+    /// Take the lock, waiting for its holder to release it.
+    pub fn lock(&self) -> LockedGuard<'_, T> {
+        self.take(Hold::Guard);
+        LockedGuard { lock: self }
+    }
+
+    /// Take the lock if it is free.
+    pub fn try_lock(&self) -> Option<LockedGuard<'_, T>> {
+        // Not `then_some`: a guard built for nothing would release the lock
+        self.try_take(Hold::Guard)
+            .then(|| LockedGuard { lock: self })
+    }
+
+    /// Take the lock without a guard, waiting for its holder to release it.
+    /// Pair with `release`.
+    pub fn acquire(&self) {
+        self.take(Hold::Explicit);
+    }
+
+    /// Take the lock without a guard if it is free. Pair with `release`.
+    pub fn try_acquire(&self) -> bool {
+        self.try_take(Hold::Explicit)
+    }
+
+    /// Release a lock taken with `acquire` or `try_acquire`, from any thread.
+    /// Returns false, leaving the lock alone, if it is free or held by a
+    /// guard.
+    ///
+    /// # Safety
+    /// The value must not be accessed through `as_ptr` afterwards.
+    pub unsafe fn release(&self) -> bool {
+        self.give_back(Hold::Explicit)
+    }
+
+    /// Whether some thread holds the lock.
+    pub fn is_locked(&self) -> bool {
+        *self.held() != Hold::Free
+    }
+
+    /// The value, to be dereferenced only while holding the lock.
+    pub fn as_ptr(&self) -> *mut T {
+        self.value.get()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T> Deref for LockedGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> DerefMut for LockedGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T> Drop for LockedGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.give_back(Hold::Guard);
+    }
+}
+
+/// A State shared between threads (see the module docs).
+#[repr(C)]
+pub struct SharedState {
+    /// Checked by the FFI layer (see `HandleTag`).
+    pub tag: HandleTag,
+    pub state: Locked<State>,
+}
+
+impl SharedState {
+    pub fn new(state: State) -> Self {
+        SharedState {
+            tag: HandleTag::new(HandleKind::SharedState),
+            state: Locked::new(state),
+        }
+    }
+}
+
+unsafe impl Tagged for SharedState {
+    const KIND: HandleKind = HandleKind::SharedState;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::grid::create_grid;
+    use crate::automaton::stepping::step_automaton;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_threads_serialize() {
+        let shared = Arc::new(SharedState::new(State::default()));
+        create_grid(&mut shared.state.lock(), 4, 4, 4);
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || {
+                    for _ in 0..25 {
+                        step_automaton(&mut shared.state.lock());
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert_eq!(shared.state.lock().generation, 100);
+    }
+
+    #[test]
+    fn test_explicit_lock() {
+        let locked = Arc::new(Locked::new(0u32));
+        assert!(locked.try_acquire());
+        assert!(locked.try_lock().is_none());
+        assert!(!locked.try_acquire());
+
+        // Released from another thread, a waiter gets in
+        let waiter = {
+            let locked = Arc::clone(&locked);
+            thread::spawn(move || *locked.lock() += 1)
+        };
+        unsafe {
+            *locked.as_ptr() = 10;
+            assert!(locked.release());
+        }
+        waiter.join().unwrap();
+        assert!(!locked.is_locked());
+        assert!(!unsafe { locked.release() });
+        assert_eq!(*locked.lock(), 11);
+    }
+
+    #[test]
+    fn test_release_leaves_guards_alone() {
+        let locked = Locked::new(0u32);
+        let mut guard = locked.lock();
+        // Not an explicit hold: the guard keeps the lock
+        assert!(!unsafe { locked.release() });
+        assert!(locked.is_locked());
+        assert!(!locked.try_acquire());
+        *guard += 1;
+        drop(guard);
+        assert!(!locked.is_locked());
+        assert!(locked.try_acquire());
+        assert!(locked.try_lock().is_none());
+        assert!(unsafe { locked.release() });
+    }
+}
@@ -4,19 +4,86 @@
 //! stepping the automaton, and extracting/importing regions.
 //! The FFI layer in `ffi/` calls these functions.
 
+pub mod activity;
+pub mod adaptive;
+pub mod age;
+pub mod audit;
+pub mod background;
+pub mod bind;
+pub mod boundary;
+pub mod bundle;
 pub mod cadence;
+pub mod castep;
+pub mod chunked;
+pub mod components;
+pub mod conductivity;
+pub mod config;
+pub mod coupled;
+pub mod cycle;
+pub mod degrade;
 pub mod delta;
+pub mod diff;
+pub mod events;
+pub mod fastforward;
 pub mod field;
+pub mod field64;
+pub mod ghost;
 pub mod grid;
+pub mod hash;
+pub mod history;
+pub mod ifield;
 pub mod incremental;
+pub mod infinite;
 pub mod kernel;
+pub mod lenia;
+pub mod locked;
+#[cfg(feature = "oracle")]
+pub mod oracle;
+pub mod phase;
+pub mod pool;
+pub mod poststep;
+pub mod protect;
+pub mod raycast;
 pub mod region;
+pub mod registry;
+pub mod report;
+pub mod resample;
+pub mod rng;
+pub mod rule;
+pub mod search;
+pub mod shape;
+pub mod shared;
+#[cfg(feature = "shm")]
+pub mod shm;
+pub mod snapshot;
+pub mod soak;
+pub mod species;
+pub mod stack;
+pub mod stamp;
+pub mod stats;
 pub mod stepping;
+pub mod trace;
+pub mod transition;
+pub mod wireworld;
+pub mod writes;
 
 pub use field::{
     create_field_1, field_get, field_in_bounds, field_index_of, field_set, field_step, Field,
 };
-pub use grid::{count_neighbors, create_grid, in_bounds, index_of};
+pub use grid::{
+    count_neighbors, create_grid, get_cells_bulk, in_bounds, index_of, set_cells_bulk,
+    try_create_grid, GridError,
+};
 pub use incremental::StepController;
-pub use region::{extract_region, import_region};
-pub use stepping::step_automaton;
+pub use region::{
+    clear, extract_mapblock, extract_region, field_extract_region, field_import_region,
+    fill_region, import_mapblock, import_region, randomize_region, region_size, MAPBLOCK_VOLUME,
+};
+pub use rule::{export_rule_table, parse_rule, rule_golly_3d, rule_notation, RuleParseError};
+pub use snapshot::{
+    compressed_size, deserialize_field, deserialize_state, deserialize_state_compressed,
+    field_serialized_size, serialize_field, serialize_state, serialize_state_compressed,
+    serialized_size, SnapshotError,
+};
+pub use stamp::stamp_pattern;
+pub use stepping::{step_automaton, step_preview, CellChange};
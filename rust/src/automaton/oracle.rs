@@ -0,0 +1,486 @@
+//! Big-integer oracle for the diffusion arithmetic.
+//!
+//! `field_step` computes every pair flow in i64 fixed point. That is exact for
+//! everyday fields, but an extreme rate spread, a conductivity curve or a
+//! compiler/CPU quirk on a player's machine can make it go wrong in ways a CI
+//! run never sees. The oracle redoes the diffusion pass in arbitrary-precision
+//! arithmetic (`BigInt`, just the handful of operations it needs), with the
+//! same rounding, face-budget clamping, axis order and periodic pairs, and
+//! keeps cell values exact instead of wrapping them to u32.
+//!
+//! Shadow stepping runs `field_step` as usual and the oracle on a copy, and
+//! reports the first cell where they disagree. The field always keeps the
+//! fixed-point result, so shadowing a few steps in the middle of a game
+//! changes nothing but speed. Sources, boundaries, advection, protection and
+//! phase changes are not fixed-point arithmetic and are shared with the
+//! stepper.
+//!
+//! Only compiled with the `oracle` feature (on by default).
+
+use std::cmp::Ordering;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use super::boundary::apply_boundaries;
+use super::field::{
+    advect, apply_sources, diffusion_pass, field_conductivity, field_step, pair_key, Field,
+    RoundingMode,
+};
+use super::phase::apply_phase_changes;
+use super::protect::apply_protection;
+use super::rng::mix64;
+
+/// Arbitrary-precision signed integer: a sign and a little-endian magnitude
+/// with no leading zero limbs (zero is never negative).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    fn new(negative: bool, mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigInt {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+
+    pub fn from_i128(value: i128) -> Self {
+        let magnitude = value.unsigned_abs();
+        let limbs = (0..4).map(|i| (magnitude >> (32 * i)) as u32).collect();
+        BigInt::new(value < 0, limbs)
+    }
+
+    /// The value, or None if it does not fit in an i128.
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.limbs.len() > 4 {
+            return None;
+        }
+        let magnitude = self
+            .limbs
+            .iter()
+            .rev()
+            .fold(0u128, |acc, &limb| acc << 32 | limb as u128);
+        if self.negative {
+            0i128.checked_sub_unsigned(magnitude)
+        } else {
+            i128::try_from(magnitude).ok()
+        }
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.limbs.first().is_some_and(|&limb| limb & 1 != 0)
+    }
+
+    pub fn abs(&self) -> Self {
+        BigInt::new(false, self.limbs.clone())
+    }
+
+    /// `self * 2^bits`.
+    pub fn shl(&self, bits: u32) -> Self {
+        let shift = bits % 32;
+        let mut limbs = vec![0; (bits / 32) as usize];
+        let mut carry = 0;
+        for &limb in &self.limbs {
+            if shift == 0 {
+                limbs.push(limb);
+            } else {
+                limbs.push(limb << shift | carry);
+                carry = limb >> (32 - shift);
+            }
+        }
+        limbs.push(carry);
+        BigInt::new(self.negative, limbs)
+    }
+
+    /// `self / 2^bits`, truncated toward zero.
+    pub fn shr(&self, bits: u32) -> Self {
+        let skip = (bits / 32) as usize;
+        let shift = bits % 32;
+        let rest = self.limbs.get(skip..).unwrap_or(&[]);
+        let limbs = (0..rest.len())
+            .map(|i| {
+                let high = match rest.get(i + 1) {
+                    Some(&next) if shift != 0 => next << (32 - shift),
+                    _ => 0,
+                };
+                rest[i] >> shift | high
+            })
+            .collect();
+        BigInt::new(self.negative, limbs)
+    }
+
+    /// `self / divisor`, truncated toward zero.
+    pub fn div_small(&self, divisor: u32) -> Self {
+        let mut limbs = vec![0; self.limbs.len()];
+        let mut remainder = 0u64;
+        for (i, &limb) in self.limbs.iter().enumerate().rev() {
+            let current = remainder << 32 | limb as u64;
+            limbs[i] = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+        BigInt::new(self.negative, limbs)
+    }
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut limbs = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &limb) in long.iter().enumerate() {
+        let sum = limb as u64 + short.get(i).copied().unwrap_or(0) as u64 + carry;
+        limbs.push(sum as u32);
+        carry = sum >> 32;
+    }
+    limbs.push(carry as u32);
+    limbs
+}
+
+/// `a - b` for `a >= b`.
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &limb) in a.iter().enumerate() {
+        let mut diff = limb as i64 - b.get(i).copied().unwrap_or(0) as i64 - borrow;
+        borrow = (diff < 0) as i64;
+        diff += borrow << 32;
+        limbs.push(diff as u32);
+    }
+    limbs
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.limbs, &other.limbs),
+            (true, true) => cmp_magnitude(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.limbs.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_magnitude(&self.limbs, &other.limbs));
+        }
+        match cmp_magnitude(&self.limbs, &other.limbs) {
+            Ordering::Less => BigInt::new(other.negative, sub_magnitude(&other.limbs, &self.limbs)),
+            _ => BigInt::new(self.negative, sub_magnitude(&self.limbs, &other.limbs)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let t = limbs[i + j] as u64 + a as u64 * b as u64 + carry;
+                limbs[i + j] = t as u32;
+                carry = t >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        BigInt::new(self.negative != other.negative, limbs)
+    }
+}
+
+/// `compute_flow_in` in exact arithmetic, for the divisor `7 * 2^shift`.
+fn exact_flow(
+    gradient: i128,
+    conductivity: &BigInt,
+    shift: u32,
+    rounding: RoundingMode,
+    pair_key: u64,
+    remainder_acc: &mut BigInt,
+) -> BigInt {
+    let divisor = BigInt::from_i128(7).shl(shift);
+    let product = &BigInt::from_i128(gradient) * conductivity;
+    // Truncated division, as Rust's `/` and `%`
+    let flow_truncated = product.shr(shift).div_small(7);
+    let remainder = (&product - &(&flow_truncated * &divisor)).abs();
+
+    let round_away = match rounding {
+        RoundingMode::Stochastic => {
+            *remainder_acc = &*remainder_acc + &remainder;
+            if *remainder_acc >= divisor {
+                *remainder_acc = &*remainder_acc - &divisor;
+                true
+            } else {
+                false
+            }
+        }
+        RoundingMode::Truncate => false,
+        RoundingMode::Hash => {
+            let hash = mix64(pair_key);
+            // A divisor beyond u64 leaves the hash as it is
+            let threshold = match divisor.to_i128().map(u64::try_from) {
+                Some(Ok(divisor)) => hash % divisor,
+                _ => hash,
+            };
+            BigInt::from_i128(threshold as i128) < remainder
+        }
+        RoundingMode::HalfEven => {
+            let twice = remainder.shl(1);
+            twice > divisor || (twice == divisor && flow_truncated.is_odd())
+        }
+    };
+
+    let one = BigInt::from_i128(1);
+    if !round_away {
+        flow_truncated
+    } else if gradient >= 0 {
+        &flow_truncated + &one
+    } else {
+        &flow_truncated - &one
+    }
+}
+
+/// Phase C of `field_step` in exact arithmetic: the diffused cells, unwrapped.
+pub fn exact_diffuse(field: &Field) -> Vec<i128> {
+    let pass = diffusion_pass(field, true);
+    let conductivity = field_conductivity(field);
+    let [w, h, d] = pass.dims;
+    let extents = [w, h, d];
+    let strides = [1, w, w * h];
+    let slowest = pass.rates.into_iter().max().unwrap_or(0) as u32;
+    let shift = slowest + 16;
+    let mut cells: Vec<i128> = field.cells.iter().map(|&v| v as i128).collect();
+    let mut new_cells = cells.clone();
+    let mut remainder_acc = BigInt::from_i128(0);
+    // The conductivity curve is looked up by cell value; an exact value out of
+    // u32 range has already diverged, so any lookup will do
+    let as_cell = |v: i128| v.clamp(0, u32::MAX as i128) as u32;
+    // `face_budget`, for exact values
+    let budget = |v: i128| (v - 1).max(0) / 6;
+
+    for axis in 0..3 {
+        if axis > 0 {
+            cells.copy_from_slice(&new_cells);
+        }
+        let scale = slowest - pass.rates[axis] as u32;
+        for idx_a in 0..cells.len() {
+            let coord = idx_a / strides[axis] % extents[axis];
+            let idx_b = if coord + 1 < extents[axis] {
+                idx_a + strides[axis]
+            } else if pass.periodic[axis] {
+                idx_a - coord * strides[axis]
+            } else {
+                continue;
+            };
+            let (a, b) = (cells[idx_a], cells[idx_b]);
+            let pair = BigInt::from_i128(conductivity(as_cell(a), as_cell(b)) as i128).shl(scale);
+            let key = pair_key(pass.generation, idx_a, axis as u64);
+            let flow = exact_flow(a - b, &pair, shift, pass.rounding, key, &mut remainder_acc);
+            let flow = flow
+                .max(BigInt::from_i128(-budget(b)))
+                .min(BigInt::from_i128(budget(a)));
+            // Clamped to budgets, which are cell-sized
+            let flow = flow.to_i128().unwrap_or(0);
+            new_cells[idx_a] -= flow;
+            new_cells[idx_b] += flow;
+        }
+    }
+    new_cells
+}
+
+/// First disagreement between `field_step` and the oracle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShadowMismatch {
+    /// Generation the step produced.
+    pub generation: u64,
+    pub cell: [i16; 3],
+    /// The oracle's value; outside u32 range if the exact result overflows a cell.
+    pub expected: i128,
+    pub actual: u32,
+}
+
+/// `field_step`, checked against the oracle. The field is always stepped with
+/// the fixed-point result; on a disagreement the first differing cell (in
+/// z, y, x order) is returned.
+pub fn field_step_shadowed(field: &mut Field) -> Result<(), ShadowMismatch> {
+    let mut oracle = field.clone();
+    oracle.flow_record = None;
+    field_step(field);
+
+    apply_sources(&mut oracle);
+    apply_boundaries(&mut oracle);
+    let exact = exact_diffuse(&oracle);
+    let mut new_cells = Vec::with_capacity(exact.len());
+    for (idx, &value) in exact.iter().enumerate() {
+        match u32::try_from(value) {
+            Ok(value) => new_cells.push(value),
+            Err(_) => return Err(mismatch(field, idx, value)),
+        }
+    }
+    advect(&oracle, &mut new_cells, &mut |_, _, _, _| {});
+    apply_protection(&mut oracle.protection, &oracle.cells, &mut new_cells);
+    oracle.cells = new_cells;
+    apply_phase_changes(&mut oracle);
+
+    match (oracle.cells.iter().zip(&field.cells)).position(|(a, b)| a != b) {
+        Some(idx) => Err(mismatch(field, idx, oracle.cells[idx] as i128)),
+        None => Ok(()),
+    }
+}
+
+fn mismatch(field: &Field, idx: usize, expected: i128) -> ShadowMismatch {
+    let (w, h) = (field.width as usize, field.height as usize);
+    ShadowMismatch {
+        generation: field.generation,
+        cell: [idx % w, idx / w % h, idx / (w * h)].map(|c| c as i16),
+        expected,
+        actual: field.cells[idx],
+    }
+}
+
+/// Run up to `steps` shadowed steps, stopping after the first that disagrees.
+/// Returns the number of steps taken and the mismatch, if any.
+pub fn field_shadow_steps(field: &mut Field, steps: u64) -> (u64, Option<ShadowMismatch>) {
+    for taken in 1..=steps {
+        if let Err(mismatch) = field_step_shadowed(field) {
+            return (taken, Some(mismatch));
+        }
+    }
+    (steps, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_set};
+
+    #[test]
+    fn test_bigint_arithmetic() {
+        let big = |v: i128| BigInt::from_i128(v);
+        let values = [
+            0,
+            1,
+            -1,
+            7,
+            -123_456_789_012_345,
+            i64::MAX as i128,
+            i64::MIN as i128,
+        ];
+        for &a in &values {
+            for &b in &values {
+                assert_eq!((&big(a) + &big(b)).to_i128(), Some(a + b));
+                assert_eq!((&big(a) - &big(b)).to_i128(), Some(a - b));
+                assert_eq!((&big(a) * &big(b)).to_i128(), Some(a * b));
+                assert_eq!(big(a).cmp(&big(b)), a.cmp(&b));
+            }
+            assert_eq!(big(a).shl(37).shr(37), big(a));
+            assert_eq!(big(a).shr(3).to_i128(), Some(a / 8));
+            assert_eq!(big(a).div_small(7).to_i128(), Some(a / 7));
+        }
+        let huge = big(1).shl(300);
+        assert_eq!(huge.to_i128(), None);
+        assert_eq!((&huge - &huge.shr(1)).shr(299).to_i128(), Some(1));
+        assert_eq!(big(i128::MIN).to_i128(), Some(i128::MIN));
+    }
+
+    fn warm_field(rounding: RoundingMode) -> Field {
+        let mut field = create_field_1(6, 5, 4, 3);
+        field.rounding = rounding;
+        field_set(&mut field, 2, 2, 1, 4_000_000);
+        field_set(&mut field, 5, 0, 3, 77_777);
+        field.axis_rates = Some([2, 3, 5]);
+        field.periodic = [true, false, false];
+        field.advection = [0, -4096, 0];
+        field
+    }
+
+    #[test]
+    fn test_everyday_fields_agree_in_every_mode() {
+        for mode in 0..4 {
+            let mut field = warm_field(RoundingMode::from_u8(mode).unwrap());
+            assert_eq!(
+                field_shadow_steps(&mut field, 20),
+                (20, None),
+                "mode {mode}"
+            );
+            assert_eq!(field.generation, 20);
+        }
+    }
+
+    #[test]
+    fn test_exact_beyond_i64() {
+        // Scaling x by 2^40 takes gradient * conductivity past i64
+        let mut field = create_field_1(4, 2, 2, 0);
+        field.axis_rates = Some([0, 40, 40]);
+        let high = u32::MAX / 2;
+        field_set(&mut field, 1, 0, 0, high);
+        assert_eq!((high as i64 - 1).checked_mul(65535 << 40), None);
+        let exact = exact_diffuse(&field);
+        let total: u64 = field.cells.iter().map(|&v| v as u64).sum();
+        assert_eq!(exact.iter().sum::<i128>(), total as i128);
+        // Each x neighbor takes just under a seventh
+        let share = (high as i128 - 1) * 65535 / (7 * 65536);
+        assert!((exact[0] - 1 - share).abs() <= 1, "{}", exact[0]);
+    }
+
+    /// Cells one below u32::MAX amid full ones: rounding up several inflows
+    /// into one of them wraps it past u32::MAX.
+    fn brim_field() -> Field {
+        let mut field = create_field_1(5, 5, 5, 0);
+        field.cells.fill(u32::MAX);
+        field.rounding = RoundingMode::Hash;
+        for (x, y, z) in [(2, 2, 2), (1, 1, 1), (3, 3, 3), (1, 3, 2)] {
+            field_set(&mut field, x, y, z, u32::MAX - 1);
+        }
+        field
+    }
+
+    #[test]
+    fn test_wrapped_cell_is_caught() {
+        let mut field = brim_field();
+        let (taken, mismatch) = field_shadow_steps(&mut field, 5);
+        assert_eq!(taken, 1);
+        assert_eq!(
+            mismatch,
+            Some(ShadowMismatch {
+                generation: 1,
+                cell: [1, 3, 2],
+                expected: 1 << 32,
+                actual: 0,
+            })
+        );
+        // The field keeps the fixed-point result
+        assert_eq!(field.generation, 1);
+        assert_eq!(field.cells[1 + 3 * 5 + 2 * 25], 0);
+    }
+}
@@ -0,0 +1,199 @@
+//! Phase changes with latent heat.
+//!
+//! A field can carry a companion phase per cell (ice = 0, water = 1, steam = 2,
+//! ...) and an ascending list of thresholds between consecutive phases. A cell
+//! in phase `p` whose value reaches `thresholds[p].value + thresholds[p].latent`
+//! moves up to phase `p + 1` and gives up `latent` units, which stay stored in
+//! the phase itself; a cell that drops below `thresholds[p - 1].value` moves
+//! down and gets its latent units back. Melting ice therefore sits at the
+//! threshold while it absorbs heat, and the total energy (cell values plus
+//! stored latent heat, see `phase_energy`) is conserved.
+//!
+//! The gap of `latent` between the up and down conditions is also the
+//! hysteresis that keeps a cell from flickering at the boundary.
+//!
+//! Writing a cell directly (`field_set`, region import) keeps its phase, so the
+//! next step may exchange latent heat for a value that never got there by
+//! diffusion; reconfigure the thresholds to reclassify after bulk edits.
+
+use super::field::Field;
+
+/// Boundary between phase `i` and phase `i + 1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhaseThreshold {
+    /// Value at which the lower phase starts to convert.
+    pub value: u32,
+    /// Units absorbed on the way up and released on the way down.
+    pub latent: u32,
+}
+
+/// Phase configuration and per-cell phase state of a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Phases {
+    /// Strictly ascending by `value`; at most 255 entries (256 phases).
+    pub thresholds: Vec<PhaseThreshold>,
+    /// Phase per cell, in the field's z,y,x order.
+    pub phase: Vec<u8>,
+}
+
+/// Configure phase thresholds, classifying every cell by its current value
+/// (no latent heat is exchanged for the initial classification). An empty list
+/// removes phase tracking.
+///
+/// # Returns
+/// False (and nothing changes) if the thresholds are not strictly ascending or
+/// there are more than 255 of them.
+pub fn field_set_phase_thresholds(field: &mut Field, thresholds: &[PhaseThreshold]) -> bool {
+    if thresholds.len() > u8::MAX as usize
+        || thresholds.windows(2).any(|w| w[0].value >= w[1].value)
+    {
+        return false;
+    }
+    if thresholds.is_empty() {
+        field.phases = None;
+        return true;
+    }
+    let phase = field
+        .cells
+        .iter()
+        .map(|&v| thresholds.iter().take_while(|t| v >= t.value).count() as u8)
+        .collect();
+    field.phases = Some(Phases {
+        thresholds: thresholds.to_vec(),
+        phase,
+    });
+    true
+}
+
+/// Convert cells across thresholds after a step. A cell may cross several
+/// boundaries in one call if it has enough (or too little) energy.
+pub fn apply_phase_changes(field: &mut Field) {
+    let Some(phases) = field.phases.as_mut() else {
+        return;
+    };
+    let thresholds = &phases.thresholds;
+    for (value, phase) in field.cells.iter_mut().zip(phases.phase.iter_mut()) {
+        // Upward: absorb latent heat, leaving the cell at or above the threshold
+        while let Some(t) = thresholds.get(*phase as usize) {
+            match value.checked_sub(t.latent) {
+                Some(rest) if rest >= t.value => {
+                    *value = rest;
+                    *phase += 1;
+                }
+                _ => break,
+            }
+        }
+        // Downward: release latent heat (skipped if it would not fit in u32)
+        while *phase > 0 {
+            let t = thresholds[*phase as usize - 1];
+            if *value >= t.value {
+                break;
+            }
+            match value.checked_add(t.latent) {
+                Some(released) => {
+                    *value = released;
+                    *phase -= 1;
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+/// Latent heat stored in the phases of all cells (0 without phase tracking).
+pub fn latent_energy(field: &Field) -> u64 {
+    let Some(phases) = &field.phases else {
+        return 0;
+    };
+    // Latent heat stored by a cell in phase p: sum of the first p latents
+    let mut stored_by_phase = vec![0u64; phases.thresholds.len() + 1];
+    for (i, t) in phases.thresholds.iter().enumerate() {
+        stored_by_phase[i + 1] = stored_by_phase[i] + t.latent as u64;
+    }
+    phases
+        .phase
+        .iter()
+        .map(|&p| stored_by_phase[p as usize])
+        .sum()
+}
+
+/// Total energy: cell values plus stored latent heat. Conserved by stepping.
+pub fn phase_energy(field: &Field) -> u64 {
+    field.total() + latent_energy(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_set, field_step, field_step_fused};
+
+    const ICE_WATER_STEAM: [PhaseThreshold; 2] = [
+        PhaseThreshold {
+            value: 1000,
+            latent: 5000,
+        },
+        PhaseThreshold {
+            value: 4000,
+            latent: 20_000,
+        },
+    ];
+
+    #[test]
+    fn test_transitions_and_hysteresis() {
+        let mut field = create_field_1(3, 1, 1, 0);
+        field.cells = vec![999, 2000, 30_000];
+        assert!(field_set_phase_thresholds(&mut field, &ICE_WATER_STEAM));
+        assert_eq!(field.phases.as_ref().unwrap().phase, [0, 1, 2]);
+
+        field.cells = vec![6000, 999, 3999];
+        let stored = latent_energy(&field);
+        apply_phase_changes(&mut field);
+        // 6000 melts (1000 + 5000); 999 freezes; 3999 condenses then stays water
+        assert_eq!(field.cells, [1000, 5999, 23_999]);
+        assert_eq!(field.phases.as_ref().unwrap().phase, [1, 0, 1]);
+        assert_eq!(
+            field.total() + latent_energy(&field),
+            6000 + 999 + 3999 + stored
+        );
+
+        // Just below the melt point + latent: no change either way
+        field.cells = vec![5999, 1000, 4000];
+        apply_phase_changes(&mut field);
+        assert_eq!(field.cells, [5999, 1000, 4000]);
+        assert_eq!(field.phases.as_ref().unwrap().phase, [1, 0, 1]);
+    }
+
+    #[test]
+    fn test_steps_conserve_energy() {
+        for step in [field_step, field_step_fused] {
+            let mut field = create_field_1(8, 8, 8, 0);
+            for (x, value) in [(1, 3_000_000), (6, 50_000)] {
+                field_set(&mut field, x, 4, 4, value);
+            }
+            assert!(field_set_phase_thresholds(&mut field, &ICE_WATER_STEAM));
+            let energy = phase_energy(&field);
+            for _ in 0..30 {
+                step(&mut field);
+                assert_eq!(phase_energy(&field), energy);
+            }
+            let phase = &field.phases.as_ref().unwrap().phase;
+            assert!(phase.iter().any(|&p| p > 0));
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_thresholds() {
+        let mut field = create_field_1(2, 2, 2, 0);
+        let descending = [ICE_WATER_STEAM[1], ICE_WATER_STEAM[0]];
+        assert!(!field_set_phase_thresholds(&mut field, &descending));
+        let too_many: Vec<_> = (0..256)
+            .map(|value| PhaseThreshold { value, latent: 1 })
+            .collect();
+        assert!(!field_set_phase_thresholds(&mut field, &too_many));
+        assert!(field.phases.is_none());
+
+        assert!(field_set_phase_thresholds(&mut field, &ICE_WATER_STEAM));
+        assert!(field_set_phase_thresholds(&mut field, &[]));
+        assert!(field.phases.is_none());
+    }
+}
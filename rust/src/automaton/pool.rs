@@ -0,0 +1,196 @@
+//! Reusable byte buffers for extraction.
+//!
+//! Lua pulls visualization data every second or so, and a full-grid extraction
+//! of a large world is several megabytes. Allocating and freeing that each time
+//! churns the allocator, so callers can instead borrow a buffer from a pool and
+//! hand it back when done.
+//!
+//! Buffers are grouped into power-of-two size classes starting at one mapblock
+//! (4096 bytes), so a buffer released after one extraction fits the next
+//! extraction of the same region. Only a few idle buffers are kept per class.
+
+use std::collections::HashMap;
+
+/// Smallest size class: one 16³ mapblock.
+pub const MIN_BUFFER_SIZE: usize = 4096;
+
+/// Largest buffer handed out (1 GiB); bigger requests are refused rather than
+/// aborting the process on allocation failure.
+pub const MAX_BUFFER_SIZE: usize = 1 << 30;
+
+/// Idle buffers retained per size class; extras are freed on release.
+pub const MAX_IDLE_PER_CLASS: usize = 4;
+
+/// Pool of reusable byte buffers handed out as raw pointers.
+///
+/// Acquired buffers are owned by the caller until released; the pool only
+/// remembers their capacity so it can reclaim them.
+#[derive(Default)]
+pub struct BufferPool {
+    /// Idle buffers, indexed by size class.
+    idle: Vec<Vec<Box<[u8]>>>,
+    /// Outstanding buffers: address -> capacity.
+    lent: HashMap<usize, usize>,
+}
+
+/// Size class index and capacity for a request of `size` bytes.
+fn size_class(size: usize) -> Option<(usize, usize)> {
+    let capacity = size.max(MIN_BUFFER_SIZE).checked_next_power_of_two()?;
+    let class = (capacity.trailing_zeros() - MIN_BUFFER_SIZE.trailing_zeros()) as usize;
+    Some((class, capacity))
+}
+
+impl BufferPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Borrow a buffer of at least `size` bytes.
+    ///
+    /// Returns the buffer pointer and its actual capacity, or None if `size`
+    /// exceeds `MAX_BUFFER_SIZE`. Contents are unspecified (a reused
+    /// buffer still holds its previous data).
+    pub fn acquire(&mut self, size: usize) -> Option<(*mut u8, usize)> {
+        if size > MAX_BUFFER_SIZE {
+            return None;
+        }
+        let (class, capacity) = size_class(size)?;
+        if self.idle.len() <= class {
+            self.idle.resize_with(class + 1, Vec::new);
+        }
+        let buffer = self.idle[class]
+            .pop()
+            .unwrap_or_else(|| vec![0u8; capacity].into_boxed_slice());
+
+        let ptr = Box::into_raw(buffer) as *mut u8;
+        self.lent.insert(ptr as usize, capacity);
+        Some((ptr, capacity))
+    }
+
+    /// Return a buffer obtained from `acquire`.
+    ///
+    /// Returns false (and does nothing) if `ptr` is not an outstanding buffer of
+    /// this pool, so double releases and foreign pointers are harmless.
+    pub fn release(&mut self, ptr: *mut u8) -> bool {
+        let Some(capacity) = self.lent.remove(&(ptr as usize)) else {
+            return false;
+        };
+        // SAFETY: ptr/capacity came from Box::into_raw in acquire and were
+        // removed from `lent`, so this is the only reconstruction.
+        let buffer = unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, capacity)) };
+
+        let (class, _) = size_class(capacity).expect("capacity is a size class");
+        if self.idle[class].len() < MAX_IDLE_PER_CLASS {
+            self.idle[class].push(buffer);
+        }
+        true
+    }
+
+    /// Free all idle buffers (outstanding ones are unaffected).
+    pub fn trim(&mut self) {
+        for class in &mut self.idle {
+            *class = Vec::new();
+        }
+    }
+
+    /// Free every buffer, idle and outstanding, leaving the pool as new.
+    ///
+    /// Outstanding pointers become dangling, so this is only for teardown and
+    /// reinitialization, when the caller's old pointers are known to be gone.
+    /// Returns the number of outstanding buffers that were reclaimed.
+    pub fn reset(&mut self) -> usize {
+        self.trim();
+        let reclaimed = self.lent.len();
+        for (ptr, capacity) in self.lent.drain() {
+            // SAFETY: as in release; the caller has given up every
+            // outstanding pointer, so this is the only reconstruction.
+            drop(unsafe {
+                Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr as *mut u8, capacity))
+            });
+        }
+        reclaimed
+    }
+
+    /// Bytes held in idle buffers.
+    pub fn idle_bytes(&self) -> usize {
+        self.idle.iter().flatten().map(|b| b.len()).sum()
+    }
+
+    /// Number of buffers currently lent out.
+    pub fn outstanding(&self) -> usize {
+        self.lent.len()
+    }
+}
+
+impl Drop for BufferPool {
+    fn drop(&mut self) {
+        // The pool is going away, so outstanding buffers are freed rather than leaked
+        self.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_classes() {
+        assert_eq!(size_class(0), Some((0, 4096)));
+        assert_eq!(size_class(4096), Some((0, 4096)));
+        assert_eq!(size_class(4097), Some((1, 8192)));
+        assert_eq!(size_class(3 << 20), Some((10, 4 << 20)));
+        assert_eq!(size_class(usize::MAX), None);
+    }
+
+    #[test]
+    fn test_buffers_are_reused() {
+        let mut pool = BufferPool::new();
+
+        let (a, cap) = pool.acquire(100 * 100 * 100).unwrap();
+        assert_eq!(cap, 1 << 20);
+        unsafe { *a.add(cap - 1) = 42 };
+        assert!(pool.release(a));
+        assert_eq!(pool.idle_bytes(), 1 << 20);
+
+        // Same class hands back the same allocation
+        let (b, _) = pool.acquire(900_000).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(pool.idle_bytes(), 0);
+        assert_eq!(pool.outstanding(), 1);
+        assert!(pool.release(b));
+
+        assert!(pool.acquire(MAX_BUFFER_SIZE + 1).is_none());
+    }
+
+    #[test]
+    fn test_bad_release_and_idle_cap() {
+        let mut pool = BufferPool::new();
+        let mut local = [0u8; 4];
+        assert!(!pool.release(local.as_mut_ptr()));
+
+        let bufs: Vec<_> = (0..MAX_IDLE_PER_CLASS + 2)
+            .map(|_| pool.acquire(10).unwrap().0)
+            .collect();
+        for &buf in &bufs {
+            assert!(pool.release(buf));
+        }
+        // Double release is rejected
+        assert!(!pool.release(bufs[0]));
+        assert_eq!(pool.idle_bytes(), MAX_IDLE_PER_CLASS * MIN_BUFFER_SIZE);
+
+        pool.trim();
+        assert_eq!(pool.idle_bytes(), 0);
+        let (buf, _) = pool.acquire(10).unwrap();
+        assert!(pool.release(buf));
+
+        // Reset reclaims outstanding buffers too
+        pool.acquire(10).unwrap();
+        pool.acquire(5000).unwrap();
+        assert_eq!(pool.reset(), 2);
+        assert_eq!(pool.outstanding(), 0);
+        assert_eq!(pool.reset(), 0);
+
+        // Outstanding buffers are freed when the pool is dropped
+        pool.acquire(10).unwrap();
+    }
+}
@@ -0,0 +1,165 @@
+//! Native operations chained after every incremental step.
+//!
+//! A StepController can carry a short pipeline of operations that run inside
+//! `finalize_step`, after the new cells are in place and the generation is
+//! bumped but before the generation-complete event is queued. Their outputs
+//! (a coupled grid, running statistics) are therefore always tagged with, and
+//! consistent with, the generation they were computed from: no caller can
+//! observe the field at generation N next to a grid still showing N - 1.
+//!
+//! Operations run in registration order, so a decay registered before a
+//! statistics operation is reflected in those statistics.
+
+use super::field::Field;
+use crate::state::State;
+
+/// Operations a pipeline holds at most.
+pub const MAX_POST_STEP_OPS: usize = 8;
+
+/// Summary of the field after a step (see `PostStepOp::Statistics`), or of
+/// any field on demand (`FieldStats::of`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FieldStats {
+    /// Generation the statistics describe (0 until the first step).
+    pub generation: u64,
+    /// Sum of all cells.
+    pub total: u64,
+    pub min: u32,
+    pub max: u32,
+}
+
+impl FieldStats {
+    /// Statistics of the field as it is now, in one pass over the cells.
+    pub fn of(field: &Field) -> Self {
+        let (total, min, max) = field
+            .cells
+            .iter()
+            .fold((0u64, u32::MAX, 0u32), |(total, min, max), &v| {
+                (total + v as u64, min.min(v), max.max(v))
+            });
+        FieldStats {
+            generation: field.generation,
+            total,
+            min: if field.cells.is_empty() { 0 } else { min },
+            max,
+        }
+    }
+}
+
+/// One operation of a post-step pipeline.
+pub enum PostStepOp {
+    /// Cells at or above `threshold` are alive in `grid`, all others dead.
+    /// The grid has the field's dimensions and its generation follows the field's.
+    /// Boxed so the grid keeps its address while more operations are added.
+    ThresholdCouple { threshold: u32, grid: Box<State> },
+    /// Every cell loses `value >> shift`, never dropping below 1. Not conserved:
+    /// use it for quantities that dissipate (pollution, scent).
+    Decay { shift: u8 },
+    /// Recompute `FieldStats` of the field.
+    Statistics(FieldStats),
+}
+
+impl PostStepOp {
+    /// A threshold coupling with an all-dead grid matching `field`.
+    pub fn threshold_couple(field: &Field, threshold: u32) -> Self {
+        let grid = Box::new(State {
+            width: field.width,
+            height: field.height,
+            depth: field.depth,
+            cells: vec![0; field.cells.len()],
+            generation: field.generation,
+            ..State::default()
+        });
+        PostStepOp::ThresholdCouple { threshold, grid }
+    }
+
+    fn apply(&mut self, field: &mut Field) {
+        match self {
+            PostStepOp::ThresholdCouple { threshold, grid } => {
+                for (alive, &value) in grid.cells.iter_mut().zip(&field.cells) {
+                    *alive = (value >= *threshold) as u8;
+                }
+                grid.generation = field.generation;
+            }
+            PostStepOp::Decay { shift } => {
+                for value in &mut field.cells {
+                    let loss = value.checked_shr(*shift as u32).unwrap_or(0);
+                    *value = (*value - loss).max(1);
+                }
+            }
+            PostStepOp::Statistics(stats) => *stats = FieldStats::of(field),
+        }
+    }
+}
+
+/// Ordered list of post-step operations.
+#[derive(Default)]
+pub struct PostStepPipeline {
+    pub ops: Vec<PostStepOp>,
+}
+
+impl PostStepPipeline {
+    /// Append an operation, returning its index, or None if the pipeline is full.
+    pub fn push(&mut self, op: PostStepOp) -> Option<usize> {
+        if self.ops.len() >= MAX_POST_STEP_OPS {
+            return None;
+        }
+        self.ops.push(op);
+        Some(self.ops.len() - 1)
+    }
+
+    /// Run every operation in order on the freshly stepped field.
+    pub fn run(&mut self, field: &mut Field) {
+        for op in &mut self.ops {
+            op.apply(field);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_set};
+
+    #[test]
+    fn test_pipeline_runs_in_order() {
+        let mut field = create_field_1(4, 1, 1, 0);
+        field_set(&mut field, 0, 0, 0, 1000);
+        field_set(&mut field, 1, 0, 0, 120);
+        field.generation = 7;
+
+        let mut pipeline = PostStepPipeline::default();
+        assert_eq!(pipeline.push(PostStepOp::Decay { shift: 2 }), Some(0));
+        assert_eq!(
+            pipeline.push(PostStepOp::threshold_couple(&field, 100)),
+            Some(1)
+        );
+        assert_eq!(
+            pipeline.push(PostStepOp::Statistics(FieldStats::default())),
+            Some(2)
+        );
+        pipeline.run(&mut field);
+
+        assert_eq!(field.cells, [750, 90, 1, 1]);
+        let PostStepOp::ThresholdCouple { grid, .. } = &pipeline.ops[1] else {
+            panic!("expected a threshold coupling");
+        };
+        assert_eq!(grid.cells, [1, 0, 0, 0]);
+        assert_eq!(grid.generation, 7);
+        let PostStepOp::Statistics(stats) = pipeline.ops[2] else {
+            panic!("expected statistics");
+        };
+        assert_eq!(
+            stats,
+            FieldStats {
+                generation: 7,
+                total: 842,
+                min: 1,
+                max: 750
+            }
+        );
+
+        while pipeline.push(PostStepOp::Decay { shift: 40 }).is_some() {}
+        assert_eq!(pipeline.ops.len(), MAX_POST_STEP_OPS);
+    }
+}
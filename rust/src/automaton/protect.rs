@@ -0,0 +1,153 @@
+//! Protection masks: cells a step may not grow into.
+//!
+//! Mirrors land claims (e.g. Luanti area protection) natively. The mask is
+//! consulted when a step commits: a protected cell never ends the step above
+//! the value it went in with. In a grid that suppresses births; in a field it
+//! removes the increase diffusion or advection would have brought (sources and
+//! boundaries, which run before the step's snapshot, are not vetoed). Deaths
+//! and decreases pass through unchanged.
+//!
+//! Every suppressed birth, or unit of field value, is added to the
+//! `suppressed` tally. A field with protected cells therefore loses exactly
+//! the tallied amount: the total after a step is the total before, minus what
+//! the tally grew by.
+
+use super::region::{clamp_box, for_each_row};
+
+/// Protected cells of a grid or field and the tally of what they vetoed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Protection {
+    /// One flag per cell, in the cells' z,y,x order.
+    pub mask: Vec<bool>,
+    /// Births (grid) or value (field) suppressed since the mask was created.
+    pub suppressed: u64,
+}
+
+impl Protection {
+    /// Clamp every protected cell of `after` to its value in `before`.
+    /// Returns the amount removed without adding it to the tally (a dry run
+    /// can call this on a copy).
+    pub fn veto<T: Copy + Ord + Into<u64>>(&self, before: &[T], after: &mut [T]) -> u64 {
+        let mut removed = 0;
+        for ((&protected, &old), new) in self.mask.iter().zip(before).zip(after) {
+            if protected && *new > old {
+                removed += (*new).into() - old.into();
+                *new = old;
+            }
+        }
+        removed
+    }
+}
+
+/// Mark (`protected`) or clear the half-open box `[min, max)` of a grid or
+/// field with dimensions `dims`, creating the mask on first use (or anew if
+/// the cell count changed). Returns the number of cells in the clamped box,
+/// or 0 if it lies outside.
+pub fn protect_region(
+    protection: &mut Option<Protection>,
+    dims: [i16; 3],
+    min: [i16; 3],
+    max: [i16; 3],
+    protected: bool,
+) -> u64 {
+    let Some((lo, hi)) = clamp_box(dims, min, max) else {
+        return 0;
+    };
+    let cells = dims.iter().map(|&d| d as usize).product();
+    let protection = protection.get_or_insert_with(Protection::default);
+    if protection.mask.len() != cells {
+        protection.mask = vec![false; cells];
+    }
+    let mut covered = 0;
+    for_each_row(dims, lo, hi, |start, len| {
+        protection.mask[start..start + len].fill(protected);
+        covered += len as u64;
+    });
+    covered
+}
+
+/// Commit-time veto: clamp the protected cells of `after` (the next
+/// generation) to `before` and tally what was removed. No-op without a mask,
+/// or if the mask no longer matches the cell count.
+pub fn apply_protection<T: Copy + Ord + Into<u64>>(
+    protection: &mut Option<Protection>,
+    before: &[T],
+    after: &mut [T],
+) {
+    if let Some(protection) = protection {
+        if protection.mask.len() == after.len() {
+            protection.suppressed += protection.veto(before, after);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{
+        create_field_1, field_get, field_set, field_step_fused, RoundingMode,
+    };
+    use crate::automaton::grid::{create_grid, index_of};
+    use crate::automaton::incremental::StepController;
+    use crate::automaton::stepping::step_automaton;
+    use crate::state::State;
+
+    fn total(cells: &[u32]) -> u64 {
+        cells.iter().map(|&v| v as u64).sum()
+    }
+
+    #[test]
+    fn test_protected_cells_are_never_born() {
+        let mut state = State::default();
+        create_grid(&mut state, 8, 8, 8);
+        // B1/S: every cell touching exactly one live cell is born
+        state.rule = crate::state::Rule {
+            birth: 1 << 1,
+            survival: 0,
+        };
+        let idx = index_of(&state, 4, 4, 4);
+        state.cells[idx] = 1;
+        let dims = [8, 8, 8];
+        assert_eq!(
+            protect_region(&mut state.protection, dims, [0, 0, 5], [8, 8, 9], true),
+            8 * 8 * 3
+        );
+        assert_eq!(
+            protect_region(&mut state.protection, dims, [9, 0, 0], [12, 8, 8], true),
+            0
+        );
+
+        step_automaton(&mut state);
+        assert_eq!(state.cells[index_of(&state, 4, 4, 3)], 1);
+        assert_eq!(state.cells[index_of(&state, 4, 4, 5)], 0);
+        let protection = state.protection.as_ref().unwrap();
+        assert_eq!(protection.suppressed, 9);
+    }
+
+    #[test]
+    fn test_field_increases_are_suppressed_and_tallied() {
+        let mut field = create_field_1(12, 4, 4, 1);
+        field.rounding = RoundingMode::Hash;
+        field_set(&mut field, 2, 2, 2, 1_000_000);
+        let dims = [12, 4, 4];
+        protect_region(&mut field.protection, dims, [6, 0, 0], [12, 4, 4], true);
+        let mut ctrl = StepController::from_field(field.clone(), 2);
+
+        let before = total(&field.cells);
+        for _ in 0..40 {
+            field_step_fused(&mut field);
+            ctrl.step_blocking();
+        }
+        let suppressed = field.protection.as_ref().unwrap().suppressed;
+        assert!(suppressed > 0);
+        assert_eq!(total(&field.cells), before - suppressed);
+        assert_eq!(field_get(&field, 6, 2, 2).unwrap().get(), 1);
+        assert_eq!(ctrl.field.cells, field.cells);
+
+        // Clearing the mask lets the flow through again
+        protect_region(&mut field.protection, dims, [0, 0, 0], [12, 4, 4], false);
+        field_step_fused(&mut field);
+        assert!(field_get(&field, 6, 2, 2).unwrap().get() > 1);
+        assert_eq!(field.protection.as_ref().unwrap().suppressed, suppressed);
+    }
+}
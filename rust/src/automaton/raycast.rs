@@ -0,0 +1,259 @@
+//! Ray queries through grids and fields (line of sight, "where does the smoke
+//! become visible").
+//!
+//! Rays are traced with a 3D DDA (Amanatides & Woo): the walk visits every
+//! cell the ray passes through, in order, stepping across one cell face at a
+//! time. Positions are in cell units with cell (x, y, z) covering
+//! [x, x + 1) × [y, y + 1) × [z, z + 1), so a ray from the middle of a cell
+//! starts at (x + 0.5, y + 0.5, z + 0.5). A ray starting outside the grid is
+//! clipped to it first.
+
+use super::field::{field_index_of, Field};
+use super::grid::index_of;
+use crate::state::State;
+
+/// The first matching cell along a ray.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    pub cell: [i16; 3],
+    /// Distance from the origin, along the ray, to where it enters the cell
+    /// (0 for a hit in the origin's cell).
+    pub distance: f64,
+}
+
+/// Why a ray could not be traced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RayError {
+    /// A zero-length direction.
+    ZeroDirection,
+    /// NaN or infinite origin, direction, or negative or NaN maximum distance.
+    NotFinite,
+}
+
+/// Walk the cells of a `dims` grid along the ray from `origin` in `direction`
+/// (any non-zero length), up to `max_dist` (which may be infinite), and return
+/// the first one `hit` accepts.
+pub fn trace(
+    dims: [i16; 3],
+    origin: [f64; 3],
+    direction: [f64; 3],
+    max_dist: f64,
+    mut hit: impl FnMut([i16; 3]) -> bool,
+) -> Result<Option<RayHit>, RayError> {
+    if origin.iter().chain(&direction).any(|v| !v.is_finite())
+        || max_dist.is_nan()
+        || max_dist < 0.0
+    {
+        return Err(RayError::NotFinite);
+    }
+    // Scaled by the largest component first, so that squaring neither
+    // overflows for huge directions nor underflows to zero for tiny ones
+    let largest = direction.iter().fold(0.0f64, |m, d| m.max(d.abs()));
+    if largest == 0.0 {
+        return Err(RayError::ZeroDirection);
+    }
+    let direction = direction.map(|d| d / largest);
+    let length = direction.iter().map(|d| d * d).sum::<f64>().sqrt();
+    let direction = direction.map(|d| d / length);
+    let size = dims.map(|e| e.max(0) as f64);
+
+    // Clip the ray to the grid's box
+    let (mut t_enter, mut t_exit) = (0.0f64, max_dist);
+    for axis in 0..3 {
+        if direction[axis] == 0.0 {
+            if origin[axis] < 0.0 || origin[axis] >= size[axis] {
+                return Ok(None);
+            }
+            continue;
+        }
+        let t0 = -origin[axis] / direction[axis];
+        let t1 = (size[axis] - origin[axis]) / direction[axis];
+        t_enter = t_enter.max(t0.min(t1));
+        t_exit = t_exit.min(t0.max(t1));
+    }
+    if t_enter > t_exit || size.contains(&0.0) {
+        return Ok(None);
+    }
+    // No path through the box is longer than its diagonal
+    let diagonal = size.iter().map(|s| s * s).sum::<f64>().sqrt();
+    let t_exit = t_exit.min(t_enter + diagonal);
+
+    let mut cell = [0i16; 3];
+    let mut step = [0i16; 3];
+    // Distance along the ray to the next face crossing on each axis, and
+    // between crossings
+    let mut t_next = [f64::INFINITY; 3];
+    let mut t_delta = [f64::INFINITY; 3];
+    for axis in 0..3 {
+        let entry = origin[axis] + direction[axis] * t_enter;
+        cell[axis] = (entry.floor() as i64).clamp(0, dims[axis] as i64 - 1) as i16;
+        if direction[axis] != 0.0 {
+            step[axis] = if direction[axis] > 0.0 { 1 } else { -1 };
+            let face = cell[axis] as f64 + if step[axis] > 0 { 1.0 } else { 0.0 };
+            t_next[axis] = (face - origin[axis]) / direction[axis];
+            t_delta[axis] = 1.0 / direction[axis].abs();
+        }
+    }
+
+    let mut distance = t_enter;
+    loop {
+        if hit(cell) {
+            return Ok(Some(RayHit { cell, distance }));
+        }
+        let axis = (0..3)
+            .min_by(|&a, &b| t_next[a].total_cmp(&t_next[b]))
+            .unwrap();
+        if t_next[axis] > t_exit {
+            return Ok(None);
+        }
+        distance = t_next[axis];
+        cell[axis] += step[axis];
+        if !(0..dims[axis]).contains(&cell[axis]) {
+            return Ok(None);
+        }
+        t_next[axis] += t_delta[axis];
+    }
+}
+
+/// The first live cell along a ray through the grid (see `trace`).
+pub fn raycast(
+    state: &State,
+    origin: [f64; 3],
+    direction: [f64; 3],
+    max_dist: f64,
+) -> Result<Option<RayHit>, RayError> {
+    let dims = [state.width, state.height, state.depth];
+    trace(dims, origin, direction, max_dist, |[x, y, z]| {
+        state.cells[index_of(state, x, y, z)] != 0
+    })
+}
+
+/// The first cell along a ray through the field whose value is above
+/// `threshold` (see `trace`).
+pub fn field_raycast(
+    field: &Field,
+    origin: [f64; 3],
+    direction: [f64; 3],
+    max_dist: f64,
+    threshold: u32,
+) -> Result<Option<RayHit>, RayError> {
+    let dims = [field.width, field.height, field.depth];
+    trace(dims, origin, direction, max_dist, |[x, y, z]| {
+        field.cells[field_index_of(field, x, y, z)] > threshold
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_set};
+    use crate::automaton::grid::create_grid;
+
+    fn grid_with(cells: &[(i16, i16, i16)]) -> State {
+        let mut state = State::default();
+        create_grid(&mut state, 8, 8, 8);
+        for &(x, y, z) in cells {
+            let idx = index_of(&state, x, y, z);
+            state.cells[idx] = 1;
+        }
+        state
+    }
+
+    #[test]
+    fn test_axis_and_diagonal_rays() {
+        let state = grid_with(&[(6, 2, 2), (5, 5, 5)]);
+        let hit = raycast(&state, [0.5, 2.5, 2.5], [1.0, 0.0, 0.0], 100.0).unwrap();
+        assert_eq!(
+            hit,
+            Some(RayHit {
+                cell: [6, 2, 2],
+                distance: 5.5
+            })
+        );
+        // Too short to reach it
+        assert_eq!(
+            raycast(&state, [0.5, 2.5, 2.5], [1.0, 0.0, 0.0], 5.0),
+            Ok(None)
+        );
+        // The direction's length does not matter
+        let hit = raycast(&state, [0.5, 0.5, 0.5], [3.0, 3.0, 3.0], 100.0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(hit.cell, [5, 5, 5]);
+        assert!((hit.distance - 4.5 * 3f64.sqrt()).abs() < 1e-9);
+        // A hit in the origin's cell
+        let hit = raycast(&state, [6.2, 2.9, 2.1], [-1.0, 0.0, 0.0], 1.0).unwrap();
+        assert_eq!(hit.map(|hit| hit.distance), Some(0.0));
+    }
+
+    #[test]
+    fn test_extreme_directions_and_unbounded_rays() {
+        let state = grid_with(&[(6, 2, 2)]);
+        let origin = [0.5, 2.5, 2.5];
+        for scale in [1e200, 1e-320, f64::MAX] {
+            let hit = raycast(&state, origin, [scale, 0.0, 0.0], f64::INFINITY).unwrap();
+            assert_eq!(
+                hit,
+                Some(RayHit {
+                    cell: [6, 2, 2],
+                    distance: 5.5
+                })
+            );
+        }
+        // An unbounded ray that misses still ends
+        let miss = raycast(&state, origin, [-1e200, 1e200, 0.0], f64::INFINITY);
+        assert_eq!(miss, Ok(None));
+        assert_eq!(
+            raycast(&state, origin, [0.0, -0.0, 0.0], f64::INFINITY),
+            Err(RayError::ZeroDirection)
+        );
+    }
+
+    #[test]
+    fn test_rays_from_outside() {
+        let state = grid_with(&[(0, 3, 3), (7, 4, 4)]);
+        // Entering through the -x face
+        let hit = raycast(&state, [-4.0, 3.5, 3.5], [1.0, 0.0, 0.0], 10.0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(hit.cell, [0, 3, 3]);
+        assert_eq!(hit.distance, 4.0);
+        // Backwards through the +x face
+        let hit = raycast(&state, [20.0, 4.5, 4.5], [-2.0, 0.0, 0.0], 100.0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(hit.cell, [7, 4, 4]);
+        assert_eq!(hit.distance, 12.0);
+        // Missing the grid, or pointing away from it
+        assert_eq!(
+            raycast(&state, [-4.0, 9.0, 3.5], [1.0, 0.0, 0.0], 100.0),
+            Ok(None)
+        );
+        assert_eq!(
+            raycast(&state, [-4.0, 3.5, 3.5], [-1.0, 0.0, 0.0], 100.0),
+            Ok(None)
+        );
+        assert_eq!(
+            raycast(&state, [1.0, 1.0, 1.0], [0.0, 0.0, 0.0], 10.0),
+            Err(RayError::ZeroDirection)
+        );
+        assert_eq!(
+            raycast(&state, [f64::NAN, 1.0, 1.0], [1.0, 0.0, 0.0], 10.0),
+            Err(RayError::NotFinite)
+        );
+    }
+
+    #[test]
+    fn test_field_threshold() {
+        let mut field = create_field_1(8, 8, 8, 2);
+        field_set(&mut field, 0, 0, 4, 50);
+        field_set(&mut field, 0, 0, 6, 500);
+        let up = [0.0, 0.0, 1.0];
+        let hit = field_raycast(&field, [0.5, 0.5, 0.5], up, 100.0, 100).unwrap();
+        assert_eq!(hit.map(|hit| hit.cell), Some([0, 0, 6]));
+        let hit = field_raycast(&field, [0.5, 0.5, 0.5], up, 100.0, 10).unwrap();
+        assert_eq!(hit.map(|hit| hit.cell), Some([0, 0, 4]));
+        let miss = field_raycast(&field, [0.5, 0.5, 0.5], up, 100.0, 1000).unwrap();
+        assert_eq!(miss, None);
+    }
+}
@@ -1,8 +1,65 @@
-//! Region extraction and import operations.
+//! Region extraction and import operations (binary State, u32 Field, u64 Field64,
+//! and i32 IField).
 
+use super::chunked::chunked_extract_region;
+use super::field::Field;
+use super::field64::Field64;
 use super::grid::index_of;
+use super::ifield::IField;
+use super::rng::hash_coord;
 use crate::state::State;
 
+/// Clamp the half-open box `[min, max)` to a grid of size `dims`.
+/// None if the clamped box is empty or inverted.
+pub(crate) fn clamp_box(
+    dims: [i16; 3],
+    min: [i16; 3],
+    max: [i16; 3],
+) -> Option<([i16; 3], [i16; 3])> {
+    let mut lo = [0i16; 3];
+    let mut hi = [0i16; 3];
+    for axis in 0..3 {
+        lo[axis] = min[axis].max(0).min(dims[axis]);
+        hi[axis] = max[axis].max(0).min(dims[axis]);
+        if lo[axis] >= hi[axis] {
+            return None;
+        }
+    }
+    Some((lo, hi))
+}
+
+/// Visit each x-row of a clamped box in z,y order as `(start_index, row_len)`.
+pub(crate) fn for_each_row(dims: [i16; 3], lo: [i16; 3], hi: [i16; 3], mut f: impl FnMut(usize, usize)) {
+    let (w, h) = (dims[0] as usize, dims[1] as usize);
+    let row_len = (hi[0] - lo[0]) as usize;
+    for z in lo[2] as usize..hi[2] as usize {
+        for y in lo[1] as usize..hi[1] as usize {
+            f((z * h + y) * w + lo[0] as usize, row_len);
+        }
+    }
+}
+
+/// Number of cells in the half-open box `[min, max)` (x, y, z) after clamping
+/// to the grid, i.e. the buffer size `extract_region` / `import_region` need.
+/// 0 if the clamped region is empty or inverted.
+pub fn region_size(state: &State, min: [i16; 3], max: [i16; 3]) -> usize {
+    clamped_volume([state.width, state.height, state.depth], min, max)
+}
+
+/// `region_size` for a field: the u32 buffer length `field_extract_region` /
+/// `field_import_region` need.
+pub fn field_region_size(field: &Field, min: [i16; 3], max: [i16; 3]) -> usize {
+    clamped_volume([field.width, field.height, field.depth], min, max)
+}
+
+/// Number of cells in `[min, max)` clamped to a grid of size `dims`.
+fn clamped_volume(dims: [i16; 3], min: [i16; 3], max: [i16; 3]) -> usize {
+    match clamp_box(dims, min, max) {
+        Some((lo, hi)) => (0..3).map(|axis| (hi[axis] - lo[axis]) as usize).product(),
+        None => 0,
+    }
+}
+
 /// Extract a rectangular region from the grid into a flat buffer.
 ///
 /// # Layout
@@ -121,6 +178,291 @@ pub fn import_region(
     offset as u64
 }
 
+/// Fill the half-open box `[min, max)` (x, y, z) with a single value.
+///
+/// Each x-row of the box is contiguous in memory, so it is written with one
+/// slice fill (memset) rather than cell by cell. The value is normalized:
+/// 0 = dead, any non-zero = alive.
+///
+/// # Returns
+/// Number of cells written, or 0 on error (no grid, or an empty/inverted region
+/// after clamping to the grid).
+pub fn fill_region(state: &mut State, min: [i16; 3], max: [i16; 3], value: u8) -> u64 {
+    let [min_x, min_y, min_z] = min;
+    let [max_x, max_y, max_z] = max;
+
+    // Clamp coordinates to grid bounds
+    let min_x = min_x.max(0).min(state.width);
+    let min_y = min_y.max(0).min(state.height);
+    let min_z = min_z.max(0).min(state.depth);
+    let max_x = max_x.max(0).min(state.width);
+    let max_y = max_y.max(0).min(state.height);
+    let max_z = max_z.max(0).min(state.depth);
+
+    // Handle empty or inverted regions
+    if min_x >= max_x || min_y >= max_y || min_z >= max_z {
+        return 0;
+    }
+
+    let normalized = if value == 0 { 0 } else { 1 };
+    let row_len = (max_x - min_x) as usize;
+
+    for z in min_z..max_z {
+        for y in min_y..max_y {
+            let start = index_of(state, min_x, y, z);
+            state.cells[start..start + row_len].fill(normalized);
+        }
+    }
+
+    row_len as u64 * (max_y - min_y) as u64 * (max_z - min_z) as u64
+}
+
+/// Extract the half-open box `[min, max)` (x, y, z) of a field into a flat
+/// u32 buffer.
+///
+/// # Layout
+/// z,y,x order (x fastest), the same layout as `extract_region`. The region is
+/// clamped to the field.
+///
+/// # Returns
+/// Number of cells written, or 0 on error (empty/inverted region after
+/// clamping, or `out` too small).
+pub fn field_extract_region(field: &Field, out: &mut [u32], min: [i16; 3], max: [i16; 3]) -> u64 {
+    let dims = [field.width, field.height, field.depth];
+    if let Some(chunks) = &field.chunks {
+        return chunked_extract_region(chunks, dims, out, min, max);
+    }
+    extract_cells(dims, &field.cells, out, min, max)
+}
+
+/// `field_extract_region` for a `Field64` (u64 buffer).
+pub fn field64_extract_region(
+    field: &Field64,
+    out: &mut [u64],
+    min: [i16; 3],
+    max: [i16; 3],
+) -> u64 {
+    let dims = [field.width, field.height, field.depth];
+    extract_cells(dims, &field.cells, out, min, max)
+}
+
+/// Copy the clamped box `[min, max)` of `cells` (a grid of size `dims`) into `out`.
+fn extract_cells<T: Copy>(
+    dims: [i16; 3],
+    cells: &[T],
+    out: &mut [T],
+    min: [i16; 3],
+    max: [i16; 3],
+) -> u64 {
+    let Some((lo, hi)) = clamp_box(dims, min, max) else {
+        return 0;
+    };
+    let total: usize = (0..3).map(|axis| (hi[axis] - lo[axis]) as usize).product();
+    if out.len() < total {
+        return 0;
+    }
+
+    let mut offset = 0;
+    for_each_row(dims, lo, hi, |start, row_len| {
+        out[offset..offset + row_len].copy_from_slice(&cells[start..start + row_len]);
+        offset += row_len;
+    });
+    offset as u64
+}
+
+/// Import the half-open box `[min, max)` (x, y, z) of a field from a flat u32
+/// buffer (layout as in `field_extract_region`).
+///
+/// Zero values are raised to 1: field cells never hold absolute zero (see
+/// `create_field`).
+///
+/// # Returns
+/// Number of cells read, or 0 on error (empty/inverted region after clamping,
+/// or `data` too short).
+pub fn field_import_region(field: &mut Field, data: &[u32], min: [i16; 3], max: [i16; 3]) -> u64 {
+    let dims = [field.width, field.height, field.depth];
+    import_cells(dims, &mut field.cells, data, min, max, 1)
+}
+
+/// `field_import_region` for a `Field64` (u64 buffer).
+pub fn field64_import_region(
+    field: &mut Field64,
+    data: &[u64],
+    min: [i16; 3],
+    max: [i16; 3],
+) -> u64 {
+    let dims = [field.width, field.height, field.depth];
+    import_cells(dims, &mut field.cells, data, min, max, 1)
+}
+
+/// `field_extract_region` for an `IField` (i32 buffer).
+pub fn ifield_extract_region(field: &IField, out: &mut [i32], min: [i16; 3], max: [i16; 3]) -> u64 {
+    let dims = [field.width, field.height, field.depth];
+    extract_cells(dims, &field.cells, out, min, max)
+}
+
+/// `field_import_region` for an `IField` (i32 buffer). Values are stored
+/// unchanged: zero and negative values are valid signed cells.
+pub fn ifield_import_region(field: &mut IField, data: &[i32], min: [i16; 3], max: [i16; 3]) -> u64 {
+    let dims = [field.width, field.height, field.depth];
+    import_cells(dims, &mut field.cells, data, min, max, i32::MIN)
+}
+
+/// Copy `data` into the clamped box `[min, max)` of `cells`, raising values
+/// below `floor` to it.
+fn import_cells<T: Copy + Ord>(
+    dims: [i16; 3],
+    cells: &mut [T],
+    data: &[T],
+    min: [i16; 3],
+    max: [i16; 3],
+    floor: T,
+) -> u64 {
+    let Some((lo, hi)) = clamp_box(dims, min, max) else {
+        return 0;
+    };
+    let total: usize = (0..3).map(|axis| (hi[axis] - lo[axis]) as usize).product();
+    if data.len() < total {
+        return 0;
+    }
+
+    let mut offset = 0;
+    for_each_row(dims, lo, hi, |start, row_len| {
+        let src = &data[offset..offset + row_len];
+        for (dst, &value) in cells[start..start + row_len].iter_mut().zip(src) {
+            *dst = value.max(floor);
+        }
+        offset += row_len;
+    });
+    offset as u64
+}
+
+/// Edge length of a Luanti mapblock.
+pub const MAPBLOCK_SIZE: i64 = 16;
+
+/// Cells (and buffer bytes) in one mapblock: 16 x 16 x 16.
+pub const MAPBLOCK_VOLUME: usize = 4096;
+
+/// Grid-space range covered by block coordinate `b` along an axis of length
+/// `dim`, clamped to the grid, plus the offset of its first cell within the
+/// block. None if the block does not overlap the grid on this axis.
+fn mapblock_span(b: i64, dim: i16) -> Option<(usize, usize, usize)> {
+    let start = b.checked_mul(MAPBLOCK_SIZE)?;
+    let lo = start.max(0);
+    let hi = start.saturating_add(MAPBLOCK_SIZE).min(dim as i64);
+    if lo >= hi {
+        return None;
+    }
+    Some((lo as usize, hi as usize, (lo - start) as usize))
+}
+
+/// Walk the x-rows of a mapblock that lie inside the grid, as
+/// `(grid_index, block_offset, row_len)`.
+fn mapblock_rows(dims: [i16; 3], block: [i64; 3], mut f: impl FnMut(usize, usize, usize)) -> u64 {
+    let (Some(xs), Some(ys), Some(zs)) = (
+        mapblock_span(block[0], dims[0]),
+        mapblock_span(block[1], dims[1]),
+        mapblock_span(block[2], dims[2]),
+    ) else {
+        return 0;
+    };
+    let (w, h) = (dims[0] as usize, dims[1] as usize);
+    let row_len = xs.1 - xs.0;
+    let block_size = MAPBLOCK_SIZE as usize;
+
+    for (bz, z) in (zs.0..zs.1).enumerate() {
+        for (by, y) in (ys.0..ys.1).enumerate() {
+            let grid_index = (z * h + y) * w + xs.0;
+            let block_offset = ((zs.2 + bz) * block_size + ys.2 + by) * block_size + xs.2;
+            f(grid_index, block_offset, row_len);
+        }
+    }
+    (row_len * (ys.1 - ys.0) * (zs.1 - zs.0)) as u64
+}
+
+/// Extract one 16x16x16 mapblock, addressed in block coordinates (block
+/// `(bx, by, bz)` covers grid cells `[16*b, 16*b + 16)` on each axis).
+///
+/// # Layout
+/// z,y,x order (x fastest), the same order as Luanti's VoxelManip / VoxelArea
+/// iteration. Cells of the block that fall outside the grid read as dead.
+///
+/// # Returns
+/// Number of block cells that lie inside the grid (0..=4096). The whole buffer
+/// is written either way.
+pub fn extract_mapblock(state: &State, block: [i64; 3], out: &mut [u8; MAPBLOCK_VOLUME]) -> u64 {
+    out.fill(0);
+    let dims = [state.width, state.height, state.depth];
+    mapblock_rows(dims, block, |grid_index, block_offset, row_len| {
+        out[block_offset..block_offset + row_len]
+            .copy_from_slice(&state.cells[grid_index..grid_index + row_len]);
+    })
+}
+
+/// Import one 16x16x16 mapblock (layout as in `extract_mapblock`).
+///
+/// Values are normalized (0 = dead, non-zero = alive). Block cells that fall
+/// outside the grid are ignored.
+///
+/// # Returns
+/// Number of cells written into the grid (0..=4096).
+pub fn import_mapblock(state: &mut State, block: [i64; 3], data: &[u8; MAPBLOCK_VOLUME]) -> u64 {
+    let dims = [state.width, state.height, state.depth];
+    let cells = &mut state.cells;
+    mapblock_rows(dims, block, |grid_index, block_offset, row_len| {
+        let src = &data[block_offset..block_offset + row_len];
+        for (dst, &value) in cells[grid_index..grid_index + row_len].iter_mut().zip(src) {
+            *dst = (value != 0) as u8;
+        }
+    })
+}
+
+/// Parts-per-million scale for `randomize_region` densities.
+pub const DENSITY_PPM_MAX: u32 = 1_000_000;
+
+/// Overwrite the half-open box `[min, max)` (x, y, z) with random soup.
+///
+/// Each cell becomes alive with probability `density_ppm / 1_000_000` (values
+/// above that are treated as 100%). The outcome of a cell depends only on
+/// `seed` and its coordinates, so the same call reproduces the same soup
+/// regardless of grid size or how the box is clipped.
+///
+/// # Returns
+/// Number of cells written, or 0 on error (no grid, or an empty/inverted region
+/// after clamping to the grid).
+pub fn randomize_region(
+    state: &mut State,
+    min: [i16; 3],
+    max: [i16; 3],
+    density_ppm: u32,
+    seed: u64,
+) -> u64 {
+    let dims = [state.width, state.height, state.depth];
+    let Some((lo, hi)) = clamp_box(dims, min, max) else {
+        return 0;
+    };
+
+    let density = density_ppm.min(DENSITY_PPM_MAX) as u128;
+    let mut written = 0;
+    for z in lo[2]..hi[2] {
+        for y in lo[1]..hi[1] {
+            for x in lo[0]..hi[0] {
+                // Map the hash uniformly onto 0..1_000_000 (multiply-shift)
+                let roll = (hash_coord(seed, x, y, z) as u128 * DENSITY_PPM_MAX as u128) >> 64;
+                let idx = index_of(state, x, y, z);
+                state.cells[idx] = (roll < density) as u8;
+                written += 1;
+            }
+        }
+    }
+    written
+}
+
+/// Set every cell in the grid to dead. The generation counter is unchanged.
+pub fn clear(state: &mut State) {
+    state.cells.fill(0);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -128,13 +470,7 @@ mod tests {
 
     #[test]
     fn test_extract_region_basic() {
-        let mut state = State {
-            width: 0,
-            height: 0,
-            depth: 0,
-            cells: Vec::new(),
-            generation: 0,
-        };
+        let mut state = State::default();
 
         create_grid(&mut state, 8, 8, 8);
 
@@ -162,13 +498,7 @@ mod tests {
 
     #[test]
     fn test_extract_region_full_grid() {
-        let mut state = State {
-            width: 0,
-            height: 0,
-            depth: 0,
-            cells: Vec::new(),
-            generation: 0,
-        };
+        let mut state = State::default();
 
         create_grid(&mut state, 4, 4, 4);
 
@@ -186,13 +516,7 @@ mod tests {
 
     #[test]
     fn test_extract_region_empty() {
-        let mut state = State {
-            width: 0,
-            height: 0,
-            depth: 0,
-            cells: Vec::new(),
-            generation: 0,
-        };
+        let mut state = State::default();
 
         create_grid(&mut state, 4, 4, 4);
 
@@ -205,13 +529,7 @@ mod tests {
 
     #[test]
     fn test_extract_region_out_of_bounds() {
-        let mut state = State {
-            width: 0,
-            height: 0,
-            depth: 0,
-            cells: Vec::new(),
-            generation: 0,
-        };
+        let mut state = State::default();
 
         create_grid(&mut state, 4, 4, 4);
 
@@ -224,13 +542,7 @@ mod tests {
 
     #[test]
     fn test_import_region_basic() {
-        let mut state = State {
-            width: 0,
-            height: 0,
-            depth: 0,
-            cells: Vec::new(),
-            generation: 0,
-        };
+        let mut state = State::default();
 
         create_grid(&mut state, 8, 8, 8);
 
@@ -249,13 +561,7 @@ mod tests {
 
     #[test]
     fn test_import_region_normalization() {
-        let mut state = State {
-            width: 0,
-            height: 0,
-            depth: 0,
-            cells: Vec::new(),
-            generation: 0,
-        };
+        let mut state = State::default();
 
         create_grid(&mut state, 4, 4, 4);
 
@@ -279,15 +585,203 @@ mod tests {
         assert_eq!(state.cells[index_of(&state, 0, 1, 0)], 1);
     }
 
+    #[test]
+    fn test_region_size() {
+        let mut state = State::default();
+
+        create_grid(&mut state, 8, 4, 2);
+
+        assert_eq!(region_size(&state, [0, 0, 0], [8, 4, 2]), 64);
+        assert_eq!(region_size(&state, [-5, 1, 0], [3, 100, 1]), 9);
+        assert_eq!(region_size(&state, [4, 0, 0], [2, 4, 2]), 0);
+        assert_eq!(region_size(&state, [8, 0, 0], [12, 4, 2]), 0);
+    }
+
+    #[test]
+    fn test_fill_region() {
+        let mut state = State::default();
+
+        create_grid(&mut state, 8, 8, 8);
+
+        assert_eq!(fill_region(&mut state, [2, 3, 4], [5, 5, 6], 7), 12);
+        assert_eq!(state.cells.iter().filter(|&&c| c == 1).count(), 12);
+        assert_eq!(state.cells[index_of(&state, 2, 3, 4)], 1);
+        assert_eq!(state.cells[index_of(&state, 4, 4, 5)], 1);
+        assert_eq!(state.cells[index_of(&state, 5, 4, 5)], 0);
+        assert_eq!(state.cells[index_of(&state, 1, 3, 4)], 0);
+
+        // Clamped to the grid, and a zero value kills cells
+        assert_eq!(
+            fill_region(&mut state, [-4, -4, -4], [100, 100, 100], 1),
+            512
+        );
+        assert_eq!(fill_region(&mut state, [0, 0, 0], [8, 8, 1], 0), 64);
+        assert_eq!(state.cells.iter().filter(|&&c| c == 1).count(), 448);
+
+        // Inverted and empty regions write nothing
+        assert_eq!(fill_region(&mut state, [4, 0, 0], [2, 8, 8], 0), 0);
+        assert_eq!(fill_region(&mut state, [9, 9, 9], [12, 12, 12], 0), 0);
+        assert_eq!(state.cells.iter().filter(|&&c| c == 1).count(), 448);
+    }
+
+    #[test]
+    fn test_randomize_region() {
+        let mut a = State::default();
+        create_grid(&mut a, 16, 16, 16);
+
+        assert_eq!(
+            randomize_region(&mut a, [0, 0, 0], [16, 16, 16], 250_000, 7),
+            4096
+        );
+        let alive = a.cells.iter().filter(|&&c| c == 1).count();
+        assert!((900..1150).contains(&alive), "alive = {}", alive);
+
+        // Reproducible, and independent of clipping and grid size
+        let mut b = State::default();
+        create_grid(&mut b, 20, 20, 20);
+        randomize_region(&mut b, [-3, -3, -3], [16, 16, 16], 250_000, 7);
+        for (x, y, z) in [(0, 0, 0), (5, 9, 2), (15, 15, 15), (3, 14, 8)] {
+            assert_eq!(
+                a.cells[index_of(&a, x, y, z)],
+                b.cells[index_of(&b, x, y, z)]
+            );
+        }
+
+        let mut c = State::default();
+        create_grid(&mut c, 16, 16, 16);
+        randomize_region(&mut c, [0, 0, 0], [16, 16, 16], 250_000, 8);
+        assert_ne!(a.cells, c.cells);
+
+        // Density extremes overwrite the whole box
+        randomize_region(&mut c, [0, 0, 0], [16, 16, 16], 0, 1);
+        assert!(c.cells.iter().all(|&v| v == 0));
+        randomize_region(&mut c, [0, 0, 0], [16, 16, 16], u32::MAX, 1);
+        assert!(c.cells.iter().all(|&v| v == 1));
+
+        assert_eq!(
+            randomize_region(&mut c, [4, 0, 0], [2, 8, 8], 500_000, 1),
+            0
+        );
+    }
+
+    #[test]
+    fn test_mapblock_round_trip() {
+        let mut state = State::default();
+        create_grid(&mut state, 40, 20, 16);
+
+        let idx = index_of(&state, 17, 3, 15);
+        state.cells[idx] = 1;
+        let idx = index_of(&state, 39, 19, 0);
+        state.cells[idx] = 1;
+
+        let mut block = [7u8; MAPBLOCK_VOLUME];
+        assert_eq!(extract_mapblock(&state, [1, 0, 0], &mut block), 4096);
+        // VoxelArea order: (z * 16 + y) * 16 + x within the block
+        assert_eq!(block[(15 * 16 + 3) * 16 + 1], 1);
+        assert_eq!(block.iter().filter(|&&c| c == 1).count(), 1);
+
+        // Partial block at the grid edge: x 32..40, y 16..20
+        assert_eq!(extract_mapblock(&state, [2, 1, 0], &mut block), 8 * 4 * 16);
+        assert_eq!(block[3 * 16 + 7], 1);
+        assert_eq!(block[3 * 16 + 8], 0);
+
+        let mut copy = State::default();
+        create_grid(&mut copy, 40, 20, 16);
+        for bx in 0..3 {
+            for by in 0..2 {
+                extract_mapblock(&state, [bx, by, 0], &mut block);
+                import_mapblock(&mut copy, [bx, by, 0], &block);
+            }
+        }
+        assert_eq!(copy.cells, state.cells);
+    }
+
+    #[test]
+    fn test_mapblock_outside_grid() {
+        let mut state = State::default();
+        create_grid(&mut state, 16, 16, 16);
+        fill_region(&mut state, [0, 0, 0], [16, 16, 16], 1);
+
+        let mut block = [1u8; MAPBLOCK_VOLUME];
+        assert_eq!(extract_mapblock(&state, [-1, 0, 0], &mut block), 0);
+        assert!(block.iter().all(|&c| c == 0));
+        // Block coordinates far beyond i16 range must not overflow
+        assert_eq!(
+            extract_mapblock(&state, [i64::MAX, 0, i64::MIN], &mut block),
+            0
+        );
+
+        let block = [0u8; MAPBLOCK_VOLUME];
+        assert_eq!(import_mapblock(&mut state, [1, 0, 0], &block), 0);
+        assert_eq!(import_mapblock(&mut state, [0, 0, 0], &block), 4096);
+        assert!(state.cells.iter().all(|&c| c == 0));
+    }
+
+    #[test]
+    fn test_field_region_round_trip() {
+        use crate::automaton::field::{create_field_1, field_index_of};
+
+        let mut field = create_field_1(8, 8, 8, 3);
+        let idx = field_index_of(&field, 3, 2, 1);
+        field.cells[idx] = 5000;
+
+        let mut buffer = vec![0u32; 27];
+        assert_eq!(
+            field_extract_region(&field, &mut buffer, [2, 1, 0], [5, 4, 3]),
+            27
+        );
+        // (3,2,1) relative to (2,1,0) -> (1,1,1) -> 9 + 3 + 1
+        assert_eq!(buffer[13], 5000);
+        assert_eq!(buffer.iter().filter(|&&v| v == 1).count(), 26);
+
+        // Clamped, buffer too small, inverted
+        assert_eq!(
+            field_extract_region(&field, &mut buffer, [6, 6, 6], [20, 20, 20]),
+            8
+        );
+        assert_eq!(
+            field_extract_region(&field, &mut buffer, [0, 0, 0], [4, 4, 4]),
+            0
+        );
+        assert_eq!(
+            field_extract_region(&field, &mut buffer, [3, 0, 0], [1, 1, 1]),
+            0
+        );
+
+        let mut copy = create_field_1(8, 8, 8, 3);
+        let data: Vec<u32> = (0..27).collect();
+        assert_eq!(
+            field_import_region(&mut copy, &data, [2, 1, 0], [5, 4, 3]),
+            27
+        );
+        // Absolute zero is raised to the minimum quantum
+        assert_eq!(copy.cells[field_index_of(&copy, 2, 1, 0)], 1);
+        assert_eq!(copy.cells[field_index_of(&copy, 3, 2, 1)], 13);
+        assert_eq!(copy.cells[field_index_of(&copy, 4, 3, 2)], 26);
+        assert_eq!(
+            field_import_region(&mut copy, &data[..26], [2, 1, 0], [5, 4, 3]),
+            0
+        );
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut state = State::default();
+
+        create_grid(&mut state, 4, 4, 4);
+        fill_region(&mut state, [0, 0, 0], [4, 4, 4], 1);
+        state.generation = 3;
+
+        clear(&mut state);
+
+        assert!(state.cells.iter().all(|&c| c == 0));
+        assert_eq!(state.cells.len(), 64);
+        assert_eq!(state.generation, 3);
+    }
+
     #[test]
     fn test_extract_import_symmetry() {
-        let mut state1 = State {
-            width: 0,
-            height: 0,
-            depth: 0,
-            cells: Vec::new(),
-            generation: 0,
-        };
+        let mut state1 = State::default();
 
         create_grid(&mut state1, 8, 8, 8);
 
@@ -303,13 +797,7 @@ mod tests {
         extract_region(&state1, &mut extract_buffer, 0, 0, 0, 4, 4, 4);
 
         // Create new state and import
-        let mut state2 = State {
-            width: 0,
-            height: 0,
-            depth: 0,
-            cells: Vec::new(),
-            generation: 0,
-        };
+        let mut state2 = State::default();
 
         create_grid(&mut state2, 8, 8, 8);
         import_region(&mut state2, &extract_buffer, 0, 0, 0, 4, 4, 4);
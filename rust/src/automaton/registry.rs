@@ -0,0 +1,166 @@
+//! Slab of values addressed by generation-checked u32 ids.
+//!
+//! An id packs a slot index (low `ID_INDEX_BITS` bits) with the slot's
+//! generation (high bits). Removing a value bumps its slot's generation, so an
+//! id kept after removal no longer matches even once the slot is reused: a
+//! double free or use-after-free through an id is a failed lookup rather than
+//! a dangling pointer. Generations wrap after 4095 reuses of one slot, and 0 is
+//! never a valid id.
+
+/// Bits of an id holding the slot index.
+pub const ID_INDEX_BITS: u32 = 20;
+
+/// Most values a slab holds at once.
+pub const MAX_IDS: usize = 1 << ID_INDEX_BITS;
+
+const INDEX_MASK: u32 = (1 << ID_INDEX_BITS) - 1;
+const MAX_GENERATION: u32 = u32::MAX >> ID_INDEX_BITS;
+
+struct Slot<T> {
+    /// Generation of the slot's current (or next) value, 1..=MAX_GENERATION.
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Values addressed by u32 ids (see the module docs).
+pub struct IdSlab<T> {
+    slots: Vec<Slot<T>>,
+    /// Indices of empty slots, reused last-freed first.
+    free: Vec<u32>,
+}
+
+impl<T> Default for IdSlab<T> {
+    fn default() -> Self {
+        IdSlab {
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+}
+
+impl<T> IdSlab<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store `value` and return its id, or give it back if the slab is full.
+    pub fn insert(&mut self, value: T) -> Result<u32, T> {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None if self.slots.len() < MAX_IDS => {
+                self.slots.push(Slot {
+                    generation: 1,
+                    value: None,
+                });
+                (self.slots.len() - 1) as u32
+            }
+            None => return Err(value),
+        };
+        let slot = &mut self.slots[index as usize];
+        slot.value = Some(value);
+        Ok(slot.generation << ID_INDEX_BITS | index)
+    }
+
+    /// Slot of a live id.
+    fn slot(&self, id: u32) -> Option<&Slot<T>> {
+        let slot = self.slots.get((id & INDEX_MASK) as usize)?;
+        (slot.generation == id >> ID_INDEX_BITS && slot.value.is_some()).then_some(slot)
+    }
+
+    /// The value of a live id.
+    pub fn get(&self, id: u32) -> Option<&T> {
+        self.slot(id)?.value.as_ref()
+    }
+
+    /// Mutable access to the value of a live id.
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut T> {
+        self.slot(id)?;
+        self.slots[(id & INDEX_MASK) as usize].value.as_mut()
+    }
+
+    /// Take the value of a live id out, retiring the id.
+    pub fn remove(&mut self, id: u32) -> Option<T> {
+        self.slot(id)?;
+        let index = id & INDEX_MASK;
+        let slot = &mut self.slots[index as usize];
+        slot.generation = slot.generation % MAX_GENERATION + 1;
+        self.free.push(index);
+        slot.value.take()
+    }
+
+    /// Live ids, in slot order.
+    pub fn ids(&self) -> Vec<u32> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.value.is_some())
+            .map(|(index, slot)| slot.generation << ID_INDEX_BITS | index as u32)
+            .collect()
+    }
+
+    /// Number of live values.
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop every value, retiring all ids. Returns how many there were.
+    pub fn clear(&mut self) -> usize {
+        let live = self.ids();
+        for &id in &live {
+            self.remove(id);
+        }
+        live.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_ids_are_rejected() {
+        let mut slab = IdSlab::new();
+        let a = slab.insert("a").unwrap();
+        let b = slab.insert("b").unwrap();
+        assert_ne!(a, 0);
+        assert_eq!(slab.get(a), Some(&"a"));
+        assert_eq!(slab.ids(), vec![a, b]);
+
+        assert_eq!(slab.remove(a), Some("a"));
+        assert_eq!(slab.remove(a), None);
+        assert_eq!(slab.get(a), None);
+
+        // The slot is reused under a new id; the old one stays dead
+        let c = slab.insert("c").unwrap();
+        assert_eq!(c & INDEX_MASK, a & INDEX_MASK);
+        assert_ne!(c, a);
+        assert_eq!(slab.get(a), None);
+        *slab.get_mut(c).unwrap() = "c2";
+        assert_eq!(slab.get(c), Some(&"c2"));
+        assert_eq!(slab.get(0), None);
+        assert_eq!(slab.get(u32::MAX), None);
+
+        assert_eq!(slab.len(), 2);
+        assert_eq!(slab.clear(), 2);
+        assert!(slab.is_empty());
+        assert_eq!(slab.get(b), None);
+    }
+
+    #[test]
+    fn test_generation_wraps_without_zero() {
+        let mut slab = IdSlab::new();
+        let first = slab.insert(0).unwrap();
+        let mut id = first;
+        for _ in 0..MAX_GENERATION {
+            slab.remove(id);
+            id = slab.insert(0).unwrap();
+            assert_ne!(id >> ID_INDEX_BITS, 0);
+        }
+        // A full cycle later the first id is live again
+        assert_eq!(id, first);
+    }
+}
@@ -0,0 +1,188 @@
+//! Debug reports: a compact text summary of a handle for bug reports.
+//!
+//! A report is sectioned `key = value` text in the TOML subset of the
+//! configuration blobs, short enough to paste into an issue: the binary, the
+//! handle's dimensions, generation, content hash and settings, its counters,
+//! the time one step takes right now (a dry run, so the handle is untouched),
+//! the mass ledger of a field, and the most recent errors the caller passes
+//! in. With the settings and a snapshot, a maintainer can rebuild the handle;
+//! with the hash, they can tell whether their replay reached the same state.
+
+use std::fmt::Write;
+use std::time::Instant;
+
+use super::boundary::Boundary;
+use super::config::{field_config, state_config};
+use super::field::{field_step_selected, Field};
+use super::hash::{field_hash, state_hash};
+use super::stepping::step_preview;
+use crate::state::State;
+
+/// The binary's version, profile and target.
+fn build_section(out: &mut String) {
+    let profile = if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    };
+    // Writing to a String cannot fail
+    let _ = writeln!(out, "version = \"{}\"", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(out, "profile = \"{profile}\"");
+    let _ = writeln!(
+        out,
+        "target = \"{}-{}\"",
+        std::env::consts::ARCH,
+        std::env::consts::OS
+    );
+}
+
+fn errors_section(out: &mut String, errors: &[String]) {
+    let quoted: Vec<String> = errors
+        .iter()
+        .map(|error| format!("\"{}\"", error.replace(['"', '\\', '\n'], "'")))
+        .collect();
+    let _ = writeln!(out, "\n[errors]\nrecent = [{}]", quoted.join(", "));
+}
+
+/// Report on a grid; `errors` are the most recent failures, oldest first.
+pub fn state_report(state: &State, errors: &[String]) -> String {
+    let mut out = String::new();
+    build_section(&mut out);
+
+    let _ = writeln!(out, "\n[grid]");
+    let _ = writeln!(
+        out,
+        "size = [{}, {}, {}]",
+        state.width, state.height, state.depth
+    );
+    let _ = writeln!(out, "generation = {}", state.generation);
+    let _ = writeln!(out, "hash = \"{:#018x}\"", state_hash(state));
+    out.push_str(&state_config(state));
+    let _ = writeln!(out, "mode = \"{:?}\"", state.mode);
+    let _ = writeln!(out, "transitions = {}", state.transitions.is_some());
+    let _ = writeln!(out, "species = {}", state.species.is_some());
+    let max_age = state.age.as_ref().map(|age| age.max_age as i64);
+    let _ = writeln!(out, "max_age = {}", max_age.unwrap_or(-1));
+
+    let _ = writeln!(out, "\n[counters]");
+    let live = state.cells.iter().filter(|&&cell| cell != 0).count();
+    let _ = writeln!(out, "live_cells = {live}");
+    let suppressed = state.protection.as_ref().map_or(0, |p| p.suppressed);
+    let _ = writeln!(out, "suppressed_births = {suppressed}");
+    let queue = state.write_queue.as_ref();
+    let _ = writeln!(out, "pending_writes = {}", queue.map_or(0, |q| q.len()));
+    let _ = writeln!(
+        out,
+        "overflowed_writes = {}",
+        queue.map_or(0, |q| q.overflowed)
+    );
+    let _ = writeln!(
+        out,
+        "checkpoints = {}",
+        state.history.as_ref().map_or(0, |h| h.len())
+    );
+    let window = state.cycles.as_ref().map_or(0, |c| c.window());
+    let _ = writeln!(out, "cycle_window = {window}");
+
+    let start = Instant::now();
+    let changes = step_preview(state).len();
+    let _ = writeln!(out, "\n[timing]");
+    let _ = writeln!(out, "step_ns = {}", start.elapsed().as_nanos());
+    let _ = writeln!(out, "step_changes = {changes}");
+
+    errors_section(&mut out, errors);
+    out
+}
+
+/// Report on a field; `errors` are the most recent failures, oldest first.
+pub fn field_report(field: &Field, errors: &[String]) -> String {
+    let mut out = String::new();
+    build_section(&mut out);
+
+    let _ = writeln!(out, "\n[field]");
+    let _ = writeln!(
+        out,
+        "size = [{}, {}, {}]",
+        field.width, field.height, field.depth
+    );
+    let _ = writeln!(out, "generation = {}", field.generation);
+    let _ = writeln!(out, "hash = \"{:#018x}\"", field_hash(field));
+    out.push_str(&field_config(field));
+
+    // Where mass enters and leaves: without sources, sinks, non-reflective
+    // faces or protection, the total is constant from step to step
+    let _ = writeln!(out, "\n[ledger]");
+    let total: u64 = field.cells.iter().map(|&v| v as u64).sum();
+    let _ = writeln!(out, "total = {total}");
+    let min = field.cells.iter().min().copied().unwrap_or(0);
+    let max = field.cells.iter().max().copied().unwrap_or(0);
+    let _ = writeln!(out, "min = {min}\nmax = {max}");
+    let injected: i64 = field.sources.values().filter(|&&r| r > 0).sum();
+    let drained: i64 = field.sources.values().filter(|&&r| r < 0).sum();
+    let _ = writeln!(out, "source_rate = {injected}\nsink_rate = {}", -drained);
+    let open = (0..6)
+        .filter(|&face| !field.periodic[face / 2])
+        .filter(|&face| field.boundaries[face] != Boundary::Reflective)
+        .count();
+    let _ = writeln!(out, "open_faces = {open}");
+    let suppressed = field.protection.as_ref().map_or(0, |p| p.suppressed);
+    let _ = writeln!(out, "suppressed = {suppressed}");
+
+    // A copy keeps the field (and its flow record) untouched
+    let mut probe = field.clone();
+    probe.flow_record = None;
+    let start = Instant::now();
+    field_step_selected(&mut probe);
+    let elapsed = start.elapsed().as_nanos();
+    let after: u64 = probe.cells.iter().map(|&v| v as u64).sum();
+    let _ = writeln!(out, "step_delta = {}", after as i128 - total as i128);
+    let _ = writeln!(out, "\n[timing]\nstep_ns = {elapsed}");
+
+    errors_section(&mut out, errors);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::config::apply_field_config;
+    use crate::automaton::field::{create_field_1, field_set};
+    use crate::automaton::grid::create_grid;
+    use crate::automaton::stamp::{stamp_pattern, STAMP_GLIDER};
+
+    #[test]
+    fn test_state_report() {
+        let mut state = State::default();
+        create_grid(&mut state, 10, 10, 10);
+        stamp_pattern(&mut state, STAMP_GLIDER, 3, 3, 3, 0).unwrap();
+        let before = state.cells.clone();
+        let report = state_report(&state, &["va_set_config: \"bad\"".to_string()]);
+        assert!(report.contains("size = [10, 10, 10]\n"));
+        assert!(report.contains("rule = \"B4/S4\"\n"));
+        assert!(report.contains(&format!("hash = \"{:#018x}\"", state_hash(&state))));
+        assert!(report.contains("live_cells = "));
+        assert!(report.contains("\n[timing]\nstep_ns = "));
+        assert!(report.ends_with("recent = [\"va_set_config: 'bad'\"]\n"));
+        assert_eq!(state.cells, before);
+        assert_eq!(state.generation, 0);
+    }
+
+    #[test]
+    fn test_field_report_ledger() {
+        let mut field = create_field_1(4, 4, 4, 2);
+        field_set(&mut field, 1, 1, 1, 10_000);
+        apply_field_config(
+            &mut field,
+            "sources = [[0, 0, 0, 50], [3, 3, 3, -20]]\nboundaries = [\"open\", \"reflective\", \"reflective\", \"reflective\", \"fixed:5\", \"reflective\"]\nperiodic = [1, 0, 0]",
+        )
+        .unwrap();
+        let report = field_report(&field, &[]);
+        assert!(report.contains(&format!("total = {}\n", 10_000 + 63)));
+        assert!(report.contains("source_rate = 50\nsink_rate = 20\n"));
+        // The open face is on a periodic axis
+        assert!(report.contains("open_faces = 1\n"));
+        assert!(report.contains("diffusion_rate = 2\n"));
+        assert!(report.ends_with("recent = []\n"));
+        assert_eq!(field.generation, 0);
+    }
+}
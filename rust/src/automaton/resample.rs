@@ -0,0 +1,296 @@
+//! Conservative resolution changes between a coarse and a fine field.
+//!
+//! LOD tiers and mapgen hand a region between resolutions over its lifetime:
+//! generated coarse, refined when a player comes close, aggregated again
+//! when they leave. Both directions conserve the total exactly.
+//!
+//! A fine field covers its coarse field with `factor`³ fine cells per coarse
+//! cell, so its dimensions are the coarse ones times `factor`. Refinement
+//! splits each coarse value evenly over its block; the `value % factor³`
+//! leftover units go one each to block cells picked by a hash of the coarse
+//! index, so no corner of the block is systematically heavier. Aggregation
+//! sums each block. Refine then aggregate is the identity.
+//!
+//! Every fine cell must keep the minimum quantum of 1, so refinement needs at
+//! least `factor³` in every coarse cell. Both operations check the whole field
+//! before writing anything: on error neither field is modified.
+//!
+//! Downsampling (`field_downsample`) is the lossy, display-side counterpart of
+//! aggregation: each block becomes its average rather than its sum, so a
+//! distant chunk renders on the same value scale as a near one. Edge blocks of
+//! a field whose size is not a multiple of `factor` average only the cells they
+//! cover.
+
+use super::field::Field;
+use super::rng::mix64;
+
+/// Why a resolution change was refused (neither field is modified).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResampleError {
+    /// `factor` is 0, or the fine dimensions are not the coarse ones times `factor`.
+    Dimensions,
+    /// A coarse cell holds less than `factor`³, too little to leave 1 in every
+    /// fine cell of its block (first offending coarse cell).
+    InsufficientMass { x: i16, y: i16, z: i16 },
+    /// A block sums to more than u32::MAX (first offending coarse cell).
+    Overflow { x: i16, y: i16, z: i16 },
+}
+
+/// Check that `fine` is `coarse` scaled by `factor` on every axis.
+fn check_dims(coarse: &Field, fine: &Field, factor: u8) -> Result<usize, ResampleError> {
+    let f = factor as i32;
+    let scaled = |c: i16| c as i32 * f;
+    if factor == 0
+        || scaled(coarse.width) != fine.width as i32
+        || scaled(coarse.height) != fine.height as i32
+        || scaled(coarse.depth) != fine.depth as i32
+    {
+        return Err(ResampleError::Dimensions);
+    }
+    Ok(factor as usize)
+}
+
+/// Coarse (x, y, z) of coarse index `idx`.
+fn coarse_coords(coarse: &Field, idx: usize) -> (i16, i16, i16) {
+    let (w, h) = (coarse.width as usize, coarse.height as usize);
+    (
+        (idx % w) as i16,
+        (idx / w % h) as i16,
+        (idx / (w * h)) as i16,
+    )
+}
+
+/// Visit the fine indices of the block under every coarse cell, in z,y,x
+/// order within the block: `visit(coarse_idx, block_offset, fine_idx)`.
+fn for_each_block(
+    coarse: &Field,
+    fine_dims: (usize, usize),
+    f: usize,
+    mut visit: impl FnMut(usize, usize, usize),
+) {
+    let (cw, ch, cd) = (
+        coarse.width as usize,
+        coarse.height as usize,
+        coarse.depth as usize,
+    );
+    let (fw, fh) = fine_dims;
+    for cz in 0..cd {
+        for cy in 0..ch {
+            for cx in 0..cw {
+                let coarse_idx = (cz * ch + cy) * cw + cx;
+                let mut offset = 0;
+                for dz in 0..f {
+                    for dy in 0..f {
+                        let row = ((cz * f + dz) * fh + cy * f + dy) * fw + cx * f;
+                        for dx in 0..f {
+                            visit(coarse_idx, offset, row + dx);
+                            offset += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Redistribute `coarse` into `fine` (see the module docs). `fine` takes the
+/// coarse generation; its other settings are kept.
+pub fn field_refine(coarse: &Field, fine: &mut Field, factor: u8) -> Result<(), ResampleError> {
+    let f = check_dims(coarse, fine, factor)?;
+    let block = (f * f * f) as u32;
+    if let Some(idx) = coarse.cells.iter().position(|&v| v < block) {
+        let (x, y, z) = coarse_coords(coarse, idx);
+        return Err(ResampleError::InsufficientMass { x, y, z });
+    }
+
+    let fine_dims = (fine.width as usize, fine.height as usize);
+    let cells = &mut fine.cells;
+    for_each_block(coarse, fine_dims, f, |coarse_idx, offset, fine_idx| {
+        let value = coarse.cells[coarse_idx];
+        let (base, extra) = (value / block, value % block);
+        // Rotate the leftover units to a hashed starting cell of the block
+        let start = (mix64(coarse_idx as u64) % block as u64) as u32;
+        let slot = (offset as u32 + block - start) % block;
+        cells[fine_idx] = base + (slot < extra) as u32;
+    });
+    fine.generation = coarse.generation;
+    Ok(())
+}
+
+/// Sum each block of `fine` into the matching cell of `coarse` (see the
+/// module docs). `coarse` takes the fine generation; its other settings are kept.
+pub fn field_aggregate(fine: &Field, coarse: &mut Field, factor: u8) -> Result<(), ResampleError> {
+    let f = check_dims(coarse, fine, factor)?;
+    let fine_dims = (fine.width as usize, fine.height as usize);
+    let mut sums = vec![0u64; coarse.cells.len()];
+    for_each_block(coarse, fine_dims, f, |coarse_idx, _, fine_idx| {
+        sums[coarse_idx] += fine.cells[fine_idx] as u64;
+    });
+    if let Some(idx) = sums.iter().position(|&sum| sum > u32::MAX as u64) {
+        let (x, y, z) = coarse_coords(coarse, idx);
+        return Err(ResampleError::Overflow { x, y, z });
+    }
+
+    for (cell, sum) in coarse.cells.iter_mut().zip(sums) {
+        *cell = sum as u32;
+    }
+    coarse.generation = fine.generation;
+    Ok(())
+}
+
+/// Dimensions of `field` downsampled by `factor` (partial edge blocks count),
+/// or None for a factor of 0.
+pub fn downsampled_dims(field: &Field, factor: u8) -> Option<[i16; 3]> {
+    let f = factor as i32;
+    let reduce = |d: i16| ((d as i32 + f - 1) / f) as i16;
+    (factor > 0).then(|| {
+        [
+            reduce(field.width),
+            reduce(field.height),
+            reduce(field.depth),
+        ]
+    })
+}
+
+/// Write the average of every `factor`³ block of `field` into `out`, in z,y,x
+/// order over `downsampled_dims`. Averages are exact integer quotients of the
+/// block sum, rounded half to even. Returns the number of cells written, or 0
+/// if the factor is 0 or `out` is too small.
+pub fn field_downsample(field: &Field, factor: u8, out: &mut [u32]) -> u64 {
+    let Some([cw, ch, cd]) = downsampled_dims(field, factor) else {
+        return 0;
+    };
+    let (cw, ch, cd) = (cw as usize, ch as usize, cd as usize);
+    let total = cw * ch * cd;
+    if out.len() < total {
+        return 0;
+    }
+    let (w, h, d) = (
+        field.width as usize,
+        field.height as usize,
+        field.depth as usize,
+    );
+    let f = factor as usize;
+    let mut sums = vec![0u64; total];
+    let mut counts = vec![0u64; total];
+    for z in 0..d {
+        for y in 0..h {
+            let row = (z * h + y) * w;
+            let coarse_row = (z / f * ch + y / f) * cw;
+            for x in 0..w {
+                sums[coarse_row + x / f] += field.cells[row + x] as u64;
+                counts[coarse_row + x / f] += 1;
+            }
+        }
+    }
+    for ((cell, sum), count) in out.iter_mut().zip(sums).zip(counts) {
+        let (quotient, remainder) = (sum / count, sum % count);
+        let round_up = 2 * remainder > count || (2 * remainder == count && quotient % 2 == 1);
+        *cell = (quotient + round_up as u64) as u32;
+    }
+    total as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_get, field_set, field_step};
+
+    fn total(field: &Field) -> u64 {
+        field.cells.iter().map(|&v| v as u64).sum()
+    }
+
+    #[test]
+    fn test_refine_aggregate_round_trip() {
+        let mut coarse = create_field_1(3, 2, 4, 2);
+        for (i, cell) in coarse.cells.iter_mut().enumerate() {
+            *cell = 27 + (i as u32 * 7919) % 1000;
+        }
+        field_set(&mut coarse, 2, 1, 3, 4_000_000_000);
+        coarse.generation = 12;
+        let original = coarse.cells.clone();
+
+        let mut fine = create_field_1(9, 6, 12, 2);
+        assert_eq!(field_refine(&coarse, &mut fine, 3), Ok(()));
+        assert_eq!(total(&fine), total(&coarse));
+        assert_eq!(fine.generation, 12);
+        assert!(fine.cells.iter().all(|&v| v >= 1));
+        // A block differs by at most one unit between its cells
+        let block: Vec<u32> = (6..9)
+            .flat_map(|x| (3..6).flat_map(move |y| (9..12).map(move |z| (x, y, z))))
+            .map(|(x, y, z)| field_get(&fine, x, y, z).unwrap().get())
+            .collect();
+        let (lo, hi) = (block.iter().min().unwrap(), block.iter().max().unwrap());
+        assert!(hi - lo <= 1 && *lo == 4_000_000_000 / 27);
+
+        coarse.cells.fill(1);
+        assert_eq!(field_aggregate(&fine, &mut coarse, 3), Ok(()));
+        assert_eq!(coarse.cells, original);
+
+        // Mass that moved on the fine grid is still conserved in aggregate
+        for _ in 0..5 {
+            field_step(&mut fine);
+        }
+        assert_eq!(field_aggregate(&fine, &mut coarse, 3), Ok(()));
+        assert_eq!(total(&coarse), total(&fine));
+        assert_eq!(coarse.generation, 17);
+    }
+
+    #[test]
+    fn test_downsample_averages_blocks() {
+        let mut field = create_field_1(5, 4, 2, 0);
+        for (i, cell) in field.cells.iter_mut().enumerate() {
+            *cell = i as u32 * 10 + 1;
+        }
+        assert_eq!(downsampled_dims(&field, 2), Some([3, 2, 1]));
+        assert_eq!(downsampled_dims(&field, 0), None);
+        let mut out = [0u32; 6];
+        assert_eq!(field_downsample(&field, 2, &mut out[..5]), 0);
+        assert_eq!(field_downsample(&field, 2, &mut out), 6);
+        // Block (0, 0, 0): cells 0, 1, 5, 6, 20, 21, 25, 26 average 131
+        assert_eq!(out[0], 131);
+        // Edge block x = 4 covers a single column: cells 4, 9, 24, 29
+        assert_eq!(out[2], (41 + 91 + 241 + 291) / 4);
+
+        // Halves round to even
+        let mut field = create_field_1(2, 1, 1, 0);
+        field.cells.copy_from_slice(&[1, 2]);
+        field_downsample(&field, 2, &mut out);
+        assert_eq!(out[0], 2);
+        field.cells.copy_from_slice(&[2, 3]);
+        field_downsample(&field, 2, &mut out);
+        assert_eq!(out[0], 2);
+        field.cells.fill(u32::MAX);
+        field_downsample(&field, 8, &mut out);
+        assert_eq!(out[0], u32::MAX);
+    }
+
+    #[test]
+    fn test_refusals_leave_fields_untouched() {
+        let mut coarse = create_field_1(2, 2, 2, 2);
+        coarse.cells.fill(100);
+        let mut fine = create_field_1(4, 4, 4, 2);
+        assert_eq!(
+            field_refine(&coarse, &mut create_field_1(4, 4, 5, 2), 2),
+            Err(ResampleError::Dimensions)
+        );
+        assert_eq!(
+            field_refine(&coarse, &mut fine, 0),
+            Err(ResampleError::Dimensions)
+        );
+
+        field_set(&mut coarse, 1, 0, 1, 7);
+        assert_eq!(
+            field_refine(&coarse, &mut fine, 2),
+            Err(ResampleError::InsufficientMass { x: 1, y: 0, z: 1 })
+        );
+        assert!(fine.cells.iter().all(|&v| v == 1));
+
+        field_set(&mut fine, 3, 2, 0, u32::MAX);
+        assert_eq!(
+            field_aggregate(&fine, &mut coarse, 2),
+            Err(ResampleError::Overflow { x: 1, y: 1, z: 0 })
+        );
+        assert_eq!(field_get(&coarse, 0, 0, 0).unwrap().get(), 100);
+    }
+}
@@ -0,0 +1,89 @@
+//! Small deterministic PRNG (SplitMix64).
+//!
+//! Used wherever the library needs randomness (random stamps, region soup).
+//! Deterministic from a u64 seed, so the same seed reproduces the same world on
+//! every platform. Not cryptographic.
+
+/// SplitMix64 generator. Passes BigCrush, one u64 of state, trivially seedable.
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    /// Next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mix64(self.state)
+    }
+
+    /// Uniform value in `0..bound` (bound must be non-zero). Uses the
+    /// multiply-shift reduction, so the bias is at most `bound / 2^64`.
+    pub fn next_below(&mut self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+
+    /// True with probability `numerator / 256`.
+    pub fn chance_256(&mut self, numerator: u8) -> bool {
+        ((self.next_u64() >> 56) as u8) < numerator
+    }
+}
+
+/// SplitMix64 finalizer: a fast, well-distributed 64-bit hash.
+#[inline]
+pub fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Hash a voxel coordinate together with a seed (for position-derived seeds).
+#[inline]
+pub fn hash_coord(seed: u64, x: i16, y: i16, z: i16) -> u64 {
+    let packed = (x as u16 as u64) | ((y as u16 as u64) << 16) | ((z as u16 as u64) << 32);
+    mix64(seed ^ mix64(packed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_values() {
+        // Reference output of SplitMix64 seeded with 0
+        let mut rng = SplitMix64::new(0);
+        assert_eq!(rng.next_u64(), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(rng.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+    }
+
+    #[test]
+    fn test_next_below_in_range() {
+        let mut rng = SplitMix64::new(42);
+        let mut seen = [false; 7];
+        for _ in 0..1000 {
+            let v = rng.next_below(7);
+            assert!(v < 7);
+            seen[v as usize] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+    }
+
+    #[test]
+    fn test_chance_extremes() {
+        let mut rng = SplitMix64::new(7);
+        assert!((0..100).all(|_| !rng.chance_256(0)));
+        let hits = (0..10_000).filter(|_| rng.chance_256(128)).count();
+        assert!((4_500..5_500).contains(&hits), "hits = {}", hits);
+    }
+
+    #[test]
+    fn test_hash_coord_distinguishes_axes() {
+        assert_ne!(hash_coord(1, 1, 0, 0), hash_coord(1, 0, 1, 0));
+        assert_ne!(hash_coord(1, 0, 0, 1), hash_coord(2, 0, 0, 1));
+        assert_eq!(hash_coord(9, -3, 4, 5), hash_coord(9, -3, 4, 5));
+    }
+}
@@ -0,0 +1,212 @@
+//! Rule notation and rule-table export.
+//!
+//! Two text notations are understood, both over the 26-cell Moore neighborhood:
+//! - `B4/S4`: birth counts, then survival counts (common 3D CA notation)
+//! - `3D4/4`: survival counts, then birth counts, as used by Golly's 3D.lua
+//!   (optional trailing `M` for Moore; other neighborhoods are rejected)
+//!
+//! Count lists are comma separated and may use ranges (`5..7`). An empty list
+//! (`B4/S`) means no counts.
+
+use crate::state::Rule;
+
+/// Errors from parsing rule notation.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RuleParseError {
+    /// Not recognized as `B.../S...` or `3D.../...`.
+    BadFormat,
+    /// A neighbor count is not a number or exceeds 26.
+    BadCount,
+    /// Neighborhood suffix other than Moore (`M`).
+    UnsupportedNeighborhood(char),
+}
+
+/// Format a count mask as a comma-separated list, collapsing runs of 3+ into ranges.
+fn format_counts(mask: u32) -> String {
+    let mut parts = Vec::new();
+    let mut n = 0;
+    while n <= 26 {
+        if mask & (1 << n) == 0 {
+            n += 1;
+            continue;
+        }
+        let start = n;
+        while n < 26 && mask & (1 << (n + 1)) != 0 {
+            n += 1;
+        }
+        match n - start {
+            0 => parts.push(format!("{}", start)),
+            1 => parts.push(format!("{},{}", start, n)),
+            _ => parts.push(format!("{}..{}", start, n)),
+        }
+        n += 1;
+    }
+    parts.join(",")
+}
+
+fn parse_counts(text: &str) -> Result<u32, RuleParseError> {
+    let mut mask = 0u32;
+    for part in text.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (lo, hi) = match part.split_once("..") {
+            Some((lo, hi)) => (lo, hi),
+            None => (part, part),
+        };
+        let lo: u32 = lo.trim().parse().map_err(|_| RuleParseError::BadCount)?;
+        let hi: u32 = hi.trim().parse().map_err(|_| RuleParseError::BadCount)?;
+        if lo > hi || hi > 26 {
+            return Err(RuleParseError::BadCount);
+        }
+        for n in lo..=hi {
+            mask |= 1 << n;
+        }
+    }
+    Ok(mask)
+}
+
+/// `B.../S...` notation, e.g. `B4/S4`.
+pub fn rule_notation(rule: &Rule) -> String {
+    format!(
+        "B{}/S{}",
+        format_counts(rule.birth),
+        format_counts(rule.survival)
+    )
+}
+
+/// Golly 3D.lua notation (survival first), e.g. `3D4/4`.
+pub fn rule_golly_3d(rule: &Rule) -> String {
+    format!(
+        "3D{}/{}",
+        format_counts(rule.survival),
+        format_counts(rule.birth)
+    )
+}
+
+/// Parse either `B.../S...` or Golly `3D.../...` notation (case-insensitive).
+pub fn parse_rule(text: &str) -> Result<Rule, RuleParseError> {
+    let text = text.trim().to_ascii_uppercase();
+
+    if let Some(body) = text.strip_prefix("3D") {
+        let body = match body.chars().last() {
+            Some('M') => &body[..body.len() - 1],
+            Some(c) if c.is_ascii_alphabetic() => {
+                return Err(RuleParseError::UnsupportedNeighborhood(c))
+            }
+            _ => body,
+        };
+        let (survival, birth) = body.split_once('/').ok_or(RuleParseError::BadFormat)?;
+        return Ok(Rule {
+            birth: parse_counts(birth)?,
+            survival: parse_counts(survival)?,
+        });
+    }
+
+    let (b, s) = text.split_once('/').ok_or(RuleParseError::BadFormat)?;
+    // Accept both B.../S... and S.../B... orderings
+    let (b, s) = if b.starts_with('S') { (s, b) } else { (b, s) };
+    let birth = b.strip_prefix('B').ok_or(RuleParseError::BadFormat)?;
+    let survival = s.strip_prefix('S').ok_or(RuleParseError::BadFormat)?;
+    Ok(Rule {
+        birth: parse_counts(birth)?,
+        survival: parse_counts(survival)?,
+    })
+}
+
+/// Export the rule as a Golly-style `@RULE` / `@TABLE` text file.
+///
+/// The metadata lines carry both notations (the `golly-3d` line can be pasted
+/// straight into Golly's 3D.lua). The table lists every
+/// `current,live_neighbors,next` transition, so tools that don't speak either
+/// notation can still read the rule.
+pub fn export_rule_table(rule: &Rule) -> String {
+    let notation = rule_notation(rule);
+    let mut out = String::new();
+    out.push_str(&format!("@RULE {}\n", notation.replace('/', "")));
+    out.push_str("# Exported by voxel-automata\n");
+    out.push_str(&format!("# notation: {}\n", notation));
+    out.push_str(&format!("# golly-3d: {}\n", rule_golly_3d(rule)));
+    out.push_str("# neighborhood: Moore (26 neighbors, 3x3x3 cube excluding center)\n");
+    out.push_str("# states: 2 (0 = dead, 1 = alive)\n");
+    out.push_str(
+        "# outer-totalistic: next state depends only on current state and live neighbor count\n",
+    );
+    out.push('\n');
+    out.push_str("@TABLE\n");
+    out.push_str("n_states:2\n");
+    out.push_str("neighborhood:Moore3D\n");
+    out.push_str("# current,live_neighbors,next\n");
+    for (current, mask) in [(0, rule.birth), (1, rule.survival)] {
+        for n in 0..=26 {
+            out.push_str(&format!("{},{},{}\n", current, n, (mask >> n) & 1));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_notations() {
+        let rule = Rule::default();
+        assert_eq!(rule_notation(&rule), "B4/S4");
+        assert_eq!(rule_golly_3d(&rule), "3D4/4");
+    }
+
+    #[test]
+    fn test_ranges_collapse() {
+        let rule = Rule {
+            birth: (1 << 5) | (1 << 6) | (1 << 7) | (1 << 13),
+            survival: (1 << 1) | (1 << 2),
+        };
+        assert_eq!(rule_notation(&rule), "B5..7,13/S1,2");
+        assert_eq!(rule_golly_3d(&rule), "3D1,2/5..7,13");
+        assert_eq!(
+            rule_notation(&Rule {
+                birth: 0,
+                survival: 1 << 26
+            }),
+            "B/S26"
+        );
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let rule = Rule {
+            birth: (1 << 5) | (1 << 6) | (1 << 7) | (1 << 26),
+            survival: 1 | (1 << 4),
+        };
+        assert_eq!(parse_rule(&rule_notation(&rule)), Ok(rule));
+        assert_eq!(parse_rule(&rule_golly_3d(&rule)), Ok(rule));
+        assert_eq!(parse_rule("3d4/4m"), Ok(Rule::default()));
+        assert_eq!(parse_rule("S4/B4"), Ok(Rule::default()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_rule("Life"), Err(RuleParseError::BadFormat));
+        assert_eq!(parse_rule("B27/S4"), Err(RuleParseError::BadCount));
+        assert_eq!(parse_rule("B7..5/S4"), Err(RuleParseError::BadCount));
+        assert_eq!(parse_rule("B4/Sx"), Err(RuleParseError::BadCount));
+        assert_eq!(
+            parse_rule("3D4/4F"),
+            Err(RuleParseError::UnsupportedNeighborhood('F'))
+        );
+    }
+
+    #[test]
+    fn test_export_rule_table() {
+        let table = export_rule_table(&Rule::default());
+        assert!(table.starts_with("@RULE B4S4\n"));
+        assert!(table.contains("# golly-3d: 3D4/4\n"));
+        assert!(table.contains("\n0,4,1\n"));
+        assert!(table.contains("\n1,4,1\n"));
+        assert!(table.contains("\n0,5,0\n"));
+        // 2 states x 27 neighbor counts
+        let rows = table
+            .lines()
+            .filter(|l| l.split(',').count() == 3 && !l.starts_with('#'))
+            .count();
+        assert_eq!(rows, 54);
+    }
+}
@@ -0,0 +1,204 @@
+//! Searching the grid for occurrences of a small pattern (achievement
+//! triggers such as "the player's automaton produced a glider").
+//!
+//! A pattern is a box of cells in z,y,x order, like an extracted region:
+//! `PATTERN_DEAD` cells must be dead, `PATTERN_ALIVE` cells alive, and any
+//! other value matches either (for shapes that are not boxes). To tell a
+//! glider from a glider touching something else, include a dead margin.
+//!
+//! Optionally each of the 24 cube rotations (see `stamp::rotate`) is tried
+//! too; rotations under which the pattern is symmetric are reported once,
+//! under the lowest rotation id.
+
+use super::grid::{in_bounds, index_of};
+use super::stamp::{rotate, ROTATION_COUNT};
+use crate::state::State;
+
+/// Pattern cell that must be dead.
+pub const PATTERN_DEAD: u8 = 0;
+/// Pattern cell that must be alive.
+pub const PATTERN_ALIVE: u8 = 1;
+
+/// An occurrence of a pattern.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PatternMatch {
+    /// Minimum corner of the (rotated) pattern's box in the grid.
+    pub position: [i16; 3],
+    /// Rotation (0..24) applied to the pattern, 0 for the pattern as given.
+    pub rotation: u8,
+}
+
+/// A pattern under one rotation, as the offsets it constrains.
+struct Oriented {
+    rotation: u8,
+    dims: [i16; 3],
+    /// Offsets from the box's minimum corner and the state required there,
+    /// live cells first: they are rare in a grid, so mismatches fail fast.
+    checks: Vec<([i16; 3], bool)>,
+}
+
+impl Oriented {
+    fn new(pattern: &[u8], dims: [i16; 3], rotation: u8) -> Self {
+        let mut cells = Vec::with_capacity(pattern.len());
+        for z in 0..dims[2] {
+            for y in 0..dims[1] {
+                for x in 0..dims[0] {
+                    cells.push(rotate([x, y, z], rotation));
+                }
+            }
+        }
+        let min = [0, 1, 2].map(|axis| cells.iter().map(|c| c[axis]).min().unwrap_or(0));
+        let max = [0, 1, 2].map(|axis| cells.iter().map(|c| c[axis]).max().unwrap_or(0));
+        let mut checks: Vec<_> = cells
+            .iter()
+            .zip(pattern)
+            .filter(|&(_, &value)| value == PATTERN_DEAD || value == PATTERN_ALIVE)
+            .map(|(cell, &value)| {
+                let offset = [0, 1, 2].map(|axis| cell[axis] - min[axis]);
+                (offset, value == PATTERN_ALIVE)
+            })
+            .collect();
+        checks.sort_by_key(|&(offset, alive)| (!alive, offset));
+        Oriented {
+            rotation,
+            dims: [0, 1, 2].map(|axis| max[axis] - min[axis] + 1),
+            checks,
+        }
+    }
+
+    fn matches_at(&self, state: &State, corner: [i16; 3]) -> bool {
+        self.checks.iter().all(|&([dx, dy, dz], alive)| {
+            let (x, y, z) = (corner[0] + dx, corner[1] + dy, corner[2] + dz);
+            (state.cells[index_of(state, x, y, z)] != 0) == alive
+        })
+    }
+}
+
+/// Every occurrence of the `dims` box `pattern` in the grid, in z,y,x order of
+/// position, all rotations of one position together. With `rotations` the 24
+/// cube rotations are tried, otherwise only the pattern as given. None if a
+/// dimension is not positive or `pattern` does not hold exactly the box.
+pub fn find_pattern(
+    state: &State,
+    pattern: &[u8],
+    dims: [i16; 3],
+    rotations: bool,
+) -> Option<Vec<PatternMatch>> {
+    if dims.iter().any(|&e| e <= 0)
+        || pattern.len() != dims.iter().map(|&e| e as usize).product::<usize>()
+    {
+        return None;
+    }
+    let count = if rotations { ROTATION_COUNT } else { 1 };
+    let mut orientations: Vec<Oriented> = Vec::new();
+    for rotation in 0..count {
+        let oriented = Oriented::new(pattern, dims, rotation);
+        // A symmetric pattern looks the same under several rotations
+        if !orientations
+            .iter()
+            .any(|seen| seen.dims == oriented.dims && seen.checks == oriented.checks)
+        {
+            orientations.push(oriented);
+        }
+    }
+
+    let mut found = Vec::new();
+    for z in 0..state.depth {
+        for y in 0..state.height {
+            for x in 0..state.width {
+                for oriented in &orientations {
+                    let [w, h, d] = oriented.dims;
+                    // The box's far corner must lie in the grid too
+                    let fits = in_bounds(state, x, y, z)
+                        && (x as i32 + w as i32) <= state.width as i32
+                        && (y as i32 + h as i32) <= state.height as i32
+                        && (z as i32 + d as i32) <= state.depth as i32;
+                    if fits && oriented.matches_at(state, [x, y, z]) {
+                        found.push(PatternMatch {
+                            position: [x, y, z],
+                            rotation: oriented.rotation,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    Some(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::grid::create_grid;
+    use crate::automaton::stamp::{stamp_pattern, STAMP_GLIDER};
+
+    #[test]
+    fn test_finds_exact_occurrences() {
+        let mut state = State::default();
+        create_grid(&mut state, 8, 8, 8);
+        // An L tromino in the z = 0 plane, matched without a dead margin
+        for (x, y) in [(2, 2), (3, 2), (2, 3)] {
+            let idx = index_of(&state, x, y, 0);
+            state.cells[idx] = 1;
+        }
+        let l = [1, 1, 1, 0];
+        let found = find_pattern(&state, &l, [2, 2, 1], false).unwrap();
+        assert_eq!(
+            found,
+            vec![PatternMatch {
+                position: [2, 2, 0],
+                rotation: 0
+            }]
+        );
+        // A don't-care cell also matches the live corner
+        let found = find_pattern(&state, &[1, 1, 1, 9], [2, 2, 1], false).unwrap();
+        assert_eq!(found.len(), 1);
+        // Rotated copies of the L are found under rotations instead
+        let flipped = [0, 1, 1, 1];
+        assert!(find_pattern(&state, &flipped, [2, 2, 1], false)
+            .unwrap()
+            .is_empty());
+        assert!(!find_pattern(&state, &flipped, [2, 2, 1], true)
+            .unwrap()
+            .is_empty());
+        assert_eq!(find_pattern(&state, &l, [2, 2, 2], false), None);
+        assert_eq!(find_pattern(&state, &l, [4, 0, 1], false), None);
+    }
+
+    #[test]
+    fn test_finds_rotated_glider_once_per_symmetry() {
+        let mut state = State::default();
+        create_grid(&mut state, 12, 12, 12);
+        stamp_pattern(&mut state, STAMP_GLIDER, 6, 6, 6, 17).unwrap();
+        // The unrotated glider with a dead margin, as the pattern
+        let mut pattern = State::default();
+        create_grid(&mut pattern, 5, 5, 5);
+        stamp_pattern(&mut pattern, STAMP_GLIDER, 1, 1, 1, 0).unwrap();
+
+        let found = find_pattern(&state, &pattern.cells, [5, 5, 5], true).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].rotation, 17);
+        assert!(find_pattern(&state, &pattern.cells, [5, 5, 5], false)
+            .unwrap()
+            .is_empty());
+
+        // A cube is the same under every rotation: one match, rotation 0
+        let cube = [1; 8];
+        let mut state = State::default();
+        create_grid(&mut state, 4, 4, 4);
+        for (x, y, z) in [(0, 0, 0), (1, 0, 0), (0, 1, 0), (1, 1, 0)] {
+            for dz in 0..2 {
+                let idx = index_of(&state, x, y, z + dz);
+                state.cells[idx] = 1;
+            }
+        }
+        let found = find_pattern(&state, &cube, [2, 2, 2], true).unwrap();
+        assert_eq!(
+            found,
+            vec![PatternMatch {
+                position: [0, 0, 0],
+                rotation: 0
+            }]
+        );
+    }
+}
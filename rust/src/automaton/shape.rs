@@ -0,0 +1,266 @@
+//! Analytic initial conditions for fields: boxes, spheres, shells, gradients.
+//!
+//! Setting up a hot sphere or a temperature ramp cell by cell from Lua costs
+//! one FFI call per cell. `field_paint` writes a whole shape in one call.
+//! Shapes are clipped to the field, but always evaluated in their own
+//! unclipped geometry: a gradient half outside the field still ramps from
+//! its true start. Distances use exact integer arithmetic, so a shape paints
+//! the same cells on every platform. Painted values of 0 are stored as 1 (the
+//! minimum quantum, see `create_field`).
+
+use super::field::Field;
+use super::region::clamp_box;
+
+/// A shape and the values it paints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    /// Every cell of the half-open box `[min, max)`.
+    Box {
+        min: [i16; 3],
+        max: [i16; 3],
+        value: u32,
+    },
+    /// Cells whose distance from `center` is at most `radius`.
+    Sphere {
+        center: [i16; 3],
+        radius: u16,
+        value: u32,
+    },
+    /// The outer `thickness` cells of a sphere: distance in
+    /// `(radius - thickness, radius]`. A thickness above the radius fills it.
+    Shell {
+        center: [i16; 3],
+        radius: u16,
+        thickness: u16,
+        value: u32,
+    },
+    /// The box `[min, max)`, ramping along `axis` (0 = x, 1 = y, 2 = z) from
+    /// `from` in the first layer to `to` in the last.
+    LinearGradient {
+        min: [i16; 3],
+        max: [i16; 3],
+        axis: u8,
+        from: u32,
+        to: u32,
+    },
+    /// A sphere ramping from `inner` at the center to `outer` at `radius`.
+    RadialGradient {
+        center: [i16; 3],
+        radius: u16,
+        inner: u32,
+        outer: u32,
+    },
+}
+
+/// `from + (to - from) * num / den`, rounded half up (den > 0).
+fn lerp(from: u32, to: u32, num: i64, den: i64) -> u32 {
+    let delta = (to as i64 - from as i64) * num;
+    (from as i64 + (2 * delta + den).div_euclid(2 * den)) as u32
+}
+
+/// Squared distance of `(x, y, z)` from `center`.
+fn distance_sq(center: [i16; 3], x: i16, y: i16, z: i16) -> u64 {
+    [x, y, z]
+        .iter()
+        .zip(center)
+        .map(|(&p, c)| (p as i64 - c as i64).pow(2) as u64)
+        .sum()
+}
+
+impl Shape {
+    /// Unclipped bounding box `[min, max)`, or None if the shape is invalid.
+    fn bounds(&self) -> Option<([i16; 3], [i16; 3])> {
+        let around = |center: [i16; 3], radius: u16| {
+            let r = radius as i32;
+            let clip = |v: i32| v.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+            (
+                center.map(|c| clip(c as i32 - r)),
+                center.map(|c| clip(c as i32 + r + 1)),
+            )
+        };
+        match *self {
+            Shape::Box { min, max, .. } => Some((min, max)),
+            Shape::LinearGradient { axis, .. } if axis > 2 => None,
+            Shape::LinearGradient { min, max, .. } => Some((min, max)),
+            Shape::Sphere { center, radius, .. }
+            | Shape::Shell { center, radius, .. }
+            | Shape::RadialGradient { center, radius, .. } => Some(around(center, radius)),
+        }
+    }
+
+    /// Value painted at `(x, y, z)` (inside `bounds`), or None if outside the shape.
+    fn value_at(&self, x: i16, y: i16, z: i16) -> Option<u32> {
+        match *self {
+            Shape::Box { value, .. } => Some(value),
+            Shape::Sphere {
+                center,
+                radius,
+                value,
+            } => (distance_sq(center, x, y, z) <= (radius as u64).pow(2)).then_some(value),
+            Shape::Shell {
+                center,
+                radius,
+                thickness,
+                value,
+            } => {
+                let d2 = distance_sq(center, x, y, z);
+                let hollow = radius.checked_sub(thickness).map(|r| (r as u64).pow(2));
+                let in_shell = d2 <= (radius as u64).pow(2) && hollow.is_none_or(|h| d2 > h);
+                in_shell.then_some(value)
+            }
+            Shape::LinearGradient {
+                min,
+                max,
+                axis,
+                from,
+                to,
+            } => {
+                let a = axis as usize;
+                let span = max[a] as i64 - min[a] as i64 - 1;
+                let pos = [x, y, z][a] as i64 - min[a] as i64;
+                Some(if span <= 0 {
+                    from
+                } else {
+                    lerp(from, to, pos, span)
+                })
+            }
+            Shape::RadialGradient {
+                center,
+                radius,
+                inner,
+                outer,
+            } => {
+                let d2 = distance_sq(center, x, y, z);
+                if d2 > (radius as u64).pow(2) {
+                    return None;
+                }
+                if radius == 0 {
+                    return Some(inner);
+                }
+                // Distance in 1/256 cells, exact integer square root
+                let distance = (d2 << 16).isqrt() as i64;
+                Some(lerp(inner, outer, distance, radius as i64 * 256))
+            }
+        }
+    }
+}
+
+/// Paint `shape` into `field` (see the module docs).
+///
+/// # Returns
+/// Number of cells written (0 if the shape misses the field or is invalid,
+/// e.g. a gradient axis above 2).
+pub fn field_paint(field: &mut Field, shape: &Shape) -> u64 {
+    let Some((min, max)) = shape.bounds() else {
+        return 0;
+    };
+    let dims = [field.width, field.height, field.depth];
+    let Some((lo, hi)) = clamp_box(dims, min, max) else {
+        return 0;
+    };
+
+    let (w, h) = (field.width as usize, field.height as usize);
+    let mut written = 0;
+    for z in lo[2]..hi[2] {
+        for y in lo[1]..hi[1] {
+            for x in lo[0]..hi[0] {
+                if let Some(value) = shape.value_at(x, y, z) {
+                    field.cells[(z as usize * h + y as usize) * w + x as usize] = value.max(1);
+                    written += 1;
+                }
+            }
+        }
+    }
+    written
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_get};
+
+    fn get(field: &Field, x: i16, y: i16, z: i16) -> u32 {
+        field_get(field, x, y, z).unwrap().get()
+    }
+
+    #[test]
+    fn test_solid_shapes() {
+        let mut field = create_field_1(16, 16, 16, 2);
+        let sphere = Shape::Sphere {
+            center: [8, 8, 8],
+            radius: 3,
+            value: 500,
+        };
+        // 1 + 6 * 3 + 12 * 4 + 8 * 1 + 6 * 2 + 24 * 2: lattice points within radius 3
+        assert_eq!(field_paint(&mut field, &sphere), 123);
+        assert_eq!(get(&field, 11, 8, 8), 500);
+        assert_eq!(get(&field, 10, 10, 9), 500);
+        assert_eq!(get(&field, 10, 10, 10), 1);
+
+        let shell = Shape::Shell {
+            center: [8, 8, 8],
+            radius: 3,
+            thickness: 1,
+            value: 9,
+        };
+        // Radius-3 ball minus radius-2 ball (33 points)
+        assert_eq!(field_paint(&mut field, &shell), 123 - 33);
+        assert_eq!((get(&field, 8, 8, 5), get(&field, 8, 8, 6)), (9, 500));
+
+        // Clipped at the field corner; value 0 is stored as 1
+        let corner = Shape::Box {
+            min: [-4, -4, -4],
+            max: [2, 2, 3],
+            value: 0,
+        };
+        assert_eq!(field_paint(&mut field, &corner), 12);
+        assert_eq!(get(&field, 1, 1, 2), 1);
+        let outside = Shape::Sphere {
+            center: [-10, 0, 0],
+            radius: 5,
+            value: 7,
+        };
+        assert_eq!(field_paint(&mut field, &outside), 0);
+    }
+
+    #[test]
+    fn test_gradients() {
+        let mut field = create_field_1(16, 4, 4, 2);
+        let ramp = Shape::LinearGradient {
+            min: [-5, 0, 0],
+            max: [16, 4, 4],
+            axis: 0,
+            from: 1000,
+            to: 0,
+        };
+        assert_eq!(field_paint(&mut field, &ramp), 16 * 16);
+        // The ramp starts at x = -5, outside the field: x = 0 is already 1/4 down
+        assert_eq!(get(&field, 0, 2, 3), 750);
+        assert_eq!(get(&field, 15, 0, 0), 1);
+        assert!((1..16).all(|x| get(&field, x, 0, 0) < get(&field, x - 1, 0, 0)));
+        let bad_axis = Shape::LinearGradient {
+            min: [0; 3],
+            max: [16, 4, 4],
+            axis: 3,
+            from: 1,
+            to: 2,
+        };
+        assert_eq!(field_paint(&mut field, &bad_axis), 0);
+
+        let mut field = create_field_1(21, 21, 21, 2);
+        let glow = Shape::RadialGradient {
+            center: [10, 10, 10],
+            radius: 8,
+            inner: 8000,
+            outer: 0,
+        };
+        field_paint(&mut field, &glow);
+        assert_eq!(get(&field, 10, 10, 10), 8000);
+        assert_eq!(get(&field, 14, 10, 10), 4000);
+        assert_eq!(get(&field, 10, 13, 14), 3000);
+        assert_eq!(get(&field, 10, 10, 18), 1);
+        assert_eq!(get(&field, 10, 10, 19), 1);
+        // Symmetric under axis swaps and reflections
+        assert_eq!(get(&field, 7, 12, 5), get(&field, 15, 13, 8));
+    }
+}
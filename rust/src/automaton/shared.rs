@@ -0,0 +1,120 @@
+//! One field shared by a single writer and any number of readers.
+//!
+//! A visualization layer and a statistics recorder both want to watch the
+//! simulation's field, but copying it for each of them doubles the memory and
+//! the copies go stale. A `FieldWriter` owns the field behind a reference
+//! count and hands out `FieldReader`s that observe the same cells.
+//!
+//! The split is enforced by the types: the writer is the only handle with
+//! mutable access and cannot be cloned, readers can be cloned freely but only
+//! read. Readers see every write as soon as it is made. When the writer is
+//! dropped, its readers keep the field as it was last written; the field is
+//! freed with the last handle.
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use super::field::Field;
+
+/// The single handle allowed to modify a shared field.
+pub struct FieldWriter {
+    field: Arc<RwLock<Field>>,
+}
+
+/// A read-only handle on a shared field.
+#[derive(Clone)]
+pub struct FieldReader {
+    field: Arc<RwLock<Field>>,
+}
+
+impl FieldWriter {
+    /// Share `field`, with no readers yet.
+    pub fn new(field: Field) -> Self {
+        FieldWriter {
+            field: Arc::new(RwLock::new(field)),
+        }
+    }
+
+    /// A new reader of the field.
+    pub fn reader(&self) -> FieldReader {
+        FieldReader {
+            field: Arc::clone(&self.field),
+        }
+    }
+
+    /// Number of live readers.
+    pub fn readers(&self) -> usize {
+        Arc::strong_count(&self.field) - 1
+    }
+
+    /// Run `f` with read access to the field.
+    pub fn read<R>(&self, f: impl FnOnce(&Field) -> R) -> R {
+        f(&self.field.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Run `f` with write access to the field.
+    pub fn write<R>(&mut self, f: impl FnOnce(&mut Field) -> R) -> R {
+        f(&mut self.field.write().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Take the field back, which requires every reader to be gone. Returns
+    /// the writer unchanged otherwise.
+    pub fn into_field(self) -> Result<Field, Self> {
+        Arc::try_unwrap(self.field)
+            .map(|lock| lock.into_inner().unwrap_or_else(PoisonError::into_inner))
+            .map_err(|field| FieldWriter { field })
+    }
+}
+
+impl FieldReader {
+    /// Run `f` with read access to the field.
+    pub fn read<R>(&self, f: impl FnOnce(&Field) -> R) -> R {
+        f(&self.field.read().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_get, field_set, field_step};
+
+    #[test]
+    fn test_readers_observe_writes() {
+        let mut writer = FieldWriter::new(create_field_1(4, 4, 4, 1));
+        let visual = writer.reader();
+        let stats = visual.clone();
+        assert_eq!(writer.readers(), 2);
+
+        writer.write(|field| {
+            field_set(field, 1, 1, 1, 500);
+            field_step(field);
+        });
+        assert_eq!(visual.read(|field| field.generation), 1);
+        assert_eq!(
+            stats.read(|field| field_get(field, 1, 1, 1)),
+            writer.read(|field| field_get(field, 1, 1, 1))
+        );
+
+        // Readers keep the field alive, frozen, once the writer is gone
+        let Err(writer) = writer.into_field() else {
+            panic!("readers remain");
+        };
+        drop(stats);
+        drop(writer);
+        assert_eq!(visual.read(|field| field.generation), 1);
+    }
+
+    #[test]
+    fn test_into_field_once_readers_are_gone() {
+        let writer = FieldWriter::new(create_field_1(2, 2, 2, 1));
+        let reader = writer.reader();
+        let Err(writer) = writer.into_field() else {
+            panic!("readers remain");
+        };
+        drop(reader);
+        assert_eq!(writer.readers(), 0);
+        let Ok(field) = writer.into_field() else {
+            panic!("no readers remain");
+        };
+        assert_eq!(field.cells.len(), 8);
+    }
+}
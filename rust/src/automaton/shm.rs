@@ -0,0 +1,220 @@
+//! Field export to named shared memory.
+//!
+//! A standalone visualizer or statistics daemon cannot call into the game
+//! server's copy of the library. With an export, the field's cells are
+//! mirrored into a memory-mapped file (under `/dev/shm` on Linux, so it never
+//! touches disk; the temp directory elsewhere) that any process can map read
+//! only. `publish` copies the current cells in; the mod calls it after each
+//! step it wants observers to see.
+//!
+//! The file starts with a fixed 64-byte header describing the layout (all
+//! integers in native byte order, as both sides run on the same machine):
+//!
+//! | Offset | Type    | Content                                          |
+//! |--------|---------|--------------------------------------------------|
+//! | 0      | [u8; 4] | magic `VAFS`                                     |
+//! | 4      | u32     | layout version (`SHM_VERSION`)                   |
+//! | 8      | u32     | byte offset of the cells (`SHM_HEADER_LEN`)      |
+//! | 12     | u32     | bytes per cell (4)                               |
+//! | 16     | u32 x 3 | width, height, depth                             |
+//! | 28     | u32     | reserved (0)                                     |
+//! | 32     | u64     | sequence number, odd while a publish is underway |
+//! | 40     | u64     | generation of the published cells                |
+//! | 48     | u64 x 2 | reserved (0)                                     |
+//! | 64     | u32 x n | cells in z,y,x order (x fastest)                 |
+//!
+//! Readers use the sequence number as a seqlock: read it, skip if odd, copy
+//! what they need, and retry if it changed meanwhile. Dropping the export
+//! removes the name; processes that mapped it keep their view of the last
+//! publish.
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+
+use memmap2::MmapMut;
+
+use super::field::Field;
+
+pub const SHM_MAGIC: [u8; 4] = *b"VAFS";
+pub const SHM_VERSION: u32 = 1;
+/// Header bytes before the cells.
+pub const SHM_HEADER_LEN: usize = 64;
+/// Longest export name.
+pub const MAX_SHM_NAME: usize = 64;
+
+const SEQUENCE_OFFSET: usize = 32;
+const GENERATION_OFFSET: usize = 40;
+
+/// A field's cells mirrored into a named shared-memory file.
+pub struct ShmExport {
+    map: MmapMut,
+    path: PathBuf,
+    dims: [i16; 3],
+    /// Keeps the file open for as long as it is mapped.
+    _file: File,
+}
+
+/// Directory holding the exports.
+fn shm_dir() -> PathBuf {
+    let dev_shm = Path::new("/dev/shm");
+    if cfg!(target_os = "linux") && dev_shm.is_dir() {
+        dev_shm.to_path_buf()
+    } else {
+        std::env::temp_dir()
+    }
+}
+
+/// A name is 1 to `MAX_SHM_NAME` ASCII letters, digits, `_`, `-` or `.`, not
+/// starting with `.`, so it cannot leave the export directory.
+pub fn valid_shm_name(name: &str) -> bool {
+    (1..=MAX_SHM_NAME).contains(&name.len())
+        && !name.starts_with('.')
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.'))
+}
+
+impl ShmExport {
+    /// Export `field` under `name`, replacing any earlier export of that
+    /// name, and publish its current cells.
+    pub fn create(name: &str, field: &Field) -> io::Result<Self> {
+        if !valid_shm_name(name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid export name",
+            ));
+        }
+        let path = shm_dir().join(name);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        let len = SHM_HEADER_LEN + field.cells.len() * size_of::<u32>();
+        file.set_len(len as u64)?;
+        // SAFETY: the file was just created at this length and is only
+        // written through this mapping; other processes map it read-only
+        let map = unsafe { MmapMut::map_mut(&file)? };
+        let mut export = ShmExport {
+            map,
+            path,
+            dims: [field.width, field.height, field.depth],
+            _file: file,
+        };
+
+        let header = &mut export.map[..SHM_HEADER_LEN];
+        header[0..4].copy_from_slice(&SHM_MAGIC);
+        header[4..8].copy_from_slice(&SHM_VERSION.to_ne_bytes());
+        header[8..12].copy_from_slice(&(SHM_HEADER_LEN as u32).to_ne_bytes());
+        header[12..16].copy_from_slice(&(size_of::<u32>() as u32).to_ne_bytes());
+        for (axis, &len) in export.dims.iter().enumerate() {
+            let at = 16 + 4 * axis;
+            header[at..at + 4].copy_from_slice(&(len as u32).to_ne_bytes());
+        }
+        export.publish(field);
+        Ok(export)
+    }
+
+    /// Path of the shared-memory file, for the observing process.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Copy the field's cells and generation in. Returns false (nothing
+    /// written) if the field's dimensions differ from the exported ones.
+    pub fn publish(&mut self, field: &Field) -> bool {
+        if [field.width, field.height, field.depth] != self.dims {
+            return false;
+        }
+        let sequence = self.sequence();
+        let start = sequence.load(Ordering::Relaxed);
+        sequence.store(start | 1, Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        let generation = field.generation.to_ne_bytes();
+        self.map[GENERATION_OFFSET..GENERATION_OFFSET + 8].copy_from_slice(&generation);
+        let cells = self.map[SHM_HEADER_LEN..].chunks_exact_mut(size_of::<u32>());
+        for (slot, value) in cells.zip(&field.cells) {
+            slot.copy_from_slice(&value.to_ne_bytes());
+        }
+
+        self.sequence()
+            .store((start | 1).wrapping_add(1), Ordering::Release);
+        true
+    }
+
+    fn sequence(&self) -> &AtomicU64 {
+        let ptr = self.map[SEQUENCE_OFFSET..].as_ptr() as *mut u64;
+        // SAFETY: the mapping is page-aligned, so the offset is 8-aligned, and
+        // the word is only accessed atomically for as long as `self` lives
+        unsafe { AtomicU64::from_ptr(ptr) }
+    }
+}
+
+impl Drop for ShmExport {
+    fn drop(&mut self) {
+        // Observers keep their mapping; only the name goes away
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_set, field_step};
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_ne_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    fn u64_at(bytes: &[u8], at: usize) -> u64 {
+        u64::from_ne_bytes(bytes[at..at + 8].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_export_publishes_layout_and_cells() {
+        let mut field = create_field_1(4, 3, 2, 1);
+        field_set(&mut field, 1, 2, 1, 5000);
+        let name = format!("va-test-export-{}", std::process::id());
+        let mut export = ShmExport::create(&name, &field).unwrap();
+        let path = export.path().to_path_buf();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(bytes.len(), SHM_HEADER_LEN + 24 * 4);
+        assert_eq!(bytes[0..4], SHM_MAGIC);
+        assert_eq!(u32_at(&bytes, 4), SHM_VERSION);
+        assert_eq!(u32_at(&bytes, 8), SHM_HEADER_LEN as u32);
+        assert_eq!(u32_at(&bytes, 12), 4);
+        assert_eq!([16, 20, 24].map(|at| u32_at(&bytes, at)), [4, 3, 2]);
+        assert_eq!(u64_at(&bytes, 32), 2);
+        let idx = 1 + 2 * 4 + 12;
+        assert_eq!(u32_at(&bytes, SHM_HEADER_LEN + 4 * idx), 5000);
+
+        field_step(&mut field);
+        assert!(export.publish(&field));
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(u64_at(&bytes, 32), 4);
+        assert_eq!(u64_at(&bytes, 40), 1);
+        let cells: Vec<u32> = (0..24)
+            .map(|i| u32_at(&bytes, SHM_HEADER_LEN + 4 * i))
+            .collect();
+        assert_eq!(cells, field.cells);
+
+        assert!(!export.publish(&create_field_1(4, 3, 3, 1)));
+        drop(export);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_names_stay_in_the_export_directory() {
+        assert!(valid_shm_name("voxel_automata.field-1"));
+        for name in ["", "../etc", "a/b", ".hidden", &"x".repeat(65)] {
+            assert!(!valid_shm_name(name), "{name}");
+        }
+        let field = create_field_1(2, 2, 2, 1);
+        assert!(ShmExport::create("a/b", &field).is_err());
+    }
+}
@@ -0,0 +1,775 @@
+//! Versioned binary snapshots of automaton state (for save files / mod storage).
+//!
+//! # Format (little-endian)
+//! ```text
+//! offset  size  field
+//!      0     4  magic "VAST" (raw) or "VASR" (run-length encoded)
+//!      4     2  version (u16)
+//!      6     6  width, height, depth (i16 each)
+//!     12     4  birth mask (u32)
+//!     16     4  survival mask (u32)
+//!     20     8  generation (u64)
+//!     28     n  cell payload
+//! ```
+//!
+//! Raw payload: one byte per cell in z,y,x order (n = width * height * depth).
+//!
+//! RLE payload: a sequence of runs, each `value: u8` followed by the run length
+//! as an unsigned LEB128 varint. Runs cover the cells in z,y,x order. Mostly-dead
+//! grids shrink to a handful of bytes.
+//!
+//! # Field format (little-endian)
+//! ```text
+//! offset  size  field
+//!      0     4  magic "VAFD"
+//!      4     2  version (u16)
+//!      6     6  width, height, depth (i16 each)
+//!     12     1  diffusion_rate (u8)
+//!     13     1  flags (bit 0: delta encoded, bits 1-2: rounding mode)
+//!     14     2  conductivity (u16)
+//!     16     8  generation (u64)
+//!     24     8  baseline checksum (u64, 0 unless delta encoded)
+//!     32    12  advection bias x, y, z (i32 each; version 2+)
+//!     44     n  cell payload (offset 32 in version 1)
+//! ```
+//!
+//! Plain payload: one u32 per cell in z,y,x order (n = 4 * cell count).
+//!
+//! Delta payload: per cell, `cell - baseline_cell` (wrapping) as a zigzag
+//! LEB128 varint. The baseline is a Field of the same dimensions the caller
+//! keeps around (e.g. the last full save); its checksum is stored so decoding
+//! against the wrong baseline fails instead of producing garbage. Smooth or
+//! slowly changing fields shrink to one or two bytes per cell.
+
+use super::field::{Field, RoundingMode, MAX_ADVECTION};
+use super::grid::MAX_GRID_CELLS;
+use super::rng::mix64;
+use crate::state::{HandleKind, HandleTag, Rule, State, StepMode};
+
+/// Magic bytes at the start of every raw state snapshot.
+pub const SNAPSHOT_MAGIC: [u8; 4] = *b"VAST";
+
+/// Magic bytes at the start of every run-length encoded state snapshot.
+pub const SNAPSHOT_MAGIC_RLE: [u8; 4] = *b"VASR";
+
+/// Current snapshot format version.
+pub const SNAPSHOT_VERSION: u16 = 1;
+
+/// Size of the fixed header preceding the cell data.
+pub const SNAPSHOT_HEADER_LEN: usize = 28;
+
+/// Magic bytes at the start of every field snapshot.
+pub const FIELD_SNAPSHOT_MAGIC: [u8; 4] = *b"VAFD";
+
+/// Current field snapshot format version.
+pub const FIELD_SNAPSHOT_VERSION: u16 = 2;
+
+/// Size of the fixed field header preceding the cell data.
+pub const FIELD_SNAPSHOT_HEADER_LEN: usize = 44;
+
+/// Header size of version 1 field snapshots (no advection).
+const FIELD_SNAPSHOT_HEADER_LEN_V1: usize = 32;
+
+const FIELD_FLAG_DELTA: u8 = 1;
+const FIELD_ROUNDING_SHIFT: u8 = 1;
+const FIELD_ROUNDING_MASK: u8 = 0b11;
+
+/// Errors from decoding a snapshot.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// Buffer is shorter than the header, or the cell payload is truncated.
+    Truncated,
+    /// Magic bytes do not match.
+    BadMagic,
+    /// Version is newer than this build understands.
+    UnsupportedVersion(u16),
+    /// Negative dimensions, some but not all dimensions zero, more than
+    /// `grid::MAX_GRID_CELLS` cells, or too many cells to allocate.
+    BadDimensions,
+    /// Output buffer too small for the encoded snapshot.
+    BufferTooSmall,
+    /// RLE runs are malformed (zero-length run, overlong varint, or too many cells).
+    Corrupt,
+    /// Delta-encoded field: baseline missing, or not the one it was encoded against.
+    BaselineMismatch,
+}
+
+/// Number of bytes `serialize_state` will write for this state.
+pub fn serialized_size(state: &State) -> usize {
+    SNAPSHOT_HEADER_LEN + state.cells.len()
+}
+
+/// Number of bytes `serialize_state_compressed` will write for this state.
+///
+/// Exact, not an estimate: computed by scanning the runs without encoding them.
+pub fn compressed_size(state: &State) -> usize {
+    SNAPSHOT_HEADER_LEN
+        + runs(&state.cells)
+            .map(|(_, len)| 1 + varint_len(len as u64))
+            .sum::<usize>()
+}
+
+/// Encode `state` into `out`. Returns the number of bytes written.
+pub fn serialize_state(state: &State, out: &mut [u8]) -> Result<usize, SnapshotError> {
+    let total = serialized_size(state);
+    if out.len() < total {
+        return Err(SnapshotError::BufferTooSmall);
+    }
+
+    write_header(state, SNAPSHOT_MAGIC, out);
+    out[SNAPSHOT_HEADER_LEN..total].copy_from_slice(&state.cells);
+
+    Ok(total)
+}
+
+/// Encode `state` into `out` with a run-length encoded payload.
+/// Returns the number of bytes written.
+pub fn serialize_state_compressed(state: &State, out: &mut [u8]) -> Result<usize, SnapshotError> {
+    let total = compressed_size(state);
+    if out.len() < total {
+        return Err(SnapshotError::BufferTooSmall);
+    }
+
+    write_header(state, SNAPSHOT_MAGIC_RLE, out);
+    let mut offset = SNAPSHOT_HEADER_LEN;
+    for (value, len) in runs(&state.cells) {
+        out[offset] = value;
+        offset += 1;
+        offset += write_varint(len as u64, &mut out[offset..]);
+    }
+
+    Ok(total)
+}
+
+/// Decode a snapshot into a new State.
+///
+/// Cell bytes are normalized (non-zero = alive) and rule masks are trimmed to
+/// bits 0..=26, so a corrupted blob can never produce out-of-range values.
+pub fn deserialize_state(data: &[u8]) -> Result<State, SnapshotError> {
+    let (mut state, len) = read_header(data, SNAPSHOT_MAGIC)?;
+
+    let payload = &data[SNAPSHOT_HEADER_LEN..];
+    if payload.len() < len {
+        return Err(SnapshotError::Truncated);
+    }
+
+    state.cells = payload[..len].iter().map(|&c| (c != 0) as u8).collect();
+    Ok(state)
+}
+
+/// Decode a run-length encoded snapshot into a new State.
+///
+/// Same normalization as `deserialize_state`. Runs must cover exactly
+/// `width * height * depth` cells.
+pub fn deserialize_state_compressed(data: &[u8]) -> Result<State, SnapshotError> {
+    let (mut state, len) = read_header(data, SNAPSHOT_MAGIC_RLE)?;
+
+    // Grown run by run rather than reserved up front, so a truncated blob
+    // claiming a huge grid fails before allocating it
+    let mut cells = Vec::new();
+    let mut payload = &data[SNAPSHOT_HEADER_LEN..];
+    while cells.len() < len {
+        let (&value, rest) = payload.split_first().ok_or(SnapshotError::Truncated)?;
+        let (run, used) = read_varint(rest)?;
+        if run == 0 || run > (len - cells.len()) as u64 {
+            return Err(SnapshotError::Corrupt);
+        }
+        cells
+            .try_reserve(run as usize)
+            .map_err(|_| SnapshotError::BadDimensions)?;
+        cells.resize(cells.len() + run as usize, (value != 0) as u8);
+        payload = &rest[used..];
+    }
+
+    state.cells = cells;
+    Ok(state)
+}
+
+/// Checksum identifying a delta baseline (dimensions and cell values).
+pub fn field_checksum(field: &Field) -> u64 {
+    let dims = (field.width as u16 as u64)
+        | ((field.height as u16 as u64) << 16)
+        | ((field.depth as u16 as u64) << 32);
+    let hash = field
+        .cells
+        .iter()
+        .fold(mix64(dims), |h, &c| mix64(h ^ c as u64));
+    // 0 is reserved for "no baseline"
+    hash.max(1)
+}
+
+/// Zigzag-encoded difference between a cell and its baseline value.
+#[inline]
+fn zigzag_delta(value: u32, base: u32) -> u64 {
+    let delta = value.wrapping_sub(base) as i32;
+    ((delta << 1) ^ (delta >> 31)) as u32 as u64
+}
+
+/// Baseline usable for `field`: same dimensions.
+fn baseline_matches(field: &Field, baseline: &Field) -> bool {
+    (field.width, field.height, field.depth) == (baseline.width, baseline.height, baseline.depth)
+}
+
+/// Number of bytes `serialize_field` will write.
+///
+/// With a baseline of the same dimensions the payload is delta encoded;
+/// otherwise (no baseline, or mismatched dimensions) it is plain.
+pub fn field_serialized_size(field: &Field, baseline: Option<&Field>) -> usize {
+    let payload = match baseline.filter(|b| baseline_matches(field, b)) {
+        Some(base) => field
+            .cells
+            .iter()
+            .zip(&base.cells)
+            .map(|(&c, &b)| varint_len(zigzag_delta(c, b)))
+            .sum(),
+        None => field.cells.len() * 4,
+    };
+    FIELD_SNAPSHOT_HEADER_LEN + payload
+}
+
+/// Encode `field` into `out`, delta encoded against `baseline` when given (see
+/// `field_serialized_size`). Returns the number of bytes written.
+pub fn serialize_field(
+    field: &Field,
+    baseline: Option<&Field>,
+    out: &mut [u8],
+) -> Result<usize, SnapshotError> {
+    let total = field_serialized_size(field, baseline);
+    if out.len() < total {
+        return Err(SnapshotError::BufferTooSmall);
+    }
+    let baseline = baseline.filter(|b| baseline_matches(field, b));
+
+    out[0..4].copy_from_slice(&FIELD_SNAPSHOT_MAGIC);
+    out[4..6].copy_from_slice(&FIELD_SNAPSHOT_VERSION.to_le_bytes());
+    out[6..8].copy_from_slice(&field.width.to_le_bytes());
+    out[8..10].copy_from_slice(&field.height.to_le_bytes());
+    out[10..12].copy_from_slice(&field.depth.to_le_bytes());
+    out[12] = field.diffusion_rate;
+    let delta = if baseline.is_some() {
+        FIELD_FLAG_DELTA
+    } else {
+        0
+    };
+    out[13] = delta | (field.rounding as u8) << FIELD_ROUNDING_SHIFT;
+    out[14..16].copy_from_slice(&field.conductivity.to_le_bytes());
+    out[16..24].copy_from_slice(&field.generation.to_le_bytes());
+    out[24..32].copy_from_slice(&baseline.map_or(0, field_checksum).to_le_bytes());
+    for (axis, bias) in field.advection.iter().enumerate() {
+        out[32 + axis * 4..36 + axis * 4].copy_from_slice(&bias.to_le_bytes());
+    }
+
+    let mut offset = FIELD_SNAPSHOT_HEADER_LEN;
+    match baseline {
+        Some(base) => {
+            for (&c, &b) in field.cells.iter().zip(&base.cells) {
+                offset += write_varint(zigzag_delta(c, b), &mut out[offset..]);
+            }
+        }
+        None => {
+            for &c in &field.cells {
+                out[offset..offset + 4].copy_from_slice(&c.to_le_bytes());
+                offset += 4;
+            }
+        }
+    }
+
+    Ok(total)
+}
+
+/// Decode a field snapshot into a new Field.
+///
+/// Delta-encoded snapshots need the same `baseline` they were encoded against
+/// (checked by checksum); plain snapshots ignore it.
+pub fn deserialize_field(data: &[u8], baseline: Option<&Field>) -> Result<Field, SnapshotError> {
+    if data.len() < FIELD_SNAPSHOT_HEADER_LEN_V1 {
+        return Err(SnapshotError::Truncated);
+    }
+    if data[0..4] != FIELD_SNAPSHOT_MAGIC {
+        return Err(SnapshotError::BadMagic);
+    }
+
+    let i16_at = |i: usize| i16::from_le_bytes([data[i], data[i + 1]]);
+    let u64_at = |i: usize| u64::from_le_bytes(data[i..i + 8].try_into().unwrap());
+
+    let version = u16::from_le_bytes([data[4], data[5]]);
+    if version == 0 || version > FIELD_SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+
+    let (width, height, depth) = (i16_at(6), i16_at(8), i16_at(10));
+    if width <= 0 || height <= 0 || depth <= 0 {
+        return Err(SnapshotError::BadDimensions);
+    }
+    let len = width as usize * height as usize * depth as usize;
+
+    let (header_len, advection) = if version >= 2 {
+        if data.len() < FIELD_SNAPSHOT_HEADER_LEN {
+            return Err(SnapshotError::Truncated);
+        }
+        let i32_at = |i: usize| i32::from_le_bytes(data[i..i + 4].try_into().unwrap());
+        let advection = [i32_at(32), i32_at(36), i32_at(40)];
+        let total: u64 = advection.iter().map(|b| b.unsigned_abs() as u64).sum();
+        if total > MAX_ADVECTION as u64 {
+            return Err(SnapshotError::Corrupt);
+        }
+        (FIELD_SNAPSHOT_HEADER_LEN, advection)
+    } else {
+        (FIELD_SNAPSHOT_HEADER_LEN_V1, [0; 3])
+    };
+
+    let payload = &data[header_len..];
+    let cells = if data[13] & FIELD_FLAG_DELTA != 0 {
+        let base = baseline
+            .filter(|b| (b.width, b.height, b.depth) == (width, height, depth))
+            .filter(|b| field_checksum(b) == u64_at(24))
+            .ok_or(SnapshotError::BaselineMismatch)?;
+
+        let mut cells = Vec::with_capacity(len);
+        let mut rest = payload;
+        for &b in &base.cells {
+            let (zigzag, used) = read_varint(rest)?;
+            if zigzag > u32::MAX as u64 {
+                return Err(SnapshotError::Corrupt);
+            }
+            let zigzag = zigzag as u32;
+            let delta = ((zigzag >> 1) as i32) ^ -((zigzag & 1) as i32);
+            cells.push(b.wrapping_add(delta as u32));
+            rest = &rest[used..];
+        }
+        cells
+    } else {
+        if payload.len() < len * 4 {
+            return Err(SnapshotError::Truncated);
+        }
+        payload[..len * 4]
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+            .collect()
+    };
+
+    Ok(Field {
+        tag: HandleTag::new(HandleKind::Field),
+        width,
+        height,
+        depth,
+        cells,
+        generation: u64_at(16),
+        diffusion_rate: data[12],
+        axis_rates: None,
+        conductivity: u16::from_le_bytes([data[14], data[15]]),
+        conductivity_curve: None,
+        flow_record: None,
+        // Older snapshots have these bits clear, which is the default mode
+        rounding: RoundingMode::from_u8(data[13] >> FIELD_ROUNDING_SHIFT & FIELD_ROUNDING_MASK)
+            .unwrap_or_default(),
+        algorithm: Default::default(),
+        advection,
+        sources: Default::default(),
+        phases: None,
+        boundaries: Default::default(),
+        periodic: [false; 3],
+        protection: None,
+        zones: None,
+        chunks: None,
+    })
+}
+
+/// Write the fixed header. `out` must hold at least `SNAPSHOT_HEADER_LEN` bytes.
+fn write_header(state: &State, magic: [u8; 4], out: &mut [u8]) {
+    out[0..4].copy_from_slice(&magic);
+    out[4..6].copy_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+    out[6..8].copy_from_slice(&state.width.to_le_bytes());
+    out[8..10].copy_from_slice(&state.height.to_le_bytes());
+    out[10..12].copy_from_slice(&state.depth.to_le_bytes());
+    out[12..16].copy_from_slice(&state.rule.birth.to_le_bytes());
+    out[16..20].copy_from_slice(&state.rule.survival.to_le_bytes());
+    out[20..28].copy_from_slice(&state.generation.to_le_bytes());
+}
+
+/// Validate the fixed header and return a State with empty cells plus the
+/// expected cell count.
+fn read_header(data: &[u8], magic: [u8; 4]) -> Result<(State, usize), SnapshotError> {
+    if data.len() < SNAPSHOT_HEADER_LEN {
+        return Err(SnapshotError::Truncated);
+    }
+    if data[0..4] != magic {
+        return Err(SnapshotError::BadMagic);
+    }
+
+    let u16_at = |i: usize| u16::from_le_bytes([data[i], data[i + 1]]);
+    let i16_at = |i: usize| i16::from_le_bytes([data[i], data[i + 1]]);
+    let u32_at = |i: usize| u32::from_le_bytes(data[i..i + 4].try_into().unwrap());
+
+    let version = u16_at(4);
+    if version == 0 || version > SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+
+    let (width, height, depth) = (i16_at(6), i16_at(8), i16_at(10));
+    let all_zero = width == 0 && height == 0 && depth == 0;
+    if !all_zero && (width <= 0 || height <= 0 || depth <= 0) {
+        return Err(SnapshotError::BadDimensions);
+    }
+    // The header is untrusted: refuse sizes no grid could have before anyone
+    // allocates for them
+    let len = width as u64 * height as u64 * depth as u64;
+    if len > MAX_GRID_CELLS {
+        return Err(SnapshotError::BadDimensions);
+    }
+
+    let state = State {
+        tag: HandleTag::new(HandleKind::State),
+        width,
+        height,
+        depth,
+        cells: Vec::new(),
+        generation: u64::from_le_bytes(data[20..28].try_into().unwrap()),
+        rule: Rule {
+            birth: u32_at(12) & Rule::MASK,
+            survival: u32_at(16) & Rule::MASK,
+        },
+        protection: None,
+        write_queue: None,
+        species: None,
+        age: None,
+        transitions: None,
+        mode: StepMode::Rule,
+        history: None,
+        cycles: None,
+    };
+    Ok((state, len as usize))
+}
+
+/// Iterate over maximal runs of equal bytes as `(value, length)`.
+fn runs(cells: &[u8]) -> impl Iterator<Item = (u8, usize)> + '_ {
+    let mut i = 0;
+    std::iter::from_fn(move || {
+        let value = *cells.get(i)?;
+        let start = i;
+        while i < cells.len() && cells[i] == value {
+            i += 1;
+        }
+        Some((value, i - start))
+    })
+}
+
+fn varint_len(mut v: u64) -> usize {
+    let mut n = 1;
+    while v >= 0x80 {
+        v >>= 7;
+        n += 1;
+    }
+    n
+}
+
+/// Write `v` as unsigned LEB128. Returns the number of bytes written.
+fn write_varint(mut v: u64, out: &mut [u8]) -> usize {
+    let mut n = 0;
+    while v >= 0x80 {
+        out[n] = (v as u8) | 0x80;
+        v >>= 7;
+        n += 1;
+    }
+    out[n] = v as u8;
+    n + 1
+}
+
+/// Read an unsigned LEB128 varint. Returns `(value, bytes consumed)`.
+fn read_varint(data: &[u8]) -> Result<(u64, usize), SnapshotError> {
+    let mut value = 0u64;
+    for (i, &byte) in data.iter().enumerate().take(10) {
+        value |= ((byte & 0x7F) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    if data.len() < 10 {
+        Err(SnapshotError::Truncated)
+    } else {
+        Err(SnapshotError::Corrupt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::grid::{create_grid, index_of};
+    use crate::automaton::stepping::step_automaton;
+
+    fn sample_state() -> State {
+        let mut state = State::default();
+        create_grid(&mut state, 6, 5, 4);
+        state.rule = Rule {
+            birth: 0b1010,
+            survival: 0b1100,
+        };
+        let idx = index_of(&state, 1, 2, 3);
+        state.cells[idx] = 1;
+        let idx = index_of(&state, 5, 4, 0);
+        state.cells[idx] = 1;
+        state.generation = 42;
+        state
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let state = sample_state();
+        let mut buf = vec![0u8; serialized_size(&state)];
+        assert_eq!(serialize_state(&state, &mut buf), Ok(28 + 120));
+
+        let restored = deserialize_state(&buf).unwrap();
+        assert_eq!((restored.width, restored.height, restored.depth), (6, 5, 4));
+        assert_eq!(restored.generation, 42);
+        assert_eq!(restored.rule, state.rule);
+        assert_eq!(restored.cells, state.cells);
+    }
+
+    #[test]
+    fn test_restored_state_steps_identically() {
+        let mut a = sample_state();
+        a.rule = Rule::default();
+        let mut buf = vec![0u8; serialized_size(&a)];
+        serialize_state(&a, &mut buf).unwrap();
+        let mut b = deserialize_state(&buf).unwrap();
+
+        step_automaton(&mut a);
+        step_automaton(&mut b);
+        assert_eq!(a.cells, b.cells);
+        assert_eq!(a.generation, b.generation);
+    }
+
+    #[test]
+    fn test_empty_state_round_trip() {
+        let state = State::default();
+        let mut buf = vec![0u8; SNAPSHOT_HEADER_LEN];
+        assert_eq!(serialize_state(&state, &mut buf), Ok(SNAPSHOT_HEADER_LEN));
+        let restored = deserialize_state(&buf).unwrap();
+        assert!(restored.cells.is_empty());
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        let state = sample_state();
+        let mut buf = vec![0u8; serialized_size(&state)];
+        serialize_state(&state, &mut buf).unwrap();
+
+        assert_eq!(
+            serialize_state(&state, &mut [0u8; 10]),
+            Err(SnapshotError::BufferTooSmall)
+        );
+        assert!(matches!(
+            deserialize_state(&buf[..20]),
+            Err(SnapshotError::Truncated)
+        ));
+        assert!(matches!(
+            deserialize_state(&buf[..buf.len() - 1]),
+            Err(SnapshotError::Truncated)
+        ));
+
+        let mut bad = buf.clone();
+        bad[0] = b'X';
+        assert!(matches!(
+            deserialize_state(&bad),
+            Err(SnapshotError::BadMagic)
+        ));
+
+        let mut bad = buf.clone();
+        bad[4..6].copy_from_slice(&99u16.to_le_bytes());
+        assert!(matches!(
+            deserialize_state(&bad),
+            Err(SnapshotError::UnsupportedVersion(99))
+        ));
+
+        let mut bad = buf.clone();
+        bad[6..8].copy_from_slice(&(-6i16).to_le_bytes());
+        assert!(matches!(
+            deserialize_state(&bad),
+            Err(SnapshotError::BadDimensions)
+        ));
+    }
+
+    #[test]
+    fn test_compressed_round_trip() {
+        let mut state = sample_state();
+        // Long run to exercise multi-byte varints
+        create_grid(&mut state, 64, 64, 64);
+        let idx = index_of(&state, 10, 20, 30);
+        state.cells[idx] = 1;
+
+        let size = compressed_size(&state);
+        let mut buf = vec![0u8; size];
+        assert_eq!(serialize_state_compressed(&state, &mut buf), Ok(size));
+        assert!(size < serialized_size(&state) / 100);
+
+        let restored = deserialize_state_compressed(&buf).unwrap();
+        assert_eq!(restored.cells, state.cells);
+        assert_eq!(restored.rule, state.rule);
+        assert_eq!(restored.generation, state.generation);
+
+        // Formats are not interchangeable
+        assert_eq!(deserialize_state(&buf).err(), Some(SnapshotError::BadMagic));
+    }
+
+    #[test]
+    fn test_compressed_rejects_corrupt_runs() {
+        let state = sample_state();
+        let mut buf = vec![0u8; compressed_size(&state)];
+        serialize_state_compressed(&state, &mut buf).unwrap();
+
+        // Truncated payload
+        assert_eq!(
+            deserialize_state_compressed(&buf[..buf.len() - 1]).err(),
+            Some(SnapshotError::Truncated)
+        );
+
+        // Run longer than the grid
+        let mut bad = buf[..SNAPSHOT_HEADER_LEN].to_vec();
+        bad.extend_from_slice(&[0, 0xFF, 0x01]);
+        assert_eq!(
+            deserialize_state_compressed(&bad).err(),
+            Some(SnapshotError::Corrupt)
+        );
+
+        // Zero-length run
+        let mut bad = buf[..SNAPSHOT_HEADER_LEN].to_vec();
+        bad.extend_from_slice(&[1, 0]);
+        assert_eq!(
+            deserialize_state_compressed(&bad).err(),
+            Some(SnapshotError::Corrupt)
+        );
+    }
+
+    #[test]
+    fn test_rejects_huge_dimensions_without_allocating() {
+        let state = sample_state();
+        let mut header = vec![0u8; compressed_size(&state)];
+        serialize_state_compressed(&state, &mut header).unwrap();
+        header.truncate(SNAPSHOT_HEADER_LEN);
+
+        // 32767^3 cells is far over MAX_GRID_CELLS
+        let mut huge = header.clone();
+        for offset in [6, 8, 10] {
+            huge[offset..offset + 2].copy_from_slice(&i16::MAX.to_le_bytes());
+        }
+        assert_eq!(
+            deserialize_state_compressed(&huge).err(),
+            Some(SnapshotError::BadDimensions)
+        );
+        huge[0..4].copy_from_slice(&SNAPSHOT_MAGIC);
+        assert_eq!(
+            deserialize_state(&huge).err(),
+            Some(SnapshotError::BadDimensions)
+        );
+
+        // A permitted size with no payload fails as truncated, not by
+        // allocating 1 GiB first
+        let mut large = header;
+        for offset in [6, 8, 10] {
+            large[offset..offset + 2].copy_from_slice(&1024i16.to_le_bytes());
+        }
+        assert_eq!(
+            deserialize_state_compressed(&large).err(),
+            Some(SnapshotError::Truncated)
+        );
+    }
+
+    fn smooth_field() -> Field {
+        use crate::automaton::field::{create_field_1, field_step};
+        let mut field = create_field_1(10, 9, 8, 2);
+        field.conductivity = 40_000;
+        let idx = field.cells.len() / 2;
+        field.cells[idx] = 5_000_000;
+        for _ in 0..3 {
+            field_step(&mut field);
+        }
+        field
+    }
+
+    #[test]
+    fn test_field_round_trip() {
+        let mut field = smooth_field();
+        field.rounding = RoundingMode::HalfEven;
+        field.advection = [0, -8192, 100];
+        let size = field_serialized_size(&field, None);
+        assert_eq!(size, FIELD_SNAPSHOT_HEADER_LEN + 720 * 4);
+        let mut buf = vec![0u8; size];
+        assert_eq!(serialize_field(&field, None, &mut buf), Ok(size));
+
+        let restored = deserialize_field(&buf, None).unwrap();
+        assert_eq!(
+            (restored.width, restored.height, restored.depth),
+            (10, 9, 8)
+        );
+        assert_eq!(restored.diffusion_rate, 2);
+        assert_eq!(restored.conductivity, 40_000);
+        assert_eq!(restored.generation, 3);
+        assert_eq!(restored.rounding, RoundingMode::HalfEven);
+        assert_eq!(restored.advection, [0, -8192, 100]);
+        assert_eq!(restored.cells, field.cells);
+
+        // Version 1 blobs (no advection block) still load
+        let mut v1 = buf.clone();
+        v1.drain(32..44);
+        v1[4..6].copy_from_slice(&1u16.to_le_bytes());
+        let old = deserialize_field(&v1, None).unwrap();
+        assert_eq!(old.advection, [0; 3]);
+        assert_eq!(old.cells, field.cells);
+
+        assert_eq!(
+            deserialize_field(&buf[..size - 1], None).err(),
+            Some(SnapshotError::Truncated)
+        );
+        assert_eq!(deserialize_state(&buf).err(), Some(SnapshotError::BadMagic));
+    }
+
+    #[test]
+    fn test_field_delta_round_trip() {
+        use crate::automaton::field::field_step;
+        let baseline = smooth_field();
+        let mut field = baseline.clone();
+        field_step(&mut field);
+        field.cells[0] = 0; // Large negative delta still round-trips
+
+        let size = field_serialized_size(&field, Some(&baseline));
+        assert!(
+            size < field_serialized_size(&field, None) / 2,
+            "size {}",
+            size
+        );
+        let mut buf = vec![0u8; size];
+        serialize_field(&field, Some(&baseline), &mut buf).unwrap();
+
+        let restored = deserialize_field(&buf, Some(&baseline)).unwrap();
+        assert_eq!(restored.cells, field.cells);
+        assert_eq!(restored.generation, 4);
+
+        // Missing or wrong baseline is detected
+        assert_eq!(
+            deserialize_field(&buf, None).err(),
+            Some(SnapshotError::BaselineMismatch)
+        );
+        assert_eq!(
+            deserialize_field(&buf, Some(&field)).err(),
+            Some(SnapshotError::BaselineMismatch)
+        );
+
+        // Mismatched baseline dimensions fall back to plain encoding
+        let other = crate::automaton::field::create_field_1(2, 2, 2, 2);
+        let size = field_serialized_size(&field, Some(&other));
+        assert_eq!(size, field_serialized_size(&field, None));
+        let mut buf = vec![0u8; size];
+        serialize_field(&field, Some(&other), &mut buf).unwrap();
+        assert_eq!(deserialize_field(&buf, None).unwrap().cells, field.cells);
+    }
+
+    #[test]
+    fn test_varint_round_trip() {
+        let mut buf = [0u8; 10];
+        for &v in &[0u64, 1, 127, 128, 300, 16_384, u32::MAX as u64, u64::MAX] {
+            let n = write_varint(v, &mut buf);
+            assert_eq!(n, varint_len(v));
+            assert_eq!(read_varint(&buf[..n]), Ok((v, n)));
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! Soak testing: long-running randomized stepping with invariant checks.
+//!
+//! The conservation tests under `src/tests` only run in CI. A soak runs the
+//! same kind of checks on the deployed build, against the server's own field
+//! configuration, for as long as the admin is willing to wait. Every round is
+//! driven by a single seed, so the first violation can be replayed exactly.
+
+use std::time::{Duration, Instant};
+
+use super::field::{field_step, field_step_fused, Field, RoundingMode, MAX_ADVECTION};
+use super::incremental::StepController;
+use super::rng::{mix64, SplitMix64};
+
+/// Also step a copy with the fused (rotationally symmetric) algorithm.
+pub const SOAK_FUSED: u32 = 1 << 0;
+/// Also step a copy through the incremental StepController with random tick budgets.
+pub const SOAK_CONTROLLER: u32 = 1 << 1;
+/// Randomize diffusion rate, conductivity, rounding, and advection per round, not just cell values.
+pub const SOAK_RANDOM_PARAMS: u32 = 1 << 2;
+
+/// Largest value a randomized cell is given. Keeps the grid total far from
+/// u64 limits while still exercising large gradients.
+const MAX_RANDOM_VALUE: u32 = 1 << 28;
+
+/// First invariant a soak round violated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoakViolation {
+    /// The field total changed across a step.
+    Conservation = 1,
+    /// Cell count or generation changed unexpectedly, or a cell exceeds the total.
+    Bounds = 2,
+    /// A cell dropped to zero (below the minimum quantum).
+    Underflow = 3,
+}
+
+/// Outcome of a soak run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SoakReport {
+    /// Rounds completed (including a failing one).
+    pub rounds: u64,
+    /// First violation, if any.
+    pub violation: Option<SoakViolation>,
+    /// Seed of the failing round (replay with `soak_round`), or 0 if none failed.
+    pub seed: u64,
+}
+
+/// Check the invariants for one step from `before_total` to `field`.
+fn check_step(
+    field: &Field,
+    before_total: u64,
+    cells: usize,
+    generation: u64,
+) -> Result<(), SoakViolation> {
+    if field.cells.len() != cells || field.generation != generation {
+        return Err(SoakViolation::Bounds);
+    }
+    let mut total = 0u64;
+    for &c in &field.cells {
+        if c == 0 {
+            return Err(SoakViolation::Underflow);
+        }
+        total += c as u64;
+    }
+    if total != before_total {
+        return Err(SoakViolation::Conservation);
+    }
+    if field.cells.iter().any(|&c| c as u64 > total) {
+        return Err(SoakViolation::Bounds);
+    }
+    Ok(())
+}
+
+/// Step `field` `steps` times with `step`, checking invariants after each.
+fn run_steps(
+    field: &mut Field,
+    steps: u32,
+    mut step: impl FnMut(&mut Field),
+) -> Result<(), SoakViolation> {
+    let total = field.total();
+    for _ in 0..steps {
+        let generation = field.generation + 1;
+        step(field);
+        check_step(field, total, field.cells.len(), generation)?;
+    }
+    Ok(())
+}
+
+/// Build the randomized starting field for a round.
+fn round_field(template: &Field, rng: &mut SplitMix64, flags: u32) -> Field {
+    let mut field = template.clone();
+    // Sources, sinks, and latent heat change the total by design
+    field.sources.clear();
+    field.phases = None;
+    if flags & SOAK_RANDOM_PARAMS != 0 {
+        field.diffusion_rate = rng.next_below(5) as u8;
+        field.conductivity = 1 + rng.next_below(u16::MAX as u64) as u16;
+        field.rounding = RoundingMode::from_u8(rng.next_below(4) as u8).unwrap_or_default();
+        let axis = rng.next_below(3) as usize;
+        field.advection = [0; 3];
+        field.advection[axis] =
+            rng.next_below(2 * MAX_ADVECTION as u64 + 1) as i32 - MAX_ADVECTION as i32;
+    }
+    // Sparse hot spots on a random background level
+    let background = 1 + rng.next_below(1000) as u32;
+    for cell in field.cells.iter_mut() {
+        *cell = if rng.chance_256(16) {
+            1 + rng.next_below(MAX_RANDOM_VALUE as u64) as u32
+        } else {
+            background
+        };
+    }
+    field
+}
+
+/// Run one reproducible soak round: randomize a copy of `template` from
+/// `seed`, then step it 1..=8 times with each selected algorithm.
+///
+/// `ctrl` is reused for `SOAK_CONTROLLER` rounds (building a thread pool per
+/// round would dominate the run time); pass None to create one on demand.
+pub fn soak_round(
+    template: &Field,
+    seed: u64,
+    flags: u32,
+    ctrl: Option<&mut StepController>,
+) -> Result<(), SoakViolation> {
+    let mut rng = SplitMix64::new(seed);
+    let start = round_field(template, &mut rng, flags);
+    let steps = 1 + rng.next_below(8) as u32;
+
+    run_steps(&mut start.clone(), steps, field_step)?;
+
+    if flags & SOAK_FUSED != 0 {
+        run_steps(&mut start.clone(), steps, field_step_fused)?;
+    }
+
+    if flags & SOAK_CONTROLLER != 0 {
+        let mut owned;
+        let ctrl = match ctrl {
+            Some(ctrl) => ctrl,
+            None => {
+                owned = StepController::from_field(start.clone(), 1);
+                &mut owned
+            }
+        };
+        ctrl.active_step = None;
+        run_steps(&mut start.clone(), steps, |field| {
+            std::mem::swap(field, &mut ctrl.field);
+            ctrl.begin_step().ok();
+            // Tiny random budgets split the step across many ticks
+            while !ctrl.tick(rng.next_below(50)) {}
+            std::mem::swap(field, &mut ctrl.field);
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Run randomized rounds against copies of `template` until `duration`
+/// elapses (at least one round), stopping at the first violation.
+///
+/// Round seeds are derived from `base_seed`, so the whole run is reproducible,
+/// and the failing round can be replayed on its own with `soak_round`.
+pub fn soak(template: &Field, duration: Duration, flags: u32, base_seed: u64) -> SoakReport {
+    let deadline = Instant::now() + duration;
+    let mut ctrl =
+        (flags & SOAK_CONTROLLER != 0).then(|| StepController::from_field(template.clone(), 1));
+
+    let mut rounds = 0;
+    loop {
+        // Seeds are never 0, so 0 can mean "no failure" in reports
+        let seed = mix64(base_seed ^ rounds).max(1);
+        rounds += 1;
+        if let Err(violation) = soak_round(template, seed, flags, ctrl.as_mut()) {
+            return SoakReport {
+                rounds,
+                violation: Some(violation),
+                seed,
+            };
+        }
+        if Instant::now() >= deadline {
+            return SoakReport {
+                rounds,
+                violation: None,
+                seed: 0,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::create_field_1;
+
+    #[test]
+    fn test_short_soak_passes() {
+        let template = create_field_1(20, 18, 17, 2);
+        let flags = SOAK_FUSED | SOAK_CONTROLLER | SOAK_RANDOM_PARAMS;
+        let report = soak(&template, Duration::from_millis(50), flags, 1234);
+        assert_eq!(report.violation, None);
+        assert!(report.rounds >= 1);
+        assert_eq!(report.seed, 0);
+    }
+
+    #[test]
+    fn test_rounds_are_reproducible() {
+        let template = create_field_1(6, 6, 6, 1);
+        let mut rng_a = SplitMix64::new(99);
+        let mut rng_b = SplitMix64::new(99);
+        let a = round_field(&template, &mut rng_a, SOAK_RANDOM_PARAMS);
+        let b = round_field(&template, &mut rng_b, SOAK_RANDOM_PARAMS);
+        assert_eq!(a.cells, b.cells);
+        assert_eq!(a.conductivity, b.conductivity);
+        assert_ne!(a.cells, template.cells);
+    }
+
+    #[test]
+    fn test_violations_are_detected() {
+        let mut field = create_field_1(4, 4, 4, 1);
+        field.cells[5] = 100;
+        let total = field.total();
+
+        assert_eq!(check_step(&field, total, 64, 0), Ok(()));
+        assert_eq!(
+            check_step(&field, total + 1, 64, 0),
+            Err(SoakViolation::Conservation)
+        );
+        assert_eq!(check_step(&field, total, 64, 1), Err(SoakViolation::Bounds));
+        field.cells[0] = 0;
+        assert_eq!(
+            check_step(&field, total - 1, 64, 0),
+            Err(SoakViolation::Underflow)
+        );
+
+        // A leaky step is caught and reported with its seed
+        let leak = |f: &mut Field| {
+            f.cells[0] += 1;
+            f.generation += 1;
+        };
+        let mut field = create_field_1(4, 4, 4, 1);
+        assert_eq!(
+            run_steps(&mut field, 3, leak),
+            Err(SoakViolation::Conservation)
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! Multi-species (team-colored) Life.
+//!
+//! A grid can carry a companion species id per cell (1..=`MAX_SPECIES`), so
+//! competing colonies can be told apart and rendered with different nodes.
+//! The cells themselves stay 0/1; the ids only matter where a cell is alive,
+//! and a live cell without an id (set by `va_set_cell`, a region import, a
+//! stamp, ...) counts as species 1.
+//!
+//! With a species layer, a step counts neighbors per species:
+//!
+//! - A live cell of species `s` counts only the neighbors `s` treats as
+//!   alive (see `Species::set_relation`) against the survival mask.
+//! - A dead cell takes the majority species among its live neighbors (ties go
+//!   to the lowest id) as its candidate, counts the neighbors the candidate
+//!   treats as alive against the birth mask, and if born becomes that species.
+//!
+//! By default every species treats every other as alive, which steps exactly
+//! like a grid without species. Making two species treat each other as dead
+//! lets their colonies pass through each other without interacting.
+
+use super::grid::{in_bounds, index_of};
+use crate::state::State;
+
+/// Highest species id.
+pub const MAX_SPECIES: u8 = 16;
+
+/// Per-cell species ids and the relations between species.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Species {
+    /// Species per cell in z,y,x order; 0 where no id was set.
+    pub ids: Vec<u8>,
+    /// Bit `t` of `alive_to[s]`: species `s` counts a neighbor of species `t`
+    /// as alive. `MAX_SPECIES + 1` entries; index 0 is unused.
+    pub alive_to: Vec<u32>,
+}
+
+impl Species {
+    /// A layer for `cells` cells with no ids set and every species treating
+    /// every other as alive.
+    pub fn new(cells: usize) -> Self {
+        Species {
+            ids: vec![0; cells],
+            alive_to: vec![u32::MAX; MAX_SPECIES as usize + 1],
+        }
+    }
+
+    /// Make species `observer` count neighbors of species `other` as alive
+    /// (`alive`) or dead. Returns false if either id is outside 1..=MAX_SPECIES.
+    pub fn set_relation(&mut self, observer: u8, other: u8, alive: bool) -> bool {
+        if !(1..=MAX_SPECIES).contains(&observer) || !(1..=MAX_SPECIES).contains(&other) {
+            return false;
+        }
+        let bit = 1 << other;
+        if alive {
+            self.alive_to[observer as usize] |= bit;
+        } else {
+            self.alive_to[observer as usize] &= !bit;
+        }
+        true
+    }
+
+    /// Species of the live cell at `idx` (1 if no id was set).
+    fn of(&self, idx: usize) -> u8 {
+        self.ids[idx].max(1)
+    }
+}
+
+/// Set the species of a cell, creating the layer on first use: a nonzero
+/// `species` makes the cell alive with that id, 0 kills it. Returns false
+/// (nothing changes) for coordinates outside the grid or a species above
+/// `MAX_SPECIES`.
+pub fn set_cell_species(state: &mut State, x: i16, y: i16, z: i16, species: u8) -> bool {
+    if !in_bounds(state, x, y, z) || species > MAX_SPECIES {
+        return false;
+    }
+    let (idx, len) = (index_of(state, x, y, z), state.cells.len());
+    let layer = state.species.get_or_insert_with(|| Species::new(len));
+    if layer.ids.len() != len {
+        layer.ids = vec![0; len];
+    }
+    layer.ids[idx] = species;
+    state.cells[idx] = (species != 0) as u8;
+    true
+}
+
+/// Species of a cell: 0 if it is dead or outside the grid, 1 for a live cell
+/// without a species layer or id.
+pub fn cell_species(state: &State, x: i16, y: i16, z: i16) -> u8 {
+    if !in_bounds(state, x, y, z) {
+        return 0;
+    }
+    let idx = index_of(state, x, y, z);
+    if state.cells[idx] == 0 {
+        return 0;
+    }
+    match &state.species {
+        Some(layer) if layer.ids.len() == state.cells.len() => layer.of(idx),
+        _ => 1,
+    }
+}
+
+/// Cells and species ids of the next generation under the state's rule.
+/// Ids of dead cells are 0.
+pub fn next_generation_species(state: &State, layer: &Species) -> (Vec<u8>, Vec<u8>) {
+    let len = state.cells.len();
+    let (mut next_cells, mut next_ids) = (vec![0; len], vec![0; len]);
+
+    for z in 0..state.depth {
+        for y in 0..state.height {
+            for x in 0..state.width {
+                let idx = index_of(state, x, y, z);
+                (next_cells[idx], next_ids[idx]) = next_cell_species(state, layer, x, y, z);
+            }
+        }
+    }
+    (next_cells, next_ids)
+}
+
+/// Next value and species id of the cell at (x, y, z); the id is 0 if the
+/// cell is dead.
+pub(crate) fn next_cell_species(
+    state: &State,
+    layer: &Species,
+    x: i16,
+    y: i16,
+    z: i16,
+) -> (u8, u8) {
+    let idx = index_of(state, x, y, z);
+    let rule = state.rule;
+    let mut counts = [0u8; MAX_SPECIES as usize + 1];
+    for_each_neighbor(state, x, y, z, |n| {
+        if state.cells[n] != 0 {
+            counts[layer.of(n) as usize] += 1;
+        }
+    });
+
+    let (species, mask) = if state.cells[idx] != 0 {
+        (layer.of(idx), rule.survival)
+    } else {
+        // Strict comparison keeps the lowest id on a tie
+        let mut majority = 0;
+        for s in 1..counts.len() {
+            if counts[s] > counts[majority] {
+                majority = s;
+            }
+        }
+        if majority == 0 {
+            return (0, 0);
+        }
+        (majority as u8, rule.birth)
+    };
+    let seen = layer.alive_to[species as usize];
+    let neighbors: u32 = (1..counts.len())
+        .filter(|&s| seen >> s & 1 != 0)
+        .map(|s| counts[s] as u32)
+        .sum();
+    if (mask >> neighbors) & 1 != 0 {
+        (1, species)
+    } else {
+        (0, 0)
+    }
+}
+
+/// Visit the in-grid Moore neighbors of (x, y, z) by index.
+fn for_each_neighbor(state: &State, x: i16, y: i16, z: i16, mut visit: impl FnMut(usize)) {
+    for dz in -1..=1 {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if (dx, dy, dz) == (0, 0, 0) {
+                    continue;
+                }
+                let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+                if in_bounds(state, nx, ny, nz) {
+                    visit(index_of(state, nx, ny, nz));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::grid::create_grid;
+    use crate::automaton::stepping::step_automaton;
+
+    fn plus_shape(state: &mut State, species: u8, cx: i16) {
+        for (x, y) in [(cx, 4), (cx - 1, 4), (cx + 1, 4), (cx, 3), (cx, 5)] {
+            assert!(set_cell_species(state, x, y, 4, species));
+        }
+    }
+
+    #[test]
+    fn test_single_species_matches_plain_life() {
+        let mut plain = State::default();
+        create_grid(&mut plain, 8, 8, 8);
+        for (x, y) in [(4, 4), (3, 4), (5, 4), (4, 3), (4, 5)] {
+            let idx = index_of(&plain, x, y, 4);
+            plain.cells[idx] = 1;
+        }
+        let mut colored = State::default();
+        create_grid(&mut colored, 8, 8, 8);
+        plus_shape(&mut colored, 3, 4);
+        for _ in 0..4 {
+            step_automaton(&mut plain);
+            step_automaton(&mut colored);
+            assert_eq!(plain.cells, colored.cells);
+        }
+        let layer = colored.species.as_ref().unwrap();
+        for (idx, &alive) in colored.cells.iter().enumerate() {
+            assert_eq!(layer.ids[idx], alive * 3);
+        }
+    }
+
+    #[test]
+    fn test_births_take_majority_species() {
+        let mut state = State::default();
+        create_grid(&mut state, 8, 8, 8);
+        // (3, 4, 3) sees four live neighbors: three of species 2, one of 5
+        for (x, y, species) in [(4, 4, 2), (3, 4, 2), (4, 3, 2), (4, 5, 5)] {
+            set_cell_species(&mut state, x, y, 4, species);
+        }
+        assert_eq!(cell_species(&state, 4, 5, 4), 5);
+        step_automaton(&mut state);
+        assert_eq!(cell_species(&state, 3, 4, 3), 2);
+        assert_eq!(cell_species(&state, 0, 0, 0), 0);
+        assert_eq!(cell_species(&state, -1, 0, 0), 0);
+    }
+
+    #[test]
+    fn test_species_ignoring_each_other() {
+        let mut state = State::default();
+        create_grid(&mut state, 12, 8, 8);
+        plus_shape(&mut state, 1, 3);
+        plus_shape(&mut state, 2, 6);
+        let mut apart = state.clone();
+        {
+            let layer = apart.species.as_mut().unwrap();
+            assert!(layer.set_relation(1, 2, false));
+            assert!(layer.set_relation(2, 1, false));
+            assert!(!layer.set_relation(0, 1, false));
+            assert!(!layer.set_relation(1, MAX_SPECIES + 1, false));
+        }
+        step_automaton(&mut state);
+        step_automaton(&mut apart);
+        // Neighbors across the two colonies change the outcome only when the
+        // species see each other
+        assert_ne!(state.cells, apart.cells);
+        // Both centers have exactly four neighbors of their own species
+        assert_eq!(cell_species(&apart, 3, 4, 4), 1);
+        assert_eq!(cell_species(&apart, 6, 4, 4), 2);
+        assert!(!set_cell_species(&mut state, 0, 0, 0, MAX_SPECIES + 1));
+    }
+}
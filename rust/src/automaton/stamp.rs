@@ -0,0 +1,298 @@
+//! Built-in pattern stamps for seeding initial conditions.
+//!
+//! Each pattern is a set of offsets around an anchor cell. Stamping ORs the
+//! pattern into the grid (live cells are set, nothing is cleared) and clips
+//! anything outside the grid. Oscillators and gliders are tuned for the default
+//! B4/S4 rule; under other rules they are just shapes.
+
+use super::grid::{in_bounds, index_of};
+use super::rng::{hash_coord, SplitMix64};
+use crate::state::State;
+
+/// 3x3x3 solid cube centered on the anchor.
+pub const STAMP_CUBE: u8 = 0;
+/// Solid ball of radius 3.
+pub const STAMP_SPHERE: u8 = 1;
+/// Hollow spherical shell of radius 4, one cell thick.
+pub const STAMP_SHELL: u8 = 2;
+/// Period-2 B4/S4 oscillator: a 4-cell tetrahedron that flips to its mirror image.
+pub const STAMP_BLINKER: u8 = 3;
+/// Period-2 B4/S4 glider: moves 2 cells along -z every 2 generations (before rotation).
+pub const STAMP_GLIDER: u8 = 4;
+/// Random ~50% fill of a radius-3 ball, seeded from the anchor position.
+pub const STAMP_RANDOM_BLOB: u8 = 5;
+
+/// Number of built-in patterns (valid ids are `0..STAMP_COUNT`).
+pub const STAMP_COUNT: u8 = 6;
+
+/// Number of distinct axis-aligned orientations of a cube.
+pub const ROTATION_COUNT: u8 = 24;
+
+const BLINKER: [[i16; 3]; 4] = [[0, 0, 0], [1, 1, 0], [1, 0, 1], [0, 1, 1]];
+
+const GLIDER: [[i16; 3]; 6] = [
+    [0, 0, 0],
+    [1, 0, 0],
+    [0, 1, 0],
+    [1, 1, 0],
+    [2, 1, 2],
+    [1, 2, 2],
+];
+
+/// The 24 proper rotations of the cube as signed permutation matrices.
+///
+/// Index 0 is the identity. The order is fixed (permutations in lexicographic
+/// order, then sign patterns), so rotation ids are stable across versions.
+pub fn rotation_matrices() -> [[[i8; 3]; 3]; 24] {
+    const PERMS: [[usize; 3]; 6] = [
+        [0, 1, 2],
+        [0, 2, 1],
+        [1, 0, 2],
+        [1, 2, 0],
+        [2, 0, 1],
+        [2, 1, 0],
+    ];
+    let mut out = [[[0i8; 3]; 3]; 24];
+    let mut n = 0;
+    for perm in PERMS {
+        let parity = if perm == [0, 1, 2] || perm == [1, 2, 0] || perm == [2, 0, 1] {
+            1
+        } else {
+            -1
+        };
+        for signs in 0..8u8 {
+            let s = [
+                if signs & 1 == 0 { 1 } else { -1 },
+                if signs & 2 == 0 { 1 } else { -1 },
+                if signs & 4 == 0 { 1 } else { -1 },
+            ];
+            // det = parity(perm) * product(signs); keep only proper rotations
+            if parity * s[0] * s[1] * s[2] != 1 {
+                continue;
+            }
+            for row in 0..3 {
+                out[n][row][perm[row]] = s[row];
+            }
+            n += 1;
+        }
+    }
+    out
+}
+
+/// Apply rotation `rotation` (0..24) to an offset.
+pub fn rotate(offset: [i16; 3], rotation: u8) -> [i16; 3] {
+    let m = rotation_matrices()[rotation as usize % 24];
+    let mut out = [0i16; 3];
+    for (row, out_v) in out.iter_mut().enumerate() {
+        *out_v = (0..3).map(|col| m[row][col] as i16 * offset[col]).sum();
+    }
+    out
+}
+
+fn ball(radius: i16, keep: impl Fn(i32) -> bool) -> Vec<[i16; 3]> {
+    let mut cells = Vec::new();
+    for z in -radius..=radius {
+        for y in -radius..=radius {
+            for x in -radius..=radius {
+                let d2 = (x as i32).pow(2) + (y as i32).pow(2) + (z as i32).pow(2);
+                if keep(d2) {
+                    cells.push([x, y, z]);
+                }
+            }
+        }
+    }
+    cells
+}
+
+/// Offsets of pattern `pattern_id` before rotation, or None for an unknown id.
+///
+/// `seed` only affects `STAMP_RANDOM_BLOB`.
+pub fn pattern_cells(pattern_id: u8, seed: u64) -> Option<Vec<[i16; 3]>> {
+    let cells = match pattern_id {
+        STAMP_CUBE => ball(1, |_| true),
+        STAMP_SPHERE => ball(3, |d2| d2 <= 9),
+        // Cells whose distance rounds to 4: (3.5)^2 < d2 <= (4.5)^2
+        STAMP_SHELL => ball(4, |d2| 4 * d2 > 49 && 4 * d2 <= 81),
+        STAMP_BLINKER => BLINKER.to_vec(),
+        STAMP_GLIDER => GLIDER.to_vec(),
+        STAMP_RANDOM_BLOB => {
+            let mut rng = SplitMix64::new(seed);
+            ball(3, |d2| d2 <= 9)
+                .into_iter()
+                .filter(|_| rng.chance_256(128))
+                .collect()
+        }
+        _ => return None,
+    };
+    Some(cells)
+}
+
+/// Stamp a built-in pattern at `(x, y, z)` with the given rotation (0..24).
+///
+/// Random blobs are seeded from the anchor position and the current generation,
+/// so the same call on the same state is reproducible.
+///
+/// # Returns
+/// Number of in-bounds cells set alive, or None for an unknown pattern or rotation.
+pub fn stamp_pattern(
+    state: &mut State,
+    pattern_id: u8,
+    x: i16,
+    y: i16,
+    z: i16,
+    rotation: u8,
+) -> Option<u32> {
+    if rotation >= ROTATION_COUNT {
+        return None;
+    }
+    let seed = hash_coord(state.generation, x, y, z);
+    let cells = pattern_cells(pattern_id, seed)?;
+
+    let mut written = 0;
+    for offset in cells {
+        let [dx, dy, dz] = rotate(offset, rotation);
+        let (Some(px), Some(py), Some(pz)) =
+            (x.checked_add(dx), y.checked_add(dy), z.checked_add(dz))
+        else {
+            continue;
+        };
+        if in_bounds(state, px, py, pz) {
+            let idx = index_of(state, px, py, pz);
+            state.cells[idx] = 1;
+            written += 1;
+        }
+    }
+    Some(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::grid::create_grid;
+    use crate::automaton::stepping::step_automaton;
+
+    fn alive(state: &State) -> Vec<[i16; 3]> {
+        let mut out = Vec::new();
+        for z in 0..state.depth {
+            for y in 0..state.height {
+                for x in 0..state.width {
+                    if state.cells[index_of(state, x, y, z)] == 1 {
+                        out.push([x, y, z]);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    fn grid(size: i16) -> State {
+        let mut state = State::default();
+        create_grid(&mut state, size, size, size);
+        state
+    }
+
+    #[test]
+    fn test_rotations_are_distinct_proper_rotations() {
+        let mats = rotation_matrices();
+        assert_eq!(mats[0], [[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+        for (i, a) in mats.iter().enumerate() {
+            for b in &mats[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+        // Rotations preserve handedness: x cross y = z
+        for r in 0..24 {
+            let (x, y, z) = (
+                rotate([1, 0, 0], r),
+                rotate([0, 1, 0], r),
+                rotate([0, 0, 1], r),
+            );
+            let cross = [
+                x[1] * y[2] - x[2] * y[1],
+                x[2] * y[0] - x[0] * y[2],
+                x[0] * y[1] - x[1] * y[0],
+            ];
+            assert_eq!(cross, z);
+        }
+    }
+
+    #[test]
+    fn test_solid_shapes() {
+        let mut state = grid(16);
+        assert_eq!(stamp_pattern(&mut state, STAMP_CUBE, 8, 8, 8, 0), Some(27));
+        let mut state = grid(16);
+        assert_eq!(
+            stamp_pattern(&mut state, STAMP_SPHERE, 8, 8, 8, 0),
+            Some(123)
+        );
+        // Shell is hollow
+        let mut state = grid(16);
+        stamp_pattern(&mut state, STAMP_SHELL, 8, 8, 8, 0).unwrap();
+        assert_eq!(state.cells[index_of(&state, 8, 8, 8)], 0);
+        assert_eq!(state.cells[index_of(&state, 12, 8, 8)], 1);
+    }
+
+    #[test]
+    fn test_clipping_and_invalid_ids() {
+        let mut state = grid(8);
+        // Corner cube: only the 2x2x2 octant is inside
+        assert_eq!(stamp_pattern(&mut state, STAMP_CUBE, 0, 0, 0, 0), Some(8));
+        assert_eq!(stamp_pattern(&mut state, STAMP_COUNT, 4, 4, 4, 0), None);
+        assert_eq!(stamp_pattern(&mut state, STAMP_CUBE, 4, 4, 4, 24), None);
+        // Far outside and near i16 limits: nothing written, no overflow
+        assert_eq!(
+            stamp_pattern(&mut state, STAMP_CUBE, i16::MAX, i16::MIN, 0, 5),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_blinker_oscillates() {
+        let mut state = grid(12);
+        stamp_pattern(&mut state, STAMP_BLINKER, 5, 5, 5, 7).unwrap();
+        let start = alive(&state);
+
+        step_automaton(&mut state);
+        assert_ne!(alive(&state), start);
+        step_automaton(&mut state);
+        assert_eq!(alive(&state), start);
+    }
+
+    #[test]
+    fn test_glider_translates_under_every_rotation() {
+        for r in 0..ROTATION_COUNT {
+            let mut state = grid(20);
+            stamp_pattern(&mut state, STAMP_GLIDER, 10, 10, 10, r).unwrap();
+            let start = alive(&state);
+
+            step_automaton(&mut state);
+            step_automaton(&mut state);
+
+            let [dx, dy, dz] = rotate([0, 0, -2], r);
+            let moved: Vec<_> = start
+                .iter()
+                .map(|p| [p[0] + dx, p[1] + dy, p[2] + dz])
+                .collect();
+            let mut now = alive(&state);
+            let mut moved = moved;
+            now.sort();
+            moved.sort();
+            assert_eq!(now, moved, "rotation {}", r);
+        }
+    }
+
+    #[test]
+    fn test_random_blob_reproducible() {
+        let mut a = grid(16);
+        let mut b = grid(16);
+        let na = stamp_pattern(&mut a, STAMP_RANDOM_BLOB, 8, 8, 8, 0).unwrap();
+        let nb = stamp_pattern(&mut b, STAMP_RANDOM_BLOB, 8, 8, 8, 0).unwrap();
+        assert_eq!(a.cells, b.cells);
+        assert_eq!(na, nb);
+        assert!(na > 20 && na < 103, "blob size {}", na);
+
+        let mut c = grid(16);
+        stamp_pattern(&mut c, STAMP_RANDOM_BLOB, 8, 8, 9, 0).unwrap();
+        assert_ne!(a.cells, c.cells);
+    }
+}
@@ -0,0 +1,207 @@
+//! Value distribution of a field: histograms and percentiles.
+//!
+//! A HUD showing the temperature spread of a world would otherwise have to
+//! pull the whole grid into Lua every frame. Histogram buckets split the
+//! field's own range `[min, max]` (see `FieldStats`) evenly, so they follow
+//! the field as it heats or cools; the caller reads min and max alongside to
+//! label them.
+//!
+//! Classification (`field_classify`) goes the other way: fixed thresholds
+//! chosen by the caller turn every cell into a small class index (cold, warm,
+//! hot), ready to pick a node per cell without touching the raw values in Lua.
+//! Zones are the same classification stored on the field (`field.zones`), so
+//! gameplay systems can ask for the zone of a cell or region (cold,
+//! comfortable, hot) without passing the breakpoints on every call.
+
+use super::field::{field_in_bounds, field_index_of, Field};
+use super::poststep::FieldStats;
+use super::region::{clamp_box, for_each_row};
+
+/// Count the cells of `field` into `buckets.len()` equal-width buckets over
+/// `[min, max]`: bucket `i` holds values from `min + i * span / n` up to (not
+/// including) the next bound, with the last bucket ending at `max` inclusive.
+/// Returns the statistics the buckets were laid out from. A field holding one
+/// value puts every cell in bucket 0. Does nothing for an empty `buckets`.
+pub fn field_histogram(field: &Field, buckets: &mut [u64]) -> FieldStats {
+    let stats = FieldStats::of(field);
+    buckets.fill(0);
+    if buckets.is_empty() {
+        return stats;
+    }
+    let count = buckets.len() as u128;
+    let span = (stats.max - stats.min) as u128 + 1;
+    for &value in &field.cells {
+        let bucket = (value - stats.min) as u128 * count / span;
+        buckets[bucket as usize] += 1;
+    }
+    stats
+}
+
+/// Nearest-rank percentile: the smallest cell value with at least `percent`
+/// percent of the cells at or below it. 0 gives the minimum, 100 the maximum.
+/// None for a percent outside `[0, 100]` (or NaN) or an empty field.
+pub fn field_percentile(field: &Field, percent: f64) -> Option<u32> {
+    if !(0.0..=100.0).contains(&percent) || field.cells.is_empty() {
+        return None;
+    }
+    let n = field.cells.len();
+    let rank = ((percent / 100.0 * n as f64).ceil() as usize).clamp(1, n);
+    let mut values = field.cells.clone();
+    let (_, &mut value, _) = values.select_nth_unstable(rank - 1);
+    Some(value)
+}
+
+/// Write the class of every cell of `field` to `out` in z,y,x order: the
+/// number of `thresholds` at or below the cell's value, so values under
+/// `thresholds[0]` are class 0 and values at or above the last threshold are
+/// class `thresholds.len()`. Returns the number of cells written, or 0 if the
+/// thresholds are not strictly ascending, there are more than 255 of them, or
+/// `out` is shorter than the field.
+pub fn field_classify(field: &Field, thresholds: &[u32], out: &mut [u8]) -> u64 {
+    if !thresholds_valid(thresholds) || out.len() < field.cells.len() {
+        return 0;
+    }
+    for (class, &value) in out.iter_mut().zip(&field.cells) {
+        *class = class_of(thresholds, value);
+    }
+    field.cells.len() as u64
+}
+
+/// Strictly ascending, and few enough that every class fits in a u8.
+fn thresholds_valid(thresholds: &[u32]) -> bool {
+    thresholds.len() <= u8::MAX as usize && thresholds.windows(2).all(|w| w[0] < w[1])
+}
+
+/// Number of `thresholds` at or below `value`.
+fn class_of(thresholds: &[u32], value: u32) -> u8 {
+    thresholds.partition_point(|&t| t <= value) as u8
+}
+
+/// Configure the field's zone breakpoints (same rules as `field_classify`
+/// thresholds); an empty list removes them. Returns false, changing nothing,
+/// if the breakpoints are invalid.
+pub fn field_set_zones(field: &mut Field, breakpoints: &[u32]) -> bool {
+    if !thresholds_valid(breakpoints) {
+        return false;
+    }
+    field.zones = (!breakpoints.is_empty()).then(|| breakpoints.to_vec());
+    true
+}
+
+/// Zone of the cell at (x, y, z), or None outside the field or without zones.
+pub fn field_zone(field: &Field, x: i16, y: i16, z: i16) -> Option<u8> {
+    let zones = field.zones.as_ref()?;
+    if !field_in_bounds(field, x, y, z) {
+        return None;
+    }
+    Some(class_of(zones, field.cells[field_index_of(field, x, y, z)]))
+}
+
+/// Write the zone of every cell of the half-open box `[min, max)`, clamped to
+/// the field, to `out` in z,y,x order (the layout of `field_extract_region`).
+/// Returns the number of cells written, or 0 if the box is empty, the field
+/// has no zones, or `out` is too small.
+pub fn field_extract_zones(field: &Field, out: &mut [u8], min: [i16; 3], max: [i16; 3]) -> u64 {
+    let dims = [field.width, field.height, field.depth];
+    let (Some(zones), Some((lo, hi))) = (&field.zones, clamp_box(dims, min, max)) else {
+        return 0;
+    };
+    let size: usize = (0..3).map(|axis| (hi[axis] - lo[axis]) as usize).product();
+    if out.len() < size {
+        return 0;
+    }
+    let mut written = 0;
+    for_each_row(dims, lo, hi, |start, len| {
+        let row = &field.cells[start..start + len];
+        for (zone, &value) in out[written..written + len].iter_mut().zip(row) {
+            *zone = class_of(zones, value);
+        }
+        written += len;
+    });
+    written as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::create_field_1;
+
+    #[test]
+    fn test_histogram_spans_min_to_max() {
+        let mut field = create_field_1(10, 1, 1, 0);
+        for (i, cell) in field.cells.iter_mut().enumerate() {
+            *cell = 100 + i as u32 * 10;
+        }
+        let mut buckets = [0u64; 4];
+        let stats = field_histogram(&field, &mut buckets);
+        assert_eq!((stats.min, stats.max), (100, 190));
+        // Span 91 over 4 buckets: [100, 122.75), [122.75, 145.5), ...
+        assert_eq!(buckets, [3, 2, 2, 3]);
+        assert_eq!(buckets.iter().sum::<u64>(), 10);
+
+        field.cells.fill(u32::MAX);
+        field_histogram(&field, &mut buckets);
+        assert_eq!(buckets, [10, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let mut field = create_field_1(4, 5, 1, 0);
+        for (i, cell) in field.cells.iter_mut().rev().enumerate() {
+            *cell = i as u32 + 1;
+        }
+        assert_eq!(field_percentile(&field, 0.0), Some(1));
+        assert_eq!(field_percentile(&field, 50.0), Some(10));
+        assert_eq!(field_percentile(&field, 51.0), Some(11));
+        assert_eq!(field_percentile(&field, 100.0), Some(20));
+        assert_eq!(field_percentile(&field, 100.5), None);
+        assert_eq!(field_percentile(&field, f64::NAN), None);
+    }
+
+    #[test]
+    fn test_classify_by_thresholds() {
+        let mut field = create_field_1(5, 1, 1, 0);
+        field.cells.copy_from_slice(&[1, 99, 100, 5000, u32::MAX]);
+        let mut out = [0xffu8; 5];
+        assert_eq!(field_classify(&field, &[100, 5000], &mut out), 5);
+        assert_eq!(out, [0, 0, 1, 2, 2]);
+        assert_eq!(field_classify(&field, &[], &mut out), 5);
+        assert_eq!(out, [0; 5]);
+
+        assert_eq!(field_classify(&field, &[100, 100], &mut out), 0);
+        assert_eq!(field_classify(&field, &[100], &mut out[..4]), 0);
+    }
+
+    #[test]
+    fn test_zones_of_cells_and_regions() {
+        let mut field = create_field_1(4, 2, 2, 0);
+        field.cells[1] = 290_000;
+        field.cells[15] = 320_000;
+        let mut out = [9u8; 16];
+        assert_eq!(field_zone(&field, 0, 0, 0), None);
+        assert_eq!(field_extract_zones(&field, &mut out, [0; 3], [4, 2, 2]), 0);
+
+        // Cold below 280000, comfortable below 310000, hot above
+        assert!(field_set_zones(&mut field, &[280_000, 310_000]));
+        assert_eq!(field_zone(&field, 0, 0, 0), Some(0));
+        assert_eq!(field_zone(&field, 1, 0, 0), Some(1));
+        assert_eq!(field_zone(&field, 3, 1, 1), Some(2));
+        assert_eq!(field_zone(&field, 4, 0, 0), None);
+        assert_eq!(
+            field_extract_zones(&field, &mut out, [1, 0, 0], [9, 1, 1]),
+            3
+        );
+        assert_eq!(out[..3], [1, 0, 0]);
+        assert_eq!(
+            field_extract_zones(&field, &mut out[..15], [0; 3], [4, 2, 2]),
+            0
+        );
+        assert_eq!(field_extract_zones(&field, &mut out, [-4; 3], [9; 3]), 16);
+        assert_eq!(out[15], 2);
+
+        assert!(!field_set_zones(&mut field, &[5, 5]));
+        assert_eq!(field.zones.as_deref(), Some(&[280_000, 310_000][..]));
+        assert!(field_set_zones(&mut field, &[]));
+        assert_eq!(field.zones, None);
+    }
+}
@@ -1,51 +1,207 @@
-//! Cellular automaton stepping with B4/S4 rules.
+//! Cellular automaton stepping with birth/survival rules (B4/S4 by default).
 
+use super::cycle::record_generation;
 use super::grid::{count_neighbors, index_of};
-use crate::state::State;
+use super::history::record_checkpoint;
+use super::protect::apply_protection;
+use super::species::{next_cell_species, next_generation_species};
+use super::transition::{next_cell_table, next_generation_table};
+use super::wireworld::{next_cell_wireworld, next_generation_wireworld};
+use crate::state::{State, StepMode};
 
-/// Step the automaton forward by one generation using B4/S4 rules.
+/// Step the automaton forward by one generation using the state's rule.
 ///
-/// B4/S4 rules:
+/// Default B4/S4 rules:
 /// - Birth: A dead cell with exactly 4 neighbors becomes alive
 /// - Survival: An alive cell with exactly 4 neighbors survives
 /// - Moore neighborhood: 26 neighbors (3x3x3 cube excluding center)
+///
+/// Committed births and deaths are appended to the state's write queue, if it
+/// has one. A grid with a species layer counts neighbors per species (see
+/// `automaton::species`); one tracking ages kills cells at its age limit (see
+/// `automaton::age`). A transition table replaces the rule and species (see
+/// `automaton::transition`), and WireWorld mode replaces all three (see
+/// `automaton::wireworld`). With history enabled, a checkpoint is recorded
+/// every `interval` generations (see `automaton::history`), and with cycle
+/// tracking on, the new generation's hash is recorded (see `automaton::cycle`).
 pub fn step_automaton(state: &mut State) {
+    step_automaton_gated(state, |_| true);
+}
+
+/// `step_automaton`, with births allowed only in cells whose index satisfies
+/// `may_be_born` (survival and deaths are unaffected). The gate runs before the
+/// protection mask, so a gated birth is not counted as suppressed.
+pub fn step_automaton_gated(state: &mut State, may_be_born: impl Fn(usize) -> bool) {
     if state.cells.is_empty() {
         return;
     }
 
-    let mut next_cells = vec![0; state.cells.len()];
+    let (next_cells, next_ids) = next_cells_and_species(state);
+    commit_generation(state, next_cells, next_ids, may_be_born);
+}
+
+/// Second half of `step_automaton_gated`: make `next_cells` (and `next_ids`,
+/// if the grid tracks species) the new generation, through the birth gate,
+/// protection, ages, write queue, history and cycle tracking.
+pub(crate) fn commit_generation(
+    state: &mut State,
+    mut next_cells: Vec<u8>,
+    next_ids: Option<Vec<u8>>,
+    may_be_born: impl Fn(usize) -> bool,
+) {
+    for (idx, (next, &current)) in next_cells.iter_mut().zip(&state.cells).enumerate() {
+        if current == 0 && *next != 0 && !may_be_born(idx) {
+            *next = 0;
+        }
+    }
+    if state.mode != StepMode::WireWorld {
+        apply_protection(&mut state.protection, &state.cells, &mut next_cells);
+    }
+    if let Some(age) = &mut state.age {
+        age.advance(&state.cells, &mut next_cells);
+    }
+    if let Some(queue) = &mut state.write_queue {
+        let dims = [state.width, state.height, state.depth];
+        queue.record_step(dims, &state.cells, &next_cells);
+    }
+    if let (Some(layer), Some(mut ids)) = (&mut state.species, next_ids) {
+        // Births vetoed after the species step leave no id behind
+        for (id, &alive) in ids.iter_mut().zip(&next_cells) {
+            if alive == 0 {
+                *id = 0;
+            }
+        }
+        layer.ids = ids;
+    }
+    state.cells = next_cells;
+    state.generation += 1;
+    record_checkpoint(state);
+    record_generation(state);
+}
+
+/// A cell that the next generation would flip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellChange {
+    pub x: i16,
+    pub y: i16,
+    pub z: i16,
+    /// State after the step (true = born, false = dies).
+    pub alive: bool,
+}
 
+/// Compute the changes of the next generation without committing them, in
+/// cell index order (x fastest, then y, then z). Births the protection mask
+/// would veto are left out. The state is untouched, so a caller can inspect
+/// or veto the step before calling `step_automaton`.
+pub fn step_preview(state: &State) -> Vec<CellChange> {
+    if state.cells.is_empty() {
+        return Vec::new();
+    }
+
+    let (mut next_cells, _) = next_cells_and_species(state);
+    if let (Some(protection), StepMode::Rule) = (&state.protection, state.mode) {
+        protection.veto(&state.cells, &mut next_cells);
+    }
+    let mut changes = Vec::new();
     for z in 0..state.depth {
         for y in 0..state.height {
             for x in 0..state.width {
-                let neighbors = count_neighbors(state, x, y, z);
                 let idx = index_of(state, x, y, z);
-
-                // B4/S4 rule: Birth on 4, Survival on 4
-                next_cells[idx] = if neighbors == 4 { 1 } else { 0 };
+                if next_cells[idx] != state.cells[idx] {
+                    changes.push(CellChange {
+                        x,
+                        y,
+                        z,
+                        alive: next_cells[idx] != 0,
+                    });
+                }
             }
         }
     }
+    changes
+}
 
-    state.cells = next_cells;
-    state.generation += 1;
+/// Cells of the next generation, plus their species ids if the state has a
+/// species layer matching the grid (and no transition table or WireWorld mode).
+fn next_cells_and_species(state: &State) -> (Vec<u8>, Option<Vec<u8>>) {
+    if state.mode == StepMode::WireWorld {
+        return (next_generation_wireworld(state), None);
+    }
+    if let Some(table) = &state.transitions {
+        return (next_generation_table(state, table), None);
+    }
+    match &state.species {
+        Some(layer) if layer.ids.len() == state.cells.len() => {
+            let (cells, ids) = next_generation_species(state, layer);
+            (cells, Some(ids))
+        }
+        _ => (next_generation(state), None),
+    }
+}
+
+/// Whether a step produces species ids (see `next_cells_and_species`).
+pub(crate) fn tracks_species(state: &State) -> bool {
+    state.mode != StepMode::WireWorld
+        && state.transitions.is_none()
+        && state
+            .species
+            .as_ref()
+            .is_some_and(|layer| layer.ids.len() == state.cells.len())
+}
+
+/// Next value and species id (0 unless `tracks_species`) of the cell at
+/// (x, y, z): one cell of `next_cells_and_species`.
+pub(crate) fn next_cell(state: &State, x: i16, y: i16, z: i16) -> (u8, u8) {
+    if state.mode == StepMode::WireWorld {
+        return (next_cell_wireworld(state, x, y, z), 0);
+    }
+    if let Some(table) = &state.transitions {
+        return (next_cell_table(state, table, x, y, z), 0);
+    }
+    match &state.species {
+        Some(layer) if layer.ids.len() == state.cells.len() => {
+            next_cell_species(state, layer, x, y, z)
+        }
+        _ => (next_cell_rule(state, x, y, z), 0),
+    }
+}
+
+/// Next value of the cell at (x, y, z) under the state's rule.
+#[inline]
+fn next_cell_rule(state: &State, x: i16, y: i16, z: i16) -> u8 {
+    let neighbors = count_neighbors(state, x, y, z);
+    let mask = if state.cells[index_of(state, x, y, z)] != 0 {
+        state.rule.survival
+    } else {
+        state.rule.birth
+    };
+    ((mask >> neighbors) & 1) as u8
+}
+
+/// Cells of the next generation under the state's rule.
+fn next_generation(state: &State) -> Vec<u8> {
+    let mut next_cells = vec![0; state.cells.len()];
+
+    for z in 0..state.depth {
+        for y in 0..state.height {
+            for x in 0..state.width {
+                let idx = index_of(state, x, y, z);
+                next_cells[idx] = next_cell_rule(state, x, y, z);
+            }
+        }
+    }
+    next_cells
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::automaton::grid::create_grid;
+    use crate::state::Rule;
 
     #[test]
     fn test_step_b4s4_basic() {
-        let mut state = State {
-            width: 0,
-            height: 0,
-            depth: 0,
-            cells: Vec::new(),
-            generation: 0,
-        };
+        let mut state = State::default();
 
         create_grid(&mut state, 8, 8, 8);
 
@@ -86,13 +242,7 @@ mod tests {
 
     #[test]
     fn test_step_generation_increments() {
-        let mut state = State {
-            width: 0,
-            height: 0,
-            depth: 0,
-            cells: Vec::new(),
-            generation: 0,
-        };
+        let mut state = State::default();
 
         create_grid(&mut state, 4, 4, 4);
 
@@ -105,13 +255,7 @@ mod tests {
 
     #[test]
     fn test_step_empty_grid_stays_empty() {
-        let mut state = State {
-            width: 0,
-            height: 0,
-            depth: 0,
-            cells: Vec::new(),
-            generation: 0,
-        };
+        let mut state = State::default();
 
         create_grid(&mut state, 4, 4, 4);
 
@@ -119,4 +263,59 @@ mod tests {
         assert!(state.cells.iter().all(|&c| c == 0));
         assert_eq!(state.generation, 1);
     }
+
+    #[test]
+    fn test_preview_matches_step_without_committing() {
+        let mut state = State::default();
+        create_grid(&mut state, 8, 8, 8);
+        for (x, y) in [(4, 4), (3, 4), (5, 4), (4, 3), (4, 5)] {
+            let idx = index_of(&state, x, y, 4);
+            state.cells[idx] = 1;
+        }
+        let before = state.cells.clone();
+
+        let changes = step_preview(&state);
+        assert_eq!(state.cells, before);
+        assert_eq!(state.generation, 0);
+        // The four arms die (the center, with 4 neighbors, survives)
+        for (x, y) in [(3, 4), (5, 4), (4, 3), (4, 5)] {
+            let died = CellChange {
+                x,
+                y,
+                z: 4,
+                alive: false,
+            };
+            assert!(changes.contains(&died));
+        }
+        assert!(changes
+            .iter()
+            .all(|change| (change.x, change.y, change.z) != (4, 4, 4)));
+
+        let mut expected = before.clone();
+        for change in &changes {
+            let idx = index_of(&state, change.x, change.y, change.z);
+            expected[idx] = change.alive as u8;
+        }
+        step_automaton(&mut state);
+        assert_eq!(state.cells, expected);
+        assert!(step_preview(&State::default()).is_empty());
+    }
+
+    #[test]
+    fn test_step_custom_rule() {
+        let mut state = State::default();
+        create_grid(&mut state, 8, 8, 8);
+        // B1/S (everything dies, any cell touching exactly one live cell is born)
+        state.rule = Rule {
+            birth: 1 << 1,
+            survival: 0,
+        };
+        let idx = index_of(&state, 4, 4, 4);
+        state.cells[idx] = 1;
+
+        step_automaton(&mut state);
+
+        assert_eq!(state.cells[index_of(&state, 4, 4, 4)], 0);
+        assert_eq!(state.cells.iter().filter(|&&c| c == 1).count(), 26);
+    }
 }
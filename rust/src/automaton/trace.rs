@@ -0,0 +1,310 @@
+//! Per-cell tracing across generations.
+//!
+//! Steps the simulation forward as usual while recording what happened to one
+//! cell: its value before and after each generation, its live neighbor count
+//! (CA grid), and the flow across each of its six faces (field). This is what a
+//! "this cell did something weird" bug report needs, without rebuilding the
+//! library with print statements. `field_explain` answers the same question
+//! for the step that already happened, as readable text and without stepping.
+
+use std::fmt::Write;
+
+use super::boundary::Boundary;
+use super::conductivity::pair_conductivity;
+use super::field::{field_in_bounds, field_index_of, field_step_observed, Field};
+use super::grid::{count_neighbors, in_bounds, index_of};
+use super::stepping::step_automaton;
+use crate::state::State;
+
+/// Face order for `CellTrace::flows`.
+pub const FACE_NEG_X: usize = 0;
+pub const FACE_POS_X: usize = 1;
+pub const FACE_NEG_Y: usize = 2;
+pub const FACE_POS_Y: usize = 3;
+pub const FACE_NEG_Z: usize = 4;
+pub const FACE_POS_Z: usize = 5;
+
+/// What happened to the traced cell during one generation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CellTrace {
+    /// Generation the record starts from (the step goes to `generation + 1`).
+    pub generation: u64,
+    /// Cell value before the step.
+    pub value: u32,
+    /// Cell value after the step.
+    pub next_value: u32,
+    /// Live Moore neighbors before the step (CA grid only; 0 for fields).
+    pub neighbors: u8,
+    /// Net inflow across each face, in `FACE_*` order (fields only; 0 for the
+    /// CA grid). The sum equals `next_value - value`, except at a source or
+    /// sink, whose rate is applied before diffusion, and when the cell changes
+    /// phase (latent heat is exchanged after diffusion).
+    pub flows: [i64; 6],
+}
+
+/// Step `state` once per entry of `out`, recording cell `(x, y, z)` each time.
+///
+/// # Returns
+/// False (and nothing is stepped) if the cell is outside the grid.
+pub fn trace_cell(state: &mut State, x: i16, y: i16, z: i16, out: &mut [CellTrace]) -> bool {
+    if !in_bounds(state, x, y, z) {
+        return false;
+    }
+    let idx = index_of(state, x, y, z);
+    for record in out {
+        let generation = state.generation;
+        let value = state.cells[idx] as u32;
+        let neighbors = count_neighbors(state, x, y, z);
+        step_automaton(state);
+        *record = CellTrace {
+            generation,
+            value,
+            next_value: state.cells[idx] as u32,
+            neighbors,
+            flows: [0; 6],
+        };
+    }
+    true
+}
+
+/// Step `field` (sequential diffusion, `va_field_step`'s default) once per entry
+/// of `out`, recording cell `(x, y, z)` and the flow across each of its faces.
+///
+/// # Returns
+/// False (and nothing is stepped) if the cell is outside the field.
+pub fn field_trace_cell(field: &mut Field, x: i16, y: i16, z: i16, out: &mut [CellTrace]) -> bool {
+    if !field_in_bounds(field, x, y, z) {
+        return false;
+    }
+    let idx = field_index_of(field, x, y, z);
+    for record in out {
+        let generation = field.generation;
+        let value = field.cells[idx];
+        let mut flows = [0i64; 6];
+        field_step_observed(field, |axis, idx_a, idx_b, flow| {
+            // The traced cell is either the low side (its +axis face) or the
+            // high side (its -axis face) of the pair
+            if idx_a == idx {
+                flows[axis * 2 + 1] -= flow;
+            } else if idx_b == idx {
+                flows[axis * 2] += flow;
+            }
+        });
+        *record = CellTrace {
+            generation,
+            value,
+            next_value: field.cells[idx],
+            neighbors: 0,
+            flows,
+        };
+    }
+    true
+}
+
+const FACE_NAMES: [&str; 6] = ["-x", "+x", "-y", "+y", "-z", "+z"];
+
+/// Net inflow across each face of cell `idx` in the last recorded step, from
+/// the field's flow record, or None if recording is off or nothing was recorded.
+fn recorded_flows(field: &Field, idx: usize) -> Option<[i64; 6]> {
+    let record = field.flow_record.as_deref()?;
+    if record.len() != field.cells.len() * 3 {
+        return None;
+    }
+    let dims = [
+        field.width as usize,
+        field.height as usize,
+        field.depth as usize,
+    ];
+    let strides = [1, dims[0], dims[0] * dims[1]];
+    let mut flows = [0i64; 6];
+    for axis in 0..3 {
+        let coord = idx / strides[axis] % dims[axis];
+        let last = dims[axis] - 1;
+        // The record holds each cell's +axis face, positive toward +axis; on a
+        // periodic axis the last layer's +axis face wraps to the first layer
+        if coord > 0 {
+            flows[axis * 2] = record[(idx - strides[axis]) * 3 + axis] as i64;
+        } else if field.periodic[axis] {
+            flows[axis * 2] = record[(idx + last * strides[axis]) * 3 + axis] as i64;
+        }
+        if coord < last || field.periodic[axis] {
+            flows[axis * 2 + 1] = -(record[idx * 3 + axis] as i64);
+        }
+    }
+    Some(flows)
+}
+
+/// Human-readable account of cell `(x, y, z)`: its value, the flow across each
+/// face in the last step (if flow recording is on), its source or sink, the
+/// boundary conditions it touches, and its phase. Nothing is stepped.
+///
+/// # Returns
+/// None if the cell is outside the field.
+pub fn field_explain(field: &Field, x: i16, y: i16, z: i16) -> Option<String> {
+    if !field_in_bounds(field, x, y, z) {
+        return None;
+    }
+    let idx = field_index_of(field, x, y, z);
+    let value = field.cells[idx];
+    let mut out = String::new();
+
+    // Writing to a String cannot fail
+    let _ = writeln!(
+        out,
+        "cell ({x}, {y}, {z}) of a {}x{}x{} field at generation {}",
+        field.width, field.height, field.depth, field.generation
+    );
+    let _ = writeln!(out, "value: {value}");
+    let conductivity = pair_conductivity(
+        field.conductivity_curve.as_ref(),
+        field.conductivity as i64,
+        value,
+        value,
+    );
+    let _ = writeln!(out, "conductivity: {conductivity} / 65536");
+
+    match recorded_flows(field, idx) {
+        Some(flows) => {
+            let _ = writeln!(out, "last step flows (positive = into this cell):");
+            for (axis, pair) in flows.chunks_exact(2).enumerate() {
+                let _ = writeln!(
+                    out,
+                    "  {}: {:+}  {}: {:+}",
+                    FACE_NAMES[axis * 2],
+                    pair[0],
+                    FACE_NAMES[axis * 2 + 1],
+                    pair[1]
+                );
+            }
+            let _ = writeln!(out, "  net: {:+}", flows.iter().sum::<i64>());
+        }
+        None => {
+            let _ = writeln!(
+                out,
+                "last step flows: not recorded (enable flow recording, then step)"
+            );
+        }
+    }
+
+    match field.sources.get(&idx) {
+        Some(&rate) if rate > 0 => {
+            let _ = writeln!(out, "source: +{rate} per step (before diffusion)");
+        }
+        Some(&rate) => {
+            let _ = writeln!(
+                out,
+                "sink: {rate} per step (before diffusion, never below 1)"
+            );
+        }
+        None => {
+            let _ = writeln!(out, "source: none");
+        }
+    }
+
+    let coords = [x, y, z];
+    let extents = [field.width, field.height, field.depth];
+    let faces: Vec<String> = (0..6)
+        .filter(|&face| {
+            let axis = face / 2;
+            let edge = if face % 2 == 0 { 0 } else { extents[axis] - 1 };
+            coords[axis] == edge
+        })
+        .map(|face| {
+            let condition = match field.boundaries[face] {
+                _ if field.periodic[face / 2] => "periodic (wraps around)".to_string(),
+                Boundary::Reflective => "reflective (closed)".to_string(),
+                Boundary::Fixed(held) => format!("fixed at {held}"),
+                Boundary::Open => "open (mass leaves)".to_string(),
+            };
+            format!("{} {condition}", FACE_NAMES[face])
+        })
+        .collect();
+    if faces.is_empty() {
+        let _ = writeln!(out, "boundary: interior cell");
+    } else {
+        let _ = writeln!(out, "boundary: {}", faces.join(", "));
+    }
+
+    if let Some(phases) = &field.phases {
+        let phase = phases.phase[idx];
+        let _ = writeln!(out, "phase: {phase} of {}", phases.thresholds.len() + 1);
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_step};
+    use crate::automaton::grid::create_grid;
+
+    #[test]
+    fn test_trace_matches_plain_stepping() {
+        let mut field = create_field_1(6, 5, 4, 1);
+        let hot = field_index_of(&field, 2, 2, 2);
+        field.cells[hot] = 1_000_000;
+        let mut expected = field.clone();
+
+        let mut out = [CellTrace::default(); 3];
+        assert!(field_trace_cell(&mut field, 3, 2, 2, &mut out));
+        for _ in 0..3 {
+            field_step(&mut expected);
+        }
+        assert_eq!(field.cells, expected.cells);
+        assert_eq!(field.generation, 3);
+
+        for (i, record) in out.iter().enumerate() {
+            assert_eq!(record.generation, i as u64);
+            let net: i64 = record.flows.iter().sum();
+            assert_eq!(record.value as i64 + net, record.next_value as i64);
+        }
+        // First step: heat arrives from the hot cell on the -x side only
+        assert!(out[0].flows[FACE_NEG_X] > 0);
+        assert_eq!(out[0].flows[FACE_POS_X], 0);
+        assert_eq!(out[1].value, out[0].next_value);
+    }
+
+    #[test]
+    fn test_trace_ca_cell() {
+        let mut state = State::default();
+        create_grid(&mut state, 8, 8, 8);
+        // Cross: the center has 4 neighbors and survives under B4/S4
+        for (x, y) in [(4, 4), (3, 4), (5, 4), (4, 3), (4, 5)] {
+            let idx = index_of(&state, x, y, 4);
+            state.cells[idx] = 1;
+        }
+        let mut out = [CellTrace::default(); 1];
+        assert!(trace_cell(&mut state, 4, 4, 4, &mut out));
+        assert_eq!(out[0].neighbors, 4);
+        assert_eq!((out[0].value, out[0].next_value), (1, 1));
+        assert_eq!(state.generation, 1);
+
+        assert!(!trace_cell(&mut state, 8, 0, 0, &mut out));
+        assert_eq!(state.generation, 1);
+    }
+
+    #[test]
+    fn test_explain_matches_trace() {
+        let mut field = create_field_1(6, 5, 4, 1);
+        let hot = field_index_of(&field, 2, 2, 2);
+        field.cells[hot] = 1_000_000;
+        field.sources.insert(field_index_of(&field, 3, 2, 0), -40);
+        field.boundaries[FACE_NEG_Z] = Boundary::Fixed(500);
+        let mut traced = field.clone();
+        let mut out = [CellTrace::default(); 1];
+        field_trace_cell(&mut traced, 3, 2, 0, &mut out);
+
+        let text = field_explain(&field, 3, 2, 0).unwrap();
+        assert!(text.contains("not recorded"));
+        field.flow_record = Some(Vec::new());
+        field_step(&mut field);
+        let text = field_explain(&field, 3, 2, 0).unwrap();
+        assert!(text.contains(&format!("value: {}\n", out[0].next_value)));
+        let flows = out[0].flows;
+        assert!(text.contains(&format!("-x: {:+}  +x: {:+}", flows[0], flows[1])));
+        assert!(text.contains(&format!("net: {:+}", flows.iter().sum::<i64>())));
+        assert!(text.contains("sink: -40"));
+        assert!(text.contains("boundary: -z fixed at 500\n"));
+        assert_eq!(field_explain(&field, 6, 0, 0), None);
+    }
+}
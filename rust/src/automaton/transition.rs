@@ -0,0 +1,203 @@
+//! Multi-state automata driven by an arbitrary transition table.
+//!
+//! Birth/survival masks only describe two-state rules. A transition table maps
+//! every (current state, neighbor count) pair to the next state instead, which
+//! covers Generations-style rules (Brian's Brain, decaying trails), excitable
+//! media and other rules with refractory states.
+//!
+//! A table of `n * 27` bytes describes states `0..n`: entry `s * 27 + k` is the
+//! next state of a cell in state `s` with `k` of its 26 Moore neighbors in
+//! state 1. Only state 1 counts as a neighbor; higher states are refractory. A
+//! cell in a state the table does not cover (e.g. written before a smaller
+//! table was loaded) becomes 0.
+//!
+//! A grid with a table steps through it instead of its birth/survival rule,
+//! and its species layer is not stepped. State 0 is dead and every other
+//! state alive as far as ages and the write queue are concerned; the
+//! protection mask vetoes any increase of a protected cell's state.
+
+use super::grid::{in_bounds, index_of};
+use super::wireworld;
+use crate::state::{State, StepMode};
+
+/// Entries per state: neighbor counts 0..=26.
+pub const NEIGHBOR_COUNTS: usize = 27;
+
+/// Next state per (current state, neighbor count).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransitionTable {
+    next: Box<[u8]>,
+}
+
+impl TransitionTable {
+    /// A table from `n * 27` entries (see the module docs). None if the length
+    /// is not a positive multiple of 27, describes more than 256 states, or an
+    /// entry names a state outside `0..n`.
+    pub fn new(next: &[u8]) -> Option<Self> {
+        let states = next.len() / NEIGHBOR_COUNTS;
+        if next.is_empty() || !next.len().is_multiple_of(NEIGHBOR_COUNTS) || states > 256 {
+            return None;
+        }
+        if next.iter().any(|&s| s as usize >= states) {
+            return None;
+        }
+        Some(TransitionTable { next: next.into() })
+    }
+
+    /// Number of states the table describes.
+    pub fn states(&self) -> usize {
+        self.next.len() / NEIGHBOR_COUNTS
+    }
+
+    /// Next state of a cell in state `current` with `neighbors` firing
+    /// neighbors (0 for a state the table does not cover).
+    pub fn next_state(&self, current: u8, neighbors: u8) -> u8 {
+        let entry = current as usize * NEIGHBOR_COUNTS + neighbors as usize;
+        self.next.get(entry).copied().unwrap_or(0)
+    }
+}
+
+/// Set the cell at (x, y, z) to `value`. Returns false (nothing changes) for
+/// coordinates outside the grid, or if `value` is not a state of the grid's
+/// transition table or WireWorld mode (always false for a two-state grid).
+pub fn set_cell_state(state: &mut State, x: i16, y: i16, z: i16, value: u8) -> bool {
+    let states = match (state.mode, &state.transitions) {
+        (StepMode::WireWorld, _) => wireworld::STATES,
+        (StepMode::Rule, Some(table)) => table.states(),
+        (StepMode::Rule, None) => return false,
+    };
+    if !in_bounds(state, x, y, z) || value as usize >= states {
+        return false;
+    }
+    let idx = index_of(state, x, y, z);
+    state.cells[idx] = value;
+    true
+}
+
+/// Cells of the next generation under `table`.
+pub fn next_generation_table(state: &State, table: &TransitionTable) -> Vec<u8> {
+    let mut next_cells = vec![0; state.cells.len()];
+    for z in 0..state.depth {
+        for y in 0..state.height {
+            for x in 0..state.width {
+                let idx = index_of(state, x, y, z);
+                next_cells[idx] = next_cell_table(state, table, x, y, z);
+            }
+        }
+    }
+    next_cells
+}
+
+/// Next state of the cell at (x, y, z) under `table`.
+#[inline]
+pub(crate) fn next_cell_table(
+    state: &State,
+    table: &TransitionTable,
+    x: i16,
+    y: i16,
+    z: i16,
+) -> u8 {
+    let neighbors = count_in_state(state, x, y, z, 1);
+    table.next_state(state.cells[index_of(state, x, y, z)], neighbors)
+}
+
+/// Moore neighbors of (x, y, z) in state `value`.
+pub(crate) fn count_in_state(state: &State, x: i16, y: i16, z: i16, value: u8) -> u8 {
+    let mut count = 0;
+    for dz in -1..=1 {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if (dx, dy, dz) == (0, 0, 0) {
+                    continue;
+                }
+                let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+                if in_bounds(state, nx, ny, nz) && state.cells[index_of(state, nx, ny, nz)] == value
+                {
+                    count += 1;
+                }
+            }
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::grid::create_grid;
+    use crate::automaton::stepping::step_automaton;
+    use crate::state::Rule;
+
+    /// Two-state table equivalent to `rule`.
+    fn table_of(rule: Rule) -> Vec<u8> {
+        [rule.birth, rule.survival]
+            .iter()
+            .flat_map(|mask| (0..NEIGHBOR_COUNTS).map(move |k| (mask >> k & 1) as u8))
+            .collect()
+    }
+
+    #[test]
+    fn test_rejects_malformed_tables() {
+        assert!(TransitionTable::new(&[]).is_none());
+        assert!(TransitionTable::new(&[0; 28]).is_none());
+        assert!(TransitionTable::new(&[0; 27 * 257]).is_none());
+        let mut table = vec![0; 54];
+        table[5] = 2;
+        assert!(TransitionTable::new(&table).is_none());
+        table[5] = 1;
+        let table = TransitionTable::new(&table).unwrap();
+        assert_eq!(table.states(), 2);
+        assert_eq!(table.next_state(0, 5), 1);
+        assert_eq!(table.next_state(7, 5), 0);
+    }
+
+    #[test]
+    fn test_two_state_table_matches_rule() {
+        let mut plain = State::default();
+        create_grid(&mut plain, 8, 8, 8);
+        for (x, y) in [(4, 4), (3, 4), (5, 4), (4, 3), (4, 5)] {
+            let idx = index_of(&plain, x, y, 4);
+            plain.cells[idx] = 1;
+        }
+        let mut tabled = plain.clone();
+        tabled.transitions = TransitionTable::new(&table_of(Rule::B4S4));
+        // The rule is ignored once a table is set
+        tabled.rule = Rule {
+            birth: 0,
+            survival: 0,
+        };
+        for _ in 0..4 {
+            step_automaton(&mut plain);
+            step_automaton(&mut tabled);
+            assert_eq!(plain.cells, tabled.cells);
+        }
+    }
+
+    #[test]
+    fn test_refractory_states() {
+        let mut state = State::default();
+        create_grid(&mut state, 6, 6, 6);
+        // Brian's Brain style: dead -> firing on 2 firing neighbors, firing ->
+        // refractory, refractory -> dead
+        let mut table = vec![0; 3 * NEIGHBOR_COUNTS];
+        table[2] = 1;
+        table[NEIGHBOR_COUNTS..2 * NEIGHBOR_COUNTS].fill(2);
+        state.transitions = TransitionTable::new(&table);
+        assert!(set_cell_state(&mut state, 2, 2, 2, 1));
+        assert!(set_cell_state(&mut state, 3, 2, 2, 1));
+        assert!(!set_cell_state(&mut state, 2, 2, 2, 3));
+        assert!(!set_cell_state(&mut state, 6, 2, 2, 1));
+
+        step_automaton(&mut state);
+        let at = |state: &State, x, y, z| state.cells[index_of(state, x, y, z)];
+        assert_eq!(at(&state, 2, 2, 2), 2);
+        assert_eq!(at(&state, 2, 3, 2), 1);
+        // Refractory cells decay, firing ones turn refractory
+        step_automaton(&mut state);
+        assert_eq!(at(&state, 2, 2, 2), 0);
+        assert_eq!(at(&state, 2, 3, 2), 2);
+
+        state.transitions = None;
+        assert!(!set_cell_state(&mut state, 2, 2, 2, 1));
+    }
+}
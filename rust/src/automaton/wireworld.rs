@@ -0,0 +1,119 @@
+//! WireWorld: four-state cells for in-game logic circuits.
+//!
+//! Players lay out wire and inject electrons; each step moves every electron
+//! one cell along the wire:
+//!
+//! - `EMPTY` stays empty
+//! - `HEAD` (electron head) becomes `TAIL`
+//! - `TAIL` (electron tail) becomes `WIRE`
+//! - `WIRE` becomes `HEAD` if exactly 1 or 2 of its 26 Moore neighbors are
+//!   heads, otherwise stays wire
+//!
+//! The tail behind every head keeps electrons from flowing backwards, so
+//! diodes, clocks and logic gates can be built from wire alone.
+//!
+//! A grid in WireWorld mode ignores its rule, transition table and species
+//! layer. Nothing is ever built from an empty cell, so the protection mask
+//! has nothing to veto and is not applied; ages and the write queue treat
+//! every non-empty cell as alive.
+
+use super::grid::index_of;
+use super::transition::count_in_state;
+use crate::state::State;
+
+pub const EMPTY: u8 = 0;
+pub const WIRE: u8 = 1;
+pub const HEAD: u8 = 2;
+pub const TAIL: u8 = 3;
+
+/// Number of WireWorld states.
+pub const STATES: usize = 4;
+
+/// Cells of the next generation under the WireWorld rule. Values above `TAIL`
+/// become `EMPTY`.
+pub fn next_generation_wireworld(state: &State) -> Vec<u8> {
+    let mut next_cells = vec![EMPTY; state.cells.len()];
+    for z in 0..state.depth {
+        for y in 0..state.height {
+            for x in 0..state.width {
+                let idx = index_of(state, x, y, z);
+                next_cells[idx] = next_cell_wireworld(state, x, y, z);
+            }
+        }
+    }
+    next_cells
+}
+
+/// Next WireWorld state of the cell at (x, y, z).
+#[inline]
+pub(crate) fn next_cell_wireworld(state: &State, x: i16, y: i16, z: i16) -> u8 {
+    match state.cells[index_of(state, x, y, z)] {
+        HEAD => TAIL,
+        TAIL => WIRE,
+        WIRE if (1..=2).contains(&count_in_state(state, x, y, z, HEAD)) => HEAD,
+        WIRE => WIRE,
+        _ => EMPTY,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::grid::create_grid;
+    use crate::automaton::stepping::step_automaton;
+    use crate::automaton::transition::set_cell_state;
+    use crate::state::StepMode;
+
+    fn wire_row(state: &State) -> Vec<u8> {
+        (0..state.width)
+            .map(|x| state.cells[index_of(state, x, 1, 1)])
+            .collect()
+    }
+
+    #[test]
+    fn test_electron_travels_along_wire() {
+        let mut state = State::default();
+        create_grid(&mut state, 6, 3, 3);
+        assert!(!set_cell_state(&mut state, 0, 1, 1, WIRE));
+        state.mode = StepMode::WireWorld;
+        for x in 0..6 {
+            assert!(set_cell_state(&mut state, x, 1, 1, WIRE));
+        }
+        set_cell_state(&mut state, 0, 1, 1, TAIL);
+        set_cell_state(&mut state, 1, 1, 1, HEAD);
+        assert!(!set_cell_state(&mut state, 2, 1, 1, TAIL + 1));
+
+        step_automaton(&mut state);
+        assert_eq!(wire_row(&state), [WIRE, TAIL, HEAD, WIRE, WIRE, WIRE]);
+        step_automaton(&mut state);
+        assert_eq!(wire_row(&state), [WIRE, WIRE, TAIL, HEAD, WIRE, WIRE]);
+        for _ in 0..4 {
+            step_automaton(&mut state);
+        }
+        // The electron leaves the end of the wire
+        assert_eq!(wire_row(&state), [WIRE; 6]);
+        assert_eq!(state.generation, 6);
+    }
+
+    #[test]
+    fn test_three_heads_do_not_fire() {
+        let mut state = State::default();
+        create_grid(&mut state, 3, 3, 3);
+        state.mode = StepMode::WireWorld;
+        set_cell_state(&mut state, 1, 1, 1, WIRE);
+        for x in 0..3 {
+            set_cell_state(&mut state, x, 0, 1, HEAD);
+        }
+        let idx = index_of(&state, 1, 1, 1);
+        step_automaton(&mut state);
+        assert_eq!(state.cells[idx], WIRE);
+
+        // Two heads fire it
+        set_cell_state(&mut state, 0, 0, 1, HEAD);
+        set_cell_state(&mut state, 1, 0, 1, HEAD);
+        step_automaton(&mut state);
+        assert_eq!(state.cells[idx], HEAD);
+        // Empty cells never change
+        assert_eq!(state.cells[index_of(&state, 2, 2, 2)], EMPTY);
+    }
+}
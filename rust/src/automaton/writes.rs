@@ -0,0 +1,124 @@
+//! Deferred world writes: node changes produced by steps, applied a few per tick.
+//!
+//! Applying a large generation to the Luanti map in one server tick stalls it.
+//! With a write queue enabled, every step appends the births and deaths it
+//! committed, and Lua drains up to K of them per tick, spreading the cost over
+//! as many ticks as it takes.
+//!
+//! The queue is bounded. Once full, further changes are rejected and counted in
+//! `overflowed` rather than growing memory without limit; a caller that sees
+//! the count rise has lost track of the grid and should resynchronize the map
+//! from a full extraction. Changes are queued in commit order, so applying them
+//! in order leaves the map matching the grid even when a cell flips twice
+//! before it is drained.
+
+use std::collections::VecDeque;
+
+use super::stepping::CellChange;
+
+/// Bounded FIFO of committed cell changes awaiting a world write.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteQueue {
+    entries: VecDeque<CellChange>,
+    capacity: usize,
+    /// Changes rejected because the queue was full.
+    pub overflowed: u64,
+}
+
+impl WriteQueue {
+    /// An empty queue holding at most `capacity` changes.
+    pub fn new(capacity: usize) -> Self {
+        WriteQueue {
+            capacity,
+            ..Self::default()
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Queue a change, or count it as overflowed if the queue is full.
+    pub fn push(&mut self, change: CellChange) {
+        if self.entries.len() >= self.capacity {
+            self.overflowed += 1;
+        } else {
+            self.entries.push_back(change);
+        }
+    }
+
+    /// Take up to `max` of the oldest changes.
+    pub fn drain(&mut self, max: usize) -> impl Iterator<Item = CellChange> + '_ {
+        let count = max.min(self.entries.len());
+        self.entries.drain(..count)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Queue every cell that differs between `before` and `after` (grids of
+    /// dimensions `dims`, z,y,x order), in cell index order.
+    pub fn record_step(&mut self, dims: [i16; 3], before: &[u8], after: &[u8]) {
+        let (w, h) = (dims[0] as usize, dims[1] as usize);
+        for (idx, (&old, &new)) in before.iter().zip(after).enumerate() {
+            if old != new {
+                self.push(CellChange {
+                    x: (idx % w) as i16,
+                    y: (idx / w % h) as i16,
+                    z: (idx / (w * h)) as i16,
+                    alive: new != 0,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::grid::{create_grid, index_of};
+    use crate::automaton::stepping::{step_automaton, step_preview};
+    use crate::state::State;
+
+    #[test]
+    fn test_steps_queue_their_changes() {
+        let mut state = State::default();
+        create_grid(&mut state, 8, 8, 8);
+        for (x, y) in [(4, 4), (3, 4), (5, 4), (4, 3), (4, 5)] {
+            let idx = index_of(&state, x, y, 4);
+            state.cells[idx] = 1;
+        }
+        state.write_queue = Some(WriteQueue::new(1024));
+        let expected = step_preview(&state);
+        step_automaton(&mut state);
+
+        let queue = state.write_queue.as_mut().unwrap();
+        assert_eq!(queue.len(), expected.len());
+        let first: Vec<_> = queue.drain(3).collect();
+        assert_eq!(first, expected[..3]);
+        let rest: Vec<_> = queue.drain(usize::MAX).collect();
+        assert_eq!(rest, expected[3..]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_full_queue_counts_overflow() {
+        let mut queue = WriteQueue::new(2);
+        let change = |x| CellChange {
+            x,
+            y: 0,
+            z: 0,
+            alive: true,
+        };
+        for x in 0..5 {
+            queue.push(change(x));
+        }
+        assert_eq!((queue.len(), queue.overflowed), (2, 3));
+        assert_eq!(queue.drain(8).collect::<Vec<_>>(), [change(0), change(1)]);
+    }
+}
@@ -0,0 +1,78 @@
+//! FFI interface for quiescent tile skipping (see `automaton::activity`).
+
+use super::validate::{ctrl_mut, ctrl_ref};
+use crate::automaton::incremental::StepController;
+
+/// Makes steps skip tiles at equilibrium (nonzero) or step every tile (0).
+/// Results are identical either way; a field that has settled costs next to
+/// nothing per generation. Skipping pauses while delta overrides, contracts,
+/// phase changes or decay are configured.
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// 0 on success, -1 if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_set_skip_quiet(ctrl: *mut StepController, enabled: u8) -> i32 {
+    let Some(ctrl) = ctrl_mut(ctrl) else {
+        return -1;
+    };
+    ctrl.set_skip_quiet(enabled != 0);
+    0
+}
+
+/// Gets the number of tiles the last step begun skipped as quiet.
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// The count, or 0 if skipping is off or null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_skipped_tiles(ctrl: *const StepController) -> u64 {
+    ctrl_ref(ctrl)
+        .and_then(|ctrl| ctrl.activity.as_ref())
+        .map_or(0, |activity| activity.last_skipped as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::incremental::{
+        va_create_step_controller, va_destroy_step_controller, va_sc_field_get, va_sc_field_set,
+        va_sc_step_blocking,
+    };
+    use std::ptr;
+
+    #[test]
+    fn test_skip_quiet_via_ffi() {
+        unsafe {
+            let (plain, skipping) = (
+                va_create_step_controller(64, 32, 32, 2, 1, 0),
+                va_create_step_controller(64, 32, 32, 2, 1, 0),
+            );
+            assert_eq!(va_sc_set_skip_quiet(skipping, 1), 0);
+            va_sc_step_blocking(skipping);
+            assert_eq!(va_sc_skipped_tiles(skipping), 0);
+            va_sc_step_blocking(skipping);
+            assert_eq!(va_sc_skipped_tiles(skipping), 16);
+
+            va_sc_step_blocking(plain);
+            va_sc_step_blocking(plain);
+            for ctrl in [plain, skipping] {
+                va_sc_field_set(ctrl, 2, 2, 2, 1_000_000);
+                va_sc_step_blocking(ctrl);
+            }
+            // The corner tile and its 7 neighbors
+            assert_eq!(va_sc_skipped_tiles(skipping), 8);
+            assert_eq!((*skipping).field.cells, (*plain).field.cells);
+            assert!(va_sc_field_get(skipping, 3, 2, 2) > 1);
+
+            assert_eq!(va_sc_set_skip_quiet(ptr::null_mut(), 1), -1);
+            assert_eq!(va_sc_skipped_tiles(ptr::null()), 0);
+            va_destroy_step_controller(plain);
+            va_destroy_step_controller(skipping);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! FFI interface for adaptive fields (coarse blocks in quiet regions, fine
+//! cells in active ones).
+
+use super::validate::{adaptive_mut, adaptive_ref};
+use crate::automaton::adaptive::AdaptiveField;
+use crate::automaton::field::RoundingMode;
+
+/// Create an adaptive field, every cell at 1 and every block coarse.
+///
+/// # Returns
+/// A new AdaptiveField, or NULL unless each dimension is a positive multiple
+/// of 4 (the block edge).
+#[no_mangle]
+pub extern "C" fn va_create_adaptive_field(
+    width: i16,
+    height: i16,
+    depth: i16,
+    diffusion_rate: u8,
+) -> *mut AdaptiveField {
+    match AdaptiveField::new(width, height, depth, diffusion_rate) {
+        Some(field) => Box::into_raw(Box::new(field)),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Destroy an adaptive field. Safe to call with null pointer (no-op).
+///
+/// # Safety
+/// `field` must be null or a pointer from `va_create_adaptive_field`, not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn va_destroy_adaptive_field(field: *mut AdaptiveField) {
+    if !field.is_null() {
+        drop(Box::from_raw(field));
+    }
+}
+
+/// Set a cell, refining its block first.
+///
+/// # Safety
+/// `field` must be null or a valid AdaptiveField pointer.
+///
+/// # Returns
+/// 0 on success, 1 if out of bounds or the block's total does not fit u32
+/// cells, -1 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_adaptive_set(
+    field: *mut AdaptiveField,
+    x: i16,
+    y: i16,
+    z: i16,
+    value: u32,
+) -> i32 {
+    match adaptive_mut(field) {
+        Some(field) => !field.set(x, y, z, value) as i32,
+        None => -1,
+    }
+}
+
+/// Get a cell. A cell of a coarse block reads as its share of the block total.
+///
+/// # Safety
+/// `field` must be null or a valid AdaptiveField pointer.
+///
+/// # Returns
+/// The value, or 0 for a null pointer or out-of-bounds coordinates.
+#[no_mangle]
+pub unsafe extern "C" fn va_adaptive_get(
+    field: *const AdaptiveField,
+    x: i16,
+    y: i16,
+    z: i16,
+) -> u32 {
+    adaptive_ref(field)
+        .and_then(|field| field.get(x, y, z))
+        .unwrap_or(0)
+}
+
+/// Step the field one generation, then refine and coarsen blocks if a
+/// threshold is set.
+///
+/// # Safety
+/// `field` must be null or a valid AdaptiveField pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_adaptive_step(field: *mut AdaptiveField) {
+    if let Some(field) = adaptive_mut(field) {
+        field.step();
+    }
+}
+
+/// Store the block holding (x, y, z) at full resolution.
+///
+/// # Safety
+/// `field` must be null or a valid AdaptiveField pointer.
+///
+/// # Returns
+/// 0 on success, 1 if out of bounds or the block's total does not fit u32
+/// cells, -1 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_adaptive_refine(
+    field: *mut AdaptiveField,
+    x: i16,
+    y: i16,
+    z: i16,
+) -> i32 {
+    match adaptive_mut(field) {
+        Some(field) => !field.refine(x, y, z) as i32,
+        None => -1,
+    }
+}
+
+/// Store the block holding (x, y, z) as its total.
+///
+/// # Safety
+/// `field` must be null or a valid AdaptiveField pointer.
+///
+/// # Returns
+/// 0 on success, 1 if out of bounds, -1 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_adaptive_coarsen(
+    field: *mut AdaptiveField,
+    x: i16,
+    y: i16,
+    z: i16,
+) -> i32 {
+    match adaptive_mut(field) {
+        Some(field) => !field.coarsen(x, y, z) as i32,
+        None => -1,
+    }
+}
+
+/// Set the refine/coarsen threshold applied after every step: blocks differing
+/// from a neighbor by more than `threshold` per cell refine, blocks within half
+/// of it coarsen. 0 leaves the resolution to `va_adaptive_refine`/`coarsen`.
+///
+/// # Safety
+/// `field` must be null or a valid AdaptiveField pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_adaptive_set_threshold(field: *mut AdaptiveField, threshold: u32) {
+    if let Some(field) = adaptive_mut(field) {
+        field.threshold = threshold;
+    }
+}
+
+/// Set the rounding mode (same ids as `va_field_set_rounding`).
+///
+/// # Safety
+/// `field` must be null or a valid AdaptiveField pointer.
+///
+/// # Returns
+/// 0 on success, 1 for an unknown mode, -1 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_adaptive_set_rounding(field: *mut AdaptiveField, mode: u8) -> i32 {
+    let Some(field) = adaptive_mut(field) else {
+        return -1;
+    };
+    match RoundingMode::from_u8(mode) {
+        Some(rounding) => {
+            field.rounding = rounding;
+            0
+        }
+        None => 1,
+    }
+}
+
+/// Sum of every cell (conserved by stepping, refining and coarsening).
+///
+/// # Safety
+/// `field` must be null or a valid AdaptiveField pointer.
+///
+/// # Returns
+/// The total, or 0 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_adaptive_total(field: *const AdaptiveField) -> u64 {
+    adaptive_ref(field).map_or(0, AdaptiveField::total)
+}
+
+/// Number of blocks stored at full resolution.
+///
+/// # Safety
+/// `field` must be null or a valid AdaptiveField pointer.
+///
+/// # Returns
+/// The count, or 0 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_adaptive_refined_blocks(field: *const AdaptiveField) -> u32 {
+    adaptive_ref(field).map_or(0, |field| field.refined_blocks() as u32)
+}
+
+/// Get the field's generation.
+///
+/// # Safety
+/// `field` must be null or a valid AdaptiveField pointer.
+///
+/// # Returns
+/// The generation, or 0 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_adaptive_get_generation(field: *const AdaptiveField) -> u64 {
+    adaptive_ref(field).map_or(0, |field| field.generation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn test_adaptive_field_via_ffi() {
+        assert!(va_create_adaptive_field(10, 8, 8, 2).is_null());
+        let field = va_create_adaptive_field(16, 8, 8, 2);
+        assert!(!field.is_null());
+        unsafe {
+            assert_eq!(va_adaptive_total(field), 16 * 8 * 8);
+            assert_eq!(va_adaptive_set(field, 2, 2, 2, 500_000), 0);
+            assert_eq!(va_adaptive_set(field, 16, 0, 0, 1), 1);
+            assert_eq!(va_adaptive_get(field, 2, 2, 2), 500_000);
+            assert_eq!(va_adaptive_refined_blocks(field), 1);
+            assert_eq!(va_adaptive_set_rounding(field, 9), 1);
+            assert_eq!(va_adaptive_set_rounding(field, 2), 0);
+
+            let total = va_adaptive_total(field);
+            va_adaptive_set_threshold(field, 100);
+            for _ in 0..10 {
+                va_adaptive_step(field);
+            }
+            assert_eq!(va_adaptive_total(field), total);
+            assert_eq!(va_adaptive_get_generation(field), 10);
+            assert!(va_adaptive_refined_blocks(field) > 1);
+
+            assert_eq!(va_adaptive_coarsen(field, 2, 2, 2), 0);
+            assert_eq!(va_adaptive_refine(field, 2, 2, 2), 0);
+            assert_eq!(va_adaptive_refine(field, -1, 0, 0), 1);
+            assert_eq!(va_adaptive_total(field), total);
+
+            assert_eq!(va_adaptive_set(ptr::null_mut(), 0, 0, 0, 1), -1);
+            assert_eq!(va_adaptive_get(ptr::null(), 0, 0, 0), 0);
+            va_adaptive_step(ptr::null_mut());
+            va_destroy_adaptive_field(field);
+            va_destroy_adaptive_field(ptr::null_mut());
+        }
+    }
+}
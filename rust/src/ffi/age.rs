@@ -0,0 +1,127 @@
+//! FFI interface for per-cell age tracking (see `automaton::age`).
+
+use super::validate::{buf_mut, region_volume, state_mut, state_ref};
+use crate::automaton::age::{cell_age, extract_age_region, CellAge};
+use crate::state::State;
+
+/// Enables (`enabled` nonzero) or disables age tracking. Enabling resets every
+/// age to 0; `max_age` kills cells that reached it on the next step (0 = no
+/// limit). Calling it again while enabled only changes the limit.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_set_age_tracking(ptr: *mut State, enabled: u8, max_age: u16) -> i32 {
+    let Some(state) = state_mut(ptr) else {
+        return 1;
+    };
+    if enabled == 0 {
+        state.age = None;
+    } else {
+        let len = state.cells.len();
+        state
+            .age
+            .get_or_insert_with(|| CellAge::new(len, 0))
+            .max_age = max_age;
+    }
+    0
+}
+
+/// Gets the number of generations a cell has survived.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// The age, or 0 (dead, out of bounds, no age tracking, or null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_get_cell_age(ptr: *const State, x: i16, y: i16, z: i16) -> u16 {
+    state_ref(ptr).map_or(0, |state| cell_age(state, x, y, z))
+}
+
+/// Extracts the ages of a rectangular region into a flat u16 buffer, in the
+/// layout of `va_extract_region` (z,y,x order, clamped to the grid). Dead
+/// cells read 0.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `out_buf` must point to at least `buf_len` writable u16 values, or be null
+///
+/// # Returns
+/// Number of cells written, or 0 on error (null pointer, no age tracking,
+/// inverted region, or `buf_len` smaller than the clamped region). `buf_len`
+/// counts u16 elements, not bytes.
+#[no_mangle]
+pub unsafe extern "C" fn va_extract_age_region(
+    ptr: *const State,
+    out_buf: *mut u16,
+    buf_len: u64,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+) -> u64 {
+    let Some(state) = state_ref(ptr) else {
+        return 0;
+    };
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    if region_volume(min, max).is_none() {
+        return 0;
+    }
+    match buf_mut(out_buf, buf_len) {
+        Some(out) => extract_age_region(state, out, min, max),
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::grid::{va_create_grid, va_set_cell, va_step};
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use crate::ffi::snapshot::va_set_rule;
+    use crate::state::Rule;
+    use std::ptr;
+
+    #[test]
+    fn test_cell_age_via_ffi() {
+        unsafe {
+            let state = va_create();
+            va_create_grid(state, 4, 4, 4);
+            // Nothing is born, every live cell survives
+            assert_eq!(va_set_rule(state, 0, Rule::MASK), 0);
+            va_set_cell(state, 1, 1, 1, 1);
+            va_set_cell(state, 2, 1, 1, 1);
+            assert_eq!(va_set_age_tracking(state, 1, 0), 0);
+            va_step(state);
+            va_step(state);
+            assert_eq!(va_get_cell_age(state, 1, 1, 1), 2);
+
+            let mut out = [0u16; 4];
+            assert_eq!(
+                va_extract_age_region(state, out.as_mut_ptr(), 4, 0, 1, 1, 4, 2, 2),
+                4
+            );
+            assert_eq!(out, [0, 2, 2, 0]);
+
+            // The limit applies from the next step
+            assert_eq!(va_set_age_tracking(state, 1, 2), 0);
+            va_step(state);
+            assert_eq!(va_get_cell_age(state, 1, 1, 1), 0);
+
+            assert_eq!(va_set_age_tracking(state, 0, 0), 0);
+            assert_eq!(
+                va_extract_age_region(state, out.as_mut_ptr(), 4, 0, 1, 1, 4, 2, 2),
+                0
+            );
+            assert_eq!(va_set_age_tracking(ptr::null_mut(), 1, 0), 1);
+            assert_eq!(va_get_cell_age(ptr::null(), 1, 1, 1), 0);
+            va_destroy(state);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! FFI interface for the flow overflow audit.
+
+use super::validate::{ctrl_ref, field_mut, write_opt};
+use crate::automaton::audit::{audit_controller, field_step_checked, FlowOverflow};
+use crate::automaton::field::Field;
+use crate::automaton::incremental::StepController;
+
+/// i64 slots in an overflow report.
+pub const OVERFLOW_REPORT_LEN: usize = 8;
+
+/// Write `result` as a report and return its status code.
+///
+/// Report layout: [x, y, z, axis, value_a, value_b, conductivity, dt] for a
+/// pair overflow; [-1, -1, -1, -1, 0, 0, diffusion_rate, 0] for a divisor
+/// overflow.
+unsafe fn report(result: Result<(), FlowOverflow>, out_report: *mut i64) -> i32 {
+    let (code, values) = match result {
+        Ok(()) => return 0,
+        Err(FlowOverflow::Pair {
+            a,
+            axis,
+            value_a,
+            value_b,
+            conductivity,
+            dt,
+        }) => (
+            1,
+            [
+                a[0] as i64,
+                a[1] as i64,
+                a[2] as i64,
+                axis as i64,
+                value_a as i64,
+                value_b as i64,
+                conductivity,
+                dt,
+            ],
+        ),
+        Err(FlowOverflow::Divisor { diffusion_rate }) => {
+            (2, [-1, -1, -1, -1, 0, 0, diffusion_rate as i64, 0])
+        }
+    };
+    write_opt(out_report as *mut [i64; OVERFLOW_REPORT_LEN], values);
+    code
+}
+
+/// Steps the field like `va_field_step`, but first checks every flow
+/// computation for i64 overflow. Slower; intended for debugging extreme
+/// conductivity/value combinations.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `out_report` must point to 8 writable i64 values, or be null (skipped)
+///
+/// # Returns
+/// 0 if the field was stepped, 1 if a pair would overflow, 2 if the
+/// diffusion_rate overflows the divisor (the field is not stepped and the
+/// first offender is written to `out_report`), -1 for a null field.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_step_checked(field: *mut Field, out_report: *mut i64) -> i32 {
+    let Some(field) = field_mut(field) else {
+        return -1;
+    };
+    report(field_step_checked(field), out_report)
+}
+
+/// Checks whether any flow of the controller's next step could overflow, at
+/// the kernel's conductivity and the largest zone cadence. Does not step.
+///
+/// # Safety
+/// - `ctrl` must be a valid pointer to a StepController, or null
+/// - `out_report` must point to 8 writable i64 values, or be null (skipped)
+///
+/// # Returns
+/// 0 if safe, otherwise as `va_field_step_checked`.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_audit_overflow(
+    ctrl: *const StepController,
+    out_report: *mut i64,
+) -> i32 {
+    let Some(ctrl) = ctrl_ref(ctrl) else {
+        return -1;
+    };
+    report(audit_controller(ctrl), out_report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::create_field_1;
+    use std::ptr;
+
+    #[test]
+    fn test_checked_step_via_ffi() {
+        let mut field = create_field_1(3, 3, 3, 1);
+        let mut out = [0i64; OVERFLOW_REPORT_LEN];
+        unsafe {
+            assert_eq!(va_field_step_checked(&mut field, out.as_mut_ptr()), 0);
+            assert_eq!(field.generation, 1);
+
+            field.diffusion_rate = 60;
+            assert_eq!(va_field_step_checked(&mut field, out.as_mut_ptr()), 2);
+            assert_eq!(out, [-1, -1, -1, -1, 0, 0, 60, 0]);
+            assert_eq!(field.generation, 1);
+
+            assert_eq!(va_field_step_checked(ptr::null_mut(), ptr::null_mut()), -1);
+            assert_eq!(va_sc_audit_overflow(ptr::null(), ptr::null_mut()), -1);
+        }
+    }
+}
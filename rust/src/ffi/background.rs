@@ -0,0 +1,205 @@
+//! FFI interface for background stepping (see `automaton::background`).
+//!
+//! `va_sc_start_async` hands a StepController over to a worker thread; the
+//! caller then reads the latest completed generation through the returned
+//! AsyncStepper, and takes the controller back with `va_async_stop`.
+
+use super::validate::{buf_mut, region_volume, take_handle, write_opt};
+use crate::automaton::background::AsyncStepper;
+use crate::automaton::incremental::StepController;
+use crate::automaton::{field_extract_region, field_get};
+
+/// Starts stepping a controller on a worker thread, at most `steps_per_second`
+/// generations a second (0 = unlimited). A step already begun is finished
+/// first. The controller pointer must no longer be used; get it back with
+/// `va_async_stop`.
+///
+/// # Safety
+/// `ctrl` must be null or a pointer from `va_create_step_controller*` owned by
+/// the caller.
+///
+/// # Returns
+/// The stepper (free it with `va_async_destroy`), or null for a null controller.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_start_async(
+    ctrl: *mut StepController,
+    steps_per_second: u32,
+) -> *mut AsyncStepper {
+    let Some(ctrl) = take_handle(ctrl) else {
+        return std::ptr::null_mut();
+    };
+    Box::into_raw(Box::new(AsyncStepper::start(ctrl, steps_per_second)))
+}
+
+/// Changes the rate limit of a running stepper (0 = unlimited).
+///
+/// # Safety
+/// `stepper` must be null or a valid pointer from `va_sc_start_async`.
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_async_set_rate(
+    stepper: *const AsyncStepper,
+    steps_per_second: u32,
+) -> i32 {
+    match stepper.as_ref() {
+        Some(stepper) => {
+            stepper.set_rate(steps_per_second);
+            0
+        }
+        None => 1,
+    }
+}
+
+/// Gets the generation of the latest completed step.
+///
+/// # Safety
+/// `stepper` must be null or a valid pointer from `va_sc_start_async`.
+///
+/// # Returns
+/// The generation, or 0 for a null stepper.
+#[no_mangle]
+pub unsafe extern "C" fn va_async_generation(stepper: *const AsyncStepper) -> u64 {
+    stepper.as_ref().map_or(0, AsyncStepper::generation)
+}
+
+/// Gets a cell of the latest completed generation (as `va_field_get`).
+///
+/// # Safety
+/// `stepper` must be null or a valid pointer from `va_sc_start_async`.
+///
+/// # Returns
+/// The value, or 0 if out of bounds or `stepper` is null.
+#[no_mangle]
+pub unsafe extern "C" fn va_async_get(stepper: *const AsyncStepper, x: i16, y: i16, z: i16) -> u32 {
+    let Some(stepper) = stepper.as_ref() else {
+        return 0;
+    };
+    stepper.read(|field| field_get(field, x, y, z).map_or(0, |nz| nz.get()))
+}
+
+/// Extracts a region of the latest completed generation, with the layout and
+/// clamping of `va_field_extract_region`. All cells come from one generation.
+///
+/// # Safety
+/// - `stepper` must be null or a valid pointer from `va_sc_start_async`
+/// - `out_buf` must point to at least `buf_len` writable u32 values, or be null
+/// - `out_generation` must be a valid writable pointer, or null (skipped)
+///
+/// # Returns
+/// Number of cells written, or 0 on error (as `va_field_extract_region`).
+#[no_mangle]
+pub unsafe extern "C" fn va_async_extract_region(
+    stepper: *const AsyncStepper,
+    out_buf: *mut u32,
+    buf_len: u64,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+    out_generation: *mut u64,
+) -> u64 {
+    let Some(stepper) = stepper.as_ref() else {
+        return 0;
+    };
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    stepper.read(|field| {
+        write_opt(out_generation, field.generation);
+        if region_volume(min, max).is_none() {
+            return 0;
+        }
+        match buf_mut(out_buf, buf_len) {
+            Some(out) => field_extract_region(field, out, min, max),
+            None => 0,
+        }
+    })
+}
+
+/// Stops the worker after its current step and gives the controller back,
+/// with every generation stepped so far. Frees the stepper.
+///
+/// # Safety
+/// `stepper` must be null or a pointer from `va_sc_start_async`, not used
+/// afterwards.
+///
+/// # Returns
+/// The controller (free it with `va_destroy_step_controller`), or null if
+/// `stepper` is null or the worker failed.
+#[no_mangle]
+pub unsafe extern "C" fn va_async_stop(stepper: *mut AsyncStepper) -> *mut StepController {
+    if stepper.is_null() {
+        return std::ptr::null_mut();
+    }
+    Box::from_raw(stepper)
+        .stop()
+        .map_or(std::ptr::null_mut(), |ctrl| Box::into_raw(Box::new(ctrl)))
+}
+
+/// Stops the worker and frees the stepper and its controller. Safe to call
+/// with null pointer (no-op).
+///
+/// # Safety
+/// `stepper` must be null or a pointer from `va_sc_start_async`, not used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn va_async_destroy(stepper: *mut AsyncStepper) {
+    if !stepper.is_null() {
+        drop(Box::from_raw(stepper));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::incremental::{
+        va_create_step_controller, va_destroy_step_controller, va_sc_field_get_generation,
+        va_sc_field_set,
+    };
+    use std::ptr;
+
+    #[test]
+    fn test_async_via_ffi() {
+        unsafe {
+            let ctrl = va_create_step_controller(8, 8, 8, 2, 1, 0);
+            va_sc_field_set(ctrl, 4, 4, 4, 100_000);
+            let stepper = va_sc_start_async(ctrl, 0);
+            assert!(!stepper.is_null());
+            while va_async_generation(stepper) < 2 {
+                std::thread::yield_now();
+            }
+            let mut out = vec![0u32; 512];
+            let mut generation = 0;
+            let written = va_async_extract_region(
+                stepper,
+                out.as_mut_ptr(),
+                512,
+                0,
+                0,
+                0,
+                8,
+                8,
+                8,
+                &mut generation,
+            );
+            assert_eq!(written, 512);
+            assert!(generation >= 2);
+            // Mass is conserved in any single generation
+            assert_eq!(out.iter().map(|&v| v as u64).sum::<u64>(), 100_000 + 511);
+            assert_eq!(va_async_set_rate(stepper, 1000), 0);
+
+            let ctrl = va_async_stop(stepper);
+            assert!(va_sc_field_get_generation(ctrl) >= generation);
+            va_destroy_step_controller(ctrl);
+
+            assert!(va_sc_start_async(ptr::null_mut(), 0).is_null());
+            assert!(va_async_stop(ptr::null_mut()).is_null());
+            assert_eq!(va_async_generation(ptr::null()), 0);
+            assert_eq!(va_async_get(ptr::null(), 0, 0, 0), 0);
+            assert_eq!(va_async_set_rate(ptr::null(), 0), 1);
+            va_async_destroy(ptr::null_mut());
+        }
+    }
+}
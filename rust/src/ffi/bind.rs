@@ -0,0 +1,129 @@
+//! FFI interface for grid-field bindings (see `automaton::bind`).
+//!
+//! A binding refers to a State and a Field the caller still owns: both must
+//! stay alive until `va_unbind`, and can still be read and written through
+//! their own handles in between.
+
+use super::validate::{field_mut, state_mut};
+use crate::automaton::bind::{can_bind, step_bound, GridFieldCoupling};
+use crate::automaton::field::Field;
+use crate::state::State;
+
+/// A grid and a field advanced together.
+pub struct Binding {
+    state: *mut State,
+    field: *mut Field,
+    coupling: GridFieldCoupling,
+}
+
+/// Binds a grid to a field of the same dimensions: every step of the binding
+/// lets each live cell add `emit_rate` to the field, and (if `birth_threshold`
+/// is nonzero) allows births only where the field is above `birth_threshold`.
+///
+/// # Safety
+/// - `state` must be null or a valid State pointer that outlives the binding
+/// - `field` must be null or a valid Field pointer that outlives the binding
+///
+/// # Returns
+/// The binding (free it with `va_unbind`), or null on error (null pointer or
+/// different dimensions).
+#[no_mangle]
+pub unsafe extern "C" fn va_bind_field(
+    state: *mut State,
+    field: *mut Field,
+    emit_rate: u32,
+    birth_threshold: u32,
+) -> *mut Binding {
+    match (state_mut(state), field_mut(field)) {
+        (Some(grid), Some(f)) if can_bind(grid, f) => Box::into_raw(Box::new(Binding {
+            state,
+            field,
+            coupling: GridFieldCoupling {
+                emit_rate,
+                birth_threshold,
+            },
+        })),
+        _ => std::ptr::null_mut(),
+    }
+}
+
+/// Advances the bound grid and field by one combined step: the grid steps
+/// (births gated by the field), live cells emit into the field, then the
+/// field steps.
+///
+/// # Safety
+/// `binding` must be null or a live pointer from `va_bind_field`, whose grid
+/// and field are still alive.
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer, or the grid or field was resized
+/// since binding; nothing is stepped).
+#[no_mangle]
+pub unsafe extern "C" fn va_step_bound(binding: *mut Binding) -> i32 {
+    let Some(binding) = binding.as_mut() else {
+        return 1;
+    };
+    let (Some(state), Some(field)) = (state_mut(binding.state), field_mut(binding.field)) else {
+        return 1;
+    };
+    if step_bound(state, field, binding.coupling) {
+        0
+    } else {
+        1
+    }
+}
+
+/// Frees a binding. The grid and field are not touched. Safe to call with
+/// null pointer (no-op).
+///
+/// # Safety
+/// `binding` must be null or a pointer from `va_bind_field`, not used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn va_unbind(binding: *mut Binding) {
+    if !binding.is_null() {
+        drop(Box::from_raw(binding));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{va_create_field, va_destroy_field, va_field_total};
+    use crate::ffi::grid::{va_create_grid, va_set_cell};
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use std::ptr;
+
+    #[test]
+    fn test_bind_and_step_via_ffi() {
+        unsafe {
+            let state = va_create();
+            let field = va_create_field(8, 8, 8, 1);
+            assert!(va_bind_field(state, field, 10, 0).is_null());
+            va_create_grid(state, 8, 8, 8);
+            assert!(va_bind_field(state, ptr::null_mut(), 10, 0).is_null());
+
+            for (x, y) in [(4, 4), (3, 4), (5, 4), (4, 3), (4, 5)] {
+                va_set_cell(state, x, y, 4, 1);
+            }
+            let binding = va_bind_field(state, field, 1000, 0);
+            assert!(!binding.is_null());
+            let before = va_field_total(field);
+            assert_eq!(va_step_bound(binding), 0);
+            let alive = (*state).cells.iter().filter(|&&c| c != 0).count() as u64;
+            assert_eq!(va_field_total(field), before + alive * 1000);
+            assert_eq!(((*state).generation, (*field).generation), (1, 1));
+
+            // A resized grid no longer matches: nothing steps
+            va_create_grid(state, 4, 4, 4);
+            assert_eq!(va_step_bound(binding), 1);
+            assert_eq!((*field).generation, 1);
+            assert_eq!(va_step_bound(ptr::null_mut()), 1);
+
+            va_unbind(binding);
+            va_unbind(ptr::null_mut());
+            va_destroy(state);
+            va_destroy_field(field);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! FFI interface for scenario bundles (see `automaton::bundle`).
+
+use super::config::{read_text, status};
+use crate::automaton::bundle::{load_bundle, Bundle};
+use crate::automaton::field::Field;
+use crate::state::State;
+
+/// Creates the State and Field a bundle describes, in one call. A section the
+/// bundle leaves out yields a null handle. Handles are only created when the
+/// whole bundle is valid.
+///
+/// # Safety
+/// - `text` must point to at least `len` readable bytes, or be null
+/// - `out_state`, `out_field` must be valid writable pointers, or null (the
+///   corresponding handle is then not created)
+///
+/// # Returns
+/// 0 on success (free the handles with `va_destroy` and `va_destroy_field`),
+/// -1 for a null pointer or text that is not UTF-8, otherwise the (1-based)
+/// line of the first rejected entry. On failure nothing is written.
+#[no_mangle]
+pub unsafe extern "C" fn va_load_bundle(
+    text: *const u8,
+    len: u64,
+    out_state: *mut *mut State,
+    out_field: *mut *mut Field,
+) -> i32 {
+    let Some(text) = read_text(text, len) else {
+        return -1;
+    };
+    let Bundle { state, field } = match load_bundle(text) {
+        Ok(bundle) => bundle,
+        Err(error) => return status("va_load_bundle", Err(error)),
+    };
+    if let Some(out) = out_state.as_mut() {
+        *out = state.map_or(std::ptr::null_mut(), |s| Box::into_raw(Box::new(s)));
+    }
+    if let Some(out) = out_field.as_mut() {
+        *out = field.map_or(std::ptr::null_mut(), |f| Box::into_raw(Box::new(f)));
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{va_destroy_field, va_field_get};
+    use crate::ffi::grid::va_get_cell;
+    use crate::ffi::lifecycle::va_destroy;
+    use std::ptr;
+
+    #[test]
+    fn test_load_bundle_via_ffi() {
+        let text =
+            "[grid]\nsize = [8, 8, 8]\ncells = [[1, 2, 3]]\n\n[field]\nvalues = [[4, 4, 4, 900]]\n";
+        unsafe {
+            let (mut state, mut field) = (ptr::null_mut(), ptr::null_mut());
+            let len = text.len() as u64;
+            assert_eq!(
+                va_load_bundle(text.as_ptr(), len, &mut state, &mut field),
+                0
+            );
+            assert_eq!(va_get_cell(state, 1, 2, 3), 1);
+            assert_eq!(va_field_get(field, 4, 4, 4), 900);
+            va_destroy(state);
+            va_destroy_field(field);
+
+            // Only the grid is wanted
+            let mut state = ptr::null_mut();
+            assert_eq!(
+                va_load_bundle(text.as_ptr(), len, &mut state, ptr::null_mut()),
+                0
+            );
+            assert!(!state.is_null());
+            va_destroy(state);
+
+            let bad = "[grid]\nsize = [8, 8, 8]\ncells = [[9, 0, 0]]\n";
+            let mut state = ptr::null_mut();
+            assert_eq!(
+                va_load_bundle(bad.as_ptr(), bad.len() as u64, &mut state, ptr::null_mut()),
+                3
+            );
+            assert!(state.is_null());
+            assert_eq!(
+                va_load_bundle(ptr::null(), 4, &mut state, ptr::null_mut()),
+                -1
+            );
+        }
+    }
+}
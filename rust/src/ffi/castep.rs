@@ -0,0 +1,244 @@
+//! FFI interface for incremental grid stepping (see `automaton::castep`).
+//!
+//! Configure a grid as usual (`va_create`, `va_create_grid`, rule, stamps,
+//! ...), hand it to `va_create_ca_step_controller`, then drive it with
+//! `va_ca_sc_begin_step` and `va_ca_sc_tick` like a field StepController.
+//! `va_ca_sc_state` reads the grid in between; `va_ca_sc_release` gives it back.
+
+use super::validate::{take_handle, write_opt};
+use crate::automaton::castep::CaStepController;
+use crate::automaton::grid::{in_bounds, index_of};
+use crate::state::State;
+
+/// Hands a grid over to a new incremental stepper. On success the State
+/// pointer must no longer be used; get it back with `va_ca_sc_release`.
+///
+/// # Safety
+/// `state` must be null or a pointer from `va_create` owned by the caller.
+///
+/// # Returns
+/// The controller (free it with `va_destroy_ca_step_controller`), or null for
+/// a null state.
+#[no_mangle]
+pub unsafe extern "C" fn va_create_ca_step_controller(state: *mut State) -> *mut CaStepController {
+    let Some(state) = take_handle(state) else {
+        return std::ptr::null_mut();
+    };
+    Box::into_raw(Box::new(CaStepController::new(state)))
+}
+
+/// Frees a controller and its grid. Safe to call with null pointer (no-op).
+///
+/// # Safety
+/// `ctrl` must be null or a pointer from `va_create_ca_step_controller`, not
+/// used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn va_destroy_ca_step_controller(ctrl: *mut CaStepController) {
+    if !ctrl.is_null() {
+        drop(Box::from_raw(ctrl));
+    }
+}
+
+/// Gives the grid back as a plain State handle and frees the controller,
+/// which requires no step to be in progress.
+///
+/// # Safety
+/// `ctrl` must be null or a valid pointer from `va_create_ca_step_controller`.
+/// On success it must no longer be used.
+///
+/// # Returns
+/// The grid (free it with `va_destroy`), or null if `ctrl` is null or a step
+/// is in progress (the controller is then kept).
+#[no_mangle]
+pub unsafe extern "C" fn va_ca_sc_release(ctrl: *mut CaStepController) -> *mut State {
+    match ctrl.as_ref() {
+        Some(stepper) if !stepper.is_stepping() => {
+            let state = Box::from_raw(ctrl).state;
+            Box::into_raw(Box::new(state))
+        }
+        _ => std::ptr::null_mut(),
+    }
+}
+
+/// Borrows the grid at its last completed generation, for the read-only
+/// `va_*` functions (`va_get_cell`, `va_extract_region`, ...).
+///
+/// # Safety
+/// `ctrl` must be null or a valid pointer from `va_create_ca_step_controller`.
+/// The result is valid until the controller is released or destroyed, and
+/// must not be passed to functions that modify or step a grid.
+///
+/// # Returns
+/// The grid, or null for a null controller.
+#[no_mangle]
+pub unsafe extern "C" fn va_ca_sc_state(ctrl: *const CaStepController) -> *const State {
+    match ctrl.as_ref() {
+        Some(ctrl) => &ctrl.state,
+        None => std::ptr::null(),
+    }
+}
+
+/// Sets a cell to alive (1) or dead (0), as `va_set_cell`. Out-of-bounds
+/// coordinates are silently ignored.
+///
+/// # Safety
+/// `ctrl` must be null or a valid pointer from `va_create_ca_step_controller`.
+///
+/// # Returns
+/// 0 on success, 1 if a step is in progress (the grid is frozen until it
+/// completes), -1 if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_ca_sc_set_cell(
+    ctrl: *mut CaStepController,
+    x: i16,
+    y: i16,
+    z: i16,
+    alive: u8,
+) -> i32 {
+    let Some(ctrl) = ctrl.as_mut() else {
+        return -1;
+    };
+    if ctrl.is_stepping() {
+        return 1;
+    }
+    let state = &mut ctrl.state;
+    if in_bounds(state, x, y, z) {
+        let idx = index_of(state, x, y, z);
+        state.cells[idx] = (alive != 0) as u8;
+    }
+    0
+}
+
+/// Begins a new incremental step.
+///
+/// # Safety
+/// `ctrl` must be null or a valid pointer from `va_create_ca_step_controller`.
+///
+/// # Returns
+/// 0 on success, 1 if a step is already in progress or the grid is empty, -1
+/// if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_ca_sc_begin_step(ctrl: *mut CaStepController) -> i32 {
+    let Some(ctrl) = ctrl.as_mut() else {
+        return -1;
+    };
+    if ctrl.begin_step() {
+        0
+    } else {
+        1
+    }
+}
+
+/// Does bounded work within the given time budget (microseconds), as
+/// `va_sc_tick`. The new generation is committed when the last tile is done.
+///
+/// # Safety
+/// - `ctrl` must be null or a valid pointer from `va_create_ca_step_controller`
+/// - `out_elapsed_ns` and `out_tiles` must be valid writable pointers, or null (skipped)
+///
+/// # Returns
+/// 1 if the step completed during this tick, 0 if more work remains, -1 if no step is active.
+#[no_mangle]
+pub unsafe extern "C" fn va_ca_sc_tick(
+    ctrl: *mut CaStepController,
+    budget_us: u64,
+    out_elapsed_ns: *mut u64,
+    out_tiles: *mut u32,
+) -> i32 {
+    write_opt(out_elapsed_ns, 0);
+    write_opt(out_tiles, 0);
+    let Some(ctrl) = ctrl.as_mut() else {
+        return -1;
+    };
+    if !ctrl.is_stepping() {
+        return -1;
+    }
+    let (done, stats) = ctrl.tick_with_stats(budget_us);
+    write_opt(out_elapsed_ns, stats.elapsed_ns);
+    write_opt(out_tiles, stats.tiles);
+    if done {
+        1
+    } else {
+        0
+    }
+}
+
+/// Queries whether a step is in progress.
+///
+/// # Safety
+/// `ctrl` must be null or a valid pointer from `va_create_ca_step_controller`.
+///
+/// # Returns
+/// 1 if stepping, 0 if idle, -1 if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_ca_sc_is_stepping(ctrl: *const CaStepController) -> i32 {
+    match ctrl.as_ref() {
+        Some(ctrl) => ctrl.is_stepping() as i32,
+        None => -1,
+    }
+}
+
+/// Blocking full step (finishes a step in progress, or does a whole one).
+///
+/// # Safety
+/// `ctrl` must be null or a valid pointer from `va_create_ca_step_controller`.
+#[no_mangle]
+pub unsafe extern "C" fn va_ca_sc_step_blocking(ctrl: *mut CaStepController) {
+    if let Some(ctrl) = ctrl.as_mut() {
+        ctrl.step_blocking();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::stamp::{STAMP_GLIDER, STAMP_SPHERE};
+    use crate::ffi::grid::{va_create_grid, va_get_cell, va_step};
+    use crate::ffi::hash::va_hash;
+    use crate::ffi::lifecycle::{va_create, va_destroy, va_get_generation};
+    use crate::ffi::stamp::va_stamp;
+    use std::ptr;
+
+    #[test]
+    fn test_ca_step_controller_via_ffi() {
+        unsafe {
+            let (state, reference) = (va_create(), va_create());
+            for grid in [state, reference] {
+                va_create_grid(grid, 40, 20, 20);
+                va_stamp(grid, STAMP_SPHERE, 8, 8, 8, 0);
+                va_stamp(grid, STAMP_GLIDER, 30, 10, 10, 0);
+            }
+            let ctrl = va_create_ca_step_controller(state);
+            assert!(!ctrl.is_null());
+            assert_eq!(va_ca_sc_begin_step(ctrl), 0);
+            assert_eq!(va_ca_sc_set_cell(ctrl, 1, 1, 1, 1), 1);
+            let mut ticks = 0;
+            while va_ca_sc_tick(ctrl, 0, ptr::null_mut(), ptr::null_mut()) == 0 {
+                ticks += 1;
+            }
+            assert!(ticks > 1);
+            assert_eq!(va_ca_sc_is_stepping(ctrl), 0);
+            assert_eq!(va_ca_sc_tick(ctrl, 0, ptr::null_mut(), ptr::null_mut()), -1);
+            va_ca_sc_step_blocking(ctrl);
+            va_step(reference);
+            va_step(reference);
+
+            let view = va_ca_sc_state(ctrl);
+            assert_eq!(va_get_generation(view), 2);
+            assert_eq!(va_hash(view), va_hash(reference));
+            assert_eq!(va_ca_sc_set_cell(ctrl, 1, 1, 1, 1), 0);
+
+            let state = va_ca_sc_release(ctrl);
+            assert_eq!(va_get_cell(state, 1, 1, 1), 1);
+            va_destroy(state);
+            va_destroy(reference);
+
+            assert!(va_create_ca_step_controller(ptr::null_mut()).is_null());
+            assert!(va_ca_sc_release(ptr::null_mut()).is_null());
+            assert!(va_ca_sc_state(ptr::null()).is_null());
+            assert_eq!(va_ca_sc_begin_step(ptr::null_mut()), -1);
+            assert_eq!(va_ca_sc_is_stepping(ptr::null()), -1);
+            va_destroy_ca_step_controller(ptr::null_mut());
+        }
+    }
+}
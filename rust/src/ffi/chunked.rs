@@ -0,0 +1,117 @@
+//! FFI interface for chunked fields (huge, mostly uniform domains).
+//!
+//! A chunked field is a Field handle: `va_field_get`, `va_field_set`,
+//! `va_field_step`, `va_field_extract_region`, `va_field_total`,
+//! `va_field_get_generation`, `va_field_set_rounding` and `va_destroy_field`
+//! take it like a dense one. Calls that need dense cells fail with
+//! `VA_ERR_INVALID_STATE`.
+
+use super::validate::{any_field_ref, dims_valid};
+use crate::automaton::chunked::{create_field_chunked, field_chunk_count};
+use crate::automaton::Field;
+use std::num::NonZeroU32;
+
+/// Create a chunked field with every cell at `ambient`. Only 16³ chunks that
+/// differ from it are stored; rounding defaults to 2 (hash).
+///
+/// # Returns
+/// A new Field, or NULL for non-positive dimensions or an ambient of 0.
+#[no_mangle]
+pub extern "C" fn va_create_field_chunked(
+    width: i16,
+    height: i16,
+    depth: i16,
+    ambient: u32,
+    diffusion_rate: u8,
+) -> *mut Field {
+    let Some(ambient) = NonZeroU32::new(ambient) else {
+        return std::ptr::null_mut();
+    };
+    if !dims_valid(width, height, depth) {
+        return std::ptr::null_mut();
+    }
+    Box::into_raw(Box::new(create_field_chunked(
+        width,
+        height,
+        depth,
+        ambient,
+        diffusion_rate,
+    )))
+}
+
+/// Number of 16³ chunks a field stores (16 KB each).
+///
+/// # Safety
+/// `field` must be null or a valid Field pointer.
+///
+/// # Returns
+/// The count, or 0 for a dense field or a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_chunk_count(field: *const Field) -> u64 {
+    any_field_ref(field).map_or(0, |field| field_chunk_count(field) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::error::{va_last_error_code, VA_ERR_INVALID_STATE};
+    use crate::ffi::field::{
+        va_destroy_field, va_field_extract_region, va_field_get, va_field_get_generation,
+        va_field_max, va_field_set, va_field_set_rounding, va_field_step, va_field_total,
+    };
+    use std::ptr;
+
+    #[test]
+    fn test_chunked_field_via_ffi() {
+        assert!(va_create_field_chunked(64, 64, 64, 0, 2).is_null());
+        assert!(va_create_field_chunked(0, 64, 64, 1, 2).is_null());
+
+        let field = va_create_field_chunked(1000, 1000, 200, 20, 2);
+        assert!(!field.is_null());
+        unsafe {
+            va_field_set(field, 500, 500, 100, 4_000_000);
+            va_field_set(field, -1, 0, 0, 5);
+            va_field_set(field, 1, 1, 1, 0);
+            assert_eq!(va_field_get(field, 500, 500, 100), 4_000_000);
+            assert_eq!(va_field_get(field, 0, 0, 0), 20);
+            assert_eq!(va_field_get(field, 1, 1, 1), 1);
+            assert_eq!(va_field_get(field, 1000, 0, 0), 0);
+            assert_eq!(va_field_chunk_count(field), 2);
+            assert_eq!(va_field_set_rounding(field, 7), 1);
+            assert_eq!(va_field_set_rounding(field, 0), 0);
+
+            let total = va_field_total(field);
+            assert_eq!(total, 1000 * 1000 * 200 * 20 + 4_000_000 - 20 - 19);
+            for _ in 0..5 {
+                va_field_step(field);
+            }
+            assert_eq!(va_field_total(field), total);
+            assert_eq!(va_field_get_generation(field), 5);
+
+            let mut out = [0u32; 27];
+            let mut generation = 0;
+            let written = va_field_extract_region(
+                field,
+                out.as_mut_ptr(),
+                out.len() as u64,
+                499,
+                499,
+                99,
+                502,
+                502,
+                102,
+                &mut generation,
+            );
+            assert_eq!(written, 27);
+            assert_eq!(generation, 5);
+            assert!(out[13] < 4_000_000 && out[14] > 20);
+
+            // Calls that need dense cells refuse the chunked field
+            assert_eq!(va_field_max(field), 0);
+            assert_eq!(va_last_error_code(), VA_ERR_INVALID_STATE);
+
+            assert_eq!(va_field_chunk_count(ptr::null()), 0);
+            va_destroy_field(field);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! FFI interface for flood fill and connected component labelling (see
+//! `automaton::components`).
+
+use super::error::{fail, VA_ERR_BUFFER, VA_ERR_INVALID_ARGUMENT, VA_ERR_OUT_OF_BOUNDS};
+use super::validate::{buf_mut, state_ref};
+use crate::automaton::components::{flood_fill, label_components, Connectivity};
+use crate::automaton::grid::in_bounds;
+use crate::state::State;
+
+/// Parse a connectivity argument, recording an invalid one.
+fn connectivity(neighbors: u8) -> Option<Connectivity> {
+    let connectivity = Connectivity::from_neighbors(neighbors);
+    if connectivity.is_none() {
+        fail(
+            VA_ERR_INVALID_ARGUMENT,
+            format!("connectivity {neighbors}, expected 6, 18 or 26"),
+            (),
+        );
+    }
+    connectivity
+}
+
+/// Finds every live cell connected to the seed cell, through shared faces
+/// (`connectivity` 6), faces or edges (18), or faces, edges or corners (26).
+///
+/// # Layout
+/// `out_coords` receives x, y, z triples (i16), the seed first, then the rest
+/// in breadth-first order. Only the first `max` cells are written; call with
+/// a null `out_coords` to count them.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `out_coords` must point to at least `max * 3` writable i16 values, or be
+///   null
+///
+/// # Returns
+/// Number of connected cells including the seed (0 for a dead seed), or 0 on
+/// error (null pointer, seed outside the grid, connectivity not 6, 18 or 26).
+#[no_mangle]
+pub unsafe extern "C" fn va_flood_fill(
+    ptr: *const State,
+    x: i16,
+    y: i16,
+    z: i16,
+    connectivity_neighbors: u8,
+    out_coords: *mut i16,
+    max: u64,
+) -> u64 {
+    let (Some(state), Some(connectivity)) = (state_ref(ptr), connectivity(connectivity_neighbors))
+    else {
+        return 0;
+    };
+    if !in_bounds(state, x, y, z) {
+        return fail(
+            VA_ERR_OUT_OF_BOUNDS,
+            format!("seed ({x}, {y}, {z}) outside the grid"),
+            0,
+        );
+    }
+    let cells = flood_fill(state, [x, y, z], connectivity);
+    if let Some(out) = buf_mut(out_coords, max.saturating_mul(3)) {
+        for (slot, cell) in out.chunks_exact_mut(3).zip(&cells) {
+            slot.copy_from_slice(cell);
+        }
+    }
+    cells.len() as u64
+}
+
+/// Labels every cell of the grid with the connected component it belongs to
+/// (see `va_flood_fill` for `connectivity`): 0 for dead cells, components
+/// numbered from 1 in z,y,x order of their first cell. Comparing the count
+/// before and after an edit tells whether a structure split apart.
+///
+/// # Layout
+/// `out_labels` receives one u32 per cell in z,y,x order, like
+/// `va_extract_region` over the whole grid. Pass null to only count the
+/// components.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `out_labels` must point to at least `len` writable u32 values, or be null
+///
+/// # Returns
+/// Number of components, or 0 on error (null pointer, bad connectivity,
+/// `out_labels` shorter than the grid).
+#[no_mangle]
+pub unsafe extern "C" fn va_label_components(
+    ptr: *const State,
+    connectivity_neighbors: u8,
+    out_labels: *mut u32,
+    len: u64,
+) -> u64 {
+    let (Some(state), Some(connectivity)) = (state_ref(ptr), connectivity(connectivity_neighbors))
+    else {
+        return 0;
+    };
+    let required = state.cells.len();
+    let out = if out_labels.is_null() {
+        None
+    } else {
+        match buf_mut(out_labels, len) {
+            Some(out) if out.len() >= required => Some(&mut out[..required]),
+            _ => {
+                return fail(
+                    VA_ERR_BUFFER,
+                    format!("label buffer of {len} values; the grid has {required} cells"),
+                    0,
+                )
+            }
+        }
+    };
+    let (labels, components) = label_components(state, connectivity);
+    if let Some(out) = out {
+        out.copy_from_slice(&labels);
+    }
+    components as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::error::va_last_error_code;
+    use crate::ffi::grid::{va_create_grid, va_set_cell};
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use std::ptr;
+
+    #[test]
+    fn test_components_via_ffi() {
+        unsafe {
+            let state = va_create();
+            va_create_grid(state, 4, 4, 4);
+            for x in 0..4 {
+                va_set_cell(state, x, 0, 0, 1);
+            }
+            va_set_cell(state, 3, 3, 3, 1);
+
+            let mut coords = [0i16; 6];
+            assert_eq!(va_flood_fill(state, 0, 0, 0, 6, ptr::null_mut(), 0), 4);
+            assert_eq!(va_flood_fill(state, 0, 0, 0, 6, coords.as_mut_ptr(), 2), 4);
+            assert_eq!(coords, [0, 0, 0, 1, 0, 0]);
+            assert_eq!(va_flood_fill(state, 1, 1, 1, 6, coords.as_mut_ptr(), 2), 0);
+            assert_eq!(va_flood_fill(state, 0, 0, 0, 7, coords.as_mut_ptr(), 2), 0);
+            assert_eq!(va_last_error_code(), VA_ERR_INVALID_ARGUMENT);
+            assert_eq!(va_flood_fill(state, 4, 0, 0, 6, coords.as_mut_ptr(), 2), 0);
+            assert_eq!(va_last_error_code(), VA_ERR_OUT_OF_BOUNDS);
+
+            let mut labels = [9u32; 64];
+            assert_eq!(va_label_components(state, 26, ptr::null_mut(), 0), 2);
+            assert_eq!(va_label_components(state, 26, labels.as_mut_ptr(), 64), 2);
+            assert_eq!(&labels[..5], &[1, 1, 1, 1, 0]);
+            assert_eq!(labels[63], 2);
+            assert_eq!(va_label_components(state, 26, labels.as_mut_ptr(), 63), 0);
+            assert_eq!(va_last_error_code(), VA_ERR_BUFFER);
+            va_destroy(state);
+        }
+    }
+}
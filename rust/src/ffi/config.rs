@@ -0,0 +1,158 @@
+//! FFI interface for per-handle configuration blobs (see `automaton::config`).
+
+use super::report::note_error;
+use super::validate::{buf_ref, field_mut, field_ref, state_mut, state_ref, write_text};
+use crate::automaton::config::{
+    apply_field_config, apply_state_config, field_config, state_config, ConfigError,
+};
+use crate::automaton::field::Field;
+use crate::state::State;
+
+/// Read `len` bytes of UTF-8 text.
+pub(super) unsafe fn read_text<'a>(text: *const u8, len: u64) -> Option<&'a str> {
+    std::str::from_utf8(buf_ref(text, len)?).ok()
+}
+
+/// C status of a configuration update by `call`, noting a failure for debug
+/// reports.
+pub(super) fn status(call: &str, result: Result<(), ConfigError>) -> i32 {
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            note_error(call, error);
+            error.line().min(i32::MAX as usize) as i32
+        }
+    }
+}
+
+/// Writes the State's configuration (its rule) as a TOML blob (UTF-8, not
+/// NUL-terminated) that `va_set_config` reads back.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `out_buf` must point to at least `capacity` writable bytes, or be null
+///
+/// # Returns
+/// Number of bytes written, or 0 on error (null pointer, or `capacity` too small).
+/// Pass a null `out_buf` to query the required size without writing.
+#[no_mangle]
+pub unsafe extern "C" fn va_get_config(ptr: *const State, out_buf: *mut u8, capacity: u64) -> u64 {
+    match state_ref(ptr) {
+        Some(state) => write_text(&state_config(state), out_buf, capacity),
+        None => 0,
+    }
+}
+
+/// Applies a configuration blob from `va_get_config` (or hand-written) to the
+/// State. Keys left out keep their current setting.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `text` must point to at least `len` readable bytes, or be null
+///
+/// # Returns
+/// 0 on success, -1 for a null pointer or text that is not UTF-8, otherwise
+/// the (1-based) line of the first rejected entry. The State is unchanged on
+/// failure.
+#[no_mangle]
+pub unsafe extern "C" fn va_set_config(ptr: *mut State, text: *const u8, len: u64) -> i32 {
+    let (Some(state), Some(text)) = (state_mut(ptr), read_text(text, len)) else {
+        return -1;
+    };
+    status("va_set_config", apply_state_config(state, text))
+}
+
+/// Writes every tunable of the field (rates, conductivity and its curve,
+/// rounding, advection, boundaries, periodic axes, sources, phase thresholds)
+/// as a TOML blob (UTF-8, not NUL-terminated) that `va_field_set_config` reads
+/// back. Cells, generation, and per-cell phases are not included (see
+/// `va_field_serialize`).
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `out_buf` must point to at least `capacity` writable bytes, or be null
+///
+/// # Returns
+/// Number of bytes written, or 0 on error (null pointer, or `capacity` too small).
+/// Pass a null `out_buf` to query the required size without writing.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_get_config(
+    field: *const Field,
+    out_buf: *mut u8,
+    capacity: u64,
+) -> u64 {
+    match field_ref(field) {
+        Some(field) => write_text(&field_config(field), out_buf, capacity),
+        None => 0,
+    }
+}
+
+/// Applies a configuration blob from `va_field_get_config` (or hand-written)
+/// to the field. Keys left out keep their current setting; `sources` replaces
+/// every source. Phases are reclassified only if the thresholds change.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `text` must point to at least `len` readable bytes, or be null
+///
+/// # Returns
+/// 0 on success, -1 for a null pointer or text that is not UTF-8, otherwise
+/// the (1-based) line of the first rejected entry. The field is unchanged on
+/// failure.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_set_config(field: *mut Field, text: *const u8, len: u64) -> i32 {
+    let (Some(field), Some(text)) = (field_mut(field), read_text(text, len)) else {
+        return -1;
+    };
+    status("va_field_set_config", apply_field_config(field, text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{
+        va_create_field, va_destroy_field, va_field_add_source, va_field_set_boundary,
+    };
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use crate::ffi::snapshot::va_set_rule_string;
+    use std::ptr;
+
+    #[test]
+    fn test_config_round_trip_via_ffi() {
+        let field = va_create_field(8, 8, 8, 2);
+        let copy = va_create_field(8, 8, 8, 4);
+        unsafe {
+            va_field_set_boundary(field, 3, 1, 273_000);
+            va_field_add_source(field, 1, 2, 3, 500);
+            let len = va_field_get_config(field, ptr::null_mut(), 0);
+            let mut blob = vec![0u8; len as usize];
+            assert_eq!(va_field_get_config(field, blob.as_mut_ptr(), len - 1), 0);
+            assert_eq!(va_field_get_config(field, blob.as_mut_ptr(), len), len);
+            assert_eq!(va_field_set_config(copy, blob.as_ptr(), len), 0);
+            assert_eq!((*copy).diffusion_rate, 2);
+            assert_eq!((*copy).boundaries, (*field).boundaries);
+            assert_eq!((*copy).sources, (*field).sources);
+
+            let bad = b"diffusion_rate = 1\nboundaries = 3\n";
+            assert_eq!(va_field_set_config(copy, bad.as_ptr(), bad.len() as u64), 2);
+            assert_eq!((*copy).diffusion_rate, 2);
+            let not_utf8 = [0xffu8, 0xfe];
+            assert_eq!(va_field_set_config(copy, not_utf8.as_ptr(), 2), -1);
+            assert_eq!(va_field_set_config(ptr::null_mut(), bad.as_ptr(), 1), -1);
+
+            let state = va_create();
+            let other = va_create();
+            va_set_rule_string(state, b"B5/S23".as_ptr(), 6);
+            let mut buf = [0u8; 64];
+            let len = va_get_config(state, buf.as_mut_ptr(), 64);
+            assert_eq!(&buf[..len as usize], b"rule = \"B5/S23\"\n");
+            assert_eq!(va_set_config(other, buf.as_ptr(), len), 0);
+            assert_eq!((*other).rule, (*state).rule);
+            assert_eq!(va_get_config(ptr::null(), buf.as_mut_ptr(), 64), 0);
+            va_destroy(state);
+            va_destroy(other);
+        }
+        va_destroy_field(field);
+        va_destroy_field(copy);
+    }
+}
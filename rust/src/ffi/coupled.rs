@@ -0,0 +1,168 @@
+//! FFI interface for coupled fields (lockstep stepping with cross-terms).
+
+use super::validate::{buf_ref, coupled_mut, coupled_ref, field_ref, take_handle};
+use crate::automaton::coupled::CoupledFields;
+use crate::automaton::field::Field;
+
+/// Create an empty coupling; add fields with `va_coupled_register`.
+#[no_mangle]
+pub extern "C" fn va_create_coupled() -> *mut CoupledFields {
+    Box::into_raw(Box::default())
+}
+
+/// Destroy a coupling together with every field registered in it.
+/// Safe to call with null pointer (no-op).
+///
+/// # Safety
+/// `coupled` must be null or a pointer from `va_create_coupled`, not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn va_destroy_coupled(coupled: *mut CoupledFields) {
+    if !coupled.is_null() {
+        drop(Box::from_raw(coupled));
+    }
+}
+
+/// Hands a field over to the coupling. On success the coupling owns it: the
+/// pointer stays valid for the `va_field_*` functions (get, set, sources,
+/// regions, ...) but must no longer be passed to `va_destroy_field`; it is
+/// freed by `va_destroy_coupled`. On failure the caller keeps ownership.
+///
+/// # Safety
+/// - `coupled` must be null or a valid CoupledFields pointer
+/// - `field` must be null or a pointer from `va_create_field` owned by the caller
+///
+/// # Returns
+/// The field's index in the coefficient matrix, or -1 on failure (null pointer,
+/// 8 fields already registered, or dimensions differing from the first field).
+#[no_mangle]
+pub unsafe extern "C" fn va_coupled_register(
+    coupled: *mut CoupledFields,
+    field: *mut Field,
+) -> i32 {
+    let (Some(coupled), Some(candidate)) = (coupled_mut(coupled), field_ref(field)) else {
+        return -1;
+    };
+    // Check before taking the Box, so a rejected field is never freed
+    if coupled.check_register(candidate).is_err() {
+        return -1;
+    }
+    let Some(field) = take_handle(field) else {
+        return -1;
+    };
+    coupled
+        .register(Box::new(field))
+        .map_or(-1, |index| index as i32)
+}
+
+/// Sets how much of field `source` is added to field `target` each generation,
+/// scaled by 2^16 (65536 adds the full value, -655 removes about 1%).
+///
+/// # Safety
+/// `coupled` must be null or a valid CoupledFields pointer.
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer or index out of range).
+#[no_mangle]
+pub unsafe extern "C" fn va_coupled_set_coefficient(
+    coupled: *mut CoupledFields,
+    target: u8,
+    source: u8,
+    coefficient: i32,
+) -> i32 {
+    let Some(coupled) = coupled_mut(coupled) else {
+        return 1;
+    };
+    if coupled.set_coefficient(target as usize, source as usize, coefficient) {
+        0
+    } else {
+        1
+    }
+}
+
+/// Replaces the whole coefficient matrix (row-major, `coefficients[target * n +
+/// source]`, scaled by 2^16, n = registered fields).
+///
+/// # Safety
+/// - `coupled` must be null or a valid CoupledFields pointer
+/// - `coefficients` must point to at least `len` readable i32 values, or be null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer or `len` not n * n; matrix unchanged).
+#[no_mangle]
+pub unsafe extern "C" fn va_coupled_set_matrix(
+    coupled: *mut CoupledFields,
+    coefficients: *const i32,
+    len: u64,
+) -> i32 {
+    let (Some(coupled), Some(coefficients)) = (coupled_mut(coupled), buf_ref(coefficients, len))
+    else {
+        return 1;
+    };
+    if coupled.set_matrix(coefficients) {
+        0
+    } else {
+        1
+    }
+}
+
+/// Steps every registered field one generation, then applies the cross-terms.
+///
+/// # Safety
+/// `coupled` must be null or a valid CoupledFields pointer (no-op for null).
+#[no_mangle]
+pub unsafe extern "C" fn va_coupled_step(coupled: *mut CoupledFields) {
+    if let Some(coupled) = coupled_mut(coupled) {
+        coupled.step();
+    }
+}
+
+/// Get the number of lockstep generations completed (0 for null).
+///
+/// # Safety
+/// `coupled` must be null or a valid CoupledFields pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_coupled_get_generation(coupled: *const CoupledFields) -> u64 {
+    coupled_ref(coupled).map_or(0, |coupled| coupled.generation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{va_create_field, va_destroy_field, va_field_get, va_field_set};
+    use std::ptr;
+
+    #[test]
+    fn test_coupled_via_ffi() {
+        let coupled = va_create_coupled();
+        let temperature = va_create_field(8, 8, 8, 2);
+        let pollution = va_create_field(8, 8, 8, 2);
+        let mismatched = va_create_field(8, 4, 8, 2);
+        unsafe {
+            assert_eq!(va_coupled_register(coupled, temperature), 0);
+            assert_eq!(va_coupled_register(coupled, pollution), 1);
+            // Rejected: still owned (and destroyed) by the caller
+            assert_eq!(va_coupled_register(coupled, mismatched), -1);
+            va_destroy_field(mismatched);
+            assert_eq!(va_coupled_register(coupled, ptr::null_mut()), -1);
+
+            let matrix = [0, 1 << 15, 0, 0];
+            assert_eq!(va_coupled_set_matrix(coupled, matrix.as_ptr(), 3), 1);
+            assert_eq!(va_coupled_set_matrix(coupled, matrix.as_ptr(), 4), 0);
+            assert_eq!(va_coupled_set_coefficient(coupled, 1, 1, -(1 << 14)), 0);
+            assert_eq!(va_coupled_set_coefficient(coupled, 2, 0, 1), 1);
+
+            // Registered handles keep working with the field functions
+            va_field_set(pollution, 0, 0, 0, 400);
+            va_coupled_step(coupled);
+            assert_eq!(va_coupled_get_generation(coupled), 1);
+            assert!(va_field_get(temperature, 0, 0, 0) > 100);
+            assert!(va_field_get(pollution, 0, 0, 0) < 400);
+
+            va_coupled_step(ptr::null_mut());
+            assert_eq!(va_coupled_get_generation(ptr::null()), 0);
+            assert_eq!(va_coupled_set_coefficient(ptr::null_mut(), 0, 0, 0), 1);
+            va_destroy_coupled(coupled);
+            va_destroy_coupled(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! FFI interface for cycle detection (see `automaton::cycle`).
+//!
+//! Typical use: call `va_detect_cycle` every few steps and stop stepping the
+//! automaton while it reports a period, resuming when a player edits it.
+
+use super::validate::{state_mut, write_opt};
+use crate::automaton::cycle::{detect_cycle, run_until_stable};
+use crate::state::State;
+
+/// Reports whether the automaton has entered a cycle, looking up to
+/// `max_period` generations back (capped at 4096). The first call starts
+/// tracking (each step then hashes its generation), so a cycle of period p is
+/// seen from the p-th step after it. A `max_period` of 0 stops tracking.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// The period (1 for a still life or an empty grid), or 0 if no cycle was seen
+/// (or null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_detect_cycle(ptr: *mut State, max_period: u32) -> u32 {
+    match state_mut(ptr) {
+        Some(state) => detect_cycle(state, max_period),
+        None => 0,
+    }
+}
+
+/// Steps the automaton until it settles into a still life or an oscillator
+/// of period up to `hash_window` (1..=4096, clamped), or `max_generations`
+/// steps have been taken. For pre-baking decorative structures during map
+/// generation: stamp a seed, run it until stable, then copy it into the map.
+///
+/// A grid whose cells stop changing is caught after one step (period 1). A
+/// constant population alone does not count, since a glider keeps its
+/// population while it travels. `va_detect_cycle` tracking is unaffected.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `out_period` must be a valid writable pointer, or null (skipped);
+///   receives the period the grid ended in, or 0 if `max_generations` ran out
+///
+/// # Returns
+/// Number of generations run, or 0 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_run_until_stable(
+    ptr: *mut State,
+    max_generations: u64,
+    hash_window: u32,
+    out_period: *mut u32,
+) -> u64 {
+    let Some(state) = state_mut(ptr) else {
+        return 0;
+    };
+    let run = run_until_stable(state, max_generations, hash_window);
+    write_opt(out_period, run.period);
+    run.generations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::stamp::STAMP_BLINKER;
+    use crate::ffi::grid::{va_create_grid, va_step};
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use crate::ffi::stamp::va_stamp;
+    use std::ptr;
+
+    #[test]
+    fn test_detect_cycle_via_ffi() {
+        unsafe {
+            let state = va_create();
+            va_create_grid(state, 12, 12, 12);
+            va_stamp(state, STAMP_BLINKER, 5, 5, 5, 0);
+            assert_eq!(va_detect_cycle(state, 16), 0);
+            for _ in 0..3 {
+                va_step(state);
+            }
+            assert_eq!(va_detect_cycle(state, 16), 2);
+
+            // Resizing the grid forgets the old generations
+            va_create_grid(state, 4, 4, 4);
+            assert_eq!(va_detect_cycle(state, 16), 0);
+            va_step(state);
+            assert_eq!(va_detect_cycle(state, 16), 1);
+            assert_eq!(va_detect_cycle(ptr::null_mut(), 16), 0);
+            va_destroy(state);
+        }
+    }
+
+    #[test]
+    fn test_run_until_stable_via_ffi() {
+        unsafe {
+            let state = va_create();
+            va_create_grid(state, 12, 12, 12);
+            va_stamp(state, STAMP_BLINKER, 5, 5, 5, 0);
+            let mut period = 9;
+            assert_eq!(va_run_until_stable(state, 1000, 16, &mut period), 2);
+            assert_eq!(period, 2);
+            assert_eq!(va_run_until_stable(state, 5, 1, &mut period), 5);
+            assert_eq!(period, 0);
+            assert_eq!(
+                va_run_until_stable(ptr::null_mut(), 5, 1, ptr::null_mut()),
+                0
+            );
+            va_destroy(state);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! FFI interface for graceful degradation under memory pressure (see
+//! `automaton::degrade`).
+
+use super::validate::{ctrl_mut, ctrl_ref};
+use crate::automaton::degrade::heap_bytes;
+use crate::automaton::incremental::StepController;
+
+/// Sets the heap bytes the controller tries to stay under (0 = no cap). Each
+/// `va_sc_begin_step` that would exceed it first sheds optional memory,
+/// reported as events 2-4 by `va_sc_poll_event`; the step runs either way.
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// 0 on success, -1 if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_set_memory_cap(ctrl: *mut StepController, cap_bytes: u64) -> i32 {
+    let Some(ctrl) = ctrl_mut(ctrl) else {
+        return -1;
+    };
+    ctrl.memory.cap = (cap_bytes != 0).then_some(cap_bytes);
+    0
+}
+
+/// Gets the controller's estimated heap usage in bytes (field, side buffers,
+/// an active step; not thread stacks).
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// The estimate, or 0 if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_memory_usage(ctrl: *const StepController) -> u64 {
+    ctrl_ref(ctrl).map_or(0, heap_bytes)
+}
+
+/// Gets the degradations applied so far: bit 0 flow record dropped, bit 1
+/// buffers compacted, bit 2 reduced to one thread.
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// The bitmask, or 0 if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_get_degradations(ctrl: *const StepController) -> u32 {
+    ctrl_ref(ctrl).map_or(0, |ctrl| ctrl.memory.applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::incremental::{
+        va_create_step_controller, va_destroy_step_controller, va_sc_poll_event,
+        va_sc_step_blocking,
+    };
+    use std::ptr;
+
+    #[test]
+    fn test_memory_cap_via_ffi() {
+        unsafe {
+            let ctrl = va_create_step_controller(16, 16, 16, 2, 2, 0);
+            let usage = va_sc_memory_usage(ctrl);
+            assert!(usage >= 16 * 16 * 16 * 4);
+            va_sc_step_blocking(ctrl);
+            assert_eq!(va_sc_get_degradations(ctrl), 0);
+
+            assert_eq!(va_sc_set_memory_cap(ctrl, 1), 0);
+            va_sc_step_blocking(ctrl);
+            assert_eq!(va_sc_get_degradations(ctrl), 7);
+            let mut kinds = Vec::new();
+            let mut kind = 0;
+            while va_sc_poll_event(ctrl, &mut kind, ptr::null_mut(), ptr::null_mut()) == 1 {
+                kinds.push(kind);
+            }
+            assert_eq!(kinds, [1, 2, 3, 4, 1]);
+
+            assert_eq!(va_sc_set_memory_cap(ptr::null_mut(), 1), -1);
+            assert_eq!(va_sc_memory_usage(ptr::null()), 0);
+            va_destroy_step_controller(ctrl);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! FFI interface for cell differences between handles (see `automaton::diff`).
+//!
+//! Typical use: keep a copy of the grid from the last sync (e.g. via
+//! `va_deserialize` of a snapshot), step the live one, and send or apply
+//! `va_diff(copy, live, ...)` instead of the whole volume.
+
+use super::validate::{buf_mut, field_ref, state_ref};
+use crate::automaton::diff::{diff_fields, diff_states};
+use crate::automaton::field::Field;
+use crate::state::State;
+
+/// Slots per record in the `va_diff` and `va_field_diff` output arrays.
+pub const DIFF_RECORD_LEN: usize = 5;
+
+/// Lists the cells whose value differs between two grids of the same
+/// dimensions.
+///
+/// out_buf layout per differing cell: [x, y, z, old, new] (5 x i16), where
+/// old is the cell in `a` and new the cell in `b`, in cell index order.
+///
+/// # Safety
+/// - `a`, `b` must be valid pointers to States, or null
+/// - `out_buf` must point to at least `max * 5` writable i16 values, or be
+///   null (count only)
+///
+/// # Returns
+/// Total number of differing cells, which may exceed `max`; only the first
+/// `max` are written. -1 if either pointer is null or the dimensions differ.
+#[no_mangle]
+pub unsafe extern "C" fn va_diff(
+    a: *const State,
+    b: *const State,
+    out_buf: *mut i16,
+    max: u64,
+) -> i64 {
+    let Some(diffs) = state_ref(a)
+        .zip(state_ref(b))
+        .and_then(|(a, b)| diff_states(a, b))
+    else {
+        return -1;
+    };
+    let len = max.saturating_mul(DIFF_RECORD_LEN as u64);
+    let mut slots = buf_mut(out_buf, len)
+        .map(|out| out.chunks_exact_mut(DIFF_RECORD_LEN))
+        .into_iter()
+        .flatten();
+    let mut count = 0;
+    for diff in diffs {
+        if let Some(slot) = slots.next() {
+            slot.copy_from_slice(&[diff.x, diff.y, diff.z, diff.old as i16, diff.new as i16]);
+        }
+        count += 1;
+    }
+    count
+}
+
+/// Lists the cells whose value differs between two fields of the same
+/// dimensions.
+///
+/// out_buf layout per differing cell: [x, y, z, old, new] (5 x i64), where
+/// old is the value in `a` and new the value in `b`, in cell index order.
+///
+/// # Safety
+/// - `a`, `b` must be valid pointers to Fields, or null
+/// - `out_buf` must point to at least `max * 5` writable i64 values, or be
+///   null (count only)
+///
+/// # Returns
+/// Total number of differing cells, which may exceed `max`; only the first
+/// `max` are written. -1 if either pointer is null or the dimensions differ.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_diff(
+    a: *const Field,
+    b: *const Field,
+    out_buf: *mut i64,
+    max: u64,
+) -> i64 {
+    let Some(diffs) = field_ref(a)
+        .zip(field_ref(b))
+        .and_then(|(a, b)| diff_fields(a, b))
+    else {
+        return -1;
+    };
+    let len = max.saturating_mul(DIFF_RECORD_LEN as u64);
+    let mut slots = buf_mut(out_buf, len)
+        .map(|out| out.chunks_exact_mut(DIFF_RECORD_LEN))
+        .into_iter()
+        .flatten();
+    let mut count = 0;
+    for diff in diffs {
+        if let Some(slot) = slots.next() {
+            let [x, y, z] = [diff.x, diff.y, diff.z].map(|c| c as i64);
+            slot.copy_from_slice(&[x, y, z, diff.old as i64, diff.new as i64]);
+        }
+        count += 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{va_create_field, va_destroy_field, va_field_set};
+    use crate::ffi::grid::{va_create_grid, va_set_cell};
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use std::ptr;
+
+    #[test]
+    fn test_diff_via_ffi() {
+        unsafe {
+            let (a, b) = (va_create(), va_create());
+            va_create_grid(a, 6, 6, 6);
+            va_create_grid(b, 6, 6, 6);
+            va_set_cell(a, 1, 2, 3, 1);
+            va_set_cell(b, 4, 0, 0, 1);
+            va_set_cell(b, 5, 5, 5, 1);
+            assert_eq!(va_diff(a, b, ptr::null_mut(), 0), 3);
+            let mut out = [-1i16; 12];
+            assert_eq!(va_diff(a, b, out.as_mut_ptr(), 2), 3);
+            assert_eq!(out[..10], [4, 0, 0, 0, 1, 1, 2, 3, 1, 0]);
+            assert_eq!(out[10], -1, "wrote past max");
+            assert_eq!(va_diff(a, a, out.as_mut_ptr(), 2), 0);
+
+            va_create_grid(b, 6, 6, 5);
+            assert_eq!(va_diff(a, b, ptr::null_mut(), 0), -1);
+            assert_eq!(va_diff(a, ptr::null(), ptr::null_mut(), 0), -1);
+            va_destroy(a);
+            va_destroy(b);
+
+            let (f, g) = (va_create_field(4, 4, 4, 2), va_create_field(4, 4, 4, 2));
+            va_field_set(g, 3, 3, 3, 123_456);
+            let mut out = [0i64; 5];
+            assert_eq!(va_field_diff(f, g, out.as_mut_ptr(), 1), 1);
+            assert_eq!(out, [3, 3, 3, 1, 123_456]);
+            assert_eq!(va_field_diff(ptr::null(), g, ptr::null_mut(), 0), -1);
+            va_destroy_field(f);
+            va_destroy_field(g);
+        }
+    }
+}
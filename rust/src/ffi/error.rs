@@ -0,0 +1,126 @@
+//! Thread-local last error of the FFI calls.
+//!
+//! Failing calls still return their documented error value (0, 1, -1, NULL);
+//! in addition they record a code and a message here, errno-style, for the
+//! calling thread. A Lua caller that sees a failure can then ask why with
+//! `va_last_error_code` / `va_last_error_message`. Successful calls leave the
+//! record alone, so it is only meaningful right after a call reported failure
+//! (or after `va_clear_last_error`).
+//!
+//! Null or invalid handles, non-positive dimensions and inverted regions are
+//! recorded by the argument checks in `validate`; everything else by the
+//! failing call.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+
+/// No error recorded.
+pub const VA_ERR_NONE: i32 = 0;
+/// A handle argument was null.
+pub const VA_ERR_NULL_HANDLE: i32 = 1;
+/// An argument was out of range: non-positive dimension, inverted region,
+/// unknown mode id, bad face.
+pub const VA_ERR_INVALID_ARGUMENT: i32 = 2;
+/// Coordinates outside the grid or field.
+pub const VA_ERR_OUT_OF_BOUNDS: i32 = 3;
+/// A caller buffer was null, of an impossible length, or too small.
+pub const VA_ERR_BUFFER: i32 = 4;
+/// A requested size exceeds the library's limits.
+pub const VA_ERR_TOO_LARGE: i32 = 5;
+/// Memory could not be allocated.
+pub const VA_ERR_ALLOC: i32 = 6;
+/// The handle is busy (e.g. a StepController in the middle of a step).
+pub const VA_ERR_BUSY: i32 = 7;
+/// Caller data (configuration, snapshot, bundle, rule string) was rejected.
+pub const VA_ERR_INVALID_DATA: i32 = 8;
+/// The handle is not in a state that allows the call (e.g. no step active).
+pub const VA_ERR_INVALID_STATE: i32 = 9;
+/// A handle argument was destroyed, or is a handle of another type.
+pub const VA_ERR_INVALID_HANDLE: i32 = 10;
+
+struct LastError {
+    code: i32,
+    message: CString,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<LastError> = RefCell::new(LastError {
+        code: VA_ERR_NONE,
+        message: CString::default(),
+    });
+}
+
+/// Record `code` and `message` as this thread's last error.
+pub(crate) fn set_last_error(code: i32, message: impl Into<String>) {
+    let mut message = message.into();
+    message.retain(|c| c != '\0');
+    let message = CString::new(message).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = LastError { code, message });
+}
+
+/// Record a failure and return `value`, the call's documented error value.
+pub(crate) fn fail<T>(code: i32, message: impl Into<String>, value: T) -> T {
+    set_last_error(code, message);
+    value
+}
+
+/// Code of the last failure on the calling thread (`VA_ERR_*`), or 0 if none
+/// since the thread started or `va_clear_last_error`.
+#[no_mangle]
+pub extern "C" fn va_last_error_code() -> i32 {
+    LAST_ERROR.with(|last| last.borrow().code)
+}
+
+/// Message of the last failure on the calling thread, as a NUL-terminated
+/// UTF-8 string ("" if none).
+///
+/// # Returns
+/// A pointer owned by the library, valid until the next failing call or
+/// `va_clear_last_error` on the same thread. Copy it (`ffi.string`) before
+/// making more calls.
+#[no_mangle]
+pub extern "C" fn va_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().message.as_ptr())
+}
+
+/// Forget the calling thread's last error.
+#[no_mangle]
+pub extern "C" fn va_clear_last_error() {
+    set_last_error(VA_ERR_NONE, "");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    fn message() -> String {
+        unsafe { CStr::from_ptr(va_last_error_message()) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_last_error_is_per_thread() {
+        va_clear_last_error();
+        assert_eq!(va_last_error_code(), VA_ERR_NONE);
+        assert_eq!(message(), "");
+
+        assert_eq!(fail(VA_ERR_OUT_OF_BOUNDS, "cell (9, 0, 0)\0 outside", 0), 0);
+        assert_eq!(va_last_error_code(), VA_ERR_OUT_OF_BOUNDS);
+        assert_eq!(message(), "cell (9, 0, 0) outside");
+
+        // Another thread has its own record
+        std::thread::spawn(|| {
+            assert_eq!(va_last_error_code(), VA_ERR_NONE);
+            set_last_error(VA_ERR_BUSY, "busy");
+        })
+        .join()
+        .unwrap();
+        assert_eq!(va_last_error_code(), VA_ERR_OUT_OF_BOUNDS);
+
+        va_clear_last_error();
+        assert_eq!(va_last_error_code(), VA_ERR_NONE);
+    }
+}
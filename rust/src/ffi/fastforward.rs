@@ -0,0 +1,142 @@
+//! FFI interface for fast forward with summary samples (see
+//! `automaton::fastforward`).
+
+use super::validate::{buf_mut, field_mut, state_mut};
+use crate::automaton::fastforward::{fast_forward, field_fast_forward};
+use crate::automaton::field::Field;
+use crate::state::State;
+
+/// i64 slots per sample in the `va_fast_forward` output array.
+pub const GRID_SAMPLE_LEN: usize = 8;
+
+/// u64 slots per sample in the `va_field_fast_forward` output array.
+pub const FIELD_SAMPLE_LEN: usize = 4;
+
+/// Steps the grid `n_generations` times and records a sample after every
+/// `stride` generations.
+///
+/// out_metrics layout per sample: [generation, population, min_x, min_y,
+/// min_z, max_x, max_y, max_z] (8 x i64), the bounding box being inclusive
+/// and all -1 when no cell is alive.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `out_metrics` must point to at least `max_samples * 8` writable i64
+///   values, or be null (samples are only counted)
+///
+/// # Returns
+/// Number of samples taken (`n_generations / stride`), which may exceed
+/// `max_samples`; only the first `max_samples` are written. 0 if `ptr` is null,
+/// no grid exists, or `stride` is 0 (nothing is stepped).
+#[no_mangle]
+pub unsafe extern "C" fn va_fast_forward(
+    ptr: *mut State,
+    n_generations: u64,
+    stride: u64,
+    out_metrics: *mut i64,
+    max_samples: u64,
+) -> u64 {
+    let Some(state) = state_mut(ptr) else {
+        return 0;
+    };
+    let len = max_samples.saturating_mul(GRID_SAMPLE_LEN as u64);
+    let mut slots = buf_mut(out_metrics, len)
+        .map(|out| out.chunks_exact_mut(GRID_SAMPLE_LEN))
+        .into_iter()
+        .flatten();
+    fast_forward(state, n_generations, stride, |sample| {
+        if let Some(slot) = slots.next() {
+            let (lo, hi) = sample.bbox.unwrap_or(([-1; 3], [-1; 3]));
+            slot[0] = sample.generation as i64;
+            slot[1] = sample.population as i64;
+            for axis in 0..3 {
+                slot[2 + axis] = lo[axis] as i64;
+                slot[5 + axis] = hi[axis] as i64;
+            }
+        }
+    })
+}
+
+/// Steps the field `n_generations` times and records a sample after every
+/// `stride` generations.
+///
+/// out_metrics layout per sample: [generation, total, min, max] (4 x u64).
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `out_metrics` must point to at least `max_samples * 4` writable u64
+///   values, or be null (samples are only counted)
+///
+/// # Returns
+/// Number of samples taken, which may exceed `max_samples` (as
+/// `va_fast_forward`). 0 if `field` is null or `stride` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_fast_forward(
+    field: *mut Field,
+    n_generations: u64,
+    stride: u64,
+    out_metrics: *mut u64,
+    max_samples: u64,
+) -> u64 {
+    let Some(field) = field_mut(field) else {
+        return 0;
+    };
+    let len = max_samples.saturating_mul(FIELD_SAMPLE_LEN as u64);
+    let mut slots = buf_mut(out_metrics, len)
+        .map(|out| out.chunks_exact_mut(FIELD_SAMPLE_LEN))
+        .into_iter()
+        .flatten();
+    field_fast_forward(field, n_generations, stride, |stats| {
+        if let Some(slot) = slots.next() {
+            slot.copy_from_slice(&[
+                stats.generation,
+                stats.total,
+                stats.min as u64,
+                stats.max as u64,
+            ]);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{va_create_field, va_destroy_field, va_field_set};
+    use crate::ffi::grid::{va_create_grid, va_set_cell};
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use std::ptr;
+
+    #[test]
+    fn test_fast_forward_via_ffi() {
+        unsafe {
+            let state = va_create();
+            assert_eq!(va_fast_forward(state, 10, 1, ptr::null_mut(), 0), 0);
+            va_create_grid(state, 12, 12, 12);
+            for (x, y) in [(6, 6), (5, 6), (7, 6), (6, 5), (6, 7)] {
+                va_set_cell(state, x, y, 6, 1);
+            }
+            let mut out = [0i64; 2 * GRID_SAMPLE_LEN];
+            assert_eq!(va_fast_forward(state, 9, 3, out.as_mut_ptr(), 2), 3);
+            assert_eq!((*state).generation, 9);
+            assert_eq!(out[0], 3);
+            assert_eq!(out[GRID_SAMPLE_LEN], 6);
+            assert_eq!(va_fast_forward(state, 4, 0, out.as_mut_ptr(), 2), 0);
+            assert_eq!((*state).generation, 9);
+            va_destroy(state);
+
+            let field = va_create_field(8, 8, 8, 1);
+            va_field_set(field, 4, 4, 4, 100_000);
+            let mut out = [0u64; 8];
+            assert_eq!(va_field_fast_forward(field, 10, 5, out.as_mut_ptr(), 4), 2);
+            assert_eq!(out[0], 5);
+            assert_eq!(out[4], 10);
+            assert_eq!(out[1], out[5]);
+            assert_eq!(out[1], 100_000 + 511);
+            assert_eq!(
+                va_field_fast_forward(ptr::null_mut(), 10, 5, out.as_mut_ptr(), 4),
+                0
+            );
+            va_destroy_field(field);
+        }
+    }
+}
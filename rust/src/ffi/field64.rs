@@ -0,0 +1,265 @@
+//! FFI interface for wide-value (u64) fields.
+//!
+//! Mirrors the `va_field_*` functions for the diffusion subset `Field64`
+//! supports; cell values and region buffers are u64.
+
+use super::validate::{
+    buf_mut, buf_ref, dims_valid, field64_mut, field64_ref, region_volume, write_opt,
+};
+use crate::automaton::audit::checked_divisor;
+use crate::automaton::field::RoundingMode;
+use crate::automaton::field64::{create_field64, field64_get, field64_set, field64_step, Field64};
+use crate::automaton::region::{field64_extract_region, field64_import_region};
+
+/// Create a new u64 field with the given dimensions and diffusion rate.
+/// Every cell starts at 1. Returns NULL for non-positive dimensions.
+#[no_mangle]
+pub extern "C" fn va_create_field64(
+    width: i16,
+    height: i16,
+    depth: i16,
+    diffusion_rate: u8,
+) -> *mut Field64 {
+    if !dims_valid(width, height, depth) {
+        return std::ptr::null_mut();
+    }
+    Box::into_raw(Box::new(create_field64(
+        width,
+        height,
+        depth,
+        diffusion_rate,
+    )))
+}
+
+/// Destroy a u64 field and free its memory.
+/// Safe to call with null pointer (no-op).
+///
+/// # Safety
+/// `field` must be null or a pointer from `va_create_field64` not yet destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn va_destroy_field64(field: *mut Field64) {
+    if !field.is_null() {
+        let _ = Box::from_raw(field);
+    }
+}
+
+/// Set a cell value. Out-of-bounds coordinates and null pointers are ignored.
+///
+/// # Safety
+/// `field` must be a valid pointer to a Field64, or null.
+#[no_mangle]
+pub unsafe extern "C" fn va_field64_set(field: *mut Field64, x: i16, y: i16, z: i16, value: u64) {
+    if let Some(field) = field64_mut(field) {
+        field64_set(field, x, y, z, value);
+    }
+}
+
+/// Get a cell value (never 0 inside the field).
+/// Returns 0 for out-of-bounds coordinates or null pointer.
+///
+/// # Safety
+/// `field` must be a valid pointer to a Field64, or null.
+#[no_mangle]
+pub unsafe extern "C" fn va_field64_get(field: *const Field64, x: i16, y: i16, z: i16) -> u64 {
+    let Some(field) = field64_ref(field) else {
+        return 0;
+    };
+    field64_get(field, x, y, z).map(|nz| nz.get()).unwrap_or(0)
+}
+
+/// Step the field forward by one generation (as `va_field_step`, with i128
+/// flow arithmetic).
+///
+/// # Safety
+/// `field` must be a valid pointer to a Field64, or null (no-op).
+#[no_mangle]
+pub unsafe extern "C" fn va_field64_step(field: *mut Field64) {
+    if let Some(field) = field64_mut(field) {
+        field64_step(field);
+    }
+}
+
+/// Get the current generation number of the field (0 for null).
+///
+/// # Safety
+/// `field` must be a valid pointer to a Field64, or null.
+#[no_mangle]
+pub unsafe extern "C" fn va_field64_get_generation(field: *const Field64) -> u64 {
+    field64_ref(field).map_or(0, |field| field.generation)
+}
+
+/// Extracts a rectangular region into a flat u64 buffer (layout and clamping
+/// as in `va_field_extract_region`).
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field64, or null
+/// - `out_buf` must point to at least `buf_len` writable u64 values, or be null
+/// - `out_generation` must be a valid writable pointer, or null (skipped)
+///
+/// # Returns
+/// Number of cells written, or 0 on error (null pointer, inverted region, or
+/// `buf_len` smaller than the clamped region). `buf_len` counts u64 elements.
+#[no_mangle]
+pub unsafe extern "C" fn va_field64_extract_region(
+    field: *const Field64,
+    out_buf: *mut u64,
+    buf_len: u64,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+    out_generation: *mut u64,
+) -> u64 {
+    let Some(field) = field64_ref(field) else {
+        return 0;
+    };
+    write_opt(out_generation, field.generation);
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    if region_volume(min, max).is_none() {
+        return 0;
+    }
+    let Some(out) = buf_mut(out_buf, buf_len) else {
+        return 0;
+    };
+
+    field64_extract_region(field, out, min, max)
+}
+
+/// Imports a rectangular region from a flat u64 buffer (layout as in
+/// `va_field64_extract_region`). Zero values are stored as 1.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field64, or null
+/// - `in_buf` must point to at least `buf_len` readable u64 values, or be null
+///
+/// # Returns
+/// Number of cells read, or 0 on error (null pointer, inverted region, or
+/// `buf_len` smaller than the clamped region).
+#[no_mangle]
+pub unsafe extern "C" fn va_field64_import_region(
+    field: *mut Field64,
+    in_buf: *const u64,
+    buf_len: u64,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+) -> u64 {
+    let Some(field) = field64_mut(field) else {
+        return 0;
+    };
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    if region_volume(min, max).is_none() {
+        return 0;
+    }
+    let Some(data) = buf_ref(in_buf, buf_len) else {
+        return 0;
+    };
+
+    field64_import_region(field, data, min, max)
+}
+
+/// Sets how fractional flows are rounded (modes as in `va_field_set_rounding`).
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field64, or null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer or unknown mode; field unchanged).
+#[no_mangle]
+pub unsafe extern "C" fn va_field64_set_rounding(field: *mut Field64, mode: u8) -> i32 {
+    let (Some(field), Some(mode)) = (field64_mut(field), RoundingMode::from_u8(mode)) else {
+        return 1;
+    };
+    field.rounding = mode;
+    0
+}
+
+/// Sets separate diffusion shifts for the x, y, and z axes (as
+/// `va_field_set_axis_rates`).
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field64, or null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer, or a rate above 44; field unchanged).
+#[no_mangle]
+pub unsafe extern "C" fn va_field64_set_axis_rates(
+    field: *mut Field64,
+    rx: u8,
+    ry: u8,
+    rz: u8,
+) -> i32 {
+    let Some(field) = field64_mut(field) else {
+        return 1;
+    };
+    let rates = [rx, ry, rz];
+    if rates.iter().any(|&rate| checked_divisor(rate).is_none()) {
+        return 1;
+    }
+    field.axis_rates = Some(rates);
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn test_field64_lifecycle_and_regions() {
+        assert!(va_create_field64(0, 4, 4, 2).is_null());
+        let field = va_create_field64(4, 4, 4, 2);
+        let big = 1u64 << 50;
+        let mut out = vec![0u64; 64];
+        let mut generation = 0;
+        unsafe {
+            va_field64_set(field, 1, 1, 1, big);
+            assert_eq!(va_field64_get(field, 1, 1, 1), big);
+            assert_eq!(va_field64_get(field, 4, 0, 0), 0);
+
+            va_field64_step(field);
+            assert_eq!(va_field64_get_generation(field), 1);
+            assert_eq!(
+                va_field64_extract_region(
+                    field,
+                    out.as_mut_ptr(),
+                    64,
+                    0,
+                    0,
+                    0,
+                    4,
+                    4,
+                    4,
+                    &mut generation
+                ),
+                64
+            );
+            assert_eq!(generation, 1);
+            assert_eq!(out.iter().sum::<u64>(), big + 63);
+            assert!(va_field64_get(field, 2, 1, 1) > 1 << 40);
+
+            let fill = [0u64; 8];
+            assert_eq!(
+                va_field64_import_region(field, fill.as_ptr(), 8, 0, 0, 0, 2, 2, 2),
+                8
+            );
+            assert_eq!(va_field64_get(field, 1, 1, 1), 1);
+
+            assert_eq!(va_field64_set_rounding(field, 9), 1);
+            assert_eq!(va_field64_set_rounding(field, 3), 0);
+            assert_eq!(va_field64_set_axis_rates(field, 1, 50, 1), 1);
+            assert_eq!(va_field64_set_axis_rates(field, 1, 4, 1), 0);
+            assert_eq!((*field).axis_rates, Some([1, 4, 1]));
+            assert_eq!(va_field64_set_rounding(ptr::null_mut(), 0), 1);
+            va_field64_step(ptr::null_mut());
+            assert_eq!(va_field64_get_generation(ptr::null()), 0);
+            va_destroy_field64(field);
+            va_destroy_field64(ptr::null_mut());
+        }
+    }
+}
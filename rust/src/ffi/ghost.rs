@@ -0,0 +1,99 @@
+//! FFI interface for ghost-cell exchange between adjacent handles.
+
+use super::validate::{field_mut, state_mut};
+use crate::automaton::field::Field;
+use crate::automaton::ghost::{exchange_boundaries, field_exchange_boundaries};
+use crate::state::State;
+
+/// Exchange the one-cell ghost layers of two grids that overlap by two layers,
+/// `b` lying beyond `face` of `a` (0 = -x, 1 = +x, 2 = -y, 3 = +y, 4 = -z,
+/// 5 = +z). Call before every step of either grid, so patterns cross the seam
+/// as if the grids were one.
+///
+/// # Safety
+/// `a` and `b` must be null or valid State pointers.
+///
+/// # Returns
+/// 0 on success, 1 on failure (face above 5, faces of different size, a grid
+/// under two cells thick along the axis, or `a == b`), -1 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_exchange_boundaries(a: *mut State, b: *mut State, face: u8) -> i32 {
+    if a == b {
+        return if a.is_null() { -1 } else { 1 };
+    }
+    let (Some(a), Some(b)) = (state_mut(a), state_mut(b)) else {
+        return -1;
+    };
+    exchange_boundaries(a, b, face).map_or(1, |()| 0)
+}
+
+/// `va_exchange_boundaries` for fields. Ghost cells are copies, so each
+/// field's total counts its ghosts: sum the interiors for the world's total.
+///
+/// # Safety
+/// `a` and `b` must be null or valid Field pointers.
+///
+/// # Returns
+/// As `va_exchange_boundaries`.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_exchange_boundaries(
+    a: *mut Field,
+    b: *mut Field,
+    face: u8,
+) -> i32 {
+    if a == b {
+        return if a.is_null() { -1 } else { 1 };
+    }
+    let (Some(a), Some(b)) = (field_mut(a), field_mut(b)) else {
+        return -1;
+    };
+    field_exchange_boundaries(a, b, face).map_or(1, |()| 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{va_create_field, va_destroy_field, va_field_get, va_field_set};
+    use crate::ffi::grid::{va_create_grid, va_get_cell, va_set_cell};
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use std::ptr;
+
+    #[test]
+    fn test_exchange_via_ffi() {
+        unsafe {
+            let (a, b) = (va_create(), va_create());
+            va_create_grid(a, 4, 6, 6);
+            va_create_grid(b, 4, 6, 6);
+            // b lies beyond a's -y face: a's ghost is y 0 (b's y 4), b's
+            // ghost is y 5 (a's y 1)
+            va_set_cell(a, 1, 2, 3, 1);
+            va_set_cell(b, 2, 4, 5, 1);
+            assert_eq!(va_exchange_boundaries(a, b, 2), 0);
+            assert_eq!(va_get_cell(a, 2, 0, 5), 1);
+            assert_eq!(va_get_cell(b, 1, 5, 3), 0);
+            va_set_cell(a, 1, 1, 3, 1);
+            assert_eq!(va_exchange_boundaries(a, b, 2), 0);
+            assert_eq!(va_get_cell(b, 1, 5, 3), 1);
+
+            assert_eq!(va_exchange_boundaries(a, b, 6), 1);
+            assert_eq!(va_exchange_boundaries(a, a, 1), 1);
+            assert_eq!(va_exchange_boundaries(a, ptr::null_mut(), 1), -1);
+            assert_eq!(
+                va_exchange_boundaries(ptr::null_mut(), ptr::null_mut(), 1),
+                -1
+            );
+            va_destroy(a);
+            va_destroy(b);
+
+            let (f, g) = (va_create_field(8, 4, 4, 2), va_create_field(8, 4, 5, 2));
+            assert_eq!(va_field_exchange_boundaries(f, g, 1), 1);
+            let g2 = va_create_field(3, 4, 4, 2);
+            va_field_set(f, 6, 0, 0, 500);
+            assert_eq!(va_field_exchange_boundaries(f, g2, 1), 0);
+            assert_eq!(va_field_get(g2, 0, 0, 0), 500);
+            for field in [f, g, g2] {
+                va_destroy_field(field);
+            }
+        }
+    }
+}
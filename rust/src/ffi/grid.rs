@@ -1,15 +1,34 @@
 //! Grid creation, cell access, and stepping.
 
-use crate::automaton;
+use super::error::{
+    fail, set_last_error, VA_ERR_ALLOC, VA_ERR_BUFFER, VA_ERR_INVALID_ARGUMENT,
+    VA_ERR_OUT_OF_BOUNDS, VA_ERR_TOO_LARGE,
+};
+use super::validate::{buf_mut, buf_ref, state_mut, state_ref};
+use crate::automaton::{self, GridError};
 use crate::state::State;
 
-/// Creates a grid with the specified dimensions.
+/// `va_create_grid` result: success.
+pub const GRID_OK: i32 = 0;
+/// `va_create_grid` result: null state pointer.
+pub const GRID_ERR_NULL: i32 = 1;
+/// `va_create_grid` result: a dimension is zero or negative.
+pub const GRID_ERR_NON_POSITIVE: i32 = 2;
+/// `va_create_grid` result: more than `automaton::grid::MAX_GRID_CELLS` cells.
+pub const GRID_ERR_TOO_LARGE: i32 = 3;
+/// `va_create_grid` result: the allocation failed.
+pub const GRID_ERR_ALLOC: i32 = 4;
+
+/// Creates a grid with the specified dimensions. The cell count is computed
+/// with checked arithmetic and allocated fallibly, so absurd sizes fail with a
+/// code instead of aborting the server; on failure the previous grid is kept.
 ///
 /// # Safety
 /// - `ptr` must be a valid pointer to a State
 ///
 /// # Returns
-/// 0 on success, 1 on failure (null pointer)
+/// 0 on success, 1 for a null pointer, 2 for a non-positive dimension, 3 for
+/// more than 2^32 cells, 4 if the memory could not be allocated.
 #[no_mangle]
 pub unsafe extern "C" fn va_create_grid(
     ptr: *mut State,
@@ -17,13 +36,27 @@ pub unsafe extern "C" fn va_create_grid(
     height: i16,
     depth: i16,
 ) -> i32 {
-    if ptr.is_null() {
-        return 1;
+    let Some(state) = state_mut(ptr) else {
+        return GRID_ERR_NULL;
+    };
+    match automaton::try_create_grid(state, width, height, depth) {
+        Ok(()) => GRID_OK,
+        Err(GridError::NonPositiveDimension) => fail(
+            VA_ERR_INVALID_ARGUMENT,
+            format!("grid dimensions {width}x{height}x{depth} must all be positive"),
+            GRID_ERR_NON_POSITIVE,
+        ),
+        Err(GridError::TooLarge) => fail(
+            VA_ERR_TOO_LARGE,
+            format!("grid {width}x{height}x{depth} exceeds the cell limit"),
+            GRID_ERR_TOO_LARGE,
+        ),
+        Err(GridError::AllocationFailed) => fail(
+            VA_ERR_ALLOC,
+            format!("could not allocate a {width}x{height}x{depth} grid"),
+            GRID_ERR_ALLOC,
+        ),
     }
-
-    let state = &mut *ptr;
-    automaton::create_grid(state, width, height, depth);
-    0
 }
 
 /// Sets a cell to alive (1) or dead (0).
@@ -34,19 +67,63 @@ pub unsafe extern "C" fn va_create_grid(
 /// Out-of-bounds coordinates are silently ignored.
 #[no_mangle]
 pub unsafe extern "C" fn va_set_cell(ptr: *mut State, x: i16, y: i16, z: i16, alive: u8) {
-    if ptr.is_null() {
+    let Some(state) = state_mut(ptr) else {
         return;
-    }
-
-    let state = &mut *ptr;
+    };
     if !automaton::grid::in_bounds(state, x, y, z) {
-        return;
+        return fail(
+            VA_ERR_OUT_OF_BOUNDS,
+            format!("cell ({x}, {y}, {z}) outside the grid"),
+            (),
+        );
     }
 
     let idx = automaton::grid::index_of(state, x, y, z);
     state.cells[idx] = if alive != 0 { 1 } else { 0 };
 }
 
+/// Sets many cells in one call (e.g. thousands of player edits per tick),
+/// instead of one `va_set_cell` per cell. Cell `i` is at `coords[3i..3i+3]`
+/// (x, y, z) and becomes alive if `values[i]` is non-zero; a cell listed
+/// twice takes its last value. Out-of-bounds cells are skipped.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `coords` must point to at least `count * 3` readable i16 values
+/// - `values` must point to at least `count` readable bytes
+///
+/// # Returns
+/// Number of cells set (those inside the grid), or 0 on error (null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_set_cells_bulk(
+    ptr: *mut State,
+    coords: *const i16,
+    values: *const u8,
+    count: u64,
+) -> u64 {
+    let Some(state) = state_mut(ptr) else {
+        return 0;
+    };
+    let (Some(coords), Some(values)) = (
+        buf_ref(coords, count.saturating_mul(3)),
+        buf_ref(values, count),
+    ) else {
+        return fail(
+            VA_ERR_BUFFER,
+            "null or oversized coordinate or value array",
+            0,
+        );
+    };
+    let set = automaton::set_cells_bulk(state, coords, values);
+    if set < count {
+        set_last_error(
+            VA_ERR_OUT_OF_BOUNDS,
+            format!("{} of {count} cells outside the grid", count - set),
+        );
+    }
+    set
+}
+
 /// Gets the state of a cell (0 = dead, 1 = alive).
 ///
 /// # Safety
@@ -56,19 +133,56 @@ pub unsafe extern "C" fn va_set_cell(ptr: *mut State, x: i16, y: i16, z: i16, al
 /// 0 if out of bounds, null pointer, or dead; 1 if alive.
 #[no_mangle]
 pub unsafe extern "C" fn va_get_cell(ptr: *const State, x: i16, y: i16, z: i16) -> u8 {
-    if ptr.is_null() {
+    let Some(state) = state_ref(ptr) else {
         return 0;
-    }
-
-    let state = &*ptr;
+    };
     if !automaton::grid::in_bounds(state, x, y, z) {
-        return 0;
+        return fail(
+            VA_ERR_OUT_OF_BOUNDS,
+            format!("cell ({x}, {y}, {z}) outside the grid"),
+            0,
+        );
     }
 
     let idx = automaton::grid::index_of(state, x, y, z);
     state.cells[idx]
 }
 
+/// Reads many cells in one call (e.g. positions along a ray, or a scattered
+/// particle set), instead of one `va_get_cell` per cell. Cell `i` is at
+/// `coords[3i..3i+3]` (x, y, z); `out_values[i]` receives 1 if it is alive, 0
+/// if it is dead or outside the grid.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `coords` must point to at least `count * 3` readable i16 values
+/// - `out_values` must point to at least `count` writable bytes
+///
+/// # Returns
+/// Number of cells inside the grid, or 0 on error (null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_get_cells_bulk(
+    ptr: *const State,
+    coords: *const i16,
+    count: u64,
+    out_values: *mut u8,
+) -> u64 {
+    let Some(state) = state_ref(ptr) else {
+        return 0;
+    };
+    let (Some(coords), Some(out)) = (
+        buf_ref(coords, count.saturating_mul(3)),
+        buf_mut(out_values, count),
+    ) else {
+        return fail(
+            VA_ERR_BUFFER,
+            "null or oversized coordinate or value array",
+            0,
+        );
+    };
+    automaton::get_cells_bulk(state, coords, out)
+}
+
 /// Advances the cellular automaton by one generation.
 ///
 /// # Safety
@@ -77,17 +191,91 @@ pub unsafe extern "C" fn va_get_cell(ptr: *const State, x: i16, y: i16, z: i16)
 /// Uses B4/S4 rules with Moore neighborhood (26 neighbors).
 #[no_mangle]
 pub unsafe extern "C" fn va_step(ptr: *mut State) {
-    if ptr.is_null() {
-        return;
+    if let Some(state) = state_mut(ptr) {
+        automaton::step_automaton(state);
     }
+}
 
-    let state = &mut *ptr;
-    automaton::step_automaton(state);
+/// i16 slots per change in the `va_step_preview` output array.
+pub const PREVIEW_RECORD_LEN: usize = 4;
+
+/// Computes what the next `va_step` would change, without stepping.
+///
+/// out_changes layout per changed cell: [x, y, z, alive] (4 x i16), where
+/// alive is the cell's state after the step (1 = born, 0 = dies). Changes are
+/// listed in cell index order. The grid and generation are untouched, so the
+/// caller can show the preview or veto the step (e.g. skip it, or step and
+/// then restore cells in a protected area).
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `out_changes` must point to at least `max * 4` writable i16 values, or be
+///   null (count only)
+///
+/// # Returns
+/// Total number of changes, which may exceed `max`; only the first `max` are
+/// written. 0 if `ptr` is null or no grid exists.
+#[no_mangle]
+pub unsafe extern "C" fn va_step_preview(
+    ptr: *const State,
+    out_changes: *mut i16,
+    max: u64,
+) -> u64 {
+    let Some(state) = state_ref(ptr) else {
+        return 0;
+    };
+    let changes = automaton::step_preview(state);
+    let len = max.saturating_mul(PREVIEW_RECORD_LEN as u64);
+    if let Some(out) = buf_mut(out_changes, len) {
+        for (change, slot) in changes.iter().zip(out.chunks_exact_mut(PREVIEW_RECORD_LEN)) {
+            slot.copy_from_slice(&[change.x, change.y, change.z, change.alive as i16]);
+        }
+    }
+    changes.len() as u64
+}
+
+/// Returns a read-only pointer to the cell buffer for zero-copy access.
+///
+/// Cells are laid out in z,y,x order (`index = (z * height + y) * width + x`),
+/// one byte per cell (0 = dead, 1 = alive). LuaJIT can index the returned
+/// `const uint8_t*` directly, with no FFI call per cell.
+///
+/// # Invalidation
+/// The pointer is valid until the next call that replaces the buffer:
+/// `va_step`, `va_create_grid`, `va_deserialize[_compressed]`, or `va_destroy`.
+/// Re-fetch it after any of these. Cell writes (`va_set_cell`,
+/// `va_import_region`, `va_fill_region`, `va_stamp`, ...) keep the pointer
+/// valid but change what it points to. Never write through it.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// Pointer to the first cell, or null if `ptr` is null or no grid exists.
+#[no_mangle]
+pub unsafe extern "C" fn va_get_cells_ptr(ptr: *const State) -> *const u8 {
+    match state_ref(ptr) {
+        Some(state) if !state.cells.is_empty() => state.cells.as_ptr(),
+        _ => std::ptr::null(),
+    }
+}
+
+/// Returns the number of bytes readable through `va_get_cells_ptr`.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// `width * height * depth`, or 0 if `ptr` is null or no grid exists.
+#[no_mangle]
+pub unsafe extern "C" fn va_get_cells_len(ptr: *const State) -> u64 {
+    state_ref(ptr).map_or(0, |state| state.cells.len() as u64)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ffi::error::va_last_error_code;
     use crate::ffi::lifecycle;
     use std::ptr;
 
@@ -146,6 +334,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_set_cells_bulk() {
+        unsafe {
+            let state = lifecycle::va_create();
+            va_create_grid(state, 4, 4, 4);
+            let coords: [i16; 12] = [1, 2, 3, 4, 0, 0, 0, 0, 0, 1, 2, 3];
+            let values = [1u8, 1, 7, 0];
+            assert_eq!(
+                va_set_cells_bulk(state, coords.as_ptr(), values.as_ptr(), 3),
+                2
+            );
+            assert_eq!(va_get_cell(state, 1, 2, 3), 1);
+            assert_eq!(va_get_cell(state, 0, 0, 0), 1);
+            // The last entry for a cell wins
+            assert_eq!(
+                va_set_cells_bulk(state, coords.as_ptr(), values.as_ptr(), 4),
+                3
+            );
+            assert_eq!(va_get_cell(state, 1, 2, 3), 0);
+
+            assert_eq!(va_set_cells_bulk(state, ptr::null(), values.as_ptr(), 4), 0);
+            assert_eq!(
+                va_set_cells_bulk(ptr::null_mut(), coords.as_ptr(), values.as_ptr(), 4),
+                0
+            );
+            lifecycle::va_destroy(state);
+        }
+    }
+
+    #[test]
+    fn test_get_cells_bulk() {
+        unsafe {
+            let state = lifecycle::va_create();
+            va_create_grid(state, 4, 4, 4);
+            va_set_cell(state, 1, 2, 3, 1);
+            va_set_cell(state, 3, 3, 3, 1);
+            // A ray along x at y 2, z 3, running off the grid
+            let coords: [i16; 15] = [0, 2, 3, 1, 2, 3, 2, 2, 3, 3, 2, 3, 4, 2, 3];
+            let mut values = [9u8; 5];
+            assert_eq!(
+                va_get_cells_bulk(state, coords.as_ptr(), 5, values.as_mut_ptr()),
+                4
+            );
+            assert_eq!(values, [0, 1, 0, 0, 0]);
+            assert_eq!(
+                va_get_cells_bulk(state, coords.as_ptr(), 5, ptr::null_mut()),
+                0
+            );
+            assert_eq!(va_last_error_code(), VA_ERR_BUFFER);
+            lifecycle::va_destroy(state);
+        }
+    }
+
     #[test]
     fn test_step() {
         unsafe {
@@ -173,10 +414,75 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_step_preview() {
+        unsafe {
+            let state = lifecycle::va_create();
+            assert_eq!(va_step_preview(state, ptr::null_mut(), 0), 0);
+            va_create_grid(state, 8, 8, 8);
+            for (x, y) in [(4, 4), (3, 4), (5, 4), (4, 3), (4, 5)] {
+                va_set_cell(state, x, y, 4, 1);
+            }
+
+            let total = va_step_preview(state, ptr::null_mut(), 0);
+            assert!(total >= 4);
+            let mut out = vec![-1i16; total as usize * 4 + 4];
+            assert_eq!(va_step_preview(state, out.as_mut_ptr(), 2), total);
+            assert_eq!(out[8], -1, "wrote past max");
+            assert_eq!(va_step_preview(state, out.as_mut_ptr(), total + 1), total);
+            assert_eq!(lifecycle::va_get_generation(state), 0);
+            assert_eq!(va_get_cell(state, 3, 4, 4), 1);
+
+            va_step(state);
+            for change in out[..total as usize * 4].chunks_exact(4) {
+                let cell = va_get_cell(state, change[0], change[1], change[2]);
+                assert_eq!(cell as i16, change[3]);
+            }
+            assert_eq!(va_step_preview(ptr::null(), out.as_mut_ptr(), 1), 0);
+            lifecycle::va_destroy(state);
+        }
+    }
+
+    #[test]
+    fn test_cells_ptr() {
+        unsafe {
+            let state = lifecycle::va_create();
+            assert!(va_get_cells_ptr(state).is_null());
+            assert_eq!(va_get_cells_len(state), 0);
+
+            va_create_grid(state, 4, 3, 2);
+            va_set_cell(state, 1, 2, 1, 1);
+            let cells = va_get_cells_ptr(state);
+            let len = va_get_cells_len(state);
+            assert_eq!(len, 24);
+
+            let view = std::slice::from_raw_parts(cells, len as usize);
+            assert_eq!(view[(3 + 2) * 4 + 1], 1);
+            assert_eq!(view.iter().filter(|&&c| c == 1).count(), 1);
+
+            assert!(va_get_cells_ptr(ptr::null()).is_null());
+            assert_eq!(va_get_cells_len(ptr::null()), 0);
+
+            lifecycle::va_destroy(state);
+        }
+    }
+
     #[test]
     fn test_null_pointer_handling() {
         unsafe {
             assert_eq!(va_create_grid(ptr::null_mut(), 8, 8, 8), 1);
+
+            // Non-positive dimensions are rejected instead of wrapping to huge sizes
+            let state = lifecycle::va_create();
+            assert_eq!(va_create_grid(state, -1, 8, 8), GRID_ERR_NON_POSITIVE);
+            assert_eq!(va_create_grid(state, 8, 0, 8), GRID_ERR_NON_POSITIVE);
+            assert_eq!(
+                va_create_grid(state, i16::MAX, i16::MAX, 8),
+                GRID_ERR_TOO_LARGE
+            );
+            assert_eq!((*state).cells.len(), 0);
+            assert_eq!(va_last_error_code(), VA_ERR_TOO_LARGE);
+            lifecycle::va_destroy(state);
             va_set_cell(ptr::null_mut(), 0, 0, 0, 1); // Should not crash
             assert_eq!(va_get_cell(ptr::null(), 0, 0, 0), 0);
             va_step(ptr::null_mut()); // Should not crash
@@ -0,0 +1,72 @@
+//! FFI interface for content hashes (see `automaton::hash`).
+//!
+//! Typical use: every N generations each client sends `va_hash` of its
+//! predicted grid to the server, which resyncs clients whose hash differs
+//! from its own.
+
+use super::validate::{field_ref, state_ref};
+use crate::automaton::field::Field;
+use crate::automaton::hash::{field_hash, state_hash};
+use crate::state::State;
+
+/// Hashes the grid's dimensions, generation and cells. Deterministic across
+/// platforms and builds.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// The 64-bit hash, or 0 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_hash(ptr: *const State) -> u64 {
+    state_ref(ptr).map_or(0, state_hash)
+}
+
+/// Hashes the field's dimensions, generation and cells. Deterministic across
+/// platforms and builds.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+///
+/// # Returns
+/// The 64-bit hash, or 0 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_hash(field: *const Field) -> u64 {
+    field_ref(field).map_or(0, field_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{va_create_field, va_destroy_field, va_field_set, va_field_step};
+    use crate::ffi::grid::{va_create_grid, va_set_cell, va_step};
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use std::ptr;
+
+    #[test]
+    fn test_hash_via_ffi() {
+        unsafe {
+            let (a, b) = (va_create(), va_create());
+            for state in [a, b] {
+                va_create_grid(state, 8, 8, 8);
+                va_set_cell(state, 4, 4, 4, 1);
+                va_step(state);
+            }
+            assert_eq!(va_hash(a), va_hash(b));
+            va_set_cell(b, 1, 1, 1, 1);
+            assert_ne!(va_hash(a), va_hash(b));
+            va_destroy(a);
+            va_destroy(b);
+
+            let field = va_create_field(4, 4, 4, 2);
+            va_field_set(field, 2, 2, 2, 5000);
+            let before = va_field_hash(field);
+            va_field_step(field);
+            assert_ne!(va_field_hash(field), before);
+            va_destroy_field(field);
+
+            assert_eq!(va_hash(ptr::null()), 0);
+            assert_eq!(va_field_hash(ptr::null()), 0);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! FFI interface for checkpoint history and rollback (see
+//! `automaton::history`).
+//!
+//! Typical use: enable history once, step as usual, and on "rewind" call
+//! `va_rollback`, then drain the write queue (or re-extract the grid) to bring
+//! the world back in line.
+
+use super::validate::{state_mut, state_ref, write_opt};
+use crate::automaton::history::{rollback, Checkpoint, History};
+use crate::state::State;
+
+/// Enables history with room for `max_snapshots` checkpoints, one every
+/// `interval` generations (0 is treated as 1), and checkpoints the current
+/// generation whatever the interval, so a rollback can always reach back to
+/// the moment history was enabled. A `max_snapshots` of 0 disables history.
+/// Enabling again discards the existing checkpoints.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_enable_history(
+    ptr: *mut State,
+    max_snapshots: u32,
+    interval: u64,
+) -> i32 {
+    let Some(state) = state_mut(ptr) else {
+        return 1;
+    };
+    if max_snapshots == 0 {
+        state.history = None;
+        return 0;
+    }
+    let mut history = History::new(max_snapshots as usize, interval);
+    history.push(Checkpoint::of(state));
+    state.history = Some(history);
+    0
+}
+
+/// Rewinds the grid by `generations_back` generations, to the newest
+/// checkpoint at or before that generation (further back if the target is
+/// between checkpoints). Later checkpoints are discarded, and the changed
+/// cells are queued as world writes if the write queue is enabled.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// The generation restored, or -1 (null pointer, history disabled, or no
+/// checkpoint that old; the state is then unchanged).
+#[no_mangle]
+pub unsafe extern "C" fn va_rollback(ptr: *mut State, generations_back: u64) -> i64 {
+    state_mut(ptr)
+        .and_then(|state| rollback(state, generations_back))
+        .map_or(-1, |generation| generation as i64)
+}
+
+/// Gets the checkpoints held and the generations of the oldest and newest.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `out_oldest`, `out_newest` must be valid writable pointers, or null
+///   (skipped); they are left untouched when no checkpoint is held
+///
+/// # Returns
+/// The checkpoint count, or 0 (null pointer, history disabled).
+#[no_mangle]
+pub unsafe extern "C" fn va_get_history_range(
+    ptr: *const State,
+    out_oldest: *mut u64,
+    out_newest: *mut u64,
+) -> u32 {
+    let Some(history) = state_ref(ptr).and_then(|state| state.history.as_ref()) else {
+        return 0;
+    };
+    if let Some((oldest, newest)) = history.range() {
+        write_opt(out_oldest, oldest);
+        write_opt(out_newest, newest);
+    }
+    history.len() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::grid::{va_create_grid, va_set_cell, va_step};
+    use crate::ffi::lifecycle::{va_create, va_destroy, va_get_generation};
+    use crate::ffi::writes::{va_enable_write_queue, va_pending_writes};
+    use std::ptr;
+
+    #[test]
+    fn test_history_via_ffi() {
+        unsafe {
+            let state = va_create();
+            va_create_grid(state, 8, 8, 8);
+            for (x, y) in [(4, 4), (3, 4), (5, 4), (4, 3), (4, 5)] {
+                va_set_cell(state, x, y, 4, 1);
+            }
+            va_step(state);
+            assert_eq!(va_rollback(state, 1), -1);
+            assert_eq!(va_enable_history(state, 4, 3), 0);
+            for _ in 0..7 {
+                va_step(state);
+            }
+            let (mut oldest, mut newest) = (0, 0);
+            assert_eq!(va_get_history_range(state, &mut oldest, &mut newest), 3);
+            assert_eq!((oldest, newest), (1, 6));
+
+            va_enable_write_queue(state, 1024);
+            assert_eq!(va_rollback(state, 3), 3);
+            assert_eq!(va_get_generation(state), 3);
+            // The pattern died out at generation 2; only generation 1 differs
+            assert_eq!(va_pending_writes(state), 0);
+            assert_eq!(va_rollback(state, 1), 1);
+            assert!(va_pending_writes(state) > 0);
+            assert_eq!(va_rollback(state, 1), -1);
+
+            va_create_grid(state, 4, 4, 4);
+            assert_eq!(
+                va_get_history_range(state, ptr::null_mut(), ptr::null_mut()),
+                0
+            );
+            assert_eq!(va_enable_history(state, 0, 1), 0);
+            assert_eq!(va_rollback(state, 0), -1);
+            assert_eq!(va_rollback(ptr::null_mut(), 0), -1);
+            assert_eq!(va_enable_history(ptr::null_mut(), 4, 1), 1);
+            va_destroy(state);
+        }
+    }
+}
@@ -0,0 +1,282 @@
+//! FFI interface for signed (i32) fields.
+//!
+//! Mirrors the `va_field_*` functions for the diffusion subset `IField`
+//! supports; cell values and region buffers are i32.
+
+use super::validate::{
+    buf_mut, buf_ref, dims_valid, ifield_mut, ifield_ref, region_volume, write_opt,
+};
+use crate::automaton::audit::checked_divisor;
+use crate::automaton::field::RoundingMode;
+use crate::automaton::ifield::{create_ifield, ifield_get, ifield_set, ifield_step, IField};
+use crate::automaton::region::{ifield_extract_region, ifield_import_region};
+
+/// Create a new signed field with the given dimensions and diffusion rate.
+/// Every cell starts at 0. Returns NULL for non-positive dimensions.
+#[no_mangle]
+pub extern "C" fn va_create_ifield(
+    width: i16,
+    height: i16,
+    depth: i16,
+    diffusion_rate: u8,
+) -> *mut IField {
+    if !dims_valid(width, height, depth) {
+        return std::ptr::null_mut();
+    }
+    Box::into_raw(Box::new(create_ifield(
+        width,
+        height,
+        depth,
+        diffusion_rate,
+    )))
+}
+
+/// Destroy a signed field and free its memory.
+/// Safe to call with null pointer (no-op).
+///
+/// # Safety
+/// `field` must be null or a pointer from `va_create_ifield` not yet destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn va_destroy_ifield(field: *mut IField) {
+    if !field.is_null() {
+        let _ = Box::from_raw(field);
+    }
+}
+
+/// Set a cell value. Out-of-bounds coordinates and null pointers are ignored.
+///
+/// # Safety
+/// `field` must be a valid pointer to an IField, or null.
+#[no_mangle]
+pub unsafe extern "C" fn va_ifield_set(field: *mut IField, x: i16, y: i16, z: i16, value: i32) {
+    if let Some(field) = ifield_mut(field) {
+        ifield_set(field, x, y, z, value);
+    }
+}
+
+/// Get a cell value. 0 is a valid cell value, so check bounds with
+/// `out_ok` when it matters.
+///
+/// # Safety
+/// - `field` must be a valid pointer to an IField, or null
+/// - `out_ok` must be a valid writable pointer, or null (skipped); it receives
+///   1 if the value was read, 0 for out-of-bounds coordinates or null field
+///
+/// # Returns
+/// The value, or 0 for out-of-bounds coordinates or null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_ifield_get(
+    field: *const IField,
+    x: i16,
+    y: i16,
+    z: i16,
+    out_ok: *mut u8,
+) -> i32 {
+    let value = ifield_ref(field).and_then(|field| ifield_get(field, x, y, z).ok());
+    write_opt(out_ok, value.is_some() as u8);
+    value.unwrap_or(0)
+}
+
+/// Step the field forward by one generation (as `va_field_step`; negative
+/// values diffuse like positive ones and the signed sum is conserved).
+///
+/// # Safety
+/// `field` must be a valid pointer to an IField, or null (no-op).
+#[no_mangle]
+pub unsafe extern "C" fn va_ifield_step(field: *mut IField) {
+    if let Some(field) = ifield_mut(field) {
+        ifield_step(field);
+    }
+}
+
+/// Get the current generation number of the field (0 for null).
+///
+/// # Safety
+/// `field` must be a valid pointer to an IField, or null.
+#[no_mangle]
+pub unsafe extern "C" fn va_ifield_get_generation(field: *const IField) -> u64 {
+    ifield_ref(field).map_or(0, |field| field.generation)
+}
+
+/// Extracts a rectangular region into a flat i32 buffer (layout and clamping
+/// as in `va_field_extract_region`).
+///
+/// # Safety
+/// - `field` must be a valid pointer to an IField, or null
+/// - `out_buf` must point to at least `buf_len` writable i32 values, or be null
+/// - `out_generation` must be a valid writable pointer, or null (skipped)
+///
+/// # Returns
+/// Number of cells written, or 0 on error (null pointer, inverted region, or
+/// `buf_len` smaller than the clamped region). `buf_len` counts i32 elements.
+#[no_mangle]
+pub unsafe extern "C" fn va_ifield_extract_region(
+    field: *const IField,
+    out_buf: *mut i32,
+    buf_len: u64,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+    out_generation: *mut u64,
+) -> u64 {
+    let Some(field) = ifield_ref(field) else {
+        return 0;
+    };
+    write_opt(out_generation, field.generation);
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    if region_volume(min, max).is_none() {
+        return 0;
+    }
+    let Some(out) = buf_mut(out_buf, buf_len) else {
+        return 0;
+    };
+
+    ifield_extract_region(field, out, min, max)
+}
+
+/// Imports a rectangular region from a flat i32 buffer (layout as in
+/// `va_ifield_extract_region`). Values are stored unchanged.
+///
+/// # Safety
+/// - `field` must be a valid pointer to an IField, or null
+/// - `in_buf` must point to at least `buf_len` readable i32 values, or be null
+///
+/// # Returns
+/// Number of cells read, or 0 on error (null pointer, inverted region, or
+/// `buf_len` smaller than the clamped region).
+#[no_mangle]
+pub unsafe extern "C" fn va_ifield_import_region(
+    field: *mut IField,
+    in_buf: *const i32,
+    buf_len: u64,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+) -> u64 {
+    let Some(field) = ifield_mut(field) else {
+        return 0;
+    };
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    if region_volume(min, max).is_none() {
+        return 0;
+    }
+    let Some(data) = buf_ref(in_buf, buf_len) else {
+        return 0;
+    };
+
+    ifield_import_region(field, data, min, max)
+}
+
+/// Sets how fractional flows are rounded (modes as in `va_field_set_rounding`).
+///
+/// # Safety
+/// - `field` must be a valid pointer to an IField, or null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer or unknown mode; field unchanged).
+#[no_mangle]
+pub unsafe extern "C" fn va_ifield_set_rounding(field: *mut IField, mode: u8) -> i32 {
+    let (Some(field), Some(mode)) = (ifield_mut(field), RoundingMode::from_u8(mode)) else {
+        return 1;
+    };
+    field.rounding = mode;
+    0
+}
+
+/// Sets separate diffusion shifts for the x, y, and z axes (as
+/// `va_field_set_axis_rates`).
+///
+/// # Safety
+/// - `field` must be a valid pointer to an IField, or null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer, or a rate above 44; field unchanged).
+#[no_mangle]
+pub unsafe extern "C" fn va_ifield_set_axis_rates(
+    field: *mut IField,
+    rx: u8,
+    ry: u8,
+    rz: u8,
+) -> i32 {
+    let Some(field) = ifield_mut(field) else {
+        return 1;
+    };
+    let rates = [rx, ry, rz];
+    if rates.iter().any(|&rate| checked_divisor(rate).is_none()) {
+        return 1;
+    }
+    field.axis_rates = Some(rates);
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn test_ifield_lifecycle_and_regions() {
+        assert!(va_create_ifield(4, 0, 4, 2).is_null());
+        let field = va_create_ifield(4, 4, 4, 2);
+        let mut ok = 0u8;
+        unsafe {
+            va_ifield_set(field, 1, 1, 1, -64_000);
+            assert_eq!(va_ifield_get(field, 1, 1, 1, &mut ok), -64_000);
+            assert_eq!(ok, 1);
+            assert_eq!(va_ifield_get(field, 0, 0, 0, &mut ok), 0);
+            assert_eq!(ok, 1);
+            assert_eq!(va_ifield_get(field, 0, 4, 0, &mut ok), 0);
+            assert_eq!(ok, 0);
+
+            va_ifield_step(field);
+            assert_eq!(va_ifield_get_generation(field), 1);
+            let mut out = [0i32; 64];
+            let mut generation = 0;
+            assert_eq!(
+                va_ifield_extract_region(
+                    field,
+                    out.as_mut_ptr(),
+                    64,
+                    0,
+                    0,
+                    0,
+                    4,
+                    4,
+                    4,
+                    &mut generation
+                ),
+                64
+            );
+            assert_eq!(generation, 1);
+            assert_eq!(out.iter().sum::<i32>(), -64_000);
+            assert!(va_ifield_get(field, 2, 1, 1, ptr::null_mut()) < 0);
+
+            let fill = [0, -1, 2, -3, 4, -5, 6, -7];
+            assert_eq!(
+                va_ifield_import_region(field, fill.as_ptr(), 8, 0, 0, 0, 2, 2, 2),
+                8
+            );
+            assert_eq!(va_ifield_get(field, 1, 1, 1, ptr::null_mut()), -7);
+            assert_eq!(va_ifield_get(field, 0, 0, 0, ptr::null_mut()), 0);
+
+            assert_eq!(va_ifield_set_rounding(field, 4), 1);
+            assert_eq!(va_ifield_set_rounding(field, 2), 0);
+            assert_eq!(va_ifield_set_axis_rates(field, 45, 1, 1), 1);
+            assert_eq!(va_ifield_set_axis_rates(field, 1, 3, 1), 0);
+            assert_eq!((*field).axis_rates, Some([1, 3, 1]));
+
+            assert_eq!(va_ifield_get(ptr::null(), 0, 0, 0, &mut ok), 0);
+            assert_eq!(ok, 0);
+            va_ifield_step(ptr::null_mut());
+            assert_eq!(va_ifield_get_generation(ptr::null()), 0);
+            va_destroy_ifield(field);
+            va_destroy_ifield(ptr::null_mut());
+        }
+    }
+}
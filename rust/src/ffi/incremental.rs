@@ -1,8 +1,19 @@
 //! FFI interface for incremental stepping (Phase 8: Non-Blocking Incremental Stepping)
 
+use super::error::{
+    fail, VA_ERR_ALLOC, VA_ERR_BUSY, VA_ERR_INVALID_ARGUMENT, VA_ERR_INVALID_STATE,
+    VA_ERR_OUT_OF_BOUNDS,
+};
+use super::validate::{buf_mut, ctrl_mut, ctrl_ref, dims_valid, take_handle, write_opt};
+use crate::automaton::audit::checked_divisor;
+use crate::automaton::events::StepEvent;
+use crate::automaton::field::field_in_bounds;
 use crate::automaton::incremental::StepController;
 
-/// Create a new StepController with the given dimensions and thread pool size.
+/// Create a new StepController with the given dimensions, thread pool size and
+/// tile edge in cells: 8, 16 or 32, any other value (e.g. 0) meaning the
+/// default 16. Fields too large for 256 tiles along an axis get the next larger
+/// size; `va_sc_get_tile_size` reports the one used.
 /// Returns a pointer to the allocated StepController, or NULL if allocation fails.
 #[no_mangle]
 pub extern "C" fn va_create_step_controller(
@@ -11,12 +22,14 @@ pub extern "C" fn va_create_step_controller(
     depth: i16,
     diffusion_rate: u8,
     num_threads: u8,
+    tile_size: u8,
 ) -> *mut StepController {
-    if width <= 0 || height <= 0 || depth <= 0 {
+    if !dims_valid(width, height, depth) {
         return std::ptr::null_mut();
     }
 
-    let ctrl = StepController::new_1(width, height, depth, diffusion_rate, num_threads);
+    let mut ctrl = StepController::new_1(width, height, depth, diffusion_rate, num_threads);
+    ctrl.set_tile_size(tile_size as i16);
     Box::into_raw(Box::new(ctrl))
 }
 
@@ -32,7 +45,7 @@ pub extern "C" fn va_create_step_controller_with_initial(
     diffusion_rate: u8,
     num_threads: u8,
 ) -> *mut StepController {
-    if width <= 0 || height <= 0 || depth <= 0 {
+    if !dims_valid(width, height, depth) {
         return std::ptr::null_mut();
     }
 
@@ -47,143 +60,438 @@ pub extern "C" fn va_create_step_controller_with_initial(
 #[no_mangle]
 pub extern "C" fn va_destroy_step_controller(ctrl: *mut StepController) {
     if !ctrl.is_null() {
-        unsafe {
-            let _ = Box::from_raw(ctrl);
-        }
+        drop(unsafe { take_handle(ctrl) });
     }
 }
 
 /// Set a cell value in the inner field.
 /// Out-of-bounds coordinates are silently ignored.
-/// Returns early if a step is currently active (prevent mid-step mutation).
+/// While a step is active the write is queued and applied, in call order, as
+/// soon as the step commits (see `va_sc_pending_writes`).
 #[no_mangle]
 pub extern "C" fn va_sc_field_set(ctrl: *mut StepController, x: i16, y: i16, z: i16, value: u32) {
-    if ctrl.is_null() {
-        return;
-    }
-
-    unsafe {
-        let ctrl = &mut *ctrl;
-        if ctrl.is_stepping() {
-            return; // Prevent mutation during active step
+    if let Some(ctrl) = unsafe { ctrl_mut(ctrl) } {
+        if !field_in_bounds(&ctrl.field, x, y, z) {
+            return fail(VA_ERR_OUT_OF_BOUNDS, out_of_bounds(x, y, z), ());
         }
-        crate::automaton::field_set(&mut ctrl.field, x, y, z, value);
+        ctrl.set_cell(x, y, z, value);
     }
 }
 
+/// Get the number of `va_sc_field_set` writes queued until the active step
+/// commits.
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// The count, or 0 when idle or for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_pending_writes(ctrl: *const StepController) -> u64 {
+    ctrl_ref(ctrl).map_or(0, |ctrl| ctrl.pending_writes.len() as u64)
+}
+
 /// Get a cell value from the inner field.
 /// Get a cell value, returning the non-zero u32 or 0 on error.
 /// Returns 0 for out-of-bounds coordinates or null pointer.
 #[no_mangle]
 pub extern "C" fn va_sc_field_get(ctrl: *const StepController, x: i16, y: i16, z: i16) -> u32 {
-    if ctrl.is_null() {
+    let Some(ctrl) = (unsafe { ctrl_ref(ctrl) }) else {
         return 0;
+    };
+    match crate::automaton::field_get(&ctrl.field, x, y, z) {
+        Ok(value) => value.get(),
+        Err(_) => fail(VA_ERR_OUT_OF_BOUNDS, out_of_bounds(x, y, z), 0),
     }
+}
 
-    unsafe {
-        crate::automaton::field_get(&(*ctrl).field, x, y, z)
-            .map(|nz| nz.get())
-            .unwrap_or(0)
-    }
+/// Last-error message for coordinates outside the controller's field.
+fn out_of_bounds(x: i16, y: i16, z: i16) -> String {
+    format!("cell ({x}, {y}, {z}) outside the field")
 }
 
 /// Get the current generation number of the inner field.
 #[no_mangle]
 pub extern "C" fn va_sc_field_get_generation(ctrl: *const StepController) -> u64 {
-    if ctrl.is_null() {
-        return 0;
-    }
-
-    unsafe { (*ctrl).field.generation }
+    unsafe { ctrl_ref(ctrl) }.map_or(0, |ctrl| ctrl.field.generation)
 }
 
 /// Begin a new incremental step.
-/// Returns 0 on success, 1 if a step is already in progress.
+/// Returns 0 on success, 1 if a step is already in progress or its buffers
+/// cannot be allocated even after shedding memory.
 #[no_mangle]
 pub extern "C" fn va_sc_begin_step(ctrl: *mut StepController) -> i32 {
-    if ctrl.is_null() {
+    let Some(ctrl) = (unsafe { ctrl_mut(ctrl) }) else {
         return -1;
+    };
+    if ctrl.is_stepping() {
+        return fail(VA_ERR_BUSY, "a step is already in progress", 1);
     }
-
-    unsafe {
-        match (*ctrl).begin_step() {
-            Ok(()) => 0,
-            Err(()) => 1,
-        }
+    match ctrl.begin_step() {
+        Ok(()) => 0,
+        Err(()) => fail(VA_ERR_ALLOC, "could not allocate the step buffers", 1),
     }
 }
 
 /// Do bounded work within the given time budget (microseconds).
-/// Returns 1 if the step completed during this tick, 0 if more work remains, -1 if no step is active.
+///
+/// If non-null, `out_elapsed_ns` receives the wall time actually spent in the
+/// call and `out_tiles` the number of tiles finished, so the caller can adapt
+/// its budget to what really happened. Both receive 0 when no step is active.
+///
+/// # Safety
+/// - `ctrl` must be a valid StepController pointer, or null
+/// - `out_elapsed_ns` and `out_tiles` must be valid writable pointers, or null (skipped)
+///
+/// # Returns
+/// 1 if the step completed during this tick, 0 if more work remains, -1 if no step is active.
 #[no_mangle]
-pub extern "C" fn va_sc_tick(ctrl: *mut StepController, budget_us: u64) -> i32 {
-    if ctrl.is_null() {
+pub unsafe extern "C" fn va_sc_tick(
+    ctrl: *mut StepController,
+    budget_us: u64,
+    out_elapsed_ns: *mut u64,
+    out_tiles: *mut u32,
+) -> i32 {
+    write_opt(out_elapsed_ns, 0);
+    write_opt(out_tiles, 0);
+    let Some(ctrl) = ctrl_mut(ctrl) else {
         return -1;
+    };
+    if !ctrl.is_stepping() {
+        return fail(VA_ERR_INVALID_STATE, "no step is active", -1);
+    }
+    let (done, stats) = ctrl.tick_with_stats(budget_us);
+    write_opt(out_elapsed_ns, stats.elapsed_ns);
+    write_opt(out_tiles, stats.tiles);
+    if done {
+        1
+    } else {
+        0
     }
+}
 
-    unsafe {
-        let ctrl = &mut *ctrl;
-        if !ctrl.is_stepping() {
-            return -1;
-        }
-        if ctrl.tick(budget_us) {
-            1
-        } else {
-            0
-        }
+/// Do bounded work: finish at most `max_tiles` tiles, however long they take
+/// (resuming a tile left half-done by `va_sc_tick` counts as one). For servers
+/// that need the same progress every tick rather than a wall-clock budget.
+///
+/// # Safety
+/// - `ctrl` must be a valid StepController pointer, or null
+/// - `out_elapsed_ns` and `out_tiles` must be valid writable pointers, or null (skipped)
+///
+/// # Returns
+/// 1 if the step completed during this tick, 0 if more work remains, -1 if no step is active.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_tick_tiles(
+    ctrl: *mut StepController,
+    max_tiles: u32,
+    out_elapsed_ns: *mut u64,
+    out_tiles: *mut u32,
+) -> i32 {
+    write_opt(out_elapsed_ns, 0);
+    write_opt(out_tiles, 0);
+    let Some(ctrl) = ctrl_mut(ctrl) else {
+        return -1;
+    };
+    if !ctrl.is_stepping() {
+        return fail(VA_ERR_INVALID_STATE, "no step is active", -1);
     }
+    let (done, stats) = ctrl.tick_tiles(max_tiles);
+    write_opt(out_elapsed_ns, stats.elapsed_ns);
+    write_opt(out_tiles, stats.tiles);
+    if done {
+        1
+    } else {
+        0
+    }
+}
+
+/// Slots in the `va_sc_get_timing` output array.
+pub const TIMING_LEN: usize = 3;
+
+/// Reports measured step costs for sizing tick budgets.
+///
+/// out_timing layout (3 x f64, microseconds): [average time per tile, time
+/// of the last tick, estimated time to finish the active step]. The average
+/// is a moving one over ticks and is 0 until a tile has finished; the
+/// estimate is 0 when no step is active.
+///
+/// # Safety
+/// - `ctrl` must be a valid StepController pointer, or null
+/// - `out_timing` must point to 3 writable f64 values
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_get_timing(
+    ctrl: *const StepController,
+    out_timing: *mut f64,
+) -> i32 {
+    let (Some(ctrl), Some(out)) = (ctrl_ref(ctrl), buf_mut(out_timing, TIMING_LEN as u64)) else {
+        return 1;
+    };
+    let timing = ctrl.timing();
+    out.copy_from_slice(&[
+        timing.tile_ns / 1000.0,
+        timing.last_tick_ns as f64 / 1000.0,
+        ctrl.estimated_remaining_ns() / 1000.0,
+    ]);
+    0
 }
 
 /// Query whether a step is currently in progress.
 /// Returns 1 if stepping, 0 if idle, -1 if null pointer.
 #[no_mangle]
 pub extern "C" fn va_sc_is_stepping(ctrl: *const StepController) -> i32 {
-    if ctrl.is_null() {
+    let Some(ctrl) = (unsafe { ctrl_ref(ctrl) }) else {
         return -1;
+    };
+    if ctrl.is_stepping() {
+        1
+    } else {
+        0
     }
+}
 
-    unsafe {
-        if (*ctrl).is_stepping() {
-            1
-        } else {
-            0
-        }
-    }
+/// Get the tile edge in cells the controller steps with (8, 16 or 32).
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// The tile size, or 0 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_get_tile_size(ctrl: *const StepController) -> u8 {
+    ctrl_ref(ctrl).map_or(0, |ctrl| ctrl.tile_size as u8)
+}
+
+/// Reports how far the active step has got, for progress bars and for sizing
+/// tick budgets by how far behind the simulation is.
+///
+/// # Safety
+/// - `ctrl` must be a valid StepController pointer, or null
+/// - `out_tiles_done` and `out_total_tiles` must be valid writable pointers, or
+///   null (skipped). Both receive 0 when no step is active.
+///
+/// # Returns
+/// The share of the step done, from 0.0 to 1.0 (a tile left half-done counts
+/// its finished rows); 1.0 if no step is active, -1.0 if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_progress(
+    ctrl: *const StepController,
+    out_tiles_done: *mut u32,
+    out_total_tiles: *mut u32,
+) -> f32 {
+    write_opt(out_tiles_done, 0);
+    write_opt(out_total_tiles, 0);
+    let Some(ctrl) = ctrl_ref(ctrl) else {
+        return -1.0;
+    };
+    let Some(progress) = ctrl.progress() else {
+        return 1.0;
+    };
+    write_opt(out_tiles_done, progress.tiles_done);
+    write_opt(out_total_tiles, progress.total_tiles);
+    progress.fraction
 }
 
 /// Convenience: blocking full step (equivalent to begin_step + tick(MAX) until done).
 #[no_mangle]
 pub extern "C" fn va_sc_step_blocking(ctrl: *mut StepController) {
-    if ctrl.is_null() {
-        return;
+    if let Some(ctrl) = unsafe { ctrl_mut(ctrl) } {
+        ctrl.step_blocking();
     }
+}
 
-    unsafe {
-        (*ctrl).step_blocking();
+/// Takes the oldest queued event (call after `va_sc_tick` until it returns 0).
+///
+/// Event kinds:
+/// - 1: generation complete. `out_generation` receives the field's new
+///   generation, `out_global_tick` the controller's global tick.
+/// - 2, 3, 4: memory was shed under pressure (see `va_sc_set_memory_cap`):
+///   flow record dropped, buffers compacted, reduced to one thread.
+///   `out_generation` and `out_global_tick` receive the values when it
+///   happened, before the step from that generation began.
+///
+/// # Safety
+/// - `ctrl` must be a valid StepController pointer, or null
+/// - The out-params must be valid writable pointers, or null (skipped)
+///
+/// # Returns
+/// 1 if an event was written, 0 if the queue is empty, -1 if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_poll_event(
+    ctrl: *mut StepController,
+    out_kind: *mut u32,
+    out_generation: *mut u64,
+    out_global_tick: *mut u64,
+) -> i32 {
+    let Some(ctrl) = ctrl_mut(ctrl) else {
+        return -1;
+    };
+    let Some(event) = ctrl.events.pop() else {
+        return 0;
+    };
+    write_opt(out_kind, event.kind());
+    match event {
+        StepEvent::GenerationComplete {
+            generation,
+            global_tick,
+        } => {
+            write_opt(out_generation, generation);
+            write_opt(out_global_tick, global_tick);
+        }
+        StepEvent::Degraded {
+            generation,
+            global_tick,
+            ..
+        } => {
+            write_opt(out_generation, generation);
+            write_opt(out_global_tick, global_tick);
+        }
     }
+    1
+}
+
+/// Set the seed of the per-tile rounding streams. Stepping is deterministic
+/// for a given seed regardless of tile order, tick budgets, or thread count.
+/// Takes effect from the next begin_step.
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// 0 on success, -1 if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_set_rounding_seed(ctrl: *mut StepController, seed: u64) -> i32 {
+    let Some(ctrl) = ctrl_mut(ctrl) else {
+        return -1;
+    };
+    ctrl.rounding_seed = seed;
+    0
+}
+
+/// Set separate diffusion shifts for the x, y, and z axes of the controller's
+/// field (see `va_field_set_axis_rates`). Takes effect from the next begin_step.
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// 0 on success, 1 if a rate is above 44 (unchanged), -1 if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_set_axis_rates(
+    ctrl: *mut StepController,
+    rx: u8,
+    ry: u8,
+    rz: u8,
+) -> i32 {
+    let Some(ctrl) = ctrl_mut(ctrl) else {
+        return -1;
+    };
+    let rates = [rx, ry, rz];
+    if rates.iter().any(|&rate| checked_divisor(rate).is_none()) {
+        return fail(
+            VA_ERR_INVALID_ARGUMENT,
+            format!("axis rates {rates:?}: a rate above 44 overflows the flow divisor"),
+            1,
+        );
+    }
+    ctrl.field.axis_rates = Some(rates);
+    0
+}
+
+/// Make the axes of the controller's field wrap around (nonzero) or end at
+/// their faces (0) (see `va_field_set_periodic`). Takes effect from the next
+/// begin_step.
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// 0 on success, -1 if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_set_periodic(ctrl: *mut StepController, x: u8, y: u8, z: u8) -> i32 {
+    let Some(ctrl) = ctrl_mut(ctrl) else {
+        return -1;
+    };
+    ctrl.field.periodic = [x != 0, y != 0, z != 0];
+    0
+}
+
+/// Process the tiles nearest a cell (e.g. the player's position) first, so
+/// that when the budget runs out mid-step the remaining tiles are the distant
+/// ones. Also reorders the rest of an active step. The result of a step never
+/// depends on the order.
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// 0 on success, -1 if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_set_focus(ctrl: *mut StepController, x: i16, y: i16, z: i16) -> i32 {
+    let Some(ctrl) = ctrl_mut(ctrl) else {
+        return -1;
+    };
+    ctrl.set_focus(Some([x, y, z]));
+    0
+}
+
+/// Go back to the default tile order (see `va_sc_set_focus`).
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// 0 on success, -1 if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_clear_focus(ctrl: *mut StepController) -> i32 {
+    let Some(ctrl) = ctrl_mut(ctrl) else {
+        return -1;
+    };
+    ctrl.set_focus(None);
+    0
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::ptr;
 
     #[test]
     fn test_create_destroy_step_controller() {
-        let ctrl = va_create_step_controller(16, 16, 16, 2, 1);
+        let ctrl = va_create_step_controller(16, 16, 16, 2, 1, 0);
         assert!(!ctrl.is_null());
 
         unsafe {
             assert_eq!((*ctrl).field.width, 16);
             assert_eq!((*ctrl).field.height, 16);
             assert_eq!((*ctrl).field.depth, 16);
+            assert_eq!(va_sc_get_tile_size(ctrl), 16);
         }
 
         va_destroy_step_controller(ctrl);
     }
 
+    #[test]
+    fn test_tile_size_via_ffi() {
+        unsafe {
+            for (requested, used) in [(8, 8), (32, 32), (0, 16), (20, 16)] {
+                let ctrl = va_create_step_controller(40, 24, 16, 2, 1, requested);
+                assert_eq!(va_sc_get_tile_size(ctrl), used);
+                va_destroy_step_controller(ctrl);
+            }
+            // 4000 cells along x need more than 256 tiles of 8
+            let ctrl = va_create_step_controller(4000, 1, 1, 2, 1, 8);
+            assert_eq!(va_sc_get_tile_size(ctrl), 16);
+            va_destroy_step_controller(ctrl);
+            assert_eq!(va_sc_get_tile_size(ptr::null()), 0);
+        }
+    }
+
     #[test]
     fn test_field_set_get_via_ffi() {
-        let ctrl = va_create_step_controller(16, 16, 16, 2, 1);
+        let ctrl = va_create_step_controller(16, 16, 16, 2, 1, 0);
         assert!(!ctrl.is_null());
 
         va_sc_field_set(ctrl, 8, 8, 8, 5000);
@@ -196,7 +504,7 @@ mod tests {
 
     #[test]
     fn test_step_blocking_via_ffi() {
-        let ctrl = va_create_step_controller(16, 16, 16, 2, 1);
+        let ctrl = va_create_step_controller(16, 16, 16, 2, 1, 0);
         assert!(!ctrl.is_null());
 
         va_sc_field_set(ctrl, 8, 8, 8, 1_000_000);
@@ -214,7 +522,7 @@ mod tests {
 
     #[test]
     fn test_begin_step_and_tick() {
-        let ctrl = va_create_step_controller(16, 16, 16, 2, 1);
+        let ctrl = va_create_step_controller(16, 16, 16, 2, 1, 0);
         assert!(!ctrl.is_null());
 
         va_sc_field_set(ctrl, 8, 8, 8, 500_000);
@@ -225,11 +533,24 @@ mod tests {
 
         // Second begin should fail
         assert_eq!(va_sc_begin_step(ctrl), 1); // Already stepping
+        let mut total_tiles = 0;
+        let progress = unsafe { va_sc_progress(ctrl, ptr::null_mut(), &mut total_tiles) };
+        assert_eq!((progress, total_tiles), (0.0, 1));
+        assert_eq!(
+            unsafe { va_sc_progress(ptr::null(), ptr::null_mut(), ptr::null_mut()) },
+            -1.0
+        );
+        let mut tiles = 0;
+        assert_eq!(
+            unsafe { va_sc_tick_tiles(ctrl, 0, ptr::null_mut(), &mut tiles) },
+            0
+        );
+        assert_eq!(tiles, 0);
 
         // Tick until done (4 MB budget is plenty for 16^3)
         let mut done = false;
         for _ in 0..100 {
-            let result = va_sc_tick(ctrl, 4_000_000);
+            let result = unsafe { va_sc_tick(ctrl, 4_000_000, ptr::null_mut(), ptr::null_mut()) };
             if result == 1 {
                 done = true;
                 break;
@@ -239,6 +560,21 @@ mod tests {
 
         assert!(done, "Step should complete within 100 ticks");
         assert_eq!(va_sc_is_stepping(ctrl), 0); // Done stepping
+        let (mut tiles_done, mut total_tiles) = (7, 7);
+        let progress = unsafe { va_sc_progress(ctrl, &mut tiles_done, &mut total_tiles) };
+        assert_eq!((progress, tiles_done, total_tiles), (1.0, 0, 0));
+        let mut timing = [-1.0; TIMING_LEN];
+        assert_eq!(unsafe { va_sc_get_timing(ctrl, timing.as_mut_ptr()) }, 0);
+        assert!(timing[0] > 0.0 && timing[1] > 0.0);
+        assert_eq!(timing[2], 0.0);
+        assert_eq!(
+            unsafe { va_sc_tick_tiles(ctrl, 1, ptr::null_mut(), ptr::null_mut()) },
+            -1
+        );
+        assert_eq!(
+            unsafe { va_sc_get_timing(ptr::null(), timing.as_mut_ptr()) },
+            1
+        );
         assert_eq!(va_sc_field_get_generation(ctrl), 1);
 
         va_destroy_step_controller(ctrl);
@@ -246,7 +582,7 @@ mod tests {
 
     #[test]
     fn test_conservation_via_ffi() {
-        let ctrl = va_create_step_controller(16, 16, 16, 2, 1);
+        let ctrl = va_create_step_controller(16, 16, 16, 2, 1, 0);
         assert!(!ctrl.is_null());
 
         va_sc_field_set(ctrl, 8, 8, 8, 1_000_000);
@@ -265,6 +601,86 @@ mod tests {
         va_destroy_step_controller(ctrl);
     }
 
+    #[test]
+    fn test_tick_reports_time_and_tiles() {
+        let ctrl = va_create_step_controller(32, 16, 16, 2, 1, 0);
+        let mut elapsed_ns = u64::MAX;
+        let mut tiles = u32::MAX;
+        unsafe {
+            assert_eq!(va_sc_tick(ctrl, 1000, &mut elapsed_ns, &mut tiles), -1);
+            assert_eq!((elapsed_ns, tiles), (0, 0));
+
+            va_sc_begin_step(ctrl);
+            assert_eq!(va_sc_tick(ctrl, 4_000_000, &mut elapsed_ns, &mut tiles), 1);
+        }
+        assert_eq!(tiles, 2);
+        assert!(elapsed_ns > 0);
+        va_destroy_step_controller(ctrl);
+    }
+
+    #[test]
+    fn test_field_set_mid_step_via_ffi() {
+        let ctrl = va_create_step_controller(16, 16, 16, 2, 1, 0);
+        va_sc_field_set(ctrl, 8, 8, 8, 1_000_000);
+        va_sc_begin_step(ctrl);
+        va_sc_field_set(ctrl, 2, 2, 2, 5000);
+        va_sc_field_set(ctrl, 2, 2, 2, 7000);
+        va_sc_field_set(ctrl, 20, 2, 2, 7000);
+        assert_eq!(unsafe { va_sc_pending_writes(ctrl) }, 2);
+        assert_eq!(va_sc_field_get(ctrl, 2, 2, 2), 1);
+
+        va_sc_step_blocking(ctrl);
+        assert_eq!(unsafe { va_sc_pending_writes(ctrl) }, 0);
+        // The later write wins; the step's own result is kept elsewhere
+        assert_eq!(va_sc_field_get(ctrl, 2, 2, 2), 7000);
+        assert!(va_sc_field_get(ctrl, 7, 8, 8) > 1);
+        assert_eq!(va_sc_field_get_generation(ctrl), 1);
+        assert_eq!(unsafe { va_sc_pending_writes(ptr::null()) }, 0);
+        va_destroy_step_controller(ctrl);
+    }
+
+    #[test]
+    fn test_focus_via_ffi() {
+        let ctrl = va_create_step_controller(48, 16, 16, 2, 1, 0);
+        unsafe {
+            assert_eq!(va_sc_set_focus(ctrl, 40, 8, 8), 0);
+            va_sc_begin_step(ctrl);
+            assert_eq!((*ctrl).active_step.as_ref().unwrap().tile_queue[0].tx, 2);
+            assert_eq!(va_sc_clear_focus(ctrl), 0);
+            assert_eq!((*ctrl).focus, None);
+            assert_eq!(va_sc_set_focus(ptr::null_mut(), 0, 0, 0), -1);
+            assert_eq!(va_sc_clear_focus(ptr::null_mut()), -1);
+        }
+        va_destroy_step_controller(ctrl);
+    }
+
+    #[test]
+    fn test_poll_event() {
+        let ctrl = va_create_step_controller(16, 16, 16, 2, 1, 0);
+        let (mut kind, mut generation, mut tick) = (0u32, 0u64, 0u64);
+        unsafe {
+            assert_eq!(
+                va_sc_poll_event(ctrl, &mut kind, &mut generation, &mut tick),
+                0
+            );
+            va_sc_step_blocking(ctrl);
+            assert_eq!(
+                va_sc_poll_event(ctrl, &mut kind, &mut generation, &mut tick),
+                1
+            );
+            assert_eq!((kind, generation, tick), (1, 1, 1));
+            assert_eq!(
+                va_sc_poll_event(ctrl, ptr::null_mut(), ptr::null_mut(), ptr::null_mut()),
+                0
+            );
+            assert_eq!(
+                va_sc_poll_event(ptr::null_mut(), &mut kind, &mut generation, &mut tick),
+                -1
+            );
+        }
+        va_destroy_step_controller(ctrl);
+    }
+
     #[test]
     fn test_null_pointer_safety() {
         // These should not crash with null pointers
@@ -272,15 +688,30 @@ mod tests {
         assert_eq!(va_sc_field_get(std::ptr::null(), 0, 0, 0), 0);
         assert_eq!(va_sc_field_get_generation(std::ptr::null()), 0);
         assert_eq!(va_sc_begin_step(std::ptr::null_mut()), -1);
-        assert_eq!(va_sc_tick(std::ptr::null_mut(), 4000), -1);
+        assert_eq!(
+            unsafe { va_sc_tick(std::ptr::null_mut(), 4000, ptr::null_mut(), ptr::null_mut()) },
+            -1
+        );
         assert_eq!(va_sc_is_stepping(std::ptr::null()), -1);
         va_sc_step_blocking(std::ptr::null_mut());
+        assert_eq!(
+            unsafe { va_sc_set_rounding_seed(std::ptr::null_mut(), 1) },
+            -1
+        );
+        assert_eq!(
+            unsafe { va_sc_set_axis_rates(std::ptr::null_mut(), 1, 2, 1) },
+            -1
+        );
+        assert_eq!(
+            unsafe { va_sc_set_periodic(std::ptr::null_mut(), 1, 0, 1) },
+            -1
+        );
         va_destroy_step_controller(std::ptr::null_mut());
     }
 
     #[test]
     fn test_mutation_blocked_during_step() {
-        let ctrl = va_create_step_controller(16, 16, 16, 2, 1);
+        let ctrl = va_create_step_controller(16, 16, 16, 2, 1, 0);
         assert!(!ctrl.is_null());
 
         va_sc_field_set(ctrl, 8, 8, 8, 500_000);
@@ -299,7 +730,7 @@ mod tests {
         );
 
         // Finish the step
-        while va_sc_tick(ctrl, 4_000_000) == 0 {}
+        while unsafe { va_sc_tick(ctrl, 4_000_000, ptr::null_mut(), ptr::null_mut()) } == 0 {}
 
         // Now mutation should work
         va_sc_field_set(ctrl, 0, 0, 0, 777_777);
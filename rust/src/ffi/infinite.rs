@@ -0,0 +1,222 @@
+//! FFI interface for unbounded CA grids (sparse 16³ chunks, i32 coordinates).
+
+use super::validate::{buf_mut, inf_mut, inf_ref};
+use crate::automaton::infinite::{
+    inf_chunk_keys, inf_extract_chunk, inf_get_cell, inf_population, inf_set_cell, inf_step,
+    InfiniteState, INF_CHUNK_CELLS,
+};
+use crate::state::Rule;
+
+/// Create an empty unbounded grid with the default B4/S4 rule.
+///
+/// # Returns
+/// A new InfiniteState (never NULL).
+#[no_mangle]
+pub extern "C" fn va_create_infinite() -> *mut InfiniteState {
+    Box::into_raw(Box::default())
+}
+
+/// Destroy an unbounded grid. Safe to call with null pointer (no-op).
+///
+/// # Safety
+/// `ptr` must be null or a pointer from `va_create_infinite`, not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn va_destroy_infinite(ptr: *mut InfiniteState) {
+    if !ptr.is_null() {
+        drop(Box::from_raw(ptr));
+    }
+}
+
+/// Set a cell alive (`alive != 0`) or dead, at any i32 world coordinates.
+///
+/// # Safety
+/// `ptr` must be null or a valid InfiniteState pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_inf_set_cell(
+    ptr: *mut InfiniteState,
+    x: i32,
+    y: i32,
+    z: i32,
+    alive: u8,
+) {
+    if let Some(state) = inf_mut(ptr) {
+        inf_set_cell(state, x, y, z, alive != 0);
+    }
+}
+
+/// Get a cell.
+///
+/// # Safety
+/// `ptr` must be null or a valid InfiniteState pointer.
+///
+/// # Returns
+/// 1 if alive, 0 if dead or null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_inf_get_cell(ptr: *const InfiniteState, x: i32, y: i32, z: i32) -> u8 {
+    inf_ref(ptr).is_some_and(|state| inf_get_cell(state, x, y, z)) as u8
+}
+
+/// Step the grid forward one generation, allocating chunks the pattern grows
+/// into and freeing chunks it leaves.
+///
+/// # Safety
+/// `ptr` must be null or a valid InfiniteState pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_inf_step(ptr: *mut InfiniteState) {
+    if let Some(state) = inf_mut(ptr) {
+        inf_step(state);
+    }
+}
+
+/// Set the birth/survival rule (as in `va_set_rule`). Bit 0 of the birth mask
+/// is ignored on an unbounded grid.
+///
+/// # Safety
+/// `ptr` must be null or a valid InfiniteState pointer.
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_inf_set_rule(
+    ptr: *mut InfiniteState,
+    birth_mask: u32,
+    survival_mask: u32,
+) -> i32 {
+    let Some(state) = inf_mut(ptr) else {
+        return 1;
+    };
+    state.rule = Rule {
+        birth: birth_mask & Rule::MASK,
+        survival: survival_mask & Rule::MASK,
+    };
+    0
+}
+
+/// Get the generation.
+///
+/// # Safety
+/// `ptr` must be null or a valid InfiniteState pointer.
+///
+/// # Returns
+/// The generation, or 0 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_inf_get_generation(ptr: *const InfiniteState) -> u64 {
+    inf_ref(ptr).map_or(0, |state| state.generation)
+}
+
+/// Count live cells.
+///
+/// # Safety
+/// `ptr` must be null or a valid InfiniteState pointer.
+///
+/// # Returns
+/// The number of live cells, or 0 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_inf_population(ptr: *const InfiniteState) -> u64 {
+    inf_ref(ptr).map_or(0, inf_population)
+}
+
+/// Write the coordinates of the allocated chunks (chunk = world coordinate
+/// >> 4) as x, y, z triples into `out_keys`, sorted z, then y, then x.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to an InfiniteState, or null
+/// - `out_keys` must point to at least `3 * capacity` writable i32 values, or be null
+///
+/// # Returns
+/// The number of allocated chunks (call with `capacity` 0 to size the
+/// buffer); only the first `capacity` are written. 0 for a null `ptr`.
+#[no_mangle]
+pub unsafe extern "C" fn va_inf_list_chunks(
+    ptr: *const InfiniteState,
+    out_keys: *mut i32,
+    capacity: u64,
+) -> u64 {
+    let Some(state) = inf_ref(ptr) else {
+        return 0;
+    };
+    let keys = inf_chunk_keys(state);
+    if let Some(out) = buf_mut(out_keys, capacity.saturating_mul(3)) {
+        for (slot, key) in out.chunks_exact_mut(3).zip(&keys) {
+            slot.copy_from_slice(key);
+        }
+    }
+    keys.len() as u64
+}
+
+/// Extract one 16³ chunk into a flat u8 buffer (z,y,x order, 1 = alive). An
+/// unallocated chunk reads as all dead.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to an InfiniteState, or null
+/// - `out_buf` must point to at least `buf_len` writable bytes, or be null
+///
+/// # Returns
+/// 4096 (cells written), or 0 on error (null pointer or `buf_len` < 4096).
+#[no_mangle]
+pub unsafe extern "C" fn va_inf_extract_chunk(
+    ptr: *const InfiniteState,
+    chunk_x: i32,
+    chunk_y: i32,
+    chunk_z: i32,
+    out_buf: *mut u8,
+    buf_len: u64,
+) -> u64 {
+    let (Some(state), Some(out)) = (inf_ref(ptr), buf_mut(out_buf, buf_len)) else {
+        return 0;
+    };
+    if inf_extract_chunk(state, [chunk_x, chunk_y, chunk_z], out) {
+        INF_CHUNK_CELLS as u64
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn test_infinite_via_ffi() {
+        let state = va_create_infinite();
+        unsafe {
+            // B1/S: a single cell explodes outward across the chunk at -1
+            assert_eq!(va_inf_set_rule(state, 1 << 1 | 1, 0), 0);
+            va_inf_set_cell(state, -1, 0, 0, 1);
+            assert_eq!(va_inf_get_cell(state, -1, 0, 0), 1);
+            assert_eq!(va_inf_list_chunks(state, ptr::null_mut(), 0), 1);
+
+            va_inf_step(state);
+            assert_eq!(va_inf_get_generation(state), 1);
+            assert_eq!(va_inf_population(state), 26);
+            assert_eq!(va_inf_get_cell(state, -1, 0, 0), 0);
+            assert_eq!(va_inf_get_cell(state, 0, 1, -1), 1);
+
+            let count = va_inf_list_chunks(state, ptr::null_mut(), 0);
+            assert_eq!(count, 8);
+            let mut keys = vec![0i32; count as usize * 3];
+            assert_eq!(va_inf_list_chunks(state, keys.as_mut_ptr(), count), 8);
+            assert_eq!(&keys[..3], &[-1, -1, -1]);
+            assert_eq!(&keys[21..], &[0, 0, 0]);
+
+            let mut chunk = vec![0u8; INF_CHUNK_CELLS];
+            let written = va_inf_extract_chunk(state, 0, 0, 0, chunk.as_mut_ptr(), 4096);
+            assert_eq!(written, 4096);
+            // (0, 0, 0) and (0, 1, 0) are born; (1, 0, 0) is two cells away
+            assert_eq!(chunk[..2], [1, 0]);
+            assert_eq!(chunk[16], 1);
+            assert_eq!(
+                va_inf_extract_chunk(state, 0, 0, 0, chunk.as_mut_ptr(), 100),
+                0
+            );
+
+            va_inf_set_cell(ptr::null_mut(), 0, 0, 0, 1);
+            assert_eq!(va_inf_get_cell(ptr::null(), 0, 0, 0), 0);
+            assert_eq!(va_inf_set_rule(ptr::null_mut(), 0, 0), 1);
+            va_inf_step(ptr::null_mut());
+            va_destroy_infinite(state);
+            va_destroy_infinite(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! FFI interface for the Lenia-style field kernel (see `automaton::lenia`).
+
+use super::validate::{buf_ref, field_mut};
+use crate::automaton::field::Field;
+use crate::automaton::lenia::{field_step_lenia, LeniaRule};
+
+/// Steps the field one generation with a Lenia kernel instead of diffusion.
+/// Cells are read as 16.16 fixed point (65536 = fully alive).
+///
+/// `kernel` holds `radius + 1` weights by (rounded) distance from the cell;
+/// `growth` holds `growth_len >= 2` changes in -65536..=65536, spread evenly
+/// over potentials 0..=65536 and interpolated between. Growth is applied
+/// shifted right by `dt_shift` (0..=16).
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `kernel` must point to at least `kernel_len` readable u16 values, or be null
+/// - `growth` must point to at least `growth_len` readable i32 values, or be null
+///
+/// # Returns
+/// 0 if the field was stepped, 1 for an invalid rule (radius 0 or above 12,
+/// `kernel_len` not `radius + 1`, no kernel weight, growth table too short or
+/// out of range, `dt_shift` above 16), -1 for a null field.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_step_lenia(
+    field: *mut Field,
+    radius: u8,
+    kernel: *const u16,
+    kernel_len: u32,
+    growth: *const i32,
+    growth_len: u32,
+    dt_shift: u8,
+) -> i32 {
+    let Some(field) = field_mut(field) else {
+        return -1;
+    };
+    let (Some(kernel), Some(growth)) = (
+        buf_ref(kernel, kernel_len as u64),
+        buf_ref(growth, growth_len as u64),
+    ) else {
+        return 1;
+    };
+    match LeniaRule::new(radius, kernel, growth, dt_shift) {
+        Some(rule) => {
+            field_step_lenia(field, &rule);
+            0
+        }
+        None => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::lenia::LENIA_ONE;
+    use crate::ffi::field::{va_create_field, va_destroy_field, va_field_get, va_field_set};
+    use std::ptr;
+
+    #[test]
+    fn test_lenia_via_ffi() {
+        unsafe {
+            let field = va_create_field(5, 5, 5, 1);
+            va_field_set(field, 2, 2, 2, LENIA_ONE);
+            let kernel = [1u16, 1];
+            let one = LENIA_ONE as i32;
+            let growth = [-one, -one, one];
+            assert_eq!(
+                va_field_step_lenia(field, 1, kernel.as_ptr(), 2, growth.as_ptr(), 3, 1),
+                0
+            );
+            assert!(va_field_get(field, 2, 2, 2) < LENIA_ONE);
+            assert_eq!(
+                va_field_step_lenia(field, 2, kernel.as_ptr(), 2, growth.as_ptr(), 3, 1),
+                1
+            );
+            assert_eq!(
+                va_field_step_lenia(field, 1, kernel.as_ptr(), 2, ptr::null(), 3, 1),
+                1
+            );
+            assert_eq!(
+                va_field_step_lenia(
+                    ptr::null_mut(),
+                    1,
+                    kernel.as_ptr(),
+                    2,
+                    growth.as_ptr(),
+                    3,
+                    1
+                ),
+                -1
+            );
+            va_destroy_field(field);
+        }
+    }
+}
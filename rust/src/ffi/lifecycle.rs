@@ -1,7 +1,102 @@
-//! State creation, destruction, and generation queries.
+//! State creation, destruction, generation queries, and library-wide
+//! lifecycle (reinit, build info).
 
+use super::pool::reset_pool;
+use super::registry::reset_registry;
+use super::report::reset_errors;
+use super::validate::{state_ref, take_handle, write_text};
 use crate::state::State;
 
+/// `va_build_features` bits.
+pub const BUILD_RAYON: u32 = 1 << 0;
+pub const BUILD_SIMD: u32 = 1 << 1;
+pub const BUILD_GPU: u32 = 1 << 2;
+pub const BUILD_SPARSE: u32 = 1 << 3;
+pub const BUILD_COMPRESSION: u32 = 1 << 4;
+pub const BUILD_PYTHON: u32 = 1 << 5;
+pub const BUILD_WASM: u32 = 1 << 6;
+pub const BUILD_DEBUG: u32 = 1 << 7;
+pub const BUILD_SHM: u32 = 1 << 8;
+pub const BUILD_ORACLE: u32 = 1 << 9;
+
+/// Vector instruction sets the binary was compiled to assume.
+fn simd_features() -> Vec<&'static str> {
+    [
+        ("sse2", cfg!(target_feature = "sse2")),
+        ("sse4.1", cfg!(target_feature = "sse4.1")),
+        ("avx2", cfg!(target_feature = "avx2")),
+        ("avx512f", cfg!(target_feature = "avx512f")),
+        ("neon", cfg!(target_feature = "neon")),
+        ("simd128", cfg!(target_feature = "simd128")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
+
+/// Capabilities compiled into this binary, as `BUILD_*` bits. Tiling runs on
+/// rayon and snapshots always offer RLE compression; there is no GPU or
+/// sparse backend yet, so those bits are reserved.
+fn build_features() -> u32 {
+    let mut bits = BUILD_RAYON | BUILD_COMPRESSION;
+    if !simd_features().is_empty() {
+        bits |= BUILD_SIMD;
+    }
+    if cfg!(feature = "python") {
+        bits |= BUILD_PYTHON;
+    }
+    if cfg!(feature = "wasm") {
+        bits |= BUILD_WASM;
+    }
+    if cfg!(debug_assertions) {
+        bits |= BUILD_DEBUG;
+    }
+    if cfg!(feature = "shm") {
+        bits |= BUILD_SHM;
+    }
+    if cfg!(feature = "oracle") {
+        bits |= BUILD_ORACLE;
+    }
+    bits
+}
+
+/// Readable description of the binary, one `key = value` line per item (the
+/// TOML subset of `va_get_config`).
+fn build_info() -> String {
+    let bits = build_features();
+    let flag = |bit: u32| bits & bit != 0;
+    let simd: Vec<String> = simd_features()
+        .iter()
+        .map(|name| format!("\"{name}\""))
+        .collect();
+    let profile = if flag(BUILD_DEBUG) {
+        "debug"
+    } else {
+        "release"
+    };
+    [
+        format!("version = \"{}\"", env!("CARGO_PKG_VERSION")),
+        format!("profile = \"{profile}\""),
+        format!(
+            "target = \"{}-{}\"",
+            std::env::consts::ARCH,
+            std::env::consts::OS
+        ),
+        format!("simd = [{}]", simd.join(", ")),
+        format!("rayon = {}", flag(BUILD_RAYON)),
+        format!("gpu = {}", flag(BUILD_GPU)),
+        format!("sparse = {}", flag(BUILD_SPARSE)),
+        "compression = \"rle\"".to_string(),
+        format!("python = {}", flag(BUILD_PYTHON)),
+        format!("wasm = {}", flag(BUILD_WASM)),
+        format!("shm = {}", flag(BUILD_SHM)),
+        format!("oracle = {}", flag(BUILD_ORACLE)),
+    ]
+    .iter()
+    .map(|line| format!("{line}\n"))
+    .collect()
+}
+
 /// Creates a new automaton state and returns an opaque pointer.
 ///
 /// # Returns
@@ -11,13 +106,7 @@ use crate::state::State;
 /// The returned pointer must eventually be freed with `va_destroy()`.
 #[no_mangle]
 pub extern "C" fn va_create() -> *mut State {
-    let state = Box::new(State {
-        width: 0,
-        height: 0,
-        depth: 0,
-        cells: Vec::new(),
-        generation: 0,
-    });
+    let state = Box::new(State::default());
     Box::into_raw(state)
 }
 
@@ -29,7 +118,7 @@ pub extern "C" fn va_create() -> *mut State {
 #[no_mangle]
 pub unsafe extern "C" fn va_destroy(ptr: *mut State) {
     if !ptr.is_null() {
-        drop(Box::from_raw(ptr));
+        drop(take_handle(ptr));
     }
 }
 
@@ -42,10 +131,57 @@ pub unsafe extern "C" fn va_destroy(ptr: *mut State) {
 /// The generation counter, or 0 if ptr is null.
 #[no_mangle]
 pub unsafe extern "C" fn va_get_generation(ptr: *const State) -> u64 {
-    if ptr.is_null() {
-        return 0;
-    }
-    (*ptr).generation
+    state_ref(ptr).map_or(0, |state| state.generation)
+}
+
+/// Resets all process-wide library state, as if the library had just been loaded.
+///
+/// A Luanti mod reload re-runs init.lua against the already-loaded library, so
+/// anything the previous run left behind in global registries would otherwise
+/// leak or be initialized twice. Call this once at startup before anything else.
+/// The global state is the buffer pool, the recent errors kept for debug
+/// reports and the handles registered by id (`va_id_*`), which are destroyed;
+/// thread pools belong to their StepController and are freed with it. Handles (State, Field,
+/// StepController, ...) created as pointers are owned by the caller and are not affected.
+///
+/// Idempotent: calling it on a fresh library is a no-op.
+///
+/// # Safety
+/// Every pointer from `va_acquire_buffer()` or `va_id_*_ptr()` becomes invalid,
+/// so none may still be in use (and no buffer may be released afterwards).
+///
+/// # Returns
+/// Number of leaked resources reclaimed (buffers that were never released and
+/// ids that were never destroyed).
+#[no_mangle]
+pub unsafe extern "C" fn va_reinit() -> u64 {
+    reset_errors();
+    (reset_pool() + reset_registry()) as u64
+}
+
+/// Writes a description of this binary for bug reports: version, build
+/// profile, target, SIMD instruction sets, and which optional backends
+/// (rayon, GPU, sparse, compression, Python, wasm, shared memory) are
+/// compiled in. One `key = value` line per item, UTF-8, not NUL-terminated.
+///
+/// # Safety
+/// - `out_buf` must point to at least `capacity` writable bytes, or be null
+///
+/// # Returns
+/// Number of bytes written, or 0 if `capacity` is too small. Pass a null
+/// `out_buf` to query the required size without writing.
+#[no_mangle]
+pub unsafe extern "C" fn va_build_info(out_buf: *mut u8, capacity: u64) -> u64 {
+    write_text(&build_info(), out_buf, capacity)
+}
+
+/// Returns the capabilities of this binary as a bitmask, for Lua-side
+/// feature negotiation without parsing `va_build_info`: 1 rayon, 2 SIMD,
+/// 4 GPU, 8 sparse, 16 compression, 32 Python, 64 wasm, 128 debug build,
+/// 256 shared-memory export, 512 diffusion oracle.
+#[no_mangle]
+pub extern "C" fn va_build_features() -> u32 {
+    build_features()
 }
 
 #[cfg(test)]
@@ -87,4 +223,21 @@ mod tests {
             assert_eq!(va_get_generation(ptr::null()), 0);
         }
     }
+
+    #[test]
+    fn test_build_info() {
+        let features = va_build_features();
+        assert_ne!(features & BUILD_RAYON, 0);
+        assert_eq!(features & (BUILD_GPU | BUILD_SPARSE), 0);
+        unsafe {
+            let len = va_build_info(ptr::null_mut(), 0);
+            let mut buf = vec![0u8; len as usize];
+            assert_eq!(va_build_info(buf.as_mut_ptr(), len - 1), 0);
+            assert_eq!(va_build_info(buf.as_mut_ptr(), len), len);
+            let text = String::from_utf8(buf).unwrap();
+            assert!(text.starts_with(&format!("version = \"{}\"\n", env!("CARGO_PKG_VERSION"))));
+            assert!(text.contains("rayon = true\n"));
+            assert_eq!(text.contains("profile = \"debug\""), cfg!(debug_assertions));
+        }
+    }
 }
@@ -0,0 +1,363 @@
+//! FFI interface for thread-safe State handles (see `automaton::locked`).
+//!
+//! The `va_shared_*` calls take the handle's lock for their duration, waiting
+//! for other threads. For anything else, `va_shared_lock` / `va_shared_try_lock`
+//! hold the lock across calls and lend the State pointer, which takes every
+//! ordinary State call until `va_shared_unlock`. While holding the lock, use
+//! that pointer: a `va_shared_*` call on the same handle would wait forever.
+
+use super::error::{fail, VA_ERR_BUSY, VA_ERR_INVALID_STATE};
+use super::grid::{va_create_grid, va_get_cell, va_set_cell, va_step, GRID_ERR_NULL};
+use super::lifecycle::va_get_generation;
+use super::region::{va_extract_region, va_import_region};
+use super::validate::{shared_ref, take_handle};
+use crate::automaton::locked::SharedState;
+use crate::state::State;
+
+/// Run `f` on the State of `shared` with its lock held, or return `value` for
+/// an invalid handle.
+unsafe fn with_state<R>(
+    shared: *const SharedState,
+    value: R,
+    f: impl FnOnce(*mut State) -> R,
+) -> R {
+    match shared_ref(shared) {
+        Some(shared) => f(&mut *shared.state.lock()),
+        None => value,
+    }
+}
+
+/// Creates a new State (as `va_create`) behind a lock, for use from several
+/// threads.
+///
+/// # Returns
+/// The handle (free it with `va_destroy_shared`).
+#[no_mangle]
+pub extern "C" fn va_create_shared() -> *mut SharedState {
+    Box::into_raw(Box::new(SharedState::new(State::default())))
+}
+
+/// Destroys a shared handle, after waiting for the call or explicit lock
+/// holding it. Safe to call with null pointer (no-op).
+///
+/// # Safety
+/// `shared` must be null or a pointer from `va_create_shared`, and no thread
+/// may use it afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn va_destroy_shared(shared: *mut SharedState) {
+    if let Some(handle) = shared_ref(shared) {
+        handle.state.acquire();
+        drop(take_handle(shared));
+    }
+}
+
+/// Takes the lock, waiting for other threads, and lends the State.
+///
+/// # Safety
+/// `shared` must be null or a valid pointer from `va_create_shared`.
+///
+/// # Returns
+/// The State pointer, usable with every State call until `va_shared_unlock`
+/// (never with `va_destroy`), or null for an invalid handle.
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_lock(shared: *const SharedState) -> *mut State {
+    let Some(shared) = shared_ref(shared) else {
+        return std::ptr::null_mut();
+    };
+    shared.state.acquire();
+    shared.state.as_ptr()
+}
+
+/// `va_shared_lock` without waiting: fails if another call or thread holds
+/// the lock.
+///
+/// # Safety
+/// `shared` must be null or a valid pointer from `va_create_shared`.
+///
+/// # Returns
+/// The State pointer, or null if the lock is held (last error
+/// `VA_ERR_BUSY`) or for an invalid handle.
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_try_lock(shared: *const SharedState) -> *mut State {
+    let Some(shared) = shared_ref(shared) else {
+        return std::ptr::null_mut();
+    };
+    if !shared.state.try_acquire() {
+        return fail(VA_ERR_BUSY, "shared State is locked", std::ptr::null_mut());
+    }
+    shared.state.as_ptr()
+}
+
+/// Releases the lock taken by `va_shared_lock` / `va_shared_try_lock`, from
+/// any thread. The lent State pointer must no longer be used. The lock of a
+/// `va_shared_*` call in progress on another thread is left alone.
+///
+/// # Safety
+/// `shared` must be null or a valid pointer from `va_create_shared`.
+///
+/// # Returns
+/// 0 on success, 1 if the lock was not held by `va_shared_lock` /
+/// `va_shared_try_lock` (last error `VA_ERR_INVALID_STATE`), -1 for an invalid
+/// handle.
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_unlock(shared: *const SharedState) -> i32 {
+    let Some(shared) = shared_ref(shared) else {
+        return -1;
+    };
+    if shared.state.release() {
+        0
+    } else {
+        fail(
+            VA_ERR_INVALID_STATE,
+            "shared State is not locked by va_shared_lock",
+            1,
+        )
+    }
+}
+
+/// Whether the lock is held, by a call in progress or explicitly. Only a hint:
+/// another thread may take or release it right after.
+///
+/// # Safety
+/// `shared` must be null or a valid pointer from `va_create_shared`.
+///
+/// # Returns
+/// 1 if locked, 0 if free, -1 for an invalid handle.
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_is_locked(shared: *const SharedState) -> i32 {
+    shared_ref(shared).map_or(-1, |shared| shared.state.is_locked() as i32)
+}
+
+/// `va_create_grid` under the lock.
+///
+/// # Safety
+/// `shared` must be null or a valid pointer from `va_create_shared`.
+///
+/// # Returns
+/// As `va_create_grid`.
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_create_grid(
+    shared: *const SharedState,
+    width: i16,
+    height: i16,
+    depth: i16,
+) -> i32 {
+    with_state(shared, GRID_ERR_NULL, |state| {
+        va_create_grid(state, width, height, depth)
+    })
+}
+
+/// `va_set_cell` under the lock.
+///
+/// # Safety
+/// `shared` must be null or a valid pointer from `va_create_shared`.
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_set_cell(
+    shared: *const SharedState,
+    x: i16,
+    y: i16,
+    z: i16,
+    alive: u8,
+) {
+    with_state(shared, (), |state| va_set_cell(state, x, y, z, alive))
+}
+
+/// `va_get_cell` under the lock.
+///
+/// # Safety
+/// `shared` must be null or a valid pointer from `va_create_shared`.
+///
+/// # Returns
+/// 1 if alive; 0 if dead, out of bounds, or for an invalid handle.
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_get_cell(
+    shared: *const SharedState,
+    x: i16,
+    y: i16,
+    z: i16,
+) -> u8 {
+    with_state(shared, 0, |state| va_get_cell(state, x, y, z))
+}
+
+/// `va_step` under the lock.
+///
+/// # Safety
+/// `shared` must be null or a valid pointer from `va_create_shared`.
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_step(shared: *const SharedState) {
+    with_state(shared, (), |state| va_step(state))
+}
+
+/// `va_shared_step` without waiting: skips the step if the lock is held.
+///
+/// # Safety
+/// `shared` must be null or a valid pointer from `va_create_shared`.
+///
+/// # Returns
+/// 0 if stepped, 1 if the lock is held (last error `VA_ERR_BUSY`), -1 for an
+/// invalid handle.
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_try_step(shared: *const SharedState) -> i32 {
+    let Some(shared) = shared_ref(shared) else {
+        return -1;
+    };
+    match shared.state.try_lock() {
+        Some(mut state) => {
+            va_step(&mut *state);
+            0
+        }
+        None => fail(VA_ERR_BUSY, "shared State is locked", 1),
+    }
+}
+
+/// `va_get_generation` under the lock.
+///
+/// # Safety
+/// `shared` must be null or a valid pointer from `va_create_shared`.
+///
+/// # Returns
+/// The generation, or 0 for an invalid handle.
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_get_generation(shared: *const SharedState) -> u64 {
+    with_state(shared, 0, |state| va_get_generation(state))
+}
+
+/// `va_extract_region` under the lock.
+///
+/// # Safety
+/// - `shared` must be null or a valid pointer from `va_create_shared`
+/// - `out_buf` must point to at least `buf_len` writable bytes, or be null
+/// - `out_generation` must be a valid writable pointer, or null (skipped)
+///
+/// # Returns
+/// As `va_extract_region`.
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_extract_region(
+    shared: *const SharedState,
+    out_buf: *mut u8,
+    buf_len: u64,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+    out_generation: *mut u64,
+) -> u64 {
+    with_state(shared, 0, |state| {
+        va_extract_region(
+            state,
+            out_buf,
+            buf_len,
+            min_x,
+            min_y,
+            min_z,
+            max_x,
+            max_y,
+            max_z,
+            out_generation,
+        )
+    })
+}
+
+/// `va_import_region` under the lock.
+///
+/// # Safety
+/// - `shared` must be null or a valid pointer from `va_create_shared`
+/// - `in_buf` must point to at least `buf_len` readable bytes, or be null
+///
+/// # Returns
+/// As `va_import_region`.
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_import_region(
+    shared: *const SharedState,
+    in_buf: *const u8,
+    buf_len: u64,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+) -> u64 {
+    with_state(shared, 0, |state| {
+        va_import_region(
+            state, in_buf, buf_len, min_x, min_y, min_z, max_x, max_y, max_z,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::error::{va_last_error_code, VA_ERR_INVALID_HANDLE};
+    use std::thread;
+
+    #[test]
+    fn test_shared_via_ffi() {
+        unsafe {
+            let shared = va_create_shared();
+            assert_eq!(va_shared_create_grid(shared, 4, 4, 4), 0);
+            va_shared_set_cell(shared, 1, 1, 1, 1);
+            assert_eq!(va_shared_get_cell(shared, 1, 1, 1), 1);
+
+            // Pointers are not Send: pass the address, as Lua states would
+            let address = shared as usize;
+            let workers: Vec<_> = (0..4)
+                .map(|_| {
+                    thread::spawn(move || {
+                        for _ in 0..10 {
+                            va_shared_step(address as *const SharedState);
+                        }
+                    })
+                })
+                .collect();
+            for worker in workers {
+                worker.join().unwrap();
+            }
+            assert_eq!(va_shared_get_generation(shared), 40);
+
+            // An explicit lock lends the State and turns away try calls
+            let state = va_shared_lock(shared);
+            assert!(!state.is_null());
+            assert_eq!(va_shared_is_locked(shared), 1);
+            va_step(state);
+            assert!(va_shared_try_lock(shared).is_null());
+            assert_eq!(va_shared_try_step(shared), 1);
+            assert_eq!(va_shared_unlock(shared), 0);
+            assert_eq!(va_shared_unlock(shared), 1);
+            assert_eq!(va_shared_try_step(shared), 0);
+            assert_eq!(va_shared_get_generation(shared), 42);
+
+            // A shared handle is not a State
+            assert_eq!(va_get_generation(shared.cast()), 0);
+            assert_eq!(va_last_error_code(), VA_ERR_INVALID_HANDLE);
+            va_destroy_shared(shared);
+            va_destroy_shared(std::ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_unlock_racing_a_call_fails() {
+        unsafe {
+            let shared = va_create_shared();
+            assert_eq!(va_shared_create_grid(shared, 8, 8, 8), 0);
+            let address = shared as usize;
+            let stepper = thread::spawn(move || {
+                for _ in 0..500 {
+                    va_shared_step(address as *const SharedState);
+                }
+            });
+            // Never an explicit hold, so each unlock fails instead of freeing
+            // the lock of a step in progress
+            while !stepper.is_finished() {
+                assert_eq!(va_shared_unlock(shared), 1);
+                assert_eq!(va_last_error_code(), VA_ERR_INVALID_STATE);
+            }
+            stepper.join().unwrap();
+            assert_eq!(va_shared_get_generation(shared), 500);
+            assert_eq!(va_shared_is_locked(shared), 0);
+            va_destroy_shared(shared);
+        }
+    }
+}
@@ -6,28 +6,214 @@
 //! The actual logic is in the `automaton` module. These functions are thin wrappers
 //! that handle null checks, pointer safety, and C-to-Rust conversions.
 
+pub mod activity;
+pub mod adaptive;
+pub mod age;
+pub mod audit;
+pub mod background;
+pub mod bind;
+pub mod bundle;
 pub mod cadence;
+pub mod castep;
+pub mod chunked;
+pub mod components;
+pub mod config;
+pub mod coupled;
+pub mod cycle;
+pub mod degrade;
+pub mod diff;
+pub mod error;
+pub mod fastforward;
 pub mod field;
+pub mod field64;
+pub mod ghost;
 pub mod grid;
+pub mod hash;
+pub mod history;
+pub mod ifield;
 pub mod incremental;
+pub mod infinite;
+pub mod lenia;
 pub mod lifecycle;
+pub mod locked;
+#[cfg(feature = "oracle")]
+pub mod oracle;
+pub mod pool;
+pub mod poststep;
+pub mod protect;
+pub mod raycast;
 pub mod region;
+pub mod registry;
+pub mod report;
+pub mod resample;
+pub mod search;
+pub mod selftest;
+pub mod shape;
+pub mod shared;
+#[cfg(feature = "shm")]
+pub mod shm;
 pub mod simple;
+pub mod snapshot;
+pub mod species;
+pub mod stack;
+pub mod stamp;
+pub mod stats;
+pub mod trace;
+pub mod transition;
+pub mod txn;
+pub mod wireworld;
+pub mod writes;
+pub(crate) mod validate;
 
+pub use activity::{va_sc_set_skip_quiet, va_sc_skipped_tiles};
+pub use adaptive::{
+    va_adaptive_coarsen, va_adaptive_get, va_adaptive_get_generation, va_adaptive_refine,
+    va_adaptive_refined_blocks, va_adaptive_set, va_adaptive_set_rounding,
+    va_adaptive_set_threshold, va_adaptive_step, va_adaptive_total, va_create_adaptive_field,
+    va_destroy_adaptive_field,
+};
+pub use age::{va_extract_age_region, va_get_cell_age, va_set_age_tracking};
+pub use audit::{va_field_step_checked, va_sc_audit_overflow};
+pub use background::{
+    va_async_destroy, va_async_extract_region, va_async_generation, va_async_get,
+    va_async_set_rate, va_async_stop, va_sc_start_async,
+};
+pub use bind::{va_bind_field, va_step_bound, va_unbind};
+pub use bundle::va_load_bundle;
 pub use cadence::{
     va_sc_cadence_advance, va_sc_cadence_bisect, va_sc_cadence_lookup, va_sc_cadence_merge_poll,
     va_sc_cadence_step, va_sc_global_tick, va_sc_infinity_create, va_sc_infinity_destroy,
 };
+pub use castep::{
+    va_ca_sc_begin_step, va_ca_sc_is_stepping, va_ca_sc_release, va_ca_sc_set_cell,
+    va_ca_sc_state, va_ca_sc_step_blocking, va_ca_sc_tick, va_create_ca_step_controller,
+    va_destroy_ca_step_controller,
+};
+pub use chunked::{va_create_field_chunked, va_field_chunk_count};
+pub use components::{va_flood_fill, va_label_components};
+pub use config::{va_field_get_config, va_field_set_config, va_get_config, va_set_config};
+pub use coupled::{
+    va_coupled_get_generation, va_coupled_register, va_coupled_set_coefficient,
+    va_coupled_set_matrix, va_coupled_step, va_create_coupled, va_destroy_coupled,
+};
+pub use cycle::{va_detect_cycle, va_run_until_stable};
+pub use degrade::{va_sc_get_degradations, va_sc_memory_usage, va_sc_set_memory_cap};
+pub use diff::{va_diff, va_field_diff};
+pub use error::{va_clear_last_error, va_last_error_code, va_last_error_message};
+pub use fastforward::{va_fast_forward, va_field_fast_forward};
 pub use field::{
-    va_create_field, va_destroy_field, va_field_get, va_field_get_generation, va_field_set,
-    va_field_step,
+    va_create_field, va_destroy_field, va_field_add_sink, va_field_add_source,
+    va_field_clear_sources, va_field_extract_region, va_field_get, va_field_get_algorithm,
+    va_field_get_flows, va_field_get_generation, va_field_get_phase, va_field_get_rounding,
+    va_field_import_region, va_field_max, va_field_mean, va_field_min, va_field_remove_source,
+    va_field_sample_batch, va_field_set, va_field_set_cells_bulk, va_field_set_advection, va_field_set_algorithm,
+    va_field_set_axis_rates, va_field_set_boundary, va_field_set_conductivity_curve,
+    va_field_set_flow_recording, va_field_set_periodic, va_field_set_phase_thresholds,
+    va_field_set_rounding, va_field_step, va_field_total,
+};
+pub use field64::{
+    va_create_field64, va_destroy_field64, va_field64_extract_region, va_field64_get,
+    va_field64_get_generation, va_field64_import_region, va_field64_set, va_field64_set_axis_rates,
+    va_field64_set_rounding, va_field64_step,
+};
+pub use ghost::{va_exchange_boundaries, va_field_exchange_boundaries};
+pub use grid::{
+    va_create_grid, va_get_cell, va_get_cells_bulk, va_get_cells_len, va_get_cells_ptr,
+    va_set_cell, va_set_cells_bulk, va_step, va_step_preview,
+};
+pub use hash::{va_field_hash, va_hash};
+pub use history::{va_enable_history, va_get_history_range, va_rollback};
+pub use ifield::{
+    va_create_ifield, va_destroy_ifield, va_ifield_extract_region, va_ifield_get,
+    va_ifield_get_generation, va_ifield_import_region, va_ifield_set, va_ifield_set_axis_rates,
+    va_ifield_set_rounding, va_ifield_step,
 };
-pub use grid::{va_create_grid, va_get_cell, va_set_cell, va_step};
 pub use incremental::{
-    va_create_step_controller, va_destroy_step_controller, va_sc_begin_step, va_sc_field_get,
-    va_sc_field_get_generation, va_sc_field_set, va_sc_is_stepping, va_sc_step_blocking,
-    va_sc_tick,
+    va_create_step_controller, va_destroy_step_controller, va_sc_begin_step, va_sc_clear_focus,
+    va_sc_field_get, va_sc_field_get_generation, va_sc_field_set, va_sc_get_tile_size,
+    va_sc_get_timing, va_sc_is_stepping, va_sc_pending_writes, va_sc_poll_event, va_sc_progress,
+    va_sc_set_axis_rates, va_sc_set_focus, va_sc_set_periodic, va_sc_set_rounding_seed,
+    va_sc_step_blocking, va_sc_tick, va_sc_tick_tiles,
+};
+pub use infinite::{
+    va_create_infinite, va_destroy_infinite, va_inf_extract_chunk, va_inf_get_cell,
+    va_inf_get_generation, va_inf_list_chunks, va_inf_population, va_inf_set_cell,
+    va_inf_set_rule, va_inf_step,
+};
+pub use lenia::va_field_step_lenia;
+pub use lifecycle::{
+    va_build_features, va_build_info, va_create, va_destroy, va_get_generation, va_reinit,
+};
+pub use locked::{
+    va_create_shared, va_destroy_shared, va_shared_create_grid, va_shared_extract_region,
+    va_shared_get_cell, va_shared_get_generation, va_shared_import_region, va_shared_is_locked,
+    va_shared_lock, va_shared_set_cell, va_shared_step, va_shared_try_lock, va_shared_try_step,
+    va_shared_unlock,
+};
+#[cfg(feature = "oracle")]
+pub use oracle::va_field_shadow_steps;
+pub use pool::{va_acquire_buffer, va_release_buffer, va_trim_buffer_pool};
+pub use poststep::{
+    va_sc_post_add_decay, va_sc_post_add_stats, va_sc_post_add_threshold, va_sc_post_clear,
+    va_sc_post_grid, va_sc_post_stats,
 };
-pub use lifecycle::{va_create, va_destroy, va_get_generation};
-pub use region::{va_extract_region, va_import_region};
+pub use protect::{
+    va_field_get_suppressed, va_field_protect_region, va_get_suppressed, va_protect_region,
+    va_sc_get_suppressed, va_sc_protect_region,
+};
+pub use raycast::{va_field_raycast, va_raycast};
+pub use region::{
+    va_clear, va_extract_mapblock, va_extract_region, va_extract_region_checked, va_fill_region,
+    va_import_mapblock, va_import_region, va_import_region_checked, va_randomize_region,
+};
+pub use registry::{
+    va_id_create, va_id_create_field, va_id_create_grid, va_id_create_step_controller,
+    va_id_destroy, va_id_extract_region, va_id_field_get, va_id_field_ptr, va_id_field_set,
+    va_id_field_step, va_id_field_total, va_id_get_cell, va_id_get_generation,
+    va_id_import_region, va_id_kind, va_id_list, va_id_sc_begin_step, va_id_sc_field_get,
+    va_id_sc_field_set, va_id_sc_is_stepping, va_id_sc_ptr, va_id_sc_step_blocking, va_id_sc_tick,
+    va_id_set_cell, va_id_state_ptr, va_id_step,
+};
+pub use report::{va_dump_debug_report, va_field_dump_debug_report};
+pub use resample::{va_field_aggregate, va_field_extract_downsampled, va_field_refine};
+pub use search::va_find_pattern;
+pub use selftest::{va_self_test, va_soak, va_soak_round};
+pub use shape::{
+    va_field_fill_box, va_field_fill_linear_gradient, va_field_fill_radial_gradient,
+    va_field_fill_shell, va_field_fill_sphere,
+};
+pub use shared::{
+    va_destroy_shared_field, va_field_share, va_reader_clone, va_reader_extract_region,
+    va_reader_get, va_reader_get_generation, va_reader_total, va_release_reader,
+    va_shared_field_reader, va_shared_field_readers, va_shared_field_set, va_shared_field_step,
+    va_shared_field_unshare,
+};
+#[cfg(feature = "shm")]
+pub use shm::{va_field_shm_export, va_shm_destroy, va_shm_path, va_shm_publish};
 pub use simple::va_add;
+pub use snapshot::{
+    va_deserialize, va_deserialize_compressed, va_export_rule_table, va_field_deserialize,
+    va_field_serialize, va_get_rule, va_serialize, va_serialize_compressed,
+    va_serialized_size_hint, va_set_rule, va_set_rule_string,
+};
+pub use species::{va_get_cell_species, va_set_cell_species, va_set_species_relation};
+pub use stack::{
+    va_create_field_stack, va_destroy_field_stack, va_field_stack_get,
+    va_field_stack_get_generation, va_field_stack_set, va_field_stack_set_layer,
+    va_field_stack_step,
+};
+pub use stamp::va_stamp;
+pub use stats::{
+    va_field_extract_thresholded, va_field_extract_zones, va_field_get_zone, va_field_histogram,
+    va_field_percentile, va_field_set_zones,
+};
+pub use trace::{va_field_explain, va_field_trace_cell, va_trace_cell};
+pub use transition::{va_set_cell_state, va_set_transition_table};
+pub use txn::{
+    va_txn_abort, va_txn_add_controller, va_txn_add_field, va_txn_add_state, va_txn_begin,
+    va_txn_commit,
+};
+pub use wireworld::{va_get_step_mode, va_set_step_mode};
+pub use writes::{
+    va_drain_writes, va_enable_write_queue, va_get_overflowed_writes, va_pending_writes,
+};
@@ -0,0 +1,90 @@
+//! FFI interface for shadow stepping against the big-integer oracle (see
+//! `automaton::oracle`).
+//!
+//! Only compiled with the `oracle` feature (on by default); check bit 512 of
+//! `va_build_features` before calling.
+
+use super::validate::{field_mut, write_opt};
+use crate::automaton::field::Field;
+use crate::automaton::oracle::field_shadow_steps;
+
+/// i64 slots in a shadow report.
+pub const SHADOW_REPORT_LEN: usize = 6;
+
+/// Steps the field up to `steps` times like `va_field_step`, checking each
+/// step's diffusion against an exact big-integer recomputation. Stops after
+/// the first step that disagrees; the field keeps the normal fixed-point
+/// result either way. Much slower than a plain step: shadow a few steps now
+/// and then, not every tick.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `out_report` must point to 6 writable i64 values, or be null (skipped);
+///   on a mismatch it receives [generation, x, y, z, expected, actual], the
+///   expected value saturated to i64
+///
+/// # Returns
+/// 0 if every step agreed, 1 on a mismatch, -1 for a null field.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_shadow_steps(
+    field: *mut Field,
+    steps: u64,
+    out_report: *mut i64,
+) -> i32 {
+    let Some(field) = field_mut(field) else {
+        return -1;
+    };
+    let Some(mismatch) = field_shadow_steps(field, steps).1 else {
+        return 0;
+    };
+    let [x, y, z] = mismatch.cell.map(|c| c as i64);
+    let expected = mismatch.expected.clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+    let values = [
+        mismatch.generation as i64,
+        x,
+        y,
+        z,
+        expected,
+        mismatch.actual as i64,
+    ];
+    write_opt(out_report as *mut [i64; SHADOW_REPORT_LEN], values);
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::RoundingMode;
+    use crate::ffi::field::{
+        va_create_field, va_destroy_field, va_field_set, va_field_set_rounding,
+    };
+    use std::ptr;
+
+    #[test]
+    fn test_shadow_steps_via_ffi() {
+        unsafe {
+            let field = va_create_field(4, 2, 2, 2);
+            va_field_set(field, 1, 1, 1, 90_000);
+            let mut report = [-7i64; SHADOW_REPORT_LEN];
+            assert_eq!(va_field_shadow_steps(field, 10, report.as_mut_ptr()), 0);
+            assert_eq!(report, [-7; SHADOW_REPORT_LEN]);
+
+            // Hash rounding wraps one of these cells past u32::MAX
+            let brim = va_create_field(5, 5, 5, 0);
+            (*brim).cells.fill(u32::MAX);
+            va_field_set_rounding(brim, RoundingMode::Hash as u8);
+            for (x, y, z) in [(2, 2, 2), (1, 1, 1), (3, 3, 3), (1, 3, 2)] {
+                va_field_set(brim, x, y, z, u32::MAX - 1);
+            }
+            assert_eq!(va_field_shadow_steps(brim, 10, report.as_mut_ptr()), 1);
+            assert_eq!(report, [1, 1, 3, 2, 1 << 32, 0]);
+            va_destroy_field(brim);
+
+            assert_eq!(
+                va_field_shadow_steps(ptr::null_mut(), 1, ptr::null_mut()),
+                -1
+            );
+            va_destroy_field(field);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Pooled extraction buffers.
+//!
+//! Lua acquires a buffer once, passes it to the extraction calls, and releases
+//! it when done, instead of `ffi.new`-ing a fresh multi-megabyte array each time.
+//! The pool is process-wide and shared by all States.
+
+use std::sync::{LazyLock, Mutex, MutexGuard};
+
+use super::validate::write_opt;
+use crate::automaton::pool::BufferPool;
+
+static POOL: LazyLock<Mutex<BufferPool>> = LazyLock::new(|| Mutex::new(BufferPool::new()));
+
+fn pool() -> MutexGuard<'static, BufferPool> {
+    // A panic while holding the lock cannot leave the pool inconsistent
+    // (every operation is a single insert/remove), so ignore poisoning.
+    POOL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Free every pooled buffer, including ones still acquired (see `va_reinit`).
+/// Returns the number of acquired buffers that were reclaimed.
+pub(crate) fn reset_pool() -> usize {
+    pool().reset()
+}
+
+/// Borrows a buffer of at least `min_size` bytes from the shared pool.
+///
+/// Sizes are rounded up to a power of two (at least 4096), so repeated
+/// extractions of the same region reuse the same allocation. Contents are
+/// unspecified.
+///
+/// # Safety
+/// - `out_capacity` must be a valid writable pointer, or null (skipped)
+/// - The buffer must be returned with `va_release_buffer()` and not used after
+///
+/// # Returns
+/// Pointer to the buffer (its real size is written to `out_capacity`), or null
+/// if `min_size` exceeds 1 GiB.
+#[no_mangle]
+pub unsafe extern "C" fn va_acquire_buffer(min_size: u64, out_capacity: *mut u64) -> *mut u8 {
+    let Ok(size) = usize::try_from(min_size) else {
+        return std::ptr::null_mut();
+    };
+    match pool().acquire(size) {
+        Some((buf, capacity)) => {
+            write_opt(out_capacity, capacity as u64);
+            buf
+        }
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Returns a buffer obtained from `va_acquire_buffer()` to the pool.
+///
+/// # Safety
+/// `buf` must not be used after this call. Null, foreign, or already released
+/// pointers are rejected without touching memory.
+///
+/// # Returns
+/// 0 on success, 1 if `buf` is not an outstanding pool buffer.
+#[no_mangle]
+pub unsafe extern "C" fn va_release_buffer(buf: *mut u8) -> i32 {
+    if pool().release(buf) {
+        0
+    } else {
+        1
+    }
+}
+
+/// Frees all idle pooled buffers (e.g. after closing a large visualization).
+/// Buffers currently acquired are unaffected.
+#[no_mangle]
+pub extern "C" fn va_trim_buffer_pool() {
+    pool().trim();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn test_acquire_extract_release() {
+        unsafe {
+            let state = crate::ffi::lifecycle::va_create();
+            crate::ffi::grid::va_create_grid(state, 32, 32, 32);
+            crate::ffi::grid::va_set_cell(state, 31, 31, 31, 1);
+
+            let mut capacity = 0u64;
+            let buf = va_acquire_buffer(32 * 32 * 32, &mut capacity);
+            assert!(!buf.is_null());
+            assert_eq!(capacity, 32768);
+
+            let bytes = crate::ffi::region::va_extract_region(
+                state,
+                buf,
+                capacity,
+                0,
+                0,
+                0,
+                32,
+                32,
+                32,
+                ptr::null_mut(),
+            );
+            assert_eq!(bytes, 32768);
+            assert_eq!(*buf.add(32767), 1);
+
+            assert_eq!(va_release_buffer(buf), 0);
+            assert_eq!(va_release_buffer(buf), 1);
+            assert_eq!(va_release_buffer(ptr::null_mut()), 1);
+
+            assert!(va_acquire_buffer(u64::MAX, ptr::null_mut()).is_null());
+            va_trim_buffer_pool();
+
+            // va_reinit is covered by BufferPool::reset and IdSlab::clear: the
+            // pool and registry are shared with tests running in parallel, so
+            // a reinit here would free their buffers and ids
+
+            crate::ffi::lifecycle::va_destroy(state);
+        }
+    }
+}
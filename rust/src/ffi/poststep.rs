@@ -0,0 +1,191 @@
+//! FFI interface for post-step pipelines on a StepController.
+
+use super::validate::{ctrl_mut, ctrl_ref, write_opt};
+use crate::automaton::incremental::StepController;
+use crate::automaton::poststep::{FieldStats, PostStepOp};
+use crate::state::State;
+
+/// Append `op` to the controller's pipeline.
+unsafe fn add_op(ctrl: *mut StepController, op: impl FnOnce(&StepController) -> PostStepOp) -> i32 {
+    let Some(ctrl) = ctrl_mut(ctrl) else {
+        return -1;
+    };
+    let op = op(ctrl);
+    match ctrl.post_step.push(op) {
+        Some(index) => index as i32,
+        None => -1,
+    }
+}
+
+/// Appends a threshold coupling: after every step, a grid owned by the
+/// controller is rewritten so cells whose field value is at least `threshold`
+/// are alive and all others dead (read it with `va_sc_post_grid`).
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// Index of the operation, or -1 if null pointer or the pipeline is full (8 ops).
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_post_add_threshold(
+    ctrl: *mut StepController,
+    threshold: u32,
+) -> i32 {
+    add_op(ctrl, |ctrl| {
+        PostStepOp::threshold_couple(&ctrl.field, threshold)
+    })
+}
+
+/// Appends a decay: after every step each cell loses `value >> shift`, never
+/// dropping below 1.
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// Index of the operation, or -1 if null pointer or the pipeline is full.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_post_add_decay(ctrl: *mut StepController, shift: u8) -> i32 {
+    add_op(ctrl, |_| PostStepOp::Decay { shift })
+}
+
+/// Appends a statistics pass (total, min, max) over the field after every
+/// step; read the result with `va_sc_post_stats`.
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// Index of the operation, or -1 if null pointer or the pipeline is full.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_post_add_stats(ctrl: *mut StepController) -> i32 {
+    add_op(ctrl, |_| PostStepOp::Statistics(FieldStats::default()))
+}
+
+/// Removes every post-step operation. Grid pointers from `va_sc_post_grid`
+/// become invalid.
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// 0 on success, -1 if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_post_clear(ctrl: *mut StepController) -> i32 {
+    let Some(ctrl) = ctrl_mut(ctrl) else {
+        return -1;
+    };
+    ctrl.post_step.ops.clear();
+    0
+}
+
+/// Borrows the grid written by the threshold coupling at `index`, for use
+/// with the read-only State functions (`va_get_cell`, `va_extract_region`,
+/// `va_get_generation`, ...). Its generation is the field generation it was
+/// computed from.
+///
+/// The pointer stays owned by the controller: never pass it to `va_destroy`
+/// or a mutating function. It is valid until `va_sc_post_clear` or
+/// `va_destroy_step_controller`.
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// The grid, or null if `ctrl` is null or `index` is not a threshold coupling.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_post_grid(ctrl: *const StepController, index: u32) -> *const State {
+    let Some(ctrl) = ctrl_ref(ctrl) else {
+        return std::ptr::null();
+    };
+    match ctrl.post_step.ops.get(index as usize) {
+        Some(PostStepOp::ThresholdCouple { grid, .. }) => &**grid,
+        _ => std::ptr::null(),
+    }
+}
+
+/// Reads the result of the statistics operation at `index`. All zero until
+/// the first step completes.
+///
+/// # Safety
+/// - `ctrl` must be null or a valid StepController pointer
+/// - The out-params must be valid writable pointers, or null (skipped)
+///
+/// # Returns
+/// 0 on success, 1 if `index` is not a statistics operation, -1 if null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_post_stats(
+    ctrl: *const StepController,
+    index: u32,
+    out_generation: *mut u64,
+    out_total: *mut u64,
+    out_min: *mut u32,
+    out_max: *mut u32,
+) -> i32 {
+    let Some(ctrl) = ctrl_ref(ctrl) else {
+        return -1;
+    };
+    let Some(PostStepOp::Statistics(stats)) = ctrl.post_step.ops.get(index as usize) else {
+        return 1;
+    };
+    write_opt(out_generation, stats.generation);
+    write_opt(out_total, stats.total);
+    write_opt(out_min, stats.min);
+    write_opt(out_max, stats.max);
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::grid::va_get_cell;
+    use crate::ffi::incremental::{
+        va_create_step_controller, va_destroy_step_controller, va_sc_field_set, va_sc_step_blocking,
+    };
+    use crate::ffi::lifecycle::va_get_generation;
+    use std::ptr;
+
+    #[test]
+    fn test_post_step_pipeline_via_ffi() {
+        let ctrl = va_create_step_controller(16, 16, 16, 2, 1, 0);
+        unsafe {
+            assert_eq!(va_sc_post_add_threshold(ctrl, 500), 0);
+            assert_eq!(va_sc_post_add_stats(ctrl), 1);
+            assert!(va_sc_post_grid(ctrl, 1).is_null());
+            assert_eq!(
+                va_sc_post_stats(
+                    ctrl,
+                    0,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    ptr::null_mut()
+                ),
+                1
+            );
+
+            va_sc_field_set(ctrl, 8, 8, 8, 1_000_000);
+            va_sc_step_blocking(ctrl);
+
+            let grid = va_sc_post_grid(ctrl, 0);
+            assert_eq!(va_get_generation(grid), 1);
+            assert_eq!(va_get_cell(grid, 8, 8, 8), 1);
+            assert_eq!(va_get_cell(grid, 0, 0, 0), 0);
+
+            let (mut generation, mut total, mut min, mut max) = (0u64, 0u64, 0u32, 0u32);
+            assert_eq!(
+                va_sc_post_stats(ctrl, 1, &mut generation, &mut total, &mut min, &mut max),
+                0
+            );
+            assert_eq!(generation, 1);
+            assert_eq!(total, 16 * 16 * 16 - 1 + 1_000_000);
+            assert!(min >= 1 && max < 1_000_000);
+
+            assert_eq!(va_sc_post_clear(ctrl), 0);
+            assert!(va_sc_post_grid(ctrl, 0).is_null());
+            assert_eq!(va_sc_post_add_decay(ptr::null_mut(), 1), -1);
+            assert_eq!(va_sc_post_clear(ptr::null_mut()), -1);
+        }
+        va_destroy_step_controller(ctrl);
+    }
+}
@@ -0,0 +1,204 @@
+//! FFI interface for protection masks (see `automaton::protect`).
+//!
+//! Boxes are half-open `[min, max)` and clamped to the grid, as in
+//! `va_fill_region`. The mask is vetoed at commit time by `va_step`,
+//! `va_field_step`, and the StepController.
+
+use super::validate::{ctrl_mut, ctrl_ref, field_mut, field_ref, state_mut, state_ref};
+use crate::automaton::field::Field;
+use crate::automaton::incremental::StepController;
+use crate::automaton::protect::{protect_region, Protection};
+use crate::state::State;
+
+fn suppressed(protection: &Option<Protection>) -> u64 {
+    protection
+        .as_ref()
+        .map_or(0, |protection| protection.suppressed)
+}
+
+/// Protects (`protected` nonzero) or releases a box of the grid: protected
+/// dead cells are never born.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// Number of cells in the clamped box, or 0 on error (null pointer, no grid,
+/// or a box outside the grid).
+#[no_mangle]
+pub unsafe extern "C" fn va_protect_region(
+    ptr: *mut State,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+    protected: u8,
+) -> u64 {
+    let Some(state) = state_mut(ptr) else {
+        return 0;
+    };
+    let dims = [state.width, state.height, state.depth];
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    protect_region(&mut state.protection, dims, min, max, protected != 0)
+}
+
+/// Returns the number of births suppressed by the grid's protection mask.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// The tally since the mask was created, or 0 (null pointer, no mask).
+#[no_mangle]
+pub unsafe extern "C" fn va_get_suppressed(ptr: *const State) -> u64 {
+    state_ref(ptr).map_or(0, |state| suppressed(&state.protection))
+}
+
+/// Protects (`protected` nonzero) or releases a box of the field: a protected
+/// cell never gains value from diffusion or advection. The withheld value is
+/// removed from the field and tallied.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+///
+/// # Returns
+/// Number of cells in the clamped box, or 0 on error (null pointer or a box
+/// outside the field).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_protect_region(
+    field: *mut Field,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+    protected: u8,
+) -> u64 {
+    let Some(field) = field_mut(field) else {
+        return 0;
+    };
+    let dims = [field.width, field.height, field.depth];
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    protect_region(&mut field.protection, dims, min, max, protected != 0)
+}
+
+/// Returns the total value the field's protection mask has withheld.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+///
+/// # Returns
+/// The tally since the mask was created, or 0 (null pointer, no mask).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_get_suppressed(field: *const Field) -> u64 {
+    field_ref(field).map_or(0, |field| suppressed(&field.protection))
+}
+
+/// `va_field_protect_region` on the controller's field. Applies from the next
+/// step to commit, including one already in progress.
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// Number of cells in the clamped box, or 0 on error.
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_protect_region(
+    ctrl: *mut StepController,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+    protected: u8,
+) -> u64 {
+    let Some(ctrl) = ctrl_mut(ctrl) else {
+        return 0;
+    };
+    let field = &mut ctrl.field;
+    let dims = [field.width, field.height, field.depth];
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    protect_region(&mut field.protection, dims, min, max, protected != 0)
+}
+
+/// `va_field_get_suppressed` on the controller's field.
+///
+/// # Safety
+/// `ctrl` must be null or a valid StepController pointer.
+///
+/// # Returns
+/// The tally since the mask was created, or 0 (null pointer, no mask).
+#[no_mangle]
+pub unsafe extern "C" fn va_sc_get_suppressed(ctrl: *const StepController) -> u64 {
+    ctrl_ref(ctrl).map_or(0, |ctrl| suppressed(&ctrl.field.protection))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{va_create_field, va_destroy_field, va_field_set, va_field_step};
+    use crate::ffi::grid::{va_create_grid, va_get_cell, va_set_cell, va_step};
+    use crate::ffi::incremental::{
+        va_create_step_controller, va_destroy_step_controller, va_sc_field_set, va_sc_step_blocking,
+    };
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use crate::ffi::snapshot::{va_deserialize, va_serialize};
+    use std::ptr;
+
+    #[test]
+    fn test_protection_via_ffi() {
+        unsafe {
+            let state = va_create();
+            assert_eq!(va_protect_region(state, 0, 0, 0, 4, 4, 4, 1), 0);
+            va_create_grid(state, 8, 8, 8);
+            assert_eq!(va_protect_region(state, -2, -2, 5, 10, 10, 8, 1), 8 * 8 * 3);
+            // B4/S4 plus shape: births on both sides of z = 5
+            for (x, y) in [(4, 4), (3, 4), (5, 4), (4, 3), (4, 5)] {
+                va_set_cell(state, x, y, 4, 1);
+            }
+            let mut blob = [0u8; 1024];
+            let len = va_serialize(state, blob.as_mut_ptr(), 1024);
+            va_step(state);
+            assert!(va_get_suppressed(state) > 0);
+            assert!((0..8).all(|x| (0..8).all(|y| va_get_cell(state, x, y, 5) == 0)));
+
+            // A restored snapshot keeps the handle's mask and tally
+            let tally = va_get_suppressed(state);
+            assert_eq!(va_deserialize(state, blob.as_ptr(), len), 0);
+            assert_eq!(va_get_suppressed(state), tally);
+            va_step(state);
+            assert_eq!(va_get_suppressed(state), tally * 2);
+            assert_eq!(va_get_suppressed(ptr::null()), 0);
+            va_destroy(state);
+
+            let field = va_create_field(8, 4, 4, 1);
+            va_field_set(field, 1, 1, 1, 100_000);
+            assert_eq!(va_field_protect_region(field, 4, 0, 0, 8, 4, 4, 1), 64);
+            assert_eq!(va_field_get_suppressed(field), 0);
+            for _ in 0..30 {
+                va_field_step(field);
+            }
+            let total: u64 = (*field).cells.iter().map(|&v| v as u64).sum();
+            assert_eq!(total + va_field_get_suppressed(field), 100_000 + 127);
+            assert_eq!(
+                va_field_protect_region(ptr::null_mut(), 0, 0, 0, 1, 1, 1, 1),
+                0
+            );
+            va_destroy_field(field);
+
+            let ctrl = va_create_step_controller(8, 4, 4, 1, 1, 0);
+            va_sc_field_set(ctrl, 1, 1, 1, 100_000);
+            assert_eq!(va_sc_protect_region(ctrl, 4, 0, 0, 8, 4, 4, 1), 64);
+            for _ in 0..30 {
+                va_sc_step_blocking(ctrl);
+            }
+            assert!(va_sc_get_suppressed(ctrl) > 0);
+            assert_eq!(va_sc_get_suppressed(ptr::null()), 0);
+            va_destroy_step_controller(ctrl);
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! FFI interface for ray queries (see `automaton::raycast`).
+
+use super::error::{fail, VA_ERR_BUFFER, VA_ERR_INVALID_ARGUMENT};
+use super::validate::{buf_mut, buf_ref, field_ref, state_ref, write_opt};
+use crate::automaton::field::Field;
+use crate::automaton::raycast::{field_raycast, raycast, RayError, RayHit};
+use crate::state::State;
+
+/// Read the ray arguments, write the outcome, and map it to the return code.
+unsafe fn report_ray(
+    origin: *const f64,
+    direction: *const f64,
+    out_hit: *mut i16,
+    out_distance: *mut f64,
+    cast: impl FnOnce([f64; 3], [f64; 3]) -> Result<Option<RayHit>, RayError>,
+) -> i32 {
+    let (Some(origin), Some(direction), Some(out)) = (
+        buf_ref(origin, 3),
+        buf_ref(direction, 3),
+        buf_mut(out_hit, 3),
+    ) else {
+        return fail(VA_ERR_BUFFER, "null origin, direction or hit array", -1);
+    };
+    let origin = [origin[0], origin[1], origin[2]];
+    let direction = [direction[0], direction[1], direction[2]];
+    match cast(origin, direction) {
+        Ok(Some(hit)) => {
+            out.copy_from_slice(&hit.cell);
+            write_opt(out_distance, hit.distance);
+            1
+        }
+        Ok(None) => 0,
+        Err(RayError::ZeroDirection) => fail(VA_ERR_INVALID_ARGUMENT, "zero ray direction", -1),
+        Err(RayError::NotFinite) => fail(
+            VA_ERR_INVALID_ARGUMENT,
+            "non-finite ray origin or direction, or negative maximum distance",
+            -1,
+        ),
+    }
+}
+
+/// Traces a ray through the grid (3D DDA) and finds the first live cell, for
+/// line-of-sight queries. Positions are in cell units: cell (x, y, z) covers
+/// [x, x + 1) on each axis, so its center is (x + 0.5, y + 0.5, z + 0.5). A
+/// ray starting outside the grid is clipped to it.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `origin` and `direction` must each point to 3 readable f64 values (x, y,
+///   z); the direction need not be normalized
+/// - `out_hit` must point to 3 writable i16 values, written on a hit
+/// - `out_distance` must be a valid writable pointer, or null (skipped);
+///   receives the distance from the origin to where the ray enters the hit
+///   cell
+///
+/// # Returns
+/// 1 on a hit within `max_dist`, 0 on a miss, -1 on error (null pointer,
+/// zero or non-finite direction, non-finite origin, negative `max_dist`).
+#[no_mangle]
+pub unsafe extern "C" fn va_raycast(
+    ptr: *const State,
+    origin: *const f64,
+    direction: *const f64,
+    max_dist: f64,
+    out_hit: *mut i16,
+    out_distance: *mut f64,
+) -> i32 {
+    let Some(state) = state_ref(ptr) else {
+        return -1;
+    };
+    report_ray(
+        origin,
+        direction,
+        out_hit,
+        out_distance,
+        |origin, direction| raycast(state, origin, direction, max_dist),
+    )
+}
+
+/// `va_raycast` for fields: finds the first cell whose value is above
+/// `threshold` (e.g. where smoke becomes dense enough to see).
+///
+/// # Safety
+/// As `va_raycast`, with `field` a valid pointer to a Field, or null.
+///
+/// # Returns
+/// As `va_raycast`.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_raycast(
+    field: *const Field,
+    origin: *const f64,
+    direction: *const f64,
+    max_dist: f64,
+    threshold: u32,
+    out_hit: *mut i16,
+    out_distance: *mut f64,
+) -> i32 {
+    let Some(field) = field_ref(field) else {
+        return -1;
+    };
+    report_ray(
+        origin,
+        direction,
+        out_hit,
+        out_distance,
+        |origin, direction| field_raycast(field, origin, direction, max_dist, threshold),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{va_create_field, va_destroy_field, va_field_set};
+    use crate::ffi::grid::{va_create_grid, va_set_cell};
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use std::ptr;
+
+    #[test]
+    fn test_raycast_via_ffi() {
+        unsafe {
+            let state = va_create();
+            va_create_grid(state, 8, 8, 8);
+            va_set_cell(state, 2, 6, 3, 1);
+            let (origin, down) = ([2.5, 20.0, 3.5], [0.0, -1.0, 0.0]);
+            let mut hit = [0i16; 3];
+            let mut distance = 0.0;
+            assert_eq!(
+                va_raycast(
+                    state,
+                    origin.as_ptr(),
+                    down.as_ptr(),
+                    100.0,
+                    hit.as_mut_ptr(),
+                    &mut distance
+                ),
+                1
+            );
+            assert_eq!((hit, distance), ([2, 6, 3], 13.0));
+            let miss = va_raycast(
+                state,
+                origin.as_ptr(),
+                down.as_ptr(),
+                10.0,
+                hit.as_mut_ptr(),
+                ptr::null_mut(),
+            );
+            assert_eq!(miss, 0);
+            let zero = [0.0; 3];
+            let invalid = va_raycast(
+                state,
+                origin.as_ptr(),
+                zero.as_ptr(),
+                10.0,
+                hit.as_mut_ptr(),
+                ptr::null_mut(),
+            );
+            assert_eq!(invalid, -1);
+            let no_out = va_raycast(
+                state,
+                origin.as_ptr(),
+                down.as_ptr(),
+                10.0,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            assert_eq!(no_out, -1);
+            va_destroy(state);
+
+            let field = va_create_field(4, 4, 4, 2);
+            va_field_set(field, 3, 1, 1, 900);
+            let (origin, east) = ([0.5, 1.5, 1.5], [1.0, 0.0, 0.0]);
+            let result = va_field_raycast(
+                field,
+                origin.as_ptr(),
+                east.as_ptr(),
+                10.0,
+                100,
+                hit.as_mut_ptr(),
+                ptr::null_mut(),
+            );
+            assert_eq!((result, hit), (1, [3, 1, 1]));
+            va_destroy_field(field);
+        }
+    }
+}
@@ -1,6 +1,8 @@
-//! Region extraction and import FFI functions.
+//! Region extraction, import, mapblock, and bulk fill FFI functions.
 
-use crate::automaton;
+use super::error::{fail, VA_ERR_BUFFER};
+use super::validate::{buf_mut, buf_ref, region_volume, state_mut, state_ref, write_opt};
+use crate::automaton::{self, MAPBLOCK_VOLUME};
 use crate::state::State;
 
 /// Extracts a rectangular region of cells into a flat output buffer.
@@ -9,38 +11,46 @@ use crate::state::State;
 /// The buffer is filled in z,y,x order (z changes slowest, x changes fastest).
 /// This matches the layout expected by `va_import_region`.
 ///
+/// # Generation tag
+/// If `out_generation` is non-null it receives the generation the extracted
+/// cells belong to, so a consumer rendering asynchronously can tell when its
+/// copy is stale. The tag is written whenever `ptr` is valid, even if the
+/// extraction itself fails.
+///
 /// # Safety
 /// - `ptr` must be a valid pointer to a State with a grid, or null
-/// - `out_buf` must point to a buffer with at least
-///   `(max_x - min_x) * (max_y - min_y) * (max_z - min_z)` bytes
+/// - `out_buf` must point to at least `buf_len` writable bytes, or be null
+/// - `out_generation` must be a valid writable pointer, or null (skipped)
 ///
 /// # Returns
-/// Number of bytes written, or 0 on error.
+/// Number of bytes written, or 0 on error (null pointer, inverted region, or
+/// `buf_len` smaller than the region clamped to the grid).
 #[no_mangle]
 pub unsafe extern "C" fn va_extract_region(
     ptr: *const State,
     out_buf: *mut u8,
+    buf_len: u64,
     min_x: i16,
     min_y: i16,
     min_z: i16,
     max_x: i16,
     max_y: i16,
     max_z: i16,
+    out_generation: *mut u64,
 ) -> u64 {
-    if ptr.is_null() || out_buf.is_null() {
+    let Some(state) = state_ref(ptr) else {
         return 0;
-    }
-
-    let state = &*ptr;
-    if state.cells.is_empty() {
+    };
+    write_opt(out_generation, state.generation);
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    if region_volume(min, max).is_none() {
         return 0;
     }
+    let required = automaton::region_size(state, min, max);
+    let Some(buf_slice) = buf_mut(out_buf, buf_len).filter(|buf| buf.len() >= required) else {
+        return region_buffer_error(buf_len, required);
+    };
 
-    let width = ((max_x - min_x).max(0)) as usize;
-    let height = ((max_y - min_y).max(0)) as usize;
-    let depth = ((max_z - min_z).max(0)) as usize;
-
-    let buf_slice = std::slice::from_raw_parts_mut(out_buf, width * height * depth);
     automaton::extract_region(state, buf_slice, min_x, min_y, min_z, max_x, max_y, max_z)
 }
 
@@ -52,15 +62,16 @@ pub unsafe extern "C" fn va_extract_region(
 ///
 /// # Safety
 /// - `ptr` must be a valid pointer to a State with a grid, or null
-/// - `in_buf` must point to a buffer with at least
-///   `(max_x - min_x) * (max_y - min_y) * (max_z - min_z)` bytes
+/// - `in_buf` must point to at least `buf_len` readable bytes, or be null
 ///
 /// # Returns
-/// Number of bytes read, or 0 on error.
+/// Number of bytes read, or 0 on error (null pointer, inverted region, or
+/// `buf_len` smaller than the region clamped to the grid).
 #[no_mangle]
 pub unsafe extern "C" fn va_import_region(
     ptr: *mut State,
     in_buf: *const u8,
+    buf_len: u64,
     min_x: i16,
     min_y: i16,
     min_z: i16,
@@ -68,18 +79,265 @@ pub unsafe extern "C" fn va_import_region(
     max_y: i16,
     max_z: i16,
 ) -> u64 {
-    if ptr.is_null() || in_buf.is_null() {
+    let Some(state) = state_mut(ptr) else {
+        return 0;
+    };
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    if region_volume(min, max).is_none() {
         return 0;
     }
+    let required = automaton::region_size(state, min, max);
+    let Some(buf_slice) = buf_ref(in_buf, buf_len).filter(|buf| buf.len() >= required) else {
+        return region_buffer_error(buf_len, required);
+    };
 
-    let state = &mut *ptr;
+    automaton::import_region(state, buf_slice, min_x, min_y, min_z, max_x, max_y, max_z)
+}
 
-    let width = ((max_x - min_x).max(0)) as usize;
-    let height = ((max_y - min_y).max(0)) as usize;
-    let depth = ((max_z - min_z).max(0)) as usize;
+/// Record a region buffer of `len` bytes that is null or shorter than the
+/// `required` ones, and return 0.
+fn region_buffer_error(len: u64, required: usize) -> u64 {
+    fail(
+        VA_ERR_BUFFER,
+        format!("region buffer of {len} bytes, or null; the region needs {required}"),
+        0,
+    )
+}
 
-    let buf_slice = std::slice::from_raw_parts(in_buf, width * height * depth);
-    automaton::import_region(state, buf_slice, min_x, min_y, min_z, max_x, max_y, max_z)
+/// Extracts a region like `va_extract_region`, but reports the size it needs.
+///
+/// Lets Lua size buffers dynamically: call once with a null `out_buf` to get the
+/// required size, allocate, then call again. The region is clamped to the grid,
+/// so the required size may be smaller than the requested box. `out_generation`
+/// is tagged as in `va_extract_region`.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State with a grid, or null
+/// - `out_buf` must point to at least `buf_len` writable bytes, or be null
+/// - `out_generation` must be a valid writable pointer, or null (skipped)
+///
+/// # Returns
+/// The required buffer size in bytes. The region is written only if `out_buf`
+/// is non-null and `buf_len` is at least that size, so the call succeeded iff
+/// `0 < result <= buf_len`. Returns 0 on error (null state, inverted region) or
+/// when the clamped region is empty.
+#[no_mangle]
+pub unsafe extern "C" fn va_extract_region_checked(
+    ptr: *const State,
+    out_buf: *mut u8,
+    buf_len: u64,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+    out_generation: *mut u64,
+) -> u64 {
+    let Some(state) = state_ref(ptr) else {
+        return 0;
+    };
+    write_opt(out_generation, state.generation);
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    if region_volume(min, max).is_none() {
+        return 0;
+    }
+    let required = automaton::region_size(state, min, max) as u64;
+    if required == 0 || buf_len < required {
+        return required;
+    }
+    if let Some(buf_slice) = buf_mut(out_buf, buf_len) {
+        automaton::extract_region(state, buf_slice, min_x, min_y, min_z, max_x, max_y, max_z);
+    }
+    required
+}
+
+/// Imports a region like `va_import_region`, but reports the size it needs.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State with a grid, or null
+/// - `in_buf` must point to at least `buf_len` readable bytes, or be null
+///
+/// # Returns
+/// The required buffer size in bytes. The grid is modified only if `in_buf` is
+/// non-null and `buf_len` is at least that size, so the call succeeded iff
+/// `0 < result <= buf_len`. Returns 0 on error (null state, inverted region) or
+/// when the clamped region is empty.
+#[no_mangle]
+pub unsafe extern "C" fn va_import_region_checked(
+    ptr: *mut State,
+    in_buf: *const u8,
+    buf_len: u64,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+) -> u64 {
+    let Some(state) = state_mut(ptr) else {
+        return 0;
+    };
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    if region_volume(min, max).is_none() {
+        return 0;
+    }
+    let required = automaton::region_size(state, min, max) as u64;
+    if required == 0 || buf_len < required {
+        return required;
+    }
+    if let Some(buf_slice) = buf_ref(in_buf, buf_len) {
+        automaton::import_region(state, buf_slice, min_x, min_y, min_z, max_x, max_y, max_z);
+    }
+    required
+}
+
+/// Extracts one 16x16x16 Luanti mapblock, addressed in block coordinates.
+///
+/// Block `(bx, by, bz)` covers grid cells `[16*b, 16*b + 16)` on each axis.
+/// Block coordinates are 64-bit so Lua can pass `floor(pos / 16)` for any
+/// world position without overflow.
+///
+/// # Layout
+/// z,y,x order (x fastest), matching VoxelManip / VoxelArea iteration order.
+/// Cells of the block outside the grid read as dead. `out_generation` is
+/// tagged as in `va_extract_region`.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State with a grid, or null
+/// - `out_buf` must point to at least 4096 writable bytes, or be null
+/// - `out_generation` must be a valid writable pointer, or null (skipped)
+///
+/// # Returns
+/// Number of block cells inside the grid (0..=4096; the whole buffer is written
+/// regardless), or 0 on error (null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_extract_mapblock(
+    ptr: *const State,
+    bx: i64,
+    by: i64,
+    bz: i64,
+    out_buf: *mut u8,
+    out_generation: *mut u64,
+) -> u64 {
+    let Some(state) = state_ref(ptr) else {
+        return 0;
+    };
+    write_opt(out_generation, state.generation);
+    let Some(out) = buf_mut(out_buf, MAPBLOCK_VOLUME as u64) else {
+        return fail(VA_ERR_BUFFER, "null mapblock buffer", 0);
+    };
+    let out: &mut [u8; MAPBLOCK_VOLUME] = out.try_into().expect("slice of MAPBLOCK_VOLUME");
+
+    automaton::extract_mapblock(state, [bx, by, bz], out)
+}
+
+/// Imports one 16x16x16 Luanti mapblock (layout as in `va_extract_mapblock`).
+///
+/// Input values are normalized: 0 = dead, non-zero = alive. Block cells that
+/// fall outside the grid are ignored.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State with a grid, or null
+/// - `in_buf` must point to at least 4096 readable bytes, or be null
+///
+/// # Returns
+/// Number of cells written into the grid (0..=4096), or 0 on error (null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_import_mapblock(
+    ptr: *mut State,
+    bx: i64,
+    by: i64,
+    bz: i64,
+    in_buf: *const u8,
+) -> u64 {
+    let Some(state) = state_mut(ptr) else {
+        return 0;
+    };
+    let Some(data) = buf_ref(in_buf, MAPBLOCK_VOLUME as u64) else {
+        return fail(VA_ERR_BUFFER, "null mapblock buffer", 0);
+    };
+    let data: &[u8; MAPBLOCK_VOLUME] = data.try_into().expect("slice of MAPBLOCK_VOLUME");
+
+    automaton::import_mapblock(state, [bx, by, bz], data)
+}
+
+/// Fills a rectangular region with one value in a single call.
+///
+/// Much faster than a `va_set_cell` per cell: each row of the box is written
+/// with one memset. The value is normalized: 0 = dead, non-zero = alive.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State with a grid, or null
+///
+/// # Returns
+/// Number of cells written, or 0 on error (null pointer, inverted region, or a
+/// region that lies entirely outside the grid).
+#[no_mangle]
+pub unsafe extern "C" fn va_fill_region(
+    ptr: *mut State,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+    value: u8,
+) -> u64 {
+    let Some(state) = state_mut(ptr) else {
+        return 0;
+    };
+    if region_volume([min_x, min_y, min_z], [max_x, max_y, max_z]).is_none() {
+        return 0;
+    }
+
+    automaton::fill_region(state, [min_x, min_y, min_z], [max_x, max_y, max_z], value)
+}
+
+/// Fills a rectangular region with deterministic random soup.
+///
+/// Each cell in the box becomes alive with probability `density_ppm` parts per
+/// million (e.g. 250000 = 25%) and dead otherwise. The same `seed` always gives
+/// the same pattern at the same coordinates.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State with a grid, or null
+///
+/// # Returns
+/// Number of cells written, or 0 on error (null pointer, inverted region, or a
+/// region that lies entirely outside the grid).
+#[no_mangle]
+pub unsafe extern "C" fn va_randomize_region(
+    ptr: *mut State,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+    density_ppm: u32,
+    seed: u64,
+) -> u64 {
+    let Some(state) = state_mut(ptr) else {
+        return 0;
+    };
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    if region_volume(min, max).is_none() {
+        return 0;
+    }
+
+    automaton::randomize_region(state, min, max, density_ppm, seed)
+}
+
+/// Kills every cell in the grid. Dimensions, rule, and generation are kept.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null (no-op)
+#[no_mangle]
+pub unsafe extern "C" fn va_clear(ptr: *mut State) {
+    if let Some(state) = state_mut(ptr) {
+        automaton::clear(state);
+    }
 }
 
 #[cfg(test)]
@@ -97,12 +355,42 @@ mod tests {
             crate::ffi::grid::va_set_cell(state, 3, 2, 2, 1);
 
             let mut buffer = vec![0u8; 64];
-            let bytes = va_extract_region(state, buffer.as_mut_ptr(), 2, 2, 2, 6, 6, 6);
+            let bytes = va_extract_region(
+                state,
+                buffer.as_mut_ptr(),
+                64,
+                2,
+                2,
+                2,
+                6,
+                6,
+                6,
+                ptr::null_mut(),
+            );
 
             assert_eq!(bytes, 64);
             assert_eq!(buffer[0], 1);
             assert_eq!(buffer[1], 1);
 
+            // A buffer shorter than the region is refused, and says why
+            let short = va_extract_region(
+                state,
+                buffer.as_mut_ptr(),
+                63,
+                2,
+                2,
+                2,
+                6,
+                6,
+                6,
+                ptr::null_mut(),
+            );
+            assert_eq!(short, 0);
+            assert_eq!(
+                crate::ffi::error::va_last_error_code(),
+                crate::ffi::error::VA_ERR_BUFFER
+            );
+
             crate::ffi::lifecycle::va_destroy(state);
         }
     }
@@ -119,7 +407,7 @@ mod tests {
                 0, 0, 0, 0, 0, 0, 0, 0,
             ];
 
-            let bytes = va_import_region(state, buffer.as_ptr(), 2, 2, 2, 6, 6, 6);
+            let bytes = va_import_region(state, buffer.as_ptr(), 64, 2, 2, 2, 6, 6, 6);
 
             assert_eq!(bytes, 64);
             assert_eq!(crate::ffi::grid::va_get_cell(state, 2, 2, 2), 1);
@@ -129,37 +417,352 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_malformed_arguments_rejected() {
+        unsafe {
+            let state = crate::ffi::lifecycle::va_create();
+            crate::ffi::grid::va_create_grid(state, 8, 8, 8);
+            let mut buffer = vec![0u8; 64];
+
+            // Buffer too small for the region
+            assert_eq!(
+                va_extract_region(
+                    state,
+                    buffer.as_mut_ptr(),
+                    63,
+                    0,
+                    0,
+                    0,
+                    4,
+                    4,
+                    4,
+                    ptr::null_mut()
+                ),
+                0
+            );
+            assert_eq!(
+                va_import_region(state, buffer.as_ptr(), 63, 0, 0, 0, 4, 4, 4),
+                0
+            );
+
+            // Inverted region
+            assert_eq!(
+                va_extract_region(
+                    state,
+                    buffer.as_mut_ptr(),
+                    64,
+                    4,
+                    0,
+                    0,
+                    0,
+                    4,
+                    4,
+                    ptr::null_mut()
+                ),
+                0
+            );
+
+            // Extreme coordinates used to overflow i16 subtraction; now the
+            // region is clamped to the grid (8x1x1)
+            assert_eq!(
+                va_extract_region(
+                    state,
+                    buffer.as_mut_ptr(),
+                    64,
+                    i16::MIN,
+                    0,
+                    0,
+                    i16::MAX,
+                    1,
+                    1,
+                    ptr::null_mut()
+                ),
+                8
+            );
+
+            // Garbage length (e.g. -1 from Lua) never reaches from_raw_parts
+            assert_eq!(
+                va_import_region(state, buffer.as_ptr(), u64::MAX, 0, 0, 0, 4, 4, 4),
+                0
+            );
+
+            crate::ffi::lifecycle::va_destroy(state);
+        }
+    }
+
+    #[test]
+    fn test_checked_query_then_extract() {
+        unsafe {
+            let state = crate::ffi::lifecycle::va_create();
+            crate::ffi::grid::va_create_grid(state, 8, 8, 8);
+            crate::ffi::grid::va_set_cell(state, 6, 7, 7, 1);
+
+            // Query mode: null buffer reports the clamped size (2x1x1)
+            let required = va_extract_region_checked(
+                state,
+                ptr::null_mut(),
+                0,
+                6,
+                7,
+                7,
+                20,
+                20,
+                8,
+                ptr::null_mut(),
+            );
+            assert_eq!(required, 2);
+
+            // Too small: size reported, nothing written
+            let mut buffer = vec![9u8; 2];
+            assert_eq!(
+                va_extract_region_checked(
+                    state,
+                    buffer.as_mut_ptr(),
+                    1,
+                    6,
+                    7,
+                    7,
+                    20,
+                    20,
+                    8,
+                    ptr::null_mut()
+                ),
+                2
+            );
+            assert_eq!(buffer, [9, 9]);
+
+            assert_eq!(
+                va_extract_region_checked(
+                    state,
+                    buffer.as_mut_ptr(),
+                    2,
+                    6,
+                    7,
+                    7,
+                    20,
+                    20,
+                    8,
+                    ptr::null_mut()
+                ),
+                2
+            );
+            assert_eq!(buffer, [1, 0]);
+
+            // Import: undersized buffer leaves the grid untouched
+            let data = [0u8, 1];
+            assert_eq!(
+                va_import_region_checked(state, data.as_ptr(), 1, 6, 7, 7, 8, 8, 8),
+                2
+            );
+            assert_eq!(crate::ffi::grid::va_get_cell(state, 6, 7, 7), 1);
+            assert_eq!(
+                va_import_region_checked(state, data.as_ptr(), 2, 6, 7, 7, 8, 8, 8),
+                2
+            );
+            assert_eq!(crate::ffi::grid::va_get_cell(state, 6, 7, 7), 0);
+            assert_eq!(crate::ffi::grid::va_get_cell(state, 7, 7, 7), 1);
+
+            // Errors and empty regions
+            assert_eq!(
+                va_extract_region_checked(
+                    state,
+                    ptr::null_mut(),
+                    0,
+                    4,
+                    0,
+                    0,
+                    0,
+                    4,
+                    4,
+                    ptr::null_mut()
+                ),
+                0
+            );
+            assert_eq!(
+                va_extract_region_checked(
+                    state,
+                    ptr::null_mut(),
+                    0,
+                    8,
+                    8,
+                    8,
+                    9,
+                    9,
+                    9,
+                    ptr::null_mut()
+                ),
+                0
+            );
+            assert_eq!(
+                va_import_region_checked(ptr::null_mut(), data.as_ptr(), 2, 0, 0, 0, 1, 1, 2),
+                0
+            );
+
+            crate::ffi::lifecycle::va_destroy(state);
+        }
+    }
+
+    #[test]
+    fn test_mapblock() {
+        unsafe {
+            let state = crate::ffi::lifecycle::va_create();
+            crate::ffi::grid::va_create_grid(state, 32, 16, 16);
+            crate::ffi::grid::va_set_cell(state, 18, 1, 2, 1);
+
+            let mut block = vec![0u8; MAPBLOCK_VOLUME];
+            assert_eq!(
+                va_extract_mapblock(state, 1, 0, 0, block.as_mut_ptr(), ptr::null_mut()),
+                4096
+            );
+            assert_eq!(block[(2 * 16 + 1) * 16 + 2], 1);
+
+            block[0] = 1;
+            assert_eq!(va_import_mapblock(state, 0, 0, 0, block.as_ptr()), 4096);
+            assert_eq!(crate::ffi::grid::va_get_cell(state, 0, 0, 0), 1);
+            assert_eq!(crate::ffi::grid::va_get_cell(state, 2, 1, 2), 1);
+
+            // Far-away blocks touch nothing
+            assert_eq!(va_import_mapblock(state, 1 << 40, 0, 0, block.as_ptr()), 0);
+            assert_eq!(
+                va_extract_mapblock(state, 0, -1, 0, block.as_mut_ptr(), ptr::null_mut()),
+                0
+            );
+
+            assert_eq!(
+                va_extract_mapblock(state, 0, 0, 0, ptr::null_mut(), ptr::null_mut()),
+                0
+            );
+            assert_eq!(
+                va_import_mapblock(ptr::null_mut(), 0, 0, 0, block.as_ptr()),
+                0
+            );
+
+            crate::ffi::lifecycle::va_destroy(state);
+        }
+    }
+
+    #[test]
+    fn test_generation_tag() {
+        unsafe {
+            let state = crate::ffi::lifecycle::va_create();
+            crate::ffi::grid::va_create_grid(state, 16, 16, 16);
+            crate::ffi::grid::va_step(state);
+            crate::ffi::grid::va_step(state);
+
+            let mut generation = u64::MAX;
+            let mut buffer = vec![0u8; MAPBLOCK_VOLUME];
+            va_extract_region(
+                state,
+                buffer.as_mut_ptr(),
+                64,
+                0,
+                0,
+                0,
+                4,
+                4,
+                4,
+                &mut generation,
+            );
+            assert_eq!(generation, 2);
+
+            crate::ffi::grid::va_step(state);
+            va_extract_region_checked(state, ptr::null_mut(), 0, 0, 0, 0, 4, 4, 4, &mut generation);
+            assert_eq!(generation, 3);
+
+            crate::ffi::grid::va_step(state);
+            va_extract_mapblock(state, 0, 0, 0, buffer.as_mut_ptr(), &mut generation);
+            assert_eq!(generation, 4);
+
+            // Null state: tag untouched
+            va_extract_mapblock(ptr::null(), 0, 0, 0, buffer.as_mut_ptr(), &mut generation);
+            assert_eq!(generation, 4);
+
+            crate::ffi::lifecycle::va_destroy(state);
+        }
+    }
+
+    #[test]
+    fn test_fill_and_clear() {
+        unsafe {
+            let state = crate::ffi::lifecycle::va_create();
+            crate::ffi::grid::va_create_grid(state, 8, 8, 8);
+
+            assert_eq!(va_fill_region(state, 1, 1, 1, 3, 3, 3, 1), 8);
+            assert_eq!(crate::ffi::grid::va_get_cell(state, 2, 2, 2), 1);
+            assert_eq!(crate::ffi::grid::va_get_cell(state, 3, 2, 2), 0);
+
+            // Inverted region is rejected rather than clamped
+            assert_eq!(va_fill_region(state, 3, 1, 1, 1, 3, 3, 0), 0);
+            assert_eq!(crate::ffi::grid::va_get_cell(state, 2, 2, 2), 1);
+
+            va_clear(state);
+            assert_eq!(crate::ffi::grid::va_get_cell(state, 1, 1, 1), 0);
+            assert_eq!(crate::ffi::grid::va_get_cell(state, 2, 2, 2), 0);
+
+            assert_eq!(
+                va_randomize_region(state, 0, 0, 0, 8, 8, 8, 1_000_000, 3),
+                512
+            );
+            assert_eq!(crate::ffi::grid::va_get_cell(state, 7, 7, 7), 1);
+            assert_eq!(va_randomize_region(state, 0, 0, 0, 8, 8, 8, 0, 3), 512);
+            assert_eq!(crate::ffi::grid::va_get_cell(state, 7, 7, 7), 0);
+            assert_eq!(va_randomize_region(state, 8, 0, 0, 0, 8, 8, 0, 3), 0);
+
+            crate::ffi::lifecycle::va_destroy(state);
+        }
+    }
+
     #[test]
     fn test_null_pointer_handling() {
         unsafe {
             let mut buffer = vec![0u8; 64];
 
             assert_eq!(
-                va_extract_region(ptr::null(), buffer.as_mut_ptr(), 0, 0, 0, 4, 4, 4),
+                va_extract_region(
+                    ptr::null(),
+                    buffer.as_mut_ptr(),
+                    64,
+                    0,
+                    0,
+                    0,
+                    4,
+                    4,
+                    4,
+                    ptr::null_mut()
+                ),
                 0
             );
             assert_eq!(
                 va_extract_region(
                     ptr::null_mut() as *const State,
                     ptr::null_mut(),
+                    64,
                     0,
                     0,
                     0,
                     4,
                     4,
-                    4
+                    4,
+                    ptr::null_mut()
                 ),
                 0
             );
 
             assert_eq!(
-                va_import_region(ptr::null_mut(), buffer.as_ptr(), 0, 0, 0, 4, 4, 4),
+                va_import_region(ptr::null_mut(), buffer.as_ptr(), 64, 0, 0, 0, 4, 4, 4),
                 0
             );
             assert_eq!(
-                va_import_region(ptr::null_mut(), ptr::null(), 0, 0, 0, 4, 4, 4),
+                va_import_region(ptr::null_mut(), ptr::null(), 64, 0, 0, 0, 4, 4, 4),
+                0
+            );
+
+            assert_eq!(va_fill_region(ptr::null_mut(), 0, 0, 0, 4, 4, 4, 1), 0);
+            assert_eq!(
+                va_randomize_region(ptr::null_mut(), 0, 0, 0, 4, 4, 4, 500_000, 1),
                 0
             );
+            va_clear(ptr::null_mut());
         }
     }
 }
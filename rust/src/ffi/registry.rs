@@ -0,0 +1,497 @@
+//! Integer handle ids: an alternative to raw pointers for the core calls.
+//!
+//! `va_id_create*` store the handle in a process-wide registry and return a
+//! u32 id (never 0); the `va_id_*` functions look the id up and forward to the
+//! pointer function of the same name. An id that was destroyed, or that names
+//! a handle of another type, fails with `VA_ERR_INVALID_HANDLE` as the last
+//! error instead of reaching freed memory, and `va_id_list` enumerates the
+//! live handles for debugging.
+//!
+//! Id calls hold the registry lock for their whole duration, so an id cannot
+//! be destroyed while another thread uses it; they are serialized across
+//! threads. For calls without an id variant, `va_id_state_ptr` and friends
+//! lend the underlying pointer.
+
+use std::sync::{LazyLock, Mutex, MutexGuard};
+
+use super::error::{fail, VA_ERR_INVALID_HANDLE, VA_ERR_TOO_LARGE};
+use super::field::{va_create_field, va_field_get, va_field_set, va_field_step, va_field_total};
+use super::grid::{va_create_grid, va_get_cell, va_set_cell, va_step, GRID_ERR_NULL};
+use super::incremental::{
+    va_create_step_controller, va_sc_begin_step, va_sc_field_get, va_sc_field_set,
+    va_sc_is_stepping, va_sc_step_blocking, va_sc_tick,
+};
+use super::lifecycle::va_get_generation;
+use super::region::{va_extract_region, va_import_region};
+use super::validate::{buf_mut, take_handle};
+use crate::automaton::field::Field;
+use crate::automaton::incremental::StepController;
+use crate::automaton::registry::IdSlab;
+use crate::state::{HandleKind, State};
+
+/// A registered handle.
+enum Handle {
+    State(Box<State>),
+    Field(Box<Field>),
+    Controller(Box<StepController>),
+}
+
+impl Handle {
+    fn kind(&self) -> HandleKind {
+        match self {
+            Handle::State(_) => HandleKind::State,
+            Handle::Field(_) => HandleKind::Field,
+            Handle::Controller(_) => HandleKind::StepController,
+        }
+    }
+}
+
+static REGISTRY: LazyLock<Mutex<IdSlab<Handle>>> = LazyLock::new(|| Mutex::new(IdSlab::new()));
+
+fn registry() -> MutexGuard<'static, IdSlab<Handle>> {
+    // Every registry operation is a single slot update, so ignore poisoning
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Destroy every registered handle, retiring all ids (see `va_reinit`).
+/// Returns how many there were.
+pub(crate) fn reset_registry() -> usize {
+    registry().clear()
+}
+
+/// Register a handle, or 0 if the registry is full.
+fn register(handle: Handle) -> u32 {
+    registry()
+        .insert(handle)
+        .unwrap_or_else(|_| fail(VA_ERR_TOO_LARGE, "handle registry full (2^20 live ids)", 0))
+}
+
+/// Record why `id` does not name a live `expected` handle, and return `value`.
+fn invalid_id<R>(slab: &IdSlab<Handle>, id: u32, expected: HandleKind, value: R) -> R {
+    let message = match slab.get(id) {
+        Some(handle) => format!("id {id} is a {:?}, not a {expected:?}", handle.kind()),
+        None => format!("unknown or destroyed {expected:?} id {id}"),
+    };
+    fail(VA_ERR_INVALID_HANDLE, message, value)
+}
+
+/// Run `f` on the State of `id` with the registry locked, or return `value`.
+fn with_state<R>(id: u32, value: R, f: impl FnOnce(*mut State) -> R) -> R {
+    let mut slab = registry();
+    match slab.get_mut(id) {
+        Some(Handle::State(state)) => f(&mut **state),
+        _ => invalid_id(&slab, id, HandleKind::State, value),
+    }
+}
+
+/// `with_state` for a Field.
+fn with_field<R>(id: u32, value: R, f: impl FnOnce(*mut Field) -> R) -> R {
+    let mut slab = registry();
+    match slab.get_mut(id) {
+        Some(Handle::Field(field)) => f(&mut **field),
+        _ => invalid_id(&slab, id, HandleKind::Field, value),
+    }
+}
+
+/// `with_state` for a StepController.
+fn with_ctrl<R>(id: u32, value: R, f: impl FnOnce(*mut StepController) -> R) -> R {
+    let mut slab = registry();
+    match slab.get_mut(id) {
+        Some(Handle::Controller(ctrl)) => f(&mut **ctrl),
+        _ => invalid_id(&slab, id, HandleKind::StepController, value),
+    }
+}
+
+/// Create a State (as `va_create`) and register it.
+///
+/// # Returns
+/// Its id, or 0 if the registry is full.
+#[no_mangle]
+pub extern "C" fn va_id_create() -> u32 {
+    register(Handle::State(Box::default()))
+}
+
+/// Create a Field (as `va_create_field`) and register it.
+///
+/// # Returns
+/// Its id, or 0 on failure (non-positive dimension, or the registry is full).
+#[no_mangle]
+pub extern "C" fn va_id_create_field(
+    width: i16,
+    height: i16,
+    depth: i16,
+    diffusion_rate: u8,
+) -> u32 {
+    match unsafe { take_handle(va_create_field(width, height, depth, diffusion_rate)) } {
+        Some(field) => register(Handle::Field(Box::new(field))),
+        None => 0,
+    }
+}
+
+/// Create a StepController (as `va_create_step_controller`) and register it.
+///
+/// # Returns
+/// Its id, or 0 on failure (non-positive dimension, or the registry is full).
+#[no_mangle]
+pub extern "C" fn va_id_create_step_controller(
+    width: i16,
+    height: i16,
+    depth: i16,
+    diffusion_rate: u8,
+    num_threads: u8,
+    tile_size: u8,
+) -> u32 {
+    let ctrl =
+        va_create_step_controller(width, height, depth, diffusion_rate, num_threads, tile_size);
+    match unsafe { take_handle(ctrl) } {
+        Some(ctrl) => register(Handle::Controller(Box::new(ctrl))),
+        None => 0,
+    }
+}
+
+/// Destroy the handle of any type that `id` names, retiring the id.
+///
+/// # Returns
+/// 0 on success, 1 for an unknown or already destroyed id.
+#[no_mangle]
+pub extern "C" fn va_id_destroy(id: u32) -> i32 {
+    let removed = registry().remove(id);
+    match removed {
+        Some(handle) => {
+            drop(handle);
+            0
+        }
+        None => fail(
+            VA_ERR_INVALID_HANDLE,
+            format!("unknown or destroyed id {id}"),
+            1,
+        ),
+    }
+}
+
+/// Type of the handle `id` names.
+///
+/// # Returns
+/// 1 = State, 2 = Field, 3 = StepController, or 0 for an unknown or destroyed
+/// id.
+#[no_mangle]
+pub extern "C" fn va_id_kind(id: u32) -> i32 {
+    registry().get(id).map_or(0, |handle| handle.kind() as i32)
+}
+
+/// List the live ids, in registration slot order, for debugging (e.g.
+/// finding handles Lua forgot to destroy).
+///
+/// # Safety
+/// `out_ids` must point to at least `capacity` writable u32 values, or be null.
+///
+/// # Returns
+/// The number of live ids (call with a null `out_ids` to size the buffer);
+/// only the first `capacity` are written.
+#[no_mangle]
+pub unsafe extern "C" fn va_id_list(out_ids: *mut u32, capacity: u64) -> u64 {
+    let ids = registry().ids();
+    if let Some(out) = buf_mut(out_ids, capacity) {
+        for (slot, &id) in out.iter_mut().zip(&ids) {
+            *slot = id;
+        }
+    }
+    ids.len() as u64
+}
+
+/// Lend the State pointer of `id`, for the calls without an id variant.
+///
+/// # Returns
+/// The pointer, valid until `va_id_destroy(id)` (do not pass it to
+/// `va_destroy`), or null for an id that is not a live State. Unlike the id
+/// calls, the registry lock does not protect its use from other threads.
+#[no_mangle]
+pub extern "C" fn va_id_state_ptr(id: u32) -> *mut State {
+    with_state(id, std::ptr::null_mut(), |state| state)
+}
+
+/// `va_id_state_ptr` for a Field.
+///
+/// # Returns
+/// The pointer, or null for an id that is not a live Field.
+#[no_mangle]
+pub extern "C" fn va_id_field_ptr(id: u32) -> *mut Field {
+    with_field(id, std::ptr::null_mut(), |field| field)
+}
+
+/// `va_id_state_ptr` for a StepController.
+///
+/// # Returns
+/// The pointer, or null for an id that is not a live StepController.
+#[no_mangle]
+pub extern "C" fn va_id_sc_ptr(id: u32) -> *mut StepController {
+    with_ctrl(id, std::ptr::null_mut(), |ctrl| ctrl)
+}
+
+/// `va_create_grid` by id.
+///
+/// # Returns
+/// As `va_create_grid`, with 1 (`GRID_ERR_NULL`) for an id that is not a live
+/// State.
+#[no_mangle]
+pub extern "C" fn va_id_create_grid(id: u32, width: i16, height: i16, depth: i16) -> i32 {
+    with_state(id, GRID_ERR_NULL, |state| unsafe {
+        va_create_grid(state, width, height, depth)
+    })
+}
+
+/// `va_set_cell` by id.
+#[no_mangle]
+pub extern "C" fn va_id_set_cell(id: u32, x: i16, y: i16, z: i16, alive: u8) {
+    with_state(id, (), |state| unsafe {
+        va_set_cell(state, x, y, z, alive)
+    })
+}
+
+/// `va_get_cell` by id.
+///
+/// # Returns
+/// 1 if alive; 0 if dead, out of bounds, or for an id that is not a live State.
+#[no_mangle]
+pub extern "C" fn va_id_get_cell(id: u32, x: i16, y: i16, z: i16) -> u8 {
+    with_state(id, 0, |state| unsafe { va_get_cell(state, x, y, z) })
+}
+
+/// `va_step` by id.
+#[no_mangle]
+pub extern "C" fn va_id_step(id: u32) {
+    with_state(id, (), |state| unsafe { va_step(state) })
+}
+
+/// `va_get_generation` by id.
+///
+/// # Returns
+/// The generation, or 0 for an id that is not a live State.
+#[no_mangle]
+pub extern "C" fn va_id_get_generation(id: u32) -> u64 {
+    with_state(id, 0, |state| unsafe { va_get_generation(state) })
+}
+
+/// `va_extract_region` by id.
+///
+/// # Safety
+/// - `out_buf` must point to at least `buf_len` writable bytes, or be null
+/// - `out_generation` must be a valid writable pointer, or null (skipped)
+///
+/// # Returns
+/// As `va_extract_region`, 0 for an id that is not a live State.
+#[no_mangle]
+pub unsafe extern "C" fn va_id_extract_region(
+    id: u32,
+    out_buf: *mut u8,
+    buf_len: u64,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+    out_generation: *mut u64,
+) -> u64 {
+    with_state(id, 0, |state| {
+        va_extract_region(
+            state,
+            out_buf,
+            buf_len,
+            min_x,
+            min_y,
+            min_z,
+            max_x,
+            max_y,
+            max_z,
+            out_generation,
+        )
+    })
+}
+
+/// `va_import_region` by id.
+///
+/// # Safety
+/// `in_buf` must point to at least `buf_len` readable bytes, or be null.
+///
+/// # Returns
+/// As `va_import_region`, 0 for an id that is not a live State.
+#[no_mangle]
+pub unsafe extern "C" fn va_id_import_region(
+    id: u32,
+    in_buf: *const u8,
+    buf_len: u64,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+) -> u64 {
+    with_state(id, 0, |state| {
+        va_import_region(
+            state, in_buf, buf_len, min_x, min_y, min_z, max_x, max_y, max_z,
+        )
+    })
+}
+
+/// `va_field_set` by id.
+#[no_mangle]
+pub extern "C" fn va_id_field_set(id: u32, x: i16, y: i16, z: i16, value: u32) {
+    with_field(id, (), |field| va_field_set(field, x, y, z, value))
+}
+
+/// `va_field_get` by id.
+///
+/// # Returns
+/// The value, or 0 out of bounds or for an id that is not a live Field.
+#[no_mangle]
+pub extern "C" fn va_id_field_get(id: u32, x: i16, y: i16, z: i16) -> u32 {
+    with_field(id, 0, |field| va_field_get(field, x, y, z))
+}
+
+/// `va_field_step` by id.
+#[no_mangle]
+pub extern "C" fn va_id_field_step(id: u32) {
+    with_field(id, (), |field| va_field_step(field))
+}
+
+/// `va_field_total` by id.
+///
+/// # Returns
+/// The total, or 0 for an id that is not a live Field.
+#[no_mangle]
+pub extern "C" fn va_id_field_total(id: u32) -> u64 {
+    with_field(id, 0, |field| unsafe { va_field_total(field) })
+}
+
+/// `va_sc_begin_step` by id.
+///
+/// # Returns
+/// As `va_sc_begin_step`, -1 for an id that is not a live StepController.
+#[no_mangle]
+pub extern "C" fn va_id_sc_begin_step(id: u32) -> i32 {
+    with_ctrl(id, -1, |ctrl| va_sc_begin_step(ctrl))
+}
+
+/// `va_sc_tick` by id.
+///
+/// # Safety
+/// `out_elapsed_ns` and `out_tiles` must be valid writable pointers, or null
+/// (skipped).
+///
+/// # Returns
+/// As `va_sc_tick`, -1 for an id that is not a live StepController.
+#[no_mangle]
+pub unsafe extern "C" fn va_id_sc_tick(
+    id: u32,
+    budget_us: u64,
+    out_elapsed_ns: *mut u64,
+    out_tiles: *mut u32,
+) -> i32 {
+    with_ctrl(id, -1, |ctrl| {
+        va_sc_tick(ctrl, budget_us, out_elapsed_ns, out_tiles)
+    })
+}
+
+/// `va_sc_step_blocking` by id.
+#[no_mangle]
+pub extern "C" fn va_id_sc_step_blocking(id: u32) {
+    with_ctrl(id, (), |ctrl| va_sc_step_blocking(ctrl))
+}
+
+/// `va_sc_is_stepping` by id.
+///
+/// # Returns
+/// 1 if stepping, 0 if idle, -1 for an id that is not a live StepController.
+#[no_mangle]
+pub extern "C" fn va_id_sc_is_stepping(id: u32) -> i32 {
+    with_ctrl(id, -1, |ctrl| va_sc_is_stepping(ctrl))
+}
+
+/// `va_sc_field_set` by id.
+#[no_mangle]
+pub extern "C" fn va_id_sc_field_set(id: u32, x: i16, y: i16, z: i16, value: u32) {
+    with_ctrl(id, (), |ctrl| va_sc_field_set(ctrl, x, y, z, value))
+}
+
+/// `va_sc_field_get` by id.
+///
+/// # Returns
+/// The value, or 0 out of bounds or for an id that is not a live
+/// StepController.
+#[no_mangle]
+pub extern "C" fn va_id_sc_field_get(id: u32, x: i16, y: i16, z: i16) -> u32 {
+    with_ctrl(id, 0, |ctrl| va_sc_field_get(ctrl, x, y, z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::error::va_last_error_code;
+    use std::ptr;
+
+    #[test]
+    fn test_ids_via_ffi() {
+        let state = va_id_create();
+        let field = va_id_create_field(4, 4, 4, 2);
+        let ctrl = va_id_create_step_controller(4, 4, 4, 2, 1, 0);
+        assert!(state != 0 && field != 0 && ctrl != 0);
+        assert_eq!(va_id_create_field(0, 4, 4, 2), 0);
+        assert_eq!(va_id_kind(state), 1);
+        assert_eq!(va_id_kind(ctrl), 3);
+
+        assert_eq!(va_id_create_grid(state, 4, 4, 4), 0);
+        va_id_set_cell(state, 1, 1, 1, 1);
+        assert_eq!(va_id_get_cell(state, 1, 1, 1), 1);
+        va_id_step(state);
+        assert_eq!(va_id_get_generation(state), 1);
+        let mut cells = [0u8; 64];
+        let written = unsafe {
+            va_id_extract_region(
+                state,
+                cells.as_mut_ptr(),
+                64,
+                0,
+                0,
+                0,
+                4,
+                4,
+                4,
+                ptr::null_mut(),
+            )
+        };
+        assert_eq!(written, 64);
+
+        va_id_field_set(field, 0, 0, 0, 1000);
+        va_id_field_step(field);
+        assert_eq!(va_id_field_total(field), 1000 + 63);
+        assert_eq!(va_id_sc_begin_step(ctrl), 0);
+        assert_eq!(va_id_sc_is_stepping(ctrl), 1);
+        va_id_sc_step_blocking(ctrl);
+        assert_eq!(va_id_sc_is_stepping(ctrl), 0);
+
+        // Type confusion is an error, not a misread
+        assert_eq!(va_id_field_get(state, 0, 0, 0), 0);
+        assert_eq!(va_last_error_code(), VA_ERR_INVALID_HANDLE);
+        assert!(va_id_sc_ptr(field).is_null());
+        assert!(!va_id_field_ptr(field).is_null());
+
+        // Other tests register ids concurrently: check membership only
+        let count = unsafe { va_id_list(ptr::null_mut(), 0) };
+        let mut ids = vec![0u32; count as usize + 16];
+        let listed = unsafe { va_id_list(ids.as_mut_ptr(), ids.len() as u64) };
+        ids.truncate(listed as usize);
+        assert!([state, field, ctrl].iter().all(|id| ids.contains(id)));
+
+        // Double free and use after free are detected
+        for id in [state, field, ctrl] {
+            assert_eq!(va_id_destroy(id), 0);
+            assert_eq!(va_id_destroy(id), 1);
+            assert_eq!(va_id_kind(id), 0);
+        }
+        assert_eq!(va_id_get_generation(state), 0);
+        assert_eq!(va_last_error_code(), VA_ERR_INVALID_HANDLE);
+        assert_eq!(va_id_create_grid(state, 4, 4, 4), GRID_ERR_NULL);
+    }
+}
@@ -0,0 +1,146 @@
+//! FFI interface for debug reports (see `automaton::report`), and the
+//! process-wide record of recent errors they include.
+//!
+//! Calls that reject user data (configuration blobs, bundles, snapshots, rule
+//! strings) note why; the last `MAX_RECENT_ERRORS` notes appear in every
+//! report, so a pasted report says what went wrong without a debugger. A
+//! note is also the calling thread's last error (`VA_ERR_INVALID_DATA`).
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::{LazyLock, Mutex, MutexGuard};
+
+use super::error::{set_last_error, VA_ERR_INVALID_DATA};
+use super::validate::{field_ref, state_ref, write_text};
+use crate::automaton::field::Field;
+use crate::automaton::report::{field_report, state_report};
+use crate::state::State;
+
+/// Errors kept for reports.
+pub const MAX_RECENT_ERRORS: usize = 8;
+
+static RECENT_ERRORS: LazyLock<Mutex<VecDeque<String>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::new()));
+
+fn recent_errors() -> MutexGuard<'static, VecDeque<String>> {
+    // Every operation is a single push or pop, so ignore poisoning
+    RECENT_ERRORS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Note that `call` failed because of `error`.
+pub(crate) fn note_error(call: &str, error: impl Debug) {
+    let message = format!("{call}: {error:?}");
+    set_last_error(VA_ERR_INVALID_DATA, message.clone());
+    let mut errors = recent_errors();
+    if errors.len() >= MAX_RECENT_ERRORS {
+        errors.pop_front();
+    }
+    errors.push_back(message);
+}
+
+/// Forget the noted errors (see `va_reinit`).
+pub(crate) fn reset_errors() {
+    recent_errors().clear();
+}
+
+fn errors_snapshot() -> Vec<String> {
+    recent_errors().iter().cloned().collect()
+}
+
+/// Writes a debug report on the grid for bug reports: build, dimensions,
+/// generation, content hash, rule and mode, counters (live cells, suppressed
+/// births, queued writes, checkpoints), the time a dry-run step takes, and the
+/// most recent errors. Sectioned `key = value` lines, UTF-8, not
+/// NUL-terminated. The grid is not changed.
+///
+/// The timing differs from call to call, so allocate a little more than a
+/// null-buffer query reports (64 bytes of slack is plenty).
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `out_buf` must point to at least `capacity` writable bytes, or be null
+///
+/// # Returns
+/// Number of bytes written, or 0 on error (null pointer, or `capacity` too
+/// small). Pass a null `out_buf` to query the required size without writing.
+#[no_mangle]
+pub unsafe extern "C" fn va_dump_debug_report(
+    ptr: *const State,
+    out_buf: *mut u8,
+    capacity: u64,
+) -> u64 {
+    match state_ref(ptr) {
+        Some(state) => write_text(&state_report(state, &errors_snapshot()), out_buf, capacity),
+        None => 0,
+    }
+}
+
+/// `va_dump_debug_report` for a field: build, dimensions, generation, content
+/// hash, every setting, the mass ledger (total, range, sources and sinks,
+/// non-reflective faces, suppressed value, and the change one step would make),
+/// the time a step takes (on a copy), and the most recent errors. The field is
+/// not changed.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `out_buf` must point to at least `capacity` writable bytes, or be null
+///
+/// # Returns
+/// Number of bytes written, or 0 on error (null pointer, or `capacity` too
+/// small). Pass a null `out_buf` to query the required size without writing.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_dump_debug_report(
+    field: *const Field,
+    out_buf: *mut u8,
+    capacity: u64,
+) -> u64 {
+    match field_ref(field) {
+        Some(field) => write_text(&field_report(field, &errors_snapshot()), out_buf, capacity),
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::config::va_set_config;
+    use crate::ffi::field::{va_create_field, va_destroy_field};
+    use crate::ffi::grid::va_create_grid;
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use std::ptr;
+
+    unsafe fn report_text(state: *const State) -> String {
+        let len = va_dump_debug_report(state, ptr::null_mut(), 0);
+        let mut buf = vec![0u8; len as usize + 64];
+        let written = va_dump_debug_report(state, buf.as_mut_ptr(), buf.len() as u64);
+        buf.truncate(written as usize);
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_debug_report_via_ffi() {
+        unsafe {
+            let state = va_create();
+            va_create_grid(state, 6, 6, 6);
+            let bad = b"rule = 4";
+            assert_eq!(va_set_config(state, bad.as_ptr(), bad.len() as u64), 1);
+            let report = report_text(state);
+            assert!(report.contains("size = [6, 6, 6]\n"), "{report}");
+            // Other tests may note errors concurrently; ours is among the recent
+            assert!(report.contains("va_set_config: Invalid { line: 1 }"));
+
+            let mut small = [0u8; 8];
+            assert_eq!(va_dump_debug_report(state, small.as_mut_ptr(), 8), 0);
+            assert_eq!(va_dump_debug_report(ptr::null(), ptr::null_mut(), 0), 0);
+            va_destroy(state);
+
+            let field = va_create_field(3, 3, 3, 2);
+            let len = va_field_dump_debug_report(field, ptr::null_mut(), 0);
+            let mut buf = vec![0u8; len as usize + 64];
+            let written = va_field_dump_debug_report(field, buf.as_mut_ptr(), buf.len() as u64);
+            let text = std::str::from_utf8(&buf[..written as usize]).unwrap();
+            assert!(text.contains("[ledger]\ntotal = 27\n"), "{text}");
+            va_destroy_field(field);
+        }
+    }
+}
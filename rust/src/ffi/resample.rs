@@ -0,0 +1,184 @@
+//! FFI interface for conservative coarse/fine resolution changes.
+
+use super::validate::{buf_mut, field_mut, field_ref, write_opt};
+use crate::automaton::field::Field;
+use crate::automaton::resample::{
+    downsampled_dims, field_aggregate, field_downsample, field_refine, ResampleError,
+};
+
+/// C status of a resolution change.
+fn status(result: Result<(), ResampleError>) -> i32 {
+    match result {
+        Ok(()) => 0,
+        Err(ResampleError::Dimensions) => 1,
+        Err(ResampleError::InsufficientMass { .. }) => 2,
+        Err(ResampleError::Overflow { .. }) => 3,
+    }
+}
+
+/// Redistributes every coarse cell evenly over its `factor`³ block of `fine`,
+/// conserving the total exactly. `fine` must measure the coarse dimensions
+/// times `factor` on every axis; it takes the coarse generation.
+///
+/// # Safety
+/// `coarse` and `fine` must be null or valid, distinct Field pointers.
+///
+/// # Returns
+/// 0 on success, 1 on invalid arguments (null pointer, factor 0, or mismatched
+/// dimensions), 2 if a coarse cell holds less than `factor`³ (each fine cell
+/// keeps at least 1). Neither field is modified on failure.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_refine(
+    coarse: *const Field,
+    fine: *mut Field,
+    factor: u8,
+) -> i32 {
+    if std::ptr::eq(coarse, fine) {
+        return 1;
+    }
+    match (field_ref(coarse), field_mut(fine)) {
+        (Some(coarse), Some(fine)) => status(field_refine(coarse, fine, factor)),
+        _ => 1,
+    }
+}
+
+/// Sums every `factor`³ block of `fine` into the matching cell of `coarse`,
+/// conserving the total exactly; `coarse` takes the fine generation.
+///
+/// # Safety
+/// `fine` and `coarse` must be null or valid, distinct Field pointers.
+///
+/// # Returns
+/// 0 on success, 1 on invalid arguments (as `va_field_refine`), 3 if a block
+/// sums to more than u32::MAX. Neither field is modified on failure.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_aggregate(
+    fine: *const Field,
+    coarse: *mut Field,
+    factor: u8,
+) -> i32 {
+    if std::ptr::eq(fine, coarse) {
+        return 1;
+    }
+    match (field_ref(fine), field_mut(coarse)) {
+        (Some(fine), Some(coarse)) => status(field_aggregate(fine, coarse, factor)),
+        _ => 1,
+    }
+}
+
+/// Extracts the whole field reduced by `factor` on every axis (2, 4, 8, ...),
+/// each cell the integer average of its block (rounded half to even), for
+/// rendering distant chunks with a fraction of the transfer. The output
+/// measures `ceil(dim / factor)` per axis, z,y,x order; edge blocks average
+/// only the cells they cover. The field is not modified.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `out_buf` must point to at least `buf_len` writable u32 values, or be null
+///   (size query)
+/// - `out_dims` must be null or point to 3 writable i16 values (width, height,
+///   depth of the output)
+///
+/// # Returns
+/// Number of cells written (with a null `out_buf`, the number required), or 0
+/// on error (null field, factor 0, or `buf_len` too small).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_extract_downsampled(
+    field: *const Field,
+    out_buf: *mut u32,
+    buf_len: u64,
+    factor: u8,
+    out_dims: *mut i16,
+) -> u64 {
+    let Some(field) = field_ref(field) else {
+        return 0;
+    };
+    let Some(dims) = downsampled_dims(field, factor) else {
+        return 0;
+    };
+    if !out_dims.is_null() {
+        for (axis, &size) in dims.iter().enumerate() {
+            write_opt(out_dims.add(axis), size);
+        }
+    }
+    if out_buf.is_null() {
+        return dims.iter().map(|&d| d as u64).product();
+    }
+    match buf_mut(out_buf, buf_len) {
+        Some(out) => field_downsample(field, factor, out),
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{va_create_field, va_destroy_field, va_field_get, va_field_set};
+    use std::ptr;
+
+    #[test]
+    fn test_refine_and_aggregate_via_ffi() {
+        let coarse = va_create_field(2, 2, 2, 2);
+        let fine = va_create_field(4, 4, 4, 2);
+        unsafe {
+            assert_eq!(va_field_refine(coarse, fine, 2), 2);
+            for i in 0..8 {
+                va_field_set(coarse, i & 1, i >> 1 & 1, i >> 2, 1000 + i as u32);
+            }
+            assert_eq!(va_field_refine(coarse, fine, 3), 1);
+            assert_eq!(va_field_refine(coarse, fine, 2), 0);
+            assert_eq!(va_field_get(fine, 3, 3, 3), 1007 / 8 + 1);
+            assert_eq!(va_field_get(fine, 0, 0, 0), 1000 / 8);
+
+            va_field_set(coarse, 0, 0, 0, 1);
+            assert_eq!(va_field_aggregate(fine, coarse, 2), 0);
+            assert_eq!(va_field_get(coarse, 0, 0, 0), 1000);
+
+            va_field_set(fine, 0, 0, 0, u32::MAX);
+            assert_eq!(va_field_aggregate(fine, coarse, 2), 3);
+            assert_eq!(va_field_refine(coarse, coarse, 1), 1);
+            assert_eq!(va_field_refine(ptr::null(), fine, 2), 1);
+            assert_eq!(va_field_aggregate(fine, ptr::null_mut(), 2), 1);
+        }
+        va_destroy_field(coarse);
+        va_destroy_field(fine);
+    }
+
+    #[test]
+    fn test_extract_downsampled_via_ffi() {
+        let field = va_create_field(17, 8, 8, 2);
+        unsafe {
+            va_field_set(field, 16, 0, 0, 4001);
+            let mut dims = [0i16; 3];
+            let len = va_field_extract_downsampled(field, ptr::null_mut(), 0, 4, dims.as_mut_ptr());
+            assert_eq!((len, dims), (5 * 2 * 2, [5, 2, 2]));
+            let mut out = vec![0u32; len as usize];
+            assert_eq!(
+                va_field_extract_downsampled(field, out.as_mut_ptr(), len - 1, 4, ptr::null_mut()),
+                0
+            );
+            assert_eq!(
+                va_field_extract_downsampled(field, out.as_mut_ptr(), len, 4, ptr::null_mut()),
+                len
+            );
+            assert_eq!(out[0], 1);
+            // The 1 x 4 x 4 edge block: the hot cell and 15 cells of 1
+            assert_eq!(out[4], 4016 / 16);
+            assert_eq!(
+                va_field_extract_downsampled(field, out.as_mut_ptr(), len, 0, ptr::null_mut()),
+                0
+            );
+            assert_eq!(
+                va_field_extract_downsampled(
+                    ptr::null(),
+                    out.as_mut_ptr(),
+                    len,
+                    4,
+                    ptr::null_mut()
+                ),
+                0
+            );
+        }
+        va_destroy_field(field);
+    }
+}
@@ -0,0 +1,124 @@
+//! FFI interface for pattern search (see `automaton::search`).
+
+use super::error::{fail, VA_ERR_BUFFER};
+use super::validate::{buf_mut, buf_ref, dims_valid, state_ref};
+use crate::automaton::search::find_pattern;
+use crate::state::State;
+
+/// Finds every occurrence of a small pattern in the grid, e.g. to trigger an
+/// achievement when a player's automaton produces a glider.
+///
+/// # Pattern
+/// `pattern_buf` holds `pw * ph * pd` bytes in z,y,x order (the layout of
+/// `va_extract_region`): 0 = must be dead, 1 = must be alive, anything else
+/// (e.g. 255) = either. Surround a shape with dead cells to match it only
+/// where it stands alone. With `rotations` non-zero, the 24 cube rotations
+/// of the pattern (the ids of `va_stamp`) are tried too; a rotation that
+/// leaves the pattern unchanged is reported under the lowest id.
+///
+/// # Layout
+/// `out_positions` receives 4 i16 values per occurrence: x, y, z of the
+/// rotated pattern's minimum corner, and the rotation. Only the first `max`
+/// are written; call with a null `out_positions` to count them.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `pattern_buf` must point to at least `pw * ph * pd` readable bytes
+/// - `out_positions` must point to at least `max * 4` writable i16 values, or
+///   be null
+///
+/// # Returns
+/// Number of occurrences, or 0 on error (null pointer, non-positive pattern
+/// dimension, null pattern).
+#[no_mangle]
+pub unsafe extern "C" fn va_find_pattern(
+    ptr: *const State,
+    pattern_buf: *const u8,
+    pw: i16,
+    ph: i16,
+    pd: i16,
+    rotations: u8,
+    out_positions: *mut i16,
+    max: u64,
+) -> u64 {
+    let Some(state) = state_ref(ptr) else {
+        return 0;
+    };
+    if !dims_valid(pw, ph, pd) {
+        return 0;
+    }
+    let volume = pw as u64 * ph as u64 * pd as u64;
+    let Some(pattern) = buf_ref(pattern_buf, volume) else {
+        return fail(VA_ERR_BUFFER, "null pattern buffer", 0);
+    };
+    let Some(found) = find_pattern(state, pattern, [pw, ph, pd], rotations != 0) else {
+        return 0;
+    };
+    if let Some(out) = buf_mut(out_positions, max.saturating_mul(4)) {
+        for (slot, found) in out.chunks_exact_mut(4).zip(&found) {
+            let [x, y, z] = found.position;
+            slot.copy_from_slice(&[x, y, z, found.rotation as i16]);
+        }
+    }
+    found.len() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::stamp::STAMP_GLIDER;
+    use crate::ffi::grid::va_create_grid;
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use crate::ffi::region::va_extract_region;
+    use crate::ffi::stamp::va_stamp;
+    use std::ptr;
+
+    #[test]
+    fn test_find_pattern_via_ffi() {
+        unsafe {
+            // Cut the pattern out of a grid holding one unrotated glider
+            let sample = va_create();
+            va_create_grid(sample, 5, 5, 5);
+            va_stamp(sample, STAMP_GLIDER, 1, 1, 1, 0);
+            let mut glider = [0u8; 125];
+            let cut = va_extract_region(
+                sample,
+                glider.as_mut_ptr(),
+                125,
+                0,
+                0,
+                0,
+                5,
+                5,
+                5,
+                ptr::null_mut(),
+            );
+            assert_eq!(cut, 125);
+            va_destroy(sample);
+
+            let state = va_create();
+            va_create_grid(state, 16, 16, 16);
+            va_stamp(state, STAMP_GLIDER, 4, 4, 4, 0);
+            va_stamp(state, STAMP_GLIDER, 11, 11, 11, 5);
+            let find = |rotations, out: *mut i16, max| {
+                va_find_pattern(state, glider.as_ptr(), 5, 5, 5, rotations, out, max)
+            };
+            let mut found = [0i16; 8];
+            assert_eq!(find(0, found.as_mut_ptr(), 2), 1);
+            assert_eq!(&found[..4], &[3, 3, 3, 0]);
+            assert_eq!(find(1, ptr::null_mut(), 0), 2);
+            assert_eq!(find(1, found.as_mut_ptr(), 2), 2);
+            assert_eq!(found[7], 5);
+
+            assert_eq!(
+                va_find_pattern(state, glider.as_ptr(), 0, 5, 5, 0, ptr::null_mut(), 0),
+                0
+            );
+            assert_eq!(
+                va_find_pattern(state, ptr::null(), 5, 5, 5, 0, ptr::null_mut(), 0),
+                0
+            );
+            va_destroy(state);
+        }
+    }
+}
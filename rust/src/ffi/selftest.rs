@@ -0,0 +1,281 @@
+//! Built-in self test for validating a freshly compiled library.
+//!
+//! Server admins building the .so themselves (different compiler, target, or
+//! libc) can call `va_self_test()` once before enabling the mod. Each check is
+//! small enough that the whole battery runs in a few milliseconds.
+//!
+//! `va_soak()` is the long-running counterpart: randomized stepping of a copy
+//! of a live field for as many seconds as the caller allows.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::grid::{va_create_grid, va_get_cell, va_set_cell, va_step};
+use super::lifecycle::{va_create, va_destroy, va_get_generation};
+use super::region::{va_extract_region, va_import_region};
+use super::snapshot::{va_deserialize_compressed, va_serialize_compressed};
+use super::validate::{field_ref, write_opt};
+use crate::automaton::field::{
+    create_field_1, field_index_of, field_step, field_step_fused, Field,
+};
+use crate::automaton::soak::{soak, soak_round};
+use crate::automaton::stamp::{stamp_pattern, STAMP_BLINKER, STAMP_GLIDER};
+use crate::automaton::{create_grid, randomize_region, step_automaton};
+use crate::state::{Rule, State};
+
+/// Field diffusion did not conserve the total (sequential or fused step).
+pub const SELF_TEST_CONSERVATION: u32 = 1 << 0;
+/// Identical inputs produced different outputs (CA or field).
+pub const SELF_TEST_DETERMINISM: u32 = 1 << 1;
+/// Birth/survival rules or known B4/S4 patterns misbehaved.
+pub const SELF_TEST_RULE: u32 = 1 << 2;
+/// Data did not survive a round trip through the C entry points.
+pub const SELF_TEST_FFI_ROUND_TRIP: u32 = 1 << 3;
+
+fn grid(size: i16) -> State {
+    let mut state = State::default();
+    create_grid(&mut state, size, size, size);
+    state
+}
+
+fn check_conservation() -> bool {
+    let mut field = create_field_1(12, 12, 12, 2);
+    let idx = field_index_of(&field, 5, 6, 7);
+    field.cells[idx] = 1_000_000;
+    let idx = field_index_of(&field, 0, 0, 0);
+    field.cells[idx] = 50_000_000;
+    let expected = field.total();
+
+    let mut fused = field.clone();
+    for _ in 0..8 {
+        field_step(&mut field);
+        field_step_fused(&mut fused);
+    }
+    field.total() == expected && fused.total() == expected
+}
+
+fn check_determinism() -> bool {
+    let run = || {
+        let mut state = grid(16);
+        randomize_region(&mut state, [0, 0, 0], [16, 16, 16], 300_000, 0x5EED);
+        for _ in 0..4 {
+            step_automaton(&mut state);
+        }
+        state.cells
+    };
+
+    let diffuse = || {
+        let mut field = create_field_1(8, 8, 8, 3);
+        let idx = field_index_of(&field, 4, 4, 4);
+        field.cells[idx] = 123_457;
+        for _ in 0..6 {
+            field_step_fused(&mut field);
+        }
+        field.cells
+    };
+
+    run() == run() && diffuse() == diffuse()
+}
+
+fn check_rules() -> bool {
+    // A lone cell with no neighbors dies under B4/S4 but survives under S0
+    let mut state = grid(4);
+    state.cells[0] = 1;
+    step_automaton(&mut state);
+    if state.cells[0] != 0 {
+        return false;
+    }
+    let mut state = grid(4);
+    state.rule = Rule {
+        birth: 0,
+        survival: 1,
+    };
+    state.cells[0] = 1;
+    step_automaton(&mut state);
+    if state.cells[0] != 1 || state.cells.iter().filter(|&&c| c == 1).count() != 1 {
+        return false;
+    }
+
+    // Period-2 oscillator returns to its start
+    let mut state = grid(10);
+    stamp_pattern(&mut state, STAMP_BLINKER, 4, 4, 4, 0);
+    let start = state.cells.clone();
+    step_automaton(&mut state);
+    step_automaton(&mut state);
+    if state.cells != start {
+        return false;
+    }
+
+    // Glider moves 2 cells along -z every 2 generations
+    let mut state = grid(16);
+    stamp_pattern(&mut state, STAMP_GLIDER, 8, 8, 8, 0);
+    let mut expected = grid(16);
+    stamp_pattern(&mut expected, STAMP_GLIDER, 8, 8, 6, 0);
+    step_automaton(&mut state);
+    step_automaton(&mut state);
+    state.cells == expected.cells
+}
+
+fn check_ffi_round_trip() -> bool {
+    unsafe {
+        let a = va_create();
+        let b = va_create();
+        if a.is_null() || b.is_null() {
+            va_destroy(a);
+            va_destroy(b);
+            return false;
+        }
+
+        let mut ok = va_create_grid(a, 8, 8, 8) == 0 && va_create_grid(b, 8, 8, 8) == 0;
+        va_set_cell(a, 1, 2, 3, 1);
+        va_set_cell(a, 7, 7, 7, 1);
+        ok &= va_get_cell(a, 1, 2, 3) == 1 && va_get_cell(a, 0, 0, 0) == 0;
+
+        let mut region = [0u8; 512];
+        ok &= va_extract_region(
+            a,
+            region.as_mut_ptr(),
+            512,
+            0,
+            0,
+            0,
+            8,
+            8,
+            8,
+            ptr::null_mut(),
+        ) == 512;
+        ok &= va_import_region(b, region.as_ptr(), 512, 0, 0, 0, 8, 8, 8) == 512;
+        ok &= va_get_cell(b, 7, 7, 7) == 1;
+
+        va_step(a);
+        let len = va_serialize_compressed(a, ptr::null_mut(), 0);
+        let mut blob = vec![0u8; len as usize];
+        ok &= len > 0 && va_serialize_compressed(a, blob.as_mut_ptr(), len) == len;
+        ok &= va_deserialize_compressed(b, blob.as_ptr(), len) == 0;
+        ok &= va_get_generation(b) == 1 && (*a).cells == (*b).cells;
+
+        va_destroy(a);
+        va_destroy(b);
+        ok
+    }
+}
+
+/// Runs every check and returns a bitmask of the ones that failed.
+pub fn run_self_test() -> u32 {
+    let checks: [(u32, fn() -> bool); 4] = [
+        (SELF_TEST_CONSERVATION, check_conservation),
+        (SELF_TEST_DETERMINISM, check_determinism),
+        (SELF_TEST_RULE, check_rules),
+        (SELF_TEST_FFI_ROUND_TRIP, check_ffi_round_trip),
+    ];
+
+    let mut failures = 0;
+    for (bit, check) in checks {
+        // A panicking check counts as a failure instead of unwinding into C
+        if !catch_unwind(AssertUnwindSafe(check)).unwrap_or(false) {
+            failures |= bit;
+        }
+    }
+    failures
+}
+
+/// Runs a fast battery of invariant checks on this build of the library.
+///
+/// Covers field conservation, determinism, rule correctness, and an FFI
+/// round trip (cells, regions, snapshots). Intended for deployment
+/// validation: call once at load time and refuse to start on a non-zero result.
+///
+/// # Returns
+/// 0 if everything passed, otherwise a bitmask of `SELF_TEST_*` failures.
+#[no_mangle]
+pub extern "C" fn va_self_test() -> u32 {
+    run_self_test()
+}
+
+/// Soak-tests a field configuration: repeatedly steps randomized copies of
+/// `field` for `seconds` (at least one round), checking conservation, bounds,
+/// and underflow after every step.
+///
+/// The live field is never modified. `flags` is a bitmask of `SOAK_FUSED`,
+/// `SOAK_CONTROLLER`, and `SOAK_RANDOM_PARAMS`. This blocks the calling
+/// thread for the whole duration, so run it from an admin command, not a
+/// globalstep.
+///
+/// # Safety
+/// `field` must be null or a valid Field pointer. `out_seed` and `out_rounds`
+/// must each be null or valid for a u64 write.
+///
+/// # Returns
+/// 0 if no invariant was violated, otherwise the `SoakViolation` code (1 =
+/// conservation, 2 = bounds, 3 = underflow) of the first failure, whose round
+/// seed is written to `out_seed` (replay with `va_soak_round`). -1 for a null
+/// field. `out_rounds` receives the number of rounds run.
+#[no_mangle]
+pub unsafe extern "C" fn va_soak(
+    field: *const Field,
+    seconds: u32,
+    flags: u32,
+    out_seed: *mut u64,
+    out_rounds: *mut u64,
+) -> i32 {
+    let Some(field) = field_ref(field) else {
+        return -1;
+    };
+    // Fresh seeds each run; failures are still reproducible via the reported seed
+    let base_seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+
+    let report = soak(field, Duration::from_secs(seconds as u64), flags, base_seed);
+    write_opt(out_seed, report.seed);
+    write_opt(out_rounds, report.rounds);
+    report.violation.map_or(0, |v| v as i32)
+}
+
+/// Replays a single soak round, e.g. the failing seed reported by `va_soak`.
+///
+/// # Safety
+/// `field` must be null or a valid Field pointer.
+///
+/// # Returns
+/// 0 if the round passed, the `SoakViolation` code if it failed, -1 for a null field.
+#[no_mangle]
+pub unsafe extern "C" fn va_soak_round(field: *const Field, seed: u64, flags: u32) -> i32 {
+    let Some(field) = field_ref(field) else {
+        return -1;
+    };
+    match soak_round(field, seed, flags, None) {
+        Ok(()) => 0,
+        Err(violation) => violation as i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::soak::SOAK_FUSED;
+
+    #[test]
+    fn test_self_test_passes() {
+        assert_eq!(va_self_test(), 0);
+    }
+
+    #[test]
+    fn test_soak_leaves_field_untouched() {
+        let mut field = create_field_1(10, 10, 10, 2);
+        field.cells[123] = 5_000_000;
+        let before = field.cells.clone();
+
+        let (mut seed, mut rounds) = (7u64, 0u64);
+        unsafe {
+            assert_eq!(va_soak(&field, 0, SOAK_FUSED, &mut seed, &mut rounds), 0);
+            assert_eq!(va_soak_round(&field, 42, SOAK_FUSED), 0);
+            assert_eq!(va_soak(ptr::null(), 0, 0, &mut seed, &mut rounds), -1);
+        }
+        assert_eq!(seed, 0);
+        assert_eq!(rounds, 1);
+        assert_eq!(field.cells, before);
+        assert_eq!(field.generation, 0);
+    }
+}
@@ -0,0 +1,186 @@
+//! FFI interface for analytic shape fills on fields.
+//!
+//! One call per shape; coordinates are cells, clipping and value rules as in
+//! `automaton::shape`.
+
+use super::validate::field_mut;
+use crate::automaton::field::Field;
+use crate::automaton::shape::{field_paint, Shape};
+
+/// Paint `shape` into `field`, 0 for null.
+unsafe fn paint(field: *mut Field, shape: Shape) -> u64 {
+    field_mut(field).map_or(0, |field| field_paint(field, &shape))
+}
+
+/// Fills the half-open box `[min, max)` with `value`.
+///
+/// # Safety
+/// `field` must be a valid pointer to a Field, or null.
+///
+/// # Returns
+/// Number of cells written (0 for null pointer or a box outside the field).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_fill_box(
+    field: *mut Field,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+    value: u32,
+) -> u64 {
+    let shape = Shape::Box {
+        min: [min_x, min_y, min_z],
+        max: [max_x, max_y, max_z],
+        value,
+    };
+    paint(field, shape)
+}
+
+/// Fills every cell within `radius` of the center with `value`.
+///
+/// # Safety
+/// `field` must be a valid pointer to a Field, or null.
+///
+/// # Returns
+/// Number of cells written (0 for null pointer or a sphere outside the field).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_fill_sphere(
+    field: *mut Field,
+    cx: i16,
+    cy: i16,
+    cz: i16,
+    radius: u16,
+    value: u32,
+) -> u64 {
+    let shape = Shape::Sphere {
+        center: [cx, cy, cz],
+        radius,
+        value,
+    };
+    paint(field, shape)
+}
+
+/// Fills the outer `thickness` cells of a sphere with `value`, leaving the
+/// inside untouched.
+///
+/// # Safety
+/// `field` must be a valid pointer to a Field, or null.
+///
+/// # Returns
+/// Number of cells written (0 for null pointer or a shell outside the field).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_fill_shell(
+    field: *mut Field,
+    cx: i16,
+    cy: i16,
+    cz: i16,
+    radius: u16,
+    thickness: u16,
+    value: u32,
+) -> u64 {
+    let shape = Shape::Shell {
+        center: [cx, cy, cz],
+        radius,
+        thickness,
+        value,
+    };
+    paint(field, shape)
+}
+
+/// Fills the box `[min, max)` with a ramp along `axis` (0 = x, 1 = y, 2 = z)
+/// from `from` in its first layer to `to` in its last. The ramp spans the
+/// whole box even where it is clipped by the field.
+///
+/// # Safety
+/// `field` must be a valid pointer to a Field, or null.
+///
+/// # Returns
+/// Number of cells written (0 for null pointer, axis above 2, or a box outside
+/// the field).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_fill_linear_gradient(
+    field: *mut Field,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+    axis: u8,
+    from: u32,
+    to: u32,
+) -> u64 {
+    let shape = Shape::LinearGradient {
+        min: [min_x, min_y, min_z],
+        max: [max_x, max_y, max_z],
+        axis,
+        from,
+        to,
+    };
+    paint(field, shape)
+}
+
+/// Fills a sphere with a ramp from `inner` at the center to `outer` at `radius`.
+///
+/// # Safety
+/// `field` must be a valid pointer to a Field, or null.
+///
+/// # Returns
+/// Number of cells written (0 for null pointer or a sphere outside the field).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_fill_radial_gradient(
+    field: *mut Field,
+    cx: i16,
+    cy: i16,
+    cz: i16,
+    radius: u16,
+    inner: u32,
+    outer: u32,
+) -> u64 {
+    let shape = Shape::RadialGradient {
+        center: [cx, cy, cz],
+        radius,
+        inner,
+        outer,
+    };
+    paint(field, shape)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{va_create_field, va_destroy_field, va_field_get};
+    use std::ptr;
+
+    #[test]
+    fn test_shape_fills_via_ffi() {
+        let field = va_create_field(10, 10, 10, 2);
+        unsafe {
+            assert_eq!(va_field_fill_box(field, 0, 0, 0, 10, 10, 10, 20), 1000);
+            assert_eq!(va_field_fill_sphere(field, 5, 5, 5, 1, 300), 7);
+            assert_eq!(va_field_get(field, 5, 6, 5), 300);
+            assert_eq!(va_field_fill_shell(field, 5, 5, 5, 2, 1, 40), 33 - 7);
+            assert_eq!(va_field_get(field, 5, 5, 7), 40);
+            assert_eq!(va_field_get(field, 5, 5, 5), 300);
+
+            assert_eq!(
+                va_field_fill_linear_gradient(field, 0, 0, 0, 10, 1, 1, 0, 0, 900),
+                10
+            );
+            assert_eq!(va_field_get(field, 0, 0, 0), 1);
+            assert_eq!(va_field_get(field, 9, 0, 0), 900);
+            assert_eq!(
+                va_field_fill_linear_gradient(field, 0, 0, 0, 10, 1, 1, 7, 0, 900),
+                0
+            );
+            assert!(va_field_fill_radial_gradient(field, 9, 9, 9, 4, 1000, 200) > 0);
+            assert_eq!(va_field_get(field, 9, 9, 9), 1000);
+            assert_eq!(va_field_get(field, 9, 9, 5), 200);
+
+            assert_eq!(va_field_fill_sphere(ptr::null_mut(), 0, 0, 0, 3, 1), 0);
+        }
+        va_destroy_field(field);
+    }
+}
@@ -0,0 +1,302 @@
+//! FFI interface for fields shared between one writer and many readers (see
+//! `automaton::shared`).
+//!
+//! `va_field_share` turns a Field handle into a FieldWriter; readers from
+//! `va_shared_field_reader` only expose read functions, so a consumer given a
+//! reader cannot modify the field.
+
+use super::validate::{buf_mut, region_volume, take_handle, write_opt};
+use crate::automaton::field::field_step_selected;
+use crate::automaton::poststep::FieldStats;
+use crate::automaton::shared::{FieldReader, FieldWriter};
+use crate::automaton::{field_extract_region, field_get, field_set, Field};
+
+/// Hands a field over to a new writer. On success the field pointer must no
+/// longer be used; get it back with `va_shared_field_unshare`. On failure the
+/// caller keeps ownership.
+///
+/// # Safety
+/// `field` must be null or a pointer from `va_create_field` owned by the caller.
+///
+/// # Returns
+/// The writer (free it with `va_destroy_shared_field`), or null for a null field.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_share(field: *mut Field) -> *mut FieldWriter {
+    let Some(field) = take_handle(field) else {
+        return std::ptr::null_mut();
+    };
+    Box::into_raw(Box::new(FieldWriter::new(field)))
+}
+
+/// Gives the field back as a plain Field handle and frees the writer, which
+/// requires every reader to have been released.
+///
+/// # Safety
+/// `writer` must be null or a valid pointer from `va_field_share`. On success
+/// it must no longer be used.
+///
+/// # Returns
+/// The field (free it with `va_destroy_field`), or null if `writer` is null or
+/// readers remain (the writer is then kept).
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_field_unshare(writer: *mut FieldWriter) -> *mut Field {
+    match writer.as_ref() {
+        Some(shared) if shared.readers() == 0 => Box::from_raw(writer)
+            .into_field()
+            .map_or(std::ptr::null_mut(), |field| Box::into_raw(Box::new(field))),
+        _ => std::ptr::null_mut(),
+    }
+}
+
+/// Frees a writer. Its readers keep the field as last written, and the field
+/// is freed with the last reader. Safe to call with null pointer (no-op).
+///
+/// # Safety
+/// `writer` must be null or a pointer from `va_field_share`, not used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn va_destroy_shared_field(writer: *mut FieldWriter) {
+    if !writer.is_null() {
+        drop(Box::from_raw(writer));
+    }
+}
+
+/// Creates a new reader of the writer's field.
+///
+/// # Safety
+/// `writer` must be null or a valid pointer from `va_field_share`.
+///
+/// # Returns
+/// The reader (free it with `va_release_reader`), or null for a null writer.
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_field_reader(writer: *const FieldWriter) -> *mut FieldReader {
+    match writer.as_ref() {
+        Some(writer) => Box::into_raw(Box::new(writer.reader())),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Gets the number of live readers of the writer's field.
+///
+/// # Safety
+/// `writer` must be null or a valid pointer from `va_field_share`.
+///
+/// # Returns
+/// The reader count, or 0 for a null writer.
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_field_readers(writer: *const FieldWriter) -> u64 {
+    writer.as_ref().map_or(0, |writer| writer.readers() as u64)
+}
+
+/// Steps the shared field by one generation (as `va_field_step`).
+///
+/// # Safety
+/// `writer` must be null or a valid pointer from `va_field_share`.
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_field_step(writer: *mut FieldWriter) -> i32 {
+    match writer.as_mut() {
+        Some(writer) => {
+            writer.write(field_step_selected);
+            0
+        }
+        None => 1,
+    }
+}
+
+/// Sets a cell of the shared field (as `va_field_set`). Out-of-bounds
+/// coordinates are silently ignored.
+///
+/// # Safety
+/// `writer` must be null or a valid pointer from `va_field_share`.
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_shared_field_set(
+    writer: *mut FieldWriter,
+    x: i16,
+    y: i16,
+    z: i16,
+    value: u32,
+) -> i32 {
+    match writer.as_mut() {
+        Some(writer) => {
+            writer.write(|field| field_set(field, x, y, z, value));
+            0
+        }
+        None => 1,
+    }
+}
+
+/// Creates another reader of the same field.
+///
+/// # Safety
+/// `reader` must be null or a valid reader pointer.
+///
+/// # Returns
+/// The new reader, or null for a null reader.
+#[no_mangle]
+pub unsafe extern "C" fn va_reader_clone(reader: *const FieldReader) -> *mut FieldReader {
+    match reader.as_ref() {
+        Some(reader) => Box::into_raw(Box::new(reader.clone())),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Frees a reader. Safe to call with null pointer (no-op).
+///
+/// # Safety
+/// `reader` must be null or a reader pointer, not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn va_release_reader(reader: *mut FieldReader) {
+    if !reader.is_null() {
+        drop(Box::from_raw(reader));
+    }
+}
+
+/// Gets a cell of the shared field (as `va_field_get`).
+///
+/// # Safety
+/// `reader` must be null or a valid reader pointer.
+///
+/// # Returns
+/// The value, or 0 if out of bounds or `reader` is null.
+#[no_mangle]
+pub unsafe extern "C" fn va_reader_get(reader: *const FieldReader, x: i16, y: i16, z: i16) -> u32 {
+    let Some(reader) = reader.as_ref() else {
+        return 0;
+    };
+    reader.read(|field| field_get(field, x, y, z).map_or(0, |nz| nz.get()))
+}
+
+/// Gets the generation of the shared field.
+///
+/// # Safety
+/// `reader` must be null or a valid reader pointer.
+///
+/// # Returns
+/// The generation, or 0 for a null reader.
+#[no_mangle]
+pub unsafe extern "C" fn va_reader_get_generation(reader: *const FieldReader) -> u64 {
+    reader
+        .as_ref()
+        .map_or(0, |reader| reader.read(|field| field.generation))
+}
+
+/// Gets the sum of all cells of the shared field.
+///
+/// # Safety
+/// `reader` must be null or a valid reader pointer.
+///
+/// # Returns
+/// The total, or 0 for a null reader.
+#[no_mangle]
+pub unsafe extern "C" fn va_reader_total(reader: *const FieldReader) -> u64 {
+    reader
+        .as_ref()
+        .map_or(0, |reader| reader.read(|field| FieldStats::of(field).total))
+}
+
+/// Extracts a region of the shared field, with the layout and clamping of
+/// `va_field_extract_region`.
+///
+/// # Safety
+/// - `reader` must be null or a valid reader pointer
+/// - `out_buf` must point to at least `buf_len` writable u32 values, or be null
+/// - `out_generation` must be a valid writable pointer, or null (skipped)
+///
+/// # Returns
+/// Number of cells written, or 0 on error (as `va_field_extract_region`).
+#[no_mangle]
+pub unsafe extern "C" fn va_reader_extract_region(
+    reader: *const FieldReader,
+    out_buf: *mut u32,
+    buf_len: u64,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+    out_generation: *mut u64,
+) -> u64 {
+    let Some(reader) = reader.as_ref() else {
+        return 0;
+    };
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    reader.read(|field| {
+        write_opt(out_generation, field.generation);
+        if region_volume(min, max).is_none() {
+            return 0;
+        }
+        match buf_mut(out_buf, buf_len) {
+            Some(out) => field_extract_region(field, out, min, max),
+            None => 0,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{va_create_field, va_destroy_field, va_field_get_generation};
+    use std::ptr;
+
+    #[test]
+    fn test_shared_field_via_ffi() {
+        unsafe {
+            assert!(va_field_share(ptr::null_mut()).is_null());
+            let writer = va_field_share(va_create_field(4, 4, 4, 1));
+            let visual = va_shared_field_reader(writer);
+            let stats = va_reader_clone(visual);
+            assert_eq!(va_shared_field_readers(writer), 2);
+
+            assert_eq!(va_shared_field_set(writer, 1, 2, 3, 900), 0);
+            assert_eq!(va_reader_get(stats, 1, 2, 3), 900);
+            assert_eq!(va_shared_field_step(writer), 0);
+            assert_eq!(va_reader_get_generation(visual), 1);
+            assert_eq!(va_reader_total(visual), va_reader_total(stats));
+
+            let mut out = [0u32; 8];
+            let mut generation = 0;
+            assert_eq!(
+                va_reader_extract_region(
+                    visual,
+                    out.as_mut_ptr(),
+                    8,
+                    0,
+                    0,
+                    0,
+                    2,
+                    2,
+                    2,
+                    &mut generation
+                ),
+                8
+            );
+            assert_eq!(generation, 1);
+
+            // Unsharing waits for the readers
+            assert!(va_shared_field_unshare(writer).is_null());
+            va_release_reader(stats);
+            va_release_reader(visual);
+            assert_eq!(va_shared_field_readers(writer), 0);
+            let field = va_shared_field_unshare(writer);
+            assert_eq!(va_field_get_generation(field), 1);
+            va_destroy_field(field);
+
+            // Readers outlive a destroyed writer
+            let writer = va_field_share(va_create_field(2, 2, 2, 1));
+            va_shared_field_set(writer, 0, 0, 0, 7);
+            let reader = va_shared_field_reader(writer);
+            va_destroy_shared_field(writer);
+            assert_eq!(va_reader_get(reader, 0, 0, 0), 7);
+            va_release_reader(reader);
+            assert_eq!(va_reader_get(ptr::null(), 0, 0, 0), 0);
+            assert_eq!(va_shared_field_step(ptr::null_mut()), 1);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! FFI interface for shared-memory field exports (see `automaton::shm`).
+//!
+//! Only compiled with the `shm` feature (on by default); check bit 256 of
+//! `va_build_features` before calling.
+
+use super::validate::{buf_ref, field_ref, write_text};
+use crate::automaton::field::Field;
+use crate::automaton::shm::ShmExport;
+
+/// Exports the field's cells under `name` (1 to 64 ASCII letters, digits,
+/// `_`, `-`, `.`), replacing an earlier export of the same name, and
+/// publishes them once. The export is a copy: call `va_shm_publish` after
+/// each step observers should see.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `name` must point to at least `name_len` readable bytes, or be null
+///
+/// # Returns
+/// The export (free it with `va_shm_destroy`), or null on failure (null
+/// pointer, invalid name, or the shared-memory file could not be created).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_shm_export(
+    field: *const Field,
+    name: *const u8,
+    name_len: u64,
+) -> *mut ShmExport {
+    let name = buf_ref(name, name_len).and_then(|bytes| std::str::from_utf8(bytes).ok());
+    let (Some(field), Some(name)) = (field_ref(field), name) else {
+        return std::ptr::null_mut();
+    };
+    match ShmExport::create(name, field) {
+        Ok(export) => Box::into_raw(Box::new(export)),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Copies the field's current cells and generation into the export.
+///
+/// # Safety
+/// - `export` must be a valid pointer from `va_field_shm_export`, or null
+/// - `field` must be a valid pointer to a Field, or null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer, or dimensions differ from the
+/// exported field's).
+#[no_mangle]
+pub unsafe extern "C" fn va_shm_publish(export: *mut ShmExport, field: *const Field) -> i32 {
+    let (Some(export), Some(field)) = (export.as_mut(), field_ref(field)) else {
+        return 1;
+    };
+    if export.publish(field) {
+        0
+    } else {
+        1
+    }
+}
+
+/// Writes the path of the export's shared-memory file (UTF-8, not
+/// NUL-terminated), to hand to the observing process.
+///
+/// # Safety
+/// - `export` must be a valid pointer from `va_field_shm_export`, or null
+/// - `out_buf` must point to at least `capacity` writable bytes, or be null
+///
+/// # Returns
+/// Number of bytes written, or 0 on error (null pointer, or `capacity` too small).
+/// Pass a null `out_buf` to query the required size without writing.
+#[no_mangle]
+pub unsafe extern "C" fn va_shm_path(
+    export: *const ShmExport,
+    out_buf: *mut u8,
+    capacity: u64,
+) -> u64 {
+    match export.as_ref() {
+        Some(export) => write_text(&export.path().to_string_lossy(), out_buf, capacity),
+        None => 0,
+    }
+}
+
+/// Removes the export's name and frees it. Processes that mapped the file
+/// keep their view of the last publish. Safe to call with null pointer
+/// (no-op).
+///
+/// # Safety
+/// `export` must be null or a pointer from `va_field_shm_export`, not used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn va_shm_destroy(export: *mut ShmExport) {
+    if !export.is_null() {
+        drop(Box::from_raw(export));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{va_create_field, va_destroy_field, va_field_set, va_field_step};
+    use std::ptr;
+
+    #[test]
+    fn test_shm_export_via_ffi() {
+        unsafe {
+            let field = va_create_field(8, 8, 8, 2);
+            va_field_set(field, 3, 3, 3, 70_000);
+            let name = format!("va-test-ffi-{}", std::process::id());
+            let export = va_field_shm_export(field, name.as_ptr(), name.len() as u64);
+            assert!(!export.is_null());
+
+            let len = va_shm_path(export, ptr::null_mut(), 0);
+            let mut path = vec![0u8; len as usize];
+            assert_eq!(va_shm_path(export, path.as_mut_ptr(), len), len);
+            let path = String::from_utf8(path).unwrap();
+            assert!(path.ends_with(&name));
+
+            va_field_step(field);
+            assert_eq!(va_shm_publish(export, field), 0);
+            let bytes = std::fs::read(&path).unwrap();
+            assert_eq!(bytes.len(), 64 + 512 * 4);
+            assert_eq!(u64::from_ne_bytes(bytes[40..48].try_into().unwrap()), 1);
+
+            va_shm_destroy(export);
+            assert!(!std::path::Path::new(&path).exists());
+            let bad = "../escape";
+            assert!(va_field_shm_export(field, bad.as_ptr(), bad.len() as u64).is_null());
+            assert!(va_field_shm_export(field, ptr::null(), 4).is_null());
+            assert_eq!(va_shm_publish(ptr::null_mut(), field), 1);
+            va_destroy_field(field);
+        }
+    }
+}
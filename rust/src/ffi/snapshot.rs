@@ -0,0 +1,600 @@
+//! State and field snapshots, and rule configuration (save files / mod storage).
+
+use super::report::note_error;
+use super::validate::{buf_mut, buf_ref, field_mut, field_ref, state_mut, state_ref, write_opt};
+use crate::automaton::age::CellAge;
+use crate::automaton::field::Field;
+use crate::automaton::phase::field_set_phase_thresholds;
+use crate::automaton::rule::{export_rule_table, parse_rule};
+use crate::automaton::snapshot::{
+    compressed_size, deserialize_field, deserialize_state, deserialize_state_compressed,
+    field_serialized_size, serialize_field, serialize_state, serialize_state_compressed,
+    serialized_size,
+};
+use crate::state::{Rule, State};
+
+/// Borrow an optional baseline Field: null means no baseline and is not an
+/// error, anything else must be a live Field. Err (recorded as the last error)
+/// for a destroyed or mismatched handle.
+///
+/// # Safety
+/// `baseline` must be null or a pointer returned by `va_create_field`, live or
+/// destroyed.
+unsafe fn baseline_ref<'a>(baseline: *const Field) -> Result<Option<&'a Field>, ()> {
+    if baseline.is_null() {
+        return Ok(None);
+    }
+    field_ref(baseline).map(Some).ok_or(())
+}
+
+/// Serializes the state into a versioned binary blob.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `out_buf` must point to at least `capacity` writable bytes, or be null
+///
+/// # Returns
+/// Number of bytes written, or 0 on error (null pointer, or `capacity` too small).
+/// Pass a null `out_buf` to query the required size without writing.
+#[no_mangle]
+pub unsafe extern "C" fn va_serialize(ptr: *const State, out_buf: *mut u8, capacity: u64) -> u64 {
+    let Some(state) = state_ref(ptr) else {
+        return 0;
+    };
+    if out_buf.is_null() {
+        return serialized_size(state) as u64;
+    }
+
+    let Some(out) = buf_mut(out_buf, capacity) else {
+        return 0;
+    };
+    serialize_state(state, out).map(|n| n as u64).unwrap_or(0)
+}
+
+/// `restored` with the protection mask, write queue, species relations, and
+/// age tracking of the handle it replaces, which belong to the handle rather
+/// than the snapshot (the mask is kept if the size matches, the others always).
+/// Snapshots carry no species ids or ages, so restored live cells are species
+/// 1 and age 0.
+fn keep_handle_settings(mut restored: State, target: &mut State) -> State {
+    if (restored.width, restored.height, restored.depth)
+        == (target.width, target.height, target.depth)
+    {
+        restored.protection = target.protection.take();
+    }
+    restored.write_queue = target.write_queue.take();
+    restored.species = target.species.take().map(|mut layer| {
+        layer.ids = vec![0; restored.cells.len()];
+        layer
+    });
+    restored.age = target
+        .age
+        .take()
+        .map(|age| CellAge::new(restored.cells.len(), age.max_age));
+    restored.transitions = target.transitions.take();
+    restored.mode = target.mode;
+    restored.history = target.history.take().map(|mut history| {
+        history.clear();
+        history
+    });
+    restored.cycles = target.cycles.take().map(|mut tracker| {
+        tracker.clear();
+        tracker
+    });
+    restored
+}
+
+/// Replaces the state with the contents of a blob produced by `va_serialize`.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `in_buf` must point to at least `len` readable bytes, or be null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer or malformed blob). On failure the
+/// state is left unchanged.
+#[no_mangle]
+pub unsafe extern "C" fn va_deserialize(ptr: *mut State, in_buf: *const u8, len: u64) -> i32 {
+    let (Some(target), Some(data)) = (state_mut(ptr), buf_ref(in_buf, len)) else {
+        return 1;
+    };
+    match deserialize_state(data) {
+        Ok(state) => {
+            *target = keep_handle_settings(state, target);
+            0
+        }
+        Err(error) => {
+            note_error("va_deserialize", error);
+            1
+        }
+    }
+}
+
+/// Serializes the state with a run-length encoded cell payload.
+///
+/// # Safety
+/// Same contract as `va_serialize`.
+///
+/// # Returns
+/// Number of bytes written, or 0 on error (null pointer, or `capacity` too small).
+/// Pass a null `out_buf` to query the required size without writing.
+#[no_mangle]
+pub unsafe extern "C" fn va_serialize_compressed(
+    ptr: *const State,
+    out_buf: *mut u8,
+    capacity: u64,
+) -> u64 {
+    let Some(state) = state_ref(ptr) else {
+        return 0;
+    };
+    if out_buf.is_null() {
+        return compressed_size(state) as u64;
+    }
+
+    let Some(out) = buf_mut(out_buf, capacity) else {
+        return 0;
+    };
+    serialize_state_compressed(state, out)
+        .map(|n| n as u64)
+        .unwrap_or(0)
+}
+
+/// Replaces the state with the contents of a blob produced by `va_serialize_compressed`.
+///
+/// # Safety
+/// Same contract as `va_deserialize`.
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer or malformed blob). On failure the
+/// state is left unchanged.
+#[no_mangle]
+pub unsafe extern "C" fn va_deserialize_compressed(
+    ptr: *mut State,
+    in_buf: *const u8,
+    len: u64,
+) -> i32 {
+    let (Some(target), Some(data)) = (state_mut(ptr), buf_ref(in_buf, len)) else {
+        return 1;
+    };
+    match deserialize_state_compressed(data) {
+        Ok(state) => {
+            *target = keep_handle_settings(state, target);
+            0
+        }
+        Err(error) => {
+            note_error("va_deserialize_compressed", error);
+            1
+        }
+    }
+}
+
+/// Number of bytes needed to serialize the state (exact, so the Lua side can
+/// allocate the buffer once). `compressed` selects the RLE format when non-zero.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// Buffer size in bytes, or 0 if ptr is null.
+#[no_mangle]
+pub unsafe extern "C" fn va_serialized_size_hint(ptr: *const State, compressed: u8) -> u64 {
+    let Some(state) = state_ref(ptr) else {
+        return 0;
+    };
+    if compressed != 0 {
+        compressed_size(state) as u64
+    } else {
+        serialized_size(state) as u64
+    }
+}
+
+/// Serializes a field (dimensions, diffusion rate, conductivity, generation,
+/// and cells) into a versioned binary blob.
+///
+/// If `baseline` is non-null and has the same dimensions, cells are delta
+/// encoded against it, which shrinks saves of smooth or slowly changing fields
+/// several-fold. Keep the baseline (e.g. the last full save, restored into its
+/// own Field) to decode the blob later.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `baseline` must be a valid pointer to a Field, or null (plain encoding)
+/// - `out_buf` must point to at least `capacity` writable bytes, or be null
+///
+/// # Returns
+/// Number of bytes written, or 0 on error (null field, invalid baseline handle,
+/// or `capacity` too small).
+/// Pass a null `out_buf` to query the required size without writing.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_serialize(
+    field: *const Field,
+    baseline: *const Field,
+    out_buf: *mut u8,
+    capacity: u64,
+) -> u64 {
+    let Some(field) = field_ref(field) else {
+        return 0;
+    };
+    let Ok(baseline) = baseline_ref(baseline) else {
+        return 0;
+    };
+    if out_buf.is_null() {
+        return field_serialized_size(field, baseline) as u64;
+    }
+
+    let Some(out) = buf_mut(out_buf, capacity) else {
+        return 0;
+    };
+    serialize_field(field, baseline, out)
+        .map(|n| n as u64)
+        .unwrap_or(0)
+}
+
+/// Replaces a field with the contents of a blob produced by `va_field_serialize`.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `baseline` must be a valid pointer to a Field, or null; delta-encoded blobs
+///   need the same baseline they were written against
+/// - `in_buf` must point to at least `len` readable bytes, or be null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer, malformed blob, invalid baseline
+/// handle, or missing/wrong baseline). On failure the field is left unchanged.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_deserialize(
+    field: *mut Field,
+    baseline: *const Field,
+    in_buf: *const u8,
+    len: u64,
+) -> i32 {
+    let (Some(target), Some(data)) = (field_mut(field), buf_ref(in_buf, len)) else {
+        return 1;
+    };
+    let Ok(baseline) = baseline_ref(baseline) else {
+        return 1;
+    };
+    match deserialize_field(data, baseline) {
+        Ok(mut restored) => {
+            // Flow recording, sources, conductivity curve, axis rates, boundaries,
+            // periodic axes, protection, zones, and phase thresholds belong to
+            // the handle, not the saved state; phases are reclassified from the
+            // restored values
+            restored.flow_record = target.flow_record.take().map(|_| Vec::new());
+            restored.conductivity_curve = target.conductivity_curve.take();
+            restored.axis_rates = target.axis_rates;
+            restored.boundaries = target.boundaries;
+            restored.periodic = target.periodic;
+            restored.zones = target.zones.take();
+            if (restored.width, restored.height, restored.depth)
+                == (target.width, target.height, target.depth)
+            {
+                restored.sources = std::mem::take(&mut target.sources);
+                restored.protection = target.protection.take();
+            }
+            if let Some(phases) = target.phases.take() {
+                field_set_phase_thresholds(&mut restored, &phases.thresholds);
+            }
+            *target = restored;
+            0
+        }
+        Err(error) => {
+            note_error("va_field_deserialize", error);
+            1
+        }
+    }
+}
+
+/// Sets the birth/survival rule. Bit `n` of each mask enables neighbor count `n`.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_set_rule(ptr: *mut State, birth_mask: u32, survival_mask: u32) -> i32 {
+    let Some(state) = state_mut(ptr) else {
+        return 1;
+    };
+    state.rule = Rule {
+        birth: birth_mask & Rule::MASK,
+        survival: survival_mask & Rule::MASK,
+    };
+    0
+}
+
+/// Gets the birth/survival rule masks.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `out_birth` and `out_survival` must be valid writable pointers, or null (skipped)
+///
+/// # Returns
+/// 0 on success, 1 on failure (null state pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_get_rule(
+    ptr: *const State,
+    out_birth: *mut u32,
+    out_survival: *mut u32,
+) -> i32 {
+    let Some(state) = state_ref(ptr) else {
+        return 1;
+    };
+    let rule = state.rule;
+    write_opt(out_birth, rule.birth);
+    write_opt(out_survival, rule.survival);
+    0
+}
+
+/// Writes the current rule as a Golly-style `@RULE`/`@TABLE` text file (UTF-8,
+/// not NUL-terminated), including B/S and Golly 3D notations as metadata.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `out_buf` must point to at least `capacity` writable bytes, or be null
+///
+/// # Returns
+/// Number of bytes written, or 0 on error (null pointer, or `capacity` too small).
+/// Pass a null `out_buf` to query the required size without writing.
+#[no_mangle]
+pub unsafe extern "C" fn va_export_rule_table(
+    ptr: *const State,
+    out_buf: *mut u8,
+    capacity: u64,
+) -> u64 {
+    let Some(state) = state_ref(ptr) else {
+        return 0;
+    };
+    let table = export_rule_table(&state.rule);
+    if out_buf.is_null() {
+        return table.len() as u64;
+    }
+
+    match buf_mut(out_buf, capacity) {
+        Some(out) if out.len() >= table.len() => {
+            out[..table.len()].copy_from_slice(table.as_bytes());
+            table.len() as u64
+        }
+        _ => 0,
+    }
+}
+
+/// Sets the rule from text notation: `B4/S4` or Golly 3D.lua `3D4/4`.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `text` must point to at least `len` readable bytes, or be null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer or unparseable rule). On failure
+/// the rule is left unchanged.
+#[no_mangle]
+pub unsafe extern "C" fn va_set_rule_string(ptr: *mut State, text: *const u8, len: u64) -> i32 {
+    let (Some(state), Some(bytes)) = (state_mut(ptr), buf_ref(text, len)) else {
+        return 1;
+    };
+    let Ok(text) = std::str::from_utf8(bytes) else {
+        return 1;
+    };
+    match parse_rule(text) {
+        Ok(rule) => {
+            state.rule = rule;
+            0
+        }
+        Err(error) => {
+            note_error("va_set_rule_string", error);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::error::{va_last_error_code, VA_ERR_INVALID_HANDLE};
+    use crate::ffi::grid::{va_create_grid, va_get_cell, va_set_cell, va_step};
+    use crate::ffi::lifecycle::{va_create, va_destroy, va_get_generation};
+    use std::ptr;
+
+    #[test]
+    fn test_field_serialize_via_ffi() {
+        use crate::ffi::field::{
+            va_create_field, va_destroy_field, va_field_get, va_field_get_generation, va_field_set,
+            va_field_step,
+        };
+
+        let a = va_create_field(8, 8, 8, 2);
+        va_field_set(a, 4, 4, 4, 900_000);
+        va_field_step(a);
+
+        unsafe {
+            let size = va_field_serialize(a, ptr::null(), ptr::null_mut(), 0);
+            assert_eq!(size, 44 + 512 * 4);
+            let mut full = vec![0u8; size as usize];
+            assert_eq!(
+                va_field_serialize(a, ptr::null(), full.as_mut_ptr(), size),
+                size
+            );
+
+            // The full save doubles as the baseline for the next, delta-encoded save
+            let baseline = va_create_field(1, 1, 1, 0);
+            assert_eq!(
+                va_field_deserialize(baseline, ptr::null(), full.as_ptr(), size),
+                0
+            );
+            va_field_step(a);
+
+            let delta_size = va_field_serialize(a, baseline, ptr::null_mut(), 0);
+            assert!(delta_size < size / 2);
+            let mut delta = vec![0u8; delta_size as usize];
+            va_field_serialize(a, baseline, delta.as_mut_ptr(), delta_size);
+
+            let b = va_create_field(1, 1, 1, 0);
+            let len = delta_size;
+            assert_eq!(va_field_deserialize(b, ptr::null(), delta.as_ptr(), len), 1);
+            assert_eq!(va_field_deserialize(b, baseline, delta.as_ptr(), len), 0);
+            assert_eq!(va_field_get_generation(b), 2);
+            assert_eq!(va_field_get(b, 4, 4, 4), va_field_get(a, 4, 4, 4));
+            assert_eq!((*b).cells, (*a).cells);
+
+            // Too small / null
+            assert_eq!(va_field_serialize(a, ptr::null(), full.as_mut_ptr(), 10), 0);
+            assert_eq!(
+                va_field_serialize(ptr::null(), ptr::null(), ptr::null_mut(), 0),
+                0
+            );
+            assert_eq!(
+                va_field_deserialize(ptr::null_mut(), ptr::null(), full.as_ptr(), size),
+                1
+            );
+
+            // A handle of another kind is refused, not read as a Field
+            let state = va_create();
+            let not_field = state.cast::<Field>();
+            assert_eq!(va_field_serialize(a, not_field, ptr::null_mut(), 0), 0);
+            assert_eq!(va_last_error_code(), VA_ERR_INVALID_HANDLE);
+            assert_eq!(va_field_deserialize(b, not_field, delta.as_ptr(), len), 1);
+            va_destroy(state);
+
+            va_destroy_field(b);
+            va_destroy_field(baseline);
+        }
+        va_destroy_field(a);
+    }
+
+    #[test]
+    fn test_serialize_deserialize_via_ffi() {
+        unsafe {
+            let a = va_create();
+            va_create_grid(a, 8, 8, 8);
+            va_set_rule(a, 1 << 3, (1 << 2) | (1 << 3));
+            va_set_cell(a, 4, 4, 4, 1);
+            va_set_cell(a, 3, 4, 4, 1);
+            va_set_cell(a, 5, 4, 4, 1);
+            va_step(a);
+
+            let size = va_serialize(a, ptr::null_mut(), 0);
+            assert_eq!(size, 28 + 512);
+            let mut buf = vec![0u8; size as usize];
+            assert_eq!(va_serialize(a, buf.as_mut_ptr(), size), size);
+
+            let b = va_create();
+            assert_eq!(va_deserialize(b, buf.as_ptr(), size), 0);
+            assert_eq!(va_get_generation(b), 1);
+
+            let (mut birth, mut survival) = (0u32, 0u32);
+            assert_eq!(va_get_rule(b, &mut birth, &mut survival), 0);
+            assert_eq!((birth, survival), (1 << 3, (1 << 2) | (1 << 3)));
+
+            for z in 0..8 {
+                for y in 0..8 {
+                    for x in 0..8 {
+                        assert_eq!(va_get_cell(a, x, y, z), va_get_cell(b, x, y, z));
+                    }
+                }
+            }
+
+            va_destroy(a);
+            va_destroy(b);
+        }
+    }
+
+    #[test]
+    fn test_deserialize_failure_leaves_state_unchanged() {
+        unsafe {
+            let state = va_create();
+            va_create_grid(state, 4, 4, 4);
+            va_set_cell(state, 1, 1, 1, 1);
+
+            let garbage = [0xFFu8; 64];
+            assert_eq!(va_deserialize(state, garbage.as_ptr(), 64), 1);
+            assert_eq!(va_get_cell(state, 1, 1, 1), 1);
+
+            // Capacity too small
+            let mut small = [0u8; 16];
+            assert_eq!(va_serialize(state, small.as_mut_ptr(), 16), 0);
+
+            va_destroy(state);
+        }
+    }
+
+    #[test]
+    fn test_compressed_via_ffi() {
+        unsafe {
+            let a = va_create();
+            va_create_grid(a, 32, 32, 32);
+            va_set_cell(a, 7, 8, 9, 1);
+            va_set_cell(a, 31, 31, 31, 1);
+
+            let size = va_serialized_size_hint(a, 1);
+            assert!(size < va_serialized_size_hint(a, 0));
+            let mut buf = vec![0u8; size as usize];
+            assert_eq!(va_serialize_compressed(a, buf.as_mut_ptr(), size), size);
+            assert_eq!(va_serialize_compressed(a, buf.as_mut_ptr(), size - 1), 0);
+
+            let b = va_create();
+            assert_eq!(va_deserialize_compressed(b, buf.as_ptr(), size), 0);
+            assert_eq!(va_get_cell(b, 7, 8, 9), 1);
+            assert_eq!(va_get_cell(b, 31, 31, 31), 1);
+            assert_eq!(va_get_cell(b, 0, 0, 0), 0);
+
+            // Raw decoder rejects the compressed format
+            assert_eq!(va_deserialize(b, buf.as_ptr(), size), 1);
+
+            va_destroy(a);
+            va_destroy(b);
+        }
+    }
+
+    #[test]
+    fn test_rule_string_and_table_via_ffi() {
+        unsafe {
+            let state = va_create();
+            let text = b"3D5..7/6";
+            assert_eq!(
+                va_set_rule_string(state, text.as_ptr(), text.len() as u64),
+                0
+            );
+
+            let (mut birth, mut survival) = (0u32, 0u32);
+            va_get_rule(state, &mut birth, &mut survival);
+            assert_eq!((birth, survival), (1 << 6, 0b1110_0000));
+
+            let bad = b"Conway";
+            assert_eq!(va_set_rule_string(state, bad.as_ptr(), bad.len() as u64), 1);
+            va_get_rule(state, &mut birth, &mut survival);
+            assert_eq!(birth, 1 << 6);
+
+            let size = va_export_rule_table(state, ptr::null_mut(), 0);
+            let mut buf = vec![0u8; size as usize];
+            assert_eq!(va_export_rule_table(state, buf.as_mut_ptr(), size), size);
+            assert_eq!(va_export_rule_table(state, buf.as_mut_ptr(), size - 1), 0);
+            let table = String::from_utf8(buf).unwrap();
+            assert!(table.contains("# notation: B6/S5..7"));
+
+            va_destroy(state);
+        }
+    }
+
+    #[test]
+    fn test_null_pointer_handling() {
+        unsafe {
+            assert_eq!(va_serialize(ptr::null(), ptr::null_mut(), 0), 0);
+            assert_eq!(va_deserialize(ptr::null_mut(), ptr::null(), 0), 1);
+            assert_eq!(va_serialize_compressed(ptr::null(), ptr::null_mut(), 0), 0);
+            assert_eq!(
+                va_deserialize_compressed(ptr::null_mut(), ptr::null(), 0),
+                1
+            );
+            assert_eq!(va_serialized_size_hint(ptr::null(), 1), 0);
+            assert_eq!(va_set_rule(ptr::null_mut(), 0, 0), 1);
+            assert_eq!(va_export_rule_table(ptr::null(), ptr::null_mut(), 0), 0);
+            assert_eq!(va_set_rule_string(ptr::null_mut(), ptr::null(), 0), 1);
+            assert_eq!(
+                va_get_rule(ptr::null(), ptr::null_mut(), ptr::null_mut()),
+                1
+            );
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! FFI interface for multi-species grids (see `automaton::species`).
+
+use super::validate::{state_mut, state_ref};
+use crate::automaton::species::{cell_species, set_cell_species, Species};
+use crate::state::State;
+
+/// Sets a cell alive with species `species` (1..=16), or kills it (0). The
+/// first call gives the grid a species layer; live cells without an id count
+/// as species 1.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer, out of bounds, or species above 16).
+#[no_mangle]
+pub unsafe extern "C" fn va_set_cell_species(
+    ptr: *mut State,
+    x: i16,
+    y: i16,
+    z: i16,
+    species: u8,
+) -> i32 {
+    let Some(state) = state_mut(ptr) else {
+        return 1;
+    };
+    if set_cell_species(state, x, y, z, species) {
+        0
+    } else {
+        1
+    }
+}
+
+/// Gets the species of a cell.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// The species id, 1 for a live cell of a grid without species, or 0 (dead,
+/// out of bounds, or null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_get_cell_species(ptr: *const State, x: i16, y: i16, z: i16) -> u8 {
+    state_ref(ptr).map_or(0, |state| cell_species(state, x, y, z))
+}
+
+/// Makes species `observer` count neighbors of species `other` as alive
+/// (`alive` nonzero, the default) or dead in its birth and survival counts.
+/// Gives the grid a species layer if it has none.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer or an id outside 1..=16).
+#[no_mangle]
+pub unsafe extern "C" fn va_set_species_relation(
+    ptr: *mut State,
+    observer: u8,
+    other: u8,
+    alive: u8,
+) -> i32 {
+    let Some(state) = state_mut(ptr) else {
+        return 1;
+    };
+    let len = state.cells.len();
+    let layer = state.species.get_or_insert_with(|| Species::new(len));
+    if layer.set_relation(observer, other, alive != 0) {
+        0
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::grid::{va_create_grid, va_get_cell, va_set_cell, va_step};
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use std::ptr;
+
+    #[test]
+    fn test_species_via_ffi() {
+        unsafe {
+            let state = va_create();
+            assert_eq!(va_set_cell_species(state, 0, 0, 0, 1), 1);
+            va_create_grid(state, 8, 8, 8);
+            va_set_cell(state, 1, 1, 1, 1);
+            assert_eq!(va_get_cell_species(state, 1, 1, 1), 1);
+
+            for (x, y, species) in [(4, 4, 2), (3, 4, 2), (4, 3, 2), (4, 5, 7)] {
+                assert_eq!(va_set_cell_species(state, x, y, 4, species), 0);
+            }
+            assert_eq!(va_get_cell(state, 4, 5, 4), 1);
+            assert_eq!(va_get_cell_species(state, 4, 5, 4), 7);
+            assert_eq!(va_set_cell_species(state, 4, 5, 4, 17), 1);
+            va_step(state);
+            assert_eq!(va_get_cell_species(state, 3, 4, 3), 2);
+
+            // Species 2 ignoring 7 sees only three neighbors at (3, 4, 3)
+            assert_eq!(va_set_cell_species(state, 3, 4, 3, 0), 0);
+            assert_eq!(va_get_cell(state, 3, 4, 3), 0);
+            va_create_grid(state, 8, 8, 8);
+            assert_eq!(va_set_species_relation(state, 2, 7, 0), 0);
+            assert_eq!(va_set_species_relation(state, 0, 7, 0), 1);
+            for (x, y, species) in [(4, 4, 2), (3, 4, 2), (4, 3, 2), (4, 5, 7)] {
+                va_set_cell_species(state, x, y, 4, species);
+            }
+            va_step(state);
+            assert_eq!(va_get_cell_species(state, 3, 4, 3), 0);
+
+            assert_eq!(va_get_cell_species(ptr::null(), 0, 0, 0), 0);
+            assert_eq!(va_set_species_relation(ptr::null_mut(), 1, 2, 0), 1);
+            va_destroy(state);
+        }
+    }
+}
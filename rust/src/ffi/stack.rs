@@ -0,0 +1,171 @@
+//! FFI interface for field stacks (several coupled fields on one grid).
+
+use super::validate::{dims_valid, stack_mut, stack_ref};
+use crate::automaton::stack::{
+    create_field_stack, field_stack_step, stack_get, stack_set, FieldStack,
+};
+
+/// Create a stack of `num_layers` fields sharing one grid, every value at 1.
+/// Layers start with diffusion_rate 2 and full conductivity; change them with
+/// `va_field_stack_set_layer`.
+///
+/// # Returns
+/// A new FieldStack, or NULL for non-positive dimensions or zero layers.
+#[no_mangle]
+pub extern "C" fn va_create_field_stack(
+    width: i16,
+    height: i16,
+    depth: i16,
+    num_layers: u8,
+) -> *mut FieldStack {
+    if !dims_valid(width, height, depth) || num_layers == 0 {
+        return std::ptr::null_mut();
+    }
+    Box::into_raw(Box::new(create_field_stack(
+        width, height, depth, num_layers,
+    )))
+}
+
+/// Destroy a field stack. Safe to call with null pointer (no-op).
+///
+/// # Safety
+/// `stack` must be null or a pointer from `va_create_field_stack`, not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn va_destroy_field_stack(stack: *mut FieldStack) {
+    if !stack.is_null() {
+        drop(Box::from_raw(stack));
+    }
+}
+
+/// Set a layer's diffusion rate and conductivity.
+///
+/// # Safety
+/// `stack` must be null or a valid FieldStack pointer.
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer or layer out of range).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_stack_set_layer(
+    stack: *mut FieldStack,
+    layer: u8,
+    diffusion_rate: u8,
+    conductivity: u16,
+) -> i32 {
+    let Some(params) = stack_mut(stack).and_then(|s| s.params.get_mut(layer as usize)) else {
+        return 1;
+    };
+    params.diffusion_rate = diffusion_rate;
+    params.conductivity = conductivity;
+    0
+}
+
+/// Set a value in one layer. Out-of-bounds layers or coordinates are ignored.
+///
+/// # Safety
+/// `stack` must be null or a valid FieldStack pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_stack_set(
+    stack: *mut FieldStack,
+    layer: u8,
+    x: i16,
+    y: i16,
+    z: i16,
+    value: u32,
+) {
+    if let Some(stack) = stack_mut(stack) {
+        stack_set(stack, layer, x, y, z, value);
+    }
+}
+
+/// Get a value from one layer.
+///
+/// # Safety
+/// `stack` must be null or a valid FieldStack pointer.
+///
+/// # Returns
+/// The value, or 0 for a null pointer or out-of-bounds layer/coordinates.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_stack_get(
+    stack: *const FieldStack,
+    layer: u8,
+    x: i16,
+    y: i16,
+    z: i16,
+) -> u32 {
+    stack_ref(stack)
+        .and_then(|stack| stack_get(stack, layer, x, y, z))
+        .unwrap_or(0)
+}
+
+/// Step every layer forward one generation in one pass over the grid.
+///
+/// # Safety
+/// `stack` must be null or a valid FieldStack pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_stack_step(stack: *mut FieldStack) {
+    if let Some(stack) = stack_mut(stack) {
+        field_stack_step(stack);
+    }
+}
+
+/// Get the stack's generation (shared by all layers).
+///
+/// # Safety
+/// `stack` must be null or a valid FieldStack pointer.
+///
+/// # Returns
+/// The generation, or 0 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_stack_get_generation(stack: *const FieldStack) -> u64 {
+    stack_ref(stack).map_or(0, |stack| stack.generation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn test_field_stack_via_ffi() {
+        assert!(va_create_field_stack(4, 4, 4, 0).is_null());
+        assert!(va_create_field_stack(0, 4, 4, 3).is_null());
+
+        let stack = va_create_field_stack(8, 8, 8, 3);
+        assert!(!stack.is_null());
+        unsafe {
+            assert_eq!(va_field_stack_set_layer(stack, 2, 0, 30_000), 0);
+            assert_eq!(va_field_stack_set_layer(stack, 3, 0, 30_000), 1);
+
+            va_field_stack_set(stack, 0, 4, 4, 4, 700_000);
+            va_field_stack_set(stack, 2, 1, 1, 1, 90_000);
+            assert_eq!(va_field_stack_get(stack, 0, 4, 4, 4), 700_000);
+            assert_eq!(va_field_stack_get(stack, 1, 4, 4, 4), 1);
+            assert_eq!(va_field_stack_get(stack, 3, 4, 4, 4), 0);
+
+            let totals = |s: *const FieldStack| -> Vec<u64> {
+                (0..3)
+                    .map(|l| {
+                        (*s).cells
+                            .iter()
+                            .skip(l)
+                            .step_by(3)
+                            .map(|&v| v as u64)
+                            .sum()
+                    })
+                    .collect()
+            };
+            let before = totals(stack);
+            for _ in 0..5 {
+                va_field_stack_step(stack);
+            }
+            assert_eq!(totals(stack), before);
+            assert_eq!(va_field_stack_get_generation(stack), 5);
+            assert!(va_field_stack_get(stack, 0, 5, 4, 4) > 1);
+
+            assert_eq!(va_field_stack_get(ptr::null(), 0, 0, 0, 0), 0);
+            va_field_stack_step(ptr::null_mut());
+            va_destroy_field_stack(stack);
+            va_destroy_field_stack(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Built-in pattern stamps for seeding initial conditions.
+
+use super::validate::state_mut;
+use crate::automaton::stamp::stamp_pattern;
+use crate::state::State;
+
+/// Stamps a built-in pattern centered on (x, y, z).
+///
+/// Pattern ids: 0 = cube, 1 = sphere, 2 = hollow shell, 3 = blinker,
+/// 4 = glider, 5 = random blob. `rotation` selects one of the 24 axis-aligned
+/// orientations (0 = unrotated). Cells outside the grid are clipped.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// Number of cells set alive, or -1 on error (null pointer, unknown pattern,
+/// or rotation >= 24).
+#[no_mangle]
+pub unsafe extern "C" fn va_stamp(
+    ptr: *mut State,
+    pattern_id: u8,
+    x: i16,
+    y: i16,
+    z: i16,
+    rotation: u8,
+) -> i32 {
+    let Some(state) = state_mut(ptr) else {
+        return -1;
+    };
+
+    match stamp_pattern(state, pattern_id, x, y, z, rotation) {
+        Some(written) => written as i32,
+        None => -1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::stamp::{STAMP_CUBE, STAMP_GLIDER};
+    use crate::ffi::grid::{va_create_grid, va_get_cell};
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use std::ptr;
+
+    #[test]
+    fn test_stamp_via_ffi() {
+        unsafe {
+            let state = va_create();
+            va_create_grid(state, 16, 16, 16);
+
+            assert_eq!(va_stamp(state, STAMP_CUBE, 8, 8, 8, 0), 27);
+            assert_eq!(va_get_cell(state, 7, 9, 8), 1);
+            assert_eq!(va_stamp(state, STAMP_GLIDER, 4, 4, 4, 23), 6);
+
+            assert_eq!(va_stamp(state, 200, 8, 8, 8, 0), -1);
+            assert_eq!(va_stamp(state, STAMP_CUBE, 8, 8, 8, 24), -1);
+
+            va_destroy(state);
+        }
+    }
+
+    #[test]
+    fn test_null_pointer_handling() {
+        unsafe {
+            assert_eq!(va_stamp(ptr::null_mut(), STAMP_CUBE, 0, 0, 0, 0), -1);
+        }
+    }
+}
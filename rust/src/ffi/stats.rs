@@ -0,0 +1,296 @@
+//! FFI interface for field value distributions (see `automaton::stats`).
+
+use super::validate::{buf_mut, buf_ref, field_mut, field_ref, region_volume, write_opt};
+use crate::automaton::field::Field;
+use crate::automaton::stats::{
+    field_classify, field_extract_zones, field_histogram, field_percentile, field_set_zones,
+    field_zone,
+};
+
+/// Counts the field's cells into `bucket_count` equal-width buckets spanning
+/// the field's current `[min, max]` (the last bucket includes max). Bucket `i`
+/// starts at `min + i * (max - min + 1) / bucket_count`.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `out_buckets` must point to at least `bucket_count` writable u64 values
+/// - `out_min`, `out_max` must be null or valid for a write
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer or zero `bucket_count`).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_histogram(
+    field: *const Field,
+    bucket_count: u32,
+    out_buckets: *mut u64,
+    out_min: *mut u32,
+    out_max: *mut u32,
+) -> i32 {
+    let (Some(field), Some(buckets)) =
+        (field_ref(field), buf_mut(out_buckets, bucket_count as u64))
+    else {
+        return 1;
+    };
+    if buckets.is_empty() {
+        return 1;
+    }
+    let stats = field_histogram(field, buckets);
+    write_opt(out_min, stats.min);
+    write_opt(out_max, stats.max);
+    0
+}
+
+/// Returns the nearest-rank percentile of the field's cells: the smallest
+/// value with at least `percent` percent of the cells at or below it
+/// (0 = minimum, 50 = median, 100 = maximum).
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+///
+/// # Returns
+/// The value, or 0 for a null pointer or a percent outside [0, 100]
+/// (cells are never 0).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_percentile(field: *const Field, percent: f64) -> u32 {
+    field_ref(field)
+        .and_then(|field| field_percentile(field, percent))
+        .unwrap_or(0)
+}
+
+/// Extracts the whole field as one class byte per cell (z,y,x order): the
+/// number of `thresholds` at or below the cell's value. With thresholds
+/// `{300, 600}`, cells below 300 are 0 (cold), below 600 are 1 (warm), the rest
+/// 2 (hot), ready to index a node table in the visualizer.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `out_buf` must point to at least `buf_len` writable bytes, or be null
+///   (size query)
+/// - `thresholds` must point to `count` readable u32 values (may be null when
+///   `count` is 0)
+///
+/// # Returns
+/// Number of bytes written (with a null `out_buf`, the number required), or 0
+/// on error (null field, thresholds not strictly ascending or more than 255,
+/// or `buf_len` smaller than the cell count).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_extract_thresholded(
+    field: *const Field,
+    out_buf: *mut u8,
+    buf_len: u64,
+    thresholds: *const u32,
+    count: u32,
+) -> u64 {
+    let Some(field) = field_ref(field) else {
+        return 0;
+    };
+    if out_buf.is_null() {
+        return field.cells.len() as u64;
+    }
+    let thresholds = if count == 0 {
+        &[][..]
+    } else {
+        match buf_ref(thresholds, count as u64) {
+            Some(thresholds) => thresholds,
+            None => return 0,
+        }
+    };
+    match buf_mut(out_buf, buf_len) {
+        Some(out) => field_classify(field, thresholds, out),
+        None => 0,
+    }
+}
+
+/// Configures the field's zone breakpoints (e.g. `{280000, 310000}` for cold,
+/// comfortable, hot): a cell's zone is the number of breakpoints at or below
+/// its value. A `count` of 0 removes the zones.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `breakpoints` must point to `count` readable u32 values (may be null when
+///   `count` is 0)
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer, breakpoints not strictly
+/// ascending, or more than 255). The zones are unchanged on failure.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_set_zones(
+    field: *mut Field,
+    breakpoints: *const u32,
+    count: u32,
+) -> i32 {
+    let Some(field) = field_mut(field) else {
+        return 1;
+    };
+    let breakpoints = if count == 0 {
+        &[][..]
+    } else {
+        match buf_ref(breakpoints, count as u64) {
+            Some(breakpoints) => breakpoints,
+            None => return 1,
+        }
+    };
+    if field_set_zones(field, breakpoints) {
+        0
+    } else {
+        1
+    }
+}
+
+/// Returns the zone of one cell.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+///
+/// # Returns
+/// The zone, or -1 (null pointer, out of bounds, or no zones configured).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_get_zone(field: *const Field, x: i16, y: i16, z: i16) -> i32 {
+    field_ref(field)
+        .and_then(|field| field_zone(field, x, y, z))
+        .map_or(-1, i32::from)
+}
+
+/// Extracts the zone of every cell of a rectangular region, one byte per
+/// cell, in the layout of `va_field_extract_region` (z,y,x order, clamped to
+/// the field).
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `out_buf` must point to at least `buf_len` writable bytes, or be null
+///
+/// # Returns
+/// Number of bytes written, or 0 on error (null pointer, no zones configured,
+/// inverted region, or `buf_len` smaller than the clamped region).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_extract_zones(
+    field: *const Field,
+    out_buf: *mut u8,
+    buf_len: u64,
+    min_x: i16,
+    min_y: i16,
+    min_z: i16,
+    max_x: i16,
+    max_y: i16,
+    max_z: i16,
+) -> u64 {
+    let Some(field) = field_ref(field) else {
+        return 0;
+    };
+    let (min, max) = ([min_x, min_y, min_z], [max_x, max_y, max_z]);
+    if region_volume(min, max).is_none() {
+        return 0;
+    }
+    match buf_mut(out_buf, buf_len) {
+        Some(out) => field_extract_zones(field, out, min, max),
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{va_create_field, va_destroy_field, va_field_set};
+    use std::ptr;
+
+    #[test]
+    fn test_histogram_and_percentile_via_ffi() {
+        let field = va_create_field(8, 8, 8, 1);
+        va_field_set(field, 0, 0, 0, 1001);
+        unsafe {
+            let mut buckets = [0u64; 10];
+            let (mut min, mut max) = (0u32, 0u32);
+            assert_eq!(
+                va_field_histogram(field, 10, buckets.as_mut_ptr(), &mut min, &mut max),
+                0
+            );
+            assert_eq!((min, max), (1, 1001));
+            assert_eq!(buckets[0], 511);
+            assert_eq!(buckets[9], 1);
+            assert_eq!(buckets.iter().sum::<u64>(), 512);
+
+            let null = ptr::null_mut();
+            assert_eq!(
+                va_field_histogram(field, 0, buckets.as_mut_ptr(), null, null),
+                1
+            );
+            assert_eq!(
+                va_field_histogram(field, 4, ptr::null_mut(), &mut min, &mut max),
+                1
+            );
+
+            assert_eq!(va_field_percentile(field, 50.0), 1);
+            assert_eq!(va_field_percentile(field, 100.0), 1001);
+            assert_eq!(va_field_percentile(field, -1.0), 0);
+            assert_eq!(va_field_percentile(ptr::null(), 50.0), 0);
+        }
+        va_destroy_field(field);
+    }
+
+    #[test]
+    fn test_extract_thresholded_via_ffi() {
+        let field = va_create_field(4, 2, 2, 1);
+        va_field_set(field, 3, 1, 1, 700);
+        va_field_set(field, 0, 0, 0, 400);
+        unsafe {
+            let thresholds = [300u32, 600];
+            let len = va_field_extract_thresholded(field, ptr::null_mut(), 0, ptr::null(), 0);
+            assert_eq!(len, 16);
+            let mut out = vec![0u8; 16];
+            assert_eq!(
+                va_field_extract_thresholded(field, out.as_mut_ptr(), 16, thresholds.as_ptr(), 2),
+                16
+            );
+            assert_eq!((out[0], out[1], out[15]), (1, 0, 2));
+            assert_eq!(
+                va_field_extract_thresholded(field, out.as_mut_ptr(), 15, thresholds.as_ptr(), 2),
+                0
+            );
+            let descending = [600u32, 300];
+            assert_eq!(
+                va_field_extract_thresholded(field, out.as_mut_ptr(), 16, descending.as_ptr(), 2),
+                0
+            );
+            assert_eq!(
+                va_field_extract_thresholded(field, out.as_mut_ptr(), 16, ptr::null(), 2),
+                0
+            );
+        }
+        va_destroy_field(field);
+    }
+
+    #[test]
+    fn test_zones_via_ffi() {
+        let field = va_create_field(4, 2, 2, 1);
+        va_field_set(field, 3, 1, 1, 700);
+        va_field_set(field, 0, 0, 0, 400);
+        unsafe {
+            assert_eq!(va_field_get_zone(field, 0, 0, 0), -1);
+            let breakpoints = [300u32, 600];
+            assert_eq!(va_field_set_zones(field, breakpoints.as_ptr(), 2), 0);
+            assert_eq!(va_field_get_zone(field, 0, 0, 0), 1);
+            assert_eq!(va_field_get_zone(field, 1, 0, 0), 0);
+            assert_eq!(va_field_get_zone(field, 3, 1, 1), 2);
+            assert_eq!(va_field_get_zone(field, 4, 1, 1), -1);
+
+            let mut out = [9u8; 4];
+            assert_eq!(
+                va_field_extract_zones(field, out.as_mut_ptr(), 4, 0, 1, 1, 4, 2, 2),
+                4
+            );
+            assert_eq!(out, [0, 0, 0, 2]);
+            assert_eq!(
+                va_field_extract_zones(field, out.as_mut_ptr(), 4, 2, 0, 0, 1, 1, 1),
+                0
+            );
+
+            let descending = [600u32, 300];
+            assert_eq!(va_field_set_zones(field, descending.as_ptr(), 2), 1);
+            assert_eq!(va_field_get_zone(field, 3, 1, 1), 2);
+            assert_eq!(va_field_set_zones(field, ptr::null(), 0), 0);
+            assert_eq!(va_field_get_zone(field, 3, 1, 1), -1);
+            assert_eq!(va_field_set_zones(ptr::null_mut(), ptr::null(), 0), 1);
+        }
+        va_destroy_field(field);
+    }
+}
@@ -0,0 +1,184 @@
+//! FFI interface for single-cell tracing.
+
+use super::validate::{buf_mut, field_mut, field_ref, state_mut, write_text};
+use crate::automaton::field::Field;
+use crate::automaton::trace::{field_explain, field_trace_cell, trace_cell, CellTrace};
+use crate::state::State;
+
+/// i64 slots per trace record in the output array.
+pub const TRACE_RECORD_LEN: usize = 10;
+
+fn write_records(records: &[CellTrace], out: &mut [i64]) {
+    for (record, slot) in records.iter().zip(out.chunks_exact_mut(TRACE_RECORD_LEN)) {
+        slot[0] = record.generation as i64;
+        slot[1] = record.value as i64;
+        slot[2] = record.next_value as i64;
+        slot[3] = record.neighbors as i64;
+        slot[4..].copy_from_slice(&record.flows);
+    }
+}
+
+/// Steps the grid `n_steps` generations, recording cell `(x, y, z)` each time.
+///
+/// out layout per generation: [generation, value, next_value, neighbors,
+/// flow_-x, flow_+x, flow_-y, flow_+y, flow_-z, flow_+z] (10 x i64).
+/// Flows are always 0 for the CA grid.
+///
+/// # Safety
+/// `ptr` must be null or a valid State pointer. `out` must be null or point to
+/// at least `n_steps * 10` writable i64 values.
+///
+/// # Returns
+/// Number of generations recorded (`n_steps`), or 0 if a pointer is null or the
+/// cell is outside the grid (in which case nothing is stepped).
+#[no_mangle]
+pub unsafe extern "C" fn va_trace_cell(
+    ptr: *mut State,
+    x: i16,
+    y: i16,
+    z: i16,
+    n_steps: u32,
+    out: *mut i64,
+) -> u32 {
+    let (Some(state), Some(out)) = (
+        state_mut(ptr),
+        buf_mut(out, n_steps as u64 * TRACE_RECORD_LEN as u64),
+    ) else {
+        return 0;
+    };
+    let mut records = vec![CellTrace::default(); n_steps as usize];
+    if !trace_cell(state, x, y, z, &mut records) {
+        return 0;
+    }
+    write_records(&records, out);
+    n_steps
+}
+
+/// Steps the field `n_steps` generations (as `va_field_step`), recording cell
+/// `(x, y, z)` and the net inflow across each of its faces.
+///
+/// out layout is the same as `va_trace_cell`; neighbors is always 0, and the
+/// six flows sum to `next_value - value` (minus the source/sink rate, if the
+/// cell has one).
+///
+/// # Safety
+/// `field` must be null or a valid Field pointer. `out` must be null or point
+/// to at least `n_steps * 10` writable i64 values.
+///
+/// # Returns
+/// Number of generations recorded, or 0 if a pointer is null or the cell is
+/// outside the field (in which case nothing is stepped).
+#[no_mangle]
+pub unsafe extern "C" fn va_field_trace_cell(
+    field: *mut Field,
+    x: i16,
+    y: i16,
+    z: i16,
+    n_steps: u32,
+    out: *mut i64,
+) -> u32 {
+    let (Some(field), Some(out)) = (
+        field_mut(field),
+        buf_mut(out, n_steps as u64 * TRACE_RECORD_LEN as u64),
+    ) else {
+        return 0;
+    };
+    let mut records = vec![CellTrace::default(); n_steps as usize];
+    if !field_trace_cell(field, x, y, z, &mut records) {
+        return 0;
+    }
+    write_records(&records, out);
+    n_steps
+}
+
+/// Writes a human-readable breakdown of cell `(x, y, z)` (UTF-8, not
+/// NUL-terminated): its value and conductivity, the inflow across each face in
+/// the last step, its source or sink, the boundary faces it touches, and its
+/// phase. Face flows need `va_field_set_flow_recording` on before the step;
+/// otherwise the text says so. Nothing is stepped.
+///
+/// # Safety
+/// - `field` must be a valid pointer to a Field, or null
+/// - `out_text` must point to at least `capacity` writable bytes, or be null
+///
+/// # Returns
+/// Number of bytes written, or 0 on error (null pointer, out-of-bounds cell,
+/// or `capacity` too small). Pass a null `out_text` to query the size.
+#[no_mangle]
+pub unsafe extern "C" fn va_field_explain(
+    field: *const Field,
+    x: i16,
+    y: i16,
+    z: i16,
+    out_text: *mut u8,
+    capacity: u64,
+) -> u64 {
+    match field_ref(field).and_then(|field| field_explain(field, x, y, z)) {
+        Some(text) => write_text(&text, out_text, capacity),
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::field::{create_field_1, field_index_of};
+    use std::ptr;
+
+    #[test]
+    fn test_field_trace_layout() {
+        let mut field = create_field_1(4, 4, 4, 0);
+        let idx = field_index_of(&field, 1, 1, 1);
+        field.cells[idx] = 70_001;
+
+        let mut out = [0i64; 2 * TRACE_RECORD_LEN];
+        unsafe {
+            assert_eq!(
+                va_field_trace_cell(&mut field, 1, 1, 1, 2, out.as_mut_ptr()),
+                2
+            );
+            assert_eq!(
+                va_field_trace_cell(&mut field, 1, 1, 1, 2, ptr::null_mut()),
+                0
+            );
+            assert_eq!(
+                va_trace_cell(ptr::null_mut(), 0, 0, 0, 2, out.as_mut_ptr()),
+                0
+            );
+        }
+        assert_eq!(field.generation, 2);
+        let (first, second) = out.split_at(TRACE_RECORD_LEN);
+        assert_eq!(first[..2], [0, 70_001]);
+        assert_eq!(second[0], 1);
+        assert_eq!(second[1], first[2]);
+        // Mass leaves through all six faces
+        assert!(first[4..].iter().all(|&f| f < 0));
+        assert_eq!(first[1] + first[4..].iter().sum::<i64>(), first[2]);
+    }
+
+    #[test]
+    fn test_field_explain_via_ffi() {
+        let mut field = create_field_1(4, 4, 4, 0);
+        unsafe {
+            let len = va_field_explain(&field, 1, 2, 3, ptr::null_mut(), 0);
+            let mut text = vec![0u8; len as usize];
+            assert_eq!(
+                va_field_explain(&field, 1, 2, 3, text.as_mut_ptr(), len),
+                len
+            );
+            let text = String::from_utf8(text).unwrap();
+            assert!(text.starts_with("cell (1, 2, 3) of a 4x4x4 field"));
+            assert!(text.contains("boundary: +z reflective"));
+
+            let mut short = [0u8; 8];
+            assert_eq!(va_field_explain(&field, 1, 2, 3, short.as_mut_ptr(), 8), 0);
+            assert_eq!(va_field_explain(&field, 4, 0, 0, ptr::null_mut(), 0), 0);
+            assert_eq!(
+                va_field_explain(ptr::null(), 0, 0, 0, ptr::null_mut(), 0),
+                0
+            );
+            field.cells[0] = 9;
+            assert_eq!(va_field_explain(&field, 1, 2, 3, ptr::null_mut(), 0), len);
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! FFI interface for multi-state transition tables (see
+//! `automaton::transition`).
+
+use super::validate::{buf_ref, state_mut};
+use crate::automaton::transition::{set_cell_state, TransitionTable};
+use crate::state::State;
+
+/// Sets a transition table of `len` bytes (27 entries per state: entry
+/// `s * 27 + k` is the next state of a cell in state `s` with `k` neighbors in
+/// state 1). Steps use it instead of the birth/survival rule until it is
+/// cleared by passing a null `table` or `len` 0.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `table` must point to at least `len` readable bytes, or be null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer, `len` not a multiple of 27 or
+/// above 256 states, or an entry naming a state outside the table). On failure
+/// the current table is kept.
+#[no_mangle]
+pub unsafe extern "C" fn va_set_transition_table(
+    ptr: *mut State,
+    table: *const u8,
+    len: u64,
+) -> i32 {
+    let Some(state) = state_mut(ptr) else {
+        return 1;
+    };
+    if table.is_null() || len == 0 {
+        state.transitions = None;
+        return 0;
+    }
+    match buf_ref(table, len).and_then(TransitionTable::new) {
+        Some(table) => {
+            state.transitions = Some(table);
+            0
+        }
+        None => 1,
+    }
+}
+
+/// Sets a cell to any state of the transition table, or of WireWorld in that
+/// step mode (`va_set_cell` only writes 0 or 1).
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer, out of bounds, neither a
+/// transition table nor WireWorld mode, or `value` not one of its states).
+#[no_mangle]
+pub unsafe extern "C" fn va_set_cell_state(
+    ptr: *mut State,
+    x: i16,
+    y: i16,
+    z: i16,
+    value: u8,
+) -> i32 {
+    let Some(state) = state_mut(ptr) else {
+        return 1;
+    };
+    if set_cell_state(state, x, y, z, value) {
+        0
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::transition::NEIGHBOR_COUNTS;
+    use crate::ffi::grid::{va_create_grid, va_get_cell, va_step};
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use std::ptr;
+
+    #[test]
+    fn test_transition_table_via_ffi() {
+        unsafe {
+            let state = va_create();
+            va_create_grid(state, 4, 4, 4);
+            // Every firing cell decays through states 2 and 3 back to dead
+            let mut table = [0u8; 4 * NEIGHBOR_COUNTS];
+            table[NEIGHBOR_COUNTS..2 * NEIGHBOR_COUNTS].fill(2);
+            table[2 * NEIGHBOR_COUNTS..3 * NEIGHBOR_COUNTS].fill(3);
+            assert_eq!(va_set_cell_state(state, 1, 1, 1, 1), 1);
+            assert_eq!(
+                va_set_transition_table(state, table.as_ptr(), table.len() as u64),
+                0
+            );
+            assert_eq!(va_set_cell_state(state, 1, 1, 1, 1), 0);
+            assert_eq!(va_set_cell_state(state, 1, 1, 1, 4), 1);
+            for expected in [2, 3, 0] {
+                va_step(state);
+                assert_eq!(va_get_cell(state, 1, 1, 1), expected);
+            }
+
+            assert_eq!(va_set_transition_table(state, table.as_ptr(), 30), 1);
+            assert_eq!(va_set_cell_state(state, 1, 1, 1, 3), 0);
+            assert_eq!(va_set_transition_table(state, ptr::null(), 0), 0);
+            assert!((*state).transitions.is_none());
+            assert_eq!(
+                va_set_transition_table(ptr::null_mut(), table.as_ptr(), 27),
+                1
+            );
+            assert_eq!(va_set_cell_state(ptr::null_mut(), 1, 1, 1, 1), 1);
+            va_destroy(state);
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! FFI interface for transactions across several handles.
+//!
+//! Lua often updates a grid, a field, and a controller together (a fire that
+//! kills cells, heats the air, and adds a source). A transaction makes such an
+//! update all-or-nothing with respect to stepping: every enlisted handle is
+//! saved when it joins, mutations then go through the ordinary `va_*` calls,
+//! and `va_txn_commit` keeps them only if no participant was stepped in the
+//! meantime. Otherwise, or on `va_txn_abort`, every participant is restored to
+//! its saved copy, so the next step sees either all of the mutations or none.
+//!
+//! The saved copy is the whole handle: cells, rule, and settings of a grid or
+//! field, the field of a controller (its pipeline, events, and contracts are
+//! not rolled back). Enlisted handles must stay alive until the transaction
+//! ends.
+
+use super::validate::{ctrl_mut, field_mut, state_mut};
+use crate::automaton::field::Field;
+use crate::automaton::incremental::StepController;
+use crate::state::State;
+
+/// A handle enlisted in a transaction and its copy from enlistment.
+enum Participant {
+    State(*mut State, State),
+    Field(*mut Field, Field),
+    Controller(*mut StepController, Field),
+}
+
+impl Participant {
+    fn address(&self) -> *const () {
+        match self {
+            Participant::State(ptr, _) => *ptr as *const (),
+            Participant::Field(ptr, _) => *ptr as *const (),
+            Participant::Controller(ptr, _) => *ptr as *const (),
+        }
+    }
+
+    /// True if the handle was stepped (or began a step) since it was saved.
+    ///
+    /// # Safety
+    /// The handle must still be alive.
+    unsafe fn stepped(&self) -> bool {
+        match self {
+            Participant::State(ptr, saved) => (**ptr).generation != saved.generation,
+            Participant::Field(ptr, saved) => (**ptr).generation != saved.generation,
+            Participant::Controller(ptr, saved) => {
+                let ctrl = &**ptr;
+                ctrl.is_stepping() || ctrl.field.generation != saved.generation
+            }
+        }
+    }
+
+    /// Put the saved copy back.
+    ///
+    /// # Safety
+    /// The handle must still be alive.
+    unsafe fn restore(self) {
+        match self {
+            Participant::State(ptr, saved) => *ptr = saved,
+            Participant::Field(ptr, saved) => *ptr = saved,
+            Participant::Controller(ptr, saved) => {
+                (*ptr).field = saved;
+                (*ptr).invalidate_activity();
+            }
+        }
+    }
+}
+
+/// Open transaction: the enlisted handles, in enlistment order.
+#[derive(Default)]
+pub struct Transaction {
+    participants: Vec<Participant>,
+}
+
+impl Transaction {
+    /// Enlist a handle unless it already is.
+    fn enlist(&mut self, participant: Participant) -> i32 {
+        let address = participant.address();
+        if self.participants.iter().any(|p| p.address() == address) {
+            return 1;
+        }
+        self.participants.push(participant);
+        0
+    }
+
+    /// Restore every participant, last enlisted first.
+    unsafe fn roll_back(self) {
+        for participant in self.participants.into_iter().rev() {
+            participant.restore();
+        }
+    }
+}
+
+/// Begins a transaction. Enlist handles with `va_txn_add_*` before mutating
+/// them; end it with exactly one of `va_txn_commit` or `va_txn_abort`.
+#[no_mangle]
+pub extern "C" fn va_txn_begin() -> *mut Transaction {
+    Box::into_raw(Box::default())
+}
+
+/// Enlists a grid, saving its current contents.
+///
+/// # Safety
+/// - `txn` must be null or a live pointer from `va_txn_begin`
+/// - `ptr` must be null or a valid State pointer that outlives the transaction
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer or already enlisted).
+#[no_mangle]
+pub unsafe extern "C" fn va_txn_add_state(txn: *mut Transaction, ptr: *mut State) -> i32 {
+    let (Some(txn), Some(state)) = (txn.as_mut(), state_mut(ptr)) else {
+        return 1;
+    };
+    txn.enlist(Participant::State(ptr, state.clone()))
+}
+
+/// Enlists a field, saving its current contents.
+///
+/// # Safety
+/// - `txn` must be null or a live pointer from `va_txn_begin`
+/// - `field` must be null or a valid Field pointer that outlives the transaction
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer or already enlisted).
+#[no_mangle]
+pub unsafe extern "C" fn va_txn_add_field(txn: *mut Transaction, field: *mut Field) -> i32 {
+    let (Some(txn), Some(saved)) = (txn.as_mut(), field_mut(field)) else {
+        return 1;
+    };
+    txn.enlist(Participant::Field(field, saved.clone()))
+}
+
+/// Enlists a StepController, saving its field.
+///
+/// # Safety
+/// - `txn` must be null or a live pointer from `va_txn_begin`
+/// - `ctrl` must be null or a valid StepController pointer that outlives the
+///   transaction
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer, already enlisted, or a step in
+/// progress).
+#[no_mangle]
+pub unsafe extern "C" fn va_txn_add_controller(
+    txn: *mut Transaction,
+    ctrl: *mut StepController,
+) -> i32 {
+    let (Some(txn), Some(controller)) = (txn.as_mut(), ctrl_mut(ctrl)) else {
+        return 1;
+    };
+    if controller.is_stepping() {
+        return 1;
+    }
+    txn.enlist(Participant::Controller(ctrl, controller.field.clone()))
+}
+
+/// Ends the transaction, keeping every mutation if no participant was stepped
+/// since it was enlisted. If one was, every participant is restored instead
+/// (as `va_txn_abort`). The transaction is freed either way.
+///
+/// # Safety
+/// `txn` must be null or a live pointer from `va_txn_begin`, not used
+/// afterwards. Every enlisted handle must still be alive.
+///
+/// # Returns
+/// 0 if committed, 1 if rolled back, -1 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_txn_commit(txn: *mut Transaction) -> i32 {
+    if txn.is_null() {
+        return -1;
+    }
+    let txn = Box::from_raw(txn);
+    if txn.participants.iter().any(|p| p.stepped()) {
+        txn.roll_back();
+        return 1;
+    }
+    0
+}
+
+/// Ends the transaction, restoring every participant to its copy from
+/// enlistment. Safe to call with null pointer (no-op). A controller step begun
+/// during the transaction is not undone: it finishes from the cells it started
+/// with.
+///
+/// # Safety
+/// `txn` must be null or a live pointer from `va_txn_begin`, not used
+/// afterwards. Every enlisted handle must still be alive.
+#[no_mangle]
+pub unsafe extern "C" fn va_txn_abort(txn: *mut Transaction) {
+    if !txn.is_null() {
+        Box::from_raw(txn).roll_back();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::field::{
+        va_create_field, va_destroy_field, va_field_add_source, va_field_get, va_field_set,
+        va_field_step,
+    };
+    use crate::ffi::grid::{va_create_grid, va_get_cell, va_set_cell};
+    use crate::ffi::incremental::{
+        va_create_step_controller, va_destroy_step_controller, va_sc_field_get, va_sc_field_set,
+    };
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use std::ptr;
+
+    #[test]
+    fn test_commit_and_abort() {
+        unsafe {
+            let state = va_create();
+            va_create_grid(state, 4, 4, 4);
+            let field = va_create_field(4, 4, 4, 1);
+            let ctrl = va_create_step_controller(4, 4, 4, 1, 1, 0);
+
+            let txn = va_txn_begin();
+            assert_eq!(va_txn_add_state(txn, state), 0);
+            assert_eq!(va_txn_add_field(txn, field), 0);
+            assert_eq!(va_txn_add_controller(txn, ctrl), 0);
+            assert_eq!(va_txn_add_field(txn, field), 1);
+            assert_eq!(va_txn_add_state(txn, ptr::null_mut()), 1);
+            va_set_cell(state, 1, 1, 1, 1);
+            va_field_set(field, 1, 1, 1, 5000);
+            va_sc_field_set(ctrl, 1, 1, 1, 7000);
+            assert_eq!(va_txn_commit(txn), 0);
+            assert_eq!(va_get_cell(state, 1, 1, 1), 1);
+            assert_eq!(va_field_get(field, 1, 1, 1), 5000);
+            assert_eq!(va_sc_field_get(ctrl, 1, 1, 1), 7000);
+
+            let txn = va_txn_begin();
+            va_txn_add_state(txn, state);
+            va_txn_add_field(txn, field);
+            va_txn_add_controller(txn, ctrl);
+            va_set_cell(state, 2, 2, 2, 1);
+            va_field_add_source(field, 0, 0, 0, 100);
+            va_sc_field_set(ctrl, 1, 1, 1, 1);
+            va_txn_abort(txn);
+            assert_eq!(va_get_cell(state, 2, 2, 2), 0);
+            assert!((*field).sources.is_empty());
+            assert_eq!(va_sc_field_get(ctrl, 1, 1, 1), 7000);
+
+            // A participant stepped mid-transaction: nothing applies
+            let txn = va_txn_begin();
+            va_txn_add_state(txn, state);
+            va_txn_add_field(txn, field);
+            va_set_cell(state, 2, 2, 2, 1);
+            va_field_step(field);
+            assert_eq!(va_txn_commit(txn), 1);
+            assert_eq!(va_get_cell(state, 2, 2, 2), 0);
+            assert_eq!(va_field_get(field, 1, 1, 1), 5000);
+            assert_eq!((*field).generation, 0);
+
+            assert_eq!(va_txn_commit(ptr::null_mut()), -1);
+            va_txn_abort(ptr::null_mut());
+            va_destroy(state);
+            va_destroy_field(field);
+            va_destroy_step_controller(ctrl);
+        }
+    }
+}
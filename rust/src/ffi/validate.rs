@@ -0,0 +1,527 @@
+//! Centralized FFI argument validation.
+//!
+//! Every C entry point funnels its raw arguments through these helpers before
+//! touching memory: handle pointers become `Option<&T>`, caller buffers become
+//! slices only when non-null and of a sane length, and dimensions/regions are
+//! checked for sign and ordering. A malformed Lua call then fails with the
+//! function's documented error value instead of reaching `from_raw_parts` with
+//! a bogus length. Null handles, bad dimensions and inverted regions are also
+//! recorded as the thread's last error (see `error`); buffers are not, since a
+//! null buffer is often a legitimate size query.
+//!
+//! State, Field and StepController handles carry a `HandleTag`: their helpers
+//! also reject a destroyed handle or a handle of another type, which would
+//! otherwise be read as the wrong type. `field_ref`/`field_mut` further reject
+//! a chunked Field (`VA_ERR_INVALID_STATE`) for calls that need dense cells.
+
+use super::error::{
+    set_last_error, VA_ERR_INVALID_ARGUMENT, VA_ERR_INVALID_HANDLE, VA_ERR_INVALID_STATE,
+    VA_ERR_NULL_HANDLE,
+};
+use crate::automaton::adaptive::AdaptiveField;
+use crate::automaton::coupled::CoupledFields;
+use crate::automaton::field::Field;
+use crate::automaton::field64::Field64;
+use crate::automaton::ifield::IField;
+use crate::automaton::incremental::StepController;
+use crate::automaton::infinite::InfiniteState;
+use crate::automaton::locked::SharedState;
+use crate::automaton::stack::FieldStack;
+use crate::state::{HandleTag, State, Tagged};
+use std::mem::MaybeUninit;
+
+/// Pass a borrowed handle through, recording a null `kind` handle as the last
+/// error.
+#[inline]
+fn noted<T>(handle: Option<T>, kind: &str) -> Option<T> {
+    if handle.is_none() {
+        set_last_error(VA_ERR_NULL_HANDLE, format!("null {kind} handle"));
+    }
+    handle
+}
+
+/// Whether `ptr` is a live handle of type `T`, recording a null, destroyed or
+/// mismatched one as the last error.
+///
+/// # Safety
+/// `ptr` must be null or point to at least a readable `HandleTag`.
+unsafe fn live<T: Tagged>(ptr: *const T) -> bool {
+    if ptr.is_null() {
+        set_last_error(VA_ERR_NULL_HANDLE, format!("null {:?} handle", T::KIND));
+        return false;
+    }
+    let tag = ptr.cast::<HandleTag>().read();
+    if tag == HandleTag::new(T::KIND) {
+        return true;
+    }
+    let message = match tag.kind() {
+        Some(other) => format!("{other:?} handle passed as a {:?} handle", T::KIND),
+        None => format!("invalid or destroyed {:?} handle", T::KIND),
+    };
+    set_last_error(VA_ERR_INVALID_HANDLE, message);
+    false
+}
+
+/// Move a live tagged handle out of its allocation and free it (to destroy
+/// it, or to hand it to a new owner), leaving the freed memory tagged as
+/// destroyed so later calls with the stale pointer fail the tag check. None
+/// for a null, destroyed or mismatched handle, which is left alone.
+///
+/// # Safety
+/// `ptr` must be null or a pointer from the handle's constructor, live or
+/// destroyed; a live handle must not be used afterwards.
+pub(crate) unsafe fn take_handle<T: Tagged>(ptr: *mut T) -> Option<T> {
+    if !live(ptr) {
+        return None;
+    }
+    let value = ptr.read();
+    ptr.cast::<HandleTag>().write(HandleTag::DEAD);
+    drop(Box::from_raw(ptr.cast::<MaybeUninit<T>>()));
+    Some(value)
+}
+
+/// Borrow a State handle, or None if null, destroyed, or not a State.
+///
+/// # Safety
+/// `ptr` must be null or a pointer returned by `va_create`, live or destroyed.
+#[inline]
+pub(crate) unsafe fn state_ref<'a>(ptr: *const State) -> Option<&'a State> {
+    if live(ptr) {
+        ptr.as_ref()
+    } else {
+        None
+    }
+}
+
+/// Mutably borrow a State handle, or None if null, destroyed, or not a State.
+///
+/// # Safety
+/// `ptr` must be null or a pointer returned by `va_create`, live or destroyed,
+/// not aliased.
+#[inline]
+pub(crate) unsafe fn state_mut<'a>(ptr: *mut State) -> Option<&'a mut State> {
+    if live(ptr) {
+        ptr.as_mut()
+    } else {
+        None
+    }
+}
+
+/// Borrow a dense Field handle, or None if null, destroyed, not a Field, or
+/// chunked (the calls that take chunked fields use `any_field_ref`).
+///
+/// # Safety
+/// `ptr` must be null or a pointer returned by `va_create_field`, live or
+/// destroyed.
+#[inline]
+pub(crate) unsafe fn field_ref<'a>(ptr: *const Field) -> Option<&'a Field> {
+    any_field_ref(ptr).filter(|field| dense(field))
+}
+
+/// Mutably borrow a dense Field handle, or None if null, destroyed, not a
+/// Field, or chunked.
+///
+/// # Safety
+/// `ptr` must be null or a pointer returned by `va_create_field`, live or
+/// destroyed, not aliased.
+#[inline]
+pub(crate) unsafe fn field_mut<'a>(ptr: *mut Field) -> Option<&'a mut Field> {
+    any_field_mut(ptr).filter(|field| dense(field))
+}
+
+/// Borrow a Field handle, dense or chunked, or None if null, destroyed, or
+/// not a Field.
+///
+/// # Safety
+/// `ptr` must be null or a pointer returned by `va_create_field` or
+/// `va_create_field_chunked`, live or destroyed.
+#[inline]
+pub(crate) unsafe fn any_field_ref<'a>(ptr: *const Field) -> Option<&'a Field> {
+    if live(ptr) {
+        ptr.as_ref()
+    } else {
+        None
+    }
+}
+
+/// Mutably borrow a Field handle, dense or chunked, or None if null,
+/// destroyed, or not a Field.
+///
+/// # Safety
+/// `ptr` must be null or a pointer returned by `va_create_field` or
+/// `va_create_field_chunked`, live or destroyed, not aliased.
+#[inline]
+pub(crate) unsafe fn any_field_mut<'a>(ptr: *mut Field) -> Option<&'a mut Field> {
+    if live(ptr) {
+        ptr.as_mut()
+    } else {
+        None
+    }
+}
+
+/// Whether `field` keeps dense cells, recording a chunked one as the last
+/// error.
+fn dense(field: &Field) -> bool {
+    if field.chunks.is_some() {
+        set_last_error(
+            VA_ERR_INVALID_STATE,
+            "call needs a dense field, not a chunked one",
+        );
+        return false;
+    }
+    true
+}
+
+/// Borrow a Field64 handle, or None if null.
+///
+/// # Safety
+/// `ptr` must be null or a live pointer returned by `va_create_field64`.
+#[inline]
+pub(crate) unsafe fn field64_ref<'a>(ptr: *const Field64) -> Option<&'a Field64> {
+    noted(ptr.as_ref(), "Field64")
+}
+
+/// Mutably borrow a Field64 handle, or None if null.
+///
+/// # Safety
+/// `ptr` must be null or a live pointer returned by `va_create_field64`, not aliased.
+#[inline]
+pub(crate) unsafe fn field64_mut<'a>(ptr: *mut Field64) -> Option<&'a mut Field64> {
+    noted(ptr.as_mut(), "Field64")
+}
+
+/// Borrow an IField handle, or None if null.
+///
+/// # Safety
+/// `ptr` must be null or a live pointer returned by `va_create_ifield`.
+#[inline]
+pub(crate) unsafe fn ifield_ref<'a>(ptr: *const IField) -> Option<&'a IField> {
+    noted(ptr.as_ref(), "IField")
+}
+
+/// Mutably borrow an IField handle, or None if null.
+///
+/// # Safety
+/// `ptr` must be null or a live pointer returned by `va_create_ifield`, not aliased.
+#[inline]
+pub(crate) unsafe fn ifield_mut<'a>(ptr: *mut IField) -> Option<&'a mut IField> {
+    noted(ptr.as_mut(), "IField")
+}
+
+/// Borrow a StepController handle, or None if null, destroyed, or not a
+/// StepController.
+///
+/// # Safety
+/// `ptr` must be null or a pointer returned by `va_create_step_controller*`,
+/// live or destroyed.
+#[inline]
+pub(crate) unsafe fn ctrl_ref<'a>(ptr: *const StepController) -> Option<&'a StepController> {
+    if live(ptr) {
+        ptr.as_ref()
+    } else {
+        None
+    }
+}
+
+/// Mutably borrow a StepController handle, or None if null, destroyed, or not
+/// a StepController.
+///
+/// # Safety
+/// `ptr` must be null or a pointer returned by `va_create_step_controller*`,
+/// live or destroyed, not aliased.
+#[inline]
+pub(crate) unsafe fn ctrl_mut<'a>(ptr: *mut StepController) -> Option<&'a mut StepController> {
+    if live(ptr) {
+        ptr.as_mut()
+    } else {
+        None
+    }
+}
+
+/// Borrow a SharedState handle, or None if null, destroyed, or not a
+/// SharedState. Shared handles are only ever borrowed immutably: access to the
+/// State goes through their lock.
+///
+/// # Safety
+/// `ptr` must be null or a pointer returned by `va_create_shared`, live or
+/// destroyed.
+#[inline]
+pub(crate) unsafe fn shared_ref<'a>(ptr: *const SharedState) -> Option<&'a SharedState> {
+    if live(ptr) {
+        ptr.as_ref()
+    } else {
+        None
+    }
+}
+
+/// Borrow a FieldStack handle, or None if null.
+///
+/// # Safety
+/// `ptr` must be null or a live pointer returned by `va_create_field_stack`.
+#[inline]
+pub(crate) unsafe fn stack_ref<'a>(ptr: *const FieldStack) -> Option<&'a FieldStack> {
+    noted(ptr.as_ref(), "FieldStack")
+}
+
+/// Mutably borrow a FieldStack handle, or None if null.
+///
+/// # Safety
+/// `ptr` must be null or a live pointer returned by `va_create_field_stack`, not aliased.
+#[inline]
+pub(crate) unsafe fn stack_mut<'a>(ptr: *mut FieldStack) -> Option<&'a mut FieldStack> {
+    noted(ptr.as_mut(), "FieldStack")
+}
+
+/// Borrow a CoupledFields handle, or None if null.
+///
+/// # Safety
+/// `ptr` must be null or a live pointer returned by `va_create_coupled`.
+#[inline]
+pub(crate) unsafe fn coupled_ref<'a>(ptr: *const CoupledFields) -> Option<&'a CoupledFields> {
+    noted(ptr.as_ref(), "CoupledFields")
+}
+
+/// Mutably borrow a CoupledFields handle, or None if null.
+///
+/// # Safety
+/// `ptr` must be null or a live pointer returned by `va_create_coupled`, not aliased.
+#[inline]
+pub(crate) unsafe fn coupled_mut<'a>(ptr: *mut CoupledFields) -> Option<&'a mut CoupledFields> {
+    noted(ptr.as_mut(), "CoupledFields")
+}
+
+/// Borrow an AdaptiveField handle, or None if null.
+///
+/// # Safety
+/// `ptr` must be null or a live pointer returned by `va_create_adaptive_field`.
+#[inline]
+pub(crate) unsafe fn adaptive_ref<'a>(ptr: *const AdaptiveField) -> Option<&'a AdaptiveField> {
+    noted(ptr.as_ref(), "AdaptiveField")
+}
+
+/// Mutably borrow an AdaptiveField handle, or None if null.
+///
+/// # Safety
+/// `ptr` must be null or a live pointer returned by `va_create_adaptive_field`, not aliased.
+#[inline]
+pub(crate) unsafe fn adaptive_mut<'a>(ptr: *mut AdaptiveField) -> Option<&'a mut AdaptiveField> {
+    noted(ptr.as_mut(), "AdaptiveField")
+}
+
+/// Borrow an InfiniteState handle, or None if null.
+///
+/// # Safety
+/// `ptr` must be null or a live pointer returned by `va_create_infinite`.
+#[inline]
+pub(crate) unsafe fn inf_ref<'a>(ptr: *const InfiniteState) -> Option<&'a InfiniteState> {
+    noted(ptr.as_ref(), "InfiniteState")
+}
+
+/// Mutably borrow an InfiniteState handle, or None if null.
+///
+/// # Safety
+/// `ptr` must be null or a live pointer returned by `va_create_infinite`, not aliased.
+#[inline]
+pub(crate) unsafe fn inf_mut<'a>(ptr: *mut InfiniteState) -> Option<&'a mut InfiniteState> {
+    noted(ptr.as_mut(), "InfiniteState")
+}
+
+/// Largest buffer length (in elements) accepted from the caller. Anything larger
+/// is certainly a garbage length (e.g. a negative Lua number cast to u64), and
+/// would be undefined behavior in `from_raw_parts`.
+#[inline]
+fn max_elements<T>() -> u64 {
+    (isize::MAX as usize / std::mem::size_of::<T>().max(1)) as u64
+}
+
+/// View a caller-supplied output buffer as a slice.
+/// None if the pointer is null or `len` is not a plausible element count.
+///
+/// # Safety
+/// If non-null, `ptr` must point to at least `len` writable elements.
+#[inline]
+pub(crate) unsafe fn buf_mut<'a, T>(ptr: *mut T, len: u64) -> Option<&'a mut [T]> {
+    if ptr.is_null() || len > max_elements::<T>() {
+        return None;
+    }
+    Some(std::slice::from_raw_parts_mut(ptr, len as usize))
+}
+
+/// View a caller-supplied input buffer as a slice.
+/// None if the pointer is null or `len` is not a plausible element count.
+///
+/// # Safety
+/// If non-null, `ptr` must point to at least `len` readable elements.
+#[inline]
+pub(crate) unsafe fn buf_ref<'a, T>(ptr: *const T, len: u64) -> Option<&'a [T]> {
+    if ptr.is_null() || len > max_elements::<T>() {
+        return None;
+    }
+    Some(std::slice::from_raw_parts(ptr, len as usize))
+}
+
+/// Write an optional out-parameter; a null pointer is skipped.
+///
+/// # Safety
+/// `ptr` must be null or valid for a write of `T`.
+#[inline]
+pub(crate) unsafe fn write_opt<T>(ptr: *mut T, value: T) {
+    if let Some(out) = ptr.as_mut() {
+        *out = value;
+    }
+}
+
+/// Copy `text` into a caller-supplied byte buffer (not NUL-terminated).
+///
+/// # Safety
+/// If non-null, `out_buf` must point to at least `capacity` writable bytes.
+///
+/// # Returns
+/// The text length, or 0 if it does not fit. A null `out_buf` queries the
+/// length without writing.
+pub(crate) unsafe fn write_text(text: &str, out_buf: *mut u8, capacity: u64) -> u64 {
+    if out_buf.is_null() {
+        return text.len() as u64;
+    }
+    match buf_mut(out_buf, capacity) {
+        Some(out) if out.len() >= text.len() => {
+            out[..text.len()].copy_from_slice(text.as_bytes());
+            text.len() as u64
+        }
+        _ => 0,
+    }
+}
+
+/// Grid dimensions must all be positive.
+#[inline]
+pub(crate) fn dims_valid(width: i16, height: i16, depth: i16) -> bool {
+    let valid = width > 0 && height > 0 && depth > 0;
+    if !valid {
+        set_last_error(
+            VA_ERR_INVALID_ARGUMENT,
+            format!("dimensions {width}x{height}x{depth} must all be positive"),
+        );
+    }
+    valid
+}
+
+/// Number of cells in the half-open box `[min, max)`, computed without i16
+/// overflow. None if any axis is inverted (`min > max`). An empty box is
+/// `Some(0)`.
+#[inline]
+pub(crate) fn region_volume(min: [i16; 3], max: [i16; 3]) -> Option<usize> {
+    let mut volume = 1usize;
+    for axis in 0..3 {
+        let extent = max[axis] as i32 - min[axis] as i32;
+        if extent < 0 {
+            set_last_error(
+                VA_ERR_INVALID_ARGUMENT,
+                format!("inverted region {min:?}..{max:?}"),
+            );
+            return None;
+        }
+        volume *= extent as usize;
+    }
+    Some(volume)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::error::{va_last_error_code, va_last_error_message};
+    use std::ffi::CStr;
+    use std::ptr;
+
+    #[test]
+    fn test_null_handles() {
+        unsafe {
+            assert!(state_ref(ptr::null()).is_none());
+            assert!(state_mut(ptr::null_mut()).is_none());
+            assert!(field_ref(ptr::null()).is_none());
+            assert!(field_mut(ptr::null_mut()).is_none());
+            assert!(ctrl_ref(ptr::null()).is_none());
+            assert!(ctrl_mut(ptr::null_mut()).is_none());
+            assert!(stack_ref(ptr::null()).is_none());
+            assert!(stack_mut(ptr::null_mut()).is_none());
+            assert!(adaptive_ref(ptr::null()).is_none());
+            assert!(adaptive_mut(ptr::null_mut()).is_none());
+            assert!(inf_ref(ptr::null()).is_none());
+            assert!(inf_mut(ptr::null_mut()).is_none());
+        }
+        assert_eq!(va_last_error_code(), VA_ERR_NULL_HANDLE);
+        let message = unsafe { CStr::from_ptr(va_last_error_message()) };
+        assert_eq!(message.to_str(), Ok("null InfiniteState handle"));
+    }
+
+    #[test]
+    fn test_handle_tags() {
+        let mut state = State::default();
+        let mut field = crate::automaton::create_field_1(2, 2, 2, 2);
+        unsafe {
+            assert!(state_ref(&state).is_some());
+            assert!(field_mut(&mut field).is_some());
+
+            // A Field passed as a State is refused instead of misread
+            let as_state = (&mut field as *mut Field).cast::<State>();
+            assert!(state_mut(as_state).is_none());
+            assert_eq!(va_last_error_code(), VA_ERR_INVALID_HANDLE);
+            let message = CStr::from_ptr(va_last_error_message());
+            assert_eq!(
+                message.to_str(),
+                Ok("Field handle passed as a State handle")
+            );
+
+            // So is a destroyed one
+            state.tag = HandleTag::DEAD;
+            assert!(state_ref(&state).is_none());
+            assert_eq!(va_last_error_code(), VA_ERR_INVALID_HANDLE);
+
+            // Taking a handle marks its old memory destroyed and keeps the value live
+            let boxed = Box::into_raw(Box::new(State::default()));
+            let taken = take_handle(boxed).unwrap();
+            assert!(state_ref(&taken).is_some());
+            assert!(take_handle::<State>(ptr::null_mut()).is_none());
+        }
+    }
+
+    #[test]
+    fn test_buffers() {
+        let mut data = [1u8, 2, 3];
+        unsafe {
+            assert_eq!(buf_ref(data.as_ptr(), 3), Some(&[1u8, 2, 3][..]));
+            assert_eq!(buf_mut(data.as_mut_ptr(), 2).map(|s| s.len()), Some(2));
+            assert!(buf_ref::<u8>(ptr::null(), 3).is_none());
+            assert!(buf_mut::<u8>(ptr::null_mut(), 0).is_none());
+            // Garbage lengths are rejected before from_raw_parts
+            assert!(buf_ref(data.as_ptr(), u64::MAX).is_none());
+            assert!(buf_ref(data.as_ptr() as *const u32, u64::MAX / 2).is_none());
+        }
+    }
+
+    #[test]
+    fn test_write_opt() {
+        let mut out = 0u64;
+        unsafe {
+            write_opt(&mut out, 5);
+            write_opt(ptr::null_mut(), 6u64);
+        }
+        assert_eq!(out, 5);
+    }
+
+    #[test]
+    fn test_dims_and_regions() {
+        assert!(dims_valid(1, 1, 1));
+        assert!(!dims_valid(0, 8, 8));
+        assert!(!dims_valid(8, -1, 8));
+        assert_eq!(va_last_error_code(), VA_ERR_INVALID_ARGUMENT);
+
+        assert_eq!(region_volume([0, 0, 0], [4, 4, 4]), Some(64));
+        assert_eq!(region_volume([2, 2, 2], [2, 9, 9]), Some(0));
+        assert_eq!(region_volume([5, 0, 0], [4, 4, 4]), None);
+        // Would overflow in i16 arithmetic
+        assert_eq!(
+            region_volume([i16::MIN, 0, 0], [i16::MAX, 1, 1]),
+            Some(65535)
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! FFI interface for step modes, including WireWorld (see
+//! `automaton::wireworld`).
+
+use super::validate::{state_mut, state_ref};
+use crate::state::{State, StepMode};
+
+/// `va_set_step_mode` value: the birth/survival rule or transition table.
+pub const MODE_RULE: u32 = 0;
+/// `va_set_step_mode` value: WireWorld (0 empty, 1 wire, 2 head, 3 tail).
+pub const MODE_WIREWORLD: u32 = 1;
+
+/// Selects how steps compute the next generation. Cells are not converted:
+/// switching to WireWorld reads live cells (1) as wire, and cell states are
+/// written with `va_set_cell_state`.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer or unknown mode).
+#[no_mangle]
+pub unsafe extern "C" fn va_set_step_mode(ptr: *mut State, mode: u32) -> i32 {
+    let Some(state) = state_mut(ptr) else {
+        return 1;
+    };
+    state.mode = match mode {
+        MODE_RULE => StepMode::Rule,
+        MODE_WIREWORLD => StepMode::WireWorld,
+        _ => return 1,
+    };
+    0
+}
+
+/// Gets the step mode.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// `MODE_RULE` or `MODE_WIREWORLD`, or -1 for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn va_get_step_mode(ptr: *const State) -> i32 {
+    match state_ref(ptr).map(|state| state.mode) {
+        Some(StepMode::Rule) => MODE_RULE as i32,
+        Some(StepMode::WireWorld) => MODE_WIREWORLD as i32,
+        None => -1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::wireworld::{HEAD, TAIL, WIRE};
+    use crate::ffi::grid::{va_create_grid, va_get_cell, va_step};
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use crate::ffi::transition::va_set_cell_state;
+    use std::ptr;
+
+    #[test]
+    fn test_wireworld_via_ffi() {
+        unsafe {
+            let state = va_create();
+            va_create_grid(state, 4, 3, 3);
+            assert_eq!(va_get_step_mode(state), MODE_RULE as i32);
+            assert_eq!(va_set_step_mode(state, 2), 1);
+            assert_eq!(va_set_step_mode(state, MODE_WIREWORLD), 0);
+            assert_eq!(va_get_step_mode(state), MODE_WIREWORLD as i32);
+            for x in 0..4 {
+                assert_eq!(va_set_cell_state(state, x, 1, 1, WIRE), 0);
+            }
+            va_set_cell_state(state, 0, 1, 1, HEAD);
+            va_step(state);
+            assert_eq!(va_get_cell(state, 0, 1, 1), TAIL);
+            assert_eq!(va_get_cell(state, 1, 1, 1), HEAD);
+
+            assert_eq!(va_set_step_mode(state, MODE_RULE), 0);
+            assert_eq!(va_set_cell_state(state, 0, 1, 1, WIRE), 1);
+            assert_eq!(va_set_step_mode(ptr::null_mut(), MODE_RULE), 1);
+            assert_eq!(va_get_step_mode(ptr::null()), -1);
+            va_destroy(state);
+        }
+    }
+}
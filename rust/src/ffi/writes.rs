@@ -0,0 +1,145 @@
+//! FFI interface for the deferred world-write queue (see `automaton::writes`).
+//!
+//! Typical use: enable the queue once, call `va_step` as usual, and every
+//! server tick drain up to K changes into `minetest.set_node` /
+//! `minetest.remove_node`.
+
+use super::grid::PREVIEW_RECORD_LEN;
+use super::validate::{buf_mut, state_mut, state_ref};
+use crate::automaton::writes::WriteQueue;
+use crate::state::State;
+
+/// Enables the write queue with room for `capacity` changes, or disables it
+/// (discarding pending changes) with a capacity of 0. Re-enabling keeps the
+/// pending changes that still fit and the overflow count.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// 0 on success, 1 on failure (null pointer).
+#[no_mangle]
+pub unsafe extern "C" fn va_enable_write_queue(ptr: *mut State, capacity: u64) -> i32 {
+    let Some(state) = state_mut(ptr) else {
+        return 1;
+    };
+    let capacity = usize::try_from(capacity).unwrap_or(usize::MAX);
+    if capacity == 0 {
+        state.write_queue = None;
+        return 0;
+    }
+    let mut queue = WriteQueue::new(capacity);
+    if let Some(mut old) = state.write_queue.take() {
+        for change in old.drain(capacity) {
+            queue.push(change);
+        }
+        queue.overflowed = old.overflowed + old.len() as u64;
+    }
+    state.write_queue = Some(queue);
+    0
+}
+
+/// Removes up to `max` of the oldest queued changes.
+///
+/// out_changes layout per change: [x, y, z, alive] (4 x i16), the same records
+/// as `va_step_preview`. Apply them in order.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+/// - `out_changes` must point to at least `max * 4` writable i16 values
+///
+/// # Returns
+/// Number of changes written, or 0 (null pointer, no queue, or empty queue).
+#[no_mangle]
+pub unsafe extern "C" fn va_drain_writes(ptr: *mut State, out_changes: *mut i16, max: u64) -> u64 {
+    let Some(queue) = state_mut(ptr).and_then(|state| state.write_queue.as_mut()) else {
+        return 0;
+    };
+    let max = max.min(queue.len() as u64);
+    let Some(out) = buf_mut(out_changes, max * PREVIEW_RECORD_LEN as u64) else {
+        return 0;
+    };
+    for (change, slot) in queue
+        .drain(max as usize)
+        .zip(out.chunks_exact_mut(PREVIEW_RECORD_LEN))
+    {
+        slot.copy_from_slice(&[change.x, change.y, change.z, change.alive as i16]);
+    }
+    max
+}
+
+/// Returns the number of changes waiting to be drained.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// The queue length, or 0 (null pointer, no queue).
+#[no_mangle]
+pub unsafe extern "C" fn va_pending_writes(ptr: *const State) -> u64 {
+    state_ref(ptr)
+        .and_then(|state| state.write_queue.as_ref())
+        .map_or(0, |queue| queue.len() as u64)
+}
+
+/// Returns the number of changes rejected because the queue was full. A rising
+/// count means the map no longer matches the grid and should be resynchronized
+/// from a full extraction.
+///
+/// # Safety
+/// - `ptr` must be a valid pointer to a State, or null
+///
+/// # Returns
+/// The count since the queue was enabled, or 0 (null pointer, no queue).
+#[no_mangle]
+pub unsafe extern "C" fn va_get_overflowed_writes(ptr: *const State) -> u64 {
+    state_ref(ptr)
+        .and_then(|state| state.write_queue.as_ref())
+        .map_or(0, |queue| queue.overflowed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::grid::{va_create_grid, va_set_cell, va_step, va_step_preview};
+    use crate::ffi::lifecycle::{va_create, va_destroy};
+    use std::ptr;
+
+    #[test]
+    fn test_write_queue_via_ffi() {
+        unsafe {
+            let state = va_create();
+            va_create_grid(state, 8, 8, 8);
+            for (x, y) in [(4, 4), (3, 4), (5, 4), (4, 3), (4, 5)] {
+                va_set_cell(state, x, y, 4, 1);
+            }
+            assert_eq!(va_drain_writes(state, ptr::null_mut(), 4), 0);
+            assert_eq!(va_enable_write_queue(state, 1000), 0);
+            let mut preview = vec![0i16; 4 * 64];
+            let changed = va_step_preview(state, preview.as_mut_ptr(), 64);
+            va_step(state);
+            assert_eq!(va_pending_writes(state), changed);
+
+            let mut out = vec![0i16; 4 * 64];
+            assert_eq!(va_drain_writes(state, out.as_mut_ptr(), 3), 3);
+            assert_eq!(out[..12], preview[..12]);
+            assert_eq!(va_pending_writes(state), changed - 3);
+            assert_eq!(va_drain_writes(state, out.as_mut_ptr(), 64), changed - 3);
+            assert_eq!(out[..4], preview[12..16]);
+            assert_eq!(va_drain_writes(state, out.as_mut_ptr(), 64), 0);
+
+            // Shrinking counts what no longer fits as overflowed
+            va_step(state);
+            let pending = va_pending_writes(state);
+            assert_eq!(va_enable_write_queue(state, 2), 0);
+            assert_eq!(va_pending_writes(state), 2);
+            assert_eq!(va_get_overflowed_writes(state), pending - 2);
+
+            assert_eq!(va_enable_write_queue(state, 0), 0);
+            assert_eq!(va_pending_writes(state), 0);
+            assert_eq!(va_enable_write_queue(ptr::null_mut(), 8), 1);
+            assert_eq!(va_get_overflowed_writes(ptr::null()), 0);
+            va_destroy(state);
+        }
+    }
+}
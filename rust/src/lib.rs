@@ -4,16 +4,248 @@
 //!
 //! ## Module Structure
 //!
-//! - **`state`**: Core opaque State type (pure data structure)
+//! - **`state`**: Core opaque State type (pure data structure), and the tags
+//!   the FFI layer checks handles against
 //! - **`automaton`**: Core simulation logic
+//!   - `adaptive`: Two-level field, coarse 4³ blocks in quiet regions and fine
+//!     cells in active ones, conserving across level boundaries
+//!   - `activity`: Per-tile activity of a StepController, so steps skip tiles
+//!     at equilibrium
+//!   - `age`: Per-cell age (generations survived) with an optional age limit
+//!   - `audit`: Checked-arithmetic overflow audit of the flow computations
+//!   - `background`: A StepController stepped on a worker thread, read through
+//!     a double-buffered copy of the latest generation
+//!   - `bind`: Grid-field binding (live cells emit into the field, the field
+//!     gates births) advanced in one combined step
+//!   - `bundle`: Scenario bundles (grid and field sections with dimensions,
+//!     settings, stamps and initial cells) loaded from one text file
+//!   - `boundary`: Per-face boundary conditions (reflective, fixed value, open);
+//!     periodic axes wrap in the diffusion pass instead
+//!   - `castep`: Tiled, budgeted stepping of the grid across server ticks
+//!   - `chunked`: Chunked Field storage, sparse 16³ chunks around a uniform
+//!     ambient value, for huge mostly uniform domains
+//!   - `components`: Flood fill and connected component labelling of live
+//!     cells (6-, 18- or 26-connectivity)
+//!   - `conductivity`: Piecewise-linear value-to-conductivity curves
+//!   - `config`: Text (TOML) configuration blobs of State and Field handles
+//!   - `coupled`: Fields stepped in lockstep with a linear cross-term matrix
+//!   - `cycle`: Hashes of recent generations, to notice still lifes and
+//!     oscillators and stop stepping dormant automata
+//!   - `degrade`: Memory cap for StepControllers, met by shedding optional
+//!     memory (flow record, spare capacity, worker threads) with events
+//!   - `diff`: Cells differing between two grids or two fields of one size
+//!   - `events`: Bounded queue of StepController events (generation complete)
+//!   - `fastforward`: Many generations run natively with a metric sample
+//!     (population and bounding box, or field mass) every `stride` generations
+//!   - `field64`: Wide-value field (u64 cells, i128 flow math) sharing the
+//!     diffusion pass of `field`
+//!   - `ifield`: Signed field (i32 cells) for potentials and velocity components,
+//!     sharing the diffusion pass of `field`
+//!   - `infinite`: InfiniteState, an unbounded CA grid in lazily allocated 16³
+//!     chunks with i32 world coordinates
+//!   - `lenia`: Fixed-point Lenia on the field (radial kernel, growth curve
+//!     table) as an alternative to diffusion
+//!   - `locked`: SharedState, a State behind a lock for use from several
+//!     threads (Luanti async environments)
+//!   - `oracle` (feature `oracle`, default): Big-integer recomputation of the
+//!     diffusion pass, and shadow steps checking `field_step` against it
+//!   - `ghost`: Ghost-layer exchange between adjacent grids or fields, for
+//!     worlds sharded across handles
 //!   - `grid`: Grid operations (index calculation, bounds checking, neighbor counting)
-//!   - `stepping`: Cellular automaton stepping with B4/S4 rules
-//!   - `region`: Region extraction and import
+//!   - `hash`: Deterministic hashes of grid and field contents, for checking
+//!     that replicas (clients predicting the automaton) still agree
+//!   - `history`: Ring buffer of grid checkpoints every N generations, and
+//!     rollback to the newest checkpoint at or before a past generation
+//!   - `stepping`: Cellular automaton stepping with B4/S4 rules, and a dry-run
+//!     preview of the next generation's changes
+//!   - `region`: Region extraction, import, and bulk fill/clear (State and the
+//!     field variants)
+//!   - `registry`: Slab of values addressed by generation-checked u32 ids
+//!   - `report`: Debug reports (settings, counters, mass ledger, step timing,
+//!     recent errors) for pasting into bug reports
+//!   - `resample`: Conservative coarse-to-fine refinement and fine-to-coarse
+//!     aggregation (LOD tiers, mapgen), and averaged downsampling for display
+//!   - `phase`: Phase-change thresholds with latent heat (ice/water/steam)
+//!   - `pool`: Reusable power-of-two extraction buffers
+//!   - `poststep`: Operations chained into StepController finalize (threshold
+//!     coupling to a grid, decay, statistics)
+//!   - `protect`: Protection masks vetoing births and field increases at commit
+//!     time (land claims)
+//!   - `raycast`: 3D DDA ray traversal to the first live cell, or the first
+//!     field cell above a threshold
+//!   - `rule`: Rule notation (B/S and Golly 3D) and rule-table export
+//!   - `search`: Occurrences of a small pattern in the grid, optionally under
+//!     the 24 cube rotations
+//!   - `shape`: Analytic field fills (box, sphere, shell, linear and radial
+//!     gradients) for initial conditions
+//!   - `shared`: One field shared by a single writer and reference-counted
+//!     read-only readers (visualization, statistics) without copies
+//!   - `shm` (feature `shm`, default): Field cells mirrored into named shared
+//!     memory with a layout header, for external visualizers and daemons
+//!   - `snapshot`: Versioned binary save/restore of State (raw or RLE) and Field
+//!     (plain or delta against a baseline)
+//!   - `stack`: FieldStack, several coupled field layers stepped in one pass
+//!   - `stamp`: Built-in pattern stamps (shapes, oscillators, gliders) with 24 rotations
+//!   - `stats`: Field value distribution (histograms over `[min, max]`, percentiles),
+//!     threshold classification into u8 classes, and per-field zones
+//!   - `rng`: Deterministic SplitMix64 PRNG
+//!   - `soak`: Randomized long-running invariant checks on field copies
+//!   - `species`: Per-cell species ids (team-colored Life): majority births and
+//!     configurable inter-species visibility
+//!   - `trace`: Per-generation record of a single cell (value, neighbors, face flows)
+//!   - `transition`: Multi-state automata stepped through a (state, neighbor
+//!     count) transition table instead of the birth/survival rule
+//!   - `wireworld`: WireWorld cells (empty, wire, electron head and tail) for
+//!     in-game logic circuits, selected as the state's step mode
+//!   - `writes`: Bounded queue of committed births and deaths, drained into the
+//!     world a few per tick
+//! - **`api`**: Safe Rust API (constructors, methods, iterators on `State`, `Field`,
+//!   `StepController`) for Rust callers that don't want raw pointers
+//! - **`python`** (feature `python`): PyO3 classes with NumPy interchange
+//! - **`wasm`** (feature `wasm`): wasm-bindgen wrapper for browser demos
 //! - **`ffi`**: C ABI interface for LuaJIT
 //!   - `simple`: va_add (FFI proof of concept)
-//!   - `lifecycle`: va_create, va_destroy, va_get_generation
-//!   - `grid`: va_create_grid, va_set_cell, va_get_cell, va_step
-//!   - `region`: va_extract_region, va_import_region
+//!   - `adaptive`: va_create_adaptive_field, va_adaptive_step, va_adaptive_get/set,
+//!     va_adaptive_refine/coarsen, va_adaptive_set_threshold (large outdoor
+//!     volumes at coarse resolution where nothing happens)
+//!   - `activity`: va_sc_set_skip_quiet, va_sc_skipped_tiles (skip tiles at
+//!     equilibrium)
+//!   - `age`: va_set_age_tracking, va_get_cell_age, va_extract_age_region
+//!     (cell ages for weathered rendering)
+//!   - `audit`: va_field_step_checked, va_sc_audit_overflow (report the first
+//!     pair whose flow would overflow i64)
+//!   - `background`: va_sc_start_async, va_async_get, va_async_extract_region,
+//!     va_async_stop, ... (automatic stepping off the server thread)
+//!   - `bind`: va_bind_field, va_step_bound, va_unbind (grid and field stepped
+//!     together, coupled both ways)
+//!   - `bundle`: va_load_bundle (State and Field of a scenario data file in one
+//!     call)
+//!   - `castep`: va_create_ca_step_controller, va_ca_sc_begin_step,
+//!     va_ca_sc_tick, va_ca_sc_state, ... (large grids stepped without blocking)
+//!   - `lifecycle`: va_create, va_destroy, va_get_generation, va_reinit (reset
+//!     process-wide state on mod reload), va_build_info, va_build_features
+//!     (features and profile of the binary for bug reports)
+//!   - `ghost`: va_exchange_boundaries, va_field_exchange_boundaries (stitch
+//!     handles sharding one world, before each step)
+//!   - `grid`: va_create_grid, va_set_cell, va_get_cell, va_step,
+//!     va_set_cells_bulk, va_get_cells_bulk (many cells per call),
+//!     va_step_preview (next generation's changes without committing them),
+//!     va_get_cells_ptr, va_get_cells_len (zero-copy read access)
+//!   - `hash`: va_hash, va_field_hash (content hashes for sync verification)
+//!   - `history`: va_enable_history, va_rollback, va_get_history_range
+//!     (in-game rewind without shipping the grid to Lua each step)
+//!   - `chunked`: va_create_field_chunked, va_field_chunk_count (chunked
+//!     fields are Field handles for the va_field_* calls)
+//!   - `components`: va_flood_fill, va_label_components (cells connected to a
+//!     seed, per-cell component ids; notice structures splitting apart)
+//!   - `config`: va_get_config, va_set_config, va_field_get_config,
+//!     va_field_set_config (all tunables of a handle as one TOML blob)
+//!   - `coupled`: va_create_coupled, va_destroy_coupled, va_coupled_register
+//!     (takes ownership of a field), va_coupled_set_coefficient,
+//!     va_coupled_set_matrix, va_coupled_step, va_coupled_get_generation
+//!   - `cycle`: va_detect_cycle (period of the cycle a grid has settled into),
+//!     va_run_until_stable (step until it settles, for pre-baking structures)
+//!   - `degrade`: va_sc_set_memory_cap, va_sc_memory_usage,
+//!     va_sc_get_degradations (degrade instead of failing under memory pressure)
+//!   - `diff`: va_diff, va_field_diff (differing cells of two handles, for
+//!     network deltas and node updates)
+//!   - `error`: va_last_error_code, va_last_error_message, va_clear_last_error
+//!     (why the calling thread's last failing call failed)
+//!   - `fastforward`: va_fast_forward, va_field_fast_forward (offline rule
+//!     balancing without per-generation FFI round trips)
+//!   - `field`: va_create_field, va_field_step, va_field_get/set,
+//!     va_field_sample_batch (many points per call), va_field_set_cells_bulk, va_field_total, va_field_min, va_field_max, va_field_mean, region
+//!     extract/import, va_field_set_flow_recording, va_field_get_flows (per-axis
+//!     flow of the last step, for debugging diffusion), va_field_set_rounding,
+//!     va_field_get_rounding, va_field_add_source, va_field_add_sink,
+//!     va_field_remove_source, va_field_clear_sources (per-step injection/drain),
+//!     va_field_set_advection (directional bias such as gravity),
+//!     va_field_set_boundary (reflective, fixed-value, or open outer faces),
+//!     va_field_set_periodic (wrap-around axes for tiled domains),
+//!     va_field_set_axis_rates (per-axis diffusion, e.g. slow vertical transport),
+//!     va_field_set_conductivity_curve (value-dependent conductivity),
+//!     va_field_set_phase_thresholds, va_field_get_phase (phase changes with
+//!     latent heat)
+//!   - `field64`: va_create_field64, va_destroy_field64, va_field64_get/set,
+//!     va_field64_step, va_field64_get_generation, region extract/import,
+//!     va_field64_set_rounding, va_field64_set_axis_rates
+//!   - `ifield`: va_create_ifield, va_destroy_ifield, va_ifield_get/set,
+//!     va_ifield_step, va_ifield_get_generation, region extract/import,
+//!     va_ifield_set_rounding, va_ifield_set_axis_rates
+//!   - `infinite`: va_create_infinite, va_inf_set_cell/get_cell, va_inf_step,
+//!     va_inf_set_rule, va_inf_list_chunks, va_inf_extract_chunk (patterns
+//!     not walled in by a grid size)
+//!   - `lenia`: va_field_step_lenia (continuous automaton with a radial kernel
+//!     and growth table)
+//!   - `locked`: va_create_shared, va_destroy_shared, va_shared_step,
+//!     va_shared_set_cell, ... (State calls that lock internally),
+//!     va_shared_try_step, va_shared_lock, va_shared_try_lock,
+//!     va_shared_unlock (hold the lock across ordinary State calls)
+//!   - `oracle` (feature `oracle`): va_field_shadow_steps (field steps checked
+//!     against exact arithmetic, to catch fixed-point errors in the field)
+//!   - `pool`: va_acquire_buffer, va_release_buffer, va_trim_buffer_pool
+//!   - `poststep`: va_sc_post_add_threshold, va_sc_post_add_decay,
+//!     va_sc_post_add_stats, va_sc_post_clear, va_sc_post_grid, va_sc_post_stats
+//!   - `protect`: va_protect_region, va_get_suppressed, va_field_protect_region,
+//!     va_field_get_suppressed, va_sc_protect_region, va_sc_get_suppressed
+//!     (protected cells and the tally of what they vetoed)
+//!   - `raycast`: va_raycast, va_field_raycast (first live cell, or first field
+//!     cell above a threshold, along a ray)
+//!   - `region`: va_extract_region, va_import_region (explicit buffer length,
+//!     optional generation tag on extraction),
+//!     va_extract_region_checked, va_import_region_checked (size query),
+//!     va_extract_mapblock, va_import_mapblock (16³ blocks, i64 block coords),
+//!     va_fill_region, va_randomize_region, va_clear
+//!   - `registry`: va_id_create, va_id_create_field,
+//!     va_id_create_step_controller, va_id_destroy, va_id_kind, va_id_list
+//!     (integer handle ids: stale or mistyped ids are errors, live ids can be
+//!     enumerated), id variants of the core grid, field and StepController
+//!     calls, va_id_state_ptr, va_id_field_ptr, va_id_sc_ptr
+//!   - `report`: va_dump_debug_report, va_field_dump_debug_report (handle
+//!     summary with recent errors, for bug reports)
+//!   - `resample`: va_field_refine, va_field_aggregate (exact-mass resolution
+//!     changes between fields), va_field_extract_downsampled (block averages
+//!     for distant rendering)
+//!   - `search`: va_find_pattern (pattern occurrences, e.g. for achievements)
+//!   - `selftest`: va_self_test (deployment validation, bitmask of failures),
+//!     va_soak, va_soak_round (randomized invariant stress test on a field copy)
+//!   - `shape`: va_field_fill_box, va_field_fill_sphere, va_field_fill_shell,
+//!     va_field_fill_linear_gradient, va_field_fill_radial_gradient
+//!   - `shared`: va_field_share, va_shared_field_unshare, va_destroy_shared_field,
+//!     va_shared_field_reader, va_shared_field_readers, va_shared_field_step,
+//!     va_shared_field_set, va_reader_clone, va_release_reader, va_reader_get,
+//!     va_reader_get_generation, va_reader_total, va_reader_extract_region
+//!     (single writer, read-only consumers enforced by the handle type)
+//!   - `shm` (feature `shm`): va_field_shm_export, va_shm_publish,
+//!     va_shm_path, va_shm_destroy (live field for out-of-process observers)
+//!   - `snapshot`: va_serialize[_compressed], va_deserialize[_compressed],
+//!     va_serialized_size_hint, va_set_rule, va_get_rule, va_set_rule_string,
+//!     va_export_rule_table, va_field_serialize, va_field_deserialize (optional
+//!     delta encoding against a baseline field)
+//!   - `species`: va_set_cell_species, va_get_cell_species,
+//!     va_set_species_relation (competing colonies)
+//!   - `stack`: va_create_field_stack, va_destroy_field_stack,
+//!     va_field_stack_set_layer, va_field_stack_get/set, va_field_stack_step,
+//!     va_field_stack_get_generation
+//!   - `stamp`: va_stamp
+//!   - `stats`: va_field_histogram, va_field_percentile (value distribution
+//!     without transferring the grid), va_field_extract_thresholded (u8 class
+//!     per cell for node selection), va_field_set_zones, va_field_get_zone,
+//!     va_field_extract_zones (stored breakpoints for gameplay categories)
+//!   - `trace`: va_trace_cell, va_field_trace_cell (per-generation record of one
+//!     cell's value, neighbor count, and face flows), va_field_explain (readable
+//!     breakdown of one cell after the last step)
+//!   - `transition`: va_set_transition_table, va_set_cell_state (multi-state
+//!     rules such as Brian's Brain)
+//!   - `txn`: va_txn_begin, va_txn_add_state, va_txn_add_field,
+//!     va_txn_add_controller, va_txn_commit, va_txn_abort (all-or-nothing
+//!     mutations across handles with respect to stepping)
+//!   - `wireworld`: va_set_step_mode, va_get_step_mode (birth/survival rule
+//!     or WireWorld circuits)
+//!   - `writes`: va_enable_write_queue, va_drain_writes, va_pending_writes,
+//!     va_get_overflowed_writes (step changes applied to the map K per tick)
+//!   - `validate`: Shared argument checks (null handles, buffer lengths,
+//!     dimensions, region ordering)
 //!
 //! ## Design
 //!
@@ -22,18 +254,25 @@
 //! - **FFI layer** is minimal, just wrapping core logic with null checks and pointer safety
 //! - **Tests** are co-located with their implementations for clarity
 
+pub mod api;
 pub mod automaton;
 pub mod ffi;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 pub mod state;
 mod tests;
 
+pub use api::Cells;
+
 // Re-export public FFI API for C bindings
 pub use automaton::{Field, StepController};
 pub use ffi::{
-    va_add, va_create, va_create_field, va_create_grid, va_create_step_controller, va_destroy,
-    va_destroy_step_controller, va_extract_region, va_field_get, va_field_set, va_field_step,
-    va_get_cell, va_get_generation, va_import_region, va_sc_begin_step, va_sc_field_get,
-    va_sc_field_get_generation, va_sc_field_set, va_sc_is_stepping, va_sc_step_blocking,
-    va_sc_tick, va_set_cell, va_step,
+    va_add, va_create, va_create_field, va_create_grid, va_create_step_controller, va_deserialize,
+    va_destroy, va_destroy_step_controller, va_extract_region, va_field_get, va_field_set,
+    va_field_step, va_get_cell, va_get_generation, va_import_region, va_sc_begin_step,
+    va_sc_field_get, va_sc_field_get_generation, va_sc_field_set, va_sc_is_stepping,
+    va_sc_step_blocking, va_sc_tick, va_serialize, va_set_cell, va_step,
 };
-pub use state::State;
+pub use state::{Rule, State};
@@ -0,0 +1,285 @@
+//! Optional Python bindings (feature `python`) for analysis in notebooks.
+//!
+//! Wraps the safe `api` layer, so Python runs the exact same kernels as the
+//! Luanti mod. Bulk data crosses as NumPy arrays shaped `(depth, height, width)`,
+//! matching the z,y,x row-major layout of the cell buffers.
+//!
+//! Build with `maturin develop` from the `rust/` directory (pyproject enables the feature).
+
+use numpy::ndarray::Array3;
+use numpy::{IntoPyArray, PyArray3, PyReadonlyArray3};
+use pyo3::exceptions::{PyIndexError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+use crate::automaton::field::Field;
+use crate::automaton::incremental::StepController;
+use crate::state::State;
+
+fn shape_of(width: i16, height: i16, depth: i16) -> (usize, usize, usize) {
+    (depth as usize, height as usize, width as usize)
+}
+
+fn check_dims(width: i16, height: i16, depth: i16) -> PyResult<()> {
+    if width <= 0 || height <= 0 || depth <= 0 {
+        return Err(PyValueError::new_err("dimensions must be positive"));
+    }
+    Ok(())
+}
+
+fn to_array<'py, T: numpy::Element>(
+    py: Python<'py>,
+    cells: Vec<T>,
+    shape: (usize, usize, usize),
+) -> PyResult<Bound<'py, PyArray3<T>>> {
+    let arr =
+        Array3::from_shape_vec(shape, cells).map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+    Ok(arr.into_pyarray(py))
+}
+
+fn from_array<T: numpy::Element + Copy>(
+    arr: PyReadonlyArray3<'_, T>,
+    shape: (usize, usize, usize),
+) -> PyResult<Vec<T>> {
+    let view = arr.as_array();
+    if view.dim() != shape {
+        return Err(PyValueError::new_err(format!(
+            "expected shape {:?}, got {:?}",
+            shape,
+            view.dim()
+        )));
+    }
+    Ok(view.iter().copied().collect())
+}
+
+/// Binary B4/S4 cellular automaton grid.
+#[pyclass(name = "State")]
+pub struct PyState {
+    inner: State,
+}
+
+#[pymethods]
+impl PyState {
+    #[new]
+    fn new(width: i16, height: i16, depth: i16) -> PyResult<Self> {
+        check_dims(width, height, depth)?;
+        Ok(PyState {
+            inner: State::new(width, height, depth),
+        })
+    }
+
+    #[getter]
+    fn shape(&self) -> (usize, usize, usize) {
+        shape_of(self.inner.width, self.inner.height, self.inner.depth)
+    }
+
+    #[getter]
+    fn generation(&self) -> u64 {
+        self.inner.generation()
+    }
+
+    #[getter]
+    fn population(&self) -> usize {
+        self.inner.population()
+    }
+
+    fn get(&self, x: i16, y: i16, z: i16) -> PyResult<u8> {
+        self.inner
+            .get(x, y, z)
+            .ok_or_else(|| PyIndexError::new_err("cell out of bounds"))
+    }
+
+    fn set(&mut self, x: i16, y: i16, z: i16, alive: bool) -> PyResult<()> {
+        if self.inner.set(x, y, z, alive) {
+            Ok(())
+        } else {
+            Err(PyIndexError::new_err("cell out of bounds"))
+        }
+    }
+
+    #[pyo3(signature = (n = 1))]
+    fn step(&mut self, n: u64) {
+        self.inner.step_n(n);
+    }
+
+    /// Copy of the grid as a uint8 array shaped (depth, height, width).
+    fn to_numpy<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray3<u8>>> {
+        let shape = self.shape();
+        to_array(py, self.inner.cells.clone(), shape)
+    }
+
+    /// Overwrite the grid from a uint8 array shaped (depth, height, width).
+    fn load_numpy(&mut self, arr: PyReadonlyArray3<'_, u8>) -> PyResult<()> {
+        let cells = from_array(arr, self.shape())?;
+        self.inner.cells = cells.into_iter().map(|c| (c != 0) as u8).collect();
+        Ok(())
+    }
+}
+
+/// Integer diffusion field (sequential `va_field_step` kernel by default).
+#[pyclass(name = "Field")]
+pub struct PyField {
+    inner: Field,
+}
+
+#[pymethods]
+impl PyField {
+    #[new]
+    #[pyo3(signature = (width, height, depth, diffusion_rate = 2))]
+    fn new(width: i16, height: i16, depth: i16, diffusion_rate: u8) -> PyResult<Self> {
+        check_dims(width, height, depth)?;
+        Ok(PyField {
+            inner: Field::new(width, height, depth, diffusion_rate),
+        })
+    }
+
+    #[getter]
+    fn shape(&self) -> (usize, usize, usize) {
+        shape_of(self.inner.width, self.inner.height, self.inner.depth)
+    }
+
+    #[getter]
+    fn generation(&self) -> u64 {
+        self.inner.generation
+    }
+
+    #[getter]
+    fn total(&self) -> u64 {
+        self.inner.total()
+    }
+
+    fn get(&self, x: i16, y: i16, z: i16) -> PyResult<u32> {
+        self.inner
+            .get(x, y, z)
+            .map(|v| v.get())
+            .map_err(|_| PyIndexError::new_err("cell out of bounds"))
+    }
+
+    fn set(&mut self, x: i16, y: i16, z: i16, value: u32) -> PyResult<()> {
+        self.inner
+            .set(x, y, z, value)
+            .map_err(|_| PyIndexError::new_err("cell out of bounds"))
+    }
+
+    #[pyo3(signature = (n = 1, fused = false))]
+    fn step(&mut self, n: u64, fused: bool) {
+        for _ in 0..n {
+            if fused {
+                self.inner.step_fused();
+            } else {
+                self.inner.step();
+            }
+        }
+    }
+
+    /// Copy of the field as a uint32 array shaped (depth, height, width).
+    fn to_numpy<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray3<u32>>> {
+        let shape = self.shape();
+        to_array(py, self.inner.cells.clone(), shape)
+    }
+
+    /// Overwrite the field from a uint32 array shaped (depth, height, width).
+    fn load_numpy(&mut self, arr: PyReadonlyArray3<'_, u32>) -> PyResult<()> {
+        self.inner.cells = from_array(arr, self.shape())?;
+        Ok(())
+    }
+}
+
+/// Incremental (tiled) step controller over a field.
+#[pyclass(name = "StepController", unsendable)]
+pub struct PyStepController {
+    inner: StepController,
+}
+
+#[pymethods]
+impl PyStepController {
+    #[new]
+    #[pyo3(signature = (width, height, depth, diffusion_rate = 2, num_threads = 1))]
+    fn new(
+        width: i16,
+        height: i16,
+        depth: i16,
+        diffusion_rate: u8,
+        num_threads: u8,
+    ) -> PyResult<Self> {
+        check_dims(width, height, depth)?;
+        Ok(PyStepController {
+            inner: StepController::new_1(width, height, depth, diffusion_rate, num_threads),
+        })
+    }
+
+    #[getter]
+    fn shape(&self) -> (usize, usize, usize) {
+        let f = &self.inner.field;
+        shape_of(f.width, f.height, f.depth)
+    }
+
+    #[getter]
+    fn generation(&self) -> u64 {
+        self.inner.field.generation
+    }
+
+    #[getter]
+    fn is_stepping(&self) -> bool {
+        self.inner.is_stepping()
+    }
+
+    fn get(&self, x: i16, y: i16, z: i16) -> PyResult<u32> {
+        self.inner
+            .get(x, y, z)
+            .map(|v| v.get())
+            .map_err(|_| PyIndexError::new_err("cell out of bounds"))
+    }
+
+    fn set(&mut self, x: i16, y: i16, z: i16, value: u32) -> PyResult<()> {
+        if self.inner.set(x, y, z, value) {
+            Ok(())
+        } else {
+            Err(PyIndexError::new_err("cell out of bounds"))
+        }
+    }
+
+    fn begin_step(&mut self) -> PyResult<()> {
+        self.inner
+            .begin_step()
+            .map_err(|_| PyRuntimeError::new_err("step already in progress"))
+    }
+
+    /// Do bounded work; returns True when the step completed.
+    fn tick(&mut self, budget_us: u64) -> PyResult<bool> {
+        if !self.inner.is_stepping() {
+            return Err(PyRuntimeError::new_err("no step in progress"));
+        }
+        Ok(self.inner.tick(budget_us))
+    }
+
+    #[pyo3(signature = (n = 1))]
+    fn step(&mut self, n: u64) {
+        for _ in 0..n {
+            self.inner.step_blocking();
+        }
+    }
+
+    /// Copy of the committed field as a uint32 array shaped (depth, height, width).
+    fn to_numpy<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray3<u32>>> {
+        let shape = self.shape();
+        to_array(py, self.inner.field.cells.clone(), shape)
+    }
+
+    /// Overwrite the field from a uint32 array. Fails while a step is in progress.
+    fn load_numpy(&mut self, arr: PyReadonlyArray3<'_, u32>) -> PyResult<()> {
+        if self.inner.is_stepping() {
+            return Err(PyRuntimeError::new_err("step in progress"));
+        }
+        self.inner.field.cells = from_array(arr, self.shape())?;
+        self.inner.invalidate_activity();
+        Ok(())
+    }
+}
+
+#[pymodule]
+fn voxel_automata(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyState>()?;
+    m.add_class::<PyField>()?;
+    m.add_class::<PyStepController>()?;
+    Ok(())
+}
@@ -3,14 +3,170 @@
 //! This module defines the opaque State type that holds the automaton's grid data.
 //! The actual logic for manipulating state is in the `automaton` module.
 
+use crate::automaton::age::CellAge;
+use crate::automaton::cycle::CycleTracker;
+use crate::automaton::history::History;
+use crate::automaton::protect::Protection;
+use crate::automaton::species::Species;
+use crate::automaton::transition::TransitionTable;
+use crate::automaton::writes::WriteQueue;
+
 /// The internal state of a cellular automaton.
 ///
 /// This is an opaque type passed between C and Rust via the FFI layer.
 /// All grid manipulation logic should go in the `automaton` module, not here.
+#[derive(Clone)]
+#[repr(C)]
 pub struct State {
+    /// Checked by the FFI layer (see `HandleTag`).
+    pub tag: HandleTag,
     pub width: i16,
     pub height: i16,
     pub depth: i16,
-    pub cells: Vec<u8>, // 0 = dead, 1 = alive
+    pub cells: Vec<u8>, // 0 = dead, 1 = alive (any state of a transition table)
     pub generation: u64,
+    pub rule: Rule,
+    /// Cells that may not be born into (see `automaton::protect`).
+    pub protection: Option<Protection>,
+    /// Committed changes awaiting a world write (see `automaton::writes`).
+    pub write_queue: Option<WriteQueue>,
+    /// Species id per cell (see `automaton::species`).
+    pub species: Option<Species>,
+    /// Generations survived per cell (see `automaton::age`).
+    pub age: Option<CellAge>,
+    /// Multi-state rule replacing `rule` (see `automaton::transition`).
+    pub transitions: Option<TransitionTable>,
+    /// How steps compute the next generation.
+    pub mode: StepMode,
+    /// Checkpoints for rolling back (see `automaton::history`).
+    pub history: Option<History>,
+    /// Hashes of recent generations (see `automaton::cycle`).
+    pub cycles: Option<CycleTracker>,
+}
+
+impl Default for State {
+    /// An empty state with no grid and the default B4/S4 rule (what `va_create` returns).
+    fn default() -> Self {
+        State {
+            tag: HandleTag::new(HandleKind::State),
+            width: 0,
+            height: 0,
+            depth: 0,
+            cells: Vec::new(),
+            generation: 0,
+            rule: Rule::default(),
+            protection: None,
+            write_queue: None,
+            species: None,
+            age: None,
+            transitions: None,
+            mode: StepMode::Rule,
+            history: None,
+            cycles: None,
+        }
+    }
+}
+
+/// Tag at the start of every tagged FFI handle (see `Tagged`), so the FFI
+/// layer can reject a destroyed handle, or a handle of another type (a Field
+/// passed as a State), instead of reading it as the wrong type.
+///
+/// A destroyed handle's memory may be reused by a new handle of the same type,
+/// which then passes the check: the tag catches most stale pointers, not all.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct HandleTag {
+    magic: u32,
+    kind: u32,
+}
+
+impl HandleTag {
+    /// Magic of a live handle ("VAHT").
+    pub const MAGIC: u32 = 0x5641_4854;
+
+    /// The tag of a destroyed handle.
+    pub const DEAD: HandleTag = HandleTag { magic: 0, kind: 0 };
+
+    /// The tag of a live handle of `kind`.
+    pub const fn new(kind: HandleKind) -> Self {
+        HandleTag {
+            magic: Self::MAGIC,
+            kind: kind as u32,
+        }
+    }
+
+    /// Kind of a live handle, or None for a destroyed handle or foreign memory.
+    pub fn kind(self) -> Option<HandleKind> {
+        if self.magic != Self::MAGIC {
+            return None;
+        }
+        [
+            HandleKind::State,
+            HandleKind::Field,
+            HandleKind::StepController,
+            HandleKind::SharedState,
+        ]
+        .into_iter()
+        .find(|&kind| kind as u32 == self.kind)
+    }
+}
+
+/// Type of a tagged FFI handle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum HandleKind {
+    State = 1,
+    Field = 2,
+    StepController = 3,
+    SharedState = 4,
+}
+
+/// An FFI handle type starting with a `HandleTag`.
+///
+/// # Safety
+/// The type must be `#[repr(C)]` with a `HandleTag` as its first field, set to
+/// `HandleTag::new(KIND)` by every constructor.
+pub unsafe trait Tagged {
+    const KIND: HandleKind;
+}
+
+unsafe impl Tagged for State {
+    const KIND: HandleKind = HandleKind::State;
+}
+
+/// Outer-totalistic birth/survival rule over the 26-cell Moore neighborhood.
+///
+/// Bit `n` of each mask is set if a neighbor count of `n` triggers birth (dead cell)
+/// or survival (live cell). Only bits 0..=26 are meaningful.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub birth: u32,
+    pub survival: u32,
+}
+
+impl Rule {
+    /// Mask of the meaningful bits (neighbor counts 0..=26).
+    pub const MASK: u32 = (1 << 27) - 1;
+
+    /// The original hardcoded rule: birth on 4, survival on 4.
+    pub const B4S4: Rule = Rule {
+        birth: 1 << 4,
+        survival: 1 << 4,
+    };
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::B4S4
+    }
+}
+
+/// How `step_automaton` computes the next generation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StepMode {
+    /// The birth/survival rule, or the transition table if one is set.
+    #[default]
+    Rule,
+    /// WireWorld circuits (see `automaton::wireworld`).
+    WireWorld,
 }
@@ -0,0 +1,200 @@
+//! Optional WebAssembly bindings (feature `wasm`) for browser demos and rule explorers.
+//!
+//! A thin wasm-bindgen wrapper over the safe `api` layer, so the browser runs the
+//! genuine simulation code. Only the single-threaded pieces are exposed: the
+//! incremental StepController depends on a rayon thread pool, which wasm32 lacks.
+//!
+//! Build with:
+//! ```text
+//! wasm-pack build --target web -- --no-default-features --features wasm   # or: cargo build-wasm
+//! ```
+//!
+//! Cell buffers cross as typed arrays (`Uint8Array` / `Uint32Array`) in z,y,x
+//! order. 64-bit counters are returned as `number` (exact up to 2^53).
+
+use wasm_bindgen::prelude::*;
+
+use crate::automaton::field::Field;
+use crate::state::{Rule, State};
+
+fn check_dims(width: i16, height: i16, depth: i16) -> Result<(), JsError> {
+    if width <= 0 || height <= 0 || depth <= 0 {
+        return Err(JsError::new("dimensions must be positive"));
+    }
+    Ok(())
+}
+
+/// Binary cellular automaton grid (B4/S4 by default).
+#[wasm_bindgen(js_name = State)]
+pub struct WasmState {
+    inner: State,
+}
+
+#[wasm_bindgen(js_class = State)]
+impl WasmState {
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: i16, height: i16, depth: i16) -> Result<WasmState, JsError> {
+        check_dims(width, height, depth)?;
+        Ok(WasmState {
+            inner: State::new(width, height, depth),
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> i16 {
+        self.inner.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> i16 {
+        self.inner.height
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn depth(&self) -> i16 {
+        self.inner.depth
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> f64 {
+        self.inner.generation as f64
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn population(&self) -> u32 {
+        self.inner.population() as u32
+    }
+
+    /// Cell value (0 or 1). Out-of-bounds reads return 0.
+    pub fn get(&self, x: i16, y: i16, z: i16) -> u8 {
+        self.inner.get(x, y, z).unwrap_or(0)
+    }
+
+    /// Set a cell. Returns false if out of bounds.
+    pub fn set(&mut self, x: i16, y: i16, z: i16, alive: bool) -> bool {
+        self.inner.set(x, y, z, alive)
+    }
+
+    /// Replace the rule. Bit `n` of each mask enables neighbor count `n`.
+    #[wasm_bindgen(js_name = setRule)]
+    pub fn set_rule(&mut self, birth_mask: u32, survival_mask: u32) {
+        self.inner.set_rule(Rule {
+            birth: birth_mask,
+            survival: survival_mask,
+        });
+    }
+
+    /// Advance `n` generations.
+    pub fn step(&mut self, n: u32) {
+        self.inner.step_n(n as u64);
+    }
+
+    /// Copy of all cells in z,y,x order.
+    pub fn cells(&self) -> Vec<u8> {
+        self.inner.cells.clone()
+    }
+
+    /// Overwrite all cells from a z,y,x ordered buffer (non-zero = alive).
+    #[wasm_bindgen(js_name = setCells)]
+    pub fn set_cells(&mut self, cells: &[u8]) -> Result<(), JsError> {
+        if cells.len() != self.inner.cells.len() {
+            return Err(JsError::new("cell buffer length does not match grid size"));
+        }
+        for (dst, &src) in self.inner.cells.iter_mut().zip(cells) {
+            *dst = (src != 0) as u8;
+        }
+        Ok(())
+    }
+
+    /// Compressed snapshot (same bytes as `va_serialize_compressed`).
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes_compressed()
+    }
+
+    /// Restore from a compressed snapshot.
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(data: &[u8]) -> Result<WasmState, JsError> {
+        State::from_bytes_compressed(data)
+            .map(|inner| WasmState { inner })
+            .map_err(|e| JsError::new(&format!("invalid snapshot: {:?}", e)))
+    }
+}
+
+/// Integer diffusion field.
+#[wasm_bindgen(js_name = Field)]
+pub struct WasmField {
+    inner: Field,
+}
+
+#[wasm_bindgen(js_class = Field)]
+impl WasmField {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        width: i16,
+        height: i16,
+        depth: i16,
+        diffusion_rate: u8,
+    ) -> Result<WasmField, JsError> {
+        check_dims(width, height, depth)?;
+        Ok(WasmField {
+            inner: Field::new(width, height, depth, diffusion_rate),
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> i16 {
+        self.inner.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> i16 {
+        self.inner.height
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn depth(&self) -> i16 {
+        self.inner.depth
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> f64 {
+        self.inner.generation as f64
+    }
+
+    /// Total conserved quantity across all cells.
+    #[wasm_bindgen(getter)]
+    pub fn total(&self) -> f64 {
+        self.inner.total() as f64
+    }
+
+    /// Cell value. Out-of-bounds reads return 0.
+    pub fn get(&self, x: i16, y: i16, z: i16) -> u32 {
+        self.inner.get(x, y, z).map(|v| v.get()).unwrap_or(0)
+    }
+
+    /// Set a cell. Returns false if out of bounds.
+    pub fn set(&mut self, x: i16, y: i16, z: i16, value: u32) -> bool {
+        self.inner.set(x, y, z, value).is_ok()
+    }
+
+    /// Advance `n` generations with the sequential algorithm (same as `va_field_step`).
+    pub fn step(&mut self, n: u32) {
+        for _ in 0..n {
+            self.inner.step();
+        }
+    }
+
+    /// Advance `n` generations with the fused, rotationally symmetric algorithm.
+    #[wasm_bindgen(js_name = stepFused)]
+    pub fn step_fused(&mut self, n: u32) {
+        for _ in 0..n {
+            self.inner.step_fused();
+        }
+    }
+
+    /// Copy of all cells in z,y,x order.
+    pub fn cells(&self) -> Vec<u32> {
+        self.inner.cells.clone()
+    }
+}